@@ -2,6 +2,8 @@
 // Provides an optional HTTP API that calls the same domain services as Tauri commands
 // This allows remote clients to connect to a host instance
 
+pub mod openapi;
+pub mod rate_limit;
 pub mod routes;
 pub mod server;
 
@@ -14,20 +16,42 @@ use axum::{
 };
 use serde_json::json;
 
+use crate::db::users::User;
 use crate::services::auth::AuthService;
+use crate::services::authorization::{paired_node_has_permission, role_has_permission, Permission};
+use crate::services::pairing::{PairingService, CREDENTIAL_PREFIX};
+
+/// Marker stored in request extensions when the caller used the static API token
+/// Static tokens are shared between host nodes and are trusted with every permission
+#[derive(Debug, Clone, Copy)]
+pub struct StaticTokenAuth;
+
+/// Marker stored in request extensions when the caller used a paired node credential
+/// Paired nodes act on behalf of their own signed-in users, whom the host doesn't know, so
+/// they get every permission but deleting records and managing security.
+#[derive(Debug, Clone, Copy)]
+pub struct PairedNodeAuth {
+    pub node_id: i64,
+}
 
 /// API authentication middleware
 /// Supports both:
 /// 1. Static Bearer token (for host-to-host communication)
-/// 2. JWT tokens (for user authentication)
+/// 2. Paired node credentials (issued to client nodes by the pairing flow)
+/// 3. JWT tokens (for user authentication)
 pub async fn auth_middleware(
     State(expected_token): State<String>,
     mut req: Request,
     next: Next,
 ) -> Result<Response, Response> {
-    // Skip auth for auth endpoints
+    // Skip auth for auth endpoints and for nodes asking to pair
     let path = req.uri().path();
-    if path == "/api/auth/login" || path == "/api/health" {
+    if path == "/api/auth/login"
+        || path == "/api/auth/refresh"
+        || path == "/api/health"
+        || path == "/api/openapi.json"
+        || path.starts_with("/api/pairing/requests")
+    {
         return Ok(next.run(req).await);
     }
 
@@ -42,9 +66,18 @@ pub async fn auth_middleware(
             
             // First, check if it's the static API token
             if provided_token == expected_token {
+                req.extensions_mut().insert(StaticTokenAuth);
                 return Ok(next.run(req).await);
             }
             
+            // Then a credential issued to a paired node
+            if provided_token.starts_with(CREDENTIAL_PREFIX) {
+                if let Ok(Some(node)) = PairingService::new().authenticate_node(provided_token) {
+                    req.extensions_mut().insert(PairedNodeAuth { node_id: node.id });
+                    return Ok(next.run(req).await);
+                }
+            }
+
             // If not, try to verify it as a JWT token
             let auth_service = AuthService::new();
             if let Ok(user) = auth_service.verify_token(provided_token) {
@@ -65,6 +98,64 @@ pub async fn auth_middleware(
         .into_response())
 }
 
+/// Per-route authorization middleware
+/// Runs after `auth_middleware` and checks the role of the authenticated user
+pub async fn require_permission(
+    State(permission): State<Permission>,
+    req: Request,
+    next: Next,
+) -> Result<Response, Response> {
+    if req.extensions().get::<StaticTokenAuth>().is_some() {
+        return Ok(next.run(req).await);
+    }
+
+    if let Some(node) = req.extensions().get::<PairedNodeAuth>() {
+        if paired_node_has_permission(permission) {
+            return Ok(next.run(req).await);
+        }
+
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({
+                "error": "Forbidden",
+                "message": format!(
+                    "Paired node {} lacks permission '{}'",
+                    node.node_id,
+                    permission.as_str()
+                ),
+                "required_permission": permission.as_str()
+            })),
+        )
+            .into_response());
+    }
+
+    let Some(user) = req.extensions().get::<User>() else {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(json!({
+                "error": "Unauthorized",
+                "message": "Invalid or missing authentication token"
+            })),
+        )
+            .into_response());
+    };
+
+    if role_has_permission(&user.role, permission) {
+        return Ok(next.run(req).await);
+    }
+
+    Err((
+        StatusCode::FORBIDDEN,
+        Json(json!({
+            "error": "Forbidden",
+            "message": format!("Role '{}' lacks permission '{}'", user.role, permission.as_str()),
+            "required_permission": permission.as_str(),
+            "role": user.role
+        })),
+    )
+        .into_response())
+}
+
 /// Convert service errors to HTTP responses
 pub fn service_error_to_response(error: crate::services::ServiceError) -> Response {
     use crate::services::ServiceError;
@@ -102,5 +193,68 @@ pub fn service_error_to_response(error: crate::services::ServiceError) -> Respon
             })),
         )
             .into_response(),
+        ServiceError::Forbidden(msg) => (
+            StatusCode::FORBIDDEN,
+            Json(json!({
+                "error": "Forbidden",
+                "message": msg
+            })),
+        )
+            .into_response(),
+        ServiceError::TooManyRequests(msg) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(json!({
+                "error": "Too Many Requests",
+                "message": msg
+            })),
+        )
+            .into_response(),
+        ServiceError::Conflict(msg, details) => (
+            StatusCode::CONFLICT,
+            Json(json!({
+                "error": "Conflict",
+                "message": msg,
+                "conflicts": details
+            })),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::{middleware, Router};
+    use tower::ServiceExt;
+
+    /// The API routes as a paired node sees them
+    fn paired_node_router() -> Router {
+        Router::new()
+            .nest("/api", routes::api_routes())
+            .layer(middleware::from_fn(
+                |mut req: Request, next: Next| async move {
+                    req.extensions_mut().insert(PairedNodeAuth { node_id: 1 });
+                    next.run(req).await
+                },
+            ))
+    }
+
+    #[tokio::test]
+    async fn paired_nodes_cannot_manage_security() {
+        for (method, uri) in [
+            ("POST", "/api/auth/rotate-secret"),
+            ("POST", "/api/auth/users/1/revoke-all"),
+            ("DELETE", "/api/pairing/nodes/2"),
+            ("DELETE", "/api/patients/3"),
+        ] {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap();
+            let response = paired_node_router().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN, "{} {}", method, uri);
+        }
     }
 }
@@ -0,0 +1,349 @@
+// OpenAPI Specification
+// Builds the OpenAPI 3 document for the HTTP API from the `#[utoipa::path]` annotations on
+// the handlers in `routes.rs` and serves it at GET /api/openapi.json.
+
+use axum::{response::IntoResponse, Json};
+use serde::Serialize;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{IntoResponses, Modify, OpenApi, ToSchema};
+
+use super::routes;
+
+/// Error body returned by `service_error_to_response` and the auth endpoints
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// Error category, e.g. "Not Found", "Validation Error", "Forbidden"
+    pub error: String,
+    /// Human readable detail
+    pub message: String,
+}
+
+/// Error body of appointments that overlap others (409)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConflictBody {
+    /// Always "Conflict"
+    pub error: String,
+    pub message: String,
+    /// The existing appointments that clash, per start of the new appointment
+    pub conflicts: Vec<crate::db::appointments::AppointmentConflict>,
+}
+
+/// Body of 201 responses and of upserts that return the affected row
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IdResponse {
+    pub id: i64,
+}
+
+/// Confirmation body of updates and deletes
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MessageResponse {
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CountResponse {
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TotalResponse {
+    pub total: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HealthResponse {
+    pub status: String,
+    pub service: String,
+    pub version: String,
+}
+
+/// Body of GET /api/pairing/node
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PairedNodeIdentity {
+    pub node_id: i64,
+}
+
+/// Errors of authenticated data routes
+/// Response sets only describe the spec; the variants are never built.
+#[allow(dead_code)]
+#[derive(IntoResponses)]
+pub enum ApiErrors {
+    /// Invalid input
+    #[response(status = 400)]
+    Validation(ErrorBody),
+    /// Missing or invalid token
+    #[response(status = 401)]
+    Unauthorized(ErrorBody),
+    /// The caller's role lacks the required permission
+    #[response(status = 403)]
+    Forbidden(ErrorBody),
+    /// The resource does not exist
+    #[response(status = 404)]
+    NotFound(ErrorBody),
+    /// Database error
+    #[response(status = 500)]
+    Database(ErrorBody),
+}
+
+/// Errors of the auth endpoints
+#[allow(dead_code)]
+#[derive(IntoResponses)]
+pub enum AuthErrors {
+    /// Invalid request
+    #[response(status = 400)]
+    BadRequest(ErrorBody),
+    /// Invalid credentials or token
+    #[response(status = 401)]
+    Unauthorized(ErrorBody),
+    /// The caller's role lacks the required permission
+    #[response(status = 403)]
+    Forbidden(ErrorBody),
+}
+
+/// Adds the bearer scheme used by every authenticated route
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some(
+                        "Static API token, paired node credential or user JWT",
+                    ))
+                    .build(),
+            ),
+        );
+    }
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Nuevo Galeno API", description = "HTTP API of a Nuevo Galeno host node"),
+    modifiers(&BearerAuth),
+    security(("bearer_auth" = [])),
+    components(schemas(ErrorBody)),
+    paths(
+        openapi_json,
+        routes::health_check,
+        routes::login,
+        routes::verify_token,
+        routes::refresh_token,
+        routes::logout,
+        routes::revoke_all_sessions,
+        routes::revoke_user_sessions,
+        routes::rotate_jwt_secret,
+        routes::get_auth_audit_log,
+        routes::get_patients,
+        routes::create_patient,
+        routes::search_patients,
+        routes::get_patients_count,
+        routes::get_patient_by_id,
+        routes::update_patient,
+        routes::delete_patient,
+        routes::get_treatments,
+        routes::create_treatment,
+        routes::get_treatment_stats,
+        routes::get_treatments_by_status,
+        routes::get_treatment_by_id,
+        routes::update_treatment,
+        routes::delete_treatment,
+        routes::update_treatment_status,
+        routes::get_treatments_by_patient,
+        routes::get_payments,
+        routes::create_payment,
+        routes::get_recent_payments,
+        routes::get_payment_by_id,
+        routes::update_payment,
+        routes::delete_payment,
+        routes::get_payments_by_treatment,
+        routes::get_payments_by_patient,
+        routes::get_patient_balance,
+        routes::get_patients_with_debt,
+        routes::get_patients_with_debt_count,
+        routes::get_patients_with_debt_summary,
+        routes::get_total_debt,
+        routes::list_appointments,
+        routes::create_appointment,
+        routes::get_upcoming_appointments,
+        routes::get_pending_reminders,
+        routes::get_reminder_notification,
+        routes::mark_reminder_sent,
+        routes::get_appointment,
+        routes::update_appointment,
+        routes::delete_appointment,
+        routes::update_appointment_occurrence,
+        routes::cancel_appointment_occurrence,
+        routes::list_agenda_resources,
+        routes::get_agenda_resource,
+        routes::create_agenda_resource,
+        routes::update_agenda_resource,
+        routes::delete_agenda_resource,
+        routes::find_free_slots,
+        routes::get_odontogram_by_patient,
+        routes::clear_patient_odontogram,
+        routes::get_odontogram_surfaces_by_patient,
+        routes::get_tooth_treatments_by_patient,
+        routes::get_bridges_by_patient,
+        routes::get_tooth_condition,
+        routes::delete_tooth_condition,
+        routes::get_tooth_history,
+        routes::get_tooth_surfaces,
+        routes::clear_tooth_surfaces,
+        routes::get_tooth_surface_history,
+        routes::get_surface_treatments,
+        routes::delete_tooth_surface,
+        routes::get_surface_history,
+        routes::get_tooth_treatments,
+        routes::get_tooth_treatment_history,
+        routes::update_tooth_condition,
+        routes::add_tooth_surface_treatment,
+        routes::update_tooth_surface,
+        routes::deactivate_surface_treatment,
+        routes::add_tooth_treatment,
+        routes::deactivate_tooth_treatment,
+        routes::add_bridge,
+        routes::deactivate_bridge,
+        routes::get_treatment_catalog,
+        routes::create_treatment_catalog,
+        routes::create_treatment_catalog_item,
+        routes::get_treatment_catalog_item_by_id,
+        routes::update_treatment_catalog_item,
+        routes::delete_treatment_catalog_item,
+        routes::get_treatment_catalog_by_id,
+        routes::update_treatment_catalog,
+        routes::delete_treatment_catalog,
+        routes::get_treatment_catalog_items,
+        routes::request_pairing,
+        routes::get_pairing_status,
+        routes::get_current_paired_node,
+        routes::list_paired_nodes,
+        routes::revoke_paired_node,
+        routes::stream_events,
+    ),
+    tags(
+        (name = "system", description = "Health and API description"),
+        (name = "auth", description = "Login, token refresh, revocation and the auth audit log"),
+        (name = "patients"),
+        (name = "treatments"),
+        (name = "payments", description = "Payments, balances and debts"),
+        (name = "appointments"),
+        (name = "agenda-resources", description = "Practitioners and chairs, their working hours and free slots"),
+        (name = "odontogram", description = "Tooth conditions, surfaces, tooth treatments and bridges"),
+        (name = "treatment-catalog"),
+        (name = "pairing", description = "Pairing client nodes with this host"),
+        (name = "events", description = "Entity change stream"),
+    )
+)]
+pub struct ApiDoc;
+
+/// GET /api/openapi.json - OpenAPI 3 description of this API (no auth)
+#[utoipa::path(
+    get,
+    path = "/api/openapi.json",
+    tag = "system",
+    responses((status = 200, description = "OpenAPI 3 document", content_type = "application/json")),
+    security(())
+)]
+pub async fn openapi_json() -> impl IntoResponse {
+    Json(ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::extract::Request;
+    use axum::http::{header, Method, StatusCode};
+    use axum::Router;
+    use std::collections::BTreeSet;
+    use tower::ServiceExt;
+    use utoipa::openapi::PathItem;
+
+    fn documented_methods(item: &PathItem) -> BTreeSet<String> {
+        [
+            (Method::GET, &item.get),
+            (Method::POST, &item.post),
+            (Method::PUT, &item.put),
+            (Method::DELETE, &item.delete),
+            (Method::PATCH, &item.patch),
+        ]
+        .into_iter()
+        .filter(|(_, operation)| operation.is_some())
+        .map(|(method, _)| method.to_string())
+        .collect()
+    }
+
+    /// Methods the real API router has for `uri`, read from the Allow header of the 405
+    /// it answers to TRACE, so no handler runs; `None` when it has no route for the path
+    async fn routed_methods(uri: &str) -> Option<BTreeSet<String>> {
+        let request = Request::builder()
+            .method(Method::TRACE)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        let response = Router::new()
+            .nest("/api", routes::api_routes())
+            .oneshot(request)
+            .await
+            .unwrap();
+        if response.status() != StatusCode::METHOD_NOT_ALLOWED {
+            return None;
+        }
+
+        let allow = response.headers().get(header::ALLOW)?.to_str().ok()?;
+        Some(
+            allow
+                .split(',')
+                .map(|method| method.trim().to_string())
+                .filter(|method| method != "HEAD")
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn documented_routes_match_the_router() {
+        let spec = ApiDoc::openapi();
+        assert!(spec.paths.paths.len() > 40, "spec has only {} paths", spec.paths.paths.len());
+
+        let mut mismatches = Vec::new();
+        for (path, item) in &spec.paths.paths {
+            let uri = path
+                .split('/')
+                .map(|segment| if segment.starts_with('{') { "1" } else { segment })
+                .collect::<Vec<_>>()
+                .join("/");
+
+            let documented = documented_methods(item);
+            match routed_methods(&uri).await {
+                Some(routed) if routed == documented => {}
+                Some(routed) => mismatches.push(format!(
+                    "{}: documented {:?}, routed {:?}",
+                    path, documented, routed
+                )),
+                None => mismatches.push(format!("{}: documented but not routed", path)),
+            }
+        }
+
+        assert!(mismatches.is_empty(), "spec and router disagree: {:#?}", mismatches);
+    }
+
+    #[test]
+    fn spec_serializes_with_schemas() {
+        let json = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        assert!(json["openapi"].as_str().unwrap().starts_with("3."));
+
+        let schemas = &json["components"]["schemas"];
+        for name in ["CreatePatientInput", "Patient", "ErrorBody", "Appointment"] {
+            assert!(schemas.get(name).is_some(), "missing schema {}", name);
+        }
+        assert!(json["paths"]["/api/patients"]["get"]["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|param| param["name"] == "limit"));
+    }
+}
@@ -0,0 +1,207 @@
+// API Rate Limiting
+// Global fixed-window request limit for the HTTP API. It caps the load any number of
+// clients can put on the host; per-user login throttling lives in services::login_throttle.
+// Unauthenticated routes that hold host state (pairing requests) also get a per-IP limit.
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Long-lived Server-Sent Events stream, see `exempt`
+const EVENTS_PATH: &str = "/api/events";
+
+struct Window {
+    started_at: Instant,
+    requests: u32,
+}
+
+impl Window {
+    fn new(now: Instant) -> Self {
+        Self {
+            started_at: now,
+            requests: 0,
+        }
+    }
+
+    /// Count one request; returns the seconds left in the window when over `limit`
+    fn try_acquire(&mut self, limit: u32, now: Instant) -> Result<(), u64> {
+        if now.duration_since(self.started_at) >= WINDOW {
+            self.started_at = now;
+            self.requests = 0;
+        }
+
+        if self.requests >= limit {
+            let remaining = WINDOW.saturating_sub(now.duration_since(self.started_at));
+            return Err(remaining.as_millis().div_ceil(1000).max(1) as u64);
+        }
+
+        self.requests += 1;
+        Ok(())
+    }
+}
+
+/// Requests-per-minute limiter shared by every API route
+pub struct RateLimiter {
+    limit_per_minute: u32,
+    window: Mutex<Window>,
+}
+
+impl RateLimiter {
+    pub fn new(limit_per_minute: u32) -> Self {
+        Self {
+            limit_per_minute,
+            window: Mutex::new(Window::new(Instant::now())),
+        }
+    }
+
+    /// Count one request; returns the seconds left in the window when over the limit
+    fn try_acquire(&self, now: Instant) -> Result<(), u64> {
+        self.window
+            .lock()
+            .unwrap()
+            .try_acquire(self.limit_per_minute, now)
+    }
+}
+
+/// Requests-per-minute limiter with a separate window for each client IP
+pub struct IpRateLimiter {
+    limit_per_minute: u32,
+    windows: Mutex<HashMap<IpAddr, Window>>,
+}
+
+impl IpRateLimiter {
+    pub fn new(limit_per_minute: u32) -> Self {
+        Self {
+            limit_per_minute,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn try_acquire(&self, ip: IpAddr, now: Instant) -> Result<(), u64> {
+        let mut windows = self.windows.lock().unwrap();
+        windows.retain(|_, window| now.duration_since(window.started_at) < WINDOW);
+
+        windows
+            .entry(ip)
+            .or_insert_with(|| Window::new(now))
+            .try_acquire(self.limit_per_minute, now)
+    }
+}
+
+/// Reject requests over the configured limit with 429 and a Retry-After header
+/// The event stream is exempt: it is one long request that clients reopen on reconnect.
+pub async fn rate_limit_middleware(
+    State(limiter): State<Arc<RateLimiter>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if exempt(req.uri().path()) {
+        return next.run(req).await;
+    }
+
+    match limiter.try_acquire(Instant::now()) {
+        Ok(()) => next.run(req).await,
+        Err(retry_after) => too_many_requests(retry_after),
+    }
+}
+
+/// Reject requests over the limit of the client's IP with 429 and a Retry-After header
+pub async fn ip_rate_limit_middleware(
+    State(limiter): State<Arc<IpRateLimiter>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let ip = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+    match limiter.try_acquire(ip, Instant::now()) {
+        Ok(()) => next.run(req).await,
+        Err(retry_after) => too_many_requests(retry_after),
+    }
+}
+
+fn exempt(path: &str) -> bool {
+    path.trim_end_matches('/') == EVENTS_PATH
+}
+
+fn too_many_requests(retry_after: u64) -> Response {
+    let mut response = (
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(json!({
+            "error": "Too Many Requests",
+            "message": "API request limit reached, try again later"
+        })),
+    )
+        .into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_over_the_limit_are_rejected_until_the_window_ends() {
+        let limiter = RateLimiter::new(3);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.try_acquire(start).is_ok());
+        }
+
+        let retry_after = limiter.try_acquire(start + Duration::from_secs(20)).unwrap_err();
+        assert_eq!(retry_after, 40);
+
+        assert!(limiter.try_acquire(start + WINDOW).is_ok());
+    }
+
+    #[test]
+    fn each_ip_has_its_own_window() {
+        let limiter = IpRateLimiter::new(2);
+        let start = Instant::now();
+        let first = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10));
+        let second = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 11));
+
+        assert!(limiter.try_acquire(first, start).is_ok());
+        assert!(limiter.try_acquire(first, start).is_ok());
+        assert!(limiter.try_acquire(first, start).is_err());
+        assert!(limiter.try_acquire(second, start).is_ok());
+
+        assert!(limiter.try_acquire(first, start + WINDOW).is_ok());
+    }
+
+    #[test]
+    fn retry_after_rounds_up_to_whole_seconds() {
+        let mut window = Window::new(Instant::now());
+        let start = window.started_at;
+
+        assert!(window.try_acquire(1, start).is_ok());
+        assert_eq!(window.try_acquire(1, start + Duration::from_millis(20_500)), Err(40));
+        assert_eq!(window.try_acquire(1, start + Duration::from_millis(59_900)), Err(1));
+    }
+
+    #[test]
+    fn only_the_event_stream_is_exempt() {
+        assert!(exempt("/api/events"));
+        assert!(exempt("/api/events/"));
+        assert!(!exempt("/api/patients"));
+        assert!(!exempt("/api/events/extra"));
+    }
+}
@@ -2,19 +2,75 @@
 // Defines API endpoints that mirror Tauri commands
 
 use axum::{
-    extract::{Path, Query},
+    extract::{ConnectInfo, Path, Query},
     http::StatusCode,
-    response::IntoResponse,
+    middleware,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::MethodRouter,
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use std::net::SocketAddr;
+use std::sync::Arc;
 use tokio::task;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
 
-use crate::db::patients::{CreatePatientInput, UpdatePatientInput};
-use crate::services::auth::{AuthService, LoginRequest};
+use crate::db::agenda_resources::{AgendaResource, FreeSlot, FreeSlotQuery};
+use crate::db::appointments::{
+    Appointment, AppointmentFilter, AppointmentReminder, AppointmentWithPatient, RecurrenceScope,
+    ReminderNotification,
+};
+use crate::db::auth_audit::AuthAuditEntry;
+use crate::db::odontogram_surfaces::{
+    AddSurfaceTreatmentInput, OdontogramSurface, SurfaceHistoryEntry, UpdateSurfaceInput,
+};
+use crate::db::odontogram_tooth_treatments::{
+    AddBridgeInput, AddToothTreatmentInput, OdontogramBridge, OdontogramToothTreatment,
+    ToothTreatmentHistoryEntry,
+};
+use crate::db::odontograms::{OdontogramEntry, UpdateToothConditionInput};
+use crate::db::paired_nodes::PairedNode;
+use crate::db::patients::{CreatePatientInput, Patient, UpdatePatientInput};
+use crate::db::payments::{
+    CreatePaymentInput, PatientBalance, PatientDebtSummary, Payment, UpdatePaymentInput,
+};
+use crate::db::treatment_catalog::{
+    CreateTreatmentCatalogInput, CreateTreatmentCatalogItemInput, TreatmentCatalogEntry,
+    TreatmentCatalogItem, UpdateTreatmentCatalogInput, UpdateTreatmentCatalogItemInput,
+};
+use crate::db::treatments::{CreateTreatmentInput, Treatment, TreatmentStats, UpdateTreatmentInput};
+use crate::events::{self, ChangeEvent};
+use crate::services::agenda_resources::AgendaResourceService;
+use crate::services::appointments::AppointmentService;
+use crate::services::auth::{AuthService, LoginRequest, LoginResponse};
+use crate::services::authorization::Permission;
+use crate::services::login_throttle::{LoginOrigin, LoginThrottle};
+use crate::services::odontograms::OdontogramService;
+use crate::services::pairing::{PairingService, PairingStatus, PairingTicket};
 use crate::services::patients::PatientService;
+use crate::services::payments::PaymentService;
+use crate::services::treatment_catalog::TreatmentCatalogService;
+use crate::services::treatments::TreatmentService;
+use crate::services::{ServiceError, ServiceResult};
+
+use super::openapi::{
+    openapi_json, ApiErrors, AuthErrors, ConflictBody, CountResponse, ErrorBody, HealthResponse,
+    IdResponse, MessageResponse, PairedNodeIdentity, TotalResponse,
+};
+use super::rate_limit::IpRateLimiter;
 
 /// Health check endpoint
+#[utoipa::path(
+    get,
+    path = "/api/health",
+    tag = "system",
+    responses((status = 200, body = HealthResponse)),
+    security(())
+)]
 pub async fn health_check() -> impl IntoResponse {
     (
         StatusCode::OK,
@@ -27,34 +83,166 @@ pub async fn health_check() -> impl IntoResponse {
 }
 
 /// Query parameters for pagination
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct PaginationQuery {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
 }
 
 /// Query parameters for search
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SearchQuery {
     pub q: String,
 }
 
+/// Query parameters for debt listings
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DebtQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub q: Option<String>,
+}
+
+/// Query parameters for "recent" listings
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LimitQuery {
+    pub limit: Option<i64>,
+}
+
+/// Query parameters for upcoming appointments
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UpcomingQuery {
+    pub hours: Option<i32>,
+}
+
+/// Query parameters of appointment changes
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OverbookingQuery {
+    /// Save the appointment even if it overlaps others in the same chair or
+    /// with the same practitioner
+    #[serde(default)]
+    pub allow_overbooking: bool,
+}
+
+/// Query parameters for listing agenda resources
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ResourceListQuery {
+    pub include_inactive: Option<bool>,
+}
+
+/// Request body for status changes
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct StatusUpdateRequest {
+    pub status: String,
+}
+
+/// Request body for marking a reminder as sent
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReminderSentRequest {
+    pub notification_id: String,
+}
+
+/// Request body for changing occurrences of a recurring appointment
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct OccurrenceUpdateRequest {
+    /// Start of the occurrence according to the series rule
+    pub original_start_time: String,
+    pub scope: RecurrenceScope,
+    pub appointment: Appointment,
+}
+
+/// Request body for cancelling occurrences of a recurring appointment
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct OccurrenceCancelRequest {
+    /// Start of the occurrence according to the series rule
+    pub original_start_time: String,
+    pub scope: RecurrenceScope,
+}
+
+// ===== RESPONSE HELPERS =====
+
+/// 200 with the value as JSON body, or the mapped service error
+fn json_or_error<T: Serialize>(result: ServiceResult<T>) -> Response {
+    match result {
+        Ok(value) => (StatusCode::OK, Json(value)).into_response(),
+        Err(e) => super::service_error_to_response(e),
+    }
+}
+
+/// 200 with the value, 404 when the service found nothing
+fn found_or_error<T: Serialize>(result: ServiceResult<Option<T>>, not_found: String) -> Response {
+    match result {
+        Ok(Some(value)) => (StatusCode::OK, Json(value)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": "Not Found",
+                "message": not_found
+            })),
+        )
+            .into_response(),
+        Err(e) => super::service_error_to_response(e),
+    }
+}
+
+/// 201 with the new id, or the mapped service error
+fn created_or_error(result: ServiceResult<i64>) -> Response {
+    match result {
+        Ok(id) => (StatusCode::CREATED, Json(serde_json::json!({ "id": id }))).into_response(),
+        Err(e) => super::service_error_to_response(e),
+    }
+}
+
+/// 200 with a confirmation message
+fn message_response(message: &str) -> Response {
+    (StatusCode::OK, Json(serde_json::json!({ "message": message }))).into_response()
+}
+
+/// Confirmation message, or the mapped service error
+fn message_or_error(result: ServiceResult<()>, message: &str) -> Response {
+    match result {
+        Ok(_) => message_response(message),
+        Err(e) => super::service_error_to_response(e),
+    }
+}
+
 // ===== AUTH ROUTES =====
 
 /// POST /api/auth/login - Authenticate and get JWT token
-pub async fn login(Json(req): Json<LoginRequest>) -> impl IntoResponse {
+/// Repeated failures lock the username or client IP out for a while (429).
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, body = LoginResponse),
+        AuthErrors,
+        (status = 429, description = "Too many failed attempts", body = ErrorBody)
+    ),
+    security(())
+)]
+pub async fn login(
+    client: Option<ConnectInfo<SocketAddr>>,
+    Json(req): Json<LoginRequest>,
+) -> impl IntoResponse {
+    let origin = LoginOrigin::api(client.map(|ConnectInfo(addr)| addr.ip()));
+
     task::spawn_blocking(move || {
         let service = AuthService::new();
-        match service.login(req.username, req.password) {
+        match service.login(req.username, req.password, &origin) {
             Ok(response) => (StatusCode::OK, Json(response)).into_response(),
-            Err(e) => (
-                StatusCode::UNAUTHORIZED,
-                Json(serde_json::json!({
-                    "error": "Authentication failed",
-                    "message": e
-                })),
-            )
-                .into_response(),
+            Err(ServiceError::Unauthorized(message)) => {
+                auth_error(StatusCode::UNAUTHORIZED, message)
+            }
+            Err(e) => super::service_error_to_response(e),
         }
     })
     .await
@@ -62,7 +250,7 @@ pub async fn login(Json(req): Json<LoginRequest>) -> impl IntoResponse {
 }
 
 /// Response for token verification
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct VerifyResponse {
     pub valid: bool,
     pub user: Option<crate::db::users::User>,
@@ -81,6 +269,12 @@ fn unauthorized_response() -> axum::http::Response<axum::body::Body> {
 }
 
 /// GET /api/auth/verify - Verify JWT token (requires Authorization header)
+#[utoipa::path(
+    get,
+    path = "/api/auth/verify",
+    tag = "auth",
+    responses((status = 200, body = VerifyResponse), AuthErrors)
+)]
 pub async fn verify_token(
     headers: axum::http::HeaderMap,
 ) -> impl IntoResponse {
@@ -121,9 +315,174 @@ pub async fn verify_token(
     .unwrap()
 }
 
+/// Request body for token refresh
+#[derive(Deserialize, ToSchema)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+/// Optional request body for logout
+#[derive(Deserialize, Default, ToSchema)]
+pub struct LogoutRequest {
+    pub refresh_token: Option<String>,
+}
+
+/// Extract the bearer token from the Authorization header
+fn bearer_token(headers: &axum::http::HeaderMap) -> Option<String> {
+    headers
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .map(|token| token.to_string())
+}
+
+fn auth_error(status: StatusCode, message: String) -> Response {
+    (
+        status,
+        Json(serde_json::json!({
+            "error": "Authentication failed",
+            "message": message
+        })),
+    )
+        .into_response()
+}
+
+/// POST /api/auth/refresh - Exchange a refresh token for a new token pair
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    tag = "auth",
+    request_body = RefreshRequest,
+    responses((status = 200, body = LoginResponse), AuthErrors),
+    security(())
+)]
+pub async fn refresh_token(Json(req): Json<RefreshRequest>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        let service = AuthService::new();
+        match service.refresh(&req.refresh_token) {
+            Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+            Err(e) => auth_error(StatusCode::UNAUTHORIZED, e),
+        }
+    })
+    .await
+    .unwrap()
+}
+
+/// POST /api/auth/logout - Revoke the current access token (and refresh token if given)
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    tag = "auth",
+    request_body = Option<LogoutRequest>,
+    responses((status = 200, body = MessageResponse), AuthErrors)
+)]
+pub async fn logout(
+    headers: axum::http::HeaderMap,
+    body: Option<Json<LogoutRequest>>,
+) -> impl IntoResponse {
+    let Some(token) = bearer_token(&headers) else {
+        return auth_error(StatusCode::UNAUTHORIZED, "Token requerido".to_string());
+    };
+    let Json(req) = body.unwrap_or_default();
+
+    task::spawn_blocking(move || {
+        let service = AuthService::new();
+        match service.logout(&token, req.refresh_token.as_deref()) {
+            Ok(()) => message_response("Logged out successfully"),
+            Err(e) => auth_error(StatusCode::BAD_REQUEST, e),
+        }
+    })
+    .await
+    .unwrap()
+}
+
+/// POST /api/auth/revoke-all - Revoke every session of the authenticated user
+#[utoipa::path(
+    post,
+    path = "/api/auth/revoke-all",
+    tag = "auth",
+    responses((status = 200, body = MessageResponse), AuthErrors)
+)]
+pub async fn revoke_all_sessions(
+    user: Option<axum::Extension<crate::db::users::User>>,
+) -> impl IntoResponse {
+    let Some(axum::Extension(user)) = user else {
+        return auth_error(
+            StatusCode::BAD_REQUEST,
+            "Solo disponible para sesiones de usuario".to_string(),
+        );
+    };
+
+    revoke_sessions_of(user.id).await
+}
+
+/// POST /api/auth/users/:id/revoke-all - Revoke every session of another user (admin)
+#[utoipa::path(
+    post,
+    path = "/api/auth/users/{id}/revoke-all",
+    tag = "auth",
+    params(("id" = i64, Path, description = "User ID")),
+    responses((status = 200, body = MessageResponse), AuthErrors)
+)]
+pub async fn revoke_user_sessions(Path(id): Path<i64>) -> impl IntoResponse {
+    revoke_sessions_of(id).await
+}
+
+async fn revoke_sessions_of(user_id: i64) -> Response {
+    task::spawn_blocking(move || {
+        let service = AuthService::new();
+        match service.revoke_all_sessions(user_id) {
+            Ok(()) => message_response("Sessions revoked successfully"),
+            Err(e) => auth_error(StatusCode::BAD_REQUEST, e),
+        }
+    })
+    .await
+    .unwrap()
+}
+
+/// POST /api/auth/rotate-secret - Rotate the JWT signing secret (admin)
+#[utoipa::path(
+    post,
+    path = "/api/auth/rotate-secret",
+    tag = "auth",
+    responses((status = 200, body = MessageResponse), AuthErrors)
+)]
+pub async fn rotate_jwt_secret() -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        let service = AuthService::new();
+        match service.rotate_secret() {
+            Ok(()) => message_response("JWT secret rotated successfully"),
+            Err(e) => auth_error(StatusCode::INTERNAL_SERVER_ERROR, e),
+        }
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/auth/audit - Failed logins and lockouts, newest first (admin)
+#[utoipa::path(
+    get,
+    path = "/api/auth/audit",
+    tag = "auth",
+    params(LimitQuery),
+    responses((status = 200, body = Vec<AuthAuditEntry>), ApiErrors)
+)]
+pub async fn get_auth_audit_log(Query(params): Query<LimitQuery>) -> impl IntoResponse {
+    task::spawn_blocking(move || json_or_error(LoginThrottle::new().audit_log(params.limit)))
+        .await
+        .unwrap()
+}
+
 // ===== PATIENT ROUTES =====
 
 /// GET /api/patients - Get all patients with optional pagination
+#[utoipa::path(
+    get,
+    path = "/api/patients",
+    tag = "patients",
+    params(PaginationQuery),
+    responses((status = 200, body = Vec<Patient>), ApiErrors)
+)]
 pub async fn get_patients(Query(params): Query<PaginationQuery>) -> impl IntoResponse {
     task::spawn_blocking(move || {
         let service = PatientService::new();
@@ -137,6 +496,13 @@ pub async fn get_patients(Query(params): Query<PaginationQuery>) -> impl IntoRes
 }
 
 /// GET /api/patients/:id - Get patient by ID
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}",
+    tag = "patients",
+    params(("id" = i64, Path, description = "Patient ID")),
+    responses((status = 200, body = Patient), ApiErrors)
+)]
 pub async fn get_patient_by_id(Path(id): Path<i64>) -> impl IntoResponse {
     task::spawn_blocking(move || {
         let service = PatientService::new();
@@ -158,6 +524,13 @@ pub async fn get_patient_by_id(Path(id): Path<i64>) -> impl IntoResponse {
 }
 
 /// POST /api/patients - Create a new patient
+#[utoipa::path(
+    post,
+    path = "/api/patients",
+    tag = "patients",
+    request_body = CreatePatientInput,
+    responses((status = 201, body = IdResponse), ApiErrors)
+)]
 pub async fn create_patient(Json(input): Json<CreatePatientInput>) -> impl IntoResponse {
     task::spawn_blocking(move || {
         let service = PatientService::new();
@@ -175,6 +548,14 @@ pub async fn create_patient(Json(input): Json<CreatePatientInput>) -> impl IntoR
 }
 
 /// PUT /api/patients/:id - Update patient
+#[utoipa::path(
+    put,
+    path = "/api/patients/{id}",
+    tag = "patients",
+    params(("id" = i64, Path, description = "Patient ID")),
+    request_body = UpdatePatientInput,
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
 pub async fn update_patient(
     Path(id): Path<i64>,
     Json(input): Json<UpdatePatientInput>,
@@ -195,6 +576,13 @@ pub async fn update_patient(
 }
 
 /// DELETE /api/patients/:id - Delete patient
+#[utoipa::path(
+    delete,
+    path = "/api/patients/{id}",
+    tag = "patients",
+    params(("id" = i64, Path, description = "Patient ID")),
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
 pub async fn delete_patient(Path(id): Path<i64>) -> impl IntoResponse {
     task::spawn_blocking(move || {
         let service = PatientService::new();
@@ -212,6 +600,13 @@ pub async fn delete_patient(Path(id): Path<i64>) -> impl IntoResponse {
 }
 
 /// GET /api/patients/search?q=<query> - Search patients
+#[utoipa::path(
+    get,
+    path = "/api/patients/search",
+    tag = "patients",
+    params(SearchQuery),
+    responses((status = 200, body = Vec<Patient>), ApiErrors)
+)]
 pub async fn search_patients(Query(params): Query<SearchQuery>) -> impl IntoResponse {
     task::spawn_blocking(move || {
         let service = PatientService::new();
@@ -225,6 +620,12 @@ pub async fn search_patients(Query(params): Query<SearchQuery>) -> impl IntoResp
 }
 
 /// GET /api/patients/count - Get total patient count
+#[utoipa::path(
+    get,
+    path = "/api/patients/count",
+    tag = "patients",
+    responses((status = 200, body = CountResponse), ApiErrors)
+)]
 pub async fn get_patients_count() -> impl IntoResponse {
     task::spawn_blocking(move || {
         let service = PatientService::new();
@@ -241,19 +642,1856 @@ pub async fn get_patients_count() -> impl IntoResponse {
     .unwrap()
 }
 
-/// Create patient routes
-pub fn patient_routes() -> Router {
-    Router::new()
-        .route("/health", axum::routing::get(health_check))
-        // Auth routes (no authentication required for login)
-        .route("/auth/login", axum::routing::post(login))
-        .route("/auth/verify", axum::routing::get(verify_token))
-        // Patient routes (authentication required via middleware)
-        .route("/patients", axum::routing::get(get_patients))
-        .route("/patients", axum::routing::post(create_patient))
-        .route("/patients/search", axum::routing::get(search_patients))
-        .route("/patients/count", axum::routing::get(get_patients_count))
-        .route("/patients/:id", axum::routing::get(get_patient_by_id))
-        .route("/patients/:id", axum::routing::put(update_patient))
-        .route("/patients/:id", axum::routing::delete(delete_patient))
-}
\ No newline at end of file
+// ===== TREATMENT ROUTES =====
+
+/// GET /api/treatments - Get all treatments with optional pagination
+#[utoipa::path(
+    get,
+    path = "/api/treatments",
+    tag = "treatments",
+    params(PaginationQuery),
+    responses((status = 200, body = Vec<Treatment>), ApiErrors)
+)]
+pub async fn get_treatments(Query(params): Query<PaginationQuery>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(TreatmentService::new().get_all(params.limit, params.offset))
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/treatments/:id - Get treatment by ID
+#[utoipa::path(
+    get,
+    path = "/api/treatments/{id}",
+    tag = "treatments",
+    params(("id" = i64, Path, description = "Treatment ID")),
+    responses((status = 200, body = Treatment), ApiErrors)
+)]
+pub async fn get_treatment_by_id(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        found_or_error(
+            TreatmentService::new().get_by_id(id),
+            format!("Treatment {} not found", id),
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/treatments/status/:status - Get treatments by status
+#[utoipa::path(
+    get,
+    path = "/api/treatments/status/{status}",
+    tag = "treatments",
+    params(("status" = String, Path, description = "Treatment status")),
+    responses((status = 200, body = Vec<Treatment>), ApiErrors)
+)]
+pub async fn get_treatments_by_status(Path(status): Path<String>) -> impl IntoResponse {
+    task::spawn_blocking(move || json_or_error(TreatmentService::new().get_by_status(&status)))
+        .await
+        .unwrap()
+}
+
+/// GET /api/treatments/stats - Get treatment statistics
+#[utoipa::path(
+    get,
+    path = "/api/treatments/stats",
+    tag = "treatments",
+    responses((status = 200, body = TreatmentStats), ApiErrors)
+)]
+pub async fn get_treatment_stats() -> impl IntoResponse {
+    task::spawn_blocking(move || json_or_error(TreatmentService::new().get_stats()))
+        .await
+        .unwrap()
+}
+
+/// GET /api/patients/:id/treatments - Get treatments of a patient
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}/treatments",
+    tag = "treatments",
+    params(("id" = i64, Path, description = "Patient ID")),
+    responses((status = 200, body = Vec<Treatment>), ApiErrors)
+)]
+pub async fn get_treatments_by_patient(Path(patient_id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || json_or_error(TreatmentService::new().get_by_patient(patient_id)))
+        .await
+        .unwrap()
+}
+
+/// POST /api/treatments - Create a new treatment
+#[utoipa::path(
+    post,
+    path = "/api/treatments",
+    tag = "treatments",
+    request_body = CreateTreatmentInput,
+    responses((status = 201, body = IdResponse), ApiErrors)
+)]
+pub async fn create_treatment(Json(input): Json<CreateTreatmentInput>) -> impl IntoResponse {
+    task::spawn_blocking(move || created_or_error(TreatmentService::new().create(input)))
+        .await
+        .unwrap()
+}
+
+/// PUT /api/treatments/:id - Update treatment
+#[utoipa::path(
+    put,
+    path = "/api/treatments/{id}",
+    tag = "treatments",
+    params(("id" = i64, Path, description = "Treatment ID")),
+    request_body = UpdateTreatmentInput,
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn update_treatment(
+    Path(id): Path<i64>,
+    Json(input): Json<UpdateTreatmentInput>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            TreatmentService::new().update(id, input),
+            "Treatment updated successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// PUT /api/treatments/:id/status - Update treatment status
+#[utoipa::path(
+    put,
+    path = "/api/treatments/{id}/status",
+    tag = "treatments",
+    params(("id" = i64, Path, description = "Treatment ID")),
+    request_body = StatusUpdateRequest,
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn update_treatment_status(
+    Path(id): Path<i64>,
+    Json(req): Json<StatusUpdateRequest>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            TreatmentService::new().update_status(id, &req.status),
+            "Treatment status updated successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// DELETE /api/treatments/:id - Delete treatment
+#[utoipa::path(
+    delete,
+    path = "/api/treatments/{id}",
+    tag = "treatments",
+    params(("id" = i64, Path, description = "Treatment ID")),
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn delete_treatment(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            TreatmentService::new().delete(id),
+            "Treatment deleted successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+// ===== PAYMENT ROUTES =====
+
+/// GET /api/payments - Get all payments with optional pagination
+#[utoipa::path(
+    get,
+    path = "/api/payments",
+    tag = "payments",
+    params(PaginationQuery),
+    responses((status = 200, body = Vec<Payment>), ApiErrors)
+)]
+pub async fn get_payments(Query(params): Query<PaginationQuery>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(PaymentService::new().get_all(params.limit, params.offset))
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/payments/recent?limit=<n> - Get the most recent payments
+#[utoipa::path(
+    get,
+    path = "/api/payments/recent",
+    tag = "payments",
+    params(LimitQuery),
+    responses((status = 200, body = Vec<Payment>), ApiErrors)
+)]
+pub async fn get_recent_payments(Query(params): Query<LimitQuery>) -> impl IntoResponse {
+    task::spawn_blocking(move || json_or_error(PaymentService::new().get_recent(params.limit)))
+        .await
+        .unwrap()
+}
+
+/// GET /api/payments/:id - Get payment by ID
+#[utoipa::path(
+    get,
+    path = "/api/payments/{id}",
+    tag = "payments",
+    params(("id" = i64, Path, description = "Payment ID")),
+    responses((status = 200, body = Payment), ApiErrors)
+)]
+pub async fn get_payment_by_id(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        found_or_error(
+            PaymentService::new().get_by_id(id),
+            format!("Payment {} not found", id),
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/treatments/:id/payments - Get payments of a treatment
+#[utoipa::path(
+    get,
+    path = "/api/treatments/{id}/payments",
+    tag = "payments",
+    params(("id" = i64, Path, description = "Treatment ID")),
+    responses((status = 200, body = Vec<Payment>), ApiErrors)
+)]
+pub async fn get_payments_by_treatment(Path(treatment_id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(PaymentService::new().get_by_treatment(treatment_id))
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/patients/:id/payments - Get payments of a patient
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}/payments",
+    tag = "payments",
+    params(("id" = i64, Path, description = "Patient ID")),
+    responses((status = 200, body = Vec<Payment>), ApiErrors)
+)]
+pub async fn get_payments_by_patient(Path(patient_id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || json_or_error(PaymentService::new().get_by_patient(patient_id)))
+        .await
+        .unwrap()
+}
+
+/// GET /api/patients/:id/balance - Get the balance of a patient
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}/balance",
+    tag = "payments",
+    params(("id" = i64, Path, description = "Patient ID")),
+    responses((status = 200, body = PatientBalance), ApiErrors)
+)]
+pub async fn get_patient_balance(Path(patient_id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(PaymentService::new().get_patient_balance(patient_id))
+    })
+    .await
+    .unwrap()
+}
+
+/// POST /api/payments - Register a new payment
+#[utoipa::path(
+    post,
+    path = "/api/payments",
+    tag = "payments",
+    request_body = CreatePaymentInput,
+    responses((status = 201, body = IdResponse), ApiErrors)
+)]
+pub async fn create_payment(Json(input): Json<CreatePaymentInput>) -> impl IntoResponse {
+    task::spawn_blocking(move || created_or_error(PaymentService::new().create(input)))
+        .await
+        .unwrap()
+}
+
+/// PUT /api/payments/:id - Update payment
+#[utoipa::path(
+    put,
+    path = "/api/payments/{id}",
+    tag = "payments",
+    params(("id" = i64, Path, description = "Payment ID")),
+    request_body = UpdatePaymentInput,
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn update_payment(
+    Path(id): Path<i64>,
+    Json(input): Json<UpdatePaymentInput>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            PaymentService::new().update(id, input),
+            "Payment updated successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// DELETE /api/payments/:id - Delete payment
+#[utoipa::path(
+    delete,
+    path = "/api/payments/{id}",
+    tag = "payments",
+    params(("id" = i64, Path, description = "Payment ID")),
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn delete_payment(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            PaymentService::new().delete(id),
+            "Payment deleted successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/debts?limit=&offset=&q= - Get patients with outstanding balance
+#[utoipa::path(
+    get,
+    path = "/api/debts",
+    tag = "payments",
+    params(DebtQuery),
+    responses((status = 200, body = Vec<PatientBalance>), ApiErrors)
+)]
+pub async fn get_patients_with_debt(Query(params): Query<DebtQuery>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(PaymentService::new().get_patients_with_debt(
+            params.limit,
+            params.offset,
+            params.q,
+        ))
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/debts/count - Get the number of patients with outstanding balance
+#[utoipa::path(
+    get,
+    path = "/api/debts/count",
+    tag = "payments",
+    responses((status = 200, body = CountResponse), ApiErrors)
+)]
+pub async fn get_patients_with_debt_count() -> impl IntoResponse {
+    task::spawn_blocking(move || match PaymentService::new().get_patients_with_debt_count() {
+        Ok(count) => (StatusCode::OK, Json(serde_json::json!({ "count": count }))).into_response(),
+        Err(e) => super::service_error_to_response(e),
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/debts/summary?q= - Get a summary of outstanding debt
+#[utoipa::path(
+    get,
+    path = "/api/debts/summary",
+    tag = "payments",
+    params(DebtQuery),
+    responses((status = 200, body = PatientDebtSummary), ApiErrors)
+)]
+pub async fn get_patients_with_debt_summary(Query(params): Query<DebtQuery>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(PaymentService::new().get_patients_with_debt_summary(params.q))
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/debts/total - Get the total outstanding debt
+#[utoipa::path(
+    get,
+    path = "/api/debts/total",
+    tag = "payments",
+    responses((status = 200, body = TotalResponse), ApiErrors)
+)]
+pub async fn get_total_debt() -> impl IntoResponse {
+    task::spawn_blocking(move || match PaymentService::new().get_total_debt() {
+        Ok(total) => (StatusCode::OK, Json(serde_json::json!({ "total": total }))).into_response(),
+        Err(e) => super::service_error_to_response(e),
+    })
+    .await
+    .unwrap()
+}
+
+// ===== APPOINTMENT ROUTES =====
+
+/// GET /api/appointments?start_date=&end_date=&patient_id=&status= - List appointments
+#[utoipa::path(
+    get,
+    path = "/api/appointments",
+    tag = "appointments",
+    params(AppointmentFilter),
+    responses((status = 200, body = Vec<AppointmentWithPatient>), ApiErrors)
+)]
+pub async fn list_appointments(Query(filter): Query<AppointmentFilter>) -> impl IntoResponse {
+    task::spawn_blocking(move || json_or_error(AppointmentService::new().list(&filter)))
+        .await
+        .unwrap()
+}
+
+/// GET /api/appointments/upcoming?hours=<n> - Get upcoming scheduled appointments
+#[utoipa::path(
+    get,
+    path = "/api/appointments/upcoming",
+    tag = "appointments",
+    params(UpcomingQuery),
+    responses((status = 200, body = Vec<AppointmentWithPatient>), ApiErrors)
+)]
+pub async fn get_upcoming_appointments(Query(params): Query<UpcomingQuery>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(AppointmentService::new().get_upcoming(params.hours.unwrap_or(24)))
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/appointments/:id - Get appointment by ID
+#[utoipa::path(
+    get,
+    path = "/api/appointments/{id}",
+    tag = "appointments",
+    params(("id" = i64, Path, description = "Appointment ID")),
+    responses((status = 200, body = Appointment), ApiErrors)
+)]
+pub async fn get_appointment(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || json_or_error(AppointmentService::new().get_by_id(id)))
+        .await
+        .unwrap()
+}
+
+/// POST /api/appointments - Create a new appointment
+#[utoipa::path(
+    post,
+    path = "/api/appointments",
+    tag = "appointments",
+    params(OverbookingQuery),
+    request_body = Appointment,
+    responses((status = 201, body = IdResponse), (status = 409, description = "Overlaps other appointments; retry with allow_overbooking=true to book anyway", body = ConflictBody), ApiErrors)
+)]
+pub async fn create_appointment(
+    Query(params): Query<OverbookingQuery>,
+    Json(appointment): Json<Appointment>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        created_or_error(AppointmentService::new().create(&appointment, params.allow_overbooking))
+    })
+    .await
+    .unwrap()
+}
+
+/// PUT /api/appointments/:id - Update appointment
+#[utoipa::path(
+    put,
+    path = "/api/appointments/{id}",
+    tag = "appointments",
+    params(("id" = i64, Path, description = "Appointment ID"), OverbookingQuery),
+    request_body = Appointment,
+    responses((status = 200, body = MessageResponse), (status = 409, description = "Overlaps other appointments; retry with allow_overbooking=true to book anyway", body = ConflictBody), ApiErrors)
+)]
+pub async fn update_appointment(
+    Path(id): Path<i64>,
+    Query(params): Query<OverbookingQuery>,
+    Json(mut appointment): Json<Appointment>,
+) -> impl IntoResponse {
+    appointment.id = Some(id);
+    task::spawn_blocking(move || {
+        message_or_error(
+            AppointmentService::new().update(&appointment, params.allow_overbooking),
+            "Appointment updated successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// DELETE /api/appointments/:id - Delete appointment
+#[utoipa::path(
+    delete,
+    path = "/api/appointments/{id}",
+    tag = "appointments",
+    params(("id" = i64, Path, description = "Appointment ID")),
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn delete_appointment(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            AppointmentService::new().delete(id),
+            "Appointment deleted successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// PUT /api/appointments/:id/occurrences - Update an occurrence of a recurring
+/// appointment, the occurrences from it on, or the whole series
+#[utoipa::path(
+    put,
+    path = "/api/appointments/{id}/occurrences",
+    tag = "appointments",
+    params(
+        ("id" = i64, Path, description = "Series (recurring appointment) ID"),
+        OverbookingQuery
+    ),
+    request_body = OccurrenceUpdateRequest,
+    responses((status = 200, body = IdResponse), (status = 409, description = "Overlaps other appointments; retry with allow_overbooking=true to book anyway", body = ConflictBody), ApiErrors)
+)]
+pub async fn update_appointment_occurrence(
+    Path(id): Path<i64>,
+    Query(params): Query<OverbookingQuery>,
+    Json(req): Json<OccurrenceUpdateRequest>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        match AppointmentService::new().update_occurrence(
+            id,
+            &req.original_start_time,
+            &req.appointment,
+            req.scope,
+            params.allow_overbooking,
+        ) {
+            Ok(id) => (StatusCode::OK, Json(serde_json::json!({ "id": id }))).into_response(),
+            Err(e) => super::service_error_to_response(e),
+        }
+    })
+    .await
+    .unwrap()
+}
+
+/// POST /api/appointments/:id/occurrences/cancel - Cancel an occurrence of a
+/// recurring appointment, the occurrences from it on, or the whole series
+#[utoipa::path(
+    post,
+    path = "/api/appointments/{id}/occurrences/cancel",
+    tag = "appointments",
+    params(("id" = i64, Path, description = "Series (recurring appointment) ID")),
+    request_body = OccurrenceCancelRequest,
+    responses((status = 200, body = IdResponse), ApiErrors)
+)]
+pub async fn cancel_appointment_occurrence(
+    Path(id): Path<i64>,
+    Json(req): Json<OccurrenceCancelRequest>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        match AppointmentService::new().cancel_occurrence(id, &req.original_start_time, req.scope) {
+            Ok(id) => (StatusCode::OK, Json(serde_json::json!({ "id": id }))).into_response(),
+            Err(e) => super::service_error_to_response(e),
+        }
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/appointments/reminders/pending - Get due reminders not yet sent
+#[utoipa::path(
+    get,
+    path = "/api/appointments/reminders/pending",
+    tag = "appointments",
+    responses((status = 200, body = Vec<AppointmentReminder>), ApiErrors)
+)]
+pub async fn get_pending_reminders() -> impl IntoResponse {
+    task::spawn_blocking(move || json_or_error(AppointmentService::new().get_pending_reminders()))
+        .await
+        .unwrap()
+}
+
+/// GET /api/appointments/reminders/:id/notification - Get a reminder with its appointment
+#[utoipa::path(
+    get,
+    path = "/api/appointments/reminders/{id}/notification",
+    tag = "appointments",
+    params(("id" = i64, Path, description = "Reminder ID")),
+    responses((status = 200, body = ReminderNotification), ApiErrors)
+)]
+pub async fn get_reminder_notification(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        found_or_error(
+            AppointmentService::new().get_reminder_notification(id),
+            format!("Reminder {} not found or no longer current", id),
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// POST /api/appointments/reminders/:id/sent - Mark a reminder as sent
+#[utoipa::path(
+    post,
+    path = "/api/appointments/reminders/{id}/sent",
+    tag = "appointments",
+    params(("id" = i64, Path, description = "Reminder ID")),
+    request_body = ReminderSentRequest,
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn mark_reminder_sent(
+    Path(id): Path<i64>,
+    Json(req): Json<ReminderSentRequest>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            AppointmentService::new().mark_reminder_sent(id, &req.notification_id),
+            "Reminder marked as sent",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+// ===== AGENDA RESOURCE ROUTES =====
+
+/// GET /api/agenda-resources - List practitioners and chairs with their schedules
+#[utoipa::path(
+    get,
+    path = "/api/agenda-resources",
+    tag = "agenda-resources",
+    params(ResourceListQuery),
+    responses((status = 200, body = Vec<AgendaResource>), ApiErrors)
+)]
+pub async fn list_agenda_resources(Query(params): Query<ResourceListQuery>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(AgendaResourceService::new().list(params.include_inactive.unwrap_or(false)))
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/agenda-resources/:id - Get a resource by ID
+#[utoipa::path(
+    get,
+    path = "/api/agenda-resources/{id}",
+    tag = "agenda-resources",
+    params(("id" = i64, Path, description = "Resource ID")),
+    responses((status = 200, body = AgendaResource), ApiErrors)
+)]
+pub async fn get_agenda_resource(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        found_or_error(
+            AgendaResourceService::new().get_by_id(id),
+            format!("Resource {} not found", id),
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// POST /api/agenda-resources - Create a resource with its schedule
+#[utoipa::path(
+    post,
+    path = "/api/agenda-resources",
+    tag = "agenda-resources",
+    request_body = AgendaResource,
+    responses((status = 201, body = IdResponse), ApiErrors)
+)]
+pub async fn create_agenda_resource(Json(resource): Json<AgendaResource>) -> impl IntoResponse {
+    task::spawn_blocking(move || created_or_error(AgendaResourceService::new().create(&resource)))
+        .await
+        .unwrap()
+}
+
+/// PUT /api/agenda-resources/:id - Update a resource, replacing its schedule
+#[utoipa::path(
+    put,
+    path = "/api/agenda-resources/{id}",
+    tag = "agenda-resources",
+    params(("id" = i64, Path, description = "Resource ID")),
+    request_body = AgendaResource,
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn update_agenda_resource(
+    Path(id): Path<i64>,
+    Json(mut resource): Json<AgendaResource>,
+) -> impl IntoResponse {
+    resource.id = Some(id);
+    task::spawn_blocking(move || {
+        message_or_error(
+            AgendaResourceService::new().update(&resource),
+            "Resource updated successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// DELETE /api/agenda-resources/:id - Delete a resource
+#[utoipa::path(
+    delete,
+    path = "/api/agenda-resources/{id}",
+    tag = "agenda-resources",
+    params(("id" = i64, Path, description = "Resource ID")),
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn delete_agenda_resource(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            AgendaResourceService::new().delete(id),
+            "Resource deleted successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// POST /api/agenda-resources/free-slots - Next openings where every given resource
+/// is working and has no appointments
+#[utoipa::path(
+    post,
+    path = "/api/agenda-resources/free-slots",
+    tag = "agenda-resources",
+    request_body = FreeSlotQuery,
+    responses((status = 200, body = Vec<FreeSlot>), ApiErrors)
+)]
+pub async fn find_free_slots(Json(query): Json<FreeSlotQuery>) -> impl IntoResponse {
+    task::spawn_blocking(move || json_or_error(AgendaResourceService::new().find_free_slots(&query)))
+        .await
+        .unwrap()
+}
+
+// ===== ODONTOGRAM ROUTES =====
+
+/// GET /api/patients/:id/odontogram - Get tooth conditions of a patient
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}/odontogram",
+    tag = "odontogram",
+    params(("id" = i64, Path, description = "Patient ID")),
+    responses((status = 200, body = Vec<OdontogramEntry>), ApiErrors)
+)]
+pub async fn get_odontogram_by_patient(Path(patient_id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || json_or_error(OdontogramService::new().get_by_patient(patient_id)))
+        .await
+        .unwrap()
+}
+
+/// DELETE /api/patients/:id/odontogram - Clear all tooth conditions of a patient
+#[utoipa::path(
+    delete,
+    path = "/api/patients/{id}/odontogram",
+    tag = "odontogram",
+    params(("id" = i64, Path, description = "Patient ID")),
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn clear_patient_odontogram(Path(patient_id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            OdontogramService::new().clear_patient_odontogram(patient_id),
+            "Odontogram cleared successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/patients/:id/odontogram/teeth/:tooth - Get the condition of a tooth
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}/odontogram/teeth/{tooth}",
+    tag = "odontogram",
+    params(
+        ("id" = i64, Path, description = "Patient ID"),
+        ("tooth" = String, Path, description = "Tooth number (FDI)"),
+    ),
+    responses((status = 200, body = OdontogramEntry), ApiErrors)
+)]
+pub async fn get_tooth_condition(Path((patient_id, tooth)): Path<(i64, String)>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        found_or_error(
+            OdontogramService::new().get_tooth(patient_id, &tooth),
+            format!("Tooth {} has no recorded condition", tooth),
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// DELETE /api/patients/:id/odontogram/teeth/:tooth - Remove the condition of a tooth
+#[utoipa::path(
+    delete,
+    path = "/api/patients/{id}/odontogram/teeth/{tooth}",
+    tag = "odontogram",
+    params(
+        ("id" = i64, Path, description = "Patient ID"),
+        ("tooth" = String, Path, description = "Tooth number (FDI)"),
+    ),
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn delete_tooth_condition(
+    Path((patient_id, tooth)): Path<(i64, String)>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            OdontogramService::new().delete_tooth_condition(patient_id, &tooth),
+            "Tooth condition deleted successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/patients/:id/odontogram/teeth/:tooth/history - Get the condition history of a tooth
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}/odontogram/teeth/{tooth}/history",
+    tag = "odontogram",
+    params(
+        ("id" = i64, Path, description = "Patient ID"),
+        ("tooth" = String, Path, description = "Tooth number (FDI)"),
+    ),
+    responses((status = 200, body = Vec<OdontogramEntry>), ApiErrors)
+)]
+pub async fn get_tooth_history(Path((patient_id, tooth)): Path<(i64, String)>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(OdontogramService::new().get_tooth_history(patient_id, &tooth))
+    })
+    .await
+    .unwrap()
+}
+
+/// PUT /api/odontogram/conditions - Create or update the condition of a tooth
+#[utoipa::path(
+    put,
+    path = "/api/odontogram/conditions",
+    tag = "odontogram",
+    request_body = UpdateToothConditionInput,
+    responses((status = 200, body = IdResponse), ApiErrors)
+)]
+pub async fn update_tooth_condition(
+    Json(input): Json<UpdateToothConditionInput>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || match OdontogramService::new().update_tooth_condition(input) {
+        Ok(id) => (StatusCode::OK, Json(serde_json::json!({ "id": id }))).into_response(),
+        Err(e) => super::service_error_to_response(e),
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/patients/:id/odontogram/surfaces - Get active surfaces of a patient
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}/odontogram/surfaces",
+    tag = "odontogram",
+    params(("id" = i64, Path, description = "Patient ID")),
+    responses((status = 200, body = Vec<OdontogramSurface>), ApiErrors)
+)]
+pub async fn get_odontogram_surfaces_by_patient(Path(patient_id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(OdontogramService::new().get_surfaces_by_patient(patient_id))
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/patients/:id/odontogram/teeth/:tooth/surfaces - Get active surfaces of a tooth
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}/odontogram/teeth/{tooth}/surfaces",
+    tag = "odontogram",
+    params(
+        ("id" = i64, Path, description = "Patient ID"),
+        ("tooth" = String, Path, description = "Tooth number (FDI)"),
+    ),
+    responses((status = 200, body = Vec<OdontogramSurface>), ApiErrors)
+)]
+pub async fn get_tooth_surfaces(Path((patient_id, tooth)): Path<(i64, String)>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(OdontogramService::new().get_tooth_surfaces(patient_id, &tooth))
+    })
+    .await
+    .unwrap()
+}
+
+/// DELETE /api/patients/:id/odontogram/teeth/:tooth/surfaces - Delete every surface of a tooth
+#[utoipa::path(
+    delete,
+    path = "/api/patients/{id}/odontogram/teeth/{tooth}/surfaces",
+    tag = "odontogram",
+    params(
+        ("id" = i64, Path, description = "Patient ID"),
+        ("tooth" = String, Path, description = "Tooth number (FDI)"),
+    ),
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn clear_tooth_surfaces(
+    Path((patient_id, tooth)): Path<(i64, String)>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            OdontogramService::new().clear_tooth_surfaces(patient_id, &tooth),
+            "Tooth surfaces cleared successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/patients/:id/odontogram/teeth/:tooth/surfaces/history - Get the surface history of a tooth
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}/odontogram/teeth/{tooth}/surfaces/history",
+    tag = "odontogram",
+    params(
+        ("id" = i64, Path, description = "Patient ID"),
+        ("tooth" = String, Path, description = "Tooth number (FDI)"),
+    ),
+    responses((status = 200, body = Vec<SurfaceHistoryEntry>), ApiErrors)
+)]
+pub async fn get_tooth_surface_history(
+    Path((patient_id, tooth)): Path<(i64, String)>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(OdontogramService::new().get_tooth_surface_history(patient_id, &tooth))
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/patients/:id/odontogram/teeth/:tooth/surfaces/:surface - Get treatments of a surface
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}/odontogram/teeth/{tooth}/surfaces/{surface}",
+    tag = "odontogram",
+    params(
+        ("id" = i64, Path, description = "Patient ID"),
+        ("tooth" = String, Path, description = "Tooth number (FDI)"),
+        ("surface" = String, Path, description = "Tooth surface"),
+    ),
+    responses((status = 200, body = Vec<OdontogramSurface>), ApiErrors)
+)]
+pub async fn get_surface_treatments(
+    Path((patient_id, tooth, surface)): Path<(i64, String, String)>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(OdontogramService::new().get_surface_treatments(patient_id, &tooth, &surface))
+    })
+    .await
+    .unwrap()
+}
+
+/// DELETE /api/patients/:id/odontogram/teeth/:tooth/surfaces/:surface - Delete a surface
+#[utoipa::path(
+    delete,
+    path = "/api/patients/{id}/odontogram/teeth/{tooth}/surfaces/{surface}",
+    tag = "odontogram",
+    params(
+        ("id" = i64, Path, description = "Patient ID"),
+        ("tooth" = String, Path, description = "Tooth number (FDI)"),
+        ("surface" = String, Path, description = "Tooth surface"),
+    ),
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn delete_tooth_surface(
+    Path((patient_id, tooth, surface)): Path<(i64, String, String)>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            OdontogramService::new().delete_surface(patient_id, &tooth, &surface),
+            "Surface deleted successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/patients/:id/odontogram/teeth/:tooth/surfaces/:surface/history - Get the history of a surface
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}/odontogram/teeth/{tooth}/surfaces/{surface}/history",
+    tag = "odontogram",
+    params(
+        ("id" = i64, Path, description = "Patient ID"),
+        ("tooth" = String, Path, description = "Tooth number (FDI)"),
+        ("surface" = String, Path, description = "Tooth surface"),
+    ),
+    responses((status = 200, body = Vec<SurfaceHistoryEntry>), ApiErrors)
+)]
+pub async fn get_surface_history(
+    Path((patient_id, tooth, surface)): Path<(i64, String, String)>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(OdontogramService::new().get_surface_history(patient_id, &tooth, &surface))
+    })
+    .await
+    .unwrap()
+}
+
+/// POST /api/odontogram/surfaces - Add a treatment to a surface
+#[utoipa::path(
+    post,
+    path = "/api/odontogram/surfaces",
+    tag = "odontogram",
+    request_body = AddSurfaceTreatmentInput,
+    responses((status = 201, body = IdResponse), ApiErrors)
+)]
+pub async fn add_tooth_surface_treatment(
+    Json(input): Json<AddSurfaceTreatmentInput>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        created_or_error(OdontogramService::new().add_surface_treatment(input))
+    })
+    .await
+    .unwrap()
+}
+
+/// PUT /api/odontogram/surfaces - Update a surface
+#[utoipa::path(
+    put,
+    path = "/api/odontogram/surfaces",
+    tag = "odontogram",
+    request_body = UpdateSurfaceInput,
+    responses((status = 200, body = IdResponse), ApiErrors)
+)]
+pub async fn update_tooth_surface(Json(input): Json<UpdateSurfaceInput>) -> impl IntoResponse {
+    task::spawn_blocking(move || match OdontogramService::new().update_surface(input) {
+        Ok(id) => (StatusCode::OK, Json(serde_json::json!({ "id": id }))).into_response(),
+        Err(e) => super::service_error_to_response(e),
+    })
+    .await
+    .unwrap()
+}
+
+/// DELETE /api/odontogram/surfaces/:id - Deactivate a surface treatment
+#[utoipa::path(
+    delete,
+    path = "/api/odontogram/surfaces/{id}",
+    tag = "odontogram",
+    params(("id" = i64, Path, description = "Surface treatment ID")),
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn deactivate_surface_treatment(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            OdontogramService::new().deactivate_surface_treatment(id),
+            "Surface treatment deactivated successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/patients/:id/odontogram/tooth-treatments - Get whole-tooth treatments of a patient
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}/odontogram/tooth-treatments",
+    tag = "odontogram",
+    params(("id" = i64, Path, description = "Patient ID")),
+    responses((status = 200, body = Vec<OdontogramToothTreatment>), ApiErrors)
+)]
+pub async fn get_tooth_treatments_by_patient(Path(patient_id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(OdontogramService::new().get_tooth_treatments_by_patient(patient_id))
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/patients/:id/odontogram/teeth/:tooth/treatments - Get whole-tooth treatments of a tooth
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}/odontogram/teeth/{tooth}/treatments",
+    tag = "odontogram",
+    params(
+        ("id" = i64, Path, description = "Patient ID"),
+        ("tooth" = String, Path, description = "Tooth number (FDI)"),
+    ),
+    responses((status = 200, body = Vec<OdontogramToothTreatment>), ApiErrors)
+)]
+pub async fn get_tooth_treatments(
+    Path((patient_id, tooth)): Path<(i64, String)>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(OdontogramService::new().get_tooth_treatments(patient_id, &tooth))
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/patients/:id/odontogram/teeth/:tooth/treatments/history - Get whole-tooth treatment history
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}/odontogram/teeth/{tooth}/treatments/history",
+    tag = "odontogram",
+    params(
+        ("id" = i64, Path, description = "Patient ID"),
+        ("tooth" = String, Path, description = "Tooth number (FDI)"),
+    ),
+    responses((status = 200, body = Vec<ToothTreatmentHistoryEntry>), ApiErrors)
+)]
+pub async fn get_tooth_treatment_history(
+    Path((patient_id, tooth)): Path<(i64, String)>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(OdontogramService::new().get_tooth_treatment_history(patient_id, &tooth))
+    })
+    .await
+    .unwrap()
+}
+
+/// POST /api/odontogram/tooth-treatments - Add a whole-tooth treatment
+#[utoipa::path(
+    post,
+    path = "/api/odontogram/tooth-treatments",
+    tag = "odontogram",
+    request_body = AddToothTreatmentInput,
+    responses((status = 201, body = IdResponse), ApiErrors)
+)]
+pub async fn add_tooth_treatment(Json(input): Json<AddToothTreatmentInput>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        created_or_error(OdontogramService::new().add_tooth_treatment(input))
+    })
+    .await
+    .unwrap()
+}
+
+/// DELETE /api/odontogram/tooth-treatments/:id - Deactivate a whole-tooth treatment
+#[utoipa::path(
+    delete,
+    path = "/api/odontogram/tooth-treatments/{id}",
+    tag = "odontogram",
+    params(("id" = i64, Path, description = "Tooth treatment ID")),
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn deactivate_tooth_treatment(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            OdontogramService::new().deactivate_tooth_treatment(id),
+            "Tooth treatment deactivated successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/patients/:id/odontogram/bridges - Get active bridges of a patient
+#[utoipa::path(
+    get,
+    path = "/api/patients/{id}/odontogram/bridges",
+    tag = "odontogram",
+    params(("id" = i64, Path, description = "Patient ID")),
+    responses((status = 200, body = Vec<OdontogramBridge>), ApiErrors)
+)]
+pub async fn get_bridges_by_patient(Path(patient_id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        json_or_error(OdontogramService::new().get_bridges_by_patient(patient_id))
+    })
+    .await
+    .unwrap()
+}
+
+/// POST /api/odontogram/bridges - Add a bridge
+#[utoipa::path(
+    post,
+    path = "/api/odontogram/bridges",
+    tag = "odontogram",
+    request_body = AddBridgeInput,
+    responses((status = 201, body = IdResponse), ApiErrors)
+)]
+pub async fn add_bridge(Json(input): Json<AddBridgeInput>) -> impl IntoResponse {
+    task::spawn_blocking(move || created_or_error(OdontogramService::new().add_bridge(input)))
+        .await
+        .unwrap()
+}
+
+/// DELETE /api/odontogram/bridges/:id - Deactivate a bridge
+#[utoipa::path(
+    delete,
+    path = "/api/odontogram/bridges/{id}",
+    tag = "odontogram",
+    params(("id" = i64, Path, description = "Bridge ID")),
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn deactivate_bridge(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            OdontogramService::new().deactivate_bridge(id),
+            "Bridge deactivated successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+// ===== TREATMENT CATALOG ROUTES =====
+
+/// GET /api/treatment-catalog - Get all active catalog entries
+#[utoipa::path(
+    get,
+    path = "/api/treatment-catalog",
+    tag = "treatment-catalog",
+    responses((status = 200, body = Vec<TreatmentCatalogEntry>), ApiErrors)
+)]
+pub async fn get_treatment_catalog() -> impl IntoResponse {
+    task::spawn_blocking(move || json_or_error(TreatmentCatalogService::new().get_all()))
+        .await
+        .unwrap()
+}
+
+/// GET /api/treatment-catalog/:id - Get catalog entry by ID
+#[utoipa::path(
+    get,
+    path = "/api/treatment-catalog/{id}",
+    tag = "treatment-catalog",
+    params(("id" = i64, Path, description = "Catalog entry ID")),
+    responses((status = 200, body = TreatmentCatalogEntry), ApiErrors)
+)]
+pub async fn get_treatment_catalog_by_id(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        found_or_error(
+            TreatmentCatalogService::new().get_by_id(id),
+            format!("Treatment catalog entry {} not found", id),
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// POST /api/treatment-catalog - Create a catalog entry
+#[utoipa::path(
+    post,
+    path = "/api/treatment-catalog",
+    tag = "treatment-catalog",
+    request_body = CreateTreatmentCatalogInput,
+    responses((status = 201, body = IdResponse), ApiErrors)
+)]
+pub async fn create_treatment_catalog(
+    Json(input): Json<CreateTreatmentCatalogInput>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || created_or_error(TreatmentCatalogService::new().create(input)))
+        .await
+        .unwrap()
+}
+
+/// PUT /api/treatment-catalog/:id - Update a catalog entry
+#[utoipa::path(
+    put,
+    path = "/api/treatment-catalog/{id}",
+    tag = "treatment-catalog",
+    params(("id" = i64, Path, description = "Catalog entry ID")),
+    request_body = UpdateTreatmentCatalogInput,
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn update_treatment_catalog(
+    Path(id): Path<i64>,
+    Json(mut input): Json<UpdateTreatmentCatalogInput>,
+) -> impl IntoResponse {
+    input.id = id;
+    task::spawn_blocking(move || {
+        message_or_error(
+            TreatmentCatalogService::new().update(input),
+            "Treatment catalog entry updated successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// DELETE /api/treatment-catalog/:id - Deactivate a catalog entry
+#[utoipa::path(
+    delete,
+    path = "/api/treatment-catalog/{id}",
+    tag = "treatment-catalog",
+    params(("id" = i64, Path, description = "Catalog entry ID")),
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn delete_treatment_catalog(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            TreatmentCatalogService::new().delete(id),
+            "Treatment catalog entry deleted successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/treatment-catalog/:id/items - Get the items of a catalog entry
+#[utoipa::path(
+    get,
+    path = "/api/treatment-catalog/{id}/items",
+    tag = "treatment-catalog",
+    params(("id" = i64, Path, description = "Catalog entry ID")),
+    responses((status = 200, body = Vec<TreatmentCatalogItem>), ApiErrors)
+)]
+pub async fn get_treatment_catalog_items(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || json_or_error(TreatmentCatalogService::new().get_items(id)))
+        .await
+        .unwrap()
+}
+
+/// GET /api/treatment-catalog/items/:id - Get catalog item by ID
+#[utoipa::path(
+    get,
+    path = "/api/treatment-catalog/items/{id}",
+    tag = "treatment-catalog",
+    params(("id" = i64, Path, description = "Catalog item ID")),
+    responses((status = 200, body = TreatmentCatalogItem), ApiErrors)
+)]
+pub async fn get_treatment_catalog_item_by_id(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        found_or_error(
+            TreatmentCatalogService::new().get_item_by_id(id),
+            format!("Treatment catalog item {} not found", id),
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// POST /api/treatment-catalog/items - Create a catalog item
+#[utoipa::path(
+    post,
+    path = "/api/treatment-catalog/items",
+    tag = "treatment-catalog",
+    request_body = CreateTreatmentCatalogItemInput,
+    responses((status = 201, body = IdResponse), ApiErrors)
+)]
+pub async fn create_treatment_catalog_item(
+    Json(input): Json<CreateTreatmentCatalogItemInput>,
+) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        created_or_error(TreatmentCatalogService::new().create_item(input))
+    })
+    .await
+    .unwrap()
+}
+
+/// PUT /api/treatment-catalog/items/:id - Update a catalog item
+#[utoipa::path(
+    put,
+    path = "/api/treatment-catalog/items/{id}",
+    tag = "treatment-catalog",
+    params(("id" = i64, Path, description = "Catalog item ID")),
+    request_body = UpdateTreatmentCatalogItemInput,
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn update_treatment_catalog_item(
+    Path(id): Path<i64>,
+    Json(mut input): Json<UpdateTreatmentCatalogItemInput>,
+) -> impl IntoResponse {
+    input.id = id;
+    task::spawn_blocking(move || {
+        message_or_error(
+            TreatmentCatalogService::new().update_item(input),
+            "Treatment catalog item updated successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// DELETE /api/treatment-catalog/items/:id - Deactivate a catalog item
+#[utoipa::path(
+    delete,
+    path = "/api/treatment-catalog/items/{id}",
+    tag = "treatment-catalog",
+    params(("id" = i64, Path, description = "Catalog item ID")),
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn delete_treatment_catalog_item(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        message_or_error(
+            TreatmentCatalogService::new().delete_item(id),
+            "Treatment catalog item deleted successfully",
+        )
+    })
+    .await
+    .unwrap()
+}
+
+/// Require a permission on a single route (checked after authentication)
+fn guarded(route: MethodRouter, permission: Permission) -> MethodRouter {
+    route.route_layer(middleware::from_fn_with_state(
+        permission,
+        super::require_permission,
+    ))
+}
+
+/// Create patient routes
+pub fn patient_routes() -> Router {
+    Router::new()
+        .route("/health", axum::routing::get(health_check))
+        .route("/openapi.json", axum::routing::get(openapi_json))
+        // Auth routes (no authentication required for login)
+        .route("/auth/login", axum::routing::post(login))
+        .route("/auth/verify", axum::routing::get(verify_token))
+        .route("/auth/refresh", axum::routing::post(refresh_token))
+        .route("/auth/logout", axum::routing::post(logout))
+        .route("/auth/revoke-all", axum::routing::post(revoke_all_sessions))
+        .route(
+            "/auth/users/:id/revoke-all",
+            guarded(
+                axum::routing::post(revoke_user_sessions),
+                Permission::ManageSecurity,
+            ),
+        )
+        .route(
+            "/auth/rotate-secret",
+            guarded(axum::routing::post(rotate_jwt_secret), Permission::ManageSecurity),
+        )
+        .route(
+            "/auth/audit",
+            guarded(axum::routing::get(get_auth_audit_log), Permission::ManageSecurity),
+        )
+        // Patient routes (authentication required via middleware)
+        .route("/patients", axum::routing::get(get_patients))
+        .route("/patients", guarded(axum::routing::post(create_patient), Permission::WritePatients))
+        .route("/patients/search", axum::routing::get(search_patients))
+        .route("/patients/count", axum::routing::get(get_patients_count))
+        .route("/patients/:id", axum::routing::get(get_patient_by_id))
+        .route("/patients/:id", guarded(axum::routing::put(update_patient), Permission::WritePatients))
+        .route("/patients/:id", guarded(axum::routing::delete(delete_patient), Permission::DeleteRecords))
+}
+
+/// Create treatment routes
+pub fn treatment_routes() -> Router {
+    Router::new()
+        .route("/treatments", axum::routing::get(get_treatments))
+        .route("/treatments", guarded(axum::routing::post(create_treatment), Permission::WriteClinical))
+        .route("/treatments/stats", axum::routing::get(get_treatment_stats))
+        .route(
+            "/treatments/status/:status",
+            axum::routing::get(get_treatments_by_status),
+        )
+        .route("/treatments/:id", axum::routing::get(get_treatment_by_id))
+        .route("/treatments/:id", guarded(axum::routing::put(update_treatment), Permission::WriteClinical))
+        .route("/treatments/:id", guarded(axum::routing::delete(delete_treatment), Permission::DeleteRecords))
+        .route(
+            "/treatments/:id/status",
+            guarded(axum::routing::put(update_treatment_status), Permission::WriteClinical),
+        )
+        .route(
+            "/patients/:id/treatments",
+            axum::routing::get(get_treatments_by_patient),
+        )
+}
+
+/// Create payment routes
+pub fn payment_routes() -> Router {
+    Router::new()
+        .route("/payments", axum::routing::get(get_payments))
+        .route("/payments", guarded(axum::routing::post(create_payment), Permission::RecordPayments))
+        .route("/payments/recent", axum::routing::get(get_recent_payments))
+        .route("/payments/:id", axum::routing::get(get_payment_by_id))
+        .route("/payments/:id", guarded(axum::routing::put(update_payment), Permission::EditPayments))
+        .route("/payments/:id", guarded(axum::routing::delete(delete_payment), Permission::DeleteRecords))
+        .route(
+            "/treatments/:id/payments",
+            axum::routing::get(get_payments_by_treatment),
+        )
+        .route(
+            "/patients/:id/payments",
+            axum::routing::get(get_payments_by_patient),
+        )
+        .route(
+            "/patients/:id/balance",
+            axum::routing::get(get_patient_balance),
+        )
+        .route("/debts", axum::routing::get(get_patients_with_debt))
+        .route(
+            "/debts/count",
+            axum::routing::get(get_patients_with_debt_count),
+        )
+        .route(
+            "/debts/summary",
+            axum::routing::get(get_patients_with_debt_summary),
+        )
+        .route("/debts/total", axum::routing::get(get_total_debt))
+}
+
+/// Create appointment routes
+pub fn appointment_routes() -> Router {
+    Router::new()
+        .route("/appointments", axum::routing::get(list_appointments))
+        .route("/appointments", guarded(axum::routing::post(create_appointment), Permission::ManageAppointments))
+        .route(
+            "/appointments/upcoming",
+            axum::routing::get(get_upcoming_appointments),
+        )
+        .route(
+            "/appointments/reminders/pending",
+            axum::routing::get(get_pending_reminders),
+        )
+        .route(
+            "/appointments/reminders/:id/notification",
+            axum::routing::get(get_reminder_notification),
+        )
+        .route(
+            "/appointments/reminders/:id/sent",
+            guarded(axum::routing::post(mark_reminder_sent), Permission::ManageAppointments),
+        )
+        .route("/appointments/:id", axum::routing::get(get_appointment))
+        .route("/appointments/:id", guarded(axum::routing::put(update_appointment), Permission::ManageAppointments))
+        .route(
+            "/appointments/:id",
+            guarded(axum::routing::delete(delete_appointment), Permission::DeleteRecords),
+        )
+        .route(
+            "/appointments/:id/occurrences",
+            guarded(
+                axum::routing::put(update_appointment_occurrence),
+                Permission::ManageAppointments,
+            ),
+        )
+        .route(
+            "/appointments/:id/occurrences/cancel",
+            guarded(
+                axum::routing::post(cancel_appointment_occurrence),
+                Permission::ManageAppointments,
+            ),
+        )
+}
+
+/// Create agenda resource routes
+pub fn agenda_resource_routes() -> Router {
+    Router::new()
+        .route("/agenda-resources", axum::routing::get(list_agenda_resources))
+        .route(
+            "/agenda-resources",
+            guarded(axum::routing::post(create_agenda_resource), Permission::ManageAppointments),
+        )
+        .route(
+            "/agenda-resources/free-slots",
+            axum::routing::post(find_free_slots),
+        )
+        .route("/agenda-resources/:id", axum::routing::get(get_agenda_resource))
+        .route(
+            "/agenda-resources/:id",
+            guarded(axum::routing::put(update_agenda_resource), Permission::ManageAppointments),
+        )
+        .route(
+            "/agenda-resources/:id",
+            guarded(axum::routing::delete(delete_agenda_resource), Permission::DeleteRecords),
+        )
+}
+
+/// Create odontogram routes (tooth conditions, surfaces, whole-tooth treatments and bridges)
+pub fn odontogram_routes() -> Router {
+    Router::new()
+        .route(
+            "/patients/:id/odontogram",
+            axum::routing::get(get_odontogram_by_patient),
+        )
+        .route(
+            "/patients/:id/odontogram",
+            guarded(axum::routing::delete(clear_patient_odontogram), Permission::WriteClinical),
+        )
+        .route(
+            "/patients/:id/odontogram/surfaces",
+            axum::routing::get(get_odontogram_surfaces_by_patient),
+        )
+        .route(
+            "/patients/:id/odontogram/tooth-treatments",
+            axum::routing::get(get_tooth_treatments_by_patient),
+        )
+        .route(
+            "/patients/:id/odontogram/bridges",
+            axum::routing::get(get_bridges_by_patient),
+        )
+        .route(
+            "/patients/:id/odontogram/teeth/:tooth",
+            axum::routing::get(get_tooth_condition),
+        )
+        .route(
+            "/patients/:id/odontogram/teeth/:tooth",
+            guarded(axum::routing::delete(delete_tooth_condition), Permission::WriteClinical),
+        )
+        .route(
+            "/patients/:id/odontogram/teeth/:tooth/history",
+            axum::routing::get(get_tooth_history),
+        )
+        .route(
+            "/patients/:id/odontogram/teeth/:tooth/surfaces",
+            axum::routing::get(get_tooth_surfaces),
+        )
+        .route(
+            "/patients/:id/odontogram/teeth/:tooth/surfaces",
+            guarded(axum::routing::delete(clear_tooth_surfaces), Permission::WriteClinical),
+        )
+        .route(
+            "/patients/:id/odontogram/teeth/:tooth/surfaces/history",
+            axum::routing::get(get_tooth_surface_history),
+        )
+        .route(
+            "/patients/:id/odontogram/teeth/:tooth/surfaces/:surface",
+            axum::routing::get(get_surface_treatments),
+        )
+        .route(
+            "/patients/:id/odontogram/teeth/:tooth/surfaces/:surface",
+            guarded(axum::routing::delete(delete_tooth_surface), Permission::WriteClinical),
+        )
+        .route(
+            "/patients/:id/odontogram/teeth/:tooth/surfaces/:surface/history",
+            axum::routing::get(get_surface_history),
+        )
+        .route(
+            "/patients/:id/odontogram/teeth/:tooth/treatments",
+            axum::routing::get(get_tooth_treatments),
+        )
+        .route(
+            "/patients/:id/odontogram/teeth/:tooth/treatments/history",
+            axum::routing::get(get_tooth_treatment_history),
+        )
+        .route(
+            "/odontogram/conditions",
+            guarded(axum::routing::put(update_tooth_condition), Permission::WriteClinical),
+        )
+        .route(
+            "/odontogram/surfaces",
+            guarded(axum::routing::post(add_tooth_surface_treatment), Permission::WriteClinical),
+        )
+        .route(
+            "/odontogram/surfaces",
+            guarded(axum::routing::put(update_tooth_surface), Permission::WriteClinical),
+        )
+        .route(
+            "/odontogram/surfaces/:id",
+            guarded(axum::routing::delete(deactivate_surface_treatment), Permission::WriteClinical),
+        )
+        .route(
+            "/odontogram/tooth-treatments",
+            guarded(axum::routing::post(add_tooth_treatment), Permission::WriteClinical),
+        )
+        .route(
+            "/odontogram/tooth-treatments/:id",
+            guarded(axum::routing::delete(deactivate_tooth_treatment), Permission::WriteClinical),
+        )
+        .route("/odontogram/bridges", guarded(axum::routing::post(add_bridge), Permission::WriteClinical))
+        .route(
+            "/odontogram/bridges/:id",
+            guarded(axum::routing::delete(deactivate_bridge), Permission::WriteClinical),
+        )
+}
+
+/// Create treatment catalog routes
+pub fn treatment_catalog_routes() -> Router {
+    Router::new()
+        .route(
+            "/treatment-catalog",
+            axum::routing::get(get_treatment_catalog),
+        )
+        .route(
+            "/treatment-catalog",
+            guarded(axum::routing::post(create_treatment_catalog), Permission::ManageCatalog),
+        )
+        .route(
+            "/treatment-catalog/items",
+            guarded(axum::routing::post(create_treatment_catalog_item), Permission::ManageCatalog),
+        )
+        .route(
+            "/treatment-catalog/items/:id",
+            axum::routing::get(get_treatment_catalog_item_by_id),
+        )
+        .route(
+            "/treatment-catalog/items/:id",
+            guarded(axum::routing::put(update_treatment_catalog_item), Permission::ManageCatalog),
+        )
+        .route(
+            "/treatment-catalog/items/:id",
+            guarded(axum::routing::delete(delete_treatment_catalog_item), Permission::DeleteRecords),
+        )
+        .route(
+            "/treatment-catalog/:id",
+            axum::routing::get(get_treatment_catalog_by_id),
+        )
+        .route(
+            "/treatment-catalog/:id",
+            guarded(axum::routing::put(update_treatment_catalog), Permission::ManageCatalog),
+        )
+        .route(
+            "/treatment-catalog/:id",
+            guarded(axum::routing::delete(delete_treatment_catalog), Permission::DeleteRecords),
+        )
+        .route(
+            "/treatment-catalog/:id/items",
+            axum::routing::get(get_treatment_catalog_items),
+        )
+}
+
+// ===== PAIRING ROUTES =====
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PairingRequestInput {
+    pub node_name: String,
+}
+
+/// Header carrying the poll token of a pairing ticket
+const PAIRING_TOKEN_HEADER: &str = "X-Pairing-Token";
+
+/// POST /api/pairing/requests - Ask to pair this client with the host (no auth)
+#[utoipa::path(
+    post,
+    path = "/api/pairing/requests",
+    tag = "pairing",
+    request_body = PairingRequestInput,
+    responses((status = 201, body = PairingTicket), ApiErrors),
+    security(())
+)]
+pub async fn request_pairing(Json(input): Json<PairingRequestInput>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        let service = PairingService::new();
+        match service.request_pairing(&input.node_name) {
+            Ok(ticket) => (StatusCode::CREATED, Json(ticket)).into_response(),
+            Err(e) => super::service_error_to_response(e),
+        }
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/pairing/requests/:id - Poll a pairing request (X-Pairing-Token header)
+/// Returns the node credential once the host operator approves the request.
+#[utoipa::path(
+    get,
+    path = "/api/pairing/requests/{id}",
+    tag = "pairing",
+    params(
+        ("id" = String, Path, description = "Pairing request ID"),
+        ("X-Pairing-Token" = String, Header, description = "Poll token from the pairing ticket"),
+    ),
+    responses((status = 200, body = PairingStatus), ApiErrors),
+    security(())
+)]
+pub async fn get_pairing_status(
+    Path(id): Path<String>,
+    headers: axum::http::HeaderMap,
+) -> impl IntoResponse {
+    let poll_token = headers
+        .get(PAIRING_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    task::spawn_blocking(move || {
+        let service = PairingService::new();
+        json_or_error(service.poll_request(&id, &poll_token))
+    })
+    .await
+    .unwrap()
+}
+
+/// GET /api/pairing/node - Identify the paired node behind the current credential
+/// Lets a client check that the host still accepts its credential.
+#[utoipa::path(
+    get,
+    path = "/api/pairing/node",
+    tag = "pairing",
+    responses((status = 200, body = PairedNodeIdentity), AuthErrors)
+)]
+pub async fn get_current_paired_node(
+    node: Option<axum::Extension<super::PairedNodeAuth>>,
+) -> impl IntoResponse {
+    match node {
+        Some(axum::Extension(node)) => {
+            (StatusCode::OK, Json(serde_json::json!({ "node_id": node.node_id }))).into_response()
+        }
+        None => auth_error(
+            StatusCode::BAD_REQUEST,
+            "Solo disponible para nodos emparejados".to_string(),
+        ),
+    }
+}
+
+/// GET /api/pairing/nodes - List paired nodes (admin)
+#[utoipa::path(
+    get,
+    path = "/api/pairing/nodes",
+    tag = "pairing",
+    responses((status = 200, body = Vec<PairedNode>), ApiErrors)
+)]
+pub async fn list_paired_nodes() -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        let service = PairingService::new();
+        json_or_error(service.list_paired_nodes())
+    })
+    .await
+    .unwrap()
+}
+
+/// DELETE /api/pairing/nodes/:id - Revoke a paired node's credential (admin)
+#[utoipa::path(
+    delete,
+    path = "/api/pairing/nodes/{id}",
+    tag = "pairing",
+    params(("id" = i64, Path, description = "Paired node ID")),
+    responses((status = 200, body = MessageResponse), ApiErrors)
+)]
+pub async fn revoke_paired_node(Path(id): Path<i64>) -> impl IntoResponse {
+    task::spawn_blocking(move || {
+        let service = PairingService::new();
+        message_or_error(service.revoke_paired_node(id), "Paired node revoked successfully")
+    })
+    .await
+    .unwrap()
+}
+
+/// Pairing requests one client IP may create per minute
+/// Requests need no authentication and the queue of pending ones is small.
+const PAIRING_REQUESTS_PER_IP_PER_MINUTE: u32 = 5;
+
+/// Create node pairing routes
+pub fn pairing_routes() -> Router {
+    let limiter = Arc::new(IpRateLimiter::new(PAIRING_REQUESTS_PER_IP_PER_MINUTE));
+
+    Router::new()
+        .route(
+            "/pairing/requests",
+            axum::routing::post(request_pairing).route_layer(middleware::from_fn_with_state(
+                limiter,
+                super::rate_limit::ip_rate_limit_middleware,
+            )),
+        )
+        .route("/pairing/requests/:id", axum::routing::get(get_pairing_status))
+        .route("/pairing/node", axum::routing::get(get_current_paired_node))
+        .route(
+            "/pairing/nodes",
+            guarded(axum::routing::get(list_paired_nodes), Permission::ManageSecurity),
+        )
+        .route(
+            "/pairing/nodes/:id",
+            guarded(axum::routing::delete(revoke_paired_node), Permission::ManageSecurity),
+        )
+}
+
+// ===== EVENT ROUTES =====
+
+/// GET /api/events - Server-Sent Events stream of entity changes
+/// Each message is named after the change (`patient.updated`, ...) and carries it as JSON.
+/// Events missed by a slow subscriber are dropped; clients should reload on reconnect.
+#[utoipa::path(
+    get,
+    path = "/api/events",
+    tag = "events",
+    responses((status = 200, description = "Server-Sent Events stream; each message carries a ChangeEvent", body = ChangeEvent, content_type = "text/event-stream"), ApiErrors)
+)]
+pub async fn stream_events() -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let stream = BroadcastStream::new(events::subscribe()).filter_map(|change| {
+        change
+            .ok()
+            .map(|change| Event::default().event(change.event_type.as_str()).json_data(&change))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Create event stream routes
+pub fn event_routes() -> Router {
+    Router::new().route("/events", axum::routing::get(stream_events))
+}
+
+/// Create the full API router (nested under /api by the server)
+pub fn api_routes() -> Router {
+    patient_routes()
+        .merge(treatment_routes())
+        .merge(payment_routes())
+        .merge(appointment_routes())
+        .merge(agenda_resource_routes())
+        .merge(odontogram_routes())
+        .merge(treatment_catalog_routes())
+        .merge(pairing_routes())
+        .merge(event_routes())
+}
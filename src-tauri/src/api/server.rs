@@ -3,6 +3,8 @@
 
 use crate::node::HostConfig;
 use axum::{middleware, Router};
+use axum_server::tls_rustls::RustlsConfig;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tower_http::cors::{Any, CorsLayer};
@@ -29,12 +31,23 @@ impl ApiServer {
 
         // Create router with authentication middleware
         let mut app = Router::new()
-            .nest("/api", super::routes::patient_routes())
+            .nest("/api", super::routes::api_routes())
             .layer(middleware::from_fn_with_state(
                 token.clone(),
                 super::auth_middleware,
             ));
 
+        // The global limit runs before authentication so rejected floods stay cheap
+        if config.rate_limit_per_minute > 0 {
+            let limiter = Arc::new(super::rate_limit::RateLimiter::new(
+                config.rate_limit_per_minute,
+            ));
+            app = app.layer(middleware::from_fn_with_state(
+                limiter,
+                super::rate_limit::rate_limit_middleware,
+            ));
+        }
+
         // Add CORS if enabled
         if enable_cors {
             let cors = CorsLayer::new()
@@ -44,6 +57,13 @@ impl ApiServer {
             app = app.layer(cors);
         }
 
+        // Load (or generate) the certificate before binding so errors reach the caller
+        let tls_identity = if config.enable_tls {
+            Some(crate::tls::load_or_create_identity()?)
+        } else {
+            None
+        };
+
         let addr = format!("0.0.0.0:{}", port);
         
         // Try to bind immediately to catch early errors
@@ -54,13 +74,46 @@ impl ApiServer {
         log::info!("HTTP API server bound to {}", addr);
 
         // Spawn server in background
-        let handle = tokio::spawn(async move {
-            log::info!("Starting HTTP API server on {}", addr);
-
-            if let Err(e) = axum::serve(listener, app).await {
-                log::error!("API server error: {}", e);
+        let handle = match tls_identity {
+            Some(identity) => {
+                let rustls_config = RustlsConfig::from_pem(
+                    identity.cert_pem.into_bytes(),
+                    identity.key_pem.into_bytes(),
+                )
+                .await
+                .map_err(|e| format!("Invalid TLS certificate: {}", e))?;
+                let listener = listener
+                    .into_std()
+                    .map_err(|e| format!("Failed to prepare TLS listener: {}", e))?;
+
+                tokio::spawn(async move {
+                    log::info!(
+                        "Starting HTTPS API server on {} (certificate {})",
+                        addr,
+                        identity.fingerprint
+                    );
+
+                    if let Err(e) = axum_server::from_tcp_rustls(listener, rustls_config)
+                        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                        .await
+                    {
+                        log::error!("API server error: {}", e);
+                    }
+                })
             }
-        });
+            None => tokio::spawn(async move {
+                log::info!("Starting HTTP API server on {}", addr);
+
+                if let Err(e) = axum::serve(
+                    listener,
+                    app.into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .await
+                {
+                    log::error!("API server error: {}", e);
+                }
+            }),
+        };
 
         self.handle = Some(handle);
         log::info!("HTTP API server started on port {}", port);
@@ -76,6 +76,13 @@ pub fn get_config_manager() -> &'static Mutex<Result<ConfigManager, String>> {
     INSTANCE.get_or_init(|| Mutex::new(ConfigManager::new()))
 }
 
+/// Valor actual de una clave, para el código del backend que depende de la configuración
+/// Devuelve `None` si la clave no existe o el gestor no pudo inicializarse.
+pub fn current_value(key: &str) -> Option<Value> {
+    let guard = get_config_manager().lock().ok()?;
+    guard.as_ref().ok()?.get_value(key)
+}
+
 #[tauri::command]
 pub fn get_config_schema() -> Result<Value, String> {
     let guard = get_config_manager()
@@ -0,0 +1,712 @@
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, SecondsFormat, TimeZone, Utc,
+};
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::appointments;
+
+/// Días en los que se buscan huecos libres si no se indica el final del rango
+const SLOT_SEARCH_DAYS: i64 = 30;
+
+/// Separación por defecto entre los inicios de los huecos libres
+const DEFAULT_SLOT_STEP_MINUTES: i64 = 15;
+
+/// Huecos devueltos por defecto y como máximo
+const DEFAULT_SLOT_LIMIT: usize = 10;
+pub const MAX_SLOT_LIMIT: usize = 200;
+
+/// Duración y separación máximas de los huecos, en minutos
+pub const MAX_SLOT_MINUTES: i64 = 24 * 60;
+
+/// Tipo de recurso de la agenda
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    /// Profesional (odontólogo, higienista)
+    Practitioner,
+    /// Sillón, box o consultorio
+    Chair,
+}
+
+impl ResourceKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ResourceKind::Practitioner => "practitioner",
+            ResourceKind::Chair => "chair",
+        }
+    }
+
+    fn from_str(value: &str) -> Self {
+        match value {
+            "chair" => ResourceKind::Chair,
+            _ => ResourceKind::Practitioner,
+        }
+    }
+}
+
+/// Profesional o sillón con su horario semanal.
+///
+/// Las citas de un recurso son las que lo tienen en `practitioner_id` o en
+/// `chair_id` (igual que en la detección de conflictos).
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct AgendaResource {
+    pub id: Option<i64>,
+    pub kind: ResourceKind,
+    pub name: String,
+    /// Usuario del profesional
+    pub user_id: Option<i64>,
+    pub color: Option<String>,
+    pub active: bool,
+    /// Franjas de atención de cada día de la semana
+    #[serde(default)]
+    pub working_hours: Vec<WeeklyHours>,
+    /// Pausas dentro de las franjas de atención
+    #[serde(default)]
+    pub breaks: Vec<WeeklyHours>,
+    #[serde(default)]
+    pub days_off: Vec<DayOff>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+/// Franja horaria semanal, en hora local de la clínica
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct WeeklyHours {
+    /// 0 = lunes ... 6 = domingo
+    pub weekday: u8,
+    /// "HH:MM"
+    pub start_time: String,
+    /// "HH:MM"
+    pub end_time: String,
+}
+
+/// Días libres (vacaciones, licencias), ambos incluidos
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct DayOff {
+    /// "YYYY-MM-DD"
+    pub start_date: String,
+    /// "YYYY-MM-DD"
+    pub end_date: String,
+    pub reason: Option<String>,
+}
+
+/// Búsqueda de los próximos huecos en los que todos los recursos están libres
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct FreeSlotQuery {
+    pub resource_ids: Vec<i64>,
+    pub duration_minutes: i64,
+    /// Desde cuándo buscar (RFC 3339 o YYYY-MM-DD); por defecto, ahora
+    pub from: Option<String>,
+    /// Hasta cuándo buscar; por defecto, 30 días después de `from`
+    pub to: Option<String>,
+    /// Separación entre los inicios de los huecos; por defecto, 15 minutos
+    pub step_minutes: Option<i64>,
+    /// Cantidad de huecos; por defecto 10, como máximo 200
+    pub limit: Option<usize>,
+}
+
+/// Hueco en el que se puede agendar una cita
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
+pub struct FreeSlot {
+    pub start_time: String,
+    pub end_time: String,
+}
+
+type Interval = (DateTime<Utc>, DateTime<Utc>);
+
+pub fn list_resources(conn: &Connection, include_inactive: bool) -> Result<Vec<AgendaResource>, String> {
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT id, kind, name, user_id, color, active, created_at, updated_at
+            FROM agenda_resources
+            WHERE active = 1 OR ?1
+            ORDER BY kind, name
+            "#,
+        )
+        .map_err(|e| format!("Error al preparar query: {}", e))?;
+
+    let mut resources = stmt
+        .query_map(params![include_inactive], resource_from_row)
+        .map_err(|e| format!("Error al listar recursos: {}", e))?
+        .collect::<Result<Vec<_>>>()
+        .map_err(|e| format!("Error al procesar recursos: {}", e))?;
+
+    for resource in &mut resources {
+        load_schedule(conn, resource)?;
+    }
+
+    Ok(resources)
+}
+
+pub fn get_resource(conn: &Connection, id: i64) -> Result<Option<AgendaResource>, String> {
+    let resource = conn
+        .query_row(
+            r#"
+            SELECT id, kind, name, user_id, color, active, created_at, updated_at
+            FROM agenda_resources WHERE id = ?1
+            "#,
+            params![id],
+            resource_from_row,
+        )
+        .optional()
+        .map_err(|e| format!("Error al obtener recurso: {}", e))?;
+
+    match resource {
+        Some(mut resource) => {
+            load_schedule(conn, &mut resource)?;
+            Ok(Some(resource))
+        }
+        None => Ok(None),
+    }
+}
+
+pub fn create_resource(conn: &Connection, resource: &AgendaResource) -> Result<i64, String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Error al iniciar transacción: {}", e))?;
+
+    tx.execute(
+        r#"
+        INSERT INTO agenda_resources (kind, name, user_id, color, active, created_at, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+        "#,
+        params![
+            resource.kind.as_str(),
+            resource.name,
+            resource.user_id,
+            resource.color,
+            resource.active,
+            now
+        ],
+    )
+    .map_err(|e| format!("Error al crear recurso: {}", e))?;
+
+    let id = tx.last_insert_rowid();
+    save_schedule(&tx, id, resource)?;
+
+    tx.commit()
+        .map_err(|e| format!("Error al confirmar transacción: {}", e))?;
+    Ok(id)
+}
+
+/// Actualiza un recurso y reemplaza su horario
+pub fn update_resource(conn: &Connection, resource: &AgendaResource) -> Result<(), String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let id = resource
+        .id
+        .ok_or_else(|| "ID de recurso requerido para actualizar".to_string())?;
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Error al iniciar transacción: {}", e))?;
+
+    tx.execute(
+        r#"
+        UPDATE agenda_resources SET
+            kind = ?1,
+            name = ?2,
+            user_id = ?3,
+            color = ?4,
+            active = ?5,
+            updated_at = ?6
+        WHERE id = ?7
+        "#,
+        params![
+            resource.kind.as_str(),
+            resource.name,
+            resource.user_id,
+            resource.color,
+            resource.active,
+            now,
+            id
+        ],
+    )
+    .map_err(|e| format!("Error al actualizar recurso: {}", e))?;
+
+    tx.execute("DELETE FROM agenda_resource_hours WHERE resource_id = ?1", params![id])
+        .map_err(|e| format!("Error al actualizar horario: {}", e))?;
+    tx.execute("DELETE FROM agenda_resource_days_off WHERE resource_id = ?1", params![id])
+        .map_err(|e| format!("Error al actualizar días libres: {}", e))?;
+    save_schedule(&tx, id, resource)?;
+
+    tx.commit()
+        .map_err(|e| format!("Error al confirmar transacción: {}", e))
+}
+
+pub fn delete_resource(conn: &Connection, id: i64) -> Result<(), String> {
+    conn.execute("DELETE FROM agenda_resources WHERE id = ?1", params![id])
+        .map_err(|e| format!("Error al eliminar recurso: {}", e))?;
+    Ok(())
+}
+
+fn resource_from_row(row: &rusqlite::Row) -> Result<AgendaResource> {
+    Ok(AgendaResource {
+        id: Some(row.get(0)?),
+        kind: ResourceKind::from_str(&row.get::<_, String>(1)?),
+        name: row.get(2)?,
+        user_id: row.get(3)?,
+        color: row.get(4)?,
+        active: row.get(5)?,
+        working_hours: Vec::new(),
+        breaks: Vec::new(),
+        days_off: Vec::new(),
+        created_at: Some(row.get(6)?),
+        updated_at: Some(row.get(7)?),
+    })
+}
+
+fn load_schedule(conn: &Connection, resource: &mut AgendaResource) -> Result<(), String> {
+    let id = resource.id.unwrap_or_default();
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT weekday, start_time, end_time, is_break FROM agenda_resource_hours
+            WHERE resource_id = ?1 ORDER BY weekday, start_time
+            "#,
+        )
+        .map_err(|e| format!("Error al preparar query: {}", e))?;
+    let hours = stmt
+        .query_map(params![id], |row| {
+            Ok((
+                WeeklyHours {
+                    weekday: row.get(0)?,
+                    start_time: row.get(1)?,
+                    end_time: row.get(2)?,
+                },
+                row.get::<_, bool>(3)?,
+            ))
+        })
+        .map_err(|e| format!("Error al obtener horario: {}", e))?
+        .collect::<Result<Vec<_>>>()
+        .map_err(|e| format!("Error al procesar horario: {}", e))?;
+
+    for (hours, is_break) in hours {
+        if is_break {
+            resource.breaks.push(hours);
+        } else {
+            resource.working_hours.push(hours);
+        }
+    }
+
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT start_date, end_date, reason FROM agenda_resource_days_off
+            WHERE resource_id = ?1 ORDER BY start_date
+            "#,
+        )
+        .map_err(|e| format!("Error al preparar query: {}", e))?;
+    resource.days_off = stmt
+        .query_map(params![id], |row| {
+            Ok(DayOff {
+                start_date: row.get(0)?,
+                end_date: row.get(1)?,
+                reason: row.get(2)?,
+            })
+        })
+        .map_err(|e| format!("Error al obtener días libres: {}", e))?
+        .collect::<Result<Vec<_>>>()
+        .map_err(|e| format!("Error al procesar días libres: {}", e))?;
+
+    Ok(())
+}
+
+fn save_schedule(conn: &Connection, id: i64, resource: &AgendaResource) -> Result<(), String> {
+    let hours = resource
+        .working_hours
+        .iter()
+        .map(|hours| (hours, false))
+        .chain(resource.breaks.iter().map(|hours| (hours, true)));
+
+    for (hours, is_break) in hours {
+        conn.execute(
+            r#"
+            INSERT INTO agenda_resource_hours (resource_id, weekday, start_time, end_time, is_break)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+            params![id, hours.weekday, hours.start_time, hours.end_time, is_break],
+        )
+        .map_err(|e| format!("Error al guardar horario: {}", e))?;
+    }
+
+    for day_off in &resource.days_off {
+        conn.execute(
+            r#"
+            INSERT INTO agenda_resource_days_off (resource_id, start_date, end_date, reason)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![id, day_off.start_date, day_off.end_date, day_off.reason],
+        )
+        .map_err(|e| format!("Error al guardar días libres: {}", e))?;
+    }
+
+    Ok(())
+}
+
+// Búsqueda de huecos libres
+
+/// Próximos huecos de `duration_minutes` en los que todos los recursos de la
+/// búsqueda atienden (según su horario, pausas y días libres) y no tienen citas.
+///
+/// Las citas ocupadas se obtienen con `list_busy`, así que las ocurrencias de
+/// series recurrentes y las citas que empezaron antes del rango también cuentan.
+pub fn find_free_slots(conn: &Connection, query: &FreeSlotQuery) -> Result<Vec<FreeSlot>, String> {
+    let step_minutes = query.step_minutes.unwrap_or(DEFAULT_SLOT_STEP_MINUTES);
+    let in_range = |minutes: i64| (1..=MAX_SLOT_MINUTES).contains(&minutes);
+    if !in_range(query.duration_minutes) || !in_range(step_minutes) {
+        return Err(format!(
+            "La duración y la separación de los huecos deben estar entre 1 y {} minutos",
+            MAX_SLOT_MINUTES
+        ));
+    }
+    let duration = Duration::minutes(query.duration_minutes);
+    let step = Duration::minutes(step_minutes);
+
+    let resources = query
+        .resource_ids
+        .iter()
+        .map(|&id| get_resource(conn, id)?.ok_or_else(|| format!("Recurso {} no encontrado", id)))
+        .collect::<Result<Vec<_>, String>>()?;
+    if resources.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let from = match query.from.as_deref() {
+        Some(from) => appointments::parse_filter_date(from)?,
+        None => Utc::now(),
+    };
+    let to = match query.to.as_deref() {
+        Some(to) => appointments::parse_filter_date(to)?,
+        None => from + Duration::days(SLOT_SEARCH_DAYS),
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_SLOT_LIMIT).min(MAX_SLOT_LIMIT);
+
+    let busy = busy_intervals(conn, &resources, from, to)?;
+
+    let mut slots = Vec::new();
+    let mut date = from.with_timezone(&Local).date_naive();
+    let last = to.with_timezone(&Local).date_naive();
+    while date <= last && slots.len() < limit {
+        let mut free = vec![(from, to)];
+        for resource in &resources {
+            free = intersect(&free, &availability(resource, date)?);
+        }
+
+        for (start, end) in subtract(&free, &busy) {
+            let mut slot = align(start, step);
+            while slot + duration <= end && slots.len() < limit {
+                slots.push(FreeSlot {
+                    start_time: format_time(slot),
+                    end_time: format_time(slot + duration),
+                });
+                slot += step;
+            }
+        }
+
+        date = match date.succ_opt() {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    Ok(slots)
+}
+
+/// Citas de cualquiera de los recursos que ocupan parte de [from, to), ordenadas
+fn busy_intervals(
+    conn: &Connection,
+    resources: &[AgendaResource],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<Interval>, String> {
+    let ids: Vec<i64> = resources.iter().filter_map(|resource| resource.id).collect();
+
+    let mut busy = Vec::new();
+    for existing in appointments::list_busy(conn, from, to)? {
+        let appointment = existing.appointment;
+        let uses_resource = [appointment.practitioner_id, appointment.chair_id]
+            .into_iter()
+            .flatten()
+            .any(|id| ids.contains(&id));
+        if !uses_resource {
+            continue;
+        }
+
+        busy.push((parse_time(&appointment.start_time)?, parse_time(&appointment.end_time)?));
+    }
+
+    busy.sort();
+    Ok(busy)
+}
+
+/// Franjas en las que un recurso atiende un día, sin sus pausas
+fn availability(resource: &AgendaResource, date: NaiveDate) -> Result<Vec<Interval>, String> {
+    for day_off in &resource.days_off {
+        if parse_date(&day_off.start_date)? <= date && date <= parse_date(&day_off.end_date)? {
+            return Ok(Vec::new());
+        }
+    }
+
+    let weekday = date.weekday().num_days_from_monday() as u8;
+    let working_hours = merge(day_intervals(&resource.working_hours, weekday, date)?);
+    let breaks = day_intervals(&resource.breaks, weekday, date)?;
+    Ok(subtract(&working_hours, &breaks))
+}
+
+fn day_intervals(hours: &[WeeklyHours], weekday: u8, date: NaiveDate) -> Result<Vec<Interval>, String> {
+    let mut intervals = Vec::new();
+    for hours in hours.iter().filter(|hours| hours.weekday == weekday) {
+        let start = local_instant(date, parse_clock(&hours.start_time)?);
+        let end = local_instant(date, parse_clock(&hours.end_time)?);
+        if let (Some(start), Some(end)) = (start, end) {
+            if start < end {
+                intervals.push((start, end));
+            }
+        }
+    }
+
+    intervals.sort();
+    Ok(intervals)
+}
+
+/// Une los intervalos que se solapan (deben estar ordenados)
+fn merge(intervals: Vec<Interval>) -> Vec<Interval> {
+    let mut merged: Vec<Interval> = Vec::new();
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+fn intersect(a: &[Interval], b: &[Interval]) -> Vec<Interval> {
+    let mut result = Vec::new();
+    for &(a_start, a_end) in a {
+        for &(b_start, b_end) in b {
+            let start = a_start.max(b_start);
+            let end = a_end.min(b_end);
+            if start < end {
+                result.push((start, end));
+            }
+        }
+    }
+    result.sort();
+    result
+}
+
+/// Quita de `free` los intervalos de `busy` (ordenados por inicio)
+fn subtract(free: &[Interval], busy: &[Interval]) -> Vec<Interval> {
+    let mut result = Vec::new();
+    for &(start, end) in free {
+        let mut cursor = start;
+        for &(busy_start, busy_end) in busy {
+            if busy_end <= cursor || busy_start >= end {
+                continue;
+            }
+            if busy_start > cursor {
+                result.push((cursor, busy_start));
+            }
+            cursor = cursor.max(busy_end);
+            if cursor >= end {
+                break;
+            }
+        }
+        if cursor < end {
+            result.push((cursor, end));
+        }
+    }
+    result
+}
+
+/// Primer instante desde `instant` que cae en un múltiplo de `step` desde la
+/// medianoche local
+fn align(instant: DateTime<Utc>, step: Duration) -> DateTime<Utc> {
+    let local = instant.with_timezone(&Local);
+    let Some(midnight) = local_instant(local.date_naive(), NaiveTime::MIN) else {
+        return instant;
+    };
+
+    let step = step.num_milliseconds();
+    let elapsed = (instant - midnight).num_milliseconds();
+    let steps = (elapsed + step - 1).div_euclid(step);
+    midnight + Duration::milliseconds(steps * step)
+}
+
+fn local_instant(date: NaiveDate, time: NaiveTime) -> Option<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|instant| instant.with_timezone(&Utc))
+}
+
+/// Valida una hora "HH:MM" de un horario
+pub fn parse_clock(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| format!("Hora inválida: '{}'", value))
+}
+
+/// Valida una fecha "YYYY-MM-DD" de un día libre
+pub fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| format!("Fecha inválida: '{}'", value))
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|e| format!("Error al parsear fecha: {}", e))
+}
+
+fn format_time(value: DateTime<Utc>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::appointments::{create_appointment, Appointment};
+    use crate::db::get_connection;
+
+    fn at(date: NaiveDate, hour: u32, minute: u32) -> String {
+        format_time(local_instant(date, NaiveTime::from_hms_opt(hour, minute, 0).unwrap()).unwrap())
+    }
+
+    fn hours(weekday: u8, start: &str, end: &str) -> WeeklyHours {
+        WeeklyHours {
+            weekday,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+        }
+    }
+
+    #[test]
+    fn free_slots_skip_breaks_days_off_and_appointments() {
+        let conn = get_connection().expect("db");
+        let now = chrono::Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO patients (first_name, last_name, created_at, updated_at) VALUES ('Hueco', 'Libre', ?1, ?1)",
+            params![now],
+        )
+        .unwrap();
+        let patient_id = conn.last_insert_rowid();
+
+        // Lunes 5 y martes 6 de mayo de 2031
+        let monday = NaiveDate::from_ymd_opt(2031, 5, 5).unwrap();
+        let tuesday = monday.succ_opt().unwrap();
+
+        let practitioner = create_resource(
+            &conn,
+            &AgendaResource {
+                id: None,
+                kind: ResourceKind::Practitioner,
+                name: "Dra. Huecos".to_string(),
+                user_id: None,
+                color: None,
+                active: true,
+                working_hours: vec![hours(0, "09:00", "13:00"), hours(1, "09:00", "10:00")],
+                breaks: vec![hours(0, "10:00", "11:00")],
+                days_off: Vec::new(),
+                created_at: None,
+                updated_at: None,
+            },
+        )
+        .unwrap();
+        let chair = create_resource(
+            &conn,
+            &AgendaResource {
+                id: None,
+                kind: ResourceKind::Chair,
+                name: "Sillón Huecos".to_string(),
+                user_id: None,
+                color: None,
+                active: true,
+                working_hours: (0..5).map(|day| hours(day, "08:00", "20:00")).collect(),
+                breaks: Vec::new(),
+                days_off: vec![DayOff {
+                    start_date: "2031-05-06".to_string(),
+                    end_date: "2031-05-06".to_string(),
+                    reason: Some("Mantenimiento".to_string()),
+                }],
+                created_at: None,
+                updated_at: None,
+            },
+        )
+        .unwrap();
+
+        let appointment =
+            |start: String, end: String, chair_id: Option<i64>, practitioner_id: Option<i64>| {
+                Appointment {
+                    id: None,
+                    patient_id,
+                    title: "Extracción".to_string(),
+                    description: None,
+                    start_time: start,
+                    end_time: end,
+                    status: "scheduled".to_string(),
+                    appointment_type: None,
+                    location: None,
+                    reminder_minutes: None,
+                    color: None,
+                    created_by: None,
+                    created_at: None,
+                    updated_at: None,
+                    recurrence_rule: None,
+                    series_id: None,
+                    original_start_time: None,
+                    practitioner_id,
+                    chair_id,
+                }
+            };
+
+        // El lunes el sillón está ocupado de 11:00 a 12:00 y la doctora atiende
+        // en otro sillón de 12:30 a 13:00; una cita que solo nombra el sillón en
+        // su ubicación no lo ocupa
+        let mut by_name = appointment(at(monday, 9, 0), at(monday, 9, 30), None, None);
+        by_name.location = Some("Sillón Huecos".to_string());
+        let busy = [
+            appointment(at(monday, 11, 0), at(monday, 12, 0), Some(chair), None),
+            appointment(at(monday, 12, 30), at(monday, 13, 0), None, Some(practitioner)),
+            by_name,
+        ];
+        for busy in &busy {
+            create_appointment(&conn, busy).unwrap();
+        }
+
+        let slots = find_free_slots(
+            &conn,
+            &FreeSlotQuery {
+                resource_ids: vec![practitioner, chair],
+                duration_minutes: 30,
+                from: Some(at(monday, 0, 0)),
+                to: Some(at(tuesday, 23, 0)),
+                step_minutes: Some(30),
+                limit: None,
+            },
+        )
+        .unwrap();
+
+        let starts: Vec<String> = slots.into_iter().map(|slot| slot.start_time).collect();
+        assert_eq!(starts, [at(monday, 9, 0), at(monday, 9, 30), at(monday, 12, 0)]);
+
+        // Duraciones fuera de rango se rechazan en lugar de desbordar
+        let too_long = FreeSlotQuery {
+            resource_ids: vec![practitioner],
+            duration_minutes: i64::MAX,
+            from: None,
+            to: None,
+            step_minutes: None,
+            limit: None,
+        };
+        assert!(find_free_slots(&conn, &too_long).is_err());
+
+        delete_resource(&conn, practitioner).unwrap();
+        delete_resource(&conn, chair).unwrap();
+        assert!(get_resource(&conn, chair).unwrap().is_none());
+        conn.execute("DELETE FROM patients WHERE id = ?1", params![patient_id])
+            .unwrap();
+    }
+}
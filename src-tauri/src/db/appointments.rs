@@ -1,8 +1,11 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, SecondsFormat, Utc};
 use rusqlite::{params, Connection, Result};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
+use super::recurrence::RecurrenceRule;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Appointment {
     pub id: Option<i64>,
     pub patient_id: i64,
@@ -18,9 +21,56 @@ pub struct Appointment {
     pub created_by: Option<i64>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    /// Regla de recurrencia estilo RRULE (p. ej. "FREQ=WEEKLY;COUNT=10") de una serie
+    #[serde(default)]
+    pub recurrence_rule: Option<String>,
+    /// Serie a la que pertenece una ocurrencia o excepción
+    #[serde(default)]
+    pub series_id: Option<i64>,
+    /// Inicio que tenía la ocurrencia según la regla de la serie; junto con
+    /// `series_id` identifica la ocurrencia
+    #[serde(default)]
+    pub original_start_time: Option<String>,
+    /// Profesional que atiende (recurso de la agenda de tipo `practitioner`)
+    #[serde(default)]
+    pub practitioner_id: Option<i64>,
+    /// Sillón, box o consultorio (recurso de la agenda de tipo `chair`)
+    #[serde(default)]
+    pub chair_id: Option<i64>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Alcance de un cambio sobre una ocurrencia de una cita recurrente
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RecurrenceScope {
+    /// Solo esta ocurrencia (se guarda como excepción de la serie)
+    Occurrence,
+    /// Esta ocurrencia y las siguientes (la serie se divide en dos)
+    Following,
+    /// Toda la serie
+    Series,
+}
+
+/// Motivo por el que una cita choca con otra
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictReason {
+    /// Mismo sillón, box o consultorio (`chair_id`)
+    Chair,
+    /// Mismo profesional (`practitioner_id`)
+    Practitioner,
+}
+
+/// Cita existente que se superpone con la que se quiere guardar
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct AppointmentConflict {
+    /// Inicio de la cita (u ocurrencia) nueva que choca
+    pub start_time: String,
+    pub reasons: Vec<ConflictReason>,
+    pub appointment: AppointmentWithPatient,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct AppointmentWithPatient {
     #[serde(flatten)]
     pub appointment: Appointment,
@@ -28,18 +78,40 @@ pub struct AppointmentWithPatient {
     pub patient_phone: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct AppointmentReminder {
     pub id: Option<i64>,
     pub appointment_id: i64,
     pub scheduled_time: String,
     pub sent: bool,
     pub sent_at: Option<String>,
+    /// Aviso enviado: `email:`, `sms:` o `whatsapp:` seguido del ID del mensaje al
+    /// paciente, o `desktop:` cuando solo se avisó al personal
     pub notification_id: Option<String>,
     pub created_at: Option<String>,
+    /// Inicio de la cita u ocurrencia que anuncia el recordatorio
+    #[serde(default)]
+    pub occurrence_start_time: Option<String>,
+    /// Envíos fallidos hasta ahora
+    #[serde(default)]
+    pub attempts: i32,
+    /// Próximo reintento después de un envío fallido
+    #[serde(default)]
+    pub next_attempt_at: Option<String>,
+    /// Error del último envío fallido, o motivo por el que se descartó
+    #[serde(default)]
+    pub last_error: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Recordatorio junto con la cita (u ocurrencia) que anuncia
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ReminderNotification {
+    pub reminder: AppointmentReminder,
+    pub appointment: AppointmentWithPatient,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct AppointmentFilter {
     pub start_date: Option<String>,
     pub end_date: Option<String>,
@@ -47,6 +119,14 @@ pub struct AppointmentFilter {
     pub status: Option<String>,
 }
 
+const APPOINTMENT_COLUMNS: &str = r#"
+    a.id, a.patient_id, a.title, a.description, a.start_time, a.end_time,
+    a.status, a.appointment_type, a.location, a.reminder_minutes, a.color,
+    a.created_by, a.created_at, a.updated_at,
+    a.recurrence_rule, a.series_id, a.original_start_time,
+    a.practitioner_id, a.chair_id
+"#;
+
 pub fn create_appointment(conn: &Connection, appointment: &Appointment) -> Result<i64, String> {
     let now = chrono::Utc::now().to_rfc3339();
 
@@ -55,8 +135,10 @@ pub fn create_appointment(conn: &Connection, appointment: &Appointment) -> Resul
         INSERT INTO appointments (
             patient_id, title, description, start_time, end_time, 
             status, appointment_type, location, reminder_minutes, 
-            color, created_by, created_at, updated_at
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            color, created_by, created_at, updated_at,
+            recurrence_rule, series_id, original_start_time,
+            practitioner_id, chair_id
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)
         "#,
         params![
             appointment.patient_id,
@@ -71,7 +153,12 @@ pub fn create_appointment(conn: &Connection, appointment: &Appointment) -> Resul
             appointment.color,
             appointment.created_by,
             now,
-            now
+            now,
+            appointment.recurrence_rule,
+            appointment.series_id,
+            appointment.original_start_time,
+            appointment.practitioner_id,
+            appointment.chair_id
         ],
     )
     .map_err(|e| format!("Error al crear cita: {}", e))?;
@@ -99,6 +186,7 @@ pub fn update_appointment(conn: &Connection, appointment: &Appointment) -> Resul
     let id = appointment
         .id
         .ok_or_else(|| "ID de cita requerido para actualizar".to_string())?;
+    let previous = get_appointment(conn, id).ok();
 
     conn.execute(
         r#"
@@ -113,8 +201,11 @@ pub fn update_appointment(conn: &Connection, appointment: &Appointment) -> Resul
             location = ?8,
             reminder_minutes = ?9,
             color = ?10,
-            updated_at = ?11
-        WHERE id = ?12
+            updated_at = ?11,
+            recurrence_rule = ?12,
+            practitioner_id = ?13,
+            chair_id = ?14
+        WHERE id = ?15
         "#,
         params![
             appointment.patient_id,
@@ -128,16 +219,27 @@ pub fn update_appointment(conn: &Connection, appointment: &Appointment) -> Resul
             appointment.reminder_minutes,
             appointment.color,
             now,
+            appointment.recurrence_rule,
+            appointment.practitioner_id,
+            appointment.chair_id,
             id
         ],
     )
     .map_err(|e| format!("Error al actualizar cita: {}", e))?;
 
-    // Actualizar recordatorios si cambió el reminder_minutes o start_time
-    delete_reminders_for_appointment(conn, id)?;
-    if let Some(reminder_mins) = appointment.reminder_minutes {
-        if reminder_mins > 0 {
-            create_reminder_for_appointment(conn, id, &appointment.start_time, reminder_mins)?;
+    // Actualizar recordatorios si cambió el reminder_minutes, start_time o la regla;
+    // si no, se conservan los ya enviados
+    let moved = previous.is_none_or(|previous| {
+        previous.start_time != appointment.start_time
+            || previous.reminder_minutes != appointment.reminder_minutes
+            || previous.recurrence_rule != appointment.recurrence_rule
+    });
+    if moved {
+        delete_reminders_for_appointment(conn, id)?;
+        if let Some(reminder_mins) = appointment.reminder_minutes {
+            if reminder_mins > 0 {
+                create_reminder_for_appointment(conn, id, &appointment.start_time, reminder_mins)?;
+            }
         }
     }
 
@@ -151,54 +253,70 @@ pub fn delete_appointment(conn: &Connection, id: i64) -> Result<(), String> {
 }
 
 pub fn get_appointment(conn: &Connection, id: i64) -> Result<Appointment, String> {
+    let query = format!("SELECT {} FROM appointments a WHERE a.id = ?1", APPOINTMENT_COLUMNS);
     let mut stmt = conn
-        .prepare(
-            r#"
-        SELECT id, patient_id, title, description, start_time, end_time,
-               status, appointment_type, location, reminder_minutes, color,
-               created_by, created_at, updated_at
-        FROM appointments WHERE id = ?1
-        "#,
-        )
+        .prepare(&query)
         .map_err(|e| format!("Error al preparar query: {}", e))?;
 
-    stmt.query_row(params![id], |row| {
-        Ok(Appointment {
-            id: Some(row.get(0)?),
-            patient_id: row.get(1)?,
-            title: row.get(2)?,
-            description: row.get(3)?,
-            start_time: row.get(4)?,
-            end_time: row.get(5)?,
-            status: row.get(6)?,
-            appointment_type: row.get(7)?,
-            location: row.get(8)?,
-            reminder_minutes: row.get(9)?,
-            color: row.get(10)?,
-            created_by: row.get(11)?,
-            created_at: Some(row.get(12)?),
-            updated_at: Some(row.get(13)?),
-        })
+    stmt.query_row(params![id], appointment_from_row)
+        .map_err(|e| format!("Error al obtener cita: {}", e))
+}
+
+fn appointment_from_row(row: &rusqlite::Row) -> Result<Appointment> {
+    Ok(Appointment {
+        id: Some(row.get(0)?),
+        patient_id: row.get(1)?,
+        title: row.get(2)?,
+        description: row.get(3)?,
+        start_time: row.get(4)?,
+        end_time: row.get(5)?,
+        status: row.get(6)?,
+        appointment_type: row.get(7)?,
+        location: row.get(8)?,
+        reminder_minutes: row.get(9)?,
+        color: row.get(10)?,
+        created_by: row.get(11)?,
+        created_at: Some(row.get(12)?),
+        updated_at: Some(row.get(13)?),
+        recurrence_rule: row.get(14)?,
+        series_id: row.get(15)?,
+        original_start_time: row.get(16)?,
+        practitioner_id: row.get(17)?,
+        chair_id: row.get(18)?,
     })
-    .map_err(|e| format!("Error al obtener cita: {}", e))
 }
 
+pub fn appointment_exists(conn: &Connection, id: i64) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT COUNT(1) FROM appointments WHERE id = ?1",
+        params![id],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+    .map_err(|e| format!("Error al verificar cita: {}", e))
+}
+
+/// Lista las citas del filtro, expandiendo las series recurrentes en sus
+/// ocurrencias dentro del rango de fechas.
+///
+/// Las ocurrencias generadas llevan el `id` de la serie, `series_id` y
+/// `original_start_time`; las excepciones (ocurrencias modificadas o canceladas)
+/// se devuelven como citas propias en lugar de la ocurrencia que reemplazan.
 pub fn list_appointments(
     conn: &Connection,
     filter: &AppointmentFilter,
 ) -> Result<Vec<AppointmentWithPatient>, String> {
-    let mut query = String::from(
+    // Citas simples y excepciones de series
+    let mut query = format!(
         r#"
-        SELECT 
-            a.id, a.patient_id, a.title, a.description, a.start_time, a.end_time,
-            a.status, a.appointment_type, a.location, a.reminder_minutes, a.color,
-            a.created_by, a.created_at, a.updated_at,
+        SELECT {},
             p.first_name || ' ' || p.last_name as patient_name,
             p.phone
         FROM appointments a
         INNER JOIN patients p ON a.patient_id = p.id
-        WHERE 1=1
+        WHERE a.recurrence_rule IS NULL
         "#,
+        APPOINTMENT_COLUMNS
     );
 
     let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
@@ -223,10 +341,140 @@ pub fn list_appointments(
         params.push(Box::new(status.clone()));
     }
 
-    query.push_str(" ORDER BY a.start_time ASC");
+    let mut appointments = query_with_patient(conn, &query, &params)?;
+
+    // Series: el rango de fechas se aplica a cada ocurrencia
+    let from = filter.start_date.as_deref().map(parse_filter_date).transpose()?;
+    let to = filter.end_date.as_deref().map(parse_filter_date).transpose()?;
+
+    let mut query = format!(
+        r#"
+        SELECT {},
+            p.first_name || ' ' || p.last_name as patient_name,
+            p.phone
+        FROM appointments a
+        INNER JOIN patients p ON a.patient_id = p.id
+        WHERE a.recurrence_rule IS NOT NULL
+        "#,
+        APPOINTMENT_COLUMNS
+    );
+
+    let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+    if let Some(patient_id) = filter.patient_id {
+        query.push_str(" AND a.patient_id = ?");
+        params.push(Box::new(patient_id));
+    }
+
+    if let Some(ref status) = filter.status {
+        query.push_str(" AND a.status = ?");
+        params.push(Box::new(status.clone()));
+    }
+
+    for series in query_with_patient(conn, &query, &params)? {
+        appointments.extend(expand_series(conn, &series, from, to)?);
+    }
+
+    appointments.sort_by_cached_key(|a| parse_time(&a.appointment.start_time).ok());
+
+    Ok(appointments)
+}
+
+/// Citas y ocurrencias de series que ocupan parte de [from, to), sin las
+/// canceladas o ausentes. A diferencia de `list_appointments`, incluye las que
+/// empezaron antes de `from` y siguen en curso, por largas que sean.
+pub fn list_busy(
+    conn: &Connection,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<AppointmentWithPatient>, String> {
+    let from_time = format_time(from.fixed_offset());
+    let to_time = format_time(to.fixed_offset());
 
+    let query = format!(
+        r#"
+        SELECT {},
+            p.first_name || ' ' || p.last_name as patient_name,
+            p.phone
+        FROM appointments a
+        INNER JOIN patients p ON a.patient_id = p.id
+        WHERE a.recurrence_rule IS NULL AND a.start_time < ? AND a.end_time > ?
+        "#,
+        APPOINTMENT_COLUMNS
+    );
+    let params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(to_time), Box::new(from_time)];
+    let mut busy = query_with_patient(conn, &query, &params)?;
+
+    let query = format!(
+        r#"
+        SELECT {},
+            p.first_name || ' ' || p.last_name as patient_name,
+            p.phone
+        FROM appointments a
+        INNER JOIN patients p ON a.patient_id = p.id
+        WHERE a.recurrence_rule IS NOT NULL
+        "#,
+        APPOINTMENT_COLUMNS
+    );
+    for series in query_with_patient(conn, &query, &[])? {
+        // Las ocurrencias que empiezan hasta una duración antes de `from` siguen en curso
+        let duration =
+            parse_time(&series.appointment.end_time)? - parse_time(&series.appointment.start_time)?;
+        for occurrence in expand_series(conn, &series, Some(from - duration), Some(to))? {
+            if parse_time(&occurrence.appointment.start_time)? < to
+                && parse_time(&occurrence.appointment.end_time)? > from
+            {
+                busy.push(occurrence);
+            }
+        }
+    }
+
+    busy.retain(|a| !FREE_STATUSES.contains(&a.appointment.status.as_str()));
+    busy.sort_by_cached_key(|a| parse_time(&a.appointment.start_time).ok());
+
+    Ok(busy)
+}
+
+/// Ocurrencias de una serie que empiezan entre `from` y `to`, sin las que
+/// reemplaza una excepción
+fn expand_series(
+    conn: &Connection,
+    series: &AppointmentWithPatient,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<Vec<AppointmentWithPatient>, String> {
+    let exceptions = list_exceptions(conn, series.appointment.id.unwrap_or_default())?
+        .iter()
+        .filter_map(|exception| exception.original_start_time.as_deref())
+        .map(parse_time)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut occurrences = Vec::new();
+    for occurrence in series_rule(&series.appointment)?.occurrences(
+        parse_time(&series.appointment.start_time)?,
+        from,
+        to,
+    ) {
+        if exceptions.contains(&occurrence) {
+            continue;
+        }
+        occurrences.push(AppointmentWithPatient {
+            appointment: occurrence_of(&series.appointment, occurrence)?,
+            patient_name: series.patient_name.clone(),
+            patient_phone: series.patient_phone.clone(),
+        });
+    }
+
+    Ok(occurrences)
+}
+
+fn query_with_patient(
+    conn: &Connection,
+    query: &str,
+    params: &[Box<dyn rusqlite::ToSql>],
+) -> Result<Vec<AppointmentWithPatient>, String> {
     let mut stmt = conn
-        .prepare(&query)
+        .prepare(query)
         .map_err(|e| format!("Error al preparar query: {}", e))?;
 
     let param_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
@@ -234,24 +482,9 @@ pub fn list_appointments(
     let appointments = stmt
         .query_map(&param_refs[..], |row| {
             Ok(AppointmentWithPatient {
-                appointment: Appointment {
-                    id: Some(row.get(0)?),
-                    patient_id: row.get(1)?,
-                    title: row.get(2)?,
-                    description: row.get(3)?,
-                    start_time: row.get(4)?,
-                    end_time: row.get(5)?,
-                    status: row.get(6)?,
-                    appointment_type: row.get(7)?,
-                    location: row.get(8)?,
-                    reminder_minutes: row.get(9)?,
-                    color: row.get(10)?,
-                    created_by: row.get(11)?,
-                    created_at: Some(row.get(12)?),
-                    updated_at: Some(row.get(13)?),
-                },
-                patient_name: row.get(14)?,
-                patient_phone: row.get(15)?,
+                appointment: appointment_from_row(row)?,
+                patient_name: row.get(19)?,
+                patient_phone: row.get(20)?,
             })
         })
         .map_err(|e| format!("Error al ejecutar query: {}", e))?
@@ -261,32 +494,427 @@ pub fn list_appointments(
     Ok(appointments)
 }
 
+// Detección de conflictos
+
+/// Días desde el inicio de una serie durante los que se buscan conflictos; las
+/// series sin fin se revisarían sin límite
+const CONFLICT_HORIZON_DAYS: i64 = 365;
+
+/// Estados de las citas que no ocupan la agenda
+pub const FREE_STATUSES: [&str; 2] = ["cancelled", "no_show"];
+
+/// Busca las citas que se superponen con `appointment` en el mismo sillón o con
+/// el mismo profesional.
+///
+/// Si la cita es recurrente se revisan sus ocurrencias del primer año. Se ignoran
+/// las citas canceladas o ausentes y las citas (u ocurrencias de series) cuyo `id`
+/// esté en `ignore`, que es como se excluye la propia cita al modificarla.
+pub fn find_conflicts(
+    conn: &Connection,
+    appointment: &Appointment,
+    ignore: &[i64],
+) -> Result<Vec<AppointmentConflict>, String> {
+    if (appointment.practitioner_id.is_none() && appointment.chair_id.is_none())
+        || FREE_STATUSES.contains(&appointment.status.as_str())
+    {
+        return Ok(Vec::new());
+    }
+
+    let start = parse_time(&appointment.start_time)?;
+    let duration = parse_time(&appointment.end_time)? - start;
+    let starts = match appointment.recurrence_rule {
+        Some(_) => {
+            let horizon = start.with_timezone(&Utc) + chrono::Duration::days(CONFLICT_HORIZON_DAYS);
+            series_rule(appointment)?.occurrences(start, None, Some(horizon))
+        }
+        None => vec![start],
+    };
+    let (Some(first), Some(last)) = (starts.first(), starts.last()) else {
+        return Ok(Vec::new());
+    };
+
+    let mut conflicts = Vec::new();
+    let from = first.with_timezone(&Utc);
+    let to = (*last + duration).with_timezone(&Utc);
+    for existing in list_busy(conn, from, to)? {
+        let other = &existing.appointment;
+        if other.id.is_some_and(|id| ignore.contains(&id))
+            || other.series_id.is_some_and(|id| ignore.contains(&id))
+        {
+            continue;
+        }
+
+        let mut reasons = Vec::new();
+        if appointment.chair_id.is_some() && other.chair_id == appointment.chair_id {
+            reasons.push(ConflictReason::Chair);
+        }
+        if appointment.practitioner_id.is_some()
+            && other.practitioner_id == appointment.practitioner_id
+        {
+            reasons.push(ConflictReason::Practitioner);
+        }
+        if reasons.is_empty() {
+            continue;
+        }
+
+        let other_start = parse_time(&other.start_time)?;
+        let other_end = parse_time(&other.end_time)?;
+        for start in starts
+            .iter()
+            .filter(|&&start| start < other_end && other_start < start + duration)
+        {
+            conflicts.push(AppointmentConflict {
+                start_time: format_time(*start),
+                reasons: reasons.clone(),
+                appointment: existing.clone(),
+            });
+        }
+    }
+
+    Ok(conflicts)
+}
+
+// Funciones de series recurrentes
+
+/// Obtiene una ocurrencia de una serie: su excepción si fue modificada, o la
+/// ocurrencia generada por la regla.
+pub fn get_occurrence(
+    conn: &Connection,
+    series_id: i64,
+    original_start_time: &str,
+) -> Result<Appointment, String> {
+    let original = parse_time(original_start_time)?;
+
+    if let Some(exception) = find_exception(conn, series_id, original)? {
+        return Ok(exception);
+    }
+
+    let series = get_appointment(conn, series_id)?;
+    if !series_rule(&series)?.is_occurrence(parse_time(&series.start_time)?, original) {
+        return Err(format!(
+            "La serie {} no tiene una ocurrencia que empiece el {}",
+            series_id, original_start_time
+        ));
+    }
+
+    occurrence_of(&series, original)
+}
+
+/// Modifica una ocurrencia de una serie, o también las siguientes o toda la serie.
+///
+/// - `Occurrence`: los datos se guardan como excepción de esa ocurrencia.
+/// - `Series`: se aplican a la serie; el corrimiento de horario de la ocurrencia
+///   se aplica a todas.
+/// - `Following`: la serie termina antes de la ocurrencia y desde ella empieza
+///   una serie nueva con los datos recibidos (y lo que resta de COUNT).
+///
+/// Las excepciones posteriores siguen a la serie y se descartan las que dejan
+/// de coincidir con su regla.
+///
+/// Retorna el id de la cita que guarda el cambio: la excepción, la serie o la
+/// serie nueva.
+pub fn update_occurrence(
+    conn: &Connection,
+    series_id: i64,
+    original_start_time: &str,
+    appointment: &Appointment,
+    scope: RecurrenceScope,
+) -> Result<i64, String> {
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Error al iniciar transacción: {}", e))?;
+
+    let series = get_appointment(&tx, series_id)?;
+    let rule = series_rule(&series)?;
+    let occurrence = get_occurrence(&tx, series_id, original_start_time)?;
+    let original = parse_time(occurrence.original_start_time.as_deref().unwrap_or_default())?;
+    let series_start = parse_time(&series.start_time)?;
+    let before = rule.count_before(series_start, original);
+
+    // Una excepción editada junto con la serie vuelve a seguir a la serie
+    let edited_exception = occurrence.id.filter(|id| *id != series_id);
+
+    let id = match scope {
+        RecurrenceScope::Occurrence => {
+            let mut exception = appointment.clone();
+            exception.recurrence_rule = None;
+            exception.series_id = Some(series_id);
+            exception.original_start_time = occurrence.original_start_time.clone();
+
+            match edited_exception {
+                Some(id) => {
+                    exception.id = Some(id);
+                    update_appointment(&tx, &exception)?;
+                    id
+                }
+                None => create_appointment(&tx, &exception)?,
+            }
+        }
+        RecurrenceScope::Following if before > 0 => {
+            if let Some(id) = edited_exception {
+                delete_appointment(&tx, id)?;
+            }
+            let delta = parse_time(&appointment.start_time)? - parse_time(&occurrence.start_time)?;
+
+            // La serie original termina antes de esta ocurrencia
+            let mut truncated = rule.clone();
+            truncated.count = None;
+            truncated.until = Some(original.with_timezone(&Utc) - chrono::Duration::seconds(1));
+            tx.execute(
+                "UPDATE appointments SET recurrence_rule = ?1, updated_at = ?2 WHERE id = ?3",
+                params![truncated.to_rule_string(), Utc::now().to_rfc3339(), series_id],
+            )
+            .map_err(|e| format!("Error al actualizar serie: {}", e))?;
+
+            // Si la regla no cambió, la serie nueva conserva lo que resta de COUNT
+            let mut next = appointment.clone();
+            let requested_rule = appointment
+                .recurrence_rule
+                .as_deref()
+                .map(RecurrenceRule::parse)
+                .transpose()?;
+            let next_rule = match requested_rule {
+                Some(requested) if requested != rule => requested,
+                _ => {
+                    let mut remaining = rule.clone();
+                    remaining.count = rule
+                        .count
+                        .map(|count| count.saturating_sub(before as u32).max(1));
+                    remaining
+                }
+            };
+            next.id = None;
+            next.recurrence_rule = Some(next_rule.to_rule_string());
+            next.series_id = None;
+            next.original_start_time = None;
+            next.created_by = series.created_by;
+            let next_id = create_appointment(&tx, &next)?;
+
+            for exception in list_exceptions(&tx, series_id)? {
+                let exception_start =
+                    parse_time(exception.original_start_time.as_deref().unwrap_or_default())?;
+                if exception_start >= original {
+                    move_exception(&tx, &exception, next_id, exception_start + delta)?;
+                }
+            }
+            prune_exceptions(&tx, next_id)?;
+
+            next_id
+        }
+        // Desde la primera ocurrencia, "esta y las siguientes" es toda la serie
+        RecurrenceScope::Following | RecurrenceScope::Series => {
+            if let Some(id) = edited_exception {
+                delete_appointment(&tx, id)?;
+            }
+            let delta = parse_time(&appointment.start_time)? - parse_time(&occurrence.start_time)?;
+            let duration = parse_time(&appointment.end_time)? - parse_time(&appointment.start_time)?;
+            let start = series_start + delta;
+
+            let mut updated = appointment.clone();
+            updated.id = Some(series_id);
+            updated.recurrence_rule = appointment
+                .recurrence_rule
+                .clone()
+                .or(series.recurrence_rule.clone());
+            updated.series_id = None;
+            updated.original_start_time = None;
+            updated.start_time = format_time(start);
+            updated.end_time = format_time(start + duration);
+            update_appointment(&tx, &updated)?;
+
+            for exception in list_exceptions(&tx, series_id)? {
+                let exception_start =
+                    parse_time(exception.original_start_time.as_deref().unwrap_or_default())?;
+                move_exception(&tx, &exception, series_id, exception_start + delta)?;
+            }
+            prune_exceptions(&tx, series_id)?;
+
+            series_id
+        }
+    };
+
+    tx.commit()
+        .map_err(|e| format!("Error al confirmar transacción: {}", e))?;
+
+    Ok(id)
+}
+
+/// Cancela una ocurrencia de una serie, o también las siguientes o toda la serie.
+/// Las citas canceladas se conservan con estado `cancelled`.
+///
+/// Retorna el id de la cita que guarda el cambio (ver `update_occurrence`).
+pub fn cancel_occurrence(
+    conn: &Connection,
+    series_id: i64,
+    original_start_time: &str,
+    scope: RecurrenceScope,
+) -> Result<i64, String> {
+    let mut occurrence = get_occurrence(conn, series_id, original_start_time)?;
+    occurrence.status = "cancelled".to_string();
+
+    let id = update_occurrence(conn, series_id, original_start_time, &occurrence, scope)?;
+
+    // Las excepciones que siguen a la serie también se cancelan
+    if scope != RecurrenceScope::Occurrence {
+        conn.execute(
+            "UPDATE appointments SET status = 'cancelled', updated_at = ?1 WHERE series_id = ?2",
+            params![Utc::now().to_rfc3339(), id],
+        )
+        .map_err(|e| format!("Error al cancelar excepciones: {}", e))?;
+    }
+
+    Ok(id)
+}
+
+/// Excepciones (ocurrencias modificadas o canceladas) de una serie
+fn list_exceptions(conn: &Connection, series_id: i64) -> Result<Vec<Appointment>, String> {
+    let query = format!("SELECT {} FROM appointments a WHERE a.series_id = ?1", APPOINTMENT_COLUMNS);
+    let mut stmt = conn
+        .prepare(&query)
+        .map_err(|e| format!("Error al preparar query: {}", e))?;
+
+    let exceptions = stmt
+        .query_map(params![series_id], appointment_from_row)
+        .map_err(|e| format!("Error al ejecutar query: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Error al procesar resultados: {}", e))?;
+
+    Ok(exceptions)
+}
+
+fn find_exception(
+    conn: &Connection,
+    series_id: i64,
+    original: DateTime<FixedOffset>,
+) -> Result<Option<Appointment>, String> {
+    for exception in list_exceptions(conn, series_id)? {
+        let exception_start = exception.original_start_time.as_deref().map(parse_time).transpose()?;
+        if exception_start == Some(original) {
+            return Ok(Some(exception));
+        }
+    }
+    Ok(None)
+}
+
+/// Asigna una excepción a una serie y a otra ocurrencia original
+fn move_exception(
+    conn: &Connection,
+    exception: &Appointment,
+    series_id: i64,
+    original: DateTime<FixedOffset>,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE appointments SET series_id = ?1, original_start_time = ?2 WHERE id = ?3",
+        params![series_id, format_time(original), exception.id],
+    )
+    .map_err(|e| format!("Error al actualizar excepción: {}", e))?;
+    Ok(())
+}
+
+/// Borra las excepciones que ya no corresponden a una ocurrencia de la serie
+fn prune_exceptions(conn: &Connection, series_id: i64) -> Result<(), String> {
+    let series = get_appointment(conn, series_id)?;
+    let rule = series_rule(&series)?;
+    let start = parse_time(&series.start_time)?;
+
+    for exception in list_exceptions(conn, series_id)? {
+        let original = parse_time(exception.original_start_time.as_deref().unwrap_or_default())?;
+        if !rule.is_occurrence(start, original) {
+            delete_appointment(conn, exception.id.unwrap_or_default())?;
+        }
+    }
+    Ok(())
+}
+
+fn series_rule(series: &Appointment) -> Result<RecurrenceRule, String> {
+    let rule = series
+        .recurrence_rule
+        .as_deref()
+        .ok_or_else(|| format!("La cita {} no es recurrente", series.id.unwrap_or_default()))?;
+    RecurrenceRule::parse(rule)
+}
+
+/// La ocurrencia de una serie que empieza en `start`
+fn occurrence_of(series: &Appointment, start: DateTime<FixedOffset>) -> Result<Appointment, String> {
+    let duration = parse_time(&series.end_time)? - parse_time(&series.start_time)?;
+    let start_time = if start == parse_time(&series.start_time)? {
+        series.start_time.clone()
+    } else {
+        format_time(start)
+    };
+
+    let mut occurrence = series.clone();
+    occurrence.end_time = format_time(start + duration);
+    occurrence.series_id = series.id;
+    occurrence.original_start_time = Some(start_time.clone());
+    occurrence.start_time = start_time;
+    Ok(occurrence)
+}
+
+fn parse_time(value: &str) -> Result<DateTime<FixedOffset>, String> {
+    DateTime::parse_from_rfc3339(value).map_err(|e| format!("Error al parsear fecha: {}", e))
+}
+
+fn format_time(value: DateTime<FixedOffset>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Fecha de un filtro: RFC 3339 o solo fecha (desde el inicio del día en UTC)
+pub fn parse_filter_date(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Utc));
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date| date.and_utc())
+        .ok_or_else(|| format!("Fecha de filtro inválida: '{}'", value))
+}
+
 // Funciones de recordatorios
 
+/// Envíos fallidos tras los que un recordatorio deja de reintentarse
+pub const MAX_REMINDER_ATTEMPTS: i32 = 5;
+
+const REMINDER_COLUMNS: &str = r#"
+    id, appointment_id, scheduled_time, sent, sent_at, notification_id, created_at,
+    occurrence_start_time, attempts, next_attempt_at, last_error
+"#;
+
+/// Crea el recordatorio de la cita u ocurrencia que empieza en `start_time`, salvo
+/// que ya exista. Retorna si se creó
 fn create_reminder_for_appointment(
     conn: &Connection,
     appointment_id: i64,
     start_time: &str,
     reminder_minutes: i32,
-) -> Result<(), String> {
+) -> Result<bool, String> {
     // Calcular el momento del recordatorio
-    let start = chrono::DateTime::parse_from_rfc3339(start_time)
-        .map_err(|e| format!("Error al parsear fecha: {}", e))?;
+    let start = parse_time(start_time)?;
     let reminder_time = start - chrono::Duration::minutes(reminder_minutes as i64);
-    let scheduled_time = reminder_time.to_rfc3339();
+    let scheduled_time = reminder_time
+        .with_timezone(&Utc)
+        .to_rfc3339_opts(SecondsFormat::Millis, true);
     let now = chrono::Utc::now().to_rfc3339();
 
-    conn.execute(
-        r#"
+    let created = conn
+        .execute(
+            r#"
         INSERT INTO appointment_reminders (
-            appointment_id, scheduled_time, sent, created_at
-        ) VALUES (?1, ?2, 0, ?3)
+            appointment_id, scheduled_time, sent, created_at, occurrence_start_time
+        )
+        SELECT ?1, ?2, 0, ?3, ?4
+        WHERE NOT EXISTS (
+            SELECT 1 FROM appointment_reminders
+            WHERE appointment_id = ?1 AND occurrence_start_time = ?4
+        )
         "#,
-        params![appointment_id, scheduled_time, now],
-    )
-    .map_err(|e| format!("Error al crear recordatorio: {}", e))?;
+            params![appointment_id, scheduled_time, now, start_time],
+        )
+        .map_err(|e| format!("Error al crear recordatorio: {}", e))?;
 
-    Ok(())
+    Ok(created > 0)
 }
 
 fn delete_reminders_for_appointment(conn: &Connection, appointment_id: i64) -> Result<(), String> {
@@ -298,32 +926,60 @@ fn delete_reminders_for_appointment(conn: &Connection, appointment_id: i64) -> R
     Ok(())
 }
 
+/// Crea los recordatorios de las ocurrencias de series que empiezan entre `from`
+/// y `to`; las citas simples y las excepciones los reciben al guardarse.
+///
+/// Retorna cuántos recordatorios se crearon.
+pub fn schedule_series_reminders(
+    conn: &Connection,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<usize, String> {
+    let filter = AppointmentFilter {
+        start_date: Some(from.to_rfc3339_opts(SecondsFormat::Millis, true)),
+        end_date: Some(to.to_rfc3339_opts(SecondsFormat::Millis, true)),
+        patient_id: None,
+        status: None,
+    };
+
+    let mut created = 0;
+    for item in list_appointments(conn, &filter)? {
+        let occurrence = item.appointment;
+        let generated = occurrence.series_id.is_some() && occurrence.series_id == occurrence.id;
+        if !generated || FREE_STATUSES.contains(&occurrence.status.as_str()) {
+            continue;
+        }
+        if let (Some(id), Some(minutes)) = (occurrence.id, occurrence.reminder_minutes) {
+            if minutes > 0
+                && create_reminder_for_appointment(conn, id, &occurrence.start_time, minutes)?
+            {
+                created += 1;
+            }
+        }
+    }
+
+    Ok(created)
+}
+
 pub fn get_pending_reminders(conn: &Connection) -> Result<Vec<AppointmentReminder>, String> {
-    let now = chrono::Utc::now().to_rfc3339();
+    let now = chrono::Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
 
-    let mut stmt = conn
-        .prepare(
-            r#"
-        SELECT id, appointment_id, scheduled_time, sent, sent_at, notification_id, created_at
+    let query = format!(
+        r#"
+        SELECT {}
         FROM appointment_reminders
-        WHERE sent = 0 AND scheduled_time <= ?1
+        WHERE sent = 0 AND scheduled_time <= ?1 AND attempts < ?2
+            AND (next_attempt_at IS NULL OR next_attempt_at <= ?1)
         ORDER BY scheduled_time ASC
         "#,
-        )
+        REMINDER_COLUMNS
+    );
+    let mut stmt = conn
+        .prepare(&query)
         .map_err(|e| format!("Error al preparar query: {}", e))?;
 
     let reminders = stmt
-        .query_map(params![now], |row| {
-            Ok(AppointmentReminder {
-                id: Some(row.get(0)?),
-                appointment_id: row.get(1)?,
-                scheduled_time: row.get(2)?,
-                sent: row.get(3)?,
-                sent_at: row.get(4)?,
-                notification_id: row.get(5)?,
-                created_at: Some(row.get(6)?),
-            })
-        })
+        .query_map(params![now, MAX_REMINDER_ATTEMPTS], reminder_from_row)
         .map_err(|e| format!("Error al ejecutar query: {}", e))?
         .collect::<Result<Vec<_>, _>>()
         .map_err(|e| format!("Error al procesar resultados: {}", e))?;
@@ -331,6 +987,93 @@ pub fn get_pending_reminders(conn: &Connection) -> Result<Vec<AppointmentReminde
     Ok(reminders)
 }
 
+pub fn get_reminder(
+    conn: &Connection,
+    reminder_id: i64,
+) -> Result<Option<AppointmentReminder>, String> {
+    let query = format!("SELECT {} FROM appointment_reminders WHERE id = ?1", REMINDER_COLUMNS);
+
+    match conn.query_row(&query, params![reminder_id], reminder_from_row) {
+        Ok(reminder) => Ok(Some(reminder)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Error al obtener recordatorio: {}", e)),
+    }
+}
+
+fn reminder_from_row(row: &rusqlite::Row) -> Result<AppointmentReminder> {
+    Ok(AppointmentReminder {
+        id: Some(row.get(0)?),
+        appointment_id: row.get(1)?,
+        scheduled_time: row.get(2)?,
+        sent: row.get(3)?,
+        sent_at: row.get(4)?,
+        notification_id: row.get(5)?,
+        created_at: Some(row.get(6)?),
+        occurrence_start_time: row.get(7)?,
+        attempts: row.get(8)?,
+        next_attempt_at: row.get(9)?,
+        last_error: row.get(10)?,
+    })
+}
+
+/// La cita (u ocurrencia) que anuncia el recordatorio, con su paciente.
+///
+/// Retorna `None` si el recordatorio o la cita ya no existen, si la cita se movió
+/// de la hora que anuncia, o si fue cancelada o marcada como ausente.
+pub fn get_reminder_notification(
+    conn: &Connection,
+    reminder_id: i64,
+) -> Result<Option<ReminderNotification>, String> {
+    let Some(reminder) = get_reminder(conn, reminder_id)? else {
+        return Ok(None);
+    };
+
+    let query = format!(
+        r#"
+        SELECT {},
+            p.first_name || ' ' || p.last_name as patient_name,
+            p.phone
+        FROM appointments a
+        INNER JOIN patients p ON a.patient_id = p.id
+        WHERE a.id = ?
+        "#,
+        APPOINTMENT_COLUMNS
+    );
+    let params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(reminder.appointment_id)];
+    let Some(mut item) = query_with_patient(conn, &query, &params)?.pop() else {
+        return Ok(None);
+    };
+
+    let announced = match reminder.occurrence_start_time.as_deref() {
+        Some(start) => parse_time(start)?,
+        None => parse_time(&item.appointment.start_time)?,
+    };
+
+    // Las ocurrencias de una serie se resuelven contra su regla y excepciones
+    if item.appointment.recurrence_rule.is_some() {
+        let occurrence =
+            match get_occurrence(conn, reminder.appointment_id, &format_time(announced)) {
+                Ok(occurrence) => occurrence,
+                Err(_) => return Ok(None),
+            };
+        if occurrence.id != Some(reminder.appointment_id) {
+            return Ok(None);
+        }
+        item.appointment = occurrence;
+    }
+
+    if parse_time(&item.appointment.start_time)? != announced
+        || FREE_STATUSES.contains(&item.appointment.status.as_str())
+    {
+        return Ok(None);
+    }
+
+    Ok(Some(ReminderNotification {
+        reminder,
+        appointment: item,
+    }))
+}
+
 pub fn mark_reminder_sent(
     conn: &Connection,
     reminder_id: i64,
@@ -347,6 +1090,60 @@ pub fn mark_reminder_sent(
     Ok(())
 }
 
+/// Guarda el identificador del aviso enviado antes de terminar el envío, para que un
+/// reintento no vuelva a enviarlo
+pub fn set_reminder_notification_id(
+    conn: &Connection,
+    reminder_id: i64,
+    notification_id: &str,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE appointment_reminders SET notification_id = ?1 WHERE id = ?2",
+        params![notification_id, reminder_id],
+    )
+    .map_err(|e| format!("Error al guardar aviso de recordatorio: {}", e))?;
+
+    Ok(())
+}
+
+/// Registra un envío fallido y cuándo reintentarlo
+pub fn record_reminder_failure(
+    conn: &Connection,
+    reminder_id: i64,
+    error: &str,
+    next_attempt_at: DateTime<Utc>,
+) -> Result<(), String> {
+    conn.execute(
+        r#"
+        UPDATE appointment_reminders
+        SET attempts = attempts + 1, last_error = ?1, next_attempt_at = ?2
+        WHERE id = ?3
+        "#,
+        params![
+            error,
+            next_attempt_at.to_rfc3339_opts(SecondsFormat::Millis, true),
+            reminder_id
+        ],
+    )
+    .map_err(|e| format!("Error al registrar fallo de recordatorio: {}", e))?;
+
+    Ok(())
+}
+
+/// Descarta un recordatorio que ya no corresponde enviar (cita movida, cancelada o
+/// ya comenzada), dejando el motivo
+pub fn discard_reminder(conn: &Connection, reminder_id: i64, reason: &str) -> Result<(), String> {
+    let now = chrono::Utc::now().to_rfc3339();
+
+    conn.execute(
+        "UPDATE appointment_reminders SET sent = 1, sent_at = ?1, last_error = ?2 WHERE id = ?3",
+        params![now, reason, reminder_id],
+    )
+    .map_err(|e| format!("Error al descartar recordatorio: {}", e))?;
+
+    Ok(())
+}
+
 pub fn get_upcoming_appointments(
     conn: &Connection,
     hours: i32,
@@ -363,3 +1160,344 @@ pub fn get_upcoming_appointments(
 
     list_appointments(conn, &filter)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::get_connection;
+
+    fn appointments_of(conn: &Connection, patient_id: i64) -> Vec<Appointment> {
+        let filter = AppointmentFilter {
+            start_date: Some("2025-01-01T00:00:00.000Z".to_string()),
+            end_date: Some("2025-03-01T00:00:00.000Z".to_string()),
+            patient_id: Some(patient_id),
+            status: None,
+        };
+        list_appointments(conn, &filter)
+            .expect("list")
+            .into_iter()
+            .map(|a| a.appointment)
+            .collect()
+    }
+
+    #[test]
+    fn recurring_series_with_exceptions() {
+        let conn = get_connection().expect("db");
+        let now = chrono::Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO patients (first_name, last_name, created_at, updated_at) VALUES ('Serie', 'Recurrente', ?1, ?1)",
+            params![now],
+        )
+        .unwrap();
+        let patient_id = conn.last_insert_rowid();
+
+        let series_id = create_appointment(
+            &conn,
+            &Appointment {
+                id: None,
+                patient_id,
+                title: "Control de ortodoncia".to_string(),
+                description: None,
+                start_time: "2025-01-06T13:00:00.000Z".to_string(),
+                end_time: "2025-01-06T13:30:00.000Z".to_string(),
+                status: "scheduled".to_string(),
+                appointment_type: None,
+                location: None,
+                reminder_minutes: None,
+                color: None,
+                created_by: None,
+                created_at: None,
+                updated_at: None,
+                recurrence_rule: Some("FREQ=WEEKLY;COUNT=4".to_string()),
+                series_id: None,
+                original_start_time: None,
+                practitioner_id: None,
+                chair_id: None,
+            },
+        )
+        .unwrap();
+
+        let occurrences = appointments_of(&conn, patient_id);
+        assert_eq!(occurrences.len(), 4);
+        assert!(occurrences.iter().all(|o| o.series_id == Some(series_id)));
+
+        // Una sola ocurrencia se mueve una hora
+        let mut moved = occurrences[1].clone();
+        moved.start_time = "2025-01-13T14:00:00.000Z".to_string();
+        moved.end_time = "2025-01-13T14:30:00.000Z".to_string();
+        let original = occurrences[1].original_start_time.clone().unwrap();
+        let exception_id =
+            update_occurrence(&conn, series_id, &original, &moved, RecurrenceScope::Occurrence)
+                .unwrap();
+        assert_ne!(exception_id, series_id);
+
+        // La tercera y las siguientes pasan a una serie nueva
+        let mut renamed = occurrences[2].clone();
+        renamed.title = "Retiro de brackets".to_string();
+        let original = occurrences[2].original_start_time.clone().unwrap();
+        let next_id =
+            update_occurrence(&conn, series_id, &original, &renamed, RecurrenceScope::Following)
+                .unwrap();
+        assert_eq!(
+            get_appointment(&conn, next_id).unwrap().recurrence_rule.as_deref(),
+            Some("FREQ=WEEKLY;COUNT=2")
+        );
+
+        let occurrences = appointments_of(&conn, patient_id);
+        assert_eq!(occurrences.len(), 4);
+        assert_eq!(occurrences[1].id, Some(exception_id));
+        assert_eq!(occurrences[1].start_time, "2025-01-13T14:00:00.000Z");
+        assert_eq!(occurrences[2].title, "Retiro de brackets");
+        assert_eq!(occurrences[3].series_id, Some(next_id));
+
+        // Cancelar la primera serie también cancela su excepción
+        let original = occurrences[0].original_start_time.clone().unwrap();
+        cancel_occurrence(&conn, series_id, &original, RecurrenceScope::Series).unwrap();
+        let statuses: Vec<String> = appointments_of(&conn, patient_id)
+            .into_iter()
+            .map(|o| o.status)
+            .collect();
+        assert_eq!(statuses, ["cancelled", "cancelled", "scheduled", "scheduled"]);
+
+        conn.execute("DELETE FROM patients WHERE id = ?1", params![patient_id])
+            .unwrap();
+    }
+
+    #[test]
+    fn conflicts_by_chair_and_practitioner() {
+        let conn = get_connection().expect("db");
+        let now = chrono::Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO patients (first_name, last_name, created_at, updated_at) VALUES ('Sobre', 'Turno', ?1, ?1)",
+            params![now],
+        )
+        .unwrap();
+        let patient_id = conn.last_insert_rowid();
+        let mut resources = Vec::new();
+        for (kind, name) in [
+            ("practitioner", "Dra. A"),
+            ("practitioner", "Dr. B"),
+            ("chair", "Sillón 1"),
+            ("chair", "Sillón 2"),
+        ] {
+            conn.execute(
+                "INSERT INTO agenda_resources (kind, name, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)",
+                params![kind, name, now],
+            )
+            .unwrap();
+            resources.push(conn.last_insert_rowid());
+        }
+        let (dra_a, dr_b, chair_1, chair_2) =
+            (resources[0], resources[1], resources[2], resources[3]);
+
+        let appointment =
+            |start: &str, end: &str, chair_id: i64, practitioner_id: i64| Appointment {
+                id: None,
+                patient_id,
+                title: "Limpieza".to_string(),
+                description: None,
+                start_time: start.to_string(),
+                end_time: end.to_string(),
+                status: "scheduled".to_string(),
+                appointment_type: None,
+                location: None,
+                reminder_minutes: None,
+                color: None,
+                created_by: None,
+                created_at: None,
+                updated_at: None,
+                recurrence_rule: None,
+                series_id: None,
+                original_start_time: None,
+                practitioner_id: Some(practitioner_id),
+                chair_id: Some(chair_id),
+            };
+
+        let existing = create_appointment(
+            &conn,
+            &appointment("2031-05-05T13:00:00.000Z", "2031-05-05T14:00:00.000Z", chair_1, dra_a),
+        )
+        .unwrap();
+
+        // Mismo sillón y otro profesional
+        let conflicts = find_conflicts(
+            &conn,
+            &appointment("2031-05-05T13:30:00.000Z", "2031-05-05T14:30:00.000Z", chair_1, dr_b),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].appointment.appointment.id, Some(existing));
+        assert_eq!(conflicts[0].reasons, [ConflictReason::Chair]);
+
+        // Solo cuentan los recursos, no el texto de la ubicación
+        let mut elsewhere =
+            appointment("2031-05-05T13:30:00.000Z", "2031-05-05T14:30:00.000Z", chair_2, dr_b);
+        elsewhere.location = Some("Sillón 1".to_string());
+        assert!(find_conflicts(&conn, &elsewhere, &[]).unwrap().is_empty());
+
+        // Citas contiguas no chocan
+        let adjacent =
+            appointment("2031-05-05T14:00:00.000Z", "2031-05-05T14:30:00.000Z", chair_1, dra_a);
+        assert!(find_conflicts(&conn, &adjacent, &[]).unwrap().is_empty());
+
+        // Una serie semanal choca en su segunda ocurrencia por el profesional
+        let mut series =
+            appointment("2031-04-28T13:15:00.000Z", "2031-04-28T13:45:00.000Z", chair_2, dra_a);
+        series.recurrence_rule = Some("FREQ=WEEKLY;COUNT=3".to_string());
+        let conflicts = find_conflicts(&conn, &series, &[]).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].start_time, "2031-05-05T13:15:00.000Z");
+        assert_eq!(conflicts[0].reasons, [ConflictReason::Practitioner]);
+
+        // Una serie sin fin solo se revisa durante su primer año
+        let mut endless = series.clone();
+        endless.recurrence_rule = Some("FREQ=WEEKLY".to_string());
+        endless.start_time = "2030-05-13T13:15:00.000Z".to_string();
+        endless.end_time = "2030-05-13T13:45:00.000Z".to_string();
+        assert_eq!(find_conflicts(&conn, &endless, &[]).unwrap().len(), 1);
+        endless.start_time = "2029-05-07T13:15:00.000Z".to_string();
+        endless.end_time = "2029-05-07T13:45:00.000Z".to_string();
+        assert!(find_conflicts(&conn, &endless, &[]).unwrap().is_empty());
+
+        // Una cita de varios días ocupa el sillón hasta que termina
+        create_appointment(
+            &conn,
+            &appointment("2031-05-20T08:00:00.000Z", "2031-05-22T18:00:00.000Z", chair_2, dr_b),
+        )
+        .unwrap();
+        let late =
+            appointment("2031-05-22T10:00:00.000Z", "2031-05-22T10:30:00.000Z", chair_2, dra_a);
+        assert_eq!(find_conflicts(&conn, &late, &[]).unwrap().len(), 1);
+
+        // La propia cita y las canceladas no cuentan
+        let mut moved =
+            appointment("2031-05-05T13:30:00.000Z", "2031-05-05T14:30:00.000Z", chair_1, dra_a);
+        assert!(find_conflicts(&conn, &moved, &[existing]).unwrap().is_empty());
+        moved.id = Some(existing);
+        moved.status = "cancelled".to_string();
+        update_appointment(&conn, &moved).unwrap();
+        assert!(find_conflicts(&conn, &series, &[]).unwrap().is_empty());
+
+        conn.execute("DELETE FROM patients WHERE id = ?1", params![patient_id])
+            .unwrap();
+        conn.execute(
+            "DELETE FROM agenda_resources WHERE id IN (?1, ?2, ?3, ?4)",
+            params![dra_a, dr_b, chair_1, chair_2],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn reminders_follow_moves_and_series() {
+        let conn = get_connection().expect("db");
+        let now = Utc::now();
+        conn.execute(
+            "INSERT INTO patients (first_name, last_name, created_at, updated_at) VALUES ('Aviso', 'Pendiente', ?1, ?1)",
+            params![now.to_rfc3339()],
+        )
+        .unwrap();
+        let patient_id = conn.last_insert_rowid();
+
+        let at = |minutes: i64| {
+            (now + chrono::Duration::minutes(minutes)).to_rfc3339_opts(SecondsFormat::Millis, true)
+        };
+        let pending_of = |conn: &Connection, id: i64| -> Vec<AppointmentReminder> {
+            get_pending_reminders(conn)
+                .unwrap()
+                .into_iter()
+                .filter(|r| r.appointment_id == id)
+                .collect()
+        };
+
+        // Empieza en 10 minutos con aviso 30 minutos antes: ya está vencido
+        let mut appointment = Appointment {
+            id: None,
+            patient_id,
+            title: "Control".to_string(),
+            description: None,
+            start_time: at(10),
+            end_time: at(40),
+            status: "scheduled".to_string(),
+            appointment_type: None,
+            location: None,
+            reminder_minutes: Some(30),
+            color: None,
+            created_by: None,
+            created_at: None,
+            updated_at: None,
+            recurrence_rule: None,
+            series_id: None,
+            original_start_time: None,
+            practitioner_id: None,
+            chair_id: None,
+        };
+        let id = create_appointment(&conn, &appointment).unwrap();
+        appointment.id = Some(id);
+        let pending = pending_of(&conn, id);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].scheduled_time, at(-20));
+        let reminder_id = pending[0].id.unwrap();
+        assert!(get_reminder_notification(&conn, reminder_id).unwrap().is_some());
+
+        // Un fallo lo posterga hasta el próximo intento
+        record_reminder_failure(&conn, reminder_id, "sin red", now + chrono::Duration::minutes(1))
+            .unwrap();
+        assert!(pending_of(&conn, id).is_empty());
+
+        // Editar sin mover la cita conserva el recordatorio enviado
+        mark_reminder_sent(&conn, reminder_id, "n-1").unwrap();
+        appointment.title = "Control anual".to_string();
+        update_appointment(&conn, &appointment).unwrap();
+        assert!(get_reminder(&conn, reminder_id).unwrap().unwrap().sent);
+
+        // Moverla lo reprograma
+        appointment.start_time = at(20);
+        appointment.end_time = at(50);
+        update_appointment(&conn, &appointment).unwrap();
+        assert!(get_reminder(&conn, reminder_id).unwrap().is_none());
+        assert_eq!(pending_of(&conn, id)[0].scheduled_time, at(-10));
+
+        // Las ocurrencias de una serie reciben su recordatorio una sola vez
+        let series_id = create_appointment(
+            &conn,
+            &Appointment {
+                id: None,
+                start_time: "2031-06-02T13:00:00.000Z".to_string(),
+                end_time: "2031-06-02T13:30:00.000Z".to_string(),
+                reminder_minutes: Some(60),
+                recurrence_rule: Some("FREQ=WEEKLY;COUNT=3".to_string()),
+                ..appointment.clone()
+            },
+        )
+        .unwrap();
+        let from = parse_filter_date("2031-06-01").unwrap();
+        let to = parse_filter_date("2031-07-01").unwrap();
+        assert_eq!(schedule_series_reminders(&conn, from, to).unwrap(), 2);
+        assert_eq!(schedule_series_reminders(&conn, from, to).unwrap(), 0);
+
+        let second: i64 = conn
+            .query_row(
+                "SELECT id FROM appointment_reminders WHERE appointment_id = ?1 AND scheduled_time = '2031-06-09T12:00:00.000Z'",
+                params![series_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        let notification = get_reminder_notification(&conn, second).unwrap().unwrap();
+        assert_eq!(notification.appointment.appointment.start_time, "2031-06-09T13:00:00.000Z");
+        assert_eq!(notification.appointment.patient_name, "Aviso Pendiente");
+
+        // Una ocurrencia cancelada deja de tener aviso
+        cancel_occurrence(
+            &conn,
+            series_id,
+            "2031-06-09T13:00:00.000Z",
+            RecurrenceScope::Occurrence,
+        )
+        .unwrap();
+        assert!(get_reminder_notification(&conn, second).unwrap().is_none());
+
+        conn.execute("DELETE FROM patients WHERE id = ?1", params![patient_id])
+            .unwrap();
+    }
+}
@@ -0,0 +1,70 @@
+use chrono::Utc;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::get_connection;
+
+/// Evento de autenticación registrado (intento fallido, bloqueo, intento rechazado)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AuthAuditEntry {
+    pub id: i64,
+    pub timestamp: String,
+    pub username: String,
+    pub ip_address: Option<String>,
+    /// "api" o "desktop"
+    pub source: String,
+    /// "login_failed", "lockout" o "login_blocked"
+    pub event: String,
+    pub detail: Option<String>,
+}
+
+pub fn record_auth_event(
+    username: &str,
+    ip_address: Option<&str>,
+    source: &str,
+    event: &str,
+    detail: Option<&str>,
+) -> Result<(), String> {
+    let conn = get_connection()?;
+    let now = Utc::now().to_rfc3339();
+
+    conn.execute(
+        "INSERT INTO auth_audit_log (timestamp, username, ip_address, source, event, detail)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![&now, username, ip_address, source, event, detail],
+    )
+    .map_err(|e| format!("Error registrando auditoría de acceso: {}", e))?;
+
+    Ok(())
+}
+
+/// Eventos más recientes primero
+pub fn list_auth_events(limit: i64) -> Result<Vec<AuthAuditEntry>, String> {
+    let conn = get_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, timestamp, username, ip_address, source, event, detail
+             FROM auth_audit_log ORDER BY id DESC LIMIT ?1",
+        )
+        .map_err(|e| format!("Error preparando query: {}", e))?;
+
+    let entries = stmt
+        .query_map(params![limit], |row| {
+            Ok(AuthAuditEntry {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                username: row.get(2)?,
+                ip_address: row.get(3)?,
+                source: row.get(4)?,
+                event: row.get(5)?,
+                detail: row.get(6)?,
+            })
+        })
+        .map_err(|e| format!("Error listando auditoría de acceso: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Error leyendo auditoría de acceso: {}", e))?;
+
+    Ok(entries)
+}
@@ -31,3 +31,22 @@ pub fn get_config(key: &str) -> Result<Option<String>, String> {
         Err(e) => Err(format!("Error obteniendo configuración: {}", e)),
     }
 }
+
+/// Obtiene un valor de configuración o guarda el generado por `init` si no existe
+/// Si dos llamadas compiten, ambas devuelven el valor que quedó guardado
+pub fn get_or_init_config(key: &str, init: impl FnOnce() -> String) -> Result<String, String> {
+    if let Some(value) = get_config(key)? {
+        return Ok(value);
+    }
+
+    let conn = get_connection()?;
+    let now = Utc::now().to_rfc3339();
+
+    conn.execute(
+        "INSERT OR IGNORE INTO app_config(key, value, created_at) VALUES (?1, ?2, ?3)",
+        params![key, init(), now],
+    )
+    .map_err(|e| format!("Error guardando configuración: {}", e))?;
+
+    get_config(key)?.ok_or_else(|| format!("Configuración '{}' no encontrada", key))
+}
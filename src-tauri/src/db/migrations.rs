@@ -1,6 +1,6 @@
 use rusqlite::Connection;
 
-const CURRENT_SCHEMA_VERSION: i32 = 16;
+const CURRENT_SCHEMA_VERSION: i32 = 29;
 
 /// Ejecuta las migraciones pendientes y retorna cuántas se aplicaron.
 pub fn run_migrations(conn: &Connection) -> Result<i32, String> {
@@ -141,6 +141,97 @@ pub fn run_migrations(conn: &Connection) -> Result<i32, String> {
         applied += 1;
     }
 
+    if current_version < 17 {
+        migrate_v17(conn)?;
+        conn.execute("INSERT INTO schema_version(version) VALUES (17)", [])
+            .map_err(|e| format!("Error actualizando versión: {}", e))?;
+        applied += 1;
+    }
+
+    if current_version < 18 {
+        migrate_v18(conn)?;
+        conn.execute("INSERT INTO schema_version(version) VALUES (18)", [])
+            .map_err(|e| format!("Error actualizando versión: {}", e))?;
+        applied += 1;
+    }
+
+    if current_version < 19 {
+        migrate_v19(conn)?;
+        conn.execute("INSERT INTO schema_version(version) VALUES (19)", [])
+            .map_err(|e| format!("Error actualizando versión: {}", e))?;
+        applied += 1;
+    }
+
+    if current_version < 20 {
+        migrate_v20(conn)?;
+        conn.execute("INSERT INTO schema_version(version) VALUES (20)", [])
+            .map_err(|e| format!("Error actualizando versión: {}", e))?;
+        applied += 1;
+    }
+
+    if current_version < 21 {
+        migrate_v21(conn)?;
+        conn.execute("INSERT INTO schema_version(version) VALUES (21)", [])
+            .map_err(|e| format!("Error actualizando versión: {}", e))?;
+        applied += 1;
+    }
+
+    if current_version < 22 {
+        migrate_v22(conn)?;
+        conn.execute("INSERT INTO schema_version(version) VALUES (22)", [])
+            .map_err(|e| format!("Error actualizando versión: {}", e))?;
+        applied += 1;
+    }
+
+    if current_version < 23 {
+        migrate_v23(conn)?;
+        conn.execute("INSERT INTO schema_version(version) VALUES (23)", [])
+            .map_err(|e| format!("Error actualizando versión: {}", e))?;
+        applied += 1;
+    }
+
+    if current_version < 24 {
+        migrate_v24(conn)?;
+        conn.execute("INSERT INTO schema_version(version) VALUES (24)", [])
+            .map_err(|e| format!("Error actualizando versión: {}", e))?;
+        applied += 1;
+    }
+
+    if current_version < 25 {
+        migrate_v25(conn)?;
+        conn.execute("INSERT INTO schema_version(version) VALUES (25)", [])
+            .map_err(|e| format!("Error actualizando versión: {}", e))?;
+        applied += 1;
+    }
+
+    if current_version < 26 {
+        migrate_v26(conn)?;
+        conn.execute("INSERT INTO schema_version(version) VALUES (26)", [])
+            .map_err(|e| format!("Error actualizando versión: {}", e))?;
+        applied += 1;
+    }
+
+    if current_version < 27 {
+        migrate_v27(conn)?;
+        conn.execute("INSERT INTO schema_version(version) VALUES (27)", [])
+            .map_err(|e| format!("Error actualizando versión: {}", e))?;
+        applied += 1;
+    }
+
+    if current_version < 28 {
+        migrate_v28(conn)?;
+        conn.execute("INSERT INTO schema_version(version) VALUES (28)", [])
+            .map_err(|e| format!("Error actualizando versión: {}", e))?;
+        applied += 1;
+    }
+
+    if current_version < 29 {
+        migrate_v29(conn)?;
+        conn.execute("INSERT INTO schema_version(version) VALUES (29)", [])
+            .map_err(|e| format!("Error actualizando versión: {}", e))?;
+        applied += 1;
+    }
+
     Ok(applied)
 }
 
@@ -1120,3 +1211,294 @@ fn migrate_v16(conn: &Connection) -> Result<(), String> {
     )
     .map_err(|e| format!("migration v16 err: {}", e))
 }
+
+/// Migración v17: versión de token por usuario y lista de tokens JWT revocados
+fn migrate_v17(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE users ADD COLUMN token_version INTEGER NOT NULL DEFAULT 0;
+
+        CREATE TABLE IF NOT EXISTS revoked_tokens (
+            jti TEXT PRIMARY KEY,
+            user_id INTEGER NOT NULL,
+            expires_at INTEGER NOT NULL,
+            revoked_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_revoked_tokens_expires_at ON revoked_tokens(expires_at);
+        "#,
+    )
+    .map_err(|e| format!("migration v17 err: {}", e))
+}
+
+/// Migración v18: nodos cliente emparejados con este host y sus credenciales
+fn migrate_v18(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS paired_nodes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            node_name TEXT NOT NULL,
+            credential_hash TEXT NOT NULL UNIQUE,
+            created_at TEXT NOT NULL,
+            last_seen_at TEXT,
+            revoked_at TEXT
+        );
+        "#,
+    )
+    .map_err(|e| format!("migration v18 err: {}", e))
+}
+
+/// Migración v19: auditoría de intentos de inicio de sesión fallidos y bloqueos
+fn migrate_v19(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS auth_audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp TEXT NOT NULL DEFAULT (datetime('now')),
+            username TEXT NOT NULL,
+            ip_address TEXT,
+            source TEXT NOT NULL,
+            event TEXT NOT NULL,
+            detail TEXT
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_auth_audit_timestamp ON auth_audit_log(timestamp);
+        CREATE INDEX IF NOT EXISTS idx_auth_audit_user ON auth_audit_log(username);
+        "#,
+    )
+    .map_err(|e| format!("migration v19 err: {}", e))
+}
+
+/// Migración v20: la papelera guarda la metadata de las carpetas eliminadas
+/// (el contenido completo) para poder restaurarlas tal cual estaban
+fn migrate_v20(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE filesystem_trash ADD COLUMN entries_snapshot TEXT;
+        "#,
+    )
+    .map_err(|e| format!("migration v20 err: {}", e))
+}
+
+/// Migración v21: quota_bytes pasa a ser opcional (NULL = cuota por defecto de la
+/// configuración), para llevar el espacio usado de todos los usuarios aunque no
+/// tengan una cuota propia
+fn migrate_v21(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE filesystem_quotas_v21 (
+            username TEXT PRIMARY KEY,
+            quota_bytes INTEGER,
+            used_bytes INTEGER NOT NULL DEFAULT 0,
+            last_calculated TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (username) REFERENCES users(username) ON DELETE CASCADE
+        );
+
+        INSERT INTO filesystem_quotas_v21 (username, quota_bytes, used_bytes, last_calculated)
+        SELECT username, quota_bytes, used_bytes, last_calculated FROM filesystem_quotas;
+
+        DROP TABLE filesystem_quotas;
+        ALTER TABLE filesystem_quotas_v21 RENAME TO filesystem_quotas;
+        "#,
+    )
+    .map_err(|e| format!("migration v21 err: {}", e))
+}
+
+/// Migración v22: índice de texto completo (FTS5) con el contenido de los
+/// archivos PDF, TXT y DOCX del sistema de archivos virtual
+fn migrate_v22(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS filesystem_content_index USING fts5(
+            virtual_path UNINDEXED,
+            content,
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+        "#,
+    )
+    .map_err(|e| format!("migration v22 err: {}", e))
+}
+
+/// Migración v23: versiones anteriores de los archivos sobrescritos. El contenido
+/// se guarda en G:\.versions y cuenta para la cuota del dueño de la carpeta
+fn migrate_v23(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS filesystem_versions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            virtual_path TEXT NOT NULL,
+            version INTEGER NOT NULL,
+            size INTEGER NOT NULL,
+            mime_type TEXT,
+            storage_path TEXT NOT NULL,
+            modified_at TEXT NOT NULL,
+            replaced_at TEXT NOT NULL,
+            replaced_by TEXT NOT NULL,
+            UNIQUE (virtual_path, version)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_filesystem_versions_path
+            ON filesystem_versions(virtual_path);
+        "#,
+    )
+    .map_err(|e| format!("migration v23 err: {}", e))
+}
+
+/// Migración v24: miniaturas de imágenes y DICOM. Se borran junto con la fila de
+/// filesystem_metadata y se regeneran cuando cambia la fecha de modificación
+fn migrate_v24(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS filesystem_thumbnails (
+            file_id INTEGER PRIMARY KEY,
+            source_modified_at TEXT NOT NULL,
+            data BLOB NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (file_id) REFERENCES filesystem_metadata(id) ON DELETE CASCADE
+        );
+        "#,
+    )
+    .map_err(|e| format!("migration v24 err: {}", e))
+}
+
+/// Migración v25: cifrado de archivos. Las claves de datos se guardan envueltas con
+/// una clave derivada de la contraseña del sistema o de un archivo de clave; la
+/// más reciente es la que se usa para escribir
+fn migrate_v25(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS filesystem_encryption (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            key_source TEXT NOT NULL,
+            salt BLOB NOT NULL,
+            key_file_path TEXT,
+            updated_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS filesystem_encryption_keys (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            wrapped_key BLOB NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        "#,
+    )
+    .map_err(|e| format!("migration v25 err: {}", e))
+}
+
+/// Migración v26: citas recurrentes. Una serie guarda su regla en recurrence_rule;
+/// las ocurrencias modificadas o canceladas se guardan como citas propias con
+/// series_id y el inicio original de la ocurrencia que reemplazan
+fn migrate_v26(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE appointments ADD COLUMN recurrence_rule TEXT DEFAULT NULL;
+        ALTER TABLE appointments ADD COLUMN series_id INTEGER DEFAULT NULL
+            REFERENCES appointments(id) ON DELETE CASCADE;
+        ALTER TABLE appointments ADD COLUMN original_start_time TEXT DEFAULT NULL;
+
+        CREATE INDEX IF NOT EXISTS idx_appointments_series ON appointments(series_id);
+        CREATE INDEX IF NOT EXISTS idx_appointments_recurrence
+            ON appointments(recurrence_rule) WHERE recurrence_rule IS NOT NULL;
+        "#,
+    )
+    .map_err(|e| format!("migration v26 err: {}", e))
+}
+
+/// Migración v27: recursos de la agenda (profesionales y sillones) con su horario
+/// semanal, pausas y días libres
+fn migrate_v27(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS agenda_resources (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL CHECK (kind IN ('practitioner', 'chair')),
+            name TEXT NOT NULL,
+            user_id INTEGER,
+            color TEXT,
+            active INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE SET NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS agenda_resource_hours (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            resource_id INTEGER NOT NULL,
+            weekday INTEGER NOT NULL CHECK (weekday BETWEEN 0 AND 6),
+            start_time TEXT NOT NULL,
+            end_time TEXT NOT NULL,
+            is_break INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY (resource_id) REFERENCES agenda_resources(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS agenda_resource_days_off (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            resource_id INTEGER NOT NULL,
+            start_date TEXT NOT NULL,
+            end_date TEXT NOT NULL,
+            reason TEXT,
+            FOREIGN KEY (resource_id) REFERENCES agenda_resources(id) ON DELETE CASCADE
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_agenda_resource_hours ON agenda_resource_hours(resource_id);
+        CREATE INDEX IF NOT EXISTS idx_agenda_resource_days_off
+            ON agenda_resource_days_off(resource_id);
+        "#,
+    )
+    .map_err(|e| format!("migration v27 err: {}", e))
+}
+
+/// Migración v28: envío de recordatorios en segundo plano. Cada recordatorio guarda
+/// el inicio de la cita u ocurrencia que anuncia y los reintentos fallidos; las
+/// horas programadas pasan a UTC para poder compararlas como texto
+fn migrate_v28(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE appointment_reminders ADD COLUMN occurrence_start_time TEXT DEFAULT NULL;
+        ALTER TABLE appointment_reminders ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE appointment_reminders ADD COLUMN next_attempt_at TEXT DEFAULT NULL;
+        ALTER TABLE appointment_reminders ADD COLUMN last_error TEXT DEFAULT NULL;
+
+        UPDATE appointment_reminders
+            SET occurrence_start_time = (
+                SELECT start_time FROM appointments WHERE appointments.id = appointment_reminders.appointment_id
+            );
+
+        UPDATE appointment_reminders
+            SET scheduled_time = strftime('%Y-%m-%dT%H:%M:%fZ', scheduled_time)
+            WHERE strftime('%Y-%m-%dT%H:%M:%fZ', scheduled_time) IS NOT NULL;
+
+        CREATE INDEX IF NOT EXISTS idx_reminders_occurrence
+            ON appointment_reminders(appointment_id, occurrence_start_time);
+        "#,
+    )
+    .map_err(|e| format!("migration v28 err: {}", e))
+}
+
+/// Migración v29: profesional y sillón de cada cita como recursos de la agenda.
+/// Los sillones se toman de la ubicación de las citas existentes cuando coincide
+/// con el nombre de un sillón; el profesional queda sin asignar
+fn migrate_v29(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE appointments ADD COLUMN practitioner_id INTEGER DEFAULT NULL
+            REFERENCES agenda_resources(id) ON DELETE SET NULL;
+        ALTER TABLE appointments ADD COLUMN chair_id INTEGER DEFAULT NULL
+            REFERENCES agenda_resources(id) ON DELETE SET NULL;
+
+        UPDATE appointments
+            SET chair_id = (
+                SELECT r.id FROM agenda_resources r
+                WHERE r.kind = 'chair'
+                    AND lower(trim(r.name)) = lower(trim(appointments.location))
+                ORDER BY r.active DESC, r.id
+                LIMIT 1
+            )
+            WHERE location IS NOT NULL;
+
+        CREATE INDEX IF NOT EXISTS idx_appointments_practitioner ON appointments(practitioner_id);
+        CREATE INDEX IF NOT EXISTS idx_appointments_chair ON appointments(chair_id);
+        "#,
+    )
+    .map_err(|e| format!("migration v29 err: {}", e))
+}
@@ -1,4 +1,6 @@
+pub mod agenda_resources;
 pub mod appointments;
+pub mod auth_audit;
 pub mod config;
 pub mod db_explorer;
 pub mod intellisense;
@@ -7,11 +9,14 @@ pub mod migrations;
 pub mod odontogram_surfaces;
 pub mod odontogram_tooth_treatments;
 pub mod odontograms;
+pub mod paired_nodes;
 pub mod path;
 pub mod patients;
 pub mod payments;
 pub mod plugin_data;
+pub mod recurrence;
 pub mod templates;
+pub mod tokens;
 pub mod treatment_catalog;
 pub mod treatments;
 pub mod users;
@@ -1,10 +1,11 @@
 use chrono::Utc;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use super::get_connection;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct OdontogramSurface {
     pub id: i64,
     pub patient_id: i64,
@@ -21,7 +22,7 @@ pub struct OdontogramSurface {
     pub treatment_id: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct SurfaceHistoryEntry {
     pub id: i64,
     pub patient_id: i64,
@@ -36,7 +37,7 @@ pub struct SurfaceHistoryEntry {
     pub recorded_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UpdateSurfaceInput {
     pub patient_id: i64,
     pub tooth_number: String,
@@ -47,7 +48,7 @@ pub struct UpdateSurfaceInput {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct AddSurfaceTreatmentInput {
     pub patient_id: i64,
     pub tooth_number: String,
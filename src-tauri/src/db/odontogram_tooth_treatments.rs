@@ -1,6 +1,7 @@
 use chrono::Utc;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use super::get_connection;
 
@@ -8,7 +9,7 @@ use super::get_connection;
 // Tratamientos a nivel de diente completo (sin superficie específica)
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct OdontogramToothTreatment {
     pub id: i64,
     pub patient_id: i64,
@@ -24,7 +25,7 @@ pub struct OdontogramToothTreatment {
     pub treatment_id: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct AddToothTreatmentInput {
     pub patient_id: i64,
     pub tooth_number: String,
@@ -36,7 +37,7 @@ pub struct AddToothTreatmentInput {
     pub treatment_id: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ToothTreatmentHistoryEntry {
     pub id: i64,
     pub patient_id: i64,
@@ -287,7 +288,7 @@ pub fn get_tooth_treatment_history(
 // Puentes dentales
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct OdontogramBridge {
     pub id: i64,
     pub patient_id: i64,
@@ -304,7 +305,7 @@ pub struct OdontogramBridge {
     pub treatment_id: Option<i64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct AddBridgeInput {
     pub patient_id: i64,
     pub bridge_name: String,
@@ -1,10 +1,11 @@
 use chrono::Utc;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use super::get_connection;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct OdontogramEntry {
     pub id: i64,
     pub patient_id: i64,
@@ -15,7 +16,7 @@ pub struct OdontogramEntry {
     pub updated_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UpdateToothConditionInput {
     pub patient_id: i64,
     pub tooth_number: String,
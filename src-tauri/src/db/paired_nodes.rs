@@ -0,0 +1,115 @@
+use chrono::Utc;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::get_connection;
+
+/// Nodo cliente emparejado con este host
+/// La credencial solo se guarda como hash SHA-256; el valor en claro lo tiene el cliente.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PairedNode {
+    pub id: i64,
+    pub node_name: String,
+    pub created_at: String,
+    pub last_seen_at: Option<String>,
+    pub revoked_at: Option<String>,
+}
+
+pub fn create_paired_node(node_name: &str, credential_hash: &str) -> Result<i64, String> {
+    let conn = get_connection()?;
+    let now = Utc::now().to_rfc3339();
+
+    conn.execute(
+        "INSERT INTO paired_nodes (node_name, credential_hash, created_at) VALUES (?1, ?2, ?3)",
+        params![node_name, credential_hash, &now],
+    )
+    .map_err(|e| format!("Error registrando nodo emparejado: {}", e))?;
+
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn list_paired_nodes() -> Result<Vec<PairedNode>, String> {
+    let conn = get_connection()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, node_name, created_at, last_seen_at, revoked_at
+             FROM paired_nodes ORDER BY revoked_at IS NOT NULL, created_at DESC",
+        )
+        .map_err(|e| format!("Error preparando query: {}", e))?;
+
+    let nodes = stmt
+        .query_map([], |row| {
+            Ok(PairedNode {
+                id: row.get(0)?,
+                node_name: row.get(1)?,
+                created_at: row.get(2)?,
+                last_seen_at: row.get(3)?,
+                revoked_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| format!("Error listando nodos emparejados: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Error leyendo nodos emparejados: {}", e))?;
+
+    Ok(nodes)
+}
+
+/// Busca el nodo activo (no revocado) dueño de una credencial
+pub fn find_active_by_credential(credential_hash: &str) -> Result<Option<PairedNode>, String> {
+    let conn = get_connection()?;
+
+    let result = conn.query_row(
+        "SELECT id, node_name, created_at, last_seen_at, revoked_at
+         FROM paired_nodes WHERE credential_hash = ?1 AND revoked_at IS NULL",
+        params![credential_hash],
+        |row| {
+            Ok(PairedNode {
+                id: row.get(0)?,
+                node_name: row.get(1)?,
+                created_at: row.get(2)?,
+                last_seen_at: row.get(3)?,
+                revoked_at: row.get(4)?,
+            })
+        },
+    );
+
+    match result {
+        Ok(node) => Ok(Some(node)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(format!("Error verificando credencial de nodo: {}", e)),
+    }
+}
+
+pub fn touch_paired_node(id: i64) -> Result<(), String> {
+    let conn = get_connection()?;
+    let now = Utc::now().to_rfc3339();
+
+    conn.execute(
+        "UPDATE paired_nodes SET last_seen_at = ?1 WHERE id = ?2",
+        params![&now, id],
+    )
+    .map_err(|e| format!("Error actualizando nodo emparejado: {}", e))?;
+
+    Ok(())
+}
+
+/// Revoca la credencial de un nodo; el registro se conserva para auditoría
+pub fn revoke_paired_node(id: i64) -> Result<(), String> {
+    let conn = get_connection()?;
+    let now = Utc::now().to_rfc3339();
+
+    let updated = conn
+        .execute(
+            "UPDATE paired_nodes SET revoked_at = ?1 WHERE id = ?2 AND revoked_at IS NULL",
+            params![&now, id],
+        )
+        .map_err(|e| format!("Error revocando nodo emparejado: {}", e))?;
+
+    if updated == 0 {
+        return Err("Nodo emparejado no encontrado o ya revocado".to_string());
+    }
+
+    Ok(())
+}
@@ -1,10 +1,11 @@
 use chrono::Utc;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use super::get_connection;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Patient {
     pub id: i64,
     pub legacy_patient_id: Option<String>,
@@ -22,7 +23,7 @@ pub struct Patient {
     pub updated_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreatePatientInput {
     pub first_name: String,
     pub last_name: String,
@@ -36,7 +37,7 @@ pub struct CreatePatientInput {
     pub medical_notes: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UpdatePatientInput {
     pub first_name: Option<String>,
     pub last_name: Option<String>,
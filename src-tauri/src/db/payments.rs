@@ -1,10 +1,11 @@
 use chrono::Utc;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use super::get_connection;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Payment {
     pub id: i64,
     pub treatment_id: i64,
@@ -17,7 +18,7 @@ pub struct Payment {
     pub created_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreatePaymentInput {
     pub treatment_id: i64,
     pub amount: f64,
@@ -26,7 +27,7 @@ pub struct CreatePaymentInput {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UpdatePaymentInput {
     pub amount: Option<f64>,
     pub payment_date: Option<String>,
@@ -34,7 +35,7 @@ pub struct UpdatePaymentInput {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct PatientBalance {
     pub patient_id: i64,
     pub patient_name: String,
@@ -44,7 +45,7 @@ pub struct PatientBalance {
     pub treatments_count: i64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct PatientDebtSummary {
     pub debtors_count: i64,
     pub total_debt: f64,
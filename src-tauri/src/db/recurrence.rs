@@ -0,0 +1,393 @@
+// Reglas de recurrencia de citas: subconjunto de RRULE (RFC 5545)
+//
+// Soporta FREQ=DAILY|WEEKLY|MONTHLY|YEARLY, INTERVAL, COUNT, UNTIL y BYDAY (solo
+// con FREQ=WEEKLY, sin prefijos numéricos). Las ocurrencias se calculan en la hora
+// local, así una cita semanal a las 10:00 sigue a las 10:00 después de un cambio
+// de horario de verano.
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone, Utc,
+    Weekday,
+};
+
+/// Máximo de ocurrencias que se expanden por serie en una consulta
+pub const MAX_OCCURRENCES: usize = 1000;
+
+/// Períodos recorridos como máximo al buscar ocurrencias (evita bucles sin fin
+/// con reglas que casi nunca producen fechas válidas, como el 29 de febrero)
+const MAX_PERIODS: u32 = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<DateTime<Utc>>,
+    /// Días de la semana (FREQ=WEEKLY); vacío = el día de la primera cita
+    pub by_day: Vec<Weekday>,
+}
+
+impl RecurrenceRule {
+    /// Interpreta una regla del tipo "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;COUNT=10".
+    /// Acepta el prefijo "RRULE:".
+    pub fn parse(rule: &str) -> Result<Self, String> {
+        let rule = rule.trim();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut count = None;
+        let mut until = None;
+        let mut by_day = Vec::new();
+
+        for part in rule.split(';').filter(|part| !part.trim().is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Regla de recurrencia inválida: '{}'", part))?;
+            let value = value.trim();
+
+            match name.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("Frecuencia no soportada: '{}'", value)),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or_else(|| format!("INTERVAL inválido: '{}'", value))?
+                }
+                "COUNT" => {
+                    count = Some(
+                        value
+                            .parse::<u32>()
+                            .ok()
+                            .filter(|count| *count > 0)
+                            .ok_or_else(|| format!("COUNT inválido: '{}'", value))?,
+                    )
+                }
+                "UNTIL" => until = Some(parse_until(value)?),
+                "BYDAY" => {
+                    by_day = value
+                        .split(',')
+                        .map(parse_weekday)
+                        .collect::<Result<Vec<_>, _>>()?
+                }
+                "WKST" => {}
+                _ => return Err(format!("Parte de regla no soportada: '{}'", name)),
+            }
+        }
+
+        let frequency = frequency.ok_or("La regla de recurrencia requiere FREQ")?;
+        if count.is_some() && until.is_some() {
+            return Err("COUNT y UNTIL no pueden usarse juntos".to_string());
+        }
+        if !by_day.is_empty() && frequency != Frequency::Weekly {
+            return Err("BYDAY solo se soporta con FREQ=WEEKLY".to_string());
+        }
+
+        by_day.sort_by_key(|day| day.num_days_from_monday());
+        by_day.dedup();
+
+        Ok(Self {
+            frequency,
+            interval,
+            count,
+            until,
+            by_day,
+        })
+    }
+
+    /// Escribe la regla en formato RRULE (sin el prefijo "RRULE:")
+    pub fn to_rule_string(&self) -> String {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+
+        let mut parts = vec![format!("FREQ={}", frequency)];
+        if self.interval != 1 {
+            parts.push(format!("INTERVAL={}", self.interval));
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<&str> = self.by_day.iter().map(|day| weekday_code(*day)).collect();
+            parts.push(format!("BYDAY={}", days.join(",")));
+        }
+        if let Some(count) = self.count {
+            parts.push(format!("COUNT={}", count));
+        }
+        if let Some(until) = self.until {
+            parts.push(format!("UNTIL={}", until.format("%Y%m%dT%H%M%SZ")));
+        }
+
+        parts.join(";")
+    }
+
+    /// Inicios de las ocurrencias de una serie que empieza en `start`, en orden.
+    ///
+    /// Solo se devuelven las que empiezan entre `from` y `to` (inclusive), pero
+    /// COUNT cuenta desde el inicio de la serie. Se devuelven como mucho
+    /// `MAX_OCCURRENCES`, con la zona horaria de `start`.
+    pub fn occurrences(
+        &self,
+        start: DateTime<FixedOffset>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Vec<DateTime<FixedOffset>> {
+        let offset = *start.offset();
+        let local_start = start.with_timezone(&Local).naive_local();
+        let time = local_start.time();
+        let first_date = local_start.date();
+
+        let mut occurrences = Vec::new();
+        let mut generated = 0u32;
+
+        for period in 0..MAX_PERIODS {
+            for date in self.dates_in_period(first_date, period) {
+                if date < first_date {
+                    continue;
+                }
+
+                // La primera ocurrencia es siempre el inicio de la serie
+                let occurrence = if date == first_date {
+                    start
+                } else {
+                    match local_to_offset(date.and_time(time), offset) {
+                        Some(occurrence) => occurrence,
+                        None => continue,
+                    }
+                };
+
+                if self.count.is_some_and(|count| generated >= count)
+                    || self.until.is_some_and(|until| occurrence > until)
+                    || to.is_some_and(|to| occurrence > to)
+                {
+                    return occurrences;
+                }
+                generated += 1;
+
+                if from.is_some_and(|from| occurrence < from) {
+                    continue;
+                }
+                occurrences.push(occurrence);
+                if occurrences.len() >= MAX_OCCURRENCES {
+                    return occurrences;
+                }
+            }
+        }
+
+        occurrences
+    }
+
+    /// Si `instant` es el inicio de una ocurrencia de la serie
+    pub fn is_occurrence(&self, start: DateTime<FixedOffset>, instant: DateTime<FixedOffset>) -> bool {
+        let instant = instant.with_timezone(&Utc);
+        self.occurrences(start, Some(instant), Some(instant))
+            .first()
+            .is_some_and(|occurrence| *occurrence == instant)
+    }
+
+    /// Cantidad de ocurrencias que empiezan antes de `instant`
+    pub fn count_before(&self, start: DateTime<FixedOffset>, instant: DateTime<FixedOffset>) -> usize {
+        let before = instant.with_timezone(&Utc) - Duration::seconds(1);
+        self.occurrences(start, None, Some(before)).len()
+    }
+
+    /// Fechas candidatas del período `period` (en unidades de INTERVAL)
+    fn dates_in_period(&self, first_date: NaiveDate, period: u32) -> Vec<NaiveDate> {
+        let step = i64::from(period) * i64::from(self.interval);
+
+        match self.frequency {
+            Frequency::Daily => first_date
+                .checked_add_signed(Duration::days(step))
+                .into_iter()
+                .collect(),
+            Frequency::Weekly => {
+                let week_start = first_date
+                    - Duration::days(i64::from(first_date.weekday().num_days_from_monday()))
+                    + Duration::weeks(step);
+                let days = if self.by_day.is_empty() {
+                    vec![first_date.weekday()]
+                } else {
+                    self.by_day.clone()
+                };
+
+                days.into_iter()
+                    .map(|day| week_start + Duration::days(i64::from(day.num_days_from_monday())))
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let months = i64::from(first_date.month0()) + step;
+                let year = i64::from(first_date.year()) + months / 12;
+                let month = (months % 12) as u32 + 1;
+                i32::try_from(year)
+                    .ok()
+                    .and_then(|year| NaiveDate::from_ymd_opt(year, month, first_date.day()))
+                    .into_iter()
+                    .collect()
+            }
+            Frequency::Yearly => i32::try_from(i64::from(first_date.year()) + step)
+                .ok()
+                .and_then(|year| NaiveDate::from_ymd_opt(year, first_date.month(), first_date.day()))
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// Convierte una hora local a la zona `offset`. Las horas que no existen por un
+/// cambio de horario se corren una hora hacia adelante.
+fn local_to_offset(naive: NaiveDateTime, offset: FixedOffset) -> Option<DateTime<FixedOffset>> {
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| Local.from_local_datetime(&(naive + Duration::hours(1))).earliest())
+        .map(|local| local.with_timezone(&offset))
+}
+
+fn parse_until(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S") {
+        return Ok(Utc.from_utc_datetime(&naive));
+    }
+    // Solo fecha: incluye todo ese día
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y%m%d") {
+        if let Some(end_of_day) = date.and_hms_opt(23, 59, 59) {
+            return Ok(Utc.from_utc_datetime(&end_of_day));
+        }
+    }
+
+    Err(format!("UNTIL inválido: '{}'", value))
+}
+
+fn parse_weekday(value: &str) -> Result<Weekday, String> {
+    match value.trim().to_ascii_uppercase().as_str() {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        _ => Err(format!("Día de la semana inválido: '{}'", value)),
+    }
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start() -> DateTime<FixedOffset> {
+        // Lunes 6 de enero de 2025, 10:00 hora local
+        let naive = NaiveDate::from_ymd_opt(2025, 1, 6)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        Local.from_local_datetime(&naive).unwrap().fixed_offset()
+    }
+
+    fn local_dates(occurrences: &[DateTime<FixedOffset>]) -> Vec<String> {
+        occurrences
+            .iter()
+            .map(|occurrence| occurrence.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn weekly_rule_with_days_and_count() {
+        let rule = RecurrenceRule::parse("RRULE:FREQ=WEEKLY;BYDAY=TH,MO;COUNT=5").unwrap();
+        assert_eq!(rule.to_rule_string(), "FREQ=WEEKLY;BYDAY=MO,TH;COUNT=5");
+
+        let occurrences = rule.occurrences(start(), None, None);
+        assert_eq!(
+            local_dates(&occurrences),
+            [
+                "2025-01-06 10:00",
+                "2025-01-09 10:00",
+                "2025-01-13 10:00",
+                "2025-01-16 10:00",
+                "2025-01-20 10:00",
+            ]
+        );
+
+        // COUNT cuenta desde el inicio aunque se pida un rango posterior
+        let from = occurrences[3].with_timezone(&Utc);
+        assert_eq!(rule.occurrences(start(), Some(from), None).len(), 2);
+        assert!(rule.is_occurrence(start(), occurrences[2]));
+        assert!(!rule.is_occurrence(start(), occurrences[2] + Duration::hours(1)));
+        assert_eq!(rule.count_before(start(), occurrences[2]), 2);
+    }
+
+    #[test]
+    fn monthly_rule_skips_missing_days() {
+        let naive = NaiveDate::from_ymd_opt(2025, 1, 31)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap();
+        let start = Local.from_local_datetime(&naive).unwrap().fixed_offset();
+
+        let rule = RecurrenceRule::parse("FREQ=MONTHLY;UNTIL=20250731").unwrap();
+        assert_eq!(
+            local_dates(&rule.occurrences(start, None, None)),
+            [
+                "2025-01-31 09:30",
+                "2025-03-31 09:30",
+                "2025-05-31 09:30",
+                "2025-07-31 09:30",
+            ]
+        );
+    }
+
+    #[test]
+    fn open_ended_rules_are_capped() {
+        let rule = RecurrenceRule::parse("FREQ=DAILY;INTERVAL=2").unwrap();
+        assert_eq!(rule.occurrences(start(), None, None).len(), MAX_OCCURRENCES);
+
+        let to = (start() + Duration::days(6)).with_timezone(&Utc);
+        assert_eq!(rule.occurrences(start(), None, Some(to)).len(), 4);
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        for rule in [
+            "INTERVAL=2",
+            "FREQ=HOURLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;COUNT=2;UNTIL=20250101",
+            "FREQ=MONTHLY;BYDAY=MO",
+            "FREQ=WEEKLY;BYDAY=1MO",
+        ] {
+            assert!(RecurrenceRule::parse(rule).is_err(), "{} should be rejected", rule);
+        }
+    }
+}
@@ -34,6 +34,35 @@ pub struct UpdateTemplateInput {
     pub is_default: Option<bool>,
 }
 
+impl Template {
+    /// Reemplaza las variables `{{clave}}` del contenido; las desconocidas quedan igual
+    pub fn render(&self, values: &[(&str, String)]) -> String {
+        render_content(&self.content, values)
+    }
+}
+
+/// Reemplaza las variables `{{clave}}` (con o sin espacios internos) de un contenido
+pub fn render_content(content: &str, values: &[(&str, String)]) -> String {
+    let mut rendered = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        let key = rest[start + 2..start + end].trim();
+        rendered.push_str(&rest[..start]);
+        match values.iter().find(|(name, _)| *name == key) {
+            Some((_, value)) => rendered.push_str(value),
+            None => rendered.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &rest[start + end + 2..];
+    }
+
+    rendered.push_str(rest);
+    rendered
+}
+
 pub fn init_templates_table(conn: &Connection) -> Result<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS templates (
@@ -0,0 +1,71 @@
+use chrono::Utc;
+use rusqlite::params;
+
+use super::get_connection;
+
+/// Versión actual de tokens de un usuario (los JWT con otra versión son inválidos)
+pub fn get_token_version(user_id: i64) -> Result<i64, String> {
+    let conn = get_connection()?;
+
+    conn.query_row(
+        "SELECT token_version FROM users WHERE id = ?1",
+        params![user_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => "Usuario no encontrado".to_string(),
+        e => format!("Error obteniendo versión de token: {}", e),
+    })
+}
+
+/// Incrementa la versión de tokens, invalidando todas las sesiones del usuario
+pub fn increment_token_version(user_id: i64) -> Result<(), String> {
+    let conn = get_connection()?;
+    let now = Utc::now().to_rfc3339();
+
+    let updated = conn
+        .execute(
+            "UPDATE users SET token_version = token_version + 1, updated_at = ?1 WHERE id = ?2",
+            params![&now, user_id],
+        )
+        .map_err(|e| format!("Error revocando sesiones: {}", e))?;
+
+    if updated == 0 {
+        return Err("Usuario no encontrado".to_string());
+    }
+
+    Ok(())
+}
+
+/// Revoca un token concreto hasta su expiración (timestamp unix)
+pub fn revoke_token(jti: &str, user_id: i64, expires_at: u64) -> Result<(), String> {
+    let conn = get_connection()?;
+    let now = Utc::now().to_rfc3339();
+
+    conn.execute(
+        "INSERT OR IGNORE INTO revoked_tokens (jti, user_id, expires_at, revoked_at) VALUES (?1, ?2, ?3, ?4)",
+        params![jti, user_id, expires_at as i64, &now],
+    )
+    .map_err(|e| format!("Error revocando token: {}", e))?;
+
+    // Los tokens expirados ya no necesitan estar en la lista
+    conn.execute(
+        "DELETE FROM revoked_tokens WHERE expires_at < ?1",
+        params![Utc::now().timestamp()],
+    )
+    .map_err(|e| format!("Error limpiando tokens revocados: {}", e))?;
+
+    Ok(())
+}
+
+pub fn is_token_revoked(jti: &str) -> Result<bool, String> {
+    let conn = get_connection()?;
+
+    conn.query_row(
+        "SELECT COUNT(1) FROM revoked_tokens WHERE jti = ?1",
+        params![jti],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+    .map_err(|e| format!("Error verificando token: {}", e))
+}
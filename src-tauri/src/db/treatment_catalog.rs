@@ -1,10 +1,11 @@
 use chrono::Utc;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use super::get_connection;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TreatmentCatalogEntry {
     pub id: i64,
     pub name: String,
@@ -25,7 +26,7 @@ pub struct TreatmentCatalogEntry {
     pub updated_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TreatmentCatalogItem {
     pub id: i64,
     pub treatment_catalog_id: i64,
@@ -43,7 +44,7 @@ pub struct TreatmentCatalogItem {
     pub updated_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreateTreatmentCatalogInput {
     pub name: String,
     pub description: Option<String>,
@@ -57,7 +58,7 @@ pub struct CreateTreatmentCatalogInput {
     pub is_bridge_component: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UpdateTreatmentCatalogInput {
     pub id: i64,
     pub name: String,
@@ -73,7 +74,7 @@ pub struct UpdateTreatmentCatalogInput {
     pub is_active: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreateTreatmentCatalogItemInput {
     pub treatment_catalog_id: i64,
     pub name: String,
@@ -87,7 +88,7 @@ pub struct CreateTreatmentCatalogItemInput {
     pub display_order: i32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UpdateTreatmentCatalogItemInput {
     pub id: i64,
     pub name: String,
@@ -1,10 +1,11 @@
 use chrono::Utc;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use super::get_connection;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Treatment {
     pub id: i64,
     pub patient_id: i64,
@@ -24,7 +25,7 @@ pub struct Treatment {
     pub updated_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreateTreatmentInput {
     pub patient_id: i64,
     pub name: String,
@@ -35,7 +36,7 @@ pub struct CreateTreatmentInput {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UpdateTreatmentInput {
     pub name: Option<String>,
     pub tooth_number: Option<String>,
@@ -47,7 +48,7 @@ pub struct UpdateTreatmentInput {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TreatmentStats {
     pub pending_count: i64,
     pub in_progress_count: i64,
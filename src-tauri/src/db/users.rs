@@ -1,10 +1,12 @@
 use chrono::Utc;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use super::get_connection;
+use crate::passwords::{self, Verification};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct User {
     pub id: i64,
     pub username: String,
@@ -179,10 +181,13 @@ pub fn update_user_pin(username: &str, new_pin: Option<&str>) -> Result<(), Stri
     let conn = get_connection()?;
     let now = Utc::now().to_rfc3339();
 
+    // Los PIN se guardan hasheados igual que las contraseñas
+    let pin_hash = new_pin.map(passwords::hash_secret).transpose()?;
+
     let updated = conn
         .execute(
             "UPDATE users SET pin = ?1, updated_at = ?2 WHERE username = ?3",
-            params![new_pin, &now, username],
+            params![pin_hash, &now, username],
         )
         .map_err(|e| format!("Error actualizando PIN: {}", e))?;
 
@@ -237,10 +242,19 @@ pub fn verify_user_pin(username: &str, pin: &str) -> Result<Option<User>, String
                 return Err("Usuario inactivo".to_string());
             }
 
-            match &user.pin {
-                Some(stored_pin) if stored_pin == pin => Ok(Some(user)),
-                Some(_) => Err("PIN incorrecto".to_string()),
-                None => Err("Usuario no tiene PIN configurado".to_string()),
+            let stored_pin = match &user.pin {
+                Some(stored_pin) => stored_pin,
+                None => return Err("Usuario no tiene PIN configurado".to_string()),
+            };
+
+            match passwords::verify_pin(pin, stored_pin) {
+                Verification::Valid => Ok(Some(user)),
+                Verification::ValidLegacy => {
+                    // PIN guardado en texto plano por versiones anteriores: re-hashear
+                    update_user_pin(username, Some(pin))?;
+                    Ok(Some(user))
+                }
+                Verification::Invalid => Err("PIN incorrecto".to_string()),
             }
         }
         Err(rusqlite::Error::QueryReturnedNoRows) => Err("Usuario no encontrado".to_string()),
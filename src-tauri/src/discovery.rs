@@ -21,6 +21,12 @@ pub struct DiscoveredNode {
     pub port: u16,
     /// Service version
     pub version: String,
+    /// Whether the node serves its API over HTTPS
+    #[serde(default)]
+    pub tls: bool,
+    /// SHA-256 fingerprint of the node's certificate, to be pinned by clients
+    #[serde(default)]
+    pub fingerprint: Option<String>,
     /// Last seen timestamp (Unix timestamp)
     pub last_seen: i64,
 }
@@ -46,7 +52,8 @@ impl DiscoveryService {
     }
 
     /// Start broadcasting as a host node
-    pub async fn start_broadcasting(&self, node_name: &str, port: u16) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// `tls_fingerprint` is advertised in the TXT record when the API is served over HTTPS.
+    pub async fn start_broadcasting(&self, node_name: &str, port: u16, tls_fingerprint: Option<&str>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let instance_name = format!("{}.{}", node_name.replace(" ", "_"), SERVICE_TYPE.trim_end_matches('.'));
         let hostname = hostname::get()?.to_string_lossy().to_string();
         let hostname_mdns = if hostname.ends_with(".local.") {
@@ -56,10 +63,14 @@ impl DiscoveryService {
         };
         let version = env!("CARGO_PKG_VERSION");
 
-        let properties = [
+        let mut properties = vec![
             ("version", version),
             ("node_name", node_name),
         ];
+        if let Some(fingerprint) = tls_fingerprint {
+            properties.push(("tls", "1"));
+            properties.push(("fingerprint", fingerprint));
+        }
 
         let service_info = ServiceInfo::new(
             SERVICE_TYPE,
@@ -101,6 +112,8 @@ impl DiscoveryService {
                                     hostname: info.get_hostname().to_string(),
                                     port: info.get_port(),
                                     version: info.get_properties().get("version").map(|s| s.to_string()).unwrap_or_else(|| "unknown".to_string()),
+                                    tls: info.get_property_val_str("tls") == Some("1"),
+                                    fingerprint: info.get_property_val_str("fingerprint").map(|s| s.to_string()),
                                     last_seen: chrono::Utc::now().timestamp(),
                                 };
                                 nodes.insert(node.service_name.clone(), node);
@@ -0,0 +1,208 @@
+// Entity Change Events
+// Services publish an event after every successful write. The host streams them to client
+// nodes over Server-Sent Events (GET /api/events); every node re-emits them to its own
+// frontend as the `data:changed` Tauri event so open screens can refresh.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::Emitter;
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+use crate::global;
+use crate::node::ClientConfig;
+
+/// Tauri event carrying a `ChangeEvent` payload
+pub const FRONTEND_EVENT: &str = "data:changed";
+
+/// Events kept for slow subscribers before they start missing some
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Delay before reconnecting to the host after the stream drops
+const RECONNECT_DELAY_SECS: u64 = 5;
+
+/// A write to a domain entity
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ChangeEvent {
+    /// `<entity>.<action>`, e.g. `patient.updated`, `appointment.created`
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// ID of the changed entity
+    pub id: i64,
+    /// RFC 3339 timestamp of the change
+    pub timestamp: String,
+}
+
+static CHANNEL: Lazy<broadcast::Sender<ChangeEvent>> =
+    Lazy::new(|| broadcast::channel(CHANNEL_CAPACITY).0);
+
+/// Listener for the host's event stream (Client mode only)
+static REMOTE_LISTENER: Lazy<Mutex<Option<tauri::async_runtime::JoinHandle<()>>>> =
+    Lazy::new(|| Mutex::new(None));
+
+/// Publish a change; it is a no-op when nobody is listening
+pub fn publish(event_type: &str, id: i64) {
+    let _ = CHANNEL.send(ChangeEvent {
+        event_type: event_type.to_string(),
+        id,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    });
+}
+
+/// Subscribe to changes made on this node
+pub fn subscribe() -> broadcast::Receiver<ChangeEvent> {
+    CHANNEL.subscribe()
+}
+
+/// Send a change to this node's frontend
+pub fn emit_to_frontend(event: &ChangeEvent) {
+    if let Ok(guard) = global::GLOBAL_APP_HANDLE.lock() {
+        if let Some(app_handle) = guard.as_ref() {
+            let _ = app_handle.emit(FRONTEND_EVENT, event);
+        }
+    }
+}
+
+/// Forward changes made on this node (local commands and API requests) to its frontend
+pub fn start_local_forwarder() {
+    let mut receiver = subscribe();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => emit_to_frontend(&event),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Change forwarder skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// Follow the host's event stream and re-emit every change to this node's frontend
+/// Replaces any previous listener; reconnects until `stop_remote_listener` is called.
+pub fn start_remote_listener(config: ClientConfig) {
+    let handle = tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = listen_to_host(&config).await {
+                log::warn!("Host event stream interrupted: {}", e);
+            }
+            tokio::time::sleep(Duration::from_secs(RECONNECT_DELAY_SECS)).await;
+        }
+    });
+
+    if let Some(previous) = REMOTE_LISTENER.lock().unwrap().replace(handle) {
+        previous.abort();
+    }
+}
+
+/// Stop following the host's event stream
+pub fn stop_remote_listener() {
+    if let Some(handle) = REMOTE_LISTENER.lock().unwrap().take() {
+        handle.abort();
+    }
+}
+
+/// Read the SSE stream until it ends or fails
+async fn listen_to_host(config: &ClientConfig) -> Result<(), String> {
+    let client = crate::tls::client_builder(config.pinned_fingerprint.as_deref())
+        .connect_timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("Failed to create client: {}", e))?;
+
+    let url = format!("{}/api/events", config.remote_url.trim_end_matches('/'));
+    let mut response = client
+        .get(&url)
+        .bearer_auth(&config.auth_token)
+        .header(reqwest::header::ACCEPT, "text/event-stream")
+        .send()
+        .await
+        .map_err(|e| format!("Connection failed: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Server responded with status: {}", response.status()));
+    }
+
+    log::info!("Subscribed to host events at {}", url);
+
+    let mut buffer = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Stream error: {}", e))?
+    {
+        buffer.extend_from_slice(&chunk);
+
+        for block in drain_sse_blocks(&mut buffer) {
+            if let Some(event) = parse_sse_block(&block) {
+                emit_to_frontend(&event);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Take the complete SSE messages out of the bytes received so far
+/// Only complete messages are decoded, so a character split across chunks stays intact.
+fn drain_sse_blocks(buffer: &mut Vec<u8>) -> Vec<String> {
+    // CRLF line endings; `\r` never appears inside a multi-byte UTF-8 character
+    buffer.retain(|&byte| byte != b'\r');
+
+    let mut blocks = Vec::new();
+    while let Some(end) = buffer.windows(2).position(|pair| pair == b"\n\n") {
+        let block: Vec<u8> = buffer.drain(..end + 2).collect();
+        blocks.push(String::from_utf8_lossy(&block).into_owned());
+    }
+    blocks
+}
+
+/// Parse one SSE message (`event:`/`data:` lines); comments and keep-alives yield `None`
+fn parse_sse_block(block: &str) -> Option<ChangeEvent> {
+    let data: Vec<&str> = block
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|value| value.strip_prefix(' ').unwrap_or(value))
+        .collect();
+
+    if data.is_empty() {
+        return None;
+    }
+
+    serde_json::from_str(&data.join("\n")).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sse_messages() {
+        let block = "event: patient.updated\ndata: {\"type\":\"patient.updated\",\"id\":7,\"timestamp\":\"2024-01-01T00:00:00Z\"}\n\n";
+        let event = parse_sse_block(block).unwrap();
+        assert_eq!(event.event_type, "patient.updated");
+        assert_eq!(event.id, 7);
+
+        assert!(parse_sse_block(": keep-alive\n\n").is_none());
+    }
+
+    #[test]
+    fn keeps_characters_split_across_chunks() {
+        let message =
+            "data: {\"type\":\"odontograma.actualización\",\"id\":7,\"timestamp\":\"\"}\r\n\r\n";
+        let split = message.find('ó').unwrap() + 1;
+
+        let mut buffer = message.as_bytes()[..split].to_vec();
+        assert!(drain_sse_blocks(&mut buffer).is_empty());
+        buffer.extend_from_slice(&message.as_bytes()[split..]);
+
+        let blocks = drain_sse_blocks(&mut buffer);
+        assert_eq!(blocks.len(), 1);
+        let event = parse_sse_block(&blocks[0]).unwrap();
+        assert_eq!(event.event_type, "odontograma.actualización");
+        assert!(buffer.is_empty());
+    }
+}
@@ -0,0 +1,358 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rusqlite::{params_from_iter, types::Value, Connection};
+use serde::{Deserialize, Serialize};
+
+pub const OP_CREATE_FOLDER: &str = "create_folder";
+pub const OP_RENAME: &str = "rename";
+pub const OP_MOVE: &str = "move";
+pub const OP_DELETE: &str = "delete";
+pub const OP_TRASH: &str = "trash";
+pub const OP_RESTORE: &str = "restore";
+pub const OP_PURGE_TRASH: &str = "purge_trash";
+pub const OP_UPLOAD: &str = "upload";
+pub const OP_DOWNLOAD: &str = "download";
+pub const OP_LINK_PATIENT: &str = "link_patient";
+pub const OP_UNLINK_PATIENT: &str = "unlink_patient";
+pub const OP_GRANT_ACCESS: &str = "grant_access";
+pub const OP_REVOKE_ACCESS: &str = "revoke_access";
+pub const OP_SET_QUOTA: &str = "set_quota";
+pub const OP_LOCK: &str = "lock";
+pub const OP_UNLOCK: &str = "unlock";
+pub const OP_FORCE_UNLOCK: &str = "force_unlock";
+pub const OP_RESTORE_VERSION: &str = "restore_version";
+pub const OP_ENCRYPTION: &str = "encryption";
+
+/// Entries returned by a query when no limit is given.
+const DEFAULT_QUERY_LIMIT: i64 = 500;
+
+/// Largest number of entries a single query (or CSV export) can return.
+const MAX_QUERY_LIMIT: i64 = 50_000;
+
+/// AuditLog records filesystem operations in `filesystem_audit_log`.
+///
+/// Every operation performed through `FileOperations` is recorded, whether it
+/// succeeded or not. Writing the log never makes an operation fail: errors are
+/// only logged.
+#[derive(Debug, Clone)]
+pub struct AuditLog;
+
+/// A recorded filesystem operation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub timestamp: String,
+    pub username: String,
+    pub operation: String,
+    pub path: String,
+    /// Destination of renames, moves and restores
+    pub target_path: Option<String>,
+    pub success: bool,
+    pub error_message: Option<String>,
+    /// JSON with operation specific details (patient ID, granted permissions, ...)
+    pub metadata: Option<String>,
+}
+
+/// Filters for querying the audit log. Every field is optional.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditLogFilter {
+    pub username: Option<String>,
+    /// Matches entries whose path or target path is this path or is below it
+    pub path_prefix: Option<String>,
+    pub operation: Option<String>,
+    /// Start of the range (RFC 3339 timestamp or YYYY-MM-DD), inclusive
+    pub from: Option<String>,
+    /// End of the range (RFC 3339 timestamp or YYYY-MM-DD), a whole day when only a date
+    pub to: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+const CSV_HEADER: &str =
+    "id,timestamp,username,operation,path,target_path,success,error_message,metadata";
+
+impl AuditLog {
+    /// Creates a new AuditLog instance.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Records the outcome of an operation.
+    ///
+    /// # Arguments
+    /// - `username`: The user who performed the operation
+    /// - `operation`: One of the `OP_*` constants
+    /// - `path`: The virtual path the operation acted on
+    /// - `target_path`: The destination path, for operations that have one
+    /// - `metadata`: Optional JSON with extra details
+    /// - `result`: The result of the operation
+    pub fn record<T>(
+        &self,
+        username: &str,
+        operation: &str,
+        path: &str,
+        target_path: Option<&str>,
+        metadata: Option<&str>,
+        result: &Result<T, String>,
+    ) {
+        let error_message = result.as_ref().err().map(String::as_str);
+
+        let recorded = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))
+            .and_then(|conn| {
+                conn.execute(
+                    r#"
+                    INSERT INTO filesystem_audit_log (
+                        timestamp, username, operation, virtual_path, target_path,
+                        success, error_message, metadata
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                    "#,
+                    rusqlite::params![
+                        Utc::now().to_rfc3339(),
+                        username,
+                        operation,
+                        path,
+                        target_path,
+                        result.is_ok(),
+                        error_message,
+                        metadata,
+                    ],
+                )
+                .map_err(|e| format!("Failed to insert audit entry: {}", e))
+            });
+
+        if let Err(e) = recorded {
+            log::error!(
+                "Failed to record filesystem audit entry ({} '{}'): {}",
+                operation,
+                path,
+                e
+            );
+        }
+    }
+
+    /// Queries the audit log, most recent entries first.
+    ///
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `filter`: The filters to apply
+    ///
+    /// # Returns
+    /// - `Ok(Vec<AuditLogEntry>)` containing the matching entries
+    /// - `Err(String)` if a date is invalid or the query fails
+    pub fn query(
+        &self,
+        conn: &Connection,
+        filter: &AuditLogFilter,
+    ) -> Result<Vec<AuditLogEntry>, String> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+
+        if let Some(username) = &filter.username {
+            values.push(Value::Text(username.clone()));
+            conditions.push(format!("username = ?{}", values.len()));
+        }
+        if let Some(operation) = &filter.operation {
+            values.push(Value::Text(operation.clone()));
+            conditions.push(format!("operation = ?{}", values.len()));
+        }
+        if let Some(prefix) = &filter.path_prefix {
+            values.push(Value::Text(prefix.trim_end_matches('\\').to_string()));
+            let n = values.len();
+            conditions.push(format!(
+                "(virtual_path = ?{n} OR substr(virtual_path, 1, length(?{n}) + 1) = ?{n} || '\\' \
+                 OR target_path = ?{n} OR substr(target_path, 1, length(?{n}) + 1) = ?{n} || '\\')"
+            ));
+        }
+        if let Some(from) = &filter.from {
+            values.push(Value::Text(parse_bound(from, false)?));
+            conditions.push(format!("timestamp >= ?{}", values.len()));
+        }
+        if let Some(to) = &filter.to {
+            values.push(Value::Text(parse_bound(to, true)?));
+            conditions.push(format!("timestamp < ?{}", values.len()));
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        values.push(Value::Integer(
+            filter
+                .limit
+                .unwrap_or(DEFAULT_QUERY_LIMIT)
+                .clamp(1, MAX_QUERY_LIMIT),
+        ));
+        values.push(Value::Integer(filter.offset.unwrap_or(0).max(0)));
+
+        let query = format!(
+            r#"
+            SELECT id, timestamp, username, operation, virtual_path, target_path,
+                   success, error_message, metadata
+            FROM filesystem_audit_log
+            {}
+            ORDER BY timestamp DESC, id DESC
+            LIMIT ?{} OFFSET ?{}
+            "#,
+            where_clause,
+            values.len() - 1,
+            values.len()
+        );
+
+        let mut stmt = conn
+            .prepare(&query)
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let entries = stmt
+            .query_map(params_from_iter(values.iter()), |row| {
+                Ok(AuditLogEntry {
+                    id: row.get(0)?,
+                    timestamp: row.get(1)?,
+                    username: row.get(2)?,
+                    operation: row.get(3)?,
+                    path: row.get(4)?,
+                    target_path: row.get(5)?,
+                    success: row.get(6)?,
+                    error_message: row.get(7)?,
+                    metadata: row.get(8)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query audit log: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to collect audit entries: {}", e))?;
+
+        Ok(entries)
+    }
+
+    /// Exports the matching entries as CSV, most recent first.
+    ///
+    /// Without a limit in the filter, up to the maximum query size is exported.
+    ///
+    /// # Returns
+    /// - `Ok(String)` containing the CSV document, with a header row
+    /// - `Err(String)` if a date is invalid or the query fails
+    pub fn export_csv(&self, conn: &Connection, filter: &AuditLogFilter) -> Result<String, String> {
+        let filter = AuditLogFilter {
+            limit: Some(filter.limit.unwrap_or(MAX_QUERY_LIMIT)),
+            ..filter.clone()
+        };
+
+        Ok(self.to_csv(&self.query(conn, &filter)?))
+    }
+
+    /// Formats audit entries as CSV, with a header row.
+    fn to_csv(&self, entries: &[AuditLogEntry]) -> String {
+        let mut csv = String::from(CSV_HEADER);
+        csv.push_str("\r\n");
+
+        for entry in entries {
+            let fields = [
+                entry.id.to_string(),
+                entry.timestamp.clone(),
+                entry.username.clone(),
+                entry.operation.clone(),
+                entry.path.clone(),
+                entry.target_path.clone().unwrap_or_default(),
+                entry.success.to_string(),
+                entry.error_message.clone().unwrap_or_default(),
+                entry.metadata.clone().unwrap_or_default(),
+            ];
+            let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&row.join(","));
+            csv.push_str("\r\n");
+        }
+
+        csv
+    }
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Converts a filter date to the RFC 3339 UTC format timestamps are stored in.
+///
+/// A plain date is the start of that day, or the start of the next day for the
+/// (exclusive) end of a range, so `to` includes the whole day.
+pub(super) fn parse_bound(value: &str, end_of_range: bool) -> Result<String, String> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc).to_rfc3339());
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date '{}': expected YYYY-MM-DD or RFC 3339", value))?;
+    let date = if end_of_range {
+        date + Duration::days(1)
+    } else {
+        date
+    };
+
+    Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc().to_rfc3339())
+}
+
+/// Quotes a CSV field when needed.
+///
+/// Fields starting with a formula character are prefixed with a quote so
+/// spreadsheets don't evaluate them.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bound() {
+        assert_eq!(
+            parse_bound("2026-03-01", false).unwrap(),
+            "2026-03-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            parse_bound("2026-03-01", true).unwrap(),
+            "2026-03-02T00:00:00+00:00"
+        );
+        assert_eq!(
+            parse_bound("2026-03-01T10:00:00-03:00", false).unwrap(),
+            "2026-03-01T13:00:00+00:00"
+        );
+        assert!(parse_bound("01/03/2026", false).is_err());
+    }
+
+    #[test]
+    fn test_to_csv_escapes_fields() {
+        let entry = AuditLogEntry {
+            id: 7,
+            timestamp: "2026-03-01T10:00:00+00:00".to_string(),
+            username: "dra.perez".to_string(),
+            operation: OP_RENAME.to_string(),
+            path: "G:\\Users\\dra.perez\\a,b.pdf".to_string(),
+            target_path: Some("=cmd.pdf".to_string()),
+            success: false,
+            error_message: Some("Entry with name \"x\" already exists".to_string()),
+            metadata: None,
+        };
+
+        let csv = AuditLog::new().to_csv(&[entry]);
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            "7,2026-03-01T10:00:00+00:00,dra.perez,rename,\"G:\\Users\\dra.perez\\a,b.pdf\",'=cmd.pdf,false,\"Entry with name \"\"x\"\" already exists\","
+        );
+    }
+}
@@ -1,7 +1,18 @@
 use serde::{Deserialize, Serialize};
+use tauri::ipc::{InvokeBody, Request, Response};
 use tauri::State;
 
-use super::{DirectoryListing, FileEntry, FileOperations};
+use super::{
+    AuditLogEntry, AuditLogFilter, DicomTags, DirectoryListing, EncryptionStatus, FileEntry,
+    FileLock, FileOperations, FilePermissions, FileVersion, KeySource, PatientFile,
+    PatientFileLink, SearchQuery, SearchResult, StorageQuota, TrashEntry, UploadSession,
+};
+
+/// Header carrying the upload ID of a raw chunk sent to `fs_upload_chunk`
+const UPLOAD_ID_HEADER: &str = "x-upload-id";
+
+/// Header carrying the byte offset of a raw chunk sent to `fs_upload_chunk`
+const UPLOAD_OFFSET_HEADER: &str = "x-upload-offset";
 
 /// Shared state for filesystem operations.
 pub struct FilesystemState {
@@ -17,12 +28,11 @@ impl FilesystemState {
 }
 
 /// Gets the current username from the session.
-/// Returns "system" if no session is active (for initialization tasks).
-fn get_current_username() -> String {
-    match crate::session::get_session() {
-        Ok(Some(session)) => session.user.username,
-        _ => "system".to_string(),
-    }
+/// Fails when no session is active; the system identity is reserved for internal tasks.
+fn get_current_username() -> Result<String, String> {
+    crate::session::get_session()?
+        .map(|session| session.user.username)
+        .ok_or_else(|| "No active session".to_string())
 }
 
 /// Lists all entries in a directory.
@@ -39,7 +49,7 @@ pub async fn fs_list_directory(
     virtual_path: String,
     state: State<'_, FilesystemState>,
 ) -> Result<DirectoryListing, String> {
-    let username = get_current_username();
+    let username = get_current_username()?;
     
     state
         .operations
@@ -63,7 +73,7 @@ pub async fn fs_create_folder(
     folder_name: String,
     state: State<'_, FilesystemState>,
 ) -> Result<FileEntry, String> {
-    let username = get_current_username();
+    let username = get_current_username()?;
     
     state
         .operations
@@ -85,7 +95,7 @@ pub async fn fs_delete_entry(
     virtual_path: String,
     state: State<'_, FilesystemState>,
 ) -> Result<(), String> {
-    let username = get_current_username();
+    let username = get_current_username()?;
     
     state
         .operations
@@ -109,7 +119,7 @@ pub async fn fs_rename_entry(
     new_name: String,
     state: State<'_, FilesystemState>,
 ) -> Result<FileEntry, String> {
-    let username = get_current_username();
+    let username = get_current_username()?;
     
     state
         .operations
@@ -133,7 +143,7 @@ pub async fn fs_move_entry(
     dest_path: String,
     state: State<'_, FilesystemState>,
 ) -> Result<FileEntry, String> {
-    let username = get_current_username();
+    let username = get_current_username()?;
     
     state
         .operations
@@ -141,7 +151,10 @@ pub async fn fs_move_entry(
         .await
 }
 
-/// Uploads a file to the filesystem.
+/// Uploads a small file to the filesystem in a single call.
+///
+/// The content travels through IPC as one array, so larger files such as X-rays and
+/// scans should use `fs_begin_upload`, `fs_upload_chunk` and `fs_finish_upload`.
 ///
 /// # Arguments
 /// - `virtual_dir`: The directory to upload to
@@ -157,30 +170,329 @@ pub async fn fs_upload_file(
     virtual_dir: String,
     file_data: Vec<u8>,
     filename: String,
-    _state: State<'_, FilesystemState>,
+    state: State<'_, FilesystemState>,
+) -> Result<FileEntry, String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .upload_file(&virtual_dir, &filename, &file_data, &username)
+        .await
+}
+
+/// Starts a chunked upload.
+///
+/// # Arguments
+/// - `virtual_dir`: The directory to upload to
+/// - `filename`: The name of the file
+/// - `total_size`: The size of the file in bytes
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(UploadSession)` with the upload ID and preferred chunk size
+/// - `Err(String)` if permission is denied or the file type is not allowed
+#[tauri::command]
+pub async fn fs_begin_upload(
+    virtual_dir: String,
+    filename: String,
+    total_size: u64,
+    state: State<'_, FilesystemState>,
+) -> Result<UploadSession, String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .begin_upload(&virtual_dir, &filename, total_size, &username)
+        .await
+}
+
+/// Starts a chunked upload that overwrites an existing file.
+///
+/// The current content is kept as a previous version. Chunks are sent with
+/// `fs_upload_chunk` and the upload completed with `fs_finish_upload`.
+///
+/// # Arguments
+/// - `virtual_path`: The path of the file to overwrite
+/// - `total_size`: The size of the new content in bytes
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(UploadSession)` containing the upload ID and chunk size
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_begin_overwrite(
+    virtual_path: String,
+    total_size: u64,
+    state: State<'_, FilesystemState>,
+) -> Result<UploadSession, String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .begin_overwrite(&virtual_path, total_size, &username)
+        .await
+}
+
+/// Receives one chunk of an upload as a raw binary body.
+///
+/// The upload ID and offset are sent in the `x-upload-id` and `x-upload-offset`
+/// headers so the chunk itself is never serialized as JSON.
+///
+/// # Arguments
+/// - `request`: The raw IPC request
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(u64)` containing the bytes received so far
+/// - `Err(String)` if the chunk is rejected; the upload is discarded
+#[tauri::command]
+pub async fn fs_upload_chunk(
+    request: Request<'_>,
+    state: State<'_, FilesystemState>,
+) -> Result<u64, String> {
+    let username = get_current_username()?;
+
+    let header = |name: &str| {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| format!("Missing '{}' header", name))
+    };
+    let upload_id = header(UPLOAD_ID_HEADER)?;
+    let offset: u64 = header(UPLOAD_OFFSET_HEADER)?
+        .parse()
+        .map_err(|_| format!("Invalid '{}' header", UPLOAD_OFFSET_HEADER))?;
+
+    let InvokeBody::Raw(data) = request.body() else {
+        return Err("Upload chunks must be sent as raw binary data".to_string());
+    };
+
+    state
+        .operations
+        .upload_chunk(upload_id, offset, data, &username)
+        .await
+}
+
+/// Completes a chunked upload and records the file metadata.
+///
+/// # Arguments
+/// - `upload_id`: The ID returned by `fs_begin_upload`
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(FileEntry)` containing the uploaded file metadata
+/// - `Err(String)` if the upload is incomplete or fails
+#[tauri::command]
+pub async fn fs_finish_upload(
+    upload_id: String,
+    state: State<'_, FilesystemState>,
 ) -> Result<FileEntry, String> {
-    // TODO: Implement file upload
-    // This will be implemented in task 7
-    Err("File upload not yet implemented".to_string())
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .finish_upload(&upload_id, &username)
+        .await
+}
+
+/// Cancels a chunked upload and deletes the data received so far.
+///
+/// # Arguments
+/// - `upload_id`: The ID returned by `fs_begin_upload`
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(())` if successful
+/// - `Err(String)` if the upload doesn't exist
+#[tauri::command]
+pub async fn fs_cancel_upload(
+    upload_id: String,
+    state: State<'_, FilesystemState>,
+) -> Result<(), String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .cancel_upload(&upload_id, &username)
+        .await
 }
 
-/// Downloads a file from the filesystem.
+/// Downloads a whole file from the filesystem.
+///
+/// The content is returned as a raw binary response (an `ArrayBuffer` in the frontend).
+/// Large files should be read with `fs_download_chunk`.
 ///
 /// # Arguments
 /// - `virtual_path`: The path of the file to download
 /// - `state`: Shared filesystem state
 ///
 /// # Returns
-/// - `Ok(Vec<u8>)` containing the file content
+/// - `Ok(Response)` containing the file content
 /// - `Err(String)` if the operation fails
 #[tauri::command]
 pub async fn fs_download_file(
     virtual_path: String,
-    _state: State<'_, FilesystemState>,
-) -> Result<Vec<u8>, String> {
-    // TODO: Implement file download
-    // This will be implemented in task 7
-    Err("File download not yet implemented".to_string())
+    state: State<'_, FilesystemState>,
+) -> Result<Response, String> {
+    let username = get_current_username()?;
+
+    let data = state
+        .operations
+        .download_file(&virtual_path, &username)
+        .await?;
+
+    Ok(Response::new(data))
+}
+
+/// Downloads part of a file as a raw binary response.
+///
+/// # Arguments
+/// - `virtual_path`: The path of the file to download
+/// - `offset`: Byte position to start reading from
+/// - `length`: Maximum number of bytes to return
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(Response)` containing the chunk (empty past the end of the file)
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_download_chunk(
+    virtual_path: String,
+    offset: u64,
+    length: usize,
+    state: State<'_, FilesystemState>,
+) -> Result<Response, String> {
+    let username = get_current_username()?;
+
+    let data = state
+        .operations
+        .read_file_chunk(&virtual_path, offset, length, &username)
+        .await?;
+
+    Ok(Response::new(data))
+}
+
+/// Lists the previous versions of a file, newest first.
+///
+/// # Arguments
+/// - `virtual_path`: The path of the file
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(Vec<FileVersion>)` containing the versions
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_list_versions(
+    virtual_path: String,
+    state: State<'_, FilesystemState>,
+) -> Result<Vec<FileVersion>, String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .list_versions(&virtual_path, &username)
+        .await
+}
+
+/// Downloads part of a previous version of a file as a raw binary response.
+///
+/// # Arguments
+/// - `virtual_path`: The path of the file
+/// - `version`: The version number
+/// - `offset`: Byte position to start reading from
+/// - `length`: Maximum number of bytes to return
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(Response)` containing the chunk (empty past the end of the version)
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_download_version(
+    virtual_path: String,
+    version: i64,
+    offset: u64,
+    length: usize,
+    state: State<'_, FilesystemState>,
+) -> Result<Response, String> {
+    let username = get_current_username()?;
+
+    let data = state
+        .operations
+        .read_version_chunk(&virtual_path, version, offset, length, &username)
+        .await?;
+
+    Ok(Response::new(data))
+}
+
+/// Restores a previous version of a file, keeping the current content as a new version.
+///
+/// # Arguments
+/// - `virtual_path`: The path of the file
+/// - `version`: The version number to restore
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(FileEntry)` containing the updated file metadata
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_restore_version(
+    virtual_path: String,
+    version: i64,
+    state: State<'_, FilesystemState>,
+) -> Result<FileEntry, String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .restore_version(&virtual_path, version, &username)
+        .await
+}
+
+/// Gets the thumbnail of an image or DICOM file as a raw PNG response.
+///
+/// # Arguments
+/// - `virtual_path`: The path of the file
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(Response)` containing the PNG thumbnail
+/// - `Err(String)` if the operation fails or the file has no preview
+#[tauri::command]
+pub async fn fs_get_thumbnail(
+    virtual_path: String,
+    state: State<'_, FilesystemState>,
+) -> Result<Response, String> {
+    let username = get_current_username()?;
+
+    let data = state
+        .operations
+        .get_thumbnail(&virtual_path, &username)
+        .await?;
+
+    Ok(Response::new(data))
+}
+
+/// Gets the basic tags (patient name, study date, modality) of a DICOM file.
+///
+/// # Arguments
+/// - `virtual_path`: The path of the DICOM file
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(DicomTags)` containing the tags
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_get_dicom_tags(
+    virtual_path: String,
+    state: State<'_, FilesystemState>,
+) -> Result<DicomTags, String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .get_dicom_tags(&virtual_path, &username)
+        .await
 }
 
 /// Gets metadata for a file or folder.
@@ -195,46 +507,219 @@ pub async fn fs_download_file(
 #[tauri::command]
 pub async fn fs_get_metadata(
     virtual_path: String,
-    _state: State<'_, FilesystemState>,
+    state: State<'_, FilesystemState>,
 ) -> Result<FileMetadata, String> {
-    // TODO: Implement get metadata
-    // This will be implemented in task 7
-    Err("Get metadata not yet implemented".to_string())
+    let username = get_current_username()?;
+
+    let entry = state
+        .operations
+        .get_entry(&virtual_path, &username)
+        .await?;
+
+    Ok(FileMetadata {
+        file_type: entry.entry_type.to_db_string().to_string(),
+        name: entry.name,
+        size: entry.size,
+        mime_type: entry.mime_type,
+        created_at: entry.created_at,
+        modified_at: entry.modified_at,
+        owner: entry.owner_username,
+    })
 }
 
-/// Searches for files and folders.
+/// Searches for files and folders the current user can read.
 ///
 /// # Arguments
-/// - `query`: The search query
-/// - `root_path`: The root path to search from
+/// - `query`: The search criteria (text in names and contents, type, owner, dates, patient)
 /// - `state`: Shared filesystem state
 ///
 /// # Returns
-/// - `Ok(Vec<FileEntry>)` containing matching entries
+/// - `Ok(Vec<SearchResult>)` containing matching entries, most recently modified first
 /// - `Err(String)` if the operation fails
 #[tauri::command]
 pub async fn fs_search(
-    query: String,
-    root_path: String,
-    _state: State<'_, FilesystemState>,
-) -> Result<Vec<FileEntry>, String> {
-    // TODO: Implement search
-    // This will be implemented in task 12
-    Err("Search not yet implemented".to_string())
+    query: SearchQuery,
+    state: State<'_, FilesystemState>,
+) -> Result<Vec<SearchResult>, String> {
+    let username = get_current_username()?;
+
+    state.operations.search(&query, &username).await
 }
 
 /// Gets storage quota information for the current user.
 ///
+/// # Arguments
+/// - `username`: The user to get the quota of (admins only); defaults to the current user
+/// - `state`: Shared filesystem state
+///
 /// # Returns
 /// - `Ok(StorageQuota)` containing quota information
 /// - `Err(String)` if the operation fails
 #[tauri::command]
 pub async fn fs_get_storage_quota(
-    _state: State<'_, FilesystemState>,
+    username: Option<String>,
+    state: State<'_, FilesystemState>,
 ) -> Result<StorageQuota, String> {
-    // TODO: Implement storage quota
-    // This will be implemented in task 8
-    Err("Storage quota not yet implemented".to_string())
+    let current_username = get_current_username()?;
+
+    state
+        .operations
+        .get_storage_quota(username.as_deref(), &current_username)
+        .await
+}
+
+/// Sets the storage quota of a user (admins only).
+///
+/// # Arguments
+/// - `username`: The user to set the quota of
+/// - `quota_bytes`: The quota in bytes, or `None` to use the default quota
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(StorageQuota)` containing the updated quota information
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_set_storage_quota(
+    username: String,
+    quota_bytes: Option<i64>,
+    state: State<'_, FilesystemState>,
+) -> Result<StorageQuota, String> {
+    let current_username = get_current_username()?;
+
+    state
+        .operations
+        .set_storage_quota(&username, quota_bytes, &current_username)
+        .await
+}
+
+/// Gets the state of file encryption (admins only).
+///
+/// # Arguments
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(EncryptionStatus)` containing the state
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_get_encryption_status(
+    state: State<'_, FilesystemState>,
+) -> Result<EncryptionStatus, String> {
+    let username = get_current_username()?;
+
+    state.operations.get_encryption_status(&username).await
+}
+
+/// Sets up file encryption (admins only).
+///
+/// # Arguments
+/// - `key_source`: Whether the keys are protected with the system password or a key file
+/// - `system_password`: The system password, required for the `password` source
+/// - `key_file_path`: The key file, required for the `key_file` source
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(EncryptionStatus)` containing the new state
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_setup_encryption(
+    key_source: KeySource,
+    system_password: Option<String>,
+    key_file_path: Option<String>,
+    state: State<'_, FilesystemState>,
+) -> Result<EncryptionStatus, String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .setup_encryption(
+            key_source,
+            system_password.as_deref(),
+            key_file_path.as_deref(),
+            &username,
+        )
+        .await
+}
+
+/// Unlocks file encryption with the system password (admins only).
+///
+/// # Arguments
+/// - `system_password`: The system password
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(EncryptionStatus)` containing the new state
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_unlock_encryption(
+    system_password: String,
+    state: State<'_, FilesystemState>,
+) -> Result<EncryptionStatus, String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .unlock_encryption(&system_password, &username)
+        .await
+}
+
+/// Rotates the file encryption key (admins only).
+///
+/// Existing files are re-encrypted with the new key in the background.
+///
+/// # Arguments
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(EncryptionStatus)` containing the new state
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_rotate_encryption_key(
+    state: State<'_, FilesystemState>,
+) -> Result<EncryptionStatus, String> {
+    let username = get_current_username()?;
+
+    let status = state.operations.rotate_encryption_key(&username).await?;
+
+    let operations = state.operations.clone();
+    tauri::async_runtime::spawn(async move {
+        match operations.reencrypt_files().await {
+            Ok(count) => log::info!("Re-encrypted {} files with the new key", count),
+            Err(e) => log::warn!("Failed to re-encrypt files: {}", e),
+        }
+    });
+
+    Ok(status)
+}
+
+/// Protects the file encryption keys with a different secret (admins only).
+///
+/// # Arguments
+/// - `key_source`: Whether the keys are protected with the system password or a key file
+/// - `system_password`: The system password, required for the `password` source
+/// - `key_file_path`: The key file, required for the `key_file` source
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(EncryptionStatus)` containing the new state
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_change_encryption_key_source(
+    key_source: KeySource,
+    system_password: Option<String>,
+    key_file_path: Option<String>,
+    state: State<'_, FilesystemState>,
+) -> Result<EncryptionStatus, String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .change_encryption_key_source(
+            key_source,
+            system_password.as_deref(),
+            key_file_path.as_deref(),
+            &username,
+        )
+        .await
 }
 
 /// Links a file to a patient record.
@@ -242,20 +727,49 @@ pub async fn fs_get_storage_quota(
 /// # Arguments
 /// - `file_path`: The path of the file
 /// - `patient_id`: The ID of the patient
+/// - `notes`: Optional notes about the document
 /// - `state`: Shared filesystem state
 ///
 /// # Returns
-/// - `Ok(())` if successful
+/// - `Ok(PatientFileLink)` containing the link
 /// - `Err(String)` if the operation fails
 #[tauri::command]
 pub async fn fs_link_to_patient(
     file_path: String,
     patient_id: i64,
-    _state: State<'_, FilesystemState>,
+    notes: Option<String>,
+    state: State<'_, FilesystemState>,
+) -> Result<PatientFileLink, String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .link_to_patient(&file_path, patient_id, notes.as_deref(), &username)
+        .await
+}
+
+/// Removes the link between a file and a patient record.
+///
+/// # Arguments
+/// - `file_path`: The path of the file
+/// - `patient_id`: The ID of the patient
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(())` if successful
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_unlink_from_patient(
+    file_path: String,
+    patient_id: i64,
+    state: State<'_, FilesystemState>,
 ) -> Result<(), String> {
-    // TODO: Implement patient linking
-    // This will be implemented in task 13
-    Err("Patient linking not yet implemented".to_string())
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .unlink_from_patient(&file_path, patient_id, &username)
+        .await
 }
 
 /// Gets all files linked to a patient.
@@ -265,37 +779,113 @@ pub async fn fs_link_to_patient(
 /// - `state`: Shared filesystem state
 ///
 /// # Returns
-/// - `Ok(Vec<FileEntry>)` containing linked files
+/// - `Ok(Vec<PatientFile>)` containing linked files the user can read
 /// - `Err(String)` if the operation fails
 #[tauri::command]
 pub async fn fs_get_patient_files(
     patient_id: i64,
-    _state: State<'_, FilesystemState>,
-) -> Result<Vec<FileEntry>, String> {
-    // TODO: Implement get patient files
-    // This will be implemented in task 13
-    Err("Get patient files not yet implemented".to_string())
+    state: State<'_, FilesystemState>,
+) -> Result<Vec<PatientFile>, String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .get_patient_files(patient_id, &username)
+        .await
 }
 
 /// Gets audit log entries.
 ///
 /// # Arguments
-/// - `limit`: Maximum number of entries to return
-/// - `offset`: Number of entries to skip
+/// - `filter`: Filters by user, path prefix, operation, date range and page
 /// - `state`: Shared filesystem state
 ///
 /// # Returns
-/// - `Ok(Vec<AuditLogEntry>)` containing log entries
+/// - `Ok(Vec<AuditLogEntry>)` containing log entries, most recent first
 /// - `Err(String)` if the operation fails
 #[tauri::command]
 pub async fn fs_get_audit_logs(
-    limit: Option<i64>,
-    offset: Option<i64>,
-    _state: State<'_, FilesystemState>,
+    filter: Option<AuditLogFilter>,
+    state: State<'_, FilesystemState>,
 ) -> Result<Vec<AuditLogEntry>, String> {
-    // TODO: Implement audit logs
-    // This will be implemented in task 9
-    Err("Audit logs not yet implemented".to_string())
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .get_audit_logs(&filter.unwrap_or_default(), &username)
+        .await
+}
+
+/// Exports audit log entries as CSV.
+///
+/// # Arguments
+/// - `filter`: Filters by user, path prefix, operation and date range
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(String)` containing the CSV document
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_export_audit_logs(
+    filter: Option<AuditLogFilter>,
+    state: State<'_, FilesystemState>,
+) -> Result<String, String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .export_audit_logs(&filter.unwrap_or_default(), &username)
+        .await
+}
+
+/// Grants another user access to a file or folder.
+///
+/// # Arguments
+/// - `virtual_path`: The path of the entry
+/// - `grantee`: The username of the user receiving access
+/// - `permissions`: The permissions to grant
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(())` if successful
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_grant_access(
+    virtual_path: String,
+    grantee: String,
+    permissions: FilePermissions,
+    state: State<'_, FilesystemState>,
+) -> Result<(), String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .grant_access(&virtual_path, &grantee, permissions, &username)
+        .await
+}
+
+/// Revokes the access another user was granted to a file or folder.
+///
+/// # Arguments
+/// - `virtual_path`: The path of the entry
+/// - `grantee`: The username of the user losing access
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(())` if successful
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_revoke_access(
+    virtual_path: String,
+    grantee: String,
+    state: State<'_, FilesystemState>,
+) -> Result<(), String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .revoke_access(&virtual_path, &grantee, &username)
+        .await
 }
 
 /// Moves a file or folder to trash.
@@ -305,23 +895,36 @@ pub async fn fs_get_audit_logs(
 /// - `state`: Shared filesystem state
 ///
 /// # Returns
-/// - `Ok(())` if successful
+/// - `Ok(TrashEntry)` containing the trash record
 /// - `Err(String)` if the operation fails
 #[tauri::command]
 pub async fn fs_move_to_trash(
     virtual_path: String,
     state: State<'_, FilesystemState>,
-) -> Result<(), String> {
-    // For now, this just deletes the entry
-    // Full trash implementation will be in task 11
-    let username = get_current_username();
-    
+) -> Result<TrashEntry, String> {
+    let username = get_current_username()?;
+
     state
         .operations
-        .delete_entry(&virtual_path, &username)
+        .move_to_trash(&virtual_path, &username)
         .await
 }
 
+/// Lists the entries in the trash visible to the current user.
+///
+/// # Arguments
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(Vec<TrashEntry>)` most recently deleted first
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_list_trash(state: State<'_, FilesystemState>) -> Result<Vec<TrashEntry>, String> {
+    let username = get_current_username()?;
+
+    state.operations.list_trash(&username).await
+}
+
 /// Restores a file or folder from trash.
 ///
 /// # Arguments
@@ -334,25 +937,118 @@ pub async fn fs_move_to_trash(
 #[tauri::command]
 pub async fn fs_restore_from_trash(
     trash_id: i64,
-    _state: State<'_, FilesystemState>,
+    state: State<'_, FilesystemState>,
 ) -> Result<FileEntry, String> {
-    // TODO: Implement restore from trash
-    // This will be implemented in task 11
-    Err("Restore from trash not yet implemented".to_string())
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .restore_from_trash(trash_id, &username)
+        .await
 }
 
 /// Empties the trash (permanently deletes all trashed items).
 ///
 /// # Returns
-/// - `Ok(())` if successful
+/// - `Ok(usize)` containing the number of deleted items
 /// - `Err(String)` if the operation fails
 #[tauri::command]
-pub async fn fs_empty_trash(
-    _state: State<'_, FilesystemState>,
+pub async fn fs_empty_trash(state: State<'_, FilesystemState>) -> Result<usize, String> {
+    let username = get_current_username()?;
+
+    state.operations.empty_trash(&username).await
+}
+
+/// Locks a file or folder for editing, or extends the current user's lock.
+///
+/// # Arguments
+/// - `virtual_path`: The path of the entry to lock
+/// - `duration_minutes`: How long the lock lasts; defaults to 15 minutes
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(FileLock)` containing the lock
+/// - `Err(String)` if another user holds the lock or the operation fails
+#[tauri::command]
+pub async fn fs_acquire_lock(
+    virtual_path: String,
+    duration_minutes: Option<i64>,
+    state: State<'_, FilesystemState>,
+) -> Result<FileLock, String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .acquire_lock(&virtual_path, duration_minutes, &username)
+        .await
+}
+
+/// Extends a lock held by the current user.
+///
+/// # Arguments
+/// - `virtual_path`: The locked path
+/// - `duration_minutes`: How long the lock lasts from now; defaults to 15 minutes
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(FileLock)` containing the renewed lock
+/// - `Err(String)` if the user doesn't hold the lock
+#[tauri::command]
+pub async fn fs_renew_lock(
+    virtual_path: String,
+    duration_minutes: Option<i64>,
+    state: State<'_, FilesystemState>,
+) -> Result<FileLock, String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .renew_lock(&virtual_path, duration_minutes, &username)
+        .await
+}
+
+/// Releases a lock held by the current user.
+///
+/// # Arguments
+/// - `virtual_path`: The locked path
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(())` if successful
+/// - `Err(String)` if another user holds the lock
+#[tauri::command]
+pub async fn fs_release_lock(
+    virtual_path: String,
+    state: State<'_, FilesystemState>,
 ) -> Result<(), String> {
-    // TODO: Implement empty trash
-    // This will be implemented in task 11
-    Err("Empty trash not yet implemented".to_string())
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .release_lock(&virtual_path, &username)
+        .await
+}
+
+/// Releases a lock held by any user (admins only).
+///
+/// # Arguments
+/// - `virtual_path`: The locked path
+/// - `state`: Shared filesystem state
+///
+/// # Returns
+/// - `Ok(Option<FileLock>)` containing the released lock, None if the path was not locked
+/// - `Err(String)` if the operation fails
+#[tauri::command]
+pub async fn fs_force_unlock(
+    virtual_path: String,
+    state: State<'_, FilesystemState>,
+) -> Result<Option<FileLock>, String> {
+    let username = get_current_username()?;
+
+    state
+        .operations
+        .force_unlock(&virtual_path, &username)
+        .await
 }
 
 /// Gets the lock status of a file.
@@ -367,15 +1063,17 @@ pub async fn fs_empty_trash(
 #[tauri::command]
 pub async fn fs_get_file_lock_status(
     virtual_path: String,
-    _state: State<'_, FilesystemState>,
+    state: State<'_, FilesystemState>,
 ) -> Result<Option<FileLock>, String> {
-    // TODO: Implement file locking
-    // This will be implemented in task 10
-    Err("File locking not yet implemented".to_string())
-}
+    let username = get_current_username()?;
 
-// Placeholder types for commands that will be implemented later
+    state
+        .operations
+        .get_lock_status(&virtual_path, &username)
+        .await
+}
 
+/// Metadata returned by `fs_get_metadata`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileMetadata {
     pub name: String,
@@ -386,30 +1084,3 @@ pub struct FileMetadata {
     pub modified_at: String,
     pub owner: String,
 }
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StorageQuota {
-    pub total_bytes: i64,
-    pub used_bytes: i64,
-    pub available_bytes: i64,
-    pub percentage_used: f64,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AuditLogEntry {
-    pub id: i64,
-    pub timestamp: String,
-    pub username: String,
-    pub operation: String,
-    pub path: String,
-    pub success: bool,
-    pub error_message: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FileLock {
-    pub file_path: String,
-    pub locked_by: String,
-    pub locked_at: String,
-    pub expires_at: String,
-}
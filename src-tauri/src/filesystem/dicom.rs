@@ -0,0 +1,681 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Read;
+
+use image::{DynamicImage, GrayImage, ImageFormat, RgbImage};
+use serde::{Deserialize, Serialize};
+
+/// MIME type of DICOM files in the allowed types whitelist.
+pub const DICOM_MIME_TYPE: &str = "application/dicom";
+
+/// Bytes read from the start of a file to get its tags without loading the pixel data.
+pub const HEADER_READ_BYTES: usize = 1024 * 1024;
+
+/// Largest size a deflated dataset may inflate to.
+const MAX_INFLATED_BYTES: u64 = 256 * 1024 * 1024;
+
+const IMPLICIT_VR_LITTLE_ENDIAN: &str = "1.2.840.10008.1.2";
+const EXPLICIT_VR_LITTLE_ENDIAN: &str = "1.2.840.10008.1.2.1";
+const DEFLATED_EXPLICIT_VR_LITTLE_ENDIAN: &str = "1.2.840.10008.1.2.1.99";
+const EXPLICIT_VR_BIG_ENDIAN: &str = "1.2.840.10008.1.2.2";
+/// JPEG Baseline and Extended (8 bit), used by most intraoral sensors and scanners
+const JPEG_TRANSFER_SYNTAXES: [&str; 2] = ["1.2.840.10008.1.2.4.50", "1.2.840.10008.1.2.4.51"];
+
+type Tag = (u16, u16);
+
+const TRANSFER_SYNTAX_UID: Tag = (0x0002, 0x0010);
+const SPECIFIC_CHARACTER_SET: Tag = (0x0008, 0x0005);
+const STUDY_DATE: Tag = (0x0008, 0x0020);
+const MODALITY: Tag = (0x0008, 0x0060);
+const STUDY_DESCRIPTION: Tag = (0x0008, 0x1030);
+const PATIENT_NAME: Tag = (0x0010, 0x0010);
+const PATIENT_ID: Tag = (0x0010, 0x0020);
+const SAMPLES_PER_PIXEL: Tag = (0x0028, 0x0002);
+const PHOTOMETRIC_INTERPRETATION: Tag = (0x0028, 0x0004);
+const PLANAR_CONFIGURATION: Tag = (0x0028, 0x0006);
+const NUMBER_OF_FRAMES: Tag = (0x0028, 0x0008);
+const ROWS: Tag = (0x0028, 0x0010);
+const COLUMNS: Tag = (0x0028, 0x0011);
+const BITS_ALLOCATED: Tag = (0x0028, 0x0100);
+const BITS_STORED: Tag = (0x0028, 0x0101);
+const PIXEL_REPRESENTATION: Tag = (0x0028, 0x0103);
+const WINDOW_CENTER: Tag = (0x0028, 0x1050);
+const WINDOW_WIDTH: Tag = (0x0028, 0x1051);
+const RESCALE_INTERCEPT: Tag = (0x0028, 0x1052);
+const RESCALE_SLOPE: Tag = (0x0028, 0x1053);
+const PIXEL_DATA: Tag = (0x7FE0, 0x0010);
+
+const ITEM: Tag = (0xFFFE, 0xE000);
+const ITEM_DELIMITATION: Tag = (0xFFFE, 0xE00D);
+const SEQUENCE_DELIMITATION: Tag = (0xFFFE, 0xE0DD);
+const UNDEFINED_LENGTH: u32 = 0xFFFF_FFFF;
+
+/// Elements kept while parsing; everything else is skipped.
+const KEPT_TAGS: [Tag; 20] = [
+    TRANSFER_SYNTAX_UID,
+    SPECIFIC_CHARACTER_SET,
+    STUDY_DATE,
+    MODALITY,
+    STUDY_DESCRIPTION,
+    PATIENT_NAME,
+    PATIENT_ID,
+    SAMPLES_PER_PIXEL,
+    PHOTOMETRIC_INTERPRETATION,
+    PLANAR_CONFIGURATION,
+    NUMBER_OF_FRAMES,
+    ROWS,
+    COLUMNS,
+    BITS_ALLOCATED,
+    BITS_STORED,
+    PIXEL_REPRESENTATION,
+    WINDOW_CENTER,
+    WINDOW_WIDTH,
+    RESCALE_INTERCEPT,
+    RESCALE_SLOPE,
+];
+
+/// Basic tags of a DICOM file, as shown next to its preview.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DicomTags {
+    /// Name components joined with spaces (family name first)
+    pub patient_name: Option<String>,
+    pub patient_id: Option<String>,
+    /// Study date as YYYY-MM-DD
+    pub study_date: Option<String>,
+    /// Acquisition modality, such as `IO` (intraoral) or `PX` (panoramic)
+    pub modality: Option<String>,
+    pub study_description: Option<String>,
+    pub rows: Option<u16>,
+    pub columns: Option<u16>,
+}
+
+/// Reads the basic tags of a DICOM file.
+///
+/// Works on the start of the file: parsing stops at the pixel data, and a
+/// truncated header returns the tags found before the end of the data.
+///
+/// # Returns
+/// - `Ok(DicomTags)` containing the tags present in the file
+/// - `Err(String)` if the data is not a DICOM file
+pub fn read_tags(data: &[u8]) -> Result<DicomTags, String> {
+    let dataset = Dataset::parse(data, false)?;
+
+    Ok(DicomTags {
+        patient_name: dataset
+            .string(PATIENT_NAME)
+            .map(|name| format_person_name(&name)),
+        patient_id: dataset.string(PATIENT_ID),
+        study_date: dataset.string(STUDY_DATE).map(|date| format_date(&date)),
+        modality: dataset.string(MODALITY),
+        study_description: dataset.string(STUDY_DESCRIPTION),
+        rows: dataset.unsigned(ROWS),
+        columns: dataset.unsigned(COLUMNS),
+    })
+}
+
+/// Renders the first frame of a DICOM file.
+///
+/// Monochrome pixel data is rescaled and windowed with the window stored in the file,
+/// or with the full range of its values when it has none. Native (uncompressed) and
+/// JPEG baseline pixel data are supported.
+///
+/// # Returns
+/// - `Ok(DynamicImage)` containing the rendered frame
+/// - `Err(String)` if the file has no pixel data or uses an unsupported encoding
+pub fn render(data: &[u8]) -> Result<DynamicImage, String> {
+    let dataset = Dataset::parse(data, true)?;
+
+    match &dataset.pixel_data {
+        Some(PixelData::Native(pixels)) => render_native(&dataset, pixels),
+        Some(PixelData::Encapsulated(fragments)) => {
+            if !JPEG_TRANSFER_SYNTAXES.contains(&dataset.transfer_syntax.as_str()) {
+                return Err(format!(
+                    "Unsupported DICOM transfer syntax: {}",
+                    dataset.transfer_syntax
+                ));
+            }
+
+            // Multi-frame files store one fragment per frame; single frames may be split
+            let frame = if dataset.integer(NUMBER_OF_FRAMES).unwrap_or(1) > 1 {
+                fragments.first().cloned().unwrap_or_default()
+            } else {
+                fragments.concat()
+            };
+
+            image::load_from_memory_with_format(&frame, ImageFormat::Jpeg)
+                .map_err(|e| format!("Failed to decode DICOM JPEG frame: {}", e))
+        }
+        None => Err("DICOM file has no pixel data".to_string()),
+    }
+}
+
+fn render_native(dataset: &Dataset, pixels: &[u8]) -> Result<DynamicImage, String> {
+    let rows = dataset
+        .unsigned(ROWS)
+        .ok_or("DICOM file has no image size")? as u32;
+    let columns = dataset
+        .unsigned(COLUMNS)
+        .ok_or("DICOM file has no image size")? as u32;
+    let samples = dataset.unsigned(SAMPLES_PER_PIXEL).unwrap_or(1);
+    let bits_allocated = dataset.unsigned(BITS_ALLOCATED).unwrap_or(8);
+    let photometric = dataset
+        .string(PHOTOMETRIC_INTERPRETATION)
+        .unwrap_or_else(|| "MONOCHROME2".to_string());
+    let pixel_count = rows as usize * columns as usize;
+
+    if samples == 3 {
+        if bits_allocated != 8 || photometric != "RGB" {
+            return Err(format!("Unsupported DICOM color encoding: {}", photometric));
+        }
+        let frame = pixels
+            .get(..pixel_count * 3)
+            .ok_or("DICOM pixel data is truncated")?;
+
+        // Planar configuration 1 stores all red values, then green, then blue
+        let rgb = if dataset.unsigned(PLANAR_CONFIGURATION) == Some(1) {
+            (0..pixel_count)
+                .flat_map(|i| [frame[i], frame[pixel_count + i], frame[2 * pixel_count + i]])
+                .collect()
+        } else {
+            frame.to_vec()
+        };
+
+        return RgbImage::from_raw(columns, rows, rgb)
+            .map(DynamicImage::ImageRgb8)
+            .ok_or_else(|| "Invalid DICOM image size".to_string());
+    }
+
+    if samples != 1 {
+        return Err(format!("Unsupported DICOM samples per pixel: {}", samples));
+    }
+
+    let bytes_per_sample = match bits_allocated {
+        8 => 1,
+        16 => 2,
+        _ => {
+            return Err(format!(
+                "Unsupported DICOM bits allocated: {}",
+                bits_allocated
+            ))
+        }
+    };
+    let frame = pixels
+        .get(..pixel_count * bytes_per_sample)
+        .ok_or("DICOM pixel data is truncated")?;
+
+    let bits_stored = dataset
+        .unsigned(BITS_STORED)
+        .unwrap_or(bits_allocated)
+        .clamp(1, 16) as u32;
+    let signed = dataset.unsigned(PIXEL_REPRESENTATION) == Some(1);
+    let slope = dataset.decimal(RESCALE_SLOPE).unwrap_or(1.0);
+    let intercept = dataset.decimal(RESCALE_INTERCEPT).unwrap_or(0.0);
+
+    let values: Vec<f64> = frame
+        .chunks_exact(bytes_per_sample)
+        .map(|sample| {
+            let raw = match sample {
+                [byte] => *byte as u32,
+                [a, b] if dataset.big_endian => u16::from_be_bytes([*a, *b]) as u32,
+                [a, b] => u16::from_le_bytes([*a, *b]) as u32,
+                _ => 0,
+            };
+            let stored = raw & ((1u32 << bits_stored) - 1);
+            let value = if signed && stored & (1 << (bits_stored - 1)) != 0 {
+                stored as i64 - (1i64 << bits_stored)
+            } else {
+                stored as i64
+            };
+            value as f64 * slope + intercept
+        })
+        .collect();
+
+    let (center, width) = match (
+        dataset.decimal(WINDOW_CENTER),
+        dataset.decimal(WINDOW_WIDTH),
+    ) {
+        (Some(center), Some(width)) if width >= 1.0 => (center, width),
+        _ => {
+            let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            ((min + max) / 2.0, (max - min).max(1.0))
+        }
+    };
+    let lower = center - width / 2.0;
+
+    // MONOCHROME1 shows the lowest values as white
+    let invert = photometric == "MONOCHROME1";
+    let gray = values
+        .iter()
+        .map(|value| {
+            let level = (((value - lower) / width).clamp(0.0, 1.0) * 255.0).round() as u8;
+            if invert {
+                255 - level
+            } else {
+                level
+            }
+        })
+        .collect();
+
+    GrayImage::from_raw(columns, rows, gray)
+        .map(DynamicImage::ImageLuma8)
+        .ok_or_else(|| "Invalid DICOM image size".to_string())
+}
+
+/// Pixel data of a DICOM file.
+enum PixelData {
+    /// Uncompressed values, frame after frame
+    Native(Vec<u8>),
+    /// Compressed fragments, without the basic offset table
+    Encapsulated(Vec<Vec<u8>>),
+}
+
+/// The elements of a DICOM file needed for its tags and preview.
+struct Dataset {
+    transfer_syntax: String,
+    big_endian: bool,
+    elements: HashMap<Tag, Vec<u8>>,
+    pixel_data: Option<PixelData>,
+}
+
+impl Dataset {
+    /// Parses a DICOM Part 10 file, or a bare dataset in implicit VR little endian.
+    fn parse(data: &[u8], with_pixels: bool) -> Result<Self, String> {
+        let mut elements = HashMap::new();
+
+        let (transfer_syntax, body) = if data.get(128..132) == Some(b"DICM".as_slice()) {
+            // The file meta information is always explicit VR little endian
+            let mut meta = Reader::new(data, 132, false, true);
+            while meta.peek_group() == Some(0x0002) {
+                if meta.read_element(&mut elements, &mut None).is_none() {
+                    break;
+                }
+            }
+
+            let transfer_syntax = elements
+                .get(&TRANSFER_SYNTAX_UID)
+                .map(|value| trim_value(&String::from_utf8_lossy(value)))
+                .unwrap_or_else(|| EXPLICIT_VR_LITTLE_ENDIAN.to_string());
+            (transfer_syntax, &data[meta.pos..])
+        } else if matches!(data.get(..2), Some([0x08, 0x00]) | Some([0x02, 0x00])) {
+            (IMPLICIT_VR_LITTLE_ENDIAN.to_string(), data)
+        } else {
+            return Err("Not a DICOM file".to_string());
+        };
+
+        let body: Cow<[u8]> = if transfer_syntax == DEFLATED_EXPLICIT_VR_LITTLE_ENDIAN {
+            Cow::Owned(inflate(body, MAX_INFLATED_BYTES)?)
+        } else {
+            Cow::Borrowed(body)
+        };
+        let big_endian = transfer_syntax == EXPLICIT_VR_BIG_ENDIAN;
+        let explicit_vr = transfer_syntax != IMPLICIT_VR_LITTLE_ENDIAN;
+
+        let mut pixel_data = None;
+        let mut reader = Reader::new(&body, 0, big_endian, explicit_vr);
+        while reader.peek_group().is_some() {
+            if reader.peek_tag() == Some(PIXEL_DATA) && !with_pixels {
+                break;
+            }
+            if reader
+                .read_element(&mut elements, &mut pixel_data)
+                .is_none()
+                || pixel_data.is_some()
+            {
+                break;
+            }
+        }
+
+        Ok(Self {
+            transfer_syntax,
+            big_endian,
+            elements,
+            pixel_data,
+        })
+    }
+
+    /// Text value, decoded with the character set of the file and without padding.
+    fn string(&self, tag: Tag) -> Option<String> {
+        let value = self.elements.get(&tag)?;
+        let utf8 = self
+            .elements
+            .get(&SPECIFIC_CHARACTER_SET)
+            .is_some_and(|charset| String::from_utf8_lossy(charset).contains("ISO_IR 192"));
+
+        let text = if utf8 {
+            String::from_utf8_lossy(value).into_owned()
+        } else {
+            // Latin-1 (ISO_IR 100) is what Spanish-language equipment writes
+            encoding_rs::WINDOWS_1252.decode(value).0.into_owned()
+        };
+
+        Some(trim_value(&text)).filter(|text| !text.is_empty())
+    }
+
+    /// First value of an unsigned short (US) element.
+    fn unsigned(&self, tag: Tag) -> Option<u16> {
+        let value = self.elements.get(&tag)?;
+        let bytes = [*value.first()?, *value.get(1)?];
+        Some(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    /// First value of a decimal string (DS) element.
+    fn decimal(&self, tag: Tag) -> Option<f64> {
+        self.string(tag)?.split('\\').next()?.trim().parse().ok()
+    }
+
+    /// First value of an integer string (IS) element.
+    fn integer(&self, tag: Tag) -> Option<i64> {
+        self.string(tag)?.split('\\').next()?.trim().parse().ok()
+    }
+}
+
+/// Reads data elements, returning `None` when the data ends in the middle of one.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    big_endian: bool,
+    explicit_vr: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], pos: usize, big_endian: bool, explicit_vr: bool) -> Self {
+        Self {
+            data,
+            pos,
+            big_endian,
+            explicit_vr,
+        }
+    }
+
+    fn peek_group(&self) -> Option<u16> {
+        self.peek_tag().map(|(group, _)| group)
+    }
+
+    fn peek_tag(&self) -> Option<Tag> {
+        let bytes = self.data.get(self.pos..self.pos + 4)?;
+        Some((
+            self.to_u16([bytes[0], bytes[1]]),
+            self.to_u16([bytes[2], bytes[3]]),
+        ))
+    }
+
+    fn bytes(&mut self, length: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(length)?)?;
+        self.pos += length;
+        Some(bytes)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        let bytes = self.bytes(2)?;
+        Some(self.to_u16([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let bytes = self.bytes(4)?;
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        Some(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    fn to_u16(&self, bytes: [u8; 2]) -> u16 {
+        if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        }
+    }
+
+    /// Reads the tag and value length of the next element.
+    fn read_header(&mut self) -> Option<(Tag, u32)> {
+        let tag = (self.u16()?, self.u16()?);
+
+        // Items and delimiters never have a VR
+        if tag.0 == 0xFFFE || !self.explicit_vr {
+            return Some((tag, self.u32()?));
+        }
+
+        let vr = self.bytes(2)?;
+        let length = match vr {
+            b"OB" | b"OD" | b"OF" | b"OL" | b"OV" | b"OW" | b"SQ" | b"SV" | b"UC" | b"UN"
+            | b"UR" | b"UT" | b"UV" => {
+                self.bytes(2)?;
+                self.u32()?
+            }
+            _ => self.u16()? as u32,
+        };
+
+        Some((tag, length))
+    }
+
+    /// Reads one element, keeping its value when it is one of `KEPT_TAGS`.
+    fn read_element(
+        &mut self,
+        elements: &mut HashMap<Tag, Vec<u8>>,
+        pixel_data: &mut Option<PixelData>,
+    ) -> Option<()> {
+        let (tag, length) = self.read_header()?;
+
+        if tag == PIXEL_DATA {
+            *pixel_data = Some(if length == UNDEFINED_LENGTH {
+                PixelData::Encapsulated(self.read_fragments()?)
+            } else {
+                PixelData::Native(self.bytes(length as usize)?.to_vec())
+            });
+            return Some(());
+        }
+
+        if length == UNDEFINED_LENGTH {
+            // Only sequences have an undefined length outside the pixel data
+            return self.skip_sequence();
+        }
+
+        let value = self.bytes(length as usize)?;
+        if KEPT_TAGS.contains(&tag) {
+            elements.insert(tag, value.to_vec());
+        }
+
+        Some(())
+    }
+
+    /// Skips the items of a sequence of undefined length.
+    fn skip_sequence(&mut self) -> Option<()> {
+        loop {
+            let (tag, length) = self.read_header()?;
+            match tag {
+                SEQUENCE_DELIMITATION => return Some(()),
+                ITEM if length == UNDEFINED_LENGTH => self.skip_item()?,
+                _ => {
+                    self.bytes(length as usize)?;
+                }
+            }
+        }
+    }
+
+    /// Skips the elements of an item of undefined length.
+    fn skip_item(&mut self) -> Option<()> {
+        loop {
+            if self.peek_tag()? == ITEM_DELIMITATION {
+                self.read_header()?;
+                return Some(());
+            }
+
+            let (_, length) = self.read_header()?;
+            if length == UNDEFINED_LENGTH {
+                self.skip_sequence()?;
+            } else {
+                self.bytes(length as usize)?;
+            }
+        }
+    }
+
+    /// Reads the fragments of encapsulated pixel data.
+    fn read_fragments(&mut self) -> Option<Vec<Vec<u8>>> {
+        let mut fragments = Vec::new();
+        loop {
+            let (tag, length) = self.read_header()?;
+            match tag {
+                SEQUENCE_DELIMITATION => break,
+                ITEM => fragments.push(self.bytes(length as usize)?.to_vec()),
+                _ => return None,
+            }
+        }
+
+        // The first item is the basic offset table
+        Some(fragments.into_iter().skip(1).collect())
+    }
+}
+
+/// Inflates a deflated dataset, keeping what was decoded if the data is truncated.
+/// Fails when it inflates to more than `limit` bytes.
+fn inflate(data: &[u8], limit: u64) -> Result<Vec<u8>, String> {
+    let mut decoder = flate2::read::DeflateDecoder::new(data).take(limit + 1);
+    let mut output = Vec::new();
+    let mut buffer = [0u8; 64 * 1024];
+
+    while let Ok(read) = decoder.read(&mut buffer) {
+        if read == 0 {
+            break;
+        }
+        output.extend_from_slice(&buffer[..read]);
+    }
+
+    if output.len() as u64 > limit {
+        return Err(format!(
+            "Deflated DICOM dataset exceeds {} MB",
+            limit / (1024 * 1024)
+        ));
+    }
+
+    Ok(output)
+}
+
+fn trim_value(value: &str) -> String {
+    value
+        .trim_matches(|c: char| c == '\0' || c.is_whitespace())
+        .to_string()
+}
+
+/// Turns a person name (`Family^Given^Middle`) into space separated components.
+fn format_person_name(name: &str) -> String {
+    // Only the alphabetic representation, before any ideographic one
+    let alphabetic = name.split('=').next().unwrap_or_default();
+    alphabetic
+        .split('^')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Turns a DICOM date (YYYYMMDD) into YYYY-MM-DD.
+fn format_date(date: &str) -> String {
+    if date.len() == 8 && date.chars().all(|c| c.is_ascii_digit()) {
+        format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..])
+    } else {
+        date.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Explicit VR little endian element with a short length.
+    fn element(group: u16, number: u16, vr: &[u8; 2], value: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&group.to_le_bytes());
+        bytes.extend_from_slice(&number.to_le_bytes());
+        bytes.extend_from_slice(vr);
+        bytes.extend_from_slice(&(value.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(value);
+        bytes
+    }
+
+    fn dicom_file(pixels: &[u16], window: Option<(&str, &str)>) -> Vec<u8> {
+        let mut data = vec![0u8; 128];
+        data.extend_from_slice(b"DICM");
+        data.extend(element(0x0002, 0x0010, b"UI", b"1.2.840.10008.1.2.1\0"));
+        data.extend(element(0x0008, 0x0005, b"CS", b"ISO_IR 100"));
+        data.extend(element(0x0008, 0x0020, b"DA", b"20240115"));
+        data.extend(element(0x0008, 0x0060, b"CS", b"IO"));
+
+        // An empty sequence of undefined length, which must be skipped
+        data.extend_from_slice(&[0x08, 0x00, 0x15, 0x11]);
+        data.extend_from_slice(b"SQ\0\0");
+        data.extend_from_slice(&UNDEFINED_LENGTH.to_le_bytes());
+        data.extend_from_slice(&[0xFE, 0xFF, 0xDD, 0xE0, 0, 0, 0, 0]);
+
+        data.extend(element(0x0010, 0x0010, b"PN", b"P\xe9rez^Juan "));
+        data.extend(element(0x0028, 0x0002, b"US", &1u16.to_le_bytes()));
+        data.extend(element(0x0028, 0x0004, b"CS", b"MONOCHROME2 "));
+        data.extend(element(0x0028, 0x0010, b"US", &1u16.to_le_bytes()));
+        data.extend(element(
+            0x0028,
+            0x0011,
+            b"US",
+            &(pixels.len() as u16).to_le_bytes(),
+        ));
+        data.extend(element(0x0028, 0x0100, b"US", &16u16.to_le_bytes()));
+        data.extend(element(0x0028, 0x0101, b"US", &12u16.to_le_bytes()));
+        if let Some((center, width)) = window {
+            data.extend(element(0x0028, 0x1050, b"DS", center.as_bytes()));
+            data.extend(element(0x0028, 0x1051, b"DS", width.as_bytes()));
+        }
+
+        let pixel_bytes: Vec<u8> = pixels.iter().flat_map(|p| p.to_le_bytes()).collect();
+        data.extend_from_slice(&[0xE0, 0x7F, 0x10, 0x00]);
+        data.extend_from_slice(b"OW\0\0");
+        data.extend_from_slice(&(pixel_bytes.len() as u32).to_le_bytes());
+        data.extend(pixel_bytes);
+        data
+    }
+
+    #[test]
+    fn test_read_tags() {
+        let tags = read_tags(&dicom_file(&[0, 4095], None)).unwrap();
+
+        assert_eq!(tags.patient_name.as_deref(), Some("Pérez Juan"));
+        assert_eq!(tags.study_date.as_deref(), Some("2024-01-15"));
+        assert_eq!(tags.modality.as_deref(), Some("IO"));
+        assert_eq!(tags.columns, Some(2));
+        assert!(read_tags(b"%PDF-1.7").is_err());
+    }
+
+    #[test]
+    fn test_render_applies_window() {
+        // Without a window the full range of values is used
+        let image = render(&dicom_file(&[0, 2048, 4095], None))
+            .unwrap()
+            .to_luma8();
+        assert_eq!(image.as_raw(), &vec![0, 128, 255]);
+
+        // Values outside the window are clipped
+        let image = render(&dicom_file(&[0, 1000, 4095], Some(("1000", "200"))))
+            .unwrap()
+            .to_luma8();
+        assert_eq!(image.as_raw(), &vec![0, 128, 255]);
+    }
+
+    #[test]
+    fn test_inflate_limit() {
+        use std::io::Write;
+
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::best());
+        encoder.write_all(&vec![0u8; 4096]).unwrap();
+        let deflated = encoder.finish().unwrap();
+
+        assert_eq!(inflate(&deflated, 4096).unwrap().len(), 4096);
+        assert!(inflate(&deflated, 4095).is_err());
+        // Truncated streams keep what was decoded
+        assert!(inflate(&deflated[..deflated.len() / 2], 4096).is_ok());
+    }
+}
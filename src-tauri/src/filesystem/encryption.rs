@@ -0,0 +1,701 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use once_cell::sync::Lazy;
+use rand_core::{OsRng, RngCore};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Start of every encrypted file, followed by the ID of its data key.
+const MAGIC: &[u8; 8] = b"GLNENC01";
+
+/// Length of the header of an encrypted file (magic and key ID).
+pub const HEADER_LEN: u64 = 16;
+
+/// Plaintext bytes per segment. Each segment is authenticated on its own, so
+/// chunks of large files can be read without decrypting the whole file.
+pub const SEGMENT_SIZE: u64 = 64 * 1024;
+
+const NONCE_LEN: usize = 24;
+const TAG_LEN: usize = 16;
+
+/// Stored length of a full segment: random nonce, ciphertext and tag.
+pub const ENCRYPTED_SEGMENT_SIZE: u64 = SEGMENT_SIZE + (NONCE_LEN + TAG_LEN) as u64;
+
+/// Configuration key that turns encryption of new writes on.
+const ENABLED_CONFIG_KEY: &str = "fsEncryptionEnabled";
+
+/// Associated data of wrapped data keys.
+const KEY_WRAP_AAD: &[u8] = b"galeno-file-key";
+
+/// Minimum length of a key file.
+const MIN_KEY_FILE_LEN: usize = 32;
+
+/// Data keys unwrapped in memory; `None` while encryption is locked.
+static KEYRING: Lazy<RwLock<Option<Keyring>>> = Lazy::new(|| RwLock::new(None));
+
+/// Where the key that wraps the data keys comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySource {
+    /// Derived with Argon2id from the system password; unlocked by entering it
+    Password,
+    /// Read from a key file; unlocked automatically on startup
+    KeyFile,
+}
+
+impl KeySource {
+    fn to_db_string(self) -> &'static str {
+        match self {
+            KeySource::Password => "password",
+            KeySource::KeyFile => "key_file",
+        }
+    }
+
+    fn from_db_string(value: &str) -> Result<Self, String> {
+        match value {
+            "password" => Ok(KeySource::Password),
+            "key_file" => Ok(KeySource::KeyFile),
+            _ => Err(format!("Invalid encryption key source: '{}'", value)),
+        }
+    }
+}
+
+/// The secret that unlocks the data keys.
+pub enum KeySecret<'a> {
+    Password(&'a str),
+    /// Path of the key file; it is created with a random key if it doesn't exist
+    KeyFile(&'a str),
+}
+
+/// State of file encryption, as shown to administrators.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionStatus {
+    /// New writes are encrypted
+    pub enabled: bool,
+    /// Data keys have been created
+    pub configured: bool,
+    /// Data keys are available in memory
+    pub unlocked: bool,
+    pub key_source: Option<KeySource>,
+    pub key_file_path: Option<String>,
+    /// Key used for new writes; files with older keys are re-encrypted in the background
+    pub active_key_id: Option<i64>,
+    pub key_count: i64,
+}
+
+/// A data key that encrypts file contents.
+#[derive(Clone)]
+pub struct DataKey {
+    pub id: i64,
+    cipher: XChaCha20Poly1305,
+}
+
+impl DataKey {
+    fn new(id: i64, key: &[u8]) -> Self {
+        Self {
+            id,
+            cipher: XChaCha20Poly1305::new(Key::from_slice(key)),
+        }
+    }
+}
+
+struct Keyring {
+    /// Key that wraps the data keys, kept to wrap new ones on rotation
+    wrapping: XChaCha20Poly1305,
+    keys: HashMap<i64, DataKey>,
+    active_key_id: i64,
+}
+
+/// Settings row of `filesystem_encryption`.
+struct Settings {
+    key_source: KeySource,
+    salt: Vec<u8>,
+    key_file_path: Option<String>,
+}
+
+/// Gets the state of file encryption.
+pub fn status(conn: &Connection) -> Result<EncryptionStatus, String> {
+    let settings = load_settings(conn)?;
+    let key_count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM filesystem_encryption_keys", [], |row| {
+            row.get(0)
+        })
+        .map_err(|e| format!("Failed to count encryption keys: {}", e))?;
+    let keyring = KEYRING.read().map_err(|_| "Encryption keyring is poisoned")?;
+
+    Ok(EncryptionStatus {
+        enabled: is_enabled(),
+        configured: settings.is_some(),
+        unlocked: keyring.is_some(),
+        key_source: settings.as_ref().map(|s| s.key_source),
+        key_file_path: settings.and_then(|s| s.key_file_path),
+        active_key_id: keyring.as_ref().map(|k| k.active_key_id),
+        key_count,
+    })
+}
+
+/// Creates the first data key, wrapped with a key derived from the given secret,
+/// and unlocks encryption.
+///
+/// # Returns
+/// - `Ok(())` if encryption was set up
+/// - `Err(String)` if it is already set up or the key file cannot be used
+pub fn setup(conn: &Connection, secret: &KeySecret) -> Result<(), String> {
+    if load_settings(conn)?.is_some() {
+        return Err("File encryption is already set up".to_string());
+    }
+
+    let (settings, wrapping) = new_wrapping_key(secret)?;
+    save_settings(conn, &settings)?;
+
+    let mut keyring = Keyring {
+        wrapping,
+        keys: HashMap::new(),
+        active_key_id: 0,
+    };
+    add_data_key(conn, &mut keyring)?;
+    *KEYRING.write().map_err(|_| "Encryption keyring is poisoned")? = Some(keyring);
+
+    Ok(())
+}
+
+/// Unwraps the data keys into memory.
+///
+/// # Arguments
+/// - `conn`: Database connection
+/// - `password`: The system password, needed when the keys are wrapped with it
+///
+/// # Returns
+/// - `Ok(())` if the keys were unlocked
+/// - `Err(String)` if encryption is not set up or the secret is wrong
+pub fn unlock(conn: &Connection, password: Option<&str>) -> Result<(), String> {
+    let settings = load_settings(conn)?.ok_or("File encryption is not set up")?;
+    let wrapping = derive_wrapping_key(&settings, password)?;
+    let keyring = load_keyring(conn, wrapping)?;
+
+    *KEYRING.write().map_err(|_| "Encryption keyring is poisoned")? = Some(keyring);
+    Ok(())
+}
+
+/// Unlocks the data keys on startup when they are wrapped with a key file.
+///
+/// # Returns
+/// - `Ok(true)` if the keys were unlocked
+/// - `Ok(false)` if encryption is not set up or needs the system password
+/// - `Err(String)` if the key file cannot be used
+pub fn unlock_with_key_file(conn: &Connection) -> Result<bool, String> {
+    match load_settings(conn)? {
+        Some(settings) if settings.key_source == KeySource::KeyFile => {
+            unlock(conn, None)?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Creates a new data key for new writes. Files encrypted with older keys are
+/// re-encrypted with it by `StorageBackend::reencrypt_file`.
+///
+/// # Returns
+/// - `Ok(i64)` containing the ID of the new key
+/// - `Err(String)` if encryption is locked
+pub fn rotate_data_key(conn: &Connection) -> Result<i64, String> {
+    let mut guard = KEYRING.write().map_err(|_| "Encryption keyring is poisoned")?;
+    let keyring = guard.as_mut().ok_or_else(locked_error)?;
+
+    add_data_key(conn, keyring)
+}
+
+/// Wraps the data keys with a key derived from a new secret.
+///
+/// # Returns
+/// - `Ok(())` if the keys were wrapped again
+/// - `Err(String)` if encryption is locked or the key file cannot be used
+pub fn change_key_source(conn: &Connection, secret: &KeySecret) -> Result<(), String> {
+    let mut guard = KEYRING.write().map_err(|_| "Encryption keyring is poisoned")?;
+    let keyring = guard.as_mut().ok_or_else(locked_error)?;
+
+    let (settings, wrapping) = new_wrapping_key(secret)?;
+    let stored: Vec<(i64, Vec<u8>)> = list_wrapped_keys(conn)?;
+
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    for (id, wrapped) in stored {
+        let key = unwrap_key(&keyring.wrapping, &wrapped)?;
+        tx.execute(
+            "UPDATE filesystem_encryption_keys SET wrapped_key = ?1 WHERE id = ?2",
+            params![wrap_key(&wrapping, &key)?, id],
+        )
+        .map_err(|e| format!("Failed to update encryption key: {}", e))?;
+    }
+    save_settings(&tx, &settings)?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+    keyring.wrapping = wrapping;
+    Ok(())
+}
+
+/// Keeps the data keys usable when the system password changes.
+///
+/// Keys wrapped with the old password are wrapped again with the new one. If
+/// encryption is locked, the new password has to unlock it (as when only the
+/// hash of the same password is upgraded).
+///
+/// # Returns
+/// - `Ok(())` if the keys don't depend on the password or were wrapped again
+/// - `Err(String)` if the keys can't be unlocked to wrap them again
+pub fn on_system_password_changed(conn: &Connection, new_password: &str) -> Result<(), String> {
+    match load_settings(conn)? {
+        Some(settings) if settings.key_source == KeySource::Password => {}
+        _ => return Ok(()),
+    }
+
+    if !is_unlocked() && unlock(conn, Some(new_password)).is_err() {
+        return Err(
+            "Unlock file encryption with the current system password before changing it"
+                .to_string(),
+        );
+    }
+
+    change_key_source(conn, &KeySecret::Password(new_password))
+}
+
+/// Whether new writes must be encrypted.
+pub fn is_enabled() -> bool {
+    crate::config::current_value(ENABLED_CONFIG_KEY)
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
+}
+
+fn is_unlocked() -> bool {
+    KEYRING.read().map(|k| k.is_some()).unwrap_or(false)
+}
+
+/// Key to encrypt new writes with.
+///
+/// # Returns
+/// - `Ok(Some(DataKey))` with the active key if encryption is enabled
+/// - `Ok(None)` if new writes are stored in plain
+/// - `Err(String)` if encryption is enabled but locked, so nothing can be written
+pub fn write_key() -> Result<Option<DataKey>, String> {
+    if !is_enabled() {
+        return Ok(None);
+    }
+
+    let guard = KEYRING.read().map_err(|_| "Encryption keyring is poisoned")?;
+    let keyring = guard.as_ref().ok_or_else(locked_error)?;
+    Ok(keyring.keys.get(&keyring.active_key_id).cloned())
+}
+
+/// Key a file was encrypted with.
+pub fn key(id: i64) -> Result<DataKey, String> {
+    let guard = KEYRING.read().map_err(|_| "Encryption keyring is poisoned")?;
+    let keyring = guard.as_ref().ok_or_else(locked_error)?;
+
+    keyring
+        .keys
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| format!("Unknown encryption key: {}", id))
+}
+
+/// Gets the key ID from the start of a file, or `None` if it is not encrypted.
+pub fn parse_header(data: &[u8]) -> Option<i64> {
+    if data.len() < HEADER_LEN as usize || &data[..MAGIC.len()] != MAGIC {
+        return None;
+    }
+
+    let mut id = [0u8; 8];
+    id.copy_from_slice(&data[MAGIC.len()..HEADER_LEN as usize]);
+    Some(i64::from_le_bytes(id))
+}
+
+/// Encrypts a whole file: the header followed by its segments.
+pub fn encrypt(key: &DataKey, data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut output = Vec::with_capacity(data.len() + HEADER_LEN as usize + NONCE_LEN + TAG_LEN);
+    output.extend_from_slice(MAGIC);
+    output.extend_from_slice(&key.id.to_le_bytes());
+    output.extend(encrypt_segments(key, 0, data)?);
+    Ok(output)
+}
+
+/// Decrypts a whole file encrypted with `encrypt`.
+pub fn decrypt(key: &DataKey, data: &[u8]) -> Result<Vec<u8>, String> {
+    let segments = segment_count(data.len() as u64)?;
+    let body = &data[HEADER_LEN as usize..];
+
+    let mut output = Vec::with_capacity(plaintext_len(data.len() as u64)? as usize);
+    for (index, segment) in body.chunks(ENCRYPTED_SEGMENT_SIZE as usize).enumerate() {
+        let index = index as u64;
+        output.extend(decrypt_segment(key, index, index + 1 == segments, segment)?);
+    }
+
+    Ok(output)
+}
+
+/// Encrypts data as the segments of a file, starting at segment `first_index`.
+///
+/// The last segment is marked as final, so a file cut at a segment boundary
+/// fails to decrypt. Empty data still produces one (empty) final segment.
+pub fn encrypt_segments(key: &DataKey, first_index: u64, data: &[u8]) -> Result<Vec<u8>, String> {
+    let count = data.len().div_ceil(SEGMENT_SIZE as usize).max(1);
+    let mut output = Vec::with_capacity(data.len() + count * (NONCE_LEN + TAG_LEN));
+
+    for n in 0..count {
+        let start = n * SEGMENT_SIZE as usize;
+        let end = (start + SEGMENT_SIZE as usize).min(data.len());
+        let index = first_index + n as u64;
+
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = key
+            .cipher
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &data[start..end],
+                    aad: &segment_aad(key.id, index, n + 1 == count),
+                },
+            )
+            .map_err(|_| "Failed to encrypt file content".to_string())?;
+
+        output.extend_from_slice(&nonce);
+        output.extend(ciphertext);
+    }
+
+    Ok(output)
+}
+
+/// Decrypts one stored segment.
+///
+/// # Arguments
+/// - `key`: The key of the file
+/// - `index`: Position of the segment in the file
+/// - `last`: Whether it is the last segment of the file
+/// - `segment`: The stored segment (nonce, ciphertext and tag)
+pub fn decrypt_segment(
+    key: &DataKey,
+    index: u64,
+    last: bool,
+    segment: &[u8],
+) -> Result<Vec<u8>, String> {
+    if segment.len() < NONCE_LEN + TAG_LEN {
+        return Err("Encrypted file is truncated".to_string());
+    }
+
+    let (nonce, ciphertext) = segment.split_at(NONCE_LEN);
+    key.cipher
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: &segment_aad(key.id, index, last),
+            },
+        )
+        .map_err(|_| "Encrypted file is corrupted or was modified".to_string())
+}
+
+/// Number of segments of an encrypted file of the given stored length.
+pub fn segment_count(file_len: u64) -> Result<u64, String> {
+    let body = file_len.saturating_sub(HEADER_LEN);
+    let segments = body.div_ceil(ENCRYPTED_SEGMENT_SIZE);
+
+    // Every file has at least one segment, and the last one holds at least a nonce and tag
+    let last = body - segments.saturating_sub(1) * ENCRYPTED_SEGMENT_SIZE;
+    if segments == 0 || last < (NONCE_LEN + TAG_LEN) as u64 {
+        return Err("Encrypted file is truncated".to_string());
+    }
+
+    Ok(segments)
+}
+
+/// Plaintext length of an encrypted file of the given stored length.
+pub fn plaintext_len(file_len: u64) -> Result<u64, String> {
+    let segments = segment_count(file_len)?;
+    Ok(file_len - HEADER_LEN - segments * (NONCE_LEN + TAG_LEN) as u64)
+}
+
+/// Stored position of a segment in an encrypted file.
+pub fn segment_offset(index: u64) -> u64 {
+    HEADER_LEN + index * ENCRYPTED_SEGMENT_SIZE
+}
+
+/// Stored length of `count` segments starting at `first_index`, within a file of `file_len`.
+pub fn segments_len(file_len: u64, first_index: u64, count: u64) -> u64 {
+    (count * ENCRYPTED_SEGMENT_SIZE).min(file_len.saturating_sub(segment_offset(first_index)))
+}
+
+fn segment_aad(key_id: i64, index: u64, last: bool) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(MAGIC.len() + 17);
+    aad.extend_from_slice(MAGIC);
+    aad.extend_from_slice(&key_id.to_le_bytes());
+    aad.extend_from_slice(&index.to_le_bytes());
+    aad.push(last as u8);
+    aad
+}
+
+fn locked_error() -> String {
+    "File encryption is locked; unlock it with the system password".to_string()
+}
+
+fn load_settings(conn: &Connection) -> Result<Option<Settings>, String> {
+    let row = conn
+        .query_row(
+            "SELECT key_source, salt, key_file_path FROM filesystem_encryption WHERE id = 1",
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Vec<u8>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get encryption settings: {}", e))?;
+
+    row.map(|(source, salt, key_file_path)| {
+        Ok(Settings {
+            key_source: KeySource::from_db_string(&source)?,
+            salt,
+            key_file_path,
+        })
+    })
+    .transpose()
+}
+
+fn save_settings(conn: &Connection, settings: &Settings) -> Result<(), String> {
+    conn.execute(
+        r#"
+        INSERT OR REPLACE INTO filesystem_encryption (id, key_source, salt, key_file_path, updated_at)
+        VALUES (1, ?1, ?2, ?3, ?4)
+        "#,
+        params![
+            settings.key_source.to_db_string(),
+            settings.salt,
+            settings.key_file_path,
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )
+    .map_err(|e| format!("Failed to save encryption settings: {}", e))?;
+
+    Ok(())
+}
+
+/// Builds the settings and wrapping key for a new secret, with a fresh salt.
+fn new_wrapping_key(secret: &KeySecret) -> Result<(Settings, XChaCha20Poly1305), String> {
+    let mut salt = vec![0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+
+    let settings = match secret {
+        KeySecret::Password(_) => Settings {
+            key_source: KeySource::Password,
+            salt,
+            key_file_path: None,
+        },
+        KeySecret::KeyFile(path) => {
+            let path = std::path::Path::new(path);
+            if !path.exists() {
+                let mut key = [0u8; MIN_KEY_FILE_LEN];
+                OsRng.fill_bytes(&mut key);
+                std::fs::write(path, key)
+                    .map_err(|e| format!("Failed to create key file: {}", e))?;
+            }
+            Settings {
+                key_source: KeySource::KeyFile,
+                salt,
+                key_file_path: Some(path.to_string_lossy().into_owned()),
+            }
+        }
+    };
+
+    let password = match secret {
+        KeySecret::Password(password) => Some(*password),
+        KeySecret::KeyFile(_) => None,
+    };
+    let wrapping = derive_wrapping_key(&settings, password)?;
+
+    Ok((settings, wrapping))
+}
+
+fn derive_wrapping_key(
+    settings: &Settings,
+    password: Option<&str>,
+) -> Result<XChaCha20Poly1305, String> {
+    let mut key = [0u8; 32];
+
+    match settings.key_source {
+        KeySource::Password => {
+            let password = password.ok_or("The system password is required")?;
+            Argon2::default()
+                .hash_password_into(password.as_bytes(), &settings.salt, &mut key)
+                .map_err(|e| format!("Failed to derive encryption key: {}", e))?;
+        }
+        KeySource::KeyFile => {
+            let path = settings
+                .key_file_path
+                .as_deref()
+                .ok_or("Encryption key file is not configured")?;
+            let contents =
+                std::fs::read(path).map_err(|e| format!("Failed to read key file: {}", e))?;
+            if contents.len() < MIN_KEY_FILE_LEN {
+                return Err(format!(
+                    "Key file must hold at least {} bytes",
+                    MIN_KEY_FILE_LEN
+                ));
+            }
+
+            let mut hasher = Sha256::new();
+            hasher.update(&settings.salt);
+            hasher.update(&contents);
+            key.copy_from_slice(&hasher.finalize());
+        }
+    }
+
+    Ok(XChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+fn list_wrapped_keys(conn: &Connection) -> Result<Vec<(i64, Vec<u8>)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, wrapped_key FROM filesystem_encryption_keys ORDER BY id")
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+    let keys = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| format!("Failed to query encryption keys: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to collect encryption keys: {}", e))?;
+
+    Ok(keys)
+}
+
+/// Unwraps every data key; the newest one is used for new writes.
+fn load_keyring(conn: &Connection, wrapping: XChaCha20Poly1305) -> Result<Keyring, String> {
+    let mut keys = HashMap::new();
+    let mut active_key_id = None;
+
+    for (id, wrapped) in list_wrapped_keys(conn)? {
+        let key = unwrap_key(&wrapping, &wrapped)
+            .map_err(|_| "Wrong system password or key file".to_string())?;
+        keys.insert(id, DataKey::new(id, &key));
+        active_key_id = Some(id);
+    }
+
+    Ok(Keyring {
+        wrapping,
+        keys,
+        active_key_id: active_key_id.ok_or("No encryption keys found")?,
+    })
+}
+
+/// Creates a random data key, stores it wrapped and makes it the active key.
+fn add_data_key(conn: &Connection, keyring: &mut Keyring) -> Result<i64, String> {
+    let mut key = [0u8; 32];
+    OsRng.fill_bytes(&mut key);
+
+    conn.execute(
+        "INSERT INTO filesystem_encryption_keys (wrapped_key, created_at) VALUES (?1, ?2)",
+        params![
+            wrap_key(&keyring.wrapping, &key)?,
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )
+    .map_err(|e| format!("Failed to store encryption key: {}", e))?;
+
+    let id = conn.last_insert_rowid();
+    keyring.keys.insert(id, DataKey::new(id, &key));
+    keyring.active_key_id = id;
+
+    Ok(id)
+}
+
+fn wrap_key(wrapping: &XChaCha20Poly1305, key: &[u8]) -> Result<Vec<u8>, String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+
+    let ciphertext = wrapping
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: key,
+                aad: KEY_WRAP_AAD,
+            },
+        )
+        .map_err(|_| "Failed to wrap encryption key".to_string())?;
+
+    Ok([nonce.as_slice(), &ciphertext].concat())
+}
+
+fn unwrap_key(wrapping: &XChaCha20Poly1305, wrapped: &[u8]) -> Result<Vec<u8>, String> {
+    if wrapped.len() < NONCE_LEN {
+        return Err("Invalid wrapped encryption key".to_string());
+    }
+
+    let (nonce, ciphertext) = wrapped.split_at(NONCE_LEN);
+    wrapping
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: KEY_WRAP_AAD,
+            },
+        )
+        .map_err(|_| "Failed to unwrap encryption key".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_key(id: i64) -> DataKey {
+        DataKey::new(id, &[7u8; 32])
+    }
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let key = test_key(3);
+        for len in [0, 1, SEGMENT_SIZE as usize, SEGMENT_SIZE as usize * 2 + 5] {
+            let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let encrypted = encrypt(&key, &data).unwrap();
+
+            assert_eq!(parse_header(&encrypted), Some(3));
+            assert_eq!(plaintext_len(encrypted.len() as u64).unwrap(), len as u64);
+            assert_eq!(decrypt(&key, &encrypted).unwrap(), data);
+        }
+        assert_eq!(parse_header(b"%PDF-1.7 plain file"), None);
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let key = test_key(1);
+        let data = vec![42u8; SEGMENT_SIZE as usize + 10];
+        let encrypted = encrypt(&key, &data).unwrap();
+
+        let mut modified = encrypted.clone();
+        modified[HEADER_LEN as usize + NONCE_LEN + 5] ^= 1;
+        assert!(decrypt(&key, &modified).is_err());
+
+        // Dropping the last segment leaves a segment that is not marked as final
+        let truncated = &encrypted[..segment_offset(1) as usize];
+        assert!(decrypt(&key, truncated).is_err());
+
+        assert!(decrypt(&DataKey::new(1, &[8u8; 32]), &encrypted).is_err());
+    }
+
+    #[test]
+    fn test_wrapped_keys_need_the_wrapping_key() {
+        let wrapping = XChaCha20Poly1305::new(Key::from_slice(&[1u8; 32]));
+        let other = XChaCha20Poly1305::new(Key::from_slice(&[2u8; 32]));
+
+        let wrapped = wrap_key(&wrapping, &[9u8; 32]).unwrap();
+        assert_eq!(unwrap_key(&wrapping, &wrapped).unwrap(), vec![9u8; 32]);
+        assert!(unwrap_key(&other, &wrapped).is_err());
+    }
+}
@@ -0,0 +1,273 @@
+use chrono::{Duration, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Lock duration when the client doesn't ask for one.
+pub const DEFAULT_LOCK_MINUTES: i64 = 15;
+
+/// Longest a lock can be held without renewing it.
+const MAX_LOCK_MINUTES: i64 = 8 * 60;
+
+/// LockManager handles advisory locks on files and folders.
+///
+/// A lock is held by one user until it is released or expires. Expired locks are
+/// ignored by every check and deleted by `reap_expired`. Locks are advisory: they
+/// are honoured by `FileOperations`, not by the storage layer.
+#[derive(Debug, Clone)]
+pub struct LockManager;
+
+/// A lock held on a file or folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileLock {
+    pub file_path: String,
+    pub locked_by: String,
+    pub locked_at: String,
+    pub expires_at: String,
+}
+
+impl LockManager {
+    /// Creates a new LockManager instance.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Locks a path for a user, or extends the user's existing lock.
+    ///
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `virtual_path`: The virtual path to lock
+    /// - `username`: The user acquiring the lock
+    /// - `minutes`: How long the lock lasts (capped at 8 hours)
+    ///
+    /// # Returns
+    /// - `Ok(FileLock)` containing the lock
+    /// - `Err(String)` if another user holds the lock or the insertion fails
+    pub fn acquire(
+        &self,
+        conn: &Connection,
+        virtual_path: &str,
+        username: &str,
+        minutes: i64,
+    ) -> Result<FileLock, String> {
+        self.reap_expired(conn)?;
+
+        if let Some(lock) = self.get_lock(conn, virtual_path)? {
+            if lock.locked_by != username {
+                return Err(Self::locked_error(&lock));
+            }
+            return self.renew(conn, virtual_path, username, minutes);
+        }
+
+        let now = Utc::now();
+        let expires_at = now + Duration::minutes(minutes.clamp(1, MAX_LOCK_MINUTES));
+
+        conn.execute(
+            "INSERT INTO filesystem_locks (virtual_path, locked_by, locked_at, expires_at) VALUES (?1, ?2, ?3, ?4)",
+            params![virtual_path, username, now.to_rfc3339(), expires_at.to_rfc3339()],
+        )
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+        self.get_lock(conn, virtual_path)?
+            .ok_or_else(|| "Failed to retrieve lock".to_string())
+    }
+
+    /// Extends a lock held by the user.
+    ///
+    /// # Returns
+    /// - `Ok(FileLock)` containing the renewed lock
+    /// - `Err(String)` if the user doesn't hold a valid lock on the path
+    pub fn renew(
+        &self,
+        conn: &Connection,
+        virtual_path: &str,
+        username: &str,
+        minutes: i64,
+    ) -> Result<FileLock, String> {
+        let expires_at = Utc::now() + Duration::minutes(minutes.clamp(1, MAX_LOCK_MINUTES));
+
+        let rows_affected = conn
+            .execute(
+                r#"
+                UPDATE filesystem_locks SET expires_at = ?1
+                WHERE virtual_path = ?2 AND locked_by = ?3 AND expires_at > ?4
+                "#,
+                params![
+                    expires_at.to_rfc3339(),
+                    virtual_path,
+                    username,
+                    Utc::now().to_rfc3339()
+                ],
+            )
+            .map_err(|e| format!("Failed to renew lock: {}", e))?;
+
+        if rows_affected == 0 {
+            return Err(format!(
+                "User '{}' does not hold a lock on '{}'",
+                username, virtual_path
+            ));
+        }
+
+        self.get_lock(conn, virtual_path)?
+            .ok_or_else(|| "Failed to retrieve lock".to_string())
+    }
+
+    /// Releases a lock.
+    ///
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `virtual_path`: The locked virtual path
+    /// - `username`: The user releasing the lock
+    /// - `force`: Release the lock even if another user holds it
+    ///
+    /// # Returns
+    /// - `Ok(Some(FileLock))` with the released lock
+    /// - `Ok(None)` if the path was not locked
+    /// - `Err(String)` if another user holds the lock and `force` is false
+    pub fn release(
+        &self,
+        conn: &Connection,
+        virtual_path: &str,
+        username: &str,
+        force: bool,
+    ) -> Result<Option<FileLock>, String> {
+        let Some(lock) = self.get_lock(conn, virtual_path)? else {
+            return Ok(None);
+        };
+
+        if lock.locked_by != username && !force {
+            return Err(Self::locked_error(&lock));
+        }
+
+        conn.execute(
+            "DELETE FROM filesystem_locks WHERE virtual_path = ?1",
+            params![virtual_path],
+        )
+        .map_err(|e| format!("Failed to release lock: {}", e))?;
+
+        Ok(Some(lock))
+    }
+
+    /// Gets the valid (not expired) lock on a path.
+    pub fn get_lock(&self, conn: &Connection, virtual_path: &str) -> Result<Option<FileLock>, String> {
+        conn.query_row(
+            r#"
+            SELECT virtual_path, locked_by, locked_at, expires_at
+            FROM filesystem_locks
+            WHERE virtual_path = ?1 AND expires_at > ?2
+            "#,
+            params![virtual_path, Utc::now().to_rfc3339()],
+            Self::row_to_lock,
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get lock: {}", e))
+    }
+
+    /// Checks that no other user holds a lock that prevents modifying a path.
+    ///
+    /// A lock on the path or on any folder containing it blocks the change. With
+    /// `include_descendants`, locks on anything inside the path also block it, as
+    /// needed to move or delete a whole folder.
+    ///
+    /// # Returns
+    /// - `Ok(())` if the path can be modified by the user
+    /// - `Err(String)` naming the lock that prevents it
+    pub fn check_unlocked(
+        &self,
+        conn: &Connection,
+        virtual_path: &str,
+        username: &str,
+        include_descendants: bool,
+    ) -> Result<(), String> {
+        let lock = conn
+            .query_row(
+                r#"
+                SELECT virtual_path, locked_by, locked_at, expires_at
+                FROM filesystem_locks
+                WHERE locked_by != ?2 AND expires_at > ?3
+                  AND (virtual_path = ?1
+                       OR substr(?1, 1, length(virtual_path) + 1) = virtual_path || '\'
+                       OR (?4 AND substr(virtual_path, 1, length(?1) + 1) = ?1 || '\'))
+                LIMIT 1
+                "#,
+                params![
+                    virtual_path,
+                    username,
+                    Utc::now().to_rfc3339(),
+                    include_descendants
+                ],
+                Self::row_to_lock,
+            )
+            .optional()
+            .map_err(|e| format!("Failed to check locks: {}", e))?;
+
+        match lock {
+            Some(lock) => Err(Self::locked_error(&lock)),
+            None => Ok(()),
+        }
+    }
+
+    /// Points the locks on an entry and everything below it to a new path.
+    pub fn relocate(
+        &self,
+        conn: &Connection,
+        old_virtual_path: &str,
+        new_virtual_path: &str,
+    ) -> Result<usize, String> {
+        conn.execute(
+            r#"
+            UPDATE filesystem_locks
+            SET virtual_path = ?2 || substr(virtual_path, length(?1) + 1)
+            WHERE virtual_path = ?1 OR substr(virtual_path, 1, length(?1) + 1) = ?1 || '\'
+            "#,
+            params![old_virtual_path, new_virtual_path],
+        )
+        .map_err(|e| format!("Failed to relocate locks: {}", e))
+    }
+
+    /// Removes the locks on an entry and everything below it.
+    pub fn delete_under(&self, conn: &Connection, virtual_path: &str) -> Result<usize, String> {
+        conn.execute(
+            r#"
+            DELETE FROM filesystem_locks
+            WHERE virtual_path = ?1 OR substr(virtual_path, 1, length(?1) + 1) = ?1 || '\'
+            "#,
+            params![virtual_path],
+        )
+        .map_err(|e| format!("Failed to delete locks: {}", e))
+    }
+
+    /// Deletes expired locks.
+    ///
+    /// # Returns
+    /// - `Ok(usize)` containing the number of deleted locks
+    /// - `Err(String)` if the deletion fails
+    pub fn reap_expired(&self, conn: &Connection) -> Result<usize, String> {
+        conn.execute(
+            "DELETE FROM filesystem_locks WHERE expires_at <= ?1",
+            params![Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| format!("Failed to delete expired locks: {}", e))
+    }
+
+    fn locked_error(lock: &FileLock) -> String {
+        format!(
+            "'{}' is locked by '{}' until {}",
+            lock.file_path, lock.locked_by, lock.expires_at
+        )
+    }
+
+    fn row_to_lock(row: &rusqlite::Row) -> rusqlite::Result<FileLock> {
+        Ok(FileLock {
+            file_path: row.get(0)?,
+            locked_by: row.get(1)?,
+            locked_at: row.get(2)?,
+            expires_at: row.get(3)?,
+        })
+    }
+}
+
+impl Default for LockManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
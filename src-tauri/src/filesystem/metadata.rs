@@ -427,12 +427,13 @@ impl MetadataManager {
             .prepare(
                 r#"
                 WITH RECURSIVE folder_tree AS (
-                    -- Base case: the folder itself
+                    -- Base case: the folder itself and its children (structural
+                    -- folders such as user homes may have no metadata entry)
                     SELECT virtual_path, entry_type, size
                     FROM filesystem_metadata
-                    WHERE virtual_path = ?1
+                    WHERE virtual_path = ?1 OR parent_path = ?1
                     
-                    UNION ALL
+                    UNION
                     
                     -- Recursive case: all children
                     SELECT m.virtual_path, m.entry_type, m.size
@@ -530,6 +531,202 @@ impl MetadataManager {
         
         Ok(entries)
     }
+    
+    /// Inserts an entry with all of its original fields, including timestamps.
+    /// 
+    /// Used when restoring entries from the trash.
+    /// 
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `entry`: The entry to insert (its `id` is ignored)
+    /// 
+    /// # Returns
+    /// - `Ok(i64)` containing the ID of the inserted entry
+    /// - `Err(String)` if the insertion fails
+    pub fn reinsert_entry(&self, conn: &Connection, entry: &FileEntry) -> Result<i64, String> {
+        conn.execute(
+            r#"
+            INSERT INTO filesystem_metadata (
+                virtual_path, physical_path, name, entry_type, size, 
+                mime_type, owner_username, created_at, modified_at, parent_path
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            "#,
+            params![
+                entry.virtual_path,
+                entry.physical_path,
+                entry.name,
+                entry.entry_type.to_db_string(),
+                entry.size,
+                entry.mime_type,
+                entry.owner_username,
+                entry.created_at,
+                entry.modified_at,
+                entry.parent_path,
+            ],
+        )
+        .map_err(|e| format!("Failed to insert metadata entry: {}", e))?;
+        
+        Ok(conn.last_insert_rowid())
+    }
+    
+    /// Gets an entry and all of its descendants, parents before children.
+    /// 
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `virtual_path`: The virtual path of the root of the subtree
+    /// 
+    /// # Returns
+    /// - `Ok(Vec<FileEntry>)` (empty if the entry doesn't exist)
+    /// - `Err(String)` if the query fails
+    pub fn list_subtree(
+        &self,
+        conn: &Connection,
+        virtual_path: &str,
+    ) -> Result<Vec<FileEntry>, String> {
+        let mut stmt = conn
+            .prepare(
+                r#"
+                WITH RECURSIVE tree(virtual_path, depth) AS (
+                    SELECT virtual_path, 0
+                    FROM filesystem_metadata
+                    WHERE virtual_path = ?1
+                    
+                    UNION ALL
+                    
+                    SELECT m.virtual_path, t.depth + 1
+                    FROM filesystem_metadata m
+                    INNER JOIN tree t ON m.parent_path = t.virtual_path
+                )
+                SELECT m.id, m.virtual_path, m.physical_path, m.name, m.entry_type, m.size, 
+                       m.mime_type, m.owner_username, m.created_at, m.modified_at, m.parent_path
+                FROM filesystem_metadata m
+                INNER JOIN tree t ON m.virtual_path = t.virtual_path
+                ORDER BY t.depth ASC, m.name ASC
+                "#,
+            )
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+        
+        let entries = stmt
+            .query_map(params![virtual_path], |row| {
+                let entry_type_str: String = row.get(4)?;
+                let entry_type = EntryType::from_db_string(&entry_type_str)
+                    .map_err(|_| rusqlite::Error::InvalidQuery)?;
+                
+                Ok(FileEntry {
+                    id: row.get(0)?,
+                    virtual_path: row.get(1)?,
+                    physical_path: row.get(2)?,
+                    name: row.get(3)?,
+                    entry_type,
+                    size: row.get(5)?,
+                    mime_type: row.get(6)?,
+                    owner_username: row.get(7)?,
+                    created_at: row.get(8)?,
+                    modified_at: row.get(9)?,
+                    parent_path: row.get(10)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query entries: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to collect entries: {}", e))?;
+        
+        Ok(entries)
+    }
+    
+    /// Deletes an entry and all of its descendants.
+    /// 
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `virtual_path`: The virtual path of the root of the subtree
+    /// 
+    /// # Returns
+    /// - `Ok(usize)` containing the number of deleted entries
+    /// - `Err(String)` if the deletion fails
+    pub fn delete_subtree(&self, conn: &Connection, virtual_path: &str) -> Result<usize, String> {
+        conn.execute(
+            r#"
+            WITH RECURSIVE tree(virtual_path) AS (
+                SELECT virtual_path FROM filesystem_metadata WHERE virtual_path = ?1
+                UNION ALL
+                SELECT m.virtual_path
+                FROM filesystem_metadata m
+                INNER JOIN tree t ON m.parent_path = t.virtual_path
+            )
+            DELETE FROM filesystem_metadata
+            WHERE virtual_path IN (SELECT virtual_path FROM tree)
+            "#,
+            params![virtual_path],
+        )
+        .map_err(|e| format!("Failed to delete entries: {}", e))
+    }
+    
+    /// Rewrites the paths of everything below a folder that was renamed or moved.
+    /// 
+    /// The folder itself is updated with `update_paths`; this updates its descendants.
+    /// 
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `old_virtual_path`: The previous virtual path of the folder
+    /// - `new_virtual_path`: The new virtual path of the folder
+    /// - `old_physical_path`: The previous physical path of the folder
+    /// - `new_physical_path`: The new physical path of the folder
+    /// 
+    /// # Returns
+    /// - `Ok(usize)` containing the number of updated entries
+    /// - `Err(String)` if the update fails
+    pub fn update_descendant_paths(
+        &self,
+        conn: &Connection,
+        old_virtual_path: &str,
+        new_virtual_path: &str,
+        old_physical_path: &str,
+        new_physical_path: &str,
+    ) -> Result<usize, String> {
+        conn.execute(
+            r#"
+            UPDATE filesystem_metadata
+            SET virtual_path = ?2 || substr(virtual_path, length(?1) + 1),
+                parent_path = ?2 || substr(parent_path, length(?1) + 1),
+                physical_path = ?4 || substr(physical_path, length(?3) + 1)
+            WHERE substr(virtual_path, 1, length(?1) + 1) = ?1 || '\'
+            "#,
+            params![
+                old_virtual_path,
+                new_virtual_path,
+                old_physical_path,
+                new_physical_path
+            ],
+        )
+        .map_err(|e| format!("Failed to update descendant paths: {}", e))
+    }
+    
+    /// Looks up a file extension in the `filesystem_allowed_types` whitelist.
+    /// 
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `extension`: Lowercase extension without the dot (e.g., "pdf")
+    /// 
+    /// # Returns
+    /// - `Ok(Some(String))` with the MIME type if the extension is allowed and enabled
+    /// - `Ok(None)` if the extension is not allowed
+    /// - `Err(String)` if the query fails
+    pub fn allowed_mime_type(
+        &self,
+        conn: &Connection,
+        extension: &str,
+    ) -> Result<Option<String>, String> {
+        let result = conn.query_row(
+            "SELECT mime_type FROM filesystem_allowed_types WHERE extension = ?1 AND enabled = 1",
+            params![extension],
+            |row| row.get(0),
+        );
+        
+        match result {
+            Ok(mime_type) => Ok(Some(mime_type)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(format!("Failed to check allowed file type: {}", e)),
+        }
+    }
 }
 
 impl Default for MetadataManager {
@@ -604,6 +801,18 @@ mod tests {
         let _ = manager.delete_entry(&conn, &virtual_path);
     }
     
+    #[test]
+    fn test_allowed_mime_type() {
+        let conn = setup_test_db();
+        let manager = MetadataManager::new();
+        
+        assert_eq!(
+            manager.allowed_mime_type(&conn, "pdf").unwrap(),
+            Some("application/pdf".to_string())
+        );
+        assert_eq!(manager.allowed_mime_type(&conn, "exe").unwrap(), None);
+    }
+    
     #[test]
     fn test_update_entry() {
         let conn = setup_test_db();
@@ -6,22 +6,63 @@
 // - MetadataManager: Database operations for file/folder metadata
 // - PermissionManager: Access control and user isolation
 // - FileOperations: High-level operations coordinating all components
+// - UploadRegistry: Chunked uploads in progress and file type checks
+// - TrashManager: Recycle bin records and retention
+// - PatientLinkManager: Links between files and patient records
+// - AuditLog: Record and query of every filesystem operation
+// - QuotaManager: Per-user storage usage and quotas
+// - LockManager: Advisory locks with expiry for concurrent editing
+// - SearchIndex: Metadata search and full-text index of PDF, TXT and DOCX content
+// - VersionManager: Previous versions kept when files are overwritten
+// - ThumbnailCache: Thumbnails of images and DICOM files, and DICOM tags
+// - Encryption: Optional encryption of file contents with rotatable keys
 // - Commands: Tauri command handlers for frontend integration
 
+mod audit;
 pub mod commands;
+mod dicom;
+mod encryption;
+mod locks;
 mod metadata;
 mod operations;
 mod path_resolver;
+mod patient_links;
 mod permissions;
+mod quotas;
+mod search;
 mod storage;
+mod text_extract;
+mod thumbnails;
+mod trash;
+mod uploads;
+mod versions;
 
 // Re-export main types
+pub use audit::{AuditLogEntry, AuditLogFilter};
 pub use commands::FilesystemState;
+pub use dicom::DicomTags;
+pub use encryption::{EncryptionStatus, KeySource};
+pub use locks::FileLock;
 pub use metadata::{EntryType, FileEntry, MetadataManager};
 pub use operations::{DirectoryListing, FileOperations};
 pub use path_resolver::PathResolver;
-pub use permissions::PermissionManager;
+pub use patient_links::{PatientFile, PatientFileLink};
+pub use permissions::{FilePermissions, PermissionManager};
+pub use quotas::StorageQuota;
+pub use search::{SearchQuery, SearchResult};
+pub use versions::FileVersion;
 pub use storage::StorageBackend;
+pub use trash::TrashEntry;
+pub use uploads::UploadSession;
+
+/// How often expired trash and locks are purged, storage usage is recalculated,
+/// unindexed files are added to the content index and files are re-encrypted
+/// with the active key.
+pub const MAINTENANCE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(6 * 60 * 60);
+
+/// Identity of internal startup and maintenance tasks.
+/// Tauri commands never act as it; they require a logged-in user.
+pub(crate) const SYSTEM_USERNAME: &str = "system";
 
 /// Initializes the filesystem module.
 ///
@@ -29,6 +70,7 @@ pub use storage::StorageBackend;
 /// - Create the galeno_files directory if it doesn't exist
 /// - Initialize the filesystem state
 /// - Ensure system user exists for initialization tasks
+/// - Unlock file encryption when its keys are protected with a key file
 ///
 /// # Returns
 /// - `Ok(FilesystemState)` if initialization succeeds
@@ -52,9 +94,28 @@ pub fn initialize() -> Result<FilesystemState, String> {
     std::fs::create_dir_all(&users_physical)
         .map_err(|e| format!("Failed to create Users directory: {}", e))?;
 
+    // Keys protected with the system password are unlocked by an administrator
+    let conn = crate::db::get_connection()
+        .map_err(|e| format!("Failed to get database connection: {}", e))?;
+    match encryption::unlock_with_key_file(&conn) {
+        Ok(true) => log::info!("File encryption unlocked with key file"),
+        Ok(false) => {}
+        Err(e) => log::error!("Failed to unlock file encryption: {}", e),
+    }
+
     Ok(state)
 }
 
+/// Wraps the file encryption keys with a new system password.
+///
+/// This must be called before the new password is stored, so that the keys
+/// keep opening with it.
+pub fn on_system_password_changed(new_password: &str) -> Result<(), String> {
+    let conn = crate::db::get_connection()
+        .map_err(|e| format!("Failed to get database connection: {}", e))?;
+    encryption::on_system_password_changed(&conn, new_password)
+}
+
 /// Ensures the system user exists in the database.
 /// This user is used for initialization tasks and system operations.
 fn ensure_system_user() -> Result<(), String> {
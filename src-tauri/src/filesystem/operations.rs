@@ -1,5 +1,26 @@
 use serde::{Deserialize, Serialize};
-
+use std::sync::Arc;
+
+use super::dicom::{self, DicomTags, DICOM_MIME_TYPE};
+use super::encryption::{self, EncryptionStatus, KeySecret, KeySource};
+use super::audit::{
+    AuditLog, AuditLogEntry, AuditLogFilter, OP_CREATE_FOLDER, OP_DELETE, OP_DOWNLOAD,
+    OP_ENCRYPTION, OP_FORCE_UNLOCK, OP_GRANT_ACCESS, OP_LINK_PATIENT, OP_LOCK, OP_MOVE, OP_PURGE_TRASH,
+    OP_RENAME, OP_RESTORE, OP_RESTORE_VERSION, OP_REVOKE_ACCESS, OP_SET_QUOTA, OP_TRASH,
+    OP_UNLINK_PATIENT, OP_UNLOCK, OP_UPLOAD,
+};
+use super::locks::{FileLock, LockManager, DEFAULT_LOCK_MINUTES};
+use super::patient_links::{PatientFile, PatientFileLink, PatientLinkManager};
+use super::quotas::{QuotaManager, StorageQuota};
+use super::search::{SearchIndex, SearchQuery, SearchResult};
+use super::text_extract;
+use super::thumbnails::{self, ThumbnailCache, MAX_THUMBNAIL_SOURCE_BYTES};
+use super::trash::{TrashEntry, TrashManager, TRASH_DIR};
+use super::uploads::{
+    self, PendingUpload, UploadRegistry, UploadSession, CHUNK_SIZE, MAX_CHUNK_SIZE, STAGING_DIR,
+};
+use super::versions::{FileVersion, VersionManager, VERSIONS_DIR};
+use super::permissions::FilePermissions;
 use super::{
     EntryType, FileEntry, MetadataManager, PathResolver, PermissionManager, StorageBackend,
 };
@@ -11,20 +32,37 @@ use super::{
 /// - StorageBackend: Low-level file I/O
 /// - MetadataManager: Database metadata operations
 /// - PermissionManager: Access control
+/// - UploadRegistry: Chunked uploads in progress
+/// - TrashManager: Recycle bin records
+/// - PatientLinkManager: Links between entries and patients
+/// - AuditLog: Record of every operation
+/// - QuotaManager: Storage usage and quotas
+/// - LockManager: Advisory locks for concurrent editing
+/// - SearchIndex: Search and full-text index of file contents
+/// - VersionManager: Previous versions of overwritten files
+/// - ThumbnailCache: Thumbnails of images and DICOM files
 ///
 /// All operations:
 /// - Check permissions before execution
 /// - Validate inputs
 /// - Update metadata
 /// - Handle errors gracefully
-///
-/// Note: Audit logging will be added in task 9.
+/// - Are recorded in the audit log
 #[derive(Debug, Clone)]
 pub struct FileOperations {
     resolver: PathResolver,
     storage: StorageBackend,
     metadata: MetadataManager,
     permissions: PermissionManager,
+    uploads: Arc<UploadRegistry>,
+    trash: TrashManager,
+    links: PatientLinkManager,
+    audit: AuditLog,
+    quotas: QuotaManager,
+    locks: LockManager,
+    index: SearchIndex,
+    versions: VersionManager,
+    thumbnails: ThumbnailCache,
 }
 
 /// Represents a directory listing with its entries.
@@ -46,12 +84,30 @@ impl FileOperations {
         let storage = StorageBackend::new()?;
         let metadata = MetadataManager::new();
         let permissions = PermissionManager::new();
+        let uploads = Arc::new(UploadRegistry::new());
+        let trash = TrashManager::new();
+        let links = PatientLinkManager::new();
+        let audit = AuditLog::new();
+        let quotas = QuotaManager::new();
+        let locks = LockManager::new();
+        let index = SearchIndex::new();
+        let versions = VersionManager::new();
+        let thumbnails = ThumbnailCache::new();
 
         Ok(Self {
             resolver,
             storage,
             metadata,
             permissions,
+            uploads,
+            trash,
+            links,
+            audit,
+            quotas,
+            locks,
+            index,
+            versions,
+            thumbnails,
         })
     }
 
@@ -115,6 +171,21 @@ impl FileOperations {
         parent_virtual_path: &str,
         folder_name: &str,
         username: &str,
+    ) -> Result<FileEntry, String> {
+        let result = self
+            .try_create_folder(parent_virtual_path, folder_name, username)
+            .await;
+        let folder_virtual_path = format!("{}\\{}", parent_virtual_path, folder_name);
+        self.audit
+            .record(username, OP_CREATE_FOLDER, &folder_virtual_path, None, None, &result);
+        result
+    }
+
+    async fn try_create_folder(
+        &self,
+        parent_virtual_path: &str,
+        folder_name: &str,
+        username: &str,
     ) -> Result<FileEntry, String> {
         // Validate parent path
         self.resolver.validate_virtual_path(parent_virtual_path)?;
@@ -137,6 +208,9 @@ impl FileOperations {
         let conn = crate::db::get_connection()
             .map_err(|e| format!("Failed to get database connection: {}", e))?;
 
+        self.locks
+            .check_unlocked(&conn, parent_virtual_path, username, false)?;
+
         // Check if folder already exists
         if self.metadata.exists(&conn, &folder_virtual_path)? {
             return Err(format!(
@@ -173,10 +247,10 @@ impl FileOperations {
         Ok(entry)
     }
 
-    /// Deletes a file or folder by moving it to trash.
+    /// Permanently deletes a file or folder.
     ///
-    /// Note: This implementation moves entries to trash instead of permanent deletion.
-    /// Trash functionality will be fully implemented in task 11.
+    /// Folders are deleted with all of their content. Use `move_to_trash` for deletes
+    /// that can be undone.
     ///
     /// # Arguments
     /// - `virtual_path`: The virtual path of the entry to delete
@@ -188,7 +262,14 @@ impl FileOperations {
     ///
     /// # Requirements
     /// Validates: Requirements 3.6, 6.2, 15.2
-    pub async fn delete_entry(
+    pub async fn delete_entry(&self, virtual_path: &str, username: &str) -> Result<(), String> {
+        let result = self.try_delete_entry(virtual_path, username).await;
+        self.audit
+            .record(username, OP_DELETE, virtual_path, None, None, &result);
+        result
+    }
+
+    async fn try_delete_entry(
         &self,
         virtual_path: &str,
         username: &str,
@@ -214,6 +295,12 @@ impl FileOperations {
             .get_entry(&conn, virtual_path)?
             .ok_or_else(|| format!("Entry not found: '{}'", virtual_path))?;
 
+        // Nothing inside may be locked by someone else
+        self.locks
+            .check_unlocked(&conn, virtual_path, username, true)?;
+
+        let size = self.entry_size(&conn, &entry)?;
+
         // Delete physical file or directory
         match entry.entry_type {
             EntryType::File => {
@@ -224,43 +311,53 @@ impl FileOperations {
             }
         }
 
-        // Delete metadata entry
-        self.metadata.delete_entry(&conn, virtual_path)?;
-
-        // TODO: In task 11, implement moving to trash instead of permanent deletion
+        // Delete metadata, patient links, locks, indexed content and versions of the
+        // entry and everything below it
+        self.metadata.delete_subtree(&conn, virtual_path)?;
+        self.links.delete_under(&conn, virtual_path)?;
+        self.locks.delete_under(&conn, virtual_path)?;
+        self.index.delete_under(&conn, virtual_path)?;
+        self.delete_versions_under(virtual_path).await?;
+        self.charge_usage(&conn, virtual_path, -size);
 
         Ok(())
     }
 
-    /// Renames a file or folder.
+    /// Moves a file or folder to the recycle bin.
+    ///
+    /// The content is moved to the trash area and its metadata (the whole subtree for
+    /// folders) is removed from the tree and kept with the trash record for restoring.
     ///
     /// # Arguments
-    /// - `virtual_path`: The virtual path of the entry to rename
-    /// - `new_name`: The new name for the entry
-    /// - `username`: The username of the user renaming the entry
+    /// - `virtual_path`: The virtual path of the entry to trash
+    /// - `username`: The username of the user deleting the entry
     ///
     /// # Returns
-    /// - `Ok(FileEntry)` containing the updated metadata
-    /// - `Err(String)` if validation fails, permission denied, or rename fails
-    ///
-    /// # Requirements
-    /// Validates: Requirements 3.5, 6.1
-    pub async fn rename_entry(
+    /// - `Ok(TrashEntry)` containing the trash record
+    /// - `Err(String)` if permission denied, path doesn't exist, or the move fails
+    pub async fn move_to_trash(
         &self,
         virtual_path: &str,
-        new_name: &str,
         username: &str,
-    ) -> Result<FileEntry, String> {
+    ) -> Result<TrashEntry, String> {
+        let result = self.try_move_to_trash(virtual_path, username).await;
+        self.audit
+            .record(username, OP_TRASH, virtual_path, None, None, &result);
+        result
+    }
+
+    async fn try_move_to_trash(
+        &self,
+        virtual_path: &str,
+        username: &str,
+    ) -> Result<TrashEntry, String> {
         // Validate virtual path
         self.resolver.validate_virtual_path(virtual_path)?;
 
-        // Validate new name
-        self.validate_name(new_name)?;
-
-        // Check write permission
-        if !self.permissions.can_write(username, virtual_path)? {
+        // Check delete permission
+        if !self.permissions.can_delete(username, virtual_path)? {
             return Err(format!(
-                "Permission denied: user '{}' cannot rename '{}'",
+                "Permission denied: user '{}' cannot delete '{}'",
                 username, virtual_path
             ));
         }
@@ -269,100 +366,485 @@ impl FileOperations {
         let conn = crate::db::get_connection()
             .map_err(|e| format!("Failed to get database connection: {}", e))?;
 
-        // Get existing entry
-        let entry = self
-            .metadata
-            .get_entry(&conn, virtual_path)?
+        // Get the entry and everything below it
+        let subtree = self.metadata.list_subtree(&conn, virtual_path)?;
+        let entry = subtree
+            .first()
+            .cloned()
             .ok_or_else(|| format!("Entry not found: '{}'", virtual_path))?;
-
-        // Construct new virtual path
         let parent_path = entry
             .parent_path
-            .as_ref()
-            .ok_or_else(|| "Cannot rename root directory".to_string())?;
-        let new_virtual_path = format!("{}\\{}", parent_path, new_name);
+            .clone()
+            .ok_or_else(|| "Cannot delete root directory".to_string())?;
+
+        // Nothing inside may be locked by someone else
+        self.locks
+            .check_unlocked(&conn, virtual_path, username, true)?;
+
+        let size = self.entry_size(&conn, &entry)?;
+        let snapshot = serde_json::to_string(&subtree)
+            .map_err(|e| format!("Failed to serialize trash snapshot: {}", e))?;
+
+        // Move the content to the trash area
+        let backup_virtual_path = format!("{}\\{}", TRASH_DIR, uuid::Uuid::new_v4());
+        self.move_physical(&entry.entry_type, virtual_path, &backup_virtual_path)
+            .await?;
+        let backup_physical_path = self.resolver.virtual_to_physical(&backup_virtual_path)?;
+
+        let mut trash_entry = TrashEntry {
+            id: 0,
+            original_virtual_path: entry.virtual_path.clone(),
+            original_parent_path: parent_path,
+            name: entry.name.clone(),
+            entry_type: entry.entry_type.clone(),
+            size,
+            owner_username: entry.owner_username.clone(),
+            deleted_at: String::new(),
+            deleted_by: username.to_string(),
+            physical_backup_path: backup_physical_path.to_str().unwrap_or("").to_string(),
+            entries_snapshot: Some(snapshot),
+        };
+
+        // Record the trash entry, putting the content back if that fails
+        let recorded = self
+            .trash
+            .insert_item(&conn, &trash_entry)
+            .and_then(|id| self.metadata.delete_subtree(&conn, virtual_path).map(|_| id))
+            .and_then(|id| {
+                // Links, indexed content and versions follow the content so they come
+                // back on restore
+                self.links
+                    .relocate(&conn, virtual_path, &backup_virtual_path)
+                    .and_then(|_| self.index.relocate(&conn, virtual_path, &backup_virtual_path))
+                    .and_then(|_| {
+                        self.versions
+                            .relocate(&conn, virtual_path, &backup_virtual_path)
+                    })
+                    .map(|_| id)
+            })
+            .and_then(|id| self.locks.delete_under(&conn, virtual_path).map(|_| id));
+        match recorded {
+            Ok(id) => trash_entry.id = id,
+            Err(e) => {
+                let _ = self
+                    .move_physical(&entry.entry_type, &backup_virtual_path, virtual_path)
+                    .await;
+                return Err(e);
+            }
+        }
 
-        // Check if new name already exists
-        if self.metadata.exists(&conn, &new_virtual_path)? {
+        // Trashed content doesn't count towards the quota
+        self.charge_usage(&conn, virtual_path, -size);
+
+        if let Err(e) = self.purge_expired_trash().await {
+            log::warn!("Failed to purge expired trash: {}", e);
+        }
+
+        Ok(trash_entry)
+    }
+
+    /// Lists the recycle bin.
+    ///
+    /// Admins see every trashed entry; other users see the entries they own or deleted.
+    ///
+    /// # Arguments
+    /// - `username`: The username of the user listing the trash
+    ///
+    /// # Returns
+    /// - `Ok(Vec<TrashEntry>)` most recently deleted first
+    /// - `Err(String)` if the query fails
+    pub async fn list_trash(&self, username: &str) -> Result<Vec<TrashEntry>, String> {
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let filter = if self.permissions.is_admin(username)? {
+            None
+        } else {
+            Some(username)
+        };
+
+        self.trash.list_items(&conn, filter)
+    }
+
+    /// Restores a trashed entry to its original folder.
+    ///
+    /// Missing parent folders are recreated. If the original name is taken, the entry
+    /// is restored as "name (1)", "name (2)", ...
+    ///
+    /// # Arguments
+    /// - `trash_id`: The ID of the trash record
+    /// - `username`: The username of the user restoring the entry
+    ///
+    /// # Returns
+    /// - `Ok(FileEntry)` containing the restored entry
+    /// - `Err(String)` if permission denied, the record doesn't exist, or restoring fails
+    pub async fn restore_from_trash(
+        &self,
+        trash_id: i64,
+        username: &str,
+    ) -> Result<FileEntry, String> {
+        // Look the record up first: it is gone once restored
+        let original_path = crate::db::get_connection()
+            .ok()
+            .and_then(|conn| self.trash.get_item(&conn, trash_id).ok().flatten())
+            .map(|item| item.original_virtual_path)
+            .unwrap_or_else(|| format!("{}\\{}", TRASH_DIR, trash_id));
+
+        let result = self.try_restore_from_trash(trash_id, username).await;
+        let restored_path = result.as_ref().ok().map(|entry| entry.virtual_path.as_str());
+        self.audit
+            .record(username, OP_RESTORE, &original_path, restored_path, None, &result);
+        result
+    }
+
+    async fn try_restore_from_trash(
+        &self,
+        trash_id: i64,
+        username: &str,
+    ) -> Result<FileEntry, String> {
+        // Get database connection
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let item = self.visible_trash_item(&conn, trash_id, username)?;
+        let parent_path = item.original_parent_path.clone();
+
+        // Restoring writes into the original folder
+        if !self.permissions.can_write(username, &parent_path)? {
             return Err(format!(
-                "Entry with name '{}' already exists in '{}'",
-                new_name, parent_path
+                "Permission denied: user '{}' cannot write to '{}'",
+                username, parent_path
             ));
         }
 
-        // Get new physical path
-        let new_physical_path = self.resolver.virtual_to_physical(&new_virtual_path)?;
+        self.locks
+            .check_unlocked(&conn, &parent_path, username, false)?;
 
-        // Move physical file/directory
-        match entry.entry_type {
-            EntryType::File => {
-                self.storage
-                    .move_file(virtual_path, &new_virtual_path)
-                    .await?;
+        self.ensure_folder_exists(&parent_path, &item.owner_username)
+            .await?;
+
+        // Resolve name conflicts
+        let name = self.available_name(&conn, &parent_path, &item.name)?;
+        let target_path = format!("{}\\{}", parent_path.trim_end_matches('\\'), name);
+
+        // Rebuild the metadata of the subtree under the new path
+        let snapshot: Vec<FileEntry> = match &item.entries_snapshot {
+            Some(json) => serde_json::from_str(json)
+                .map_err(|e| format!("Invalid trash snapshot: {}", e))?,
+            None => Vec::new(),
+        };
+        let entries = self.relocate_snapshot(&item, snapshot, &target_path, &name)?;
+
+        if let Some(owner) = QuotaManager::home_owner(&target_path) {
+            self.quotas.check_space(&conn, &owner, item.size)?;
+        }
+
+        // Move the content back
+        let backup_virtual_path = self
+            .resolver
+            .physical_to_virtual(std::path::Path::new(&item.physical_backup_path))?;
+        self.move_physical(&item.entry_type, &backup_virtual_path, &target_path)
+            .await?;
+
+        for entry in &entries {
+            if let Err(e) = self.metadata.reinsert_entry(&conn, entry) {
+                // Leave the trash record intact so the restore can be retried
+                let _ = self.metadata.delete_subtree(&conn, &target_path);
+                let _ = self
+                    .move_physical(&item.entry_type, &target_path, &backup_virtual_path)
+                    .await;
+                return Err(e);
             }
-            EntryType::Folder => {
-                // For folders, we need to move the directory
-                let old_physical = self.resolver.virtual_to_physical(virtual_path)?;
-                tokio::fs::rename(&old_physical, &new_physical_path)
-                    .await
-                    .map_err(|e| format!("Failed to rename folder: {}", e))?;
+        }
+
+        self.links
+            .relocate(&conn, &backup_virtual_path, &target_path)?;
+        self.index
+            .relocate(&conn, &backup_virtual_path, &target_path)?;
+        self.versions
+            .relocate(&conn, &backup_virtual_path, &target_path)?;
+        self.trash.delete_item(&conn, item.id)?;
+        self.charge_usage(&conn, &target_path, item.size);
+
+        self.metadata
+            .get_entry(&conn, &target_path)?
+            .ok_or_else(|| "Failed to retrieve restored entry".to_string())
+    }
+
+    /// Permanently deletes every entry in the user's recycle bin.
+    ///
+    /// Admins empty the whole recycle bin.
+    ///
+    /// # Arguments
+    /// - `username`: The username of the user emptying the trash
+    ///
+    /// # Returns
+    /// - `Ok(usize)` containing the number of purged entries
+    /// - `Err(String)` if listing or deleting fails
+    pub async fn empty_trash(&self, username: &str) -> Result<usize, String> {
+        let items = self.list_trash(username).await?;
+
+        for item in &items {
+            self.purge_trash_item(item, username).await?;
+        }
+
+        Ok(items.len())
+    }
+
+    /// Permanently deletes trashed entries older than the retention period.
+    ///
+    /// # Returns
+    /// - `Ok(usize)` containing the number of purged entries
+    /// - `Err(String)` if listing or deleting fails
+    pub async fn purge_expired_trash(&self) -> Result<usize, String> {
+        let retention_days = self.trash.retention_days();
+        if retention_days == 0 {
+            return Ok(0);
+        }
+
+        let cutoff = (chrono::Utc::now() - chrono::Duration::days(retention_days)).to_rfc3339();
+        let items = {
+            let conn = crate::db::get_connection()
+                .map_err(|e| format!("Failed to get database connection: {}", e))?;
+            self.trash.list_deleted_before(&conn, &cutoff)?
+        };
+
+        for item in &items {
+            self.purge_trash_item(item, super::SYSTEM_USERNAME).await?;
+        }
+
+        Ok(items.len())
+    }
+
+    /// Gets a trash record the user is allowed to see.
+    fn visible_trash_item(
+        &self,
+        conn: &rusqlite::Connection,
+        trash_id: i64,
+        username: &str,
+    ) -> Result<TrashEntry, String> {
+        let item = self
+            .trash
+            .get_item(conn, trash_id)?
+            .ok_or_else(|| format!("Trash entry not found: {}", trash_id))?;
+
+        let visible = item.owner_username == username
+            || item.deleted_by == username
+            || self.permissions.is_admin(username)?;
+        if !visible {
+            return Err(format!("Trash entry not found: {}", trash_id));
+        }
+
+        Ok(item)
+    }
+
+    /// Deletes the content of a trash record and the record itself.
+    async fn purge_trash_item(&self, item: &TrashEntry, username: &str) -> Result<(), String> {
+        let result = self.try_purge_trash_item(item).await;
+        self.audit.record(
+            username,
+            OP_PURGE_TRASH,
+            &item.original_virtual_path,
+            None,
+            None,
+            &result,
+        );
+        result
+    }
+
+    async fn try_purge_trash_item(&self, item: &TrashEntry) -> Result<(), String> {
+        let backup_virtual_path = self
+            .resolver
+            .physical_to_virtual(std::path::Path::new(&item.physical_backup_path))?;
+
+        if self.storage.exists(&backup_virtual_path).await? {
+            match item.entry_type {
+                EntryType::File => self.storage.delete_file(&backup_virtual_path).await?,
+                EntryType::Folder => self.storage.delete_directory(&backup_virtual_path).await?,
             }
         }
 
-        // Update metadata
-        self.metadata.update_paths(
-            &conn,
-            virtual_path,
-            &new_virtual_path,
-            new_physical_path.to_str().unwrap_or(""),
-            new_name,
-            Some(parent_path),
-        )?;
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+        self.links.delete_under(&conn, &backup_virtual_path)?;
+        self.index.delete_under(&conn, &backup_virtual_path)?;
+        self.delete_versions_under(&backup_virtual_path).await?;
+        self.trash.delete_item(&conn, item.id)
+    }
 
-        // Retrieve and return updated entry
-        let updated_entry = self
+    /// Recreates a folder and its missing ancestors.
+    ///
+    /// Folders that exist on disk without metadata (such as `G:\Users`) are structural
+    /// and left as they are.
+    async fn ensure_folder_exists(
+        &self,
+        virtual_path: &str,
+        owner_username: &str,
+    ) -> Result<(), String> {
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+        let root = self.resolver.get_root_prefix().to_string();
+        let relative = virtual_path
+            .strip_prefix(&root)
+            .unwrap_or(virtual_path)
+            .trim_matches('\\');
+
+        let mut parent = root.clone();
+        for segment in relative.split('\\').filter(|segment| !segment.is_empty()) {
+            let current = format!("{}\\{}", parent.trim_end_matches('\\'), segment);
+
+            if !self.metadata.exists(&conn, &current)? && !self.storage.exists(&current).await? {
+                self.storage.create_directory(&current).await?;
+                let physical_path = self.resolver.virtual_to_physical(&current)?;
+                self.metadata.insert_entry(
+                    &conn,
+                    &current,
+                    physical_path.to_str().unwrap_or(""),
+                    segment,
+                    EntryType::Folder,
+                    0,
+                    None,
+                    owner_username,
+                    Some(&parent),
+                )?;
+            }
+
+            parent = current;
+        }
+
+        Ok(())
+    }
+
+    /// Returns `name`, or the first free "name (n)" variant in the folder.
+    fn available_name(
+        &self,
+        conn: &rusqlite::Connection,
+        parent_path: &str,
+        name: &str,
+    ) -> Result<String, String> {
+        let parent = parent_path.trim_end_matches('\\');
+        let (stem, extension) = match name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+            _ => (name, String::new()),
+        };
+
+        let mut candidate = name.to_string();
+        let mut counter = 1;
+        while self
             .metadata
-            .get_entry(&conn, &new_virtual_path)?
-            .ok_or_else(|| "Failed to retrieve renamed entry".to_string())?;
+            .exists(conn, &format!("{}\\{}", parent, candidate))?
+        {
+            candidate = format!("{} ({}){}", stem, counter, extension);
+            counter += 1;
+        }
 
-        Ok(updated_entry)
+        Ok(candidate)
     }
 
-    /// Moves a file or folder to a different directory.
+    /// Rewrites the snapshot of a trashed subtree to live under `target_path`.
+    fn relocate_snapshot(
+        &self,
+        item: &TrashEntry,
+        snapshot: Vec<FileEntry>,
+        target_path: &str,
+        name: &str,
+    ) -> Result<Vec<FileEntry>, String> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        // Records without a snapshot only describe the top entry
+        let snapshot = if snapshot.is_empty() {
+            vec![FileEntry {
+                id: 0,
+                virtual_path: item.original_virtual_path.clone(),
+                physical_path: String::new(),
+                name: item.name.clone(),
+                entry_type: item.entry_type.clone(),
+                size: item.size,
+                mime_type: None,
+                owner_username: item.owner_username.clone(),
+                created_at: now.clone(),
+                modified_at: now,
+                parent_path: Some(item.original_parent_path.clone()),
+            }]
+        } else {
+            snapshot
+        };
+
+        let original = item.original_virtual_path.as_str();
+        let relocate = |path: &str| format!("{}{}", target_path, &path[original.len()..]);
+
+        snapshot
+            .into_iter()
+            .filter(|entry| entry.virtual_path.starts_with(original))
+            .map(|mut entry| {
+                if entry.virtual_path == original {
+                    entry.name = name.to_string();
+                    entry.parent_path = Some(item.original_parent_path.clone());
+                } else {
+                    entry.parent_path = entry.parent_path.as_deref().map(relocate);
+                }
+                entry.virtual_path = relocate(&entry.virtual_path);
+                entry.physical_path = self
+                    .resolver
+                    .virtual_to_physical(&entry.virtual_path)?
+                    .to_str()
+                    .unwrap_or("")
+                    .to_string();
+                Ok(entry)
+            })
+            .collect()
+    }
+
+    /// Links a file or folder to a patient record.
+    ///
+    /// Linking an entry that is already linked to the patient replaces the notes.
     ///
     /// # Arguments
-    /// - `source_path`: The current virtual path of the entry
-    /// - `dest_parent_path`: The virtual path of the destination directory
-    /// - `username`: The username of the user moving the entry
+    /// - `virtual_path`: The virtual path of the entry
+    /// - `patient_id`: The ID of the patient
+    /// - `notes`: Optional notes about the document
+    /// - `username`: The username of the user creating the link
     ///
     /// # Returns
-    /// - `Ok(FileEntry)` containing the updated metadata
-    /// - `Err(String)` if validation fails, permission denied, or move fails
-    ///
-    /// # Requirements
-    /// Validates: Requirements 6.3, 6.4, 6.5
-    pub async fn move_entry(
+    /// - `Ok(PatientFileLink)` containing the link
+    /// - `Err(String)` if permission denied, the entry or the patient doesn't exist
+    pub async fn link_to_patient(
         &self,
-        source_path: &str,
-        dest_parent_path: &str,
+        virtual_path: &str,
+        patient_id: i64,
+        notes: Option<&str>,
         username: &str,
-    ) -> Result<FileEntry, String> {
-        // Validate paths
-        self.resolver.validate_virtual_path(source_path)?;
-        self.resolver.validate_virtual_path(dest_parent_path)?;
+    ) -> Result<PatientFileLink, String> {
+        let result = self
+            .try_link_to_patient(virtual_path, patient_id, notes, username)
+            .await;
+        let details = serde_json::json!({ "patient_id": patient_id }).to_string();
+        self.audit.record(
+            username,
+            OP_LINK_PATIENT,
+            virtual_path,
+            None,
+            Some(&details),
+            &result,
+        );
+        result
+    }
 
-        // Check permissions
-        if !self.permissions.can_write(username, source_path)? {
-            return Err(format!(
-                "Permission denied: user '{}' cannot move '{}'",
-                username, source_path
-            ));
-        }
+    async fn try_link_to_patient(
+        &self,
+        virtual_path: &str,
+        patient_id: i64,
+        notes: Option<&str>,
+        username: &str,
+    ) -> Result<PatientFileLink, String> {
+        // Validate virtual path
+        self.resolver.validate_virtual_path(virtual_path)?;
 
-        if !self.permissions.can_write(username, dest_parent_path)? {
+        // Check read permission
+        if !self.permissions.can_read(username, virtual_path)? {
             return Err(format!(
-                "Permission denied: user '{}' cannot write to '{}'",
-                username, dest_parent_path
+                "Permission denied: user '{}' cannot read '{}'",
+                username, virtual_path
             ));
         }
 
@@ -370,71 +852,2136 @@ impl FileOperations {
         let conn = crate::db::get_connection()
             .map_err(|e| format!("Failed to get database connection: {}", e))?;
 
-        // Get source entry
-        let entry = self
-            .metadata
-            .get_entry(&conn, source_path)?
-            .ok_or_else(|| format!("Source entry not found: '{}'", source_path))?;
+        if !self.metadata.exists(&conn, virtual_path)? {
+            return Err(format!("Entry not found: '{}'", virtual_path));
+        }
+        if !self.links.patient_exists(&conn, patient_id)? {
+            return Err(format!("Patient not found: {}", patient_id));
+        }
 
-        // Validate destination exists and is a folder
-        let dest_entry = self
-            .metadata
-            .get_entry(&conn, dest_parent_path)?
-            .ok_or_else(|| format!("Destination directory not found: '{}'", dest_parent_path))?;
+        let notes = notes.map(str::trim).filter(|notes| !notes.is_empty());
+        self.links
+            .link(&conn, virtual_path, patient_id, username, notes)
+    }
+
+    /// Removes the link between a file or folder and a patient record.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the entry
+    /// - `patient_id`: The ID of the patient
+    /// - `username`: The username of the user removing the link
+    ///
+    /// # Returns
+    /// - `Ok(())` if the link was removed
+    /// - `Err(String)` if permission denied or the link doesn't exist
+    pub async fn unlink_from_patient(
+        &self,
+        virtual_path: &str,
+        patient_id: i64,
+        username: &str,
+    ) -> Result<(), String> {
+        let result = self
+            .try_unlink_from_patient(virtual_path, patient_id, username)
+            .await;
+        let details = serde_json::json!({ "patient_id": patient_id }).to_string();
+        self.audit.record(
+            username,
+            OP_UNLINK_PATIENT,
+            virtual_path,
+            None,
+            Some(&details),
+            &result,
+        );
+        result
+    }
+
+    async fn try_unlink_from_patient(
+        &self,
+        virtual_path: &str,
+        patient_id: i64,
+        username: &str,
+    ) -> Result<(), String> {
+        // Validate virtual path
+        self.resolver.validate_virtual_path(virtual_path)?;
+
+        // Check read permission
+        if !self.permissions.can_read(username, virtual_path)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot read '{}'",
+                username, virtual_path
+            ));
+        }
+
+        // Get database connection
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        self.links.unlink(&conn, virtual_path, patient_id)
+    }
+
+    /// Lists the files linked to a patient that the user can read.
+    ///
+    /// # Arguments
+    /// - `patient_id`: The ID of the patient
+    /// - `username`: The username of the user listing the files
+    ///
+    /// # Returns
+    /// - `Ok(Vec<PatientFile>)` most recently linked first
+    /// - `Err(String)` if the query fails
+    pub async fn get_patient_files(
+        &self,
+        patient_id: i64,
+        username: &str,
+    ) -> Result<Vec<PatientFile>, String> {
+        // Get database connection
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let mut files = Vec::new();
+        for file in self.links.list_for_patient(&conn, patient_id)? {
+            if self.permissions.can_read(username, &file.entry.virtual_path)? {
+                files.push(file);
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Grants another user access to a file or folder.
+    ///
+    /// Only admins and the owner of the entry can share it.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the entry
+    /// - `grantee`: The username of the user receiving access
+    /// - `permissions`: The permissions to grant
+    /// - `username`: The username of the user granting access
+    ///
+    /// # Returns
+    /// - `Ok(())` if the permissions were granted
+    /// - `Err(String)` if permission denied, the entry doesn't exist, or the grant fails
+    pub async fn grant_access(
+        &self,
+        virtual_path: &str,
+        grantee: &str,
+        permissions: FilePermissions,
+        username: &str,
+    ) -> Result<(), String> {
+        let details = serde_json::json!({
+            "grantee": grantee,
+            "can_read": permissions.can_read,
+            "can_write": permissions.can_write,
+            "can_delete": permissions.can_delete,
+        })
+        .to_string();
+
+        let result = match self.check_can_share(virtual_path, username) {
+            Ok(()) => self
+                .permissions
+                .grant_access(grantee, virtual_path, permissions, username),
+            Err(e) => Err(e),
+        };
+        self.audit.record(
+            username,
+            OP_GRANT_ACCESS,
+            virtual_path,
+            None,
+            Some(&details),
+            &result,
+        );
+        result
+    }
+
+    /// Revokes the access another user was granted to a file or folder.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the entry
+    /// - `grantee`: The username of the user losing access
+    /// - `username`: The username of the user revoking access
+    ///
+    /// # Returns
+    /// - `Ok(())` if the permissions were revoked
+    /// - `Err(String)` if permission denied, the entry doesn't exist, or the revoke fails
+    pub async fn revoke_access(
+        &self,
+        virtual_path: &str,
+        grantee: &str,
+        username: &str,
+    ) -> Result<(), String> {
+        let details = serde_json::json!({ "grantee": grantee }).to_string();
+
+        let result = match self.check_can_share(virtual_path, username) {
+            Ok(()) => self.permissions.revoke_access(grantee, virtual_path),
+            Err(e) => Err(e),
+        };
+        self.audit.record(
+            username,
+            OP_REVOKE_ACCESS,
+            virtual_path,
+            None,
+            Some(&details),
+            &result,
+        );
+        result
+    }
+
+    /// Queries the filesystem audit log. Only admins can read it.
+    ///
+    /// # Arguments
+    /// - `filter`: The filters to apply
+    /// - `username`: The username of the user reading the log
+    ///
+    /// # Returns
+    /// - `Ok(Vec<AuditLogEntry>)` most recent entries first
+    /// - `Err(String)` if permission denied or the query fails
+    pub async fn get_audit_logs(
+        &self,
+        filter: &AuditLogFilter,
+        username: &str,
+    ) -> Result<Vec<AuditLogEntry>, String> {
+        self.check_can_read_audit_log(username)?;
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        self.audit.query(&conn, filter)
+    }
+
+    /// Exports the filesystem audit log as CSV. Only admins can export it.
+    ///
+    /// # Arguments
+    /// - `filter`: The filters to apply; without a limit, every matching entry is exported
+    /// - `username`: The username of the user exporting the log
+    ///
+    /// # Returns
+    /// - `Ok(String)` containing the CSV document
+    /// - `Err(String)` if permission denied or the query fails
+    pub async fn export_audit_logs(
+        &self,
+        filter: &AuditLogFilter,
+        username: &str,
+    ) -> Result<String, String> {
+        self.check_can_read_audit_log(username)?;
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        self.audit.export_csv(&conn, filter)
+    }
+
+    /// Checks that the user is an admin or owns the entry.
+    fn check_can_share(&self, virtual_path: &str, username: &str) -> Result<(), String> {
+        self.resolver.validate_virtual_path(virtual_path)?;
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+        let entry = self
+            .metadata
+            .get_entry(&conn, virtual_path)?
+            .ok_or_else(|| format!("Entry not found: '{}'", virtual_path))?;
+
+        if entry.owner_username != username && !self.permissions.is_admin(username)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot share '{}'",
+                username, virtual_path
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn check_can_read_audit_log(&self, username: &str) -> Result<(), String> {
+        if !self.permissions.is_admin(username)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot read the audit log",
+                username
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Gets the storage usage and quota of a user.
+    ///
+    /// Users can see their own quota; admins can see anyone's.
+    ///
+    /// # Arguments
+    /// - `target_username`: The user to get the quota of (`None` for the current user)
+    /// - `username`: The username of the user requesting the quota
+    ///
+    /// # Returns
+    /// - `Ok(StorageQuota)` containing the usage and limit
+    /// - `Err(String)` if permission denied or the query fails
+    pub async fn get_storage_quota(
+        &self,
+        target_username: Option<&str>,
+        username: &str,
+    ) -> Result<StorageQuota, String> {
+        let target_username = target_username.unwrap_or(username);
+        if target_username != username && !self.permissions.is_admin(username)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot see the quota of '{}'",
+                username, target_username
+            ));
+        }
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        self.quotas.get_quota(&conn, target_username)
+    }
+
+    /// Sets the storage quota of a user. Only admins can set quotas.
+    ///
+    /// # Arguments
+    /// - `target_username`: The user to set the quota of
+    /// - `quota_bytes`: The quota in bytes, or `None` to use the default quota
+    /// - `username`: The username of the admin setting the quota
+    ///
+    /// # Returns
+    /// - `Ok(StorageQuota)` containing the updated usage and limit
+    /// - `Err(String)` if permission denied or the update fails
+    pub async fn set_storage_quota(
+        &self,
+        target_username: &str,
+        quota_bytes: Option<i64>,
+        username: &str,
+    ) -> Result<StorageQuota, String> {
+        let result = self.try_set_storage_quota(target_username, quota_bytes, username);
+        let details = serde_json::json!({ "quota_bytes": quota_bytes }).to_string();
+        self.audit.record(
+            username,
+            OP_SET_QUOTA,
+            &self.resolver.get_user_root(target_username),
+            None,
+            Some(&details),
+            &result,
+        );
+        result
+    }
+
+    fn try_set_storage_quota(
+        &self,
+        target_username: &str,
+        quota_bytes: Option<i64>,
+        username: &str,
+    ) -> Result<StorageQuota, String> {
+        if !self.permissions.is_admin(username)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot set storage quotas",
+                username
+            ));
+        }
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        self.quotas.set_quota(&conn, target_username, quota_bytes)?;
+        self.quotas.get_quota(&conn, target_username)
+    }
+
+    /// Recalculates the storage usage of every user from the metadata.
+    ///
+    /// # Returns
+    /// - `Ok(usize)` containing the number of users whose usage had drifted
+    /// - `Err(String)` if the recalculation fails
+    pub async fn recalculate_quotas(&self) -> Result<usize, String> {
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        self.quotas.recalculate_all(&conn)
+    }
+
+    /// Locks a file or folder so other users cannot modify it while it is being edited.
+    ///
+    /// Acquiring a lock the user already holds extends it. While the lock is valid,
+    /// other users cannot rename, move, delete or trash the entry, nor write into it
+    /// if it is a folder.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the entry to lock
+    /// - `minutes`: How long the lock lasts (`None` for the default duration)
+    /// - `username`: The username of the user acquiring the lock
+    ///
+    /// # Returns
+    /// - `Ok(FileLock)` containing the lock
+    /// - `Err(String)` if permission denied, the entry doesn't exist, or another user holds the lock
+    pub async fn acquire_lock(
+        &self,
+        virtual_path: &str,
+        minutes: Option<i64>,
+        username: &str,
+    ) -> Result<FileLock, String> {
+        let result = self.try_acquire_lock(virtual_path, minutes, username);
+        self.audit
+            .record(username, OP_LOCK, virtual_path, None, None, &result);
+        result
+    }
+
+    fn try_acquire_lock(
+        &self,
+        virtual_path: &str,
+        minutes: Option<i64>,
+        username: &str,
+    ) -> Result<FileLock, String> {
+        self.resolver.validate_virtual_path(virtual_path)?;
+
+        if !self.permissions.can_write(username, virtual_path)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot lock '{}'",
+                username, virtual_path
+            ));
+        }
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        if !self.metadata.exists(&conn, virtual_path)? {
+            return Err(format!("Entry not found: '{}'", virtual_path));
+        }
+
+        self.locks.acquire(
+            &conn,
+            virtual_path,
+            username,
+            minutes.unwrap_or(DEFAULT_LOCK_MINUTES),
+        )
+    }
+
+    /// Extends a lock held by the user.
+    ///
+    /// Renewals are not recorded in the audit log, since editors renew their locks
+    /// periodically for as long as the document is open.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The locked virtual path
+    /// - `minutes`: How long the lock lasts from now (`None` for the default duration)
+    /// - `username`: The username of the user holding the lock
+    ///
+    /// # Returns
+    /// - `Ok(FileLock)` containing the renewed lock
+    /// - `Err(String)` if the user doesn't hold a valid lock on the path
+    pub async fn renew_lock(
+        &self,
+        virtual_path: &str,
+        minutes: Option<i64>,
+        username: &str,
+    ) -> Result<FileLock, String> {
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        self.locks.renew(
+            &conn,
+            virtual_path,
+            username,
+            minutes.unwrap_or(DEFAULT_LOCK_MINUTES),
+        )
+    }
+
+    /// Releases a lock held by the user.
+    ///
+    /// # Returns
+    /// - `Ok(())` if the lock was released or the path was not locked
+    /// - `Err(String)` if another user holds the lock
+    pub async fn release_lock(&self, virtual_path: &str, username: &str) -> Result<(), String> {
+        let result = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))
+            .and_then(|conn| self.locks.release(&conn, virtual_path, username, false))
+            .map(|_| ());
+        self.audit
+            .record(username, OP_UNLOCK, virtual_path, None, None, &result);
+        result
+    }
+
+    /// Releases a lock regardless of who holds it. Only admins can force unlocks.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The locked virtual path
+    /// - `username`: The username of the admin
+    ///
+    /// # Returns
+    /// - `Ok(Some(FileLock))` containing the released lock
+    /// - `Ok(None)` if the path was not locked
+    /// - `Err(String)` if permission denied or the deletion fails
+    pub async fn force_unlock(
+        &self,
+        virtual_path: &str,
+        username: &str,
+    ) -> Result<Option<FileLock>, String> {
+        let result = self.try_force_unlock(virtual_path, username);
+        let details = result
+            .as_ref()
+            .ok()
+            .and_then(|lock| lock.as_ref())
+            .map(|lock| serde_json::json!({ "locked_by": lock.locked_by }).to_string());
+        self.audit.record(
+            username,
+            OP_FORCE_UNLOCK,
+            virtual_path,
+            None,
+            details.as_deref(),
+            &result,
+        );
+        result
+    }
+
+    fn try_force_unlock(
+        &self,
+        virtual_path: &str,
+        username: &str,
+    ) -> Result<Option<FileLock>, String> {
+        if !self.permissions.is_admin(username)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot force unlock '{}'",
+                username, virtual_path
+            ));
+        }
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        self.locks.release(&conn, virtual_path, username, true)
+    }
+
+    /// Gets the lock on a file or folder.
+    ///
+    /// # Returns
+    /// - `Ok(Some(FileLock))` if the entry is locked
+    /// - `Ok(None)` if it isn't (or the lock expired)
+    /// - `Err(String)` if permission denied or the query fails
+    pub async fn get_lock_status(
+        &self,
+        virtual_path: &str,
+        username: &str,
+    ) -> Result<Option<FileLock>, String> {
+        self.resolver.validate_virtual_path(virtual_path)?;
+
+        if !self.permissions.can_read(username, virtual_path)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot read '{}'",
+                username, virtual_path
+            ));
+        }
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        self.locks.get_lock(&conn, virtual_path)
+    }
+
+    /// Searches files and folders by name, content, type, owner, date and linked patient.
+    ///
+    /// Only entries the user can read are returned.
+    ///
+    /// # Arguments
+    /// - `query`: The search criteria
+    /// - `username`: The username of the user searching
+    ///
+    /// # Returns
+    /// - `Ok(Vec<SearchResult>)` most recently modified first
+    /// - `Err(String)` if the query is invalid or the search fails
+    pub async fn search(
+        &self,
+        query: &SearchQuery,
+        username: &str,
+    ) -> Result<Vec<SearchResult>, String> {
+        if let Some(root_path) = &query.root_path {
+            self.resolver.validate_virtual_path(root_path)?;
+        }
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let is_admin = self.permissions.is_admin(username)?;
+        let (offset, limit) = SearchIndex::page(query);
+        let mut results = Vec::new();
+        let mut skipped = 0;
+
+        for result in self.index.search(&conn, query)? {
+            if !is_admin && !self.permissions.can_read(username, &result.entry.virtual_path)? {
+                continue;
+            }
+            if skipped < offset {
+                skipped += 1;
+                continue;
+            }
+
+            results.push(result);
+            if results.len() == limit {
+                break;
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Indexes the content of files that are not in the full-text index yet, such
+    /// as files uploaded before the index existed.
+    ///
+    /// # Returns
+    /// - `Ok(usize)` containing the number of indexed files
+    /// - `Err(String)` if listing the files fails
+    pub async fn update_content_index(&self) -> Result<usize, String> {
+        let pending = {
+            let conn = crate::db::get_connection()
+                .map_err(|e| format!("Failed to get database connection: {}", e))?;
+            self.index
+                .list_unindexed(&conn, &text_extract::indexable_mime_types())?
+        };
+
+        let mut indexed = 0;
+        for entry in pending {
+            let mime_type = entry.mime_type.as_deref().unwrap_or_default();
+            match self
+                .index_content(&entry.virtual_path, mime_type, entry.size)
+                .await
+            {
+                Ok(()) => indexed += 1,
+                Err(e) => log::warn!("Failed to index '{}': {}", entry.virtual_path, e),
+            }
+        }
+
+        Ok(indexed)
+    }
+
+    /// Extracts the text of a file and stores it in the full-text index.
+    ///
+    /// Files whose text cannot be extracted are indexed without content, so they
+    /// are not retried on every index update.
+    async fn index_content(&self, virtual_path: &str, mime_type: &str, size: i64) -> Result<(), String> {
+        if !text_extract::is_indexable(mime_type) {
+            return Ok(());
+        }
+
+        let content = if size as u64 > text_extract::MAX_EXTRACT_BYTES {
+            String::new()
+        } else {
+            let data = self.storage.read_file(virtual_path).await?;
+            let mime_type = mime_type.to_string();
+            let extracted =
+                tokio::task::spawn_blocking(move || text_extract::extract_text(&mime_type, &data))
+                    .await
+                    .map_err(|e| format!("Text extraction failed: {}", e))?;
+
+            extracted.unwrap_or_else(|e| {
+                log::info!("No text extracted from '{}': {}", virtual_path, e);
+                String::new()
+            })
+        };
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+        self.index.index(&conn, virtual_path, &content)
+    }
+
+    /// Gets the state of file encryption. Only admins can see it.
+    ///
+    /// # Arguments
+    /// - `username`: The username of the admin requesting the state
+    ///
+    /// # Returns
+    /// - `Ok(EncryptionStatus)` containing the state
+    /// - `Err(String)` if permission denied or the query fails
+    pub async fn get_encryption_status(&self, username: &str) -> Result<EncryptionStatus, String> {
+        self.check_encryption_admin(username)?;
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        encryption::status(&conn)
+    }
+
+    /// Creates the encryption keys, protected with the system password or a key file.
+    ///
+    /// Files are only encrypted once `fsEncryptionEnabled` is turned on.
+    ///
+    /// # Arguments
+    /// - `key_source`: Whether the keys are protected with the system password or a key file
+    /// - `system_password`: The system password, required for `KeySource::Password`
+    /// - `key_file_path`: The key file, required for `KeySource::KeyFile` (created if missing)
+    /// - `username`: The username of the admin setting up encryption
+    ///
+    /// # Returns
+    /// - `Ok(EncryptionStatus)` containing the new state
+    /// - `Err(String)` if permission denied, encryption is already set up or the secret is invalid
+    pub async fn setup_encryption(
+        &self,
+        key_source: KeySource,
+        system_password: Option<&str>,
+        key_file_path: Option<&str>,
+        username: &str,
+    ) -> Result<EncryptionStatus, String> {
+        let result = self.try_setup_encryption(key_source, system_password, key_file_path, username);
+        self.record_encryption(username, "setup", Some(key_source), &result);
+        result
+    }
+
+    fn try_setup_encryption(
+        &self,
+        key_source: KeySource,
+        system_password: Option<&str>,
+        key_file_path: Option<&str>,
+        username: &str,
+    ) -> Result<EncryptionStatus, String> {
+        self.check_encryption_admin(username)?;
+        let secret = Self::key_secret(key_source, system_password, key_file_path)?;
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        encryption::setup(&conn, &secret)?;
+        encryption::status(&conn)
+    }
+
+    /// Unlocks the encryption keys with the system password.
+    ///
+    /// Keys protected with a key file are unlocked on startup; keys protected with
+    /// the system password must be unlocked by an admin after every start before
+    /// encrypted files can be read or written.
+    ///
+    /// # Arguments
+    /// - `system_password`: The system password
+    /// - `username`: The username of the admin unlocking the keys
+    ///
+    /// # Returns
+    /// - `Ok(EncryptionStatus)` containing the new state
+    /// - `Err(String)` if permission denied or the password is wrong
+    pub async fn unlock_encryption(
+        &self,
+        system_password: &str,
+        username: &str,
+    ) -> Result<EncryptionStatus, String> {
+        let result = self.try_unlock_encryption(system_password, username);
+        self.record_encryption(username, "unlock", None, &result);
+        result
+    }
+
+    fn try_unlock_encryption(
+        &self,
+        system_password: &str,
+        username: &str,
+    ) -> Result<EncryptionStatus, String> {
+        self.check_encryption_admin(username)?;
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        encryption::unlock(&conn, Some(system_password))?;
+        encryption::status(&conn)
+    }
+
+    /// Creates a new encryption key for new writes.
+    ///
+    /// Files encrypted with older keys keep being readable and are re-encrypted
+    /// with the new key by `reencrypt_files`.
+    ///
+    /// # Arguments
+    /// - `username`: The username of the admin rotating the key
+    ///
+    /// # Returns
+    /// - `Ok(EncryptionStatus)` containing the new state
+    /// - `Err(String)` if permission denied or encryption is locked
+    pub async fn rotate_encryption_key(&self, username: &str) -> Result<EncryptionStatus, String> {
+        let result = self.try_rotate_encryption_key(username);
+        self.record_encryption(username, "rotate_key", None, &result);
+        result
+    }
+
+    fn try_rotate_encryption_key(&self, username: &str) -> Result<EncryptionStatus, String> {
+        self.check_encryption_admin(username)?;
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        encryption::rotate_data_key(&conn)?;
+        encryption::status(&conn)
+    }
+
+    /// Protects the encryption keys with a different secret. File contents are not
+    /// re-encrypted, since the keys themselves don't change.
+    ///
+    /// # Arguments
+    /// - `key_source`: Whether the keys are protected with the system password or a key file
+    /// - `system_password`: The system password, required for `KeySource::Password`
+    /// - `key_file_path`: The key file, required for `KeySource::KeyFile` (created if missing)
+    /// - `username`: The username of the admin changing the secret
+    ///
+    /// # Returns
+    /// - `Ok(EncryptionStatus)` containing the new state
+    /// - `Err(String)` if permission denied, encryption is locked or the secret is invalid
+    pub async fn change_encryption_key_source(
+        &self,
+        key_source: KeySource,
+        system_password: Option<&str>,
+        key_file_path: Option<&str>,
+        username: &str,
+    ) -> Result<EncryptionStatus, String> {
+        let result =
+            self.try_change_encryption_key_source(key_source, system_password, key_file_path, username);
+        self.record_encryption(username, "change_key_source", Some(key_source), &result);
+        result
+    }
+
+    fn try_change_encryption_key_source(
+        &self,
+        key_source: KeySource,
+        system_password: Option<&str>,
+        key_file_path: Option<&str>,
+        username: &str,
+    ) -> Result<EncryptionStatus, String> {
+        self.check_encryption_admin(username)?;
+        let secret = Self::key_secret(key_source, system_password, key_file_path)?;
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        encryption::change_key_source(&conn, &secret)?;
+        encryption::status(&conn)
+    }
+
+    /// Encrypts the stored files that are in plain or use an older key with the
+    /// active key. Does nothing while encryption is disabled.
+    ///
+    /// Uploads in progress are skipped; their content is encrypted as it is written.
+    ///
+    /// # Returns
+    /// - `Ok(usize)` containing the number of re-encrypted files
+    /// - `Err(String)` if encryption is enabled but locked
+    pub async fn reencrypt_files(&self) -> Result<usize, String> {
+        let pending = self.storage.list_files_to_reencrypt(&[STAGING_DIR]).await?;
+
+        let mut reencrypted = 0;
+        for virtual_path in pending {
+            match self.storage.reencrypt_file(&virtual_path).await {
+                Ok(true) => reencrypted += 1,
+                Ok(false) => {}
+                Err(e) => log::warn!("Failed to re-encrypt '{}': {}", virtual_path, e),
+            }
+        }
+
+        Ok(reencrypted)
+    }
+
+    fn check_encryption_admin(&self, username: &str) -> Result<(), String> {
+        if !self.permissions.is_admin(username)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot manage file encryption",
+                username
+            ));
+        }
+        Ok(())
+    }
+
+    /// Builds the secret of a key source, checking the system password.
+    fn key_secret<'a>(
+        key_source: KeySource,
+        system_password: Option<&'a str>,
+        key_file_path: Option<&'a str>,
+    ) -> Result<KeySecret<'a>, String> {
+        match key_source {
+            KeySource::Password => {
+                let password = system_password
+                    .ok_or("The system password is required to protect the encryption keys")?;
+                if !crate::wizard::verify_system_password(password.to_string())? {
+                    return Err("Incorrect system password".to_string());
+                }
+                Ok(KeySecret::Password(password))
+            }
+            KeySource::KeyFile => key_file_path
+                .filter(|path| !path.trim().is_empty())
+                .map(KeySecret::KeyFile)
+                .ok_or_else(|| "A key file path is required".to_string()),
+        }
+    }
+
+    fn record_encryption<T>(
+        &self,
+        username: &str,
+        action: &str,
+        key_source: Option<KeySource>,
+        result: &Result<T, String>,
+    ) {
+        let details = serde_json::json!({ "action": action, "key_source": key_source }).to_string();
+        self.audit
+            .record(username, OP_ENCRYPTION, "G:\\", None, Some(&details), result);
+    }
+
+    /// Deletes expired locks.
+    ///
+    /// # Returns
+    /// - `Ok(usize)` containing the number of deleted locks
+    /// - `Err(String)` if the deletion fails
+    pub async fn reap_expired_locks(&self) -> Result<usize, String> {
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        self.locks.reap_expired(&conn)
+    }
+
+    /// Size of a file, or of all the files in a folder, including their previous versions.
+    fn entry_size(&self, conn: &rusqlite::Connection, entry: &FileEntry) -> Result<i64, String> {
+        let content_size = match entry.entry_type {
+            EntryType::File => entry.size,
+            EntryType::Folder => self
+                .metadata
+                .calculate_folder_size(conn, &entry.virtual_path)?,
+        };
+
+        Ok(content_size + self.versions.size_under(conn, &entry.virtual_path)?)
+    }
+
+    /// Updates the usage of the user whose folder contains the path.
+    ///
+    /// Called after the operation succeeded, so a failure is only logged; the
+    /// periodic recalculation fixes the usage.
+    fn charge_usage(&self, conn: &rusqlite::Connection, virtual_path: &str, delta: i64) {
+        let Some(owner) = QuotaManager::home_owner(virtual_path) else {
+            return;
+        };
+        if delta == 0 {
+            return;
+        }
+
+        if let Err(e) = self.quotas.adjust_usage(conn, &owner, delta) {
+            log::warn!("Failed to update storage usage of '{}': {}", owner, e);
+        }
+    }
+
+    /// Updates the descendants, patient links, locks, indexed content and versions of a
+    /// renamed or moved entry.
+    fn relocate_children(
+        &self,
+        conn: &rusqlite::Connection,
+        entry: &FileEntry,
+        new_virtual_path: &str,
+        new_physical_path: &std::path::Path,
+    ) -> Result<(), String> {
+        if entry.entry_type == EntryType::Folder {
+            self.metadata.update_descendant_paths(
+                conn,
+                &entry.virtual_path,
+                new_virtual_path,
+                &entry.physical_path,
+                new_physical_path.to_str().unwrap_or(""),
+            )?;
+        }
+
+        self.links
+            .relocate(conn, &entry.virtual_path, new_virtual_path)?;
+        self.locks
+            .relocate(conn, &entry.virtual_path, new_virtual_path)?;
+        self.index
+            .relocate(conn, &entry.virtual_path, new_virtual_path)?;
+        self.versions
+            .relocate(conn, &entry.virtual_path, new_virtual_path)?;
+
+        Ok(())
+    }
+
+    /// Moves a file or a whole folder on disk.
+    async fn move_physical(
+        &self,
+        entry_type: &EntryType,
+        source_virtual_path: &str,
+        dest_virtual_path: &str,
+    ) -> Result<(), String> {
+        match entry_type {
+            EntryType::File => {
+                self.storage
+                    .move_file(source_virtual_path, dest_virtual_path)
+                    .await
+            }
+            EntryType::Folder => {
+                let source_physical = self.resolver.virtual_to_physical(source_virtual_path)?;
+                let dest_physical = self.resolver.virtual_to_physical(dest_virtual_path)?;
+
+                if let Some(parent) = dest_physical.parent() {
+                    tokio::fs::create_dir_all(parent)
+                        .await
+                        .map_err(|e| format!("Failed to create destination directory: {}", e))?;
+                }
+
+                tokio::fs::rename(&source_physical, &dest_physical)
+                    .await
+                    .map_err(|e| format!("Failed to move folder: {}", e))
+            }
+        }
+    }
+
+    /// Renames a file or folder.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the entry to rename
+    /// - `new_name`: The new name for the entry
+    /// - `username`: The username of the user renaming the entry
+    ///
+    /// # Returns
+    /// - `Ok(FileEntry)` containing the updated metadata
+    /// - `Err(String)` if validation fails, permission denied, or rename fails
+    ///
+    /// # Requirements
+    /// Validates: Requirements 3.5, 6.1
+    pub async fn rename_entry(
+        &self,
+        virtual_path: &str,
+        new_name: &str,
+        username: &str,
+    ) -> Result<FileEntry, String> {
+        let result = self.try_rename_entry(virtual_path, new_name, username).await;
+        let new_virtual_path = match &result {
+            Ok(entry) => entry.virtual_path.clone(),
+            Err(_) => match virtual_path.rsplit_once('\\') {
+                Some((parent, _)) => format!("{}\\{}", parent, new_name),
+                None => new_name.to_string(),
+            },
+        };
+        self.audit.record(
+            username,
+            OP_RENAME,
+            virtual_path,
+            Some(&new_virtual_path),
+            None,
+            &result,
+        );
+        result
+    }
+
+    async fn try_rename_entry(
+        &self,
+        virtual_path: &str,
+        new_name: &str,
+        username: &str,
+    ) -> Result<FileEntry, String> {
+        // Validate virtual path
+        self.resolver.validate_virtual_path(virtual_path)?;
+
+        // Validate new name
+        self.validate_name(new_name)?;
+
+        // Check write permission
+        if !self.permissions.can_write(username, virtual_path)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot rename '{}'",
+                username, virtual_path
+            ));
+        }
+
+        // Get database connection
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        // Get existing entry
+        let entry = self
+            .metadata
+            .get_entry(&conn, virtual_path)?
+            .ok_or_else(|| format!("Entry not found: '{}'", virtual_path))?;
+
+        self.locks
+            .check_unlocked(&conn, virtual_path, username, true)?;
+
+        // Construct new virtual path
+        let parent_path = entry
+            .parent_path
+            .as_ref()
+            .ok_or_else(|| "Cannot rename root directory".to_string())?;
+        let new_virtual_path = format!("{}\\{}", parent_path, new_name);
+
+        // Check if new name already exists
+        if self.metadata.exists(&conn, &new_virtual_path)? {
+            return Err(format!(
+                "Entry with name '{}' already exists in '{}'",
+                new_name, parent_path
+            ));
+        }
+
+        // Get new physical path
+        let new_physical_path = self.resolver.virtual_to_physical(&new_virtual_path)?;
+
+        // Move physical file/directory
+        match entry.entry_type {
+            EntryType::File => {
+                self.storage
+                    .move_file(virtual_path, &new_virtual_path)
+                    .await?;
+            }
+            EntryType::Folder => {
+                // For folders, we need to move the directory
+                let old_physical = self.resolver.virtual_to_physical(virtual_path)?;
+                tokio::fs::rename(&old_physical, &new_physical_path)
+                    .await
+                    .map_err(|e| format!("Failed to rename folder: {}", e))?;
+            }
+        }
+
+        // Update metadata
+        self.metadata.update_paths(
+            &conn,
+            virtual_path,
+            &new_virtual_path,
+            new_physical_path.to_str().unwrap_or(""),
+            new_name,
+            Some(parent_path),
+        )?;
+        self.relocate_children(&conn, &entry, &new_virtual_path, &new_physical_path)?;
+
+        // Retrieve and return updated entry
+        let updated_entry = self
+            .metadata
+            .get_entry(&conn, &new_virtual_path)?
+            .ok_or_else(|| "Failed to retrieve renamed entry".to_string())?;
+
+        Ok(updated_entry)
+    }
+
+    /// Moves a file or folder to a different directory.
+    ///
+    /// # Arguments
+    /// - `source_path`: The current virtual path of the entry
+    /// - `dest_parent_path`: The virtual path of the destination directory
+    /// - `username`: The username of the user moving the entry
+    ///
+    /// # Returns
+    /// - `Ok(FileEntry)` containing the updated metadata
+    /// - `Err(String)` if validation fails, permission denied, or move fails
+    ///
+    /// # Requirements
+    /// Validates: Requirements 6.3, 6.4, 6.5
+    pub async fn move_entry(
+        &self,
+        source_path: &str,
+        dest_parent_path: &str,
+        username: &str,
+    ) -> Result<FileEntry, String> {
+        let result = self
+            .try_move_entry(source_path, dest_parent_path, username)
+            .await;
+        let new_virtual_path = match &result {
+            Ok(entry) => entry.virtual_path.clone(),
+            Err(_) => dest_parent_path.to_string(),
+        };
+        self.audit.record(
+            username,
+            OP_MOVE,
+            source_path,
+            Some(&new_virtual_path),
+            None,
+            &result,
+        );
+        result
+    }
+
+    async fn try_move_entry(
+        &self,
+        source_path: &str,
+        dest_parent_path: &str,
+        username: &str,
+    ) -> Result<FileEntry, String> {
+        // Validate paths
+        self.resolver.validate_virtual_path(source_path)?;
+        self.resolver.validate_virtual_path(dest_parent_path)?;
+
+        // Check permissions
+        if !self.permissions.can_write(username, source_path)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot move '{}'",
+                username, source_path
+            ));
+        }
+
+        if !self.permissions.can_write(username, dest_parent_path)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot write to '{}'",
+                username, dest_parent_path
+            ));
+        }
+
+        // Get database connection
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        // Get source entry
+        let entry = self
+            .metadata
+            .get_entry(&conn, source_path)?
+            .ok_or_else(|| format!("Source entry not found: '{}'", source_path))?;
+
+        self.locks
+            .check_unlocked(&conn, source_path, username, true)?;
+        self.locks
+            .check_unlocked(&conn, dest_parent_path, username, false)?;
+
+        // Validate destination exists and is a folder
+        let dest_entry = self
+            .metadata
+            .get_entry(&conn, dest_parent_path)?
+            .ok_or_else(|| format!("Destination directory not found: '{}'", dest_parent_path))?;
+
+        if dest_entry.entry_type != EntryType::Folder {
+            return Err(format!(
+                "Destination is not a directory: '{}'",
+                dest_parent_path
+            ));
+        }
+
+        // Construct new virtual path
+        let new_virtual_path = format!("{}\\{}", dest_parent_path, entry.name);
+
+        // Check if entry with same name already exists at destination
+        if self.metadata.exists(&conn, &new_virtual_path)? {
+            return Err(format!(
+                "Entry with name '{}' already exists in '{}'",
+                entry.name, dest_parent_path
+            ));
+        }
+
+        // Moving into another user's folder counts towards that user's quota
+        let source_owner = QuotaManager::home_owner(source_path);
+        let dest_owner = QuotaManager::home_owner(&new_virtual_path);
+        let size = self.entry_size(&conn, &entry)?;
+        if source_owner != dest_owner {
+            if let Some(owner) = &dest_owner {
+                self.quotas.check_space(&conn, owner, size)?;
+            }
+        }
+
+        // Get new physical path
+        let new_physical_path = self.resolver.virtual_to_physical(&new_virtual_path)?;
+
+        // Move physical file/directory
+        match entry.entry_type {
+            EntryType::File => {
+                self.storage
+                    .move_file(source_path, &new_virtual_path)
+                    .await?;
+            }
+            EntryType::Folder => {
+                let old_physical = self.resolver.virtual_to_physical(source_path)?;
+                tokio::fs::rename(&old_physical, &new_physical_path)
+                    .await
+                    .map_err(|e| format!("Failed to move folder: {}", e))?;
+            }
+        }
+
+        // Update metadata
+        self.metadata.update_paths(
+            &conn,
+            source_path,
+            &new_virtual_path,
+            new_physical_path.to_str().unwrap_or(""),
+            &entry.name,
+            Some(dest_parent_path),
+        )?;
+        self.relocate_children(&conn, &entry, &new_virtual_path, &new_physical_path)?;
+
+        if source_owner != dest_owner {
+            self.charge_usage(&conn, source_path, -size);
+            self.charge_usage(&conn, &new_virtual_path, size);
+        }
+
+        // Retrieve and return updated entry
+        let updated_entry = self
+            .metadata
+            .get_entry(&conn, &new_virtual_path)?
+            .ok_or_else(|| "Failed to retrieve moved entry".to_string())?;
+
+        Ok(updated_entry)
+    }
+
+    /// Uploads a file whose content is already in memory.
+    ///
+    /// Goes through the same checks as a chunked upload; meant for small files.
+    /// Large files should use `begin_upload`, `upload_chunk` and `finish_upload`.
+    ///
+    /// # Arguments
+    /// - `parent_virtual_path`: The virtual path of the destination directory
+    /// - `filename`: The name of the new file
+    /// - `data`: The file content
+    /// - `username`: The username of the user uploading the file
+    ///
+    /// # Returns
+    /// - `Ok(FileEntry)` containing the metadata of the uploaded file
+    /// - `Err(String)` if validation fails, permission denied, or the upload fails
+    pub async fn upload_file(
+        &self,
+        parent_virtual_path: &str,
+        filename: &str,
+        data: &[u8],
+        username: &str,
+    ) -> Result<FileEntry, String> {
+        let session = self
+            .begin_upload(parent_virtual_path, filename, data.len() as u64, username)
+            .await?;
+
+        let mut offset = 0u64;
+        for chunk in data.chunks(CHUNK_SIZE) {
+            offset = self
+                .upload_chunk(&session.upload_id, offset, chunk, username)
+                .await?;
+        }
+
+        self.finish_upload(&session.upload_id, username).await
+    }
+
+    /// Starts a chunked upload of a new file.
+    ///
+    /// Checks write permission on the destination, validates the name and checks the
+    /// extension against the `filesystem_allowed_types` whitelist before any data is sent.
+    ///
+    /// # Arguments
+    /// - `parent_virtual_path`: The virtual path of the destination directory
+    /// - `filename`: The name of the new file
+    /// - `total_size`: The size of the file in bytes
+    /// - `username`: The username of the user uploading the file
+    ///
+    /// # Returns
+    /// - `Ok(UploadSession)` with the ID to send the chunks to
+    /// - `Err(String)` if validation fails, permission denied, or the type is not allowed
+    pub async fn begin_upload(
+        &self,
+        parent_virtual_path: &str,
+        filename: &str,
+        total_size: u64,
+        username: &str,
+    ) -> Result<UploadSession, String> {
+        let result = self
+            .try_begin_upload(parent_virtual_path, filename, total_size, false, username)
+            .await;
+
+        // Successful uploads are recorded when they finish
+        if result.is_err() {
+            let target_path = format!(
+                "{}\\{}",
+                parent_virtual_path.trim_end_matches('\\'),
+                filename
+            );
+            self.audit
+                .record(username, OP_UPLOAD, &target_path, None, None, &result);
+        }
+
+        result
+    }
+
+    /// Starts a chunked upload that replaces the content of an existing file.
+    ///
+    /// The current content is kept as a previous version, up to the configured number
+    /// of versions per file. Chunks are sent and the upload completed as for new files.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the file to overwrite
+    /// - `total_size`: The size of the new content in bytes
+    /// - `username`: The username of the user uploading the file
+    ///
+    /// # Returns
+    /// - `Ok(UploadSession)` with the ID to send the chunks to
+    /// - `Err(String)` if the file doesn't exist, is locked, or permission is denied
+    pub async fn begin_overwrite(
+        &self,
+        virtual_path: &str,
+        total_size: u64,
+        username: &str,
+    ) -> Result<UploadSession, String> {
+        let result = match virtual_path.rsplit_once('\\') {
+            Some((parent, filename)) => {
+                self.try_begin_upload(parent, filename, total_size, true, username)
+                    .await
+            }
+            None => Err(format!("Invalid file path: '{}'", virtual_path)),
+        };
+
+        // Successful uploads are recorded when they finish
+        if result.is_err() {
+            self.audit
+                .record(username, OP_UPLOAD, virtual_path, None, None, &result);
+        }
+
+        result
+    }
+
+    async fn try_begin_upload(
+        &self,
+        parent_virtual_path: &str,
+        filename: &str,
+        total_size: u64,
+        overwrite: bool,
+        username: &str,
+    ) -> Result<UploadSession, String> {
+        // Validate parent path and file name
+        self.resolver.validate_virtual_path(parent_virtual_path)?;
+        self.validate_name(filename)?;
+
+        let target_path = format!(
+            "{}\\{}",
+            parent_virtual_path.trim_end_matches('\\'),
+            filename
+        );
+
+        // Check write permission on the file being overwritten, or on the parent directory
+        let writable_path = if overwrite {
+            target_path.as_str()
+        } else {
+            parent_virtual_path
+        };
+        if !self.permissions.can_write(username, writable_path)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot write to '{}'",
+                username, writable_path
+            ));
+        }
+
+        // Get database connection
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        // Check the extension against the whitelist
+        let extension = uploads::file_extension(filename)
+            .ok_or_else(|| format!("File '{}' has no extension", filename))?;
+        let mime_type = self
+            .metadata
+            .allowed_mime_type(&conn, &extension)?
+            .ok_or_else(|| format!("File type '.{}' is not allowed", extension))?;
+
+        // Reject uploads that can't be stored before any data is sent
+        self.check_upload_target(&conn, &target_path, total_size, overwrite, username)?;
+
+        self.discard_expired_uploads().await;
+
+        let upload_id = uuid::Uuid::new_v4().to_string();
+        self.uploads.insert(
+            &upload_id,
+            PendingUpload {
+                target_path,
+                parent_path: parent_virtual_path.to_string(),
+                name: filename.to_string(),
+                staging_path: format!("{}\\{}.part", STAGING_DIR, upload_id),
+                owner_username: username.to_string(),
+                total_size,
+                received: 0,
+                mime_type,
+                overwrite,
+                busy: false,
+                last_activity: chrono::Utc::now(),
+            },
+        );
+
+        Ok(UploadSession {
+            upload_id,
+            chunk_size: CHUNK_SIZE,
+        })
+    }
+
+    /// Appends the next chunk of an upload.
+    ///
+    /// The first chunk is checked against the declared file type. Any failure discards
+    /// the whole upload, so a rejected or half-written file never reaches the tree.
+    ///
+    /// # Arguments
+    /// - `upload_id`: The ID returned by `begin_upload`
+    /// - `offset`: Position of the chunk; must equal the bytes received so far
+    /// - `data`: The chunk content
+    /// - `username`: The username of the user uploading the file
+    ///
+    /// # Returns
+    /// - `Ok(u64)` containing the number of bytes received so far
+    /// - `Err(String)` if the chunk is rejected or cannot be written
+    pub async fn upload_chunk(
+        &self,
+        upload_id: &str,
+        offset: u64,
+        data: &[u8],
+        username: &str,
+    ) -> Result<u64, String> {
+        if data.len() > MAX_CHUNK_SIZE {
+            return Err(format!(
+                "Chunk is too large (maximum {} bytes)",
+                MAX_CHUNK_SIZE
+            ));
+        }
+
+        let upload = self
+            .uploads
+            .begin_chunk(upload_id, username, offset, data.len())?;
+
+        let result = match offset {
+            0 => uploads::verify_content_type(data, &upload.mime_type),
+            _ => Ok(()),
+        };
+        let result = match result {
+            Ok(()) => self.storage.append_chunk(&upload.staging_path, data).await,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(received) => {
+                self.uploads.end_chunk(upload_id, Some(data.len()));
+                Ok(received)
+            }
+            Err(e) => {
+                self.uploads.end_chunk(upload_id, None);
+                let _ = self.cancel_upload(upload_id, username).await;
+                let result = Err(e);
+                self.audit
+                    .record(username, OP_UPLOAD, &upload.target_path, None, None, &result);
+                result
+            }
+        }
+    }
+
+    /// Completes an upload: moves the file into place and records its metadata.
+    ///
+    /// # Arguments
+    /// - `upload_id`: The ID returned by `begin_upload`
+    /// - `username`: The username of the user uploading the file
+    ///
+    /// # Returns
+    /// - `Ok(FileEntry)` containing the metadata of the uploaded file
+    /// - `Err(String)` if the upload is incomplete or the file cannot be stored
+    pub async fn finish_upload(
+        &self,
+        upload_id: &str,
+        username: &str,
+    ) -> Result<FileEntry, String> {
+        let upload = self.uploads.take(upload_id, username)?;
+
+        let result = self.store_upload(&upload, username).await;
+        if result.is_err() && self.storage.exists(&upload.staging_path).await.unwrap_or(false) {
+            let _ = self.storage.delete_file(&upload.staging_path).await;
+        }
+
+        let details = serde_json::json!({ "size": upload.total_size }).to_string();
+        self.audit.record(
+            username,
+            OP_UPLOAD,
+            &upload.target_path,
+            None,
+            Some(&details),
+            &result,
+        );
+        result
+    }
+
+    /// Abandons an upload and deletes the data received so far.
+    ///
+    /// # Arguments
+    /// - `upload_id`: The ID returned by `begin_upload`
+    /// - `username`: The username of the user uploading the file
+    ///
+    /// # Returns
+    /// - `Ok(())` if the upload was discarded
+    /// - `Err(String)` if the upload doesn't exist
+    pub async fn cancel_upload(&self, upload_id: &str, username: &str) -> Result<(), String> {
+        let upload = self.uploads.take(upload_id, username)?;
+
+        if self.storage.exists(&upload.staging_path).await? {
+            self.storage.delete_file(&upload.staging_path).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the whole content of a file.
+    ///
+    /// Meant for small files; large files should be read with `read_file_chunk`.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the file
+    /// - `username`: The username of the user reading the file
+    ///
+    /// # Returns
+    /// - `Ok(Vec<u8>)` containing the file content
+    /// - `Err(String)` if permission denied, the file doesn't exist, or the read fails
+    pub async fn download_file(
+        &self,
+        virtual_path: &str,
+        username: &str,
+    ) -> Result<Vec<u8>, String> {
+        let result = match self.get_readable_file(virtual_path, username) {
+            Ok(_) => self.storage.read_file(virtual_path).await,
+            Err(e) => Err(e),
+        };
+        self.audit
+            .record(username, OP_DOWNLOAD, virtual_path, None, None, &result);
+        result
+    }
+
+    /// Reads part of a file, so large files can be downloaded in chunks.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the file
+    /// - `offset`: Byte position to start reading from
+    /// - `length`: Maximum number of bytes to read (capped at `MAX_CHUNK_SIZE`)
+    /// - `username`: The username of the user reading the file
+    ///
+    /// # Returns
+    /// - `Ok(Vec<u8>)` containing the chunk (empty past the end of the file)
+    /// - `Err(String)` if permission denied, the file doesn't exist, or the read fails
+    pub async fn read_file_chunk(
+        &self,
+        virtual_path: &str,
+        offset: u64,
+        length: usize,
+        username: &str,
+    ) -> Result<Vec<u8>, String> {
+        let result = match self.get_readable_file(virtual_path, username) {
+            Ok(_) => {
+                self.storage
+                    .read_chunk(virtual_path, offset, length.min(MAX_CHUNK_SIZE))
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        // A chunked download is recorded once, when its first chunk is read
+        if offset == 0 || result.is_err() {
+            self.audit
+                .record(username, OP_DOWNLOAD, virtual_path, None, None, &result);
+        }
+
+        result
+    }
+
+    /// Lists the previous versions of a file, newest first.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the file
+    /// - `username`: The username of the user requesting the versions
+    ///
+    /// # Returns
+    /// - `Ok(Vec<FileVersion>)` containing the versions
+    /// - `Err(String)` if permission denied or the file doesn't exist
+    pub async fn list_versions(
+        &self,
+        virtual_path: &str,
+        username: &str,
+    ) -> Result<Vec<FileVersion>, String> {
+        self.get_readable_file(virtual_path, username)?;
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        self.versions.list_versions(&conn, virtual_path)
+    }
+
+    /// Reads part of a previous version of a file.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the file
+    /// - `version`: The version number
+    /// - `offset`: Byte position to start reading from
+    /// - `length`: Maximum number of bytes to read (capped at `MAX_CHUNK_SIZE`)
+    /// - `username`: The username of the user reading the version
+    ///
+    /// # Returns
+    /// - `Ok(Vec<u8>)` containing the chunk (empty past the end of the version)
+    /// - `Err(String)` if permission denied, the version doesn't exist, or the read fails
+    pub async fn read_version_chunk(
+        &self,
+        virtual_path: &str,
+        version: i64,
+        offset: u64,
+        length: usize,
+        username: &str,
+    ) -> Result<Vec<u8>, String> {
+        let result = match self.get_readable_version(virtual_path, version, username) {
+            Ok(found) => {
+                self.storage
+                    .read_chunk(&found.storage_path, offset, length.min(MAX_CHUNK_SIZE))
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        // A chunked download is recorded once, when its first chunk is read
+        if offset == 0 || result.is_err() {
+            let details = serde_json::json!({ "version": version }).to_string();
+            self.audit.record(
+                username,
+                OP_DOWNLOAD,
+                virtual_path,
+                None,
+                Some(&details),
+                &result,
+            );
+        }
+
+        result
+    }
+
+    /// Replaces the content of a file with one of its previous versions.
+    ///
+    /// The current content is kept as a new version, so a restore can be undone.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the file
+    /// - `version`: The version number to restore
+    /// - `username`: The username of the user restoring the version
+    ///
+    /// # Returns
+    /// - `Ok(FileEntry)` containing the updated metadata of the file
+    /// - `Err(String)` if permission denied, the file is locked, or the restore fails
+    pub async fn restore_version(
+        &self,
+        virtual_path: &str,
+        version: i64,
+        username: &str,
+    ) -> Result<FileEntry, String> {
+        let result = self.try_restore_version(virtual_path, version, username).await;
+        let details = serde_json::json!({ "version": version }).to_string();
+        self.audit.record(
+            username,
+            OP_RESTORE_VERSION,
+            virtual_path,
+            None,
+            Some(&details),
+            &result,
+        );
+        result
+    }
+
+    async fn try_restore_version(
+        &self,
+        virtual_path: &str,
+        version: i64,
+        username: &str,
+    ) -> Result<FileEntry, String> {
+        let restored = self.get_readable_version(virtual_path, version, username)?;
+
+        if !self.permissions.can_write(username, virtual_path)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot write to '{}'",
+                username, virtual_path
+            ));
+        }
+
+        let entry = {
+            let conn = crate::db::get_connection()
+                .map_err(|e| format!("Failed to get database connection: {}", e))?;
+            self.check_upload_target(&conn, virtual_path, restored.size as u64, true, username)?
+                .ok_or_else(|| format!("File not found: '{}'", virtual_path))?
+        };
+
+        self.replace_content(&entry, &restored.storage_path, true, username)
+            .await
+    }
+
+    /// Checks read access to a file and gets one of its versions.
+    fn get_readable_version(
+        &self,
+        virtual_path: &str,
+        version: i64,
+        username: &str,
+    ) -> Result<FileVersion, String> {
+        self.get_readable_file(virtual_path, username)?;
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        self.versions
+            .get_version(&conn, virtual_path, version)?
+            .ok_or_else(|| format!("Version {} of '{}' not found", version, virtual_path))
+    }
+
+    /// Gets the thumbnail of an image or DICOM file, generating it if needed.
+    ///
+    /// Thumbnails are cached and rebuilt when the file is overwritten. Like listing
+    /// a folder, viewing thumbnails is not recorded in the audit log.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the file
+    /// - `username`: The username of the user viewing the thumbnail
+    ///
+    /// # Returns
+    /// - `Ok(Vec<u8>)` containing the PNG thumbnail
+    /// - `Err(String)` if permission denied, the file has no preview, or it can't be decoded
+    pub async fn get_thumbnail(&self, virtual_path: &str, username: &str) -> Result<Vec<u8>, String> {
+        let entry = self.get_readable_file(virtual_path, username)?;
+        let mime_type = entry.mime_type.clone().unwrap_or_default();
+        if !thumbnails::is_previewable(&mime_type) {
+            return Err(format!("No preview available for '{}'", virtual_path));
+        }
+
+        {
+            let conn = crate::db::get_connection()
+                .map_err(|e| format!("Failed to get database connection: {}", e))?;
+            if let Some(cached) = self.thumbnails.get(&conn, &entry)? {
+                return Ok(cached);
+            }
+        }
+
+        if entry.size as u64 > MAX_THUMBNAIL_SOURCE_BYTES {
+            return Err(format!("File is too large to preview: '{}'", virtual_path));
+        }
+
+        // Decoding large radiographs takes a while, so keep it off the async workers
+        let data = self.storage.read_file(virtual_path).await?;
+        let thumbnail =
+            tokio::task::spawn_blocking(move || thumbnails::render_thumbnail(&mime_type, &data))
+                .await
+                .map_err(|e| format!("Thumbnail generation failed: {}", e))??;
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+        self.thumbnails.store(&conn, &entry, &thumbnail)?;
+
+        Ok(thumbnail)
+    }
+
+    /// Reads the basic tags (patient, study date, modality) of a DICOM file.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the DICOM file
+    /// - `username`: The username of the user reading the tags
+    ///
+    /// # Returns
+    /// - `Ok(DicomTags)` containing the tags
+    /// - `Err(String)` if permission denied or the file is not a DICOM file
+    pub async fn get_dicom_tags(&self, virtual_path: &str, username: &str) -> Result<DicomTags, String> {
+        let entry = self.get_readable_file(virtual_path, username)?;
+        if entry.mime_type.as_deref() != Some(DICOM_MIME_TYPE) {
+            return Err(format!("Not a DICOM file: '{}'", virtual_path));
+        }
+
+        // The tags come before the pixel data, so the start of the file is enough
+        let header = self
+            .storage
+            .read_chunk(virtual_path, 0, dicom::HEADER_READ_BYTES)
+            .await?;
+
+        dicom::read_tags(&header)
+    }
+
+    /// Gets the metadata of a file or folder the user can read.
+    ///
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the entry
+    /// - `username`: The username of the user requesting the metadata
+    ///
+    /// # Returns
+    /// - `Ok(FileEntry)` containing the metadata
+    /// - `Err(String)` if permission denied or the entry doesn't exist
+    pub async fn get_entry(&self, virtual_path: &str, username: &str) -> Result<FileEntry, String> {
+        // Validate virtual path
+        self.resolver.validate_virtual_path(virtual_path)?;
+
+        // Check read permission
+        if !self.permissions.can_read(username, virtual_path)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot read '{}'",
+                username, virtual_path
+            ));
+        }
+
+        // Get database connection
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        self.metadata
+            .get_entry(&conn, virtual_path)?
+            .ok_or_else(|| format!("Entry not found: '{}'", virtual_path))
+    }
+
+    /// Checks read access and that the path is a file with metadata.
+    fn get_readable_file(&self, virtual_path: &str, username: &str) -> Result<FileEntry, String> {
+        self.resolver.validate_virtual_path(virtual_path)?;
+
+        if !self.permissions.can_read(username, virtual_path)? {
+            return Err(format!(
+                "Permission denied: user '{}' cannot read '{}'",
+                username, virtual_path
+            ));
+        }
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        let entry = self
+            .metadata
+            .get_entry(&conn, virtual_path)?
+            .ok_or_else(|| format!("File not found: '{}'", virtual_path))?;
+
+        if entry.entry_type != EntryType::File {
+            return Err(format!("Path is not a file: '{}'", virtual_path));
+        }
+
+        Ok(entry)
+    }
+
+    /// Moves a fully received upload to its final path and inserts its metadata.
+    async fn store_upload(
+        &self,
+        upload: &PendingUpload,
+        username: &str,
+    ) -> Result<FileEntry, String> {
+        if upload.received != upload.total_size {
+            return Err(format!(
+                "Upload incomplete: received {} of {} bytes",
+                upload.received, upload.total_size
+            ));
+        }
+
+        // Empty files never receive a chunk
+        if !self.storage.exists(&upload.staging_path).await? {
+            self.storage.write_file(&upload.staging_path, &[]).await?;
+        }
+
+        // Get database connection
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+
+        // Things may have changed since the upload started: another upload may have
+        // created the same file, the file may have been locked or the space used
+        let existing = self.check_upload_target(
+            &conn,
+            &upload.target_path,
+            upload.total_size,
+            upload.overwrite,
+            username,
+        )?;
+
+        if let Some(entry) = existing {
+            return self
+                .replace_content(&entry, &upload.staging_path, false, username)
+                .await;
+        }
+
+        // Move the staged content into place
+        self.storage
+            .move_file(&upload.staging_path, &upload.target_path)
+            .await?;
+
+        let size = self.storage.get_file_size(&upload.target_path).await? as i64;
+        let physical_path = self.resolver.virtual_to_physical(&upload.target_path)?;
+
+        // Insert metadata entry
+        let inserted = self.metadata.insert_entry(
+            &conn,
+            &upload.target_path,
+            physical_path.to_str().unwrap_or(""),
+            &upload.name,
+            EntryType::File,
+            size,
+            Some(&upload.mime_type),
+            username,
+            Some(&upload.parent_path),
+        );
+        if let Err(e) = inserted {
+            let _ = self.storage.delete_file(&upload.target_path).await;
+            return Err(e);
+        }
+        self.charge_usage(&conn, &upload.target_path, size);
+
+        if let Err(e) = self
+            .index_content(&upload.target_path, &upload.mime_type, size)
+            .await
+        {
+            log::warn!("Failed to index '{}': {}", upload.target_path, e);
+        }
+
+        // Retrieve and return the created entry
+        self.metadata
+            .get_entry(&conn, &upload.target_path)?
+            .ok_or_else(|| "Failed to retrieve uploaded file entry".to_string())
+    }
+
+    /// Checks that an upload can be written to its target path.
+    ///
+    /// New files must not exist yet, while overwrites need an existing file. The path
+    /// must not be locked by another user and the owner of the folder receiving the
+    /// file must have enough space.
+    ///
+    /// # Returns
+    /// - `Ok(Option<FileEntry>)` containing the file being overwritten, if any
+    /// - `Err(String)` if the upload cannot be stored
+    fn check_upload_target(
+        &self,
+        conn: &rusqlite::Connection,
+        target_path: &str,
+        total_size: u64,
+        overwrite: bool,
+        username: &str,
+    ) -> Result<Option<FileEntry>, String> {
+        let existing = self.metadata.get_entry(conn, target_path)?;
+
+        match &existing {
+            Some(_) if !overwrite => {
+                let (parent, name) = target_path.rsplit_once('\\').unwrap_or(("", target_path));
+                return Err(format!("File already exists: '{}' in '{}'", name, parent));
+            }
+            Some(entry) if entry.entry_type != EntryType::File => {
+                return Err(format!("Path is not a file: '{}'", target_path));
+            }
+            None if overwrite => return Err(format!("File not found: '{}'", target_path)),
+            _ => {}
+        }
+
+        self.locks.check_unlocked(conn, target_path, username, false)?;
+
+        // Overwritten content is kept as a version, unless versioning is disabled
+        let additional_bytes = match &existing {
+            Some(entry) if self.versions.max_versions() == 0 => total_size as i64 - entry.size,
+            _ => total_size as i64,
+        };
+        if let Some(owner) = QuotaManager::home_owner(target_path) {
+            self.quotas.check_space(conn, &owner, additional_bytes)?;
+        }
+
+        Ok(existing)
+    }
+
+    /// Replaces the content of a file, keeping the current content as a version.
+    ///
+    /// # Arguments
+    /// - `entry`: The file to replace the content of
+    /// - `source_virtual_path`: Where the new content is
+    /// - `copy`: Copy the new content instead of moving it (to restore a version)
+    /// - `username`: The username of the user replacing the content
+    ///
+    /// # Returns
+    /// - `Ok(FileEntry)` containing the updated metadata
+    /// - `Err(String)` if the content cannot be replaced; the file is left unchanged
+    async fn replace_content(
+        &self,
+        entry: &FileEntry,
+        source_virtual_path: &str,
+        copy: bool,
+        username: &str,
+    ) -> Result<FileEntry, String> {
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
 
-        if dest_entry.entry_type != EntryType::Folder {
-            return Err(format!(
-                "Destination is not a directory: '{}'",
-                dest_parent_path
-            ));
+        // Set the current content aside
+        let storage_path = format!("{}\\{}", VERSIONS_DIR, uuid::Uuid::new_v4());
+        self.storage
+            .move_file(&entry.virtual_path, &storage_path)
+            .await?;
+
+        let version = if self.versions.max_versions() > 0 {
+            let recorded = self.versions.insert_version(
+                &conn,
+                &FileVersion {
+                    id: 0,
+                    virtual_path: entry.virtual_path.clone(),
+                    version: 0,
+                    size: entry.size,
+                    mime_type: entry.mime_type.clone(),
+                    modified_at: entry.modified_at.clone(),
+                    replaced_at: String::new(),
+                    replaced_by: username.to_string(),
+                    storage_path: storage_path.clone(),
+                },
+            );
+            match recorded {
+                Ok(version) => Some(version),
+                Err(e) => {
+                    let _ = self.storage.move_file(&storage_path, &entry.virtual_path).await;
+                    return Err(e);
+                }
+            }
+        } else {
+            None
+        };
+
+        // Put the new content in place, bringing the current content back if that fails
+        let placed = if copy {
+            self.storage
+                .copy_file(source_virtual_path, &entry.virtual_path)
+                .await
+        } else {
+            self.storage
+                .move_file(source_virtual_path, &entry.virtual_path)
+                .await
+        };
+        if let Err(e) = placed {
+            if let Some(version) = &version {
+                let _ = self.versions.delete_version(&conn, version.id);
+            }
+            let _ = self.storage.move_file(&storage_path, &entry.virtual_path).await;
+            return Err(e);
+        }
+        if version.is_none() {
+            let _ = self.storage.delete_file(&storage_path).await;
         }
 
-        // Construct new virtual path
-        let new_virtual_path = format!("{}\\{}", dest_parent_path, entry.name);
+        let size = self.storage.get_file_size(&entry.virtual_path).await? as i64;
+        self.metadata.update_size(&conn, &entry.virtual_path, size)?;
 
-        // Check if entry with same name already exists at destination
-        if self.metadata.exists(&conn, &new_virtual_path)? {
-            return Err(format!(
-                "Entry with name '{}' already exists in '{}'",
-                entry.name, dest_parent_path
-            ));
+        // A kept version still counts towards the quota
+        let delta = match version {
+            Some(_) => size,
+            None => size - entry.size,
+        };
+        self.charge_usage(&conn, &entry.virtual_path, delta);
+        self.prune_versions(&entry.virtual_path).await;
+
+        let mime_type = entry.mime_type.as_deref().unwrap_or_default();
+        if let Err(e) = self.index_content(&entry.virtual_path, mime_type, size).await {
+            log::warn!("Failed to index '{}': {}", entry.virtual_path, e);
         }
 
-        // Get new physical path
-        let new_physical_path = self.resolver.virtual_to_physical(&new_virtual_path)?;
+        self.metadata
+            .get_entry(&conn, &entry.virtual_path)?
+            .ok_or_else(|| "Failed to retrieve updated file entry".to_string())
+    }
 
-        // Move physical file/directory
-        match entry.entry_type {
-            EntryType::File => {
-                self.storage
-                    .move_file(source_path, &new_virtual_path)
-                    .await?;
-            }
-            EntryType::Folder => {
-                let old_physical = self.resolver.virtual_to_physical(source_path)?;
-                tokio::fs::rename(&old_physical, &new_physical_path)
-                    .await
-                    .map_err(|e| format!("Failed to move folder: {}", e))?;
+    /// Deletes the oldest versions of a file beyond the configured limit.
+    ///
+    /// Called after the file was updated, so failures are only logged.
+    async fn prune_versions(&self, virtual_path: &str) {
+        let result = async {
+            let conn = crate::db::get_connection()
+                .map_err(|e| format!("Failed to get database connection: {}", e))?;
+            let excess =
+                self.versions
+                    .list_excess(&conn, virtual_path, self.versions.max_versions())?;
+
+            for version in excess {
+                self.delete_version_content(&version).await?;
+                self.versions.delete_version(&conn, version.id)?;
+                self.charge_usage(&conn, virtual_path, -version.size);
             }
+
+            Ok::<(), String>(())
         }
+        .await;
 
-        // Update metadata
-        self.metadata.update_paths(
-            &conn,
-            source_path,
-            &new_virtual_path,
-            new_physical_path.to_str().unwrap_or(""),
-            &entry.name,
-            Some(dest_parent_path),
-        )?;
+        if let Err(e) = result {
+            log::warn!("Failed to prune versions of '{}': {}", virtual_path, e);
+        }
+    }
 
-        // Retrieve and return updated entry
-        let updated_entry = self
-            .metadata
-            .get_entry(&conn, &new_virtual_path)?
-            .ok_or_else(|| "Failed to retrieve moved entry".to_string())?;
+    /// Deletes the versions of an entry and of everything below it.
+    async fn delete_versions_under(&self, virtual_path: &str) -> Result<(), String> {
+        let versions = {
+            let conn = crate::db::get_connection()
+                .map_err(|e| format!("Failed to get database connection: {}", e))?;
+            self.versions.list_under(&conn, virtual_path)?
+        };
 
-        Ok(updated_entry)
+        for version in &versions {
+            self.delete_version_content(version).await?;
+        }
+
+        let conn = crate::db::get_connection()
+            .map_err(|e| format!("Failed to get database connection: {}", e))?;
+        self.versions.delete_under(&conn, virtual_path)?;
+
+        Ok(())
+    }
+
+    async fn delete_version_content(&self, version: &FileVersion) -> Result<(), String> {
+        if self.storage.exists(&version.storage_path).await? {
+            self.storage.delete_file(&version.storage_path).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Deletes the staging files of uploads abandoned without being cancelled.
+    async fn discard_expired_uploads(&self) {
+        for upload in self.uploads.take_expired() {
+            if self.storage.exists(&upload.staging_path).await.unwrap_or(false) {
+                let _ = self.storage.delete_file(&upload.staging_path).await;
+            }
+        }
     }
 
     /// Validates a file or folder name.
@@ -728,6 +3275,93 @@ mod tests {
         assert!(!metadata.exists(&conn, &folder_path).unwrap());
     }
 
+    #[tokio::test]
+    async fn test_upload_and_download_file() {
+        setup_test_db();
+        let ops = FileOperations::new().unwrap();
+
+        let username = "test_ops_user";
+        let parent_path = format!("G:\\Users\\{}", username);
+        let filename = format!("test_upload_{}.txt", chrono::Utc::now().timestamp_millis());
+        let content = b"Informe de control".repeat(100);
+
+        // Create parent directory if it doesn't exist
+        let _ = ops.create_folder("G:\\Users", username, username).await;
+
+        let session = ops
+            .begin_upload(&parent_path, &filename, content.len() as u64, username)
+            .await
+            .unwrap();
+
+        let (first, rest) = content.split_at(1000);
+        assert_eq!(
+            ops.upload_chunk(&session.upload_id, 0, first, username).await.unwrap(),
+            1000
+        );
+        assert!(ops.finish_upload(&session.upload_id, username).await.is_err());
+
+        let session = ops
+            .begin_upload(&parent_path, &filename, content.len() as u64, username)
+            .await
+            .unwrap();
+        ops.upload_chunk(&session.upload_id, 0, first, username).await.unwrap();
+        ops.upload_chunk(&session.upload_id, 1000, rest, username).await.unwrap();
+
+        let entry = ops.finish_upload(&session.upload_id, username).await;
+        assert!(entry.is_ok(), "Failed to upload: {:?}", entry.err());
+        let entry = entry.unwrap();
+        assert_eq!(entry.size, content.len() as i64);
+        assert_eq!(entry.mime_type.as_deref(), Some("text/plain"));
+
+        let file_path = format!("{}\\{}", parent_path, filename);
+        assert_eq!(ops.download_file(&file_path, username).await.unwrap(), content);
+        assert_eq!(
+            ops.read_file_chunk(&file_path, 1000, 5000, username).await.unwrap(),
+            rest
+        );
+
+        // Uploading over an existing file fails
+        assert!(ops
+            .upload_file(&parent_path, &filename, b"again", username)
+            .await
+            .is_err());
+
+        // Cleanup
+        let _ = ops.delete_entry(&file_path, username).await;
+    }
+
+    #[tokio::test]
+    async fn test_upload_rejects_disallowed_types() {
+        setup_test_db();
+        let ops = FileOperations::new().unwrap();
+
+        let username = "test_ops_user";
+        let parent_path = format!("G:\\Users\\{}", username);
+        let suffix = chrono::Utc::now().timestamp_millis();
+
+        let result = ops
+            .upload_file(&parent_path, &format!("setup_{}.exe", suffix), b"MZ", username)
+            .await;
+        assert!(result.unwrap_err().contains("not allowed"));
+
+        // An executable renamed to a whitelisted extension is rejected by its content
+        let result = ops
+            .upload_file(
+                &parent_path,
+                &format!("informe_{}.pdf", suffix),
+                b"MZ\x90\x00\x03\x00\x00\x00",
+                username,
+            )
+            .await;
+        assert!(result.is_err());
+
+        let conn = db::get_connection().unwrap();
+        let metadata = MetadataManager::new();
+        assert!(!metadata
+            .exists(&conn, &format!("{}\\informe_{}.pdf", parent_path, suffix))
+            .unwrap());
+    }
+
     #[tokio::test]
     async fn test_move_entry() {
         setup_test_db();
@@ -768,4 +3402,529 @@ mod tests {
         let _ = ops.delete_entry(&moved_path, username).await;
         let _ = ops.delete_entry(&dest_path, username).await;
     }
+
+    #[tokio::test]
+    async fn test_trash_and_restore_folder() {
+        setup_test_db();
+        let ops = FileOperations::new().unwrap();
+
+        let username = "test_ops_user";
+        let parent_path = format!("G:\\Users\\{}", username);
+        let folder_name = format!("test_trash_{}", chrono::Utc::now().timestamp_millis());
+        let folder_path = format!("{}\\{}", parent_path, folder_name);
+
+        // Create parent directory if it doesn't exist
+        let _ = ops.create_folder("G:\\Users", username, username).await;
+
+        ops.create_folder(&parent_path, &folder_name, username)
+            .await
+            .unwrap();
+        ops.upload_file(&folder_path, "notas.txt", b"control anual", username)
+            .await
+            .unwrap();
+
+        let trashed = ops.move_to_trash(&folder_path, username).await.unwrap();
+        assert_eq!(trashed.original_virtual_path, folder_path);
+        assert_eq!(trashed.size, 13);
+
+        let conn = db::get_connection().unwrap();
+        let metadata = MetadataManager::new();
+        assert!(!metadata.exists(&conn, &folder_path).unwrap());
+        assert!(ops
+            .list_trash(username)
+            .await
+            .unwrap()
+            .iter()
+            .any(|item| item.id == trashed.id));
+
+        // A new folder took the original name, so the restored one gets a suffix
+        ops.create_folder(&parent_path, &folder_name, username)
+            .await
+            .unwrap();
+
+        let restored = ops.restore_from_trash(trashed.id, username).await.unwrap();
+        assert_eq!(restored.name, format!("{} (1)", folder_name));
+
+        let restored_file = format!("{}\\notas.txt", restored.virtual_path);
+        assert!(metadata.exists(&conn, &restored_file).unwrap());
+        assert_eq!(
+            ops.download_file(&restored_file, username).await.unwrap(),
+            b"control anual"
+        );
+        assert!(ops.restore_from_trash(trashed.id, username).await.is_err());
+
+        // Cleanup
+        let _ = ops.delete_entry(&restored.virtual_path, username).await;
+        let _ = ops.delete_entry(&folder_path, username).await;
+    }
+
+    #[tokio::test]
+    async fn test_restore_recreates_missing_parent() {
+        setup_test_db();
+        let ops = FileOperations::new().unwrap();
+
+        let username = "test_ops_user";
+        let parent_path = format!("G:\\Users\\{}", username);
+        let folder_name = format!("test_trash_parent_{}", chrono::Utc::now().timestamp_millis());
+        let folder_path = format!("{}\\{}", parent_path, folder_name);
+        let file_path = format!("{}\\receta.txt", folder_path);
+
+        // Create parent directory if it doesn't exist
+        let _ = ops.create_folder("G:\\Users", username, username).await;
+
+        ops.create_folder(&parent_path, &folder_name, username)
+            .await
+            .unwrap();
+        ops.upload_file(&folder_path, "receta.txt", b"amoxicilina", username)
+            .await
+            .unwrap();
+
+        // Trash the file, then delete its folder for good
+        let trashed = ops.move_to_trash(&file_path, username).await.unwrap();
+        ops.delete_entry(&folder_path, username).await.unwrap();
+
+        let restored = ops.restore_from_trash(trashed.id, username).await.unwrap();
+        assert_eq!(restored.virtual_path, file_path);
+
+        let conn = db::get_connection().unwrap();
+        let folder = MetadataManager::new()
+            .get_entry(&conn, &folder_path)
+            .unwrap()
+            .expect("parent folder should be recreated");
+        assert_eq!(folder.entry_type, EntryType::Folder);
+
+        // Cleanup
+        let _ = ops.delete_entry(&folder_path, username).await;
+    }
+
+    #[tokio::test]
+    async fn test_patient_links_follow_renamed_folder() {
+        let conn = setup_test_db();
+        let ops = FileOperations::new().unwrap();
+
+        let username = "test_ops_user";
+        let parent_path = format!("G:\\Users\\{}", username);
+        let suffix = chrono::Utc::now().timestamp_millis();
+        let folder_name = format!("test_links_{}", suffix);
+        let folder_path = format!("{}\\{}", parent_path, folder_name);
+
+        conn.execute(
+            "INSERT INTO patients (first_name, last_name) VALUES ('Test', 'Links')",
+            [],
+        )
+        .unwrap();
+        let patient_id = conn.last_insert_rowid();
+
+        // Create parent directory if it doesn't exist
+        let _ = ops.create_folder("G:\\Users", username, username).await;
+
+        ops.create_folder(&parent_path, &folder_name, username)
+            .await
+            .unwrap();
+        let file = ops
+            .upload_file(&folder_path, "consentimiento.txt", b"firmado", username)
+            .await
+            .unwrap();
+
+        let link = ops
+            .link_to_patient(&file.virtual_path, patient_id, Some("Consentimiento"), username)
+            .await
+            .unwrap();
+        assert_eq!(link.notes.as_deref(), Some("Consentimiento"));
+        assert!(ops
+            .link_to_patient(&file.virtual_path, -1, None, username)
+            .await
+            .is_err());
+
+        // Renaming the folder moves the link with the file
+        let renamed = ops
+            .rename_entry(&folder_path, &format!("{}_renamed", folder_name), username)
+            .await
+            .unwrap();
+        let files = ops.get_patient_files(patient_id, username).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            files[0].entry.virtual_path,
+            format!("{}\\consentimiento.txt", renamed.virtual_path)
+        );
+
+        // Trashed files are hidden and come back with their link
+        let trashed = ops
+            .move_to_trash(&renamed.virtual_path, username)
+            .await
+            .unwrap();
+        assert!(ops.get_patient_files(patient_id, username).await.unwrap().is_empty());
+        ops.restore_from_trash(trashed.id, username).await.unwrap();
+        assert_eq!(ops.get_patient_files(patient_id, username).await.unwrap().len(), 1);
+
+        ops.unlink_from_patient(&files[0].entry.virtual_path, patient_id, username)
+            .await
+            .unwrap();
+        assert!(ops.get_patient_files(patient_id, username).await.unwrap().is_empty());
+
+        // Cleanup
+        let _ = ops.delete_entry(&renamed.virtual_path, username).await;
+        let _ = conn.execute("DELETE FROM patients WHERE id = ?1", [patient_id]);
+    }
+
+    #[tokio::test]
+    async fn test_operations_are_audited() {
+        setup_test_db();
+        let ops = FileOperations::new().unwrap();
+
+        let username = "test_ops_user";
+        let parent_path = format!("G:\\Users\\{}", username);
+        let folder_name = format!("test_audit_{}", chrono::Utc::now().timestamp_millis());
+        let folder_path = format!("{}\\{}", parent_path, folder_name);
+
+        // Create parent directory if it doesn't exist
+        let _ = ops.create_folder("G:\\Users", username, username).await;
+
+        ops.create_folder(&parent_path, &folder_name, username)
+            .await
+            .unwrap();
+        assert!(ops
+            .download_file(&format!("{}\\missing.pdf", folder_path), username)
+            .await
+            .is_err());
+
+        // Reading the log is reserved to admins
+        let filter = AuditLogFilter {
+            username: Some(username.to_string()),
+            path_prefix: Some(folder_path.clone()),
+            ..Default::default()
+        };
+        assert!(ops.get_audit_logs(&filter, username).await.is_err());
+
+        let conn = db::get_connection().unwrap();
+        let entries = AuditLog::new().query(&conn, &filter).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].operation, OP_DOWNLOAD);
+        assert!(!entries[0].success);
+        assert!(entries[0].error_message.is_some());
+        assert_eq!(entries[1].operation, OP_CREATE_FOLDER);
+        assert_eq!(entries[1].path, folder_path);
+        assert!(entries[1].success);
+
+        // Cleanup
+        let _ = ops.delete_entry(&folder_path, username).await;
+    }
+
+    #[tokio::test]
+    async fn test_quota_blocks_uploads_and_tracks_usage() {
+        let conn = setup_test_db();
+        let ops = FileOperations::new().unwrap();
+
+        // A dedicated user, so uploads from other tests don't change its usage
+        let username = format!("test_quota_{}", chrono::Utc::now().timestamp_millis());
+        conn.execute(
+            r#"
+            INSERT INTO users (username, password_hash, name, role, created_at, updated_at)
+            VALUES (?1, 'hash', 'Test Quota User', 'user', datetime('now'), datetime('now'))
+            "#,
+            [&username],
+        )
+        .unwrap();
+        let home = format!("G:\\Users\\{}", username);
+
+        let quotas = QuotaManager::new();
+        quotas.set_quota(&conn, &username, Some(10)).unwrap();
+
+        let file = ops
+            .upload_file(&home, "nota.txt", b"123456", &username)
+            .await
+            .unwrap();
+        assert_eq!(quotas.get_quota(&conn, &username).unwrap().used_bytes, 6);
+
+        // 6 + 5 bytes is over the 10 byte quota
+        let result = ops.upload_file(&home, "otra.txt", b"12345", &username).await;
+        assert!(result.unwrap_err().contains("quota"));
+        assert!(ops.set_storage_quota(&username, None, &username).await.is_err());
+
+        ops.delete_entry(&file.virtual_path, &username).await.unwrap();
+        let quota = quotas.get_quota(&conn, &username).unwrap();
+        assert_eq!(quota.used_bytes, 0);
+        assert_eq!(quota.available_bytes, Some(10));
+        assert_eq!(quotas.recalculate(&conn, &username).unwrap(), 0);
+
+        // Cleanup
+        let _ = conn.execute("DELETE FROM filesystem_quotas WHERE username = ?1", [&username]);
+        let _ = conn.execute("DELETE FROM users WHERE username = ?1", [&username]);
+    }
+
+    #[tokio::test]
+    async fn test_locks_block_other_users() {
+        let conn = setup_test_db();
+        let ops = FileOperations::new().unwrap();
+
+        let username = "test_ops_user";
+        let admin = "test_lock_admin";
+        conn.execute(
+            r#"
+            INSERT OR IGNORE INTO users (username, password_hash, name, role, created_at, updated_at)
+            VALUES (?1, 'hash', 'Test Lock Admin', 'admin', datetime('now'), datetime('now'))
+            "#,
+            [admin],
+        )
+        .unwrap();
+
+        let parent_path = format!("G:\\Users\\{}", username);
+        let folder_name = format!("test_lock_{}", chrono::Utc::now().timestamp_millis());
+        let folder_path = format!("{}\\{}", parent_path, folder_name);
+
+        // Create parent directory if it doesn't exist
+        let _ = ops.create_folder("G:\\Users", username, username).await;
+
+        ops.create_folder(&parent_path, &folder_name, username)
+            .await
+            .unwrap();
+        let file = ops
+            .upload_file(&folder_path, "receta.txt", b"amoxicilina", username)
+            .await
+            .unwrap();
+
+        let lock = ops
+            .acquire_lock(&file.virtual_path, Some(5), username)
+            .await
+            .unwrap();
+        assert_eq!(lock.locked_by, username);
+        assert!(ops
+            .acquire_lock(&file.virtual_path, None, admin)
+            .await
+            .is_err());
+
+        // Neither the file nor the folder containing it can be changed by others
+        let result = ops.rename_entry(&file.virtual_path, "otra.txt", admin).await;
+        assert!(result.unwrap_err().contains("locked by"));
+        let result = ops.move_to_trash(&folder_path, admin).await;
+        assert!(result.unwrap_err().contains("locked by"));
+
+        // The holder can, and the lock follows the file
+        let renamed = ops
+            .rename_entry(&file.virtual_path, "receta_final.txt", username)
+            .await
+            .unwrap();
+        let status = ops
+            .get_lock_status(&renamed.virtual_path, admin)
+            .await
+            .unwrap();
+        assert_eq!(status.unwrap().locked_by, username);
+
+        // Only admins can break someone else's lock
+        assert!(ops.release_lock(&renamed.virtual_path, admin).await.is_err());
+        assert!(ops.force_unlock(&renamed.virtual_path, username).await.is_err());
+        let released = ops.force_unlock(&renamed.virtual_path, admin).await.unwrap();
+        assert_eq!(released.unwrap().locked_by, username);
+        assert!(ops
+            .get_lock_status(&renamed.virtual_path, username)
+            .await
+            .unwrap()
+            .is_none());
+
+        // Cleanup
+        let _ = ops.delete_entry(&folder_path, admin).await;
+    }
+
+    #[tokio::test]
+    async fn test_search_finds_content_and_respects_permissions() {
+        let conn = setup_test_db();
+        let ops = FileOperations::new().unwrap();
+
+        let username = "test_ops_user";
+        let other = "test_search_other";
+        conn.execute(
+            r#"
+            INSERT OR IGNORE INTO users (username, password_hash, name, role, created_at, updated_at)
+            VALUES (?1, 'hash', 'Test Search User', 'user', datetime('now'), datetime('now'))
+            "#,
+            [other],
+        )
+        .unwrap();
+
+        let parent_path = format!("G:\\Users\\{}", username);
+        let folder_name = format!("test_search_{}", chrono::Utc::now().timestamp_millis());
+        let folder_path = format!("{}\\{}", parent_path, folder_name);
+
+        // Create parent directory if it doesn't exist
+        let _ = ops.create_folder("G:\\Users", username, username).await;
+
+        ops.create_folder(&parent_path, &folder_name, username)
+            .await
+            .unwrap();
+        ops.upload_file(
+            &folder_path,
+            "derivacion.txt",
+            "Derivación a endodoncia: pulpitis irreversible en pieza 36".as_bytes(),
+            username,
+        )
+        .await
+        .unwrap();
+
+        let query = SearchQuery {
+            text: Some("pulpitis endodon".to_string()),
+            root_path: Some(folder_path.clone()),
+            ..Default::default()
+        };
+        let results = ops.search(&query, username).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].entry.name, "derivacion.txt");
+        assert!(results[0].snippet.as_deref().unwrap().contains("[pulpitis]"));
+
+        // The index follows renamed folders
+        let renamed = ops
+            .rename_entry(&folder_path, &format!("{}_renamed", folder_name), username)
+            .await
+            .unwrap();
+        let query = SearchQuery {
+            root_path: Some(renamed.virtual_path.clone()),
+            ..query
+        };
+        assert_eq!(ops.search(&query, username).await.unwrap().len(), 1);
+
+        // Metadata filters, and other users don't see files they can't read
+        let by_type = SearchQuery {
+            text: None,
+            mime_type: Some("text/*".to_string()),
+            entry_type: Some(EntryType::File),
+            ..query.clone()
+        };
+        assert_eq!(ops.search(&by_type, username).await.unwrap().len(), 1);
+        assert!(ops.search(&query, other).await.unwrap().is_empty());
+
+        // Cleanup
+        let _ = ops.delete_entry(&renamed.virtual_path, username).await;
+    }
+
+    #[tokio::test]
+    async fn test_overwrite_keeps_versions() {
+        let conn = setup_test_db();
+        let ops = FileOperations::new().unwrap();
+
+        // A dedicated user, so the usage only counts this file and its versions
+        let username = format!("test_versions_{}", chrono::Utc::now().timestamp_millis());
+        conn.execute(
+            r#"
+            INSERT INTO users (username, password_hash, name, role, created_at, updated_at)
+            VALUES (?1, 'hash', 'Test Versions User', 'user', datetime('now'), datetime('now'))
+            "#,
+            [&username],
+        )
+        .unwrap();
+        let home = format!("G:\\Users\\{}", username);
+        let quotas = QuotaManager::new();
+
+        let file = ops
+            .upload_file(&home, "nota.txt", b"v1", &username)
+            .await
+            .unwrap();
+
+        // Overwriting needs an existing file
+        let missing = format!("{}\\otra.txt", home);
+        assert!(ops.begin_overwrite(&missing, 2, &username).await.is_err());
+
+        let session = ops
+            .begin_overwrite(&file.virtual_path, 3, &username)
+            .await
+            .unwrap();
+        ops.upload_chunk(&session.upload_id, 0, b"v22", &username)
+            .await
+            .unwrap();
+        let updated = ops
+            .finish_upload(&session.upload_id, &username)
+            .await
+            .unwrap();
+        assert_eq!(updated.size, 3);
+
+        let versions = ops.list_versions(&file.virtual_path, &username).await.unwrap();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].version, 1);
+        assert_eq!(versions[0].size, 2);
+        let old = ops
+            .read_version_chunk(&file.virtual_path, 1, 0, 1024, &username)
+            .await
+            .unwrap();
+        assert_eq!(old, b"v1");
+
+        // Versions count towards the quota
+        assert_eq!(quotas.get_quota(&conn, &username).unwrap().used_bytes, 5);
+
+        // Restoring keeps the replaced content as a new version
+        let restored = ops
+            .restore_version(&file.virtual_path, 1, &username)
+            .await
+            .unwrap();
+        assert_eq!(restored.size, 2);
+        assert_eq!(
+            ops.download_file(&file.virtual_path, &username).await.unwrap(),
+            b"v1"
+        );
+        let versions = ops.list_versions(&file.virtual_path, &username).await.unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0].size, 3);
+        assert_eq!(quotas.get_quota(&conn, &username).unwrap().used_bytes, 7);
+        assert_eq!(quotas.recalculate(&conn, &username).unwrap(), 7);
+
+        // Deleting the file deletes its versions
+        ops.delete_entry(&file.virtual_path, &username).await.unwrap();
+        assert_eq!(quotas.get_quota(&conn, &username).unwrap().used_bytes, 0);
+        assert!(VersionManager::new()
+            .list_versions(&conn, &file.virtual_path)
+            .unwrap()
+            .is_empty());
+
+        // Cleanup
+        let _ = conn.execute("DELETE FROM filesystem_quotas WHERE username = ?1", [&username]);
+        let _ = conn.execute("DELETE FROM users WHERE username = ?1", [&username]);
+    }
+
+    #[tokio::test]
+    async fn test_thumbnails_are_generated_and_cached() {
+        let conn = setup_test_db();
+        let ops = FileOperations::new().unwrap();
+
+        let username = "test_ops_user";
+        let parent_path = format!("G:\\Users\\{}", username);
+        let folder_name = format!("test_thumbs_{}", chrono::Utc::now().timestamp_millis());
+        let folder_path = format!("{}\\{}", parent_path, folder_name);
+
+        // Create parent directory if it doesn't exist
+        let _ = ops.create_folder("G:\\Users", username, username).await;
+
+        ops.create_folder(&parent_path, &folder_name, username)
+            .await
+            .unwrap();
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::ImageLuma8(image::GrayImage::new(800, 400))
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        let file = ops
+            .upload_file(&folder_path, "panoramica.png", png.get_ref(), username)
+            .await
+            .unwrap();
+        let note = ops
+            .upload_file(&folder_path, "nota.txt", b"texto", username)
+            .await
+            .unwrap();
+
+        let thumbnail = ops.get_thumbnail(&file.virtual_path, username).await.unwrap();
+        let decoded = image::load_from_memory(&thumbnail).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (256, 128));
+        assert!(ops.get_thumbnail(&note.virtual_path, username).await.is_err());
+        assert!(ops.get_dicom_tags(&file.virtual_path, username).await.is_err());
+
+        // The thumbnail is cached, and deleted with the file metadata
+        let cached = |conn: &rusqlite::Connection| -> i64 {
+            conn.query_row(
+                "SELECT COUNT(*) FROM filesystem_thumbnails WHERE file_id = ?1",
+                [file.id],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(cached(&conn), 1);
+
+        ops.delete_entry(&folder_path, username).await.unwrap();
+        assert_eq!(cached(&conn), 0);
+    }
 }
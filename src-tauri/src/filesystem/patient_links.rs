@@ -0,0 +1,236 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::FileEntry;
+
+/// PatientLinkManager handles the links between filesystem entries and patients.
+///
+/// Links are stored in `filesystem_patient_links` by virtual path, so they have to
+/// follow their entry when it is renamed, moved or trashed. Links of trashed entries
+/// point into the trash area and come back when the entry is restored.
+#[derive(Debug, Clone)]
+pub struct PatientLinkManager;
+
+/// A link between a file or folder and a patient record.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatientFileLink {
+    pub id: i64,
+    pub virtual_path: String,
+    pub patient_id: i64,
+    pub linked_at: String,
+    pub linked_by: String,
+    pub notes: Option<String>,
+}
+
+/// A file linked to a patient, as listed in the patient's documents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatientFile {
+    pub entry: FileEntry,
+    pub link: PatientFileLink,
+}
+
+const LINK_COLUMNS: &str = "id, virtual_path, patient_id, linked_at, linked_by, notes";
+
+impl PatientLinkManager {
+    /// Creates a new PatientLinkManager instance.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Links an entry to a patient, replacing the notes if the link already exists.
+    ///
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `virtual_path`: The virtual path of the entry
+    /// - `patient_id`: The ID of the patient
+    /// - `linked_by`: The username of the user creating the link
+    /// - `notes`: Optional notes about the document
+    ///
+    /// # Returns
+    /// - `Ok(PatientFileLink)` containing the link
+    /// - `Err(String)` if the insertion fails
+    pub fn link(
+        &self,
+        conn: &Connection,
+        virtual_path: &str,
+        patient_id: i64,
+        linked_by: &str,
+        notes: Option<&str>,
+    ) -> Result<PatientFileLink, String> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        conn.execute(
+            r#"
+            INSERT INTO filesystem_patient_links (virtual_path, patient_id, linked_at, linked_by, notes)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT(virtual_path, patient_id) DO UPDATE SET notes = excluded.notes
+            "#,
+            params![virtual_path, patient_id, now, linked_by, notes],
+        )
+        .map_err(|e| format!("Failed to link entry to patient: {}", e))?;
+
+        let query = format!(
+            "SELECT {} FROM filesystem_patient_links WHERE virtual_path = ?1 AND patient_id = ?2",
+            LINK_COLUMNS
+        );
+        conn.query_row(&query, params![virtual_path, patient_id], Self::row_to_link)
+            .map_err(|e| format!("Failed to get patient link: {}", e))
+    }
+
+    /// Removes the link between an entry and a patient.
+    ///
+    /// # Returns
+    /// - `Ok(())` if the link was removed
+    /// - `Err(String)` if the deletion fails or the link doesn't exist
+    pub fn unlink(&self, conn: &Connection, virtual_path: &str, patient_id: i64) -> Result<(), String> {
+        let rows_affected = conn
+            .execute(
+                "DELETE FROM filesystem_patient_links WHERE virtual_path = ?1 AND patient_id = ?2",
+                params![virtual_path, patient_id],
+            )
+            .map_err(|e| format!("Failed to unlink entry from patient: {}", e))?;
+
+        if rows_affected == 0 {
+            return Err(format!(
+                "'{}' is not linked to patient {}",
+                virtual_path, patient_id
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Checks if a patient record exists.
+    pub fn patient_exists(&self, conn: &Connection, patient_id: i64) -> Result<bool, String> {
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM patients WHERE id = ?1",
+                params![patient_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to check patient: {}", e))?;
+
+        Ok(count > 0)
+    }
+
+    /// Lists the entries linked to a patient, most recently linked first.
+    ///
+    /// Links whose entry no longer has metadata (such as trashed entries) are skipped.
+    ///
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `patient_id`: The ID of the patient
+    ///
+    /// # Returns
+    /// - `Ok(Vec<PatientFile>)` containing the linked entries
+    /// - `Err(String)` if the query fails
+    pub fn list_for_patient(
+        &self,
+        conn: &Connection,
+        patient_id: i64,
+    ) -> Result<Vec<PatientFile>, String> {
+        let mut stmt = conn
+            .prepare(
+                r#"
+                SELECT l.id, l.virtual_path, l.patient_id, l.linked_at, l.linked_by, l.notes,
+                       m.id, m.physical_path, m.name, m.entry_type, m.size,
+                       m.mime_type, m.owner_username, m.created_at, m.modified_at, m.parent_path
+                FROM filesystem_patient_links l
+                INNER JOIN filesystem_metadata m ON m.virtual_path = l.virtual_path
+                WHERE l.patient_id = ?1
+                ORDER BY l.linked_at DESC
+                "#,
+            )
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let files = stmt
+            .query_map(params![patient_id], |row| {
+                let link = Self::row_to_link(row)?;
+                let entry_type_str: String = row.get(9)?;
+                let entry_type = super::EntryType::from_db_string(&entry_type_str)
+                    .map_err(|_| rusqlite::Error::InvalidQuery)?;
+
+                Ok(PatientFile {
+                    entry: FileEntry {
+                        id: row.get(6)?,
+                        virtual_path: link.virtual_path.clone(),
+                        physical_path: row.get(7)?,
+                        name: row.get(8)?,
+                        entry_type,
+                        size: row.get(10)?,
+                        mime_type: row.get(11)?,
+                        owner_username: row.get(12)?,
+                        created_at: row.get(13)?,
+                        modified_at: row.get(14)?,
+                        parent_path: row.get(15)?,
+                    },
+                    link,
+                })
+            })
+            .map_err(|e| format!("Failed to query patient files: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to collect patient files: {}", e))?;
+
+        Ok(files)
+    }
+
+    /// Points the links of an entry and of everything below it to a new path.
+    ///
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `old_virtual_path`: The previous virtual path of the entry
+    /// - `new_virtual_path`: The new virtual path of the entry
+    ///
+    /// # Returns
+    /// - `Ok(usize)` containing the number of updated links
+    /// - `Err(String)` if the update fails
+    pub fn relocate(
+        &self,
+        conn: &Connection,
+        old_virtual_path: &str,
+        new_virtual_path: &str,
+    ) -> Result<usize, String> {
+        conn.execute(
+            r#"
+            UPDATE filesystem_patient_links
+            SET virtual_path = ?2 || substr(virtual_path, length(?1) + 1)
+            WHERE virtual_path = ?1 OR substr(virtual_path, 1, length(?1) + 1) = ?1 || '\'
+            "#,
+            params![old_virtual_path, new_virtual_path],
+        )
+        .map_err(|e| format!("Failed to relocate patient links: {}", e))
+    }
+
+    /// Removes the links of an entry and of everything below it.
+    ///
+    /// # Returns
+    /// - `Ok(usize)` containing the number of removed links
+    /// - `Err(String)` if the deletion fails
+    pub fn delete_under(&self, conn: &Connection, virtual_path: &str) -> Result<usize, String> {
+        conn.execute(
+            r#"
+            DELETE FROM filesystem_patient_links
+            WHERE virtual_path = ?1 OR substr(virtual_path, 1, length(?1) + 1) = ?1 || '\'
+            "#,
+            params![virtual_path],
+        )
+        .map_err(|e| format!("Failed to delete patient links: {}", e))
+    }
+
+    fn row_to_link(row: &rusqlite::Row) -> rusqlite::Result<PatientFileLink> {
+        Ok(PatientFileLink {
+            id: row.get(0)?,
+            virtual_path: row.get(1)?,
+            patient_id: row.get(2)?,
+            linked_at: row.get(3)?,
+            linked_by: row.get(4)?,
+            notes: row.get(5)?,
+        })
+    }
+}
+
+impl Default for PatientLinkManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -182,7 +182,7 @@ impl PermissionManager {
     /// # Returns
     /// - `Ok(bool)` - true if the user is an admin, false otherwise
     /// - `Err(String)` if the check fails
-    fn is_admin(&self, username: &str) -> Result<bool, String> {
+    pub fn is_admin(&self, username: &str) -> Result<bool, String> {
         let conn = crate::db::get_connection()
             .map_err(|e| format!("Failed to get database connection: {}", e))?;
         
@@ -251,6 +251,7 @@ impl PermissionManager {
     /// - `username`: The username to grant permissions to
     /// - `virtual_path`: The virtual path to grant access to
     /// - `permissions`: The permissions to grant (read, write, delete)
+    /// - `granted_by`: The username of the user granting the permissions
     /// 
     /// # Returns
     /// - `Ok(())` if the permissions were granted successfully
@@ -263,6 +264,7 @@ impl PermissionManager {
         username: &str,
         virtual_path: &str,
         permissions: FilePermissions,
+        granted_by: &str,
     ) -> Result<(), String> {
         let conn = crate::db::get_connection()
             .map_err(|e| format!("Failed to get database connection: {}", e))?;
@@ -284,7 +286,7 @@ impl PermissionManager {
                 permissions.can_write,
                 permissions.can_delete,
                 now,
-                "system", // TODO: Get current admin user from session
+                granted_by,
             ],
         )
         .map_err(|e| format!("Failed to grant access: {}", e))?;
@@ -540,7 +542,7 @@ mod tests {
             can_delete: false,
         };
         
-        let grant_result = manager.grant_access("test_user1", shared_path, permissions);
+        let grant_result = manager.grant_access("test_user1", shared_path, permissions, "system");
         assert!(grant_result.is_ok());
         
         // Now test_user1 should have read access
@@ -599,7 +601,7 @@ mod tests {
             can_delete: false,
         };
         
-        manager.grant_access("test_user1", shared_path, permissions).unwrap();
+        manager.grant_access("test_user1", shared_path, permissions, "system").unwrap();
         
         // Now it should be considered shared
         let result = manager.is_shared_path(shared_path);
@@ -624,7 +626,7 @@ mod tests {
             can_delete: true,
         };
         
-        manager.grant_access("test_user1", shared_path, permissions).unwrap();
+        manager.grant_access("test_user1", shared_path, permissions, "system").unwrap();
         
         // Verify access
         assert!(manager.can_read("test_user1", shared_path).unwrap());
@@ -651,7 +653,7 @@ mod tests {
             can_delete: false,
         };
         
-        manager.grant_access("test_user1", shared_path, permissions).unwrap();
+        manager.grant_access("test_user1", shared_path, permissions, "system").unwrap();
         
         // Verify read-only
         assert!(manager.can_read("test_user1", shared_path).unwrap());
@@ -664,7 +666,7 @@ mod tests {
             can_delete: true,
         };
         
-        manager.grant_access("test_user1", shared_path, permissions).unwrap();
+        manager.grant_access("test_user1", shared_path, permissions, "system").unwrap();
         
         // Verify full access
         assert!(manager.can_read("test_user1", shared_path).unwrap());
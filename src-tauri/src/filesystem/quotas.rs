@@ -0,0 +1,277 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use super::versions::VersionManager;
+use super::MetadataManager;
+
+/// Parent of the personal folders that count towards quotas.
+const USERS_PREFIX: &str = "G:\\Users\\";
+
+/// Configuration key of the quota for users without one of their own, in MB (0 = unlimited).
+const DEFAULT_QUOTA_CONFIG_KEY: &str = "fsDefaultQuotaMb";
+
+const BYTES_PER_MB: i64 = 1024 * 1024;
+
+/// QuotaManager keeps track of the space used by each user and enforces quotas.
+///
+/// Usage is the size of the user's personal folder (`G:\Users\{username}`) as
+/// computed by `MetadataManager::calculate_folder_size`, plus the previous versions
+/// of the files in it. It is stored in `filesystem_quotas` and updated
+/// incrementally by `FileOperations`; a periodic recalculation fixes any drift.
+/// Shared folders and the trash don't count.
+#[derive(Debug, Clone)]
+pub struct QuotaManager {
+    metadata: MetadataManager,
+    versions: VersionManager,
+}
+
+/// Storage usage and quota of a user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageQuota {
+    pub username: String,
+    /// `None` when the user has no limit
+    pub total_bytes: Option<i64>,
+    pub used_bytes: i64,
+    pub available_bytes: Option<i64>,
+    pub percentage_used: Option<f64>,
+    /// Whether the quota is set for the user or comes from the default
+    pub is_custom: bool,
+    pub last_calculated: String,
+}
+
+impl QuotaManager {
+    /// Creates a new QuotaManager instance.
+    pub fn new() -> Self {
+        Self {
+            metadata: MetadataManager::new(),
+            versions: VersionManager::new(),
+        }
+    }
+
+    /// Returns the user whose personal folder contains the path, if any.
+    pub fn home_owner(virtual_path: &str) -> Option<String> {
+        let relative = virtual_path.strip_prefix(USERS_PREFIX)?;
+        let username = relative.split('\\').next()?;
+
+        if username.is_empty() {
+            None
+        } else {
+            Some(username.to_string())
+        }
+    }
+
+    /// Gets the usage and quota of a user.
+    ///
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `username`: The user to get the quota of
+    ///
+    /// # Returns
+    /// - `Ok(StorageQuota)` containing the usage and limit
+    /// - `Err(String)` if the query fails
+    pub fn get_quota(&self, conn: &Connection, username: &str) -> Result<StorageQuota, String> {
+        let (custom_quota, used_bytes, last_calculated) = self.ensure_row(conn, username)?;
+        let total_bytes = custom_quota.or_else(Self::default_quota);
+
+        Ok(StorageQuota {
+            username: username.to_string(),
+            total_bytes,
+            used_bytes,
+            available_bytes: total_bytes.map(|total| (total - used_bytes).max(0)),
+            percentage_used: total_bytes
+                .filter(|total| *total > 0)
+                .map(|total| used_bytes as f64 * 100.0 / total as f64),
+            is_custom: custom_quota.is_some(),
+            last_calculated,
+        })
+    }
+
+    /// Sets the quota of a user.
+    ///
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `username`: The user to set the quota of
+    /// - `quota_bytes`: The quota in bytes, or `None` to use the default quota
+    ///
+    /// # Returns
+    /// - `Ok(())` if the quota was saved
+    /// - `Err(String)` if the quota is negative or the update fails
+    pub fn set_quota(
+        &self,
+        conn: &Connection,
+        username: &str,
+        quota_bytes: Option<i64>,
+    ) -> Result<(), String> {
+        if quota_bytes.map_or(false, |quota| quota < 0) {
+            return Err("Quota cannot be negative".to_string());
+        }
+
+        self.ensure_row(conn, username)?;
+        conn.execute(
+            "UPDATE filesystem_quotas SET quota_bytes = ?1 WHERE username = ?2",
+            params![quota_bytes, username],
+        )
+        .map_err(|e| format!("Failed to set quota: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Checks that writing `additional_bytes` more keeps the user within quota.
+    ///
+    /// # Returns
+    /// - `Ok(())` if there is enough space (or the user has no limit)
+    /// - `Err(String)` if the write would exceed the quota
+    pub fn check_space(
+        &self,
+        conn: &Connection,
+        username: &str,
+        additional_bytes: i64,
+    ) -> Result<(), String> {
+        let quota = self.get_quota(conn, username)?;
+
+        if let Some(total_bytes) = quota.total_bytes {
+            if quota.used_bytes + additional_bytes > total_bytes {
+                return Err(format!(
+                    "Storage quota exceeded: user '{}' is using {} of {} bytes and needs {} more",
+                    username, quota.used_bytes, total_bytes, additional_bytes
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds `delta` bytes (negative to subtract) to the usage of a user.
+    ///
+    /// Users without a usage record yet get it calculated instead, which already
+    /// includes the change.
+    pub fn adjust_usage(&self, conn: &Connection, username: &str, delta: i64) -> Result<(), String> {
+        let rows_affected = conn
+            .execute(
+                "UPDATE filesystem_quotas SET used_bytes = MAX(0, used_bytes + ?1) WHERE username = ?2",
+                params![delta, username],
+            )
+            .map_err(|e| format!("Failed to update storage usage: {}", e))?;
+
+        if rows_affected == 0 {
+            self.recalculate(conn, username)?;
+        }
+
+        Ok(())
+    }
+
+    /// Recalculates the usage of a user from the metadata and the file versions.
+    ///
+    /// # Returns
+    /// - `Ok(i64)` containing the usage in bytes
+    /// - `Err(String)` if the calculation or the update fails
+    pub fn recalculate(&self, conn: &Connection, username: &str) -> Result<i64, String> {
+        let home = format!("{}{}", USERS_PREFIX, username);
+        let used_bytes = self.metadata.calculate_folder_size(conn, &home)?
+            + self.versions.size_under(conn, &home)?;
+        let now = chrono::Utc::now().to_rfc3339();
+
+        conn.execute(
+            r#"
+            INSERT INTO filesystem_quotas (username, quota_bytes, used_bytes, last_calculated)
+            VALUES (?1, NULL, ?2, ?3)
+            ON CONFLICT(username) DO UPDATE SET
+                used_bytes = excluded.used_bytes,
+                last_calculated = excluded.last_calculated
+            "#,
+            params![username, used_bytes, now],
+        )
+        .map_err(|e| format!("Failed to save storage usage: {}", e))?;
+
+        Ok(used_bytes)
+    }
+
+    /// Recalculates the usage of every user.
+    ///
+    /// # Returns
+    /// - `Ok(usize)` containing the number of users whose usage had drifted
+    /// - `Err(String)` if listing users or a calculation fails
+    pub fn recalculate_all(&self, conn: &Connection) -> Result<usize, String> {
+        let mut stmt = conn
+            .prepare(
+                r#"
+                SELECT u.username, q.used_bytes
+                FROM users u
+                LEFT JOIN filesystem_quotas q ON q.username = u.username
+                "#,
+            )
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let users = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?)))
+            .map_err(|e| format!("Failed to query users: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to collect users: {}", e))?;
+
+        let mut drifted = 0;
+        for (username, stored) in users {
+            let used_bytes = self.recalculate(conn, &username)?;
+            if stored.map_or(false, |stored| stored != used_bytes) {
+                drifted += 1;
+            }
+        }
+
+        Ok(drifted)
+    }
+
+    /// Returns the quota row of a user, creating it with the calculated usage if missing.
+    fn ensure_row(
+        &self,
+        conn: &Connection,
+        username: &str,
+    ) -> Result<(Option<i64>, i64, String), String> {
+        let query = "SELECT quota_bytes, used_bytes, last_calculated FROM filesystem_quotas WHERE username = ?1";
+        let read = |conn: &Connection| {
+            conn.query_row(query, params![username], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .optional()
+            .map_err(|e| format!("Failed to get quota: {}", e))
+        };
+
+        if let Some(row) = read(conn)? {
+            return Ok(row);
+        }
+
+        self.recalculate(conn, username)?;
+        read(conn)?.ok_or_else(|| format!("Failed to create quota for user '{}'", username))
+    }
+
+    /// The quota of users without one of their own, in bytes.
+    fn default_quota() -> Option<i64> {
+        crate::config::current_value(DEFAULT_QUOTA_CONFIG_KEY)
+            .and_then(|value| value.as_i64())
+            .filter(|mb| *mb > 0)
+            .map(|mb| mb * BYTES_PER_MB)
+    }
+}
+
+impl Default for QuotaManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_home_owner() {
+        assert_eq!(
+            QuotaManager::home_owner("G:\\Users\\dra.perez\\Radiografias\\rx.png"),
+            Some("dra.perez".to_string())
+        );
+        assert_eq!(
+            QuotaManager::home_owner("G:\\Users\\dra.perez"),
+            Some("dra.perez".to_string())
+        );
+        assert_eq!(QuotaManager::home_owner("G:\\Users"), None);
+        assert_eq!(QuotaManager::home_owner("G:\\Compartido\\plantilla.docx"), None);
+    }
+}
@@ -0,0 +1,347 @@
+use rusqlite::{params, params_from_iter, types::Value, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::audit::parse_bound;
+use super::{EntryType, FileEntry};
+
+/// Results returned by a search when no limit is given.
+const DEFAULT_SEARCH_LIMIT: usize = 100;
+
+/// Largest number of results a single search can return.
+const MAX_SEARCH_LIMIT: usize = 1000;
+
+/// SearchIndex searches the filesystem metadata and maintains the full-text
+/// index of file contents.
+///
+/// The text of PDF, TXT and DOCX files is stored in the `filesystem_content_index`
+/// FTS5 table by virtual path. Like patient links, index rows follow their file
+/// when it is renamed, moved or trashed; search only returns entries that have
+/// metadata, so trashed files are never found.
+#[derive(Debug, Clone)]
+pub struct SearchIndex;
+
+/// Search criteria. Every field is optional; results match all of the given ones.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchQuery {
+    /// Words to find in the name or the content of the file
+    pub text: Option<String>,
+    /// Only entries at or below this path
+    pub root_path: Option<String>,
+    /// Exact MIME type, or a prefix such as `image/*`
+    pub mime_type: Option<String>,
+    pub owner: Option<String>,
+    pub entry_type: Option<EntryType>,
+    /// Modified at or after (RFC 3339 timestamp or YYYY-MM-DD)
+    pub modified_from: Option<String>,
+    /// Modified before (RFC 3339 timestamp or YYYY-MM-DD), a whole day when only a date
+    pub modified_to: Option<String>,
+    /// Only entries linked to this patient
+    pub patient_id: Option<i64>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// An entry found by a search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub entry: FileEntry,
+    /// Excerpt of the content around the matched words, with matches in [brackets]
+    pub snippet: Option<String>,
+}
+
+impl SearchIndex {
+    /// Creates a new SearchIndex instance.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Stores the text content of a file, replacing any previous content.
+    ///
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `virtual_path`: The virtual path of the file
+    /// - `content`: The extracted text (empty for files without text)
+    pub fn index(&self, conn: &Connection, virtual_path: &str, content: &str) -> Result<(), String> {
+        conn.execute(
+            "DELETE FROM filesystem_content_index WHERE virtual_path = ?1",
+            params![virtual_path],
+        )
+        .map_err(|e| format!("Failed to update content index: {}", e))?;
+
+        conn.execute(
+            "INSERT INTO filesystem_content_index (virtual_path, content) VALUES (?1, ?2)",
+            params![virtual_path, content],
+        )
+        .map_err(|e| format!("Failed to update content index: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Lists the files of the given MIME types whose content is not indexed yet.
+    pub fn list_unindexed(
+        &self,
+        conn: &Connection,
+        mime_types: &[&str],
+    ) -> Result<Vec<FileEntry>, String> {
+        let placeholders = (1..=mime_types.len())
+            .map(|n| format!("?{}", n))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let query = format!(
+            r#"
+            SELECT id, virtual_path, physical_path, name, entry_type, size,
+                   mime_type, owner_username, created_at, modified_at, parent_path
+            FROM filesystem_metadata
+            WHERE entry_type = 'file' AND mime_type IN ({})
+              AND virtual_path NOT IN (SELECT virtual_path FROM filesystem_content_index)
+            "#,
+            placeholders
+        );
+
+        let mut stmt = conn
+            .prepare(&query)
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let entries = stmt
+            .query_map(params_from_iter(mime_types.iter()), Self::row_to_entry)
+            .map_err(|e| format!("Failed to query unindexed files: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to collect unindexed files: {}", e))?;
+
+        Ok(entries)
+    }
+
+    /// Points the index rows of an entry and everything below it to a new path.
+    pub fn relocate(
+        &self,
+        conn: &Connection,
+        old_virtual_path: &str,
+        new_virtual_path: &str,
+    ) -> Result<usize, String> {
+        conn.execute(
+            r#"
+            UPDATE filesystem_content_index
+            SET virtual_path = ?2 || substr(virtual_path, length(?1) + 1)
+            WHERE virtual_path = ?1 OR substr(virtual_path, 1, length(?1) + 1) = ?1 || '\'
+            "#,
+            params![old_virtual_path, new_virtual_path],
+        )
+        .map_err(|e| format!("Failed to relocate content index: {}", e))
+    }
+
+    /// Removes the index rows of an entry and everything below it.
+    pub fn delete_under(&self, conn: &Connection, virtual_path: &str) -> Result<usize, String> {
+        conn.execute(
+            r#"
+            DELETE FROM filesystem_content_index
+            WHERE virtual_path = ?1 OR substr(virtual_path, 1, length(?1) + 1) = ?1 || '\'
+            "#,
+            params![virtual_path],
+        )
+        .map_err(|e| format!("Failed to delete from content index: {}", e))
+    }
+
+    /// Finds the entries matching a query, most recently modified first.
+    ///
+    /// The text matches names containing it or files whose content has all of its
+    /// words (as word prefixes). Permissions are not checked here, and `limit` and
+    /// `offset` are not applied: the caller filters and pages the results.
+    ///
+    /// # Returns
+    /// - `Ok(Vec<SearchResult>)` containing the matching entries
+    /// - `Err(String)` if a date is invalid or the query fails
+    pub fn search(&self, conn: &Connection, query: &SearchQuery) -> Result<Vec<SearchResult>, String> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+        let mut content_join = String::new();
+        let mut snippet_column = "NULL";
+
+        if let Some(text) = query.text.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+            values.push(Value::Text(format!("%{}%", escape_like(text))));
+            let name_param = values.len();
+
+            match fts_query(text) {
+                Some(fts) => {
+                    values.push(Value::Text(fts));
+                    content_join = format!(
+                        r#"
+                        LEFT JOIN (
+                            SELECT virtual_path,
+                                   snippet(filesystem_content_index, 1, '[', ']', '…', 12) AS snippet
+                            FROM filesystem_content_index
+                            WHERE filesystem_content_index MATCH ?{}
+                        ) c ON c.virtual_path = m.virtual_path
+                        "#,
+                        values.len()
+                    );
+                    snippet_column = "c.snippet";
+                    conditions.push(format!(
+                        "(m.name LIKE ?{} ESCAPE '\\' OR c.virtual_path IS NOT NULL)",
+                        name_param
+                    ));
+                }
+                None => conditions.push(format!("m.name LIKE ?{} ESCAPE '\\'", name_param)),
+            }
+        }
+        if let Some(root) = &query.root_path {
+            values.push(Value::Text(root.trim_end_matches('\\').to_string()));
+            let n = values.len();
+            conditions.push(format!(
+                "(m.virtual_path = ?{n} OR substr(m.virtual_path, 1, length(?{n}) + 1) = ?{n} || '\\')"
+            ));
+        }
+        if let Some(mime_type) = &query.mime_type {
+            let mime_type = mime_type.trim_end_matches('*');
+            if mime_type.ends_with('/') {
+                values.push(Value::Text(format!("{}%", escape_like(mime_type))));
+                conditions.push(format!("m.mime_type LIKE ?{} ESCAPE '\\'", values.len()));
+            } else {
+                values.push(Value::Text(mime_type.to_string()));
+                conditions.push(format!("m.mime_type = ?{}", values.len()));
+            }
+        }
+        if let Some(owner) = &query.owner {
+            values.push(Value::Text(owner.clone()));
+            conditions.push(format!("m.owner_username = ?{}", values.len()));
+        }
+        if let Some(entry_type) = &query.entry_type {
+            values.push(Value::Text(entry_type.to_db_string().to_string()));
+            conditions.push(format!("m.entry_type = ?{}", values.len()));
+        }
+        if let Some(from) = &query.modified_from {
+            values.push(Value::Text(parse_bound(from, false)?));
+            conditions.push(format!("m.modified_at >= ?{}", values.len()));
+        }
+        if let Some(to) = &query.modified_to {
+            values.push(Value::Text(parse_bound(to, true)?));
+            conditions.push(format!("m.modified_at < ?{}", values.len()));
+        }
+        if let Some(patient_id) = query.patient_id {
+            values.push(Value::Integer(patient_id));
+            conditions.push(format!(
+                "EXISTS (SELECT 1 FROM filesystem_patient_links l \
+                 WHERE l.virtual_path = m.virtual_path AND l.patient_id = ?{})",
+                values.len()
+            ));
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        let sql = format!(
+            r#"
+            SELECT m.id, m.virtual_path, m.physical_path, m.name, m.entry_type, m.size,
+                   m.mime_type, m.owner_username, m.created_at, m.modified_at, m.parent_path,
+                   {}
+            FROM filesystem_metadata m
+            {}
+            {}
+            ORDER BY m.modified_at DESC, m.name ASC
+            "#,
+            snippet_column, content_join, where_clause
+        );
+
+        let mut stmt = conn
+            .prepare(&sql)
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let results = stmt
+            .query_map(params_from_iter(values.iter()), |row| {
+                Ok(SearchResult {
+                    entry: Self::row_to_entry(row)?,
+                    snippet: row.get(11)?,
+                })
+            })
+            .map_err(|e| format!("Failed to search: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to collect search results: {}", e))?;
+
+        Ok(results)
+    }
+
+    /// Number of results to skip and return for a query.
+    pub fn page(query: &SearchQuery) -> (usize, usize) {
+        (
+            query.offset.unwrap_or(0),
+            query
+                .limit
+                .unwrap_or(DEFAULT_SEARCH_LIMIT)
+                .clamp(1, MAX_SEARCH_LIMIT),
+        )
+    }
+
+    fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<FileEntry> {
+        let entry_type_str: String = row.get(4)?;
+        let entry_type = EntryType::from_db_string(&entry_type_str)
+            .map_err(|_| rusqlite::Error::InvalidQuery)?;
+
+        Ok(FileEntry {
+            id: row.get(0)?,
+            virtual_path: row.get(1)?,
+            physical_path: row.get(2)?,
+            name: row.get(3)?,
+            entry_type,
+            size: row.get(5)?,
+            mime_type: row.get(6)?,
+            owner_username: row.get(7)?,
+            created_at: row.get(8)?,
+            modified_at: row.get(9)?,
+            parent_path: row.get(10)?,
+        })
+    }
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Escapes the LIKE wildcards of a user string (with `\` as the escape character).
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Builds an FTS5 query matching every word of the text as a prefix.
+///
+/// Words are quoted so FTS5 operators and punctuation in the text are taken
+/// literally. Returns `None` when the text has no words.
+fn fts_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"*", word))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fts_query() {
+        assert_eq!(
+            fts_query("derivación  endo-doncia").as_deref(),
+            Some("\"derivación\"* \"endo\"* \"doncia\"*")
+        );
+        assert_eq!(fts_query("\"OR\" NEAR(").as_deref(), Some("\"OR\"* \"NEAR\"*"));
+        assert_eq!(fts_query(" -- "), None);
+    }
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like("50%_a\\b"), "50\\%\\_a\\\\b");
+    }
+}
@@ -1,7 +1,8 @@
 use std::path::{Path, PathBuf};
 use tokio::fs;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 
+use super::encryption;
 use super::PathResolver;
 
 /// StorageBackend handles low-level async file I/O operations.
@@ -11,8 +12,11 @@ use super::PathResolver;
 /// - Path validation to ensure operations stay within galeno_files/
 /// - File metadata retrieval
 /// - Safe file operations with error handling
+/// - Transparent encryption of file contents when enabled (see `encryption`)
 /// 
 /// All operations validate paths before execution to prevent directory traversal.
+/// Encrypted and plain files can coexist: reads detect encrypted files by their
+/// header, and sizes are always reported as plaintext sizes.
 #[derive(Debug, Clone)]
 pub struct StorageBackend {
     resolver: PathResolver,
@@ -84,12 +88,18 @@ impl StorageBackend {
                 .map_err(|e| format!("Failed to create parent directory: {}", e))?;
         }
         
+        // Encrypt the content if encryption is enabled
+        let encrypted = match encryption::write_key()? {
+            Some(key) => Some(encryption::encrypt(&key, data)?),
+            None => None,
+        };
+        
         // Write the file
         let mut file = fs::File::create(&physical_path)
             .await
             .map_err(|e| format!("Failed to create file '{}': {}", virtual_path, e))?;
         
-        file.write_all(data)
+        file.write_all(encrypted.as_deref().unwrap_or(data))
             .await
             .map_err(|e| format!("Failed to write file '{}': {}", virtual_path, e))?;
         
@@ -134,9 +144,238 @@ impl StorageBackend {
             .await
             .map_err(|e| format!("Failed to read file '{}': {}", virtual_path, e))?;
         
+        match encryption::parse_header(&buffer) {
+            Some(key_id) => encryption::decrypt(&encryption::key(key_id)?, &buffer)
+                .map_err(|e| format!("Failed to decrypt file '{}': {}", virtual_path, e)),
+            None => Ok(buffer),
+        }
+    }
+    
+    /// Appends a chunk of data to the end of a file, creating it if needed.
+    /// 
+    /// Used by chunked uploads so large files never have to be held in memory at once.
+    /// 
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the file to append to
+    /// - `data`: The chunk content
+    /// 
+    /// # Returns
+    /// - `Ok(u64)` containing the file size after the append
+    /// - `Err(String)` if path validation fails or the write fails
+    pub async fn append_chunk(&self, virtual_path: &str, data: &[u8]) -> Result<u64, String> {
+        // Validate and convert to physical path
+        let physical_path = self.resolver.virtual_to_physical(virtual_path)?;
+        
+        // Ensure parent directory exists
+        if let Some(parent) = physical_path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create parent directory: {}", e))?;
+        }
+        
+        // Encrypted files keep the encryption of their first write
+        let current_len = match fs::metadata(&physical_path).await {
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        };
+        if current_len == 0 {
+            if let Some(key) = encryption::write_key()? {
+                let encrypted = encryption::encrypt(&key, data)?;
+                fs::write(&physical_path, encrypted)
+                    .await
+                    .map_err(|e| format!("Failed to write file '{}': {}", virtual_path, e))?;
+                return Ok(data.len() as u64);
+            }
+        } else if let Some(key_id) = read_key_id(&physical_path).await? {
+            return self
+                .append_encrypted(&physical_path, virtual_path, key_id, data)
+                .await;
+        }
+        
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&physical_path)
+            .await
+            .map_err(|e| format!("Failed to open file '{}': {}", virtual_path, e))?;
+        
+        file.write_all(data)
+            .await
+            .map_err(|e| format!("Failed to write file '{}': {}", virtual_path, e))?;
+        
+        file.flush()
+            .await
+            .map_err(|e| format!("Failed to flush file '{}': {}", virtual_path, e))?;
+        
+        let metadata = file
+            .metadata()
+            .await
+            .map_err(|e| format!("Failed to get file size for '{}': {}", virtual_path, e))?;
+        
+        Ok(metadata.len())
+    }
+    
+    /// Appends to an encrypted file.
+    /// 
+    /// The last segment is decrypted and written again together with the new data,
+    /// so only the new last segment is marked as final.
+    async fn append_encrypted(
+        &self,
+        physical_path: &Path,
+        virtual_path: &str,
+        key_id: i64,
+        data: &[u8],
+    ) -> Result<u64, String> {
+        let key = encryption::key(key_id)?;
+        
+        let mut file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(physical_path)
+            .await
+            .map_err(|e| format!("Failed to open file '{}': {}", virtual_path, e))?;
+        
+        let file_len = file
+            .metadata()
+            .await
+            .map_err(|e| format!("Failed to get file size for '{}': {}", virtual_path, e))?
+            .len();
+        let plain_len = encryption::plaintext_len(file_len)?;
+        let last_index = encryption::segment_count(file_len)? - 1;
+        let last_offset = encryption::segment_offset(last_index);
+        
+        // Decrypt the current last segment
+        file.seek(std::io::SeekFrom::Start(last_offset))
+            .await
+            .map_err(|e| format!("Failed to seek file '{}': {}", virtual_path, e))?;
+        let mut last_segment = Vec::new();
+        file.read_to_end(&mut last_segment)
+            .await
+            .map_err(|e| format!("Failed to read file '{}': {}", virtual_path, e))?;
+        let mut tail = encryption::decrypt_segment(&key, last_index, true, &last_segment)
+            .map_err(|e| format!("Failed to decrypt file '{}': {}", virtual_path, e))?;
+        tail.extend_from_slice(data);
+        
+        // Replace it with the new segments
+        let segments = encryption::encrypt_segments(&key, last_index, &tail)?;
+        file.set_len(last_offset)
+            .await
+            .map_err(|e| format!("Failed to write file '{}': {}", virtual_path, e))?;
+        file.seek(std::io::SeekFrom::Start(last_offset))
+            .await
+            .map_err(|e| format!("Failed to seek file '{}': {}", virtual_path, e))?;
+        file.write_all(&segments)
+            .await
+            .map_err(|e| format!("Failed to write file '{}': {}", virtual_path, e))?;
+        file.flush()
+            .await
+            .map_err(|e| format!("Failed to flush file '{}': {}", virtual_path, e))?;
+        
+        Ok(plain_len + data.len() as u64)
+    }
+    
+    /// Reads up to `length` bytes of a file starting at `offset`.
+    /// 
+    /// Returns fewer bytes at the end of the file and an empty vector past it.
+    /// 
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the file to read
+    /// - `offset`: Byte position to start reading from
+    /// - `length`: Maximum number of bytes to read
+    /// 
+    /// # Returns
+    /// - `Ok(Vec<u8>)` containing the chunk
+    /// - `Err(String)` if path validation fails, file doesn't exist, or read fails
+    pub async fn read_chunk(
+        &self,
+        virtual_path: &str,
+        offset: u64,
+        length: usize,
+    ) -> Result<Vec<u8>, String> {
+        // Validate and convert to physical path
+        let physical_path = self.resolver.virtual_to_physical(virtual_path)?;
+        
+        if !physical_path.is_file() {
+            return Err(format!("File not found: '{}'", virtual_path));
+        }
+        
+        if let Some(key_id) = read_key_id(&physical_path).await? {
+            return self
+                .read_encrypted_chunk(&physical_path, virtual_path, key_id, offset, length)
+                .await;
+        }
+        
+        let mut file = fs::File::open(&physical_path)
+            .await
+            .map_err(|e| format!("Failed to open file '{}': {}", virtual_path, e))?;
+        
+        file.seek(std::io::SeekFrom::Start(offset))
+            .await
+            .map_err(|e| format!("Failed to seek file '{}': {}", virtual_path, e))?;
+        
+        let mut buffer = Vec::with_capacity(length);
+        file.take(length as u64)
+            .read_to_end(&mut buffer)
+            .await
+            .map_err(|e| format!("Failed to read file '{}': {}", virtual_path, e))?;
+        
         Ok(buffer)
     }
     
+    /// Reads part of an encrypted file, decrypting only the segments it covers.
+    async fn read_encrypted_chunk(
+        &self,
+        physical_path: &Path,
+        virtual_path: &str,
+        key_id: i64,
+        offset: u64,
+        length: usize,
+    ) -> Result<Vec<u8>, String> {
+        let key = encryption::key(key_id)?;
+        
+        let mut file = fs::File::open(physical_path)
+            .await
+            .map_err(|e| format!("Failed to open file '{}': {}", virtual_path, e))?;
+        let file_len = file
+            .metadata()
+            .await
+            .map_err(|e| format!("Failed to get file size for '{}': {}", virtual_path, e))?
+            .len();
+        let plain_len = encryption::plaintext_len(file_len)?;
+        let segments = encryption::segment_count(file_len)?;
+        
+        let end = offset.saturating_add(length as u64).min(plain_len);
+        if offset >= end {
+            return Ok(Vec::new());
+        }
+        let first = offset / encryption::SEGMENT_SIZE;
+        let last = (end - 1) / encryption::SEGMENT_SIZE;
+        
+        file.seek(std::io::SeekFrom::Start(encryption::segment_offset(first)))
+            .await
+            .map_err(|e| format!("Failed to seek file '{}': {}", virtual_path, e))?;
+        let stored_len = encryption::segments_len(file_len, first, last - first + 1);
+        let mut stored = vec![0u8; stored_len as usize];
+        file.read_exact(&mut stored)
+            .await
+            .map_err(|e| format!("Failed to read file '{}': {}", virtual_path, e))?;
+        
+        let mut plain = Vec::new();
+        for (n, segment) in stored
+            .chunks(encryption::ENCRYPTED_SEGMENT_SIZE as usize)
+            .enumerate()
+        {
+            let index = first + n as u64;
+            plain.extend(
+                encryption::decrypt_segment(&key, index, index + 1 == segments, segment)
+                    .map_err(|e| format!("Failed to decrypt file '{}': {}", virtual_path, e))?,
+            );
+        }
+        
+        let start = (offset - first * encryption::SEGMENT_SIZE) as usize;
+        Ok(plain[start..start + (end - offset) as usize].to_vec())
+    }
+    
     /// Deletes a file at the specified virtual path.
     /// 
     /// # Arguments
@@ -256,6 +495,52 @@ impl StorageBackend {
         Ok(())
     }
     
+    /// Copies a file from source to destination virtual path.
+    /// 
+    /// Creates parent directories at destination if they don't exist.
+    /// Overwrites destination file if it exists.
+    /// 
+    /// # Arguments
+    /// - `source_virtual_path`: The virtual path of the file to copy
+    /// - `dest_virtual_path`: The target virtual path for the copy
+    /// 
+    /// # Returns
+    /// - `Ok(())` if the file was copied successfully
+    /// - `Err(String)` if path validation fails, source doesn't exist, or copy fails
+    pub async fn copy_file(
+        &self,
+        source_virtual_path: &str,
+        dest_virtual_path: &str,
+    ) -> Result<(), String> {
+        // Validate and convert both paths
+        let source_physical = self.resolver.virtual_to_physical(source_virtual_path)?;
+        let dest_physical = self.resolver.virtual_to_physical(dest_virtual_path)?;
+        
+        // Check if source exists
+        if !source_physical.is_file() {
+            return Err(format!("Source file not found: '{}'", source_virtual_path));
+        }
+        
+        // Ensure destination parent directory exists
+        if let Some(parent) = dest_physical.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("Failed to create destination directory: {}", e))?;
+        }
+        
+        // Copy the file
+        fs::copy(&source_physical, &dest_physical)
+            .await
+            .map_err(|e| {
+                format!(
+                    "Failed to copy file from '{}' to '{}': {}",
+                    source_virtual_path, dest_virtual_path, e
+                )
+            })?;
+        
+        Ok(())
+    }
+    
     /// Gets the size of a file in bytes.
     /// 
     /// # Arguments
@@ -281,7 +566,7 @@ impl StorageBackend {
             .await
             .map_err(|e| format!("Failed to get file size for '{}': {}", virtual_path, e))?;
         
-        Ok(metadata.len())
+        plain_size(&physical_path, &metadata).await
     }
     
     /// Gets file or directory metadata.
@@ -324,7 +609,7 @@ impl StorageBackend {
         
         Ok(FileMetadata {
             name,
-            size: metadata.len(),
+            size: plain_size(&physical_path, &metadata).await?,
             is_file: metadata.is_file(),
             is_directory: metadata.is_dir(),
             created: metadata.created().ok(),
@@ -379,6 +664,138 @@ impl StorageBackend {
         let physical_path = self.resolver.virtual_to_physical(virtual_path)?;
         Ok(physical_path.is_dir())
     }
+    
+    /// Lists the files that are not encrypted with the active key.
+    /// 
+    /// Returns nothing while encryption is disabled, so plain files are only
+    /// encrypted once it is turned on.
+    /// 
+    /// # Arguments
+    /// - `excluded`: Virtual folders to skip, such as uploads in progress
+    /// 
+    /// # Returns
+    /// - `Ok(Vec<String>)` containing the virtual paths of the files
+    /// - `Err(String)` if encryption is enabled but locked
+    pub async fn list_files_to_reencrypt(&self, excluded: &[&str]) -> Result<Vec<String>, String> {
+        let Some(key) = encryption::write_key()? else {
+            return Ok(Vec::new());
+        };
+        
+        let root = self.resolver.virtual_to_physical("G:\\")?;
+        let excluded = excluded
+            .iter()
+            .map(|path| self.resolver.virtual_to_physical(path))
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        let physical_paths = tokio::task::spawn_blocking(move || {
+            walkdir::WalkDir::new(&root)
+                .into_iter()
+                .filter_entry(|entry| !excluded.iter().any(|path| entry.path() == path))
+                .filter_map(Result::ok)
+                .filter(|entry| entry.file_type().is_file())
+                .filter(|entry| !entry.path().to_string_lossy().ends_with(REENCRYPT_SUFFIX))
+                .filter(|entry| {
+                    let mut header = [0u8; encryption::HEADER_LEN as usize];
+                    let read = std::fs::File::open(entry.path())
+                        .and_then(|mut file| std::io::Read::read(&mut file, &mut header));
+                    match read {
+                        Ok(n) => encryption::parse_header(&header[..n]) != Some(key.id),
+                        Err(_) => false,
+                    }
+                })
+                .map(|entry| entry.into_path())
+                .collect::<Vec<_>>()
+        })
+        .await
+        .map_err(|e| format!("Failed to list files: {}", e))?;
+        
+        physical_paths
+            .iter()
+            .map(|path| self.resolver.physical_to_virtual(path))
+            .collect()
+    }
+    
+    /// Encrypts a file with the active key, decrypting it first if it was
+    /// encrypted with an older key.
+    /// 
+    /// The new content is written next to the file and renamed over it. If the
+    /// file is written to in the meantime, it is left as it is.
+    /// 
+    /// # Arguments
+    /// - `virtual_path`: The virtual path of the file
+    /// 
+    /// # Returns
+    /// - `Ok(true)` if the file was re-encrypted
+    /// - `Ok(false)` if encryption is disabled or the file already uses the active key
+    /// - `Err(String)` if the file cannot be read, decrypted or written
+    pub async fn reencrypt_file(&self, virtual_path: &str) -> Result<bool, String> {
+        let physical_path = self.resolver.virtual_to_physical(virtual_path)?;
+        
+        let Some(key) = encryption::write_key()? else {
+            return Ok(false);
+        };
+        if read_key_id(&physical_path).await? == Some(key.id) {
+            return Ok(false);
+        }
+        
+        let before = fs::metadata(&physical_path)
+            .await
+            .map_err(|e| format!("Failed to get metadata for '{}': {}", virtual_path, e))?;
+        let data = self.read_file(virtual_path).await?;
+        let encrypted = encryption::encrypt(&key, &data)?;
+        
+        let temp_path = PathBuf::from(format!("{}{}", physical_path.display(), REENCRYPT_SUFFIX));
+        fs::write(&temp_path, encrypted)
+            .await
+            .map_err(|e| format!("Failed to write file '{}': {}", virtual_path, e))?;
+        
+        let after = fs::metadata(&physical_path).await.ok();
+        let unchanged = after.is_some_and(|after| {
+            after.len() == before.len() && after.modified().ok() == before.modified().ok()
+        });
+        if !unchanged {
+            let _ = fs::remove_file(&temp_path).await;
+            return Err(format!("File changed during re-encryption: '{}'", virtual_path));
+        }
+        
+        fs::rename(&temp_path, &physical_path)
+            .await
+            .map_err(|e| format!("Failed to replace file '{}': {}", virtual_path, e))?;
+        
+        Ok(true)
+    }
+}
+
+/// Suffix of the temporary files written while re-encrypting.
+const REENCRYPT_SUFFIX: &str = ".reencrypt";
+
+/// Reads the key ID from the header of an encrypted file, or `None` for plain files.
+async fn read_key_id(physical_path: &Path) -> Result<Option<i64>, String> {
+    let mut file = match fs::File::open(physical_path).await {
+        Ok(file) => file,
+        Err(_) => return Ok(None),
+    };
+    
+    let mut header = Vec::with_capacity(encryption::HEADER_LEN as usize);
+    (&mut file)
+        .take(encryption::HEADER_LEN)
+        .read_to_end(&mut header)
+        .await
+        .map_err(|e| format!("Failed to read file header: {}", e))?;
+    
+    Ok(encryption::parse_header(&header))
+}
+
+/// Size of the content of a file, which is smaller than its size on disk if encrypted.
+async fn plain_size(physical_path: &Path, metadata: &std::fs::Metadata) -> Result<u64, String> {
+    if !metadata.is_file() {
+        return Ok(metadata.len());
+    }
+    
+    match read_key_id(physical_path).await? {
+        Some(_) => encryption::plaintext_len(metadata.len()),
+        None => Ok(metadata.len()),
+    }
 }
 
 /// Metadata information for a file or directory.
@@ -448,6 +865,23 @@ mod tests {
         let _ = backend.delete_file(test_path).await;
     }
     
+    #[tokio::test]
+    async fn test_append_and_read_chunks() {
+        let backend = StorageBackend::new().unwrap();
+        let test_path = "G:\\test_chunks.bin";
+        let _ = backend.delete_file(test_path).await;
+        
+        assert_eq!(backend.append_chunk(test_path, b"Hello, ").await.unwrap(), 7);
+        assert_eq!(backend.append_chunk(test_path, b"Galeno!").await.unwrap(), 14);
+        
+        assert_eq!(backend.read_chunk(test_path, 0, 5).await.unwrap(), b"Hello");
+        assert_eq!(backend.read_chunk(test_path, 7, 100).await.unwrap(), b"Galeno!");
+        assert!(backend.read_chunk(test_path, 14, 10).await.unwrap().is_empty());
+        
+        // Cleanup
+        let _ = backend.delete_file(test_path).await;
+    }
+    
     #[tokio::test]
     async fn test_delete_file() {
         let backend = StorageBackend::new().unwrap();
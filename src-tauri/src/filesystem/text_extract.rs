@@ -0,0 +1,433 @@
+use std::io::{Cursor, Read};
+
+use encoding_rs::WINDOWS_1252;
+use flate2::read::ZlibDecoder;
+
+const MIME_PDF: &str = "application/pdf";
+const MIME_TXT: &str = "text/plain";
+const MIME_DOCX: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+
+/// Files larger than this are indexed without content.
+pub const MAX_EXTRACT_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Longest text kept per file, in bytes.
+const MAX_TEXT_BYTES: usize = 1024 * 1024;
+
+/// Checks if text can be extracted from files of a MIME type.
+pub fn is_indexable(mime_type: &str) -> bool {
+    matches!(mime_type, MIME_PDF | MIME_TXT | MIME_DOCX)
+}
+
+/// MIME types whose text content is indexed.
+pub fn indexable_mime_types() -> [&'static str; 3] {
+    [MIME_PDF, MIME_TXT, MIME_DOCX]
+}
+
+/// Extracts the plain text of a PDF, TXT or DOCX file.
+///
+/// PDF support is best effort: text shown with simple font encodings is found,
+/// scanned documents and CID-keyed fonts yield little or no text.
+///
+/// # Arguments
+/// - `mime_type`: The MIME type of the file
+/// - `data`: The file content
+///
+/// # Returns
+/// - `Ok(String)` containing the text, with whitespace collapsed
+/// - `Err(String)` if the type is not supported or the file is malformed
+pub fn extract_text(mime_type: &str, data: &[u8]) -> Result<String, String> {
+    let text = match mime_type {
+        MIME_TXT => decode_text(data),
+        MIME_DOCX => extract_docx(data)?,
+        MIME_PDF => extract_pdf(data)?,
+        _ => return Err(format!("Cannot extract text from '{}' files", mime_type)),
+    };
+
+    Ok(normalize_whitespace(&text))
+}
+
+/// Decodes a text file as UTF-8, falling back to Windows-1252 for legacy files.
+fn decode_text(data: &[u8]) -> String {
+    let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+
+    match std::str::from_utf8(data) {
+        Ok(text) => text.to_string(),
+        Err(_) => WINDOWS_1252.decode(data).0.into_owned(),
+    }
+}
+
+/// Extracts the text of the main document part of a DOCX file.
+fn extract_docx(data: &[u8]) -> Result<String, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data))
+        .map_err(|e| format!("Invalid DOCX file: {}", e))?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .map_err(|e| format!("Invalid DOCX file: {}", e))?
+        .take(MAX_EXTRACT_BYTES)
+        .read_to_string(&mut xml)
+        .map_err(|e| format!("Failed to read DOCX content: {}", e))?;
+
+    let mut text = String::new();
+    let mut rest = xml.as_str();
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&unescape_xml(&rest[..start]));
+
+        let end = rest[start..]
+            .find('>')
+            .map(|end| start + end)
+            .ok_or_else(|| "Invalid DOCX file: unterminated tag".to_string())?;
+        let tag = rest[start + 1..end].trim_end_matches('/');
+        match tag.split_whitespace().next().unwrap_or("") {
+            "/w:p" | "w:br" | "w:cr" => text.push('\n'),
+            "w:tab" => text.push('\t'),
+            _ => {}
+        }
+
+        if text.len() > MAX_TEXT_BYTES {
+            break;
+        }
+        rest = &rest[end + 1..];
+    }
+
+    Ok(text)
+}
+
+/// Replaces the XML entities in a text node.
+fn unescape_xml(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+
+        match decoded {
+            Some(c) => {
+                result.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Extracts the text shown by the content streams of a PDF file.
+fn extract_pdf(data: &[u8]) -> Result<String, String> {
+    if !data.starts_with(b"%PDF") {
+        return Err("Invalid PDF file: missing header".to_string());
+    }
+
+    let mut text = String::new();
+    let mut position = 0;
+
+    while let Some(start) = find(data, b"stream", position) {
+        // Skip the "endstream" keyword of the previous stream
+        if start >= 3 && &data[start - 3..start] == b"end" {
+            position = start + 6;
+            continue;
+        }
+
+        let mut content_start = start + 6;
+        if data.get(content_start) == Some(&b'\r') {
+            content_start += 1;
+        }
+        if data.get(content_start) == Some(&b'\n') {
+            content_start += 1;
+        }
+        let Some(content_end) = find(data, b"endstream", content_start) else {
+            break;
+        };
+
+        // The stream dictionary sits between the previous "obj" and "stream"
+        let dictionary_start = rfind(&data[..start], b"obj").unwrap_or(0);
+        let dictionary = &data[dictionary_start..start];
+        let content = &data[content_start..content_end];
+
+        if find(dictionary, b"/FlateDecode", 0).is_some() {
+            let mut decoded = Vec::new();
+            // Streams are often followed by a stray newline, which the decoder ignores
+            if ZlibDecoder::new(content)
+                .take(MAX_EXTRACT_BYTES)
+                .read_to_end(&mut decoded)
+                .is_ok()
+            {
+                extract_pdf_content(&decoded, &mut text);
+            }
+        } else if find(dictionary, b"/Filter", 0).is_none() {
+            extract_pdf_content(content, &mut text);
+        }
+
+        if text.len() > MAX_TEXT_BYTES {
+            break;
+        }
+        position = content_end + 9;
+    }
+
+    Ok(text)
+}
+
+/// Appends the strings shown by the text operators of a content stream.
+fn extract_pdf_content(content: &[u8], text: &mut String) {
+    let mut in_text = false;
+    let mut in_array = false;
+    let mut i = 0;
+
+    while i < content.len() {
+        match content[i] {
+            b'(' => {
+                let (string, end) = read_pdf_literal(content, i + 1);
+                if in_text {
+                    text.push_str(&WINDOWS_1252.decode(&string).0);
+                }
+                i = end;
+            }
+            b'<' if content.get(i + 1) != Some(&b'<') => {
+                let end = content[i..]
+                    .iter()
+                    .position(|&b| b == b'>')
+                    .map_or(content.len(), |end| i + end);
+                if in_text {
+                    if let Some(bytes) = decode_hex(&content[i + 1..end]) {
+                        // Two-byte glyph IDs can't be mapped without the font; skip them
+                        if bytes.iter().all(|b| !b.is_ascii_control() || b.is_ascii_whitespace()) {
+                            text.push_str(&WINDOWS_1252.decode(&bytes).0);
+                        }
+                    }
+                }
+                i = end + 1;
+            }
+            b'%' => {
+                while i < content.len() && content[i] != b'\n' && content[i] != b'\r' {
+                    i += 1;
+                }
+            }
+            b'[' => {
+                in_array = true;
+                i += 1;
+            }
+            b']' => {
+                in_array = false;
+                i += 1;
+            }
+            b if b.is_ascii_whitespace() || b"{}<>/".contains(&b) => i += 1,
+            _ => {
+                let end = content[i..]
+                    .iter()
+                    .position(|b| b.is_ascii_whitespace() || b"()<>[]{}/%".contains(b))
+                    .map_or(content.len(), |end| i + end);
+                let token = &content[i..end];
+
+                match token {
+                    b"BT" => in_text = true,
+                    b"ET" => {
+                        in_text = false;
+                        text.push('\n');
+                    }
+                    b"Td" | b"TD" | b"T*" | b"Tm" | b"'" | b"\"" if in_text => text.push(' '),
+                    _ if in_text && in_array => {
+                        // Large negative adjustments in TJ arrays separate words
+                        let adjustment = std::str::from_utf8(token)
+                            .ok()
+                            .and_then(|number| number.parse::<f64>().ok());
+                        if adjustment.map_or(false, |value| value < -200.0) {
+                            text.push(' ');
+                        }
+                    }
+                    _ => {}
+                }
+                i = end.max(i + 1);
+            }
+        }
+    }
+}
+
+/// Reads a PDF literal string starting after its opening parenthesis.
+///
+/// Returns the decoded bytes and the position after the closing parenthesis.
+fn read_pdf_literal(content: &[u8], start: usize) -> (Vec<u8>, usize) {
+    let mut bytes = Vec::new();
+    let mut depth = 0;
+    let mut i = start;
+
+    while i < content.len() {
+        match content[i] {
+            b'\\' => {
+                i += 1;
+                match content.get(i) {
+                    Some(b'n') => bytes.push(b'\n'),
+                    Some(b'r') => bytes.push(b'\r'),
+                    Some(b't') => bytes.push(b'\t'),
+                    Some(b'b') | Some(b'f') => {}
+                    Some(b'\r') => {
+                        if content.get(i + 1) == Some(&b'\n') {
+                            i += 1;
+                        }
+                    }
+                    Some(b'\n') => {}
+                    Some(digit @ b'0'..=b'7') => {
+                        let mut value = u32::from(digit - b'0');
+                        for _ in 0..2 {
+                            match content.get(i + 1) {
+                                Some(next @ b'0'..=b'7') => {
+                                    value = value * 8 + u32::from(next - b'0');
+                                    i += 1;
+                                }
+                                _ => break,
+                            }
+                        }
+                        bytes.push(value as u8);
+                    }
+                    Some(&other) => bytes.push(other),
+                    None => break,
+                }
+            }
+            b'(' => {
+                depth += 1;
+                bytes.push(b'(');
+            }
+            b')' if depth == 0 => return (bytes, i + 1),
+            b')' => {
+                depth -= 1;
+                bytes.push(b')');
+            }
+            other => bytes.push(other),
+        }
+        i += 1;
+    }
+
+    (bytes, content.len())
+}
+
+/// Decodes the digits of a PDF hex string (whitespace is ignored, odd length padded).
+fn decode_hex(digits: &[u8]) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = digits
+        .iter()
+        .copied()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect();
+
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let high = (pair[0] as char).to_digit(16)?;
+            let low = pair.get(1).map_or(Some(0), |&b| (b as char).to_digit(16))?;
+            Some((high * 16 + low) as u8)
+        })
+        .collect()
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|position| from + position)
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .rposition(|window| window == needle)
+}
+
+/// Collapses runs of whitespace into single spaces and caps the length.
+fn normalize_whitespace(text: &str) -> String {
+    let mut result = String::with_capacity(text.len().min(MAX_TEXT_BYTES));
+
+    for word in text.split_whitespace() {
+        if result.len() + word.len() + 1 > MAX_TEXT_BYTES {
+            break;
+        }
+        if !result.is_empty() {
+            result.push(' ');
+        }
+        result.push_str(word);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_extract_txt_falls_back_to_windows_1252() {
+        assert_eq!(
+            extract_text(MIME_TXT, "Derivación  a\r\nendodoncia".as_bytes()).unwrap(),
+            "Derivación a endodoncia"
+        );
+        assert_eq!(
+            extract_text(MIME_TXT, b"Derivaci\xF3n").unwrap(),
+            "Derivación"
+        );
+    }
+
+    #[test]
+    fn test_extract_docx() {
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut zip = zip::ZipWriter::new(&mut buffer);
+            zip.start_file("word/document.xml", zip::write::FileOptions::default())
+                .unwrap();
+            zip.write_all(
+                br#"<?xml version="1.0"?><w:document><w:body><w:p><w:r><w:t>Paciente:</w:t></w:r><w:r><w:tab/><w:t xml:space="preserve">Juan &amp; Ana</w:t></w:r></w:p><w:p><w:r><w:t>Pieza 36</w:t></w:r></w:p></w:body></w:document>"#,
+            )
+            .unwrap();
+            zip.finish().unwrap();
+        }
+
+        assert_eq!(
+            extract_text(MIME_DOCX, buffer.get_ref()).unwrap(),
+            "Paciente: Juan & Ana Pieza 36"
+        );
+    }
+
+    #[test]
+    fn test_extract_pdf() {
+        let content = b"BT /F1 12 Tf 72 712 Td (Carta de derivaci\\363n) Tj 0 -14 Td [(Dr.) -250 (G\\(a\\)rc) 10 (\\355a)] TJ ET";
+        let mut compressed = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        compressed.write_all(content).unwrap();
+        let compressed = compressed.finish().unwrap();
+
+        let mut pdf = b"%PDF-1.4\n4 0 obj\n<< /Length 10 /Filter /FlateDecode >>\nstream\n".to_vec();
+        pdf.extend_from_slice(&compressed);
+        pdf.extend_from_slice(b"\nendstream\nendobj\n5 0 obj\n<< /Length 20 >>\nstream\nBT (Pieza 36) Tj ET\nendstream\nendobj\n%%EOF");
+
+        assert_eq!(
+            extract_text(MIME_PDF, &pdf).unwrap(),
+            "Carta de derivación Dr. G(a)rcía Pieza 36"
+        );
+        assert!(extract_text(MIME_PDF, b"not a pdf").is_err());
+    }
+}
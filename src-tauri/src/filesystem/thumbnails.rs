@@ -0,0 +1,120 @@
+use std::io::Cursor;
+
+use image::ImageFormat;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::dicom::{self, DICOM_MIME_TYPE};
+use super::FileEntry;
+
+/// Largest width or height of a thumbnail, in pixels.
+pub const THUMBNAIL_SIZE: u32 = 256;
+
+/// Files larger than this are not decoded to build a thumbnail.
+pub const MAX_THUMBNAIL_SOURCE_BYTES: u64 = 100 * 1024 * 1024;
+
+/// MIME types that thumbnails can be generated for.
+const PREVIEWABLE_MIME_TYPES: [&str; 3] = ["image/jpeg", "image/png", DICOM_MIME_TYPE];
+
+/// ThumbnailCache stores the generated thumbnails of files.
+///
+/// Thumbnails are PNG images kept in `filesystem_thumbnails` by metadata ID, so
+/// they follow renames and moves and are deleted with the metadata row. A
+/// thumbnail is only used while the file has the modification time it was built from.
+#[derive(Debug, Clone)]
+pub struct ThumbnailCache;
+
+impl ThumbnailCache {
+    /// Creates a new ThumbnailCache instance.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Gets the cached thumbnail of a file, if it is up to date.
+    pub fn get(&self, conn: &Connection, entry: &FileEntry) -> Result<Option<Vec<u8>>, String> {
+        conn.query_row(
+            "SELECT data FROM filesystem_thumbnails WHERE file_id = ?1 AND source_modified_at = ?2",
+            params![entry.id, entry.modified_at],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get thumbnail: {}", e))
+    }
+
+    /// Stores the thumbnail of a file, replacing any previous one.
+    pub fn store(&self, conn: &Connection, entry: &FileEntry, data: &[u8]) -> Result<(), String> {
+        conn.execute(
+            r#"
+            INSERT OR REPLACE INTO filesystem_thumbnails (file_id, source_modified_at, data, created_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![entry.id, entry.modified_at, data, chrono::Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| format!("Failed to store thumbnail: {}", e))?;
+
+        Ok(())
+    }
+}
+
+impl Default for ThumbnailCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether thumbnails can be generated for a MIME type.
+pub fn is_previewable(mime_type: &str) -> bool {
+    PREVIEWABLE_MIME_TYPES.contains(&mime_type)
+}
+
+/// Builds the PNG thumbnail of an image or DICOM file.
+///
+/// The image is scaled down to fit `THUMBNAIL_SIZE`, keeping its aspect ratio;
+/// smaller images keep their size.
+///
+/// # Returns
+/// - `Ok(Vec<u8>)` containing the PNG thumbnail
+/// - `Err(String)` if the type is not previewable or the file cannot be decoded
+pub fn render_thumbnail(mime_type: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+    let image = match mime_type {
+        DICOM_MIME_TYPE => dicom::render(data)?,
+        "image/jpeg" => image::load_from_memory_with_format(data, ImageFormat::Jpeg)
+            .map_err(|e| format!("Failed to decode image: {}", e))?,
+        "image/png" => image::load_from_memory_with_format(data, ImageFormat::Png)
+            .map_err(|e| format!("Failed to decode image: {}", e))?,
+        _ => return Err(format!("No preview available for type '{}'", mime_type)),
+    };
+
+    let thumbnail = if image.width() > THUMBNAIL_SIZE || image.height() > THUMBNAIL_SIZE {
+        image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+    } else {
+        image
+    };
+
+    let mut png = Cursor::new(Vec::new());
+    thumbnail
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
+
+    Ok(png.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, RgbImage};
+
+    #[test]
+    fn test_render_thumbnail_keeps_aspect_ratio() {
+        let mut source = Cursor::new(Vec::new());
+        DynamicImage::ImageRgb8(RgbImage::new(1024, 512))
+            .write_to(&mut source, ImageFormat::Png)
+            .unwrap();
+
+        let png = render_thumbnail("image/png", source.get_ref()).unwrap();
+        let thumbnail = image::load_from_memory(&png).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (256, 128));
+
+        assert!(render_thumbnail("application/pdf", source.get_ref()).is_err());
+        assert!(render_thumbnail("image/jpeg", b"not a jpeg").is_err());
+    }
+}
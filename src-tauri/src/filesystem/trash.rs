@@ -0,0 +1,219 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use super::EntryType;
+
+/// Virtual folder holding the content of trashed entries.
+///
+/// It has no metadata entry, so it never shows up in directory listings.
+pub const TRASH_DIR: &str = "G:\\.trash";
+
+/// Configuration key of the retention period, in days (0 keeps items forever).
+const RETENTION_CONFIG_KEY: &str = "fsTrashRetentionDays";
+
+/// Retention used when the configuration cannot be read.
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+
+/// TrashManager handles the database records of the recycle bin.
+///
+/// Each trashed file or folder has one row in `filesystem_trash` with its original
+/// location and where its content was moved. For folders, the metadata of the whole
+/// subtree is kept as a JSON snapshot so it can be restored as it was.
+#[derive(Debug, Clone)]
+pub struct TrashManager;
+
+/// A file or folder in the recycle bin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: i64,
+    pub original_virtual_path: String,
+    pub original_parent_path: String,
+    pub name: String,
+    pub entry_type: EntryType,
+    pub size: i64,
+    pub owner_username: String,
+    pub deleted_at: String,
+    pub deleted_by: String,
+    /// Physical location of the trashed content (not sent to the frontend)
+    #[serde(skip)]
+    pub physical_backup_path: String,
+    /// JSON array with the metadata of the entry and its descendants
+    #[serde(skip)]
+    pub entries_snapshot: Option<String>,
+}
+
+const TRASH_COLUMNS: &str = r#"
+    id, original_virtual_path, original_parent_path, name, entry_type, size,
+    owner_username, deleted_at, deleted_by, physical_backup_path, entries_snapshot
+"#;
+
+impl TrashManager {
+    /// Creates a new TrashManager instance.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Records a trashed entry.
+    ///
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `entry`: The trashed entry (its `id` and `deleted_at` are ignored)
+    ///
+    /// # Returns
+    /// - `Ok(i64)` containing the ID of the trash record
+    /// - `Err(String)` if the insertion fails
+    pub fn insert_item(&self, conn: &Connection, entry: &TrashEntry) -> Result<i64, String> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        conn.execute(
+            r#"
+            INSERT INTO filesystem_trash (
+                original_virtual_path, original_parent_path, name, entry_type, size,
+                owner_username, deleted_at, deleted_by, physical_backup_path, entries_snapshot
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            "#,
+            params![
+                entry.original_virtual_path,
+                entry.original_parent_path,
+                entry.name,
+                entry.entry_type.to_db_string(),
+                entry.size,
+                entry.owner_username,
+                now,
+                entry.deleted_by,
+                entry.physical_backup_path,
+                entry.entries_snapshot,
+            ],
+        )
+        .map_err(|e| format!("Failed to insert trash entry: {}", e))?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Retrieves a trash record by ID.
+    ///
+    /// # Returns
+    /// - `Ok(Some(TrashEntry))` if the record exists
+    /// - `Ok(None)` if it doesn't
+    /// - `Err(String)` if the query fails
+    pub fn get_item(&self, conn: &Connection, id: i64) -> Result<Option<TrashEntry>, String> {
+        let query = format!("SELECT {} FROM filesystem_trash WHERE id = ?1", TRASH_COLUMNS);
+
+        match conn.query_row(&query, params![id], Self::row_to_entry) {
+            Ok(entry) => Ok(Some(entry)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(format!("Failed to get trash entry: {}", e)),
+        }
+    }
+
+    /// Lists trash records, most recently deleted first.
+    ///
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `username`: Only records owned or deleted by this user; `None` lists all of them
+    ///
+    /// # Returns
+    /// - `Ok(Vec<TrashEntry>)` containing the records
+    /// - `Err(String)` if the query fails
+    pub fn list_items(
+        &self,
+        conn: &Connection,
+        username: Option<&str>,
+    ) -> Result<Vec<TrashEntry>, String> {
+        let query = format!(
+            r#"
+            SELECT {} FROM filesystem_trash
+            WHERE ?1 IS NULL OR owner_username = ?1 OR deleted_by = ?1
+            ORDER BY deleted_at DESC
+            "#,
+            TRASH_COLUMNS
+        );
+
+        let mut stmt = conn
+            .prepare(&query)
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let entries = stmt
+            .query_map(params![username], Self::row_to_entry)
+            .map_err(|e| format!("Failed to query trash: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to collect trash entries: {}", e))?;
+
+        Ok(entries)
+    }
+
+    /// Lists trash records deleted before the given RFC 3339 timestamp.
+    pub fn list_deleted_before(
+        &self,
+        conn: &Connection,
+        cutoff: &str,
+    ) -> Result<Vec<TrashEntry>, String> {
+        let query = format!(
+            "SELECT {} FROM filesystem_trash WHERE deleted_at < ?1 ORDER BY deleted_at ASC",
+            TRASH_COLUMNS
+        );
+
+        let mut stmt = conn
+            .prepare(&query)
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let entries = stmt
+            .query_map(params![cutoff], Self::row_to_entry)
+            .map_err(|e| format!("Failed to query trash: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to collect trash entries: {}", e))?;
+
+        Ok(entries)
+    }
+
+    /// Deletes a trash record.
+    ///
+    /// # Returns
+    /// - `Ok(())` if the record was deleted
+    /// - `Err(String)` if the deletion fails or the record doesn't exist
+    pub fn delete_item(&self, conn: &Connection, id: i64) -> Result<(), String> {
+        let rows_affected = conn
+            .execute("DELETE FROM filesystem_trash WHERE id = ?1", params![id])
+            .map_err(|e| format!("Failed to delete trash entry: {}", e))?;
+
+        if rows_affected == 0 {
+            return Err(format!("Trash entry not found: {}", id));
+        }
+
+        Ok(())
+    }
+
+    /// Days trashed items are kept before being purged (0 keeps them forever).
+    pub fn retention_days(&self) -> i64 {
+        crate::config::current_value(RETENTION_CONFIG_KEY)
+            .and_then(|value| value.as_i64())
+            .unwrap_or(DEFAULT_RETENTION_DAYS)
+            .max(0)
+    }
+
+    fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<TrashEntry> {
+        let entry_type_str: String = row.get(4)?;
+        let entry_type = EntryType::from_db_string(&entry_type_str)
+            .map_err(|_| rusqlite::Error::InvalidQuery)?;
+
+        Ok(TrashEntry {
+            id: row.get(0)?,
+            original_virtual_path: row.get(1)?,
+            original_parent_path: row.get(2)?,
+            name: row.get(3)?,
+            entry_type,
+            size: row.get(5)?,
+            owner_username: row.get(6)?,
+            deleted_at: row.get(7)?,
+            deleted_by: row.get(8)?,
+            physical_backup_path: row.get(9)?,
+            entries_snapshot: row.get(10)?,
+        })
+    }
+}
+
+impl Default for TrashManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
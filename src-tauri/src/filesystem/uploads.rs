@@ -0,0 +1,257 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Size of the chunks the frontend sends and requests (1 MiB).
+pub const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Largest chunk accepted in a single IPC call.
+pub const MAX_CHUNK_SIZE: usize = 8 * CHUNK_SIZE;
+
+/// Minutes an unfinished upload is kept before its staging file is discarded.
+const UPLOAD_TTL_MINUTES: i64 = 60;
+
+/// Virtual folder holding the partial content of unfinished uploads.
+///
+/// It has no metadata entry, so it never shows up in directory listings.
+pub const STAGING_DIR: &str = "G:\\.uploads";
+
+/// Returned to the frontend when an upload starts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadSession {
+    pub upload_id: String,
+    /// Preferred size of each chunk sent to `fs_upload_chunk`
+    pub chunk_size: usize,
+}
+
+/// An upload that has been started but not finished yet.
+#[derive(Debug, Clone)]
+pub struct PendingUpload {
+    /// Final virtual path of the file
+    pub target_path: String,
+    pub parent_path: String,
+    pub name: String,
+    /// Virtual path of the partial file under `STAGING_DIR`
+    pub staging_path: String,
+    pub owner_username: String,
+    pub total_size: u64,
+    pub received: u64,
+    /// MIME type from the allowed types whitelist
+    pub mime_type: String,
+    /// Replaces an existing file, keeping its content as a previous version
+    pub overwrite: bool,
+    /// A chunk is being written; chunks of one upload must arrive in order
+    pub busy: bool,
+    pub last_activity: DateTime<Utc>,
+}
+
+/// UploadRegistry tracks chunked uploads between `fs_begin_upload` and `fs_finish_upload`.
+///
+/// Chunks must be sent in order: each one has to start where the previous one ended,
+/// so a retried or duplicated chunk is rejected instead of corrupting the file.
+#[derive(Debug, Default)]
+pub struct UploadRegistry {
+    pending: Mutex<HashMap<String, PendingUpload>>,
+}
+
+impl UploadRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a new upload under the given ID.
+    pub fn insert(&self, upload_id: &str, upload: PendingUpload) {
+        self.pending
+            .lock()
+            .unwrap()
+            .insert(upload_id.to_string(), upload);
+    }
+
+    /// Reserves the upload for writing the chunk that starts at `offset`.
+    ///
+    /// # Returns
+    /// - `Ok(PendingUpload)` with the state before the chunk is written
+    /// - `Err(String)` if the upload doesn't exist, belongs to another user, is busy,
+    ///   the offset is not where the previous chunk ended or the chunk exceeds the size
+    pub fn begin_chunk(
+        &self,
+        upload_id: &str,
+        username: &str,
+        offset: u64,
+        length: usize,
+    ) -> Result<PendingUpload, String> {
+        let mut pending = self.pending.lock().unwrap();
+        let upload = Self::owned_upload(&mut pending, upload_id, username)?;
+
+        if upload.busy {
+            return Err("Another chunk of this upload is still being written".to_string());
+        }
+        if offset != upload.received {
+            return Err(format!(
+                "Unexpected chunk offset {} (expected {})",
+                offset, upload.received
+            ));
+        }
+        if upload.received + length as u64 > upload.total_size {
+            return Err(format!(
+                "Chunk exceeds the declared file size of {} bytes",
+                upload.total_size
+            ));
+        }
+
+        upload.busy = true;
+        Ok(upload.clone())
+    }
+
+    /// Releases the upload after a chunk, counting its bytes if it was written.
+    pub fn end_chunk(&self, upload_id: &str, written: Option<usize>) {
+        if let Some(upload) = self.pending.lock().unwrap().get_mut(upload_id) {
+            upload.busy = false;
+            upload.last_activity = Utc::now();
+            if let Some(written) = written {
+                upload.received += written as u64;
+            }
+        }
+    }
+
+    /// Removes an upload owned by `username`, returning its state.
+    pub fn take(&self, upload_id: &str, username: &str) -> Result<PendingUpload, String> {
+        let mut pending = self.pending.lock().unwrap();
+        let upload = Self::owned_upload(&mut pending, upload_id, username)?;
+
+        if upload.busy {
+            return Err("A chunk of this upload is still being written".to_string());
+        }
+
+        Ok(pending.remove(upload_id).unwrap())
+    }
+
+    /// Removes and returns uploads with no activity within the TTL.
+    pub fn take_expired(&self) -> Vec<PendingUpload> {
+        let cutoff = Utc::now() - Duration::minutes(UPLOAD_TTL_MINUTES);
+        let mut pending = self.pending.lock().unwrap();
+
+        let expired: Vec<String> = pending
+            .iter()
+            .filter(|(_, upload)| !upload.busy && upload.last_activity < cutoff)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        expired
+            .iter()
+            .filter_map(|id| pending.remove(id))
+            .collect()
+    }
+
+    fn owned_upload<'a>(
+        pending: &'a mut HashMap<String, PendingUpload>,
+        upload_id: &str,
+        username: &str,
+    ) -> Result<&'a mut PendingUpload, String> {
+        match pending.get_mut(upload_id) {
+            Some(upload) if upload.owner_username == username => Ok(upload),
+            _ => Err(format!("Upload not found: '{}'", upload_id)),
+        }
+    }
+}
+
+/// Returns the lowercase extension of a file name, without the dot.
+pub fn file_extension(name: &str) -> Option<String> {
+    let (stem, extension) = name.rsplit_once('.')?;
+    if stem.is_empty() || extension.is_empty() {
+        return None;
+    }
+    Some(extension.to_lowercase())
+}
+
+/// Checks the first bytes of a file against the MIME type its extension declares.
+///
+/// Executables are always rejected. Content with a recognizable binary signature must
+/// match the declared type, so a renamed archive or image cannot pass as a PDF.
+/// Text formats have no reliable signature and are accepted as declared.
+///
+/// # Returns
+/// - `Ok(())` if the content is consistent with the declared type
+/// - `Err(String)` describing the mismatch otherwise
+pub fn verify_content_type(first_chunk: &[u8], declared_mime: &str) -> Result<(), String> {
+    if infer::is_app(first_chunk) {
+        return Err("Executable files are not allowed".to_string());
+    }
+
+    match infer::get(first_chunk) {
+        Some(kind)
+            if kind.matcher_type() != infer::MatcherType::Text
+                && kind.mime_type() != declared_mime =>
+        {
+            Err(format!(
+                "File content ({}) does not match its extension ({})",
+                kind.mime_type(),
+                declared_mime
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_HEADER: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0];
+
+    fn pending(total_size: u64) -> PendingUpload {
+        PendingUpload {
+            target_path: "G:\\Users\\test\\scan.png".to_string(),
+            parent_path: "G:\\Users\\test".to_string(),
+            name: "scan.png".to_string(),
+            staging_path: format!("{}\\test.part", STAGING_DIR),
+            owner_username: "test".to_string(),
+            total_size,
+            received: 0,
+            mime_type: "image/png".to_string(),
+            overwrite: false,
+            busy: false,
+            last_activity: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_file_extension() {
+        assert_eq!(file_extension("Radiografia.JPG"), Some("jpg".to_string()));
+        assert_eq!(file_extension("archive.tar.gz"), Some("gz".to_string()));
+        assert_eq!(file_extension("README"), None);
+        assert_eq!(file_extension(".hidden"), None);
+        assert_eq!(file_extension("trailing."), None);
+    }
+
+    #[test]
+    fn test_verify_content_type() {
+        assert!(verify_content_type(PNG_HEADER, "image/png").is_ok());
+        assert!(verify_content_type(PNG_HEADER, "application/pdf").is_err());
+        assert!(verify_content_type(b"MZ\x90\x00\x03\x00\x00\x00", "application/pdf").is_err());
+        assert!(verify_content_type(b"nombre,apellido\n", "text/csv").is_ok());
+    }
+
+    #[test]
+    fn test_chunks_must_arrive_in_order() {
+        let registry = UploadRegistry::new();
+        registry.insert("up1", pending(10));
+
+        assert!(registry.begin_chunk("up1", "other_user", 0, 4).is_err());
+        assert!(registry.begin_chunk("up1", "test", 2, 4).is_err());
+
+        registry.begin_chunk("up1", "test", 0, 4).unwrap();
+        assert!(registry.begin_chunk("up1", "test", 4, 4).is_err(), "busy");
+        registry.end_chunk("up1", Some(4));
+
+        assert!(registry.begin_chunk("up1", "test", 4, 7).is_err(), "exceeds size");
+        registry.begin_chunk("up1", "test", 4, 6).unwrap();
+        registry.end_chunk("up1", Some(6));
+
+        let upload = registry.take("up1", "test").unwrap();
+        assert_eq!(upload.received, 10);
+        assert!(registry.take("up1", "test").is_err());
+    }
+}
@@ -0,0 +1,243 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Virtual folder holding the content of previous file versions.
+///
+/// It has no metadata entry, so it never shows up in directory listings.
+pub const VERSIONS_DIR: &str = "G:\\.versions";
+
+/// Configuration key of the number of versions kept per file (0 keeps none).
+const MAX_VERSIONS_CONFIG_KEY: &str = "fsMaxVersionsPerFile";
+
+/// Versions kept when the configuration cannot be read.
+const DEFAULT_MAX_VERSIONS: i64 = 10;
+
+/// VersionManager handles the records of previous file versions.
+///
+/// When a file is overwritten, its content is moved to `VERSIONS_DIR` and recorded in
+/// `filesystem_versions` by virtual path. Like patient links, the records follow
+/// their file when it is renamed, moved or trashed. Versions count towards the
+/// quota of the user whose folder contains the file.
+#[derive(Debug, Clone)]
+pub struct VersionManager;
+
+/// A previous version of a file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileVersion {
+    pub id: i64,
+    pub virtual_path: String,
+    /// Increasing number of the version within the file, starting at 1
+    pub version: i64,
+    pub size: i64,
+    pub mime_type: Option<String>,
+    /// When this content was written
+    pub modified_at: String,
+    /// When this content was overwritten, and by whom
+    pub replaced_at: String,
+    pub replaced_by: String,
+    /// Virtual path of the content under `VERSIONS_DIR` (not sent to the frontend)
+    #[serde(skip)]
+    pub storage_path: String,
+}
+
+const VERSION_COLUMNS: &str = r#"
+    id, virtual_path, version, size, mime_type, modified_at, replaced_at, replaced_by,
+    storage_path
+"#;
+
+impl VersionManager {
+    /// Creates a new VersionManager instance.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Records a version, numbered after the latest version of the file.
+    ///
+    /// # Arguments
+    /// - `conn`: Database connection
+    /// - `version`: The version (its `id`, `version` and `replaced_at` are ignored)
+    ///
+    /// # Returns
+    /// - `Ok(FileVersion)` containing the recorded version
+    /// - `Err(String)` if the insertion fails
+    pub fn insert_version(&self, conn: &Connection, version: &FileVersion) -> Result<FileVersion, String> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        conn.execute(
+            r#"
+            INSERT INTO filesystem_versions (
+                virtual_path, version, size, mime_type, storage_path,
+                modified_at, replaced_at, replaced_by
+            )
+            SELECT ?1, COALESCE(MAX(version), 0) + 1, ?2, ?3, ?4, ?5, ?6, ?7
+            FROM filesystem_versions WHERE virtual_path = ?1
+            "#,
+            params![
+                version.virtual_path,
+                version.size,
+                version.mime_type,
+                version.storage_path,
+                version.modified_at,
+                now,
+                version.replaced_by,
+            ],
+        )
+        .map_err(|e| format!("Failed to insert file version: {}", e))?;
+
+        let query = format!("SELECT {} FROM filesystem_versions WHERE id = ?1", VERSION_COLUMNS);
+        conn.query_row(&query, params![conn.last_insert_rowid()], Self::row_to_version)
+            .map_err(|e| format!("Failed to get file version: {}", e))
+    }
+
+    /// Gets a version of a file by number.
+    pub fn get_version(
+        &self,
+        conn: &Connection,
+        virtual_path: &str,
+        version: i64,
+    ) -> Result<Option<FileVersion>, String> {
+        let query = format!(
+            "SELECT {} FROM filesystem_versions WHERE virtual_path = ?1 AND version = ?2",
+            VERSION_COLUMNS
+        );
+
+        conn.query_row(&query, params![virtual_path, version], Self::row_to_version)
+            .optional()
+            .map_err(|e| format!("Failed to get file version: {}", e))
+    }
+
+    /// Lists the versions of a file, newest first.
+    pub fn list_versions(&self, conn: &Connection, virtual_path: &str) -> Result<Vec<FileVersion>, String> {
+        let query = format!(
+            "SELECT {} FROM filesystem_versions WHERE virtual_path = ?1 ORDER BY version DESC",
+            VERSION_COLUMNS
+        );
+        self.query_versions(conn, &query, virtual_path)
+    }
+
+    /// Lists the versions of an entry and of everything below it.
+    pub fn list_under(&self, conn: &Connection, virtual_path: &str) -> Result<Vec<FileVersion>, String> {
+        let query = format!(
+            r#"
+            SELECT {} FROM filesystem_versions
+            WHERE virtual_path = ?1 OR substr(virtual_path, 1, length(?1) + 1) = ?1 || '\'
+            "#,
+            VERSION_COLUMNS
+        );
+        self.query_versions(conn, &query, virtual_path)
+    }
+
+    /// Lists the versions of a file beyond the newest `keep` ones.
+    pub fn list_excess(
+        &self,
+        conn: &Connection,
+        virtual_path: &str,
+        keep: i64,
+    ) -> Result<Vec<FileVersion>, String> {
+        Ok(self
+            .list_versions(conn, virtual_path)?
+            .into_iter()
+            .skip(keep.max(0) as usize)
+            .collect())
+    }
+
+    /// Total size of the versions of an entry and of everything below it.
+    pub fn size_under(&self, conn: &Connection, virtual_path: &str) -> Result<i64, String> {
+        conn.query_row(
+            r#"
+            SELECT COALESCE(SUM(size), 0) FROM filesystem_versions
+            WHERE virtual_path = ?1 OR substr(virtual_path, 1, length(?1) + 1) = ?1 || '\'
+            "#,
+            params![virtual_path],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to calculate versions size: {}", e))
+    }
+
+    /// Deletes a version record.
+    pub fn delete_version(&self, conn: &Connection, id: i64) -> Result<(), String> {
+        conn.execute("DELETE FROM filesystem_versions WHERE id = ?1", params![id])
+            .map_err(|e| format!("Failed to delete file version: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Points the versions of an entry and of everything below it to a new path.
+    pub fn relocate(
+        &self,
+        conn: &Connection,
+        old_virtual_path: &str,
+        new_virtual_path: &str,
+    ) -> Result<usize, String> {
+        conn.execute(
+            r#"
+            UPDATE filesystem_versions
+            SET virtual_path = ?2 || substr(virtual_path, length(?1) + 1)
+            WHERE virtual_path = ?1 OR substr(virtual_path, 1, length(?1) + 1) = ?1 || '\'
+            "#,
+            params![old_virtual_path, new_virtual_path],
+        )
+        .map_err(|e| format!("Failed to relocate file versions: {}", e))
+    }
+
+    /// Removes the version records of an entry and of everything below it.
+    ///
+    /// The content of the versions has to be deleted by the caller.
+    pub fn delete_under(&self, conn: &Connection, virtual_path: &str) -> Result<usize, String> {
+        conn.execute(
+            r#"
+            DELETE FROM filesystem_versions
+            WHERE virtual_path = ?1 OR substr(virtual_path, 1, length(?1) + 1) = ?1 || '\'
+            "#,
+            params![virtual_path],
+        )
+        .map_err(|e| format!("Failed to delete file versions: {}", e))
+    }
+
+    /// Number of previous versions kept per file (0 disables versioning).
+    pub fn max_versions(&self) -> i64 {
+        crate::config::current_value(MAX_VERSIONS_CONFIG_KEY)
+            .and_then(|value| value.as_i64())
+            .unwrap_or(DEFAULT_MAX_VERSIONS)
+            .max(0)
+    }
+
+    fn query_versions(
+        &self,
+        conn: &Connection,
+        query: &str,
+        virtual_path: &str,
+    ) -> Result<Vec<FileVersion>, String> {
+        let mut stmt = conn
+            .prepare(query)
+            .map_err(|e| format!("Failed to prepare query: {}", e))?;
+
+        let versions = stmt
+            .query_map(params![virtual_path], Self::row_to_version)
+            .map_err(|e| format!("Failed to query file versions: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to collect file versions: {}", e))?;
+
+        Ok(versions)
+    }
+
+    fn row_to_version(row: &rusqlite::Row) -> rusqlite::Result<FileVersion> {
+        Ok(FileVersion {
+            id: row.get(0)?,
+            virtual_path: row.get(1)?,
+            version: row.get(2)?,
+            size: row.get(3)?,
+            mime_type: row.get(4)?,
+            modified_at: row.get(5)?,
+            replaced_at: row.get(6)?,
+            replaced_by: row.get(7)?,
+            storage_path: row.get(8)?,
+        })
+    }
+}
+
+impl Default for VersionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
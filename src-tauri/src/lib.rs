@@ -3,17 +3,23 @@ mod api;
 mod config;
 mod db;
 mod discovery;
+mod events;
 mod filesystem;
 mod global;
 mod import_pipeline;
 mod importer;
 mod integrations;
 mod licensing;
+mod messaging;
 mod node;
+mod passwords;
 mod plugins;
 mod pxlib;
+mod reminders;
+mod repository;
 mod services;
 mod session;
+mod tls;
 mod wizard;
 
 use sysinfo::System;
@@ -150,31 +156,23 @@ fn get_system_stats() -> Result<serde_json::Value, String> {
 // These commands are Tauri adapters that call the domain service layer
 
 #[tauri::command]
-fn get_patients(
+async fn get_patients(
     limit: Option<i64>,
     offset: Option<i64>,
 ) -> Result<Vec<db::patients::Patient>, String> {
-    let service = services::patients::PatientService::new();
-    service
-        .get_all(limit, offset)
-        .map_err(|e: services::ServiceError| e.to_string())
+    repository::run(move |repo| repo.get_patients(limit, offset)).await
 }
 
 #[tauri::command]
-fn get_patient_by_id(id: i64) -> Result<Option<db::patients::Patient>, String> {
-    let service = services::patients::PatientService::new();
-    service
-        .get_by_id(id)
-        .map_err(|e: services::ServiceError| e.to_string())
+async fn get_patient_by_id(id: i64) -> Result<Option<db::patients::Patient>, String> {
+    repository::run(move |repo| repo.get_patient_by_id(id)).await
 }
 
 #[tauri::command]
-fn create_patient(input: db::patients::CreatePatientInput) -> Result<i64, String> {
-    let service = services::patients::PatientService::new();
+async fn create_patient(input: db::patients::CreatePatientInput) -> Result<i64, String> {
+    session::require_permission(services::authorization::Permission::WritePatients)?;
     let payload = serde_json::to_value(&input).unwrap_or_else(|_| serde_json::json!({}));
-    let id = service
-        .create(input)
-        .map_err(|e: services::ServiceError| e.to_string())?;
+    let id = repository::run(move |repo| repo.create_patient(input)).await?;
 
     std::thread::spawn(move || {
         let _ = integrations::trigger_event(integrations::TriggerEventInput {
@@ -190,79 +188,80 @@ fn create_patient(input: db::patients::CreatePatientInput) -> Result<i64, String
 }
 
 #[tauri::command]
-fn update_patient(id: i64, input: db::patients::UpdatePatientInput) -> Result<(), String> {
-    let service = services::patients::PatientService::new();
-    service
-        .update(id, input)
-        .map_err(|e: services::ServiceError| e.to_string())
+async fn update_patient(id: i64, input: db::patients::UpdatePatientInput) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::WritePatients)?;
+    repository::run(move |repo| repo.update_patient(id, input)).await
 }
 
 #[tauri::command]
-fn delete_patient(id: i64) -> Result<(), String> {
-    let service = services::patients::PatientService::new();
-    service
-        .delete(id)
-        .map_err(|e: services::ServiceError| e.to_string())
+async fn delete_patient(id: i64) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::DeleteRecords)?;
+    repository::run(move |repo| repo.delete_patient(id)).await
 }
 
 #[tauri::command]
-fn search_patients(query: String) -> Result<Vec<db::patients::Patient>, String> {
-    let service = services::patients::PatientService::new();
-    service
-        .search(&query)
-        .map_err(|e: services::ServiceError| e.to_string())
+async fn search_patients(query: String) -> Result<Vec<db::patients::Patient>, String> {
+    repository::run(move |repo| repo.search_patients(&query)).await
 }
 
 #[tauri::command]
-fn get_patients_count() -> Result<i64, String> {
-    let service = services::patients::PatientService::new();
-    service
-        .get_count()
-        .map_err(|e: services::ServiceError| e.to_string())
+async fn get_patients_count() -> Result<i64, String> {
+    repository::run(move |repo| repo.get_patients_count()).await
 }
 
 // ===== TREATMENTS COMMANDS =====
 #[tauri::command]
-fn get_all_treatments(
+async fn get_all_treatments(
     limit: Option<i64>,
     offset: Option<i64>,
 ) -> Result<Vec<db::treatments::Treatment>, String> {
-    db::treatments::get_all_treatments(limit, offset)
+    repository::run(move |repo| repo.get_all_treatments(limit, offset)).await
 }
 
 #[tauri::command]
-fn get_treatment_by_id(id: i64) -> Result<Option<db::treatments::Treatment>, String> {
-    db::treatments::get_treatment_by_id(id)
+async fn get_treatment_by_id(id: i64) -> Result<Option<db::treatments::Treatment>, String> {
+    repository::run(move |repo| repo.get_treatment_by_id(id)).await
 }
 
 #[tauri::command]
-fn get_treatments_by_patient(patient_id: i64) -> Result<Vec<db::treatments::Treatment>, String> {
-    db::treatments::get_treatments_by_patient(patient_id)
+async fn get_treatments_by_patient(
+    patient_id: i64,
+) -> Result<Vec<db::treatments::Treatment>, String> {
+    repository::run(move |repo| repo.get_treatments_by_patient(patient_id)).await
 }
 
 #[tauri::command]
-fn get_treatments_by_status(status: String) -> Result<Vec<db::treatments::Treatment>, String> {
-    db::treatments::get_treatments_by_status(&status)
+async fn get_treatments_by_status(
+    status: String,
+) -> Result<Vec<db::treatments::Treatment>, String> {
+    repository::run(move |repo| repo.get_treatments_by_status(&status)).await
 }
 
 #[tauri::command]
-fn create_treatment(input: db::treatments::CreateTreatmentInput) -> Result<i64, String> {
-    db::treatments::create_treatment(input)
+async fn create_treatment(input: db::treatments::CreateTreatmentInput) -> Result<i64, String> {
+    session::require_permission(services::authorization::Permission::WriteClinical)?;
+    repository::run(move |repo| repo.create_treatment(input)).await
 }
 
 #[tauri::command]
-fn update_treatment(id: i64, input: db::treatments::UpdateTreatmentInput) -> Result<(), String> {
-    db::treatments::update_treatment(id, input)
+async fn update_treatment(
+    id: i64,
+    input: db::treatments::UpdateTreatmentInput,
+) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::WriteClinical)?;
+    repository::run(move |repo| repo.update_treatment(id, input)).await
 }
 
 #[tauri::command]
-fn update_treatment_status(id: i64, status: String) -> Result<(), String> {
-    db::treatments::update_treatment_status(id, &status)
+async fn update_treatment_status(id: i64, status: String) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::WriteClinical)?;
+    repository::run(move |repo| repo.update_treatment_status(id, &status)).await
 }
 
 #[tauri::command]
-fn delete_treatment(id: i64) -> Result<(), String> {
-    db::treatments::delete_treatment(id)
+async fn delete_treatment(id: i64) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::DeleteRecords)?;
+    repository::run(move |repo| repo.delete_treatment(id)).await
 }
 
 // ===== TEMPLATES COMMANDS =====
@@ -316,326 +315,360 @@ fn set_default_template(id: i64, template_type: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-fn get_treatment_stats() -> Result<db::treatments::TreatmentStats, String> {
-    db::treatments::get_treatment_stats()
+async fn get_treatment_stats() -> Result<db::treatments::TreatmentStats, String> {
+    repository::run(move |repo| repo.get_treatment_stats()).await
 }
 
 // ===== PAYMENTS COMMANDS =====
 #[tauri::command]
-fn get_all_payments(
+async fn get_all_payments(
     limit: Option<i64>,
     offset: Option<i64>,
 ) -> Result<Vec<db::payments::Payment>, String> {
-    db::payments::get_all_payments(limit, offset)
+    repository::run(move |repo| repo.get_all_payments(limit, offset)).await
 }
 
 #[tauri::command]
-fn get_payment_by_id(id: i64) -> Result<Option<db::payments::Payment>, String> {
-    db::payments::get_payment_by_id(id)
+async fn get_payment_by_id(id: i64) -> Result<Option<db::payments::Payment>, String> {
+    repository::run(move |repo| repo.get_payment_by_id(id)).await
 }
 
 #[tauri::command]
-fn get_payments_by_treatment(treatment_id: i64) -> Result<Vec<db::payments::Payment>, String> {
-    db::payments::get_payments_by_treatment(treatment_id)
+async fn get_payments_by_treatment(
+    treatment_id: i64,
+) -> Result<Vec<db::payments::Payment>, String> {
+    repository::run(move |repo| repo.get_payments_by_treatment(treatment_id)).await
 }
 
 #[tauri::command]
-fn get_payments_by_patient(patient_id: i64) -> Result<Vec<db::payments::Payment>, String> {
-    db::payments::get_payments_by_patient(patient_id)
+async fn get_payments_by_patient(patient_id: i64) -> Result<Vec<db::payments::Payment>, String> {
+    repository::run(move |repo| repo.get_payments_by_patient(patient_id)).await
 }
 
 #[tauri::command]
-fn create_payment(input: db::payments::CreatePaymentInput) -> Result<i64, String> {
-    db::payments::create_payment(input)
+async fn create_payment(input: db::payments::CreatePaymentInput) -> Result<i64, String> {
+    session::require_permission(services::authorization::Permission::RecordPayments)?;
+    repository::run(move |repo| repo.create_payment(input)).await
 }
 
 #[tauri::command]
-fn update_payment(id: i64, input: db::payments::UpdatePaymentInput) -> Result<(), String> {
-    db::payments::update_payment(id, input)
+async fn update_payment(id: i64, input: db::payments::UpdatePaymentInput) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::EditPayments)?;
+    repository::run(move |repo| repo.update_payment(id, input)).await
 }
 
 #[tauri::command]
-fn delete_payment(id: i64) -> Result<(), String> {
-    db::payments::delete_payment(id)
+async fn delete_payment(id: i64) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::DeleteRecords)?;
+    repository::run(move |repo| repo.delete_payment(id)).await
 }
 
 #[tauri::command]
-fn get_patient_balance(patient_id: i64) -> Result<db::payments::PatientBalance, String> {
-    db::payments::get_patient_balance(patient_id)
+async fn get_patient_balance(patient_id: i64) -> Result<db::payments::PatientBalance, String> {
+    repository::run(move |repo| repo.get_patient_balance(patient_id)).await
 }
 
 #[tauri::command]
-fn get_patients_with_debt(
+async fn get_patients_with_debt(
     limit: Option<i64>,
     offset: Option<i64>,
     query: Option<String>,
 ) -> Result<Vec<db::payments::PatientBalance>, String> {
-    db::payments::get_patients_with_debt(limit, offset, query)
+    repository::run(move |repo| repo.get_patients_with_debt(limit, offset, query)).await
 }
 
 #[tauri::command]
-fn get_patients_with_debt_count() -> Result<i64, String> {
-    db::payments::get_patients_with_debt_count()
+async fn get_patients_with_debt_count() -> Result<i64, String> {
+    repository::run(move |repo| repo.get_patients_with_debt_count()).await
 }
 
 #[tauri::command]
-fn get_patients_with_debt_summary(
+async fn get_patients_with_debt_summary(
     query: Option<String>,
 ) -> Result<db::payments::PatientDebtSummary, String> {
-    db::payments::get_patients_with_debt_summary(query)
+    repository::run(move |repo| repo.get_patients_with_debt_summary(query)).await
 }
 
 #[tauri::command]
-fn get_total_debt() -> Result<f64, String> {
-    db::payments::get_total_debt()
+async fn get_total_debt() -> Result<f64, String> {
+    repository::run(move |repo| repo.get_total_debt()).await
 }
 
 #[tauri::command]
-fn get_recent_payments(limit: Option<i64>) -> Result<Vec<db::payments::Payment>, String> {
-    db::payments::get_recent_payments(limit)
+async fn get_recent_payments(limit: Option<i64>) -> Result<Vec<db::payments::Payment>, String> {
+    repository::run(move |repo| repo.get_recent_payments(limit)).await
 }
 
 // ===== ODONTOGRAMS COMMANDS =====
 #[tauri::command]
-fn get_odontogram_by_patient(
+async fn get_odontogram_by_patient(
     patient_id: i64,
 ) -> Result<Vec<db::odontograms::OdontogramEntry>, String> {
-    db::odontograms::get_odontogram_by_patient(patient_id)
+    repository::run(move |repo| repo.get_odontogram_by_patient(patient_id)).await
 }
 
 #[tauri::command]
-fn get_tooth_by_patient_and_number(
+async fn get_tooth_by_patient_and_number(
     patient_id: i64,
     tooth_number: String,
 ) -> Result<Option<db::odontograms::OdontogramEntry>, String> {
-    db::odontograms::get_tooth_by_patient_and_number(patient_id, &tooth_number)
+    repository::run(move |repo| repo.get_tooth_by_patient_and_number(patient_id, &tooth_number))
+        .await
 }
 
 #[tauri::command]
-fn update_tooth_condition(
+async fn update_tooth_condition(
     input: db::odontograms::UpdateToothConditionInput,
 ) -> Result<i64, String> {
-    db::odontograms::update_tooth_condition(input)
+    session::require_permission(services::authorization::Permission::WriteClinical)?;
+    repository::run(move |repo| repo.update_tooth_condition(input)).await
 }
 
 #[tauri::command]
-fn delete_tooth_condition(patient_id: i64, tooth_number: String) -> Result<(), String> {
-    db::odontograms::delete_tooth_condition(patient_id, &tooth_number)
+async fn delete_tooth_condition(patient_id: i64, tooth_number: String) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::WriteClinical)?;
+    repository::run(move |repo| repo.delete_tooth_condition(patient_id, &tooth_number)).await
 }
 
 #[tauri::command]
-fn clear_patient_odontogram(patient_id: i64) -> Result<(), String> {
-    db::odontograms::clear_patient_odontogram(patient_id)
+async fn clear_patient_odontogram(patient_id: i64) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::WriteClinical)?;
+    repository::run(move |repo| repo.clear_patient_odontogram(patient_id)).await
 }
 
 #[tauri::command]
-fn get_tooth_history(
+async fn get_tooth_history(
     patient_id: i64,
     tooth_number: String,
 ) -> Result<Vec<db::odontograms::OdontogramEntry>, String> {
-    db::odontograms::get_tooth_history(patient_id, &tooth_number)
+    repository::run(move |repo| repo.get_tooth_history(patient_id, &tooth_number)).await
 }
 
 // ===== ODONTOGRAM SURFACES COMMANDS =====
 #[tauri::command]
-fn get_odontogram_surfaces_by_patient(
+async fn get_odontogram_surfaces_by_patient(
     patient_id: i64,
 ) -> Result<Vec<db::odontogram_surfaces::OdontogramSurface>, String> {
-    db::odontogram_surfaces::get_odontogram_surfaces_by_patient(patient_id)
+    repository::run(move |repo| repo.get_odontogram_surfaces_by_patient(patient_id)).await
 }
 
 #[tauri::command]
-fn get_tooth_surfaces(
+async fn get_tooth_surfaces(
     patient_id: i64,
     tooth_number: String,
 ) -> Result<Vec<db::odontogram_surfaces::OdontogramSurface>, String> {
-    db::odontogram_surfaces::get_tooth_surfaces(patient_id, &tooth_number)
+    repository::run(move |repo| repo.get_tooth_surfaces(patient_id, &tooth_number)).await
 }
 
 #[tauri::command]
-fn update_tooth_surface(input: db::odontogram_surfaces::UpdateSurfaceInput) -> Result<i64, String> {
-    db::odontogram_surfaces::update_tooth_surface(input)
+async fn update_tooth_surface(
+    input: db::odontogram_surfaces::UpdateSurfaceInput,
+) -> Result<i64, String> {
+    session::require_permission(services::authorization::Permission::WriteClinical)?;
+    repository::run(move |repo| repo.update_tooth_surface(input)).await
 }
 
 #[tauri::command]
-fn delete_tooth_surface(
+async fn delete_tooth_surface(
     patient_id: i64,
     tooth_number: String,
     surface: String,
 ) -> Result<(), String> {
-    db::odontogram_surfaces::delete_tooth_surface(patient_id, &tooth_number, &surface)
+    session::require_permission(services::authorization::Permission::WriteClinical)?;
+    repository::run(move |repo| repo.delete_tooth_surface(patient_id, &tooth_number, &surface))
+        .await
 }
 
 #[tauri::command]
-fn clear_tooth_surfaces(patient_id: i64, tooth_number: String) -> Result<(), String> {
-    db::odontogram_surfaces::clear_tooth_surfaces(patient_id, &tooth_number)
+async fn clear_tooth_surfaces(patient_id: i64, tooth_number: String) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::WriteClinical)?;
+    repository::run(move |repo| repo.clear_tooth_surfaces(patient_id, &tooth_number)).await
 }
 
 #[tauri::command]
-fn add_tooth_surface_treatment(
+async fn add_tooth_surface_treatment(
     input: db::odontogram_surfaces::AddSurfaceTreatmentInput,
 ) -> Result<i64, String> {
-    db::odontogram_surfaces::add_tooth_surface_treatment(input)
+    session::require_permission(services::authorization::Permission::WriteClinical)?;
+    repository::run(move |repo| repo.add_tooth_surface_treatment(input)).await
 }
 
 #[tauri::command]
-fn get_surface_treatments(
+async fn get_surface_treatments(
     patient_id: i64,
     tooth_number: String,
     surface: String,
 ) -> Result<Vec<db::odontogram_surfaces::OdontogramSurface>, String> {
-    db::odontogram_surfaces::get_surface_treatments(patient_id, &tooth_number, &surface)
+    repository::run(move |repo| repo.get_surface_treatments(patient_id, &tooth_number, &surface))
+        .await
 }
 
 #[tauri::command]
-fn deactivate_surface_treatment(surface_id: i64) -> Result<(), String> {
-    db::odontogram_surfaces::deactivate_surface_treatment(surface_id)
+async fn deactivate_surface_treatment(surface_id: i64) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::WriteClinical)?;
+    repository::run(move |repo| repo.deactivate_surface_treatment(surface_id)).await
 }
 
 #[tauri::command]
-fn get_surface_history(
+async fn get_surface_history(
     patient_id: i64,
     tooth_number: String,
     surface: String,
 ) -> Result<Vec<db::odontogram_surfaces::SurfaceHistoryEntry>, String> {
-    db::odontogram_surfaces::get_surface_history(patient_id, &tooth_number, &surface)
+    repository::run(move |repo| repo.get_surface_history(patient_id, &tooth_number, &surface)).await
 }
 
 #[tauri::command]
-fn get_tooth_surface_history(
+async fn get_tooth_surface_history(
     patient_id: i64,
     tooth_number: String,
 ) -> Result<Vec<db::odontogram_surfaces::SurfaceHistoryEntry>, String> {
-    db::odontogram_surfaces::get_tooth_history(patient_id, &tooth_number)
+    repository::run(move |repo| repo.get_tooth_surface_history(patient_id, &tooth_number)).await
 }
 
 // ===== ODONTOGRAM TOOTH TREATMENTS COMMANDS (diente completo) =====
 #[tauri::command]
-fn get_tooth_treatments(
+async fn get_tooth_treatments(
     patient_id: i64,
     tooth_number: String,
 ) -> Result<Vec<db::odontogram_tooth_treatments::OdontogramToothTreatment>, String> {
-    db::odontogram_tooth_treatments::get_tooth_treatments(patient_id, &tooth_number)
+    repository::run(move |repo| repo.get_tooth_treatments(patient_id, &tooth_number)).await
 }
 
 #[tauri::command]
-fn get_tooth_treatments_by_patient(
+async fn get_tooth_treatments_by_patient(
     patient_id: i64,
 ) -> Result<Vec<db::odontogram_tooth_treatments::OdontogramToothTreatment>, String> {
-    db::odontogram_tooth_treatments::get_tooth_treatments_by_patient(patient_id)
+    repository::run(move |repo| repo.get_tooth_treatments_by_patient(patient_id)).await
 }
 
 #[tauri::command]
-fn add_tooth_treatment(
+async fn add_tooth_treatment(
     input: db::odontogram_tooth_treatments::AddToothTreatmentInput,
 ) -> Result<i64, String> {
-    db::odontogram_tooth_treatments::add_tooth_treatment(input)
+    session::require_permission(services::authorization::Permission::WriteClinical)?;
+    repository::run(move |repo| repo.add_tooth_treatment(input)).await
 }
 
 #[tauri::command]
-fn deactivate_tooth_treatment(treatment_id: i64) -> Result<(), String> {
-    db::odontogram_tooth_treatments::deactivate_tooth_treatment(treatment_id)
+async fn deactivate_tooth_treatment(treatment_id: i64) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::WriteClinical)?;
+    repository::run(move |repo| repo.deactivate_tooth_treatment(treatment_id)).await
 }
 
 #[tauri::command]
-fn get_tooth_treatment_history(
+async fn get_tooth_treatment_history(
     patient_id: i64,
     tooth_number: String,
 ) -> Result<Vec<db::odontogram_tooth_treatments::ToothTreatmentHistoryEntry>, String> {
-    db::odontogram_tooth_treatments::get_tooth_treatment_history(patient_id, &tooth_number)
+    repository::run(move |repo| repo.get_tooth_treatment_history(patient_id, &tooth_number)).await
 }
 
 // ===== ODONTOGRAM BRIDGES COMMANDS =====
 #[tauri::command]
-fn get_bridges_by_patient(
+async fn get_bridges_by_patient(
     patient_id: i64,
 ) -> Result<Vec<db::odontogram_tooth_treatments::OdontogramBridge>, String> {
-    db::odontogram_tooth_treatments::get_bridges_by_patient(patient_id)
+    repository::run(move |repo| repo.get_bridges_by_patient(patient_id)).await
 }
 
 #[tauri::command]
-fn add_bridge(input: db::odontogram_tooth_treatments::AddBridgeInput) -> Result<i64, String> {
-    db::odontogram_tooth_treatments::add_bridge(input)
+async fn add_bridge(input: db::odontogram_tooth_treatments::AddBridgeInput) -> Result<i64, String> {
+    session::require_permission(services::authorization::Permission::WriteClinical)?;
+    repository::run(move |repo| repo.add_bridge(input)).await
 }
 
 #[tauri::command]
-fn deactivate_bridge(bridge_id: i64) -> Result<(), String> {
-    db::odontogram_tooth_treatments::deactivate_bridge(bridge_id)
+async fn deactivate_bridge(bridge_id: i64) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::WriteClinical)?;
+    repository::run(move |repo| repo.deactivate_bridge(bridge_id)).await
 }
 
 // ===== TREATMENT CATALOG COMMANDS =====
 #[tauri::command]
-fn get_all_treatment_catalog() -> Result<Vec<db::treatment_catalog::TreatmentCatalogEntry>, String>
-{
-    db::treatment_catalog::get_all_treatment_catalog()
+async fn get_all_treatment_catalog(
+) -> Result<Vec<db::treatment_catalog::TreatmentCatalogEntry>, String> {
+    repository::run(move |repo| repo.get_all_treatment_catalog()).await
 }
 
 #[tauri::command]
-fn get_treatment_catalog_by_id(
+async fn get_treatment_catalog_by_id(
     id: i64,
 ) -> Result<Option<db::treatment_catalog::TreatmentCatalogEntry>, String> {
-    db::treatment_catalog::get_treatment_catalog_by_id(id)
+    repository::run(move |repo| repo.get_treatment_catalog_by_id(id)).await
 }
 
 #[tauri::command]
-fn create_treatment_catalog(
+async fn create_treatment_catalog(
     input: db::treatment_catalog::CreateTreatmentCatalogInput,
 ) -> Result<i64, String> {
-    db::treatment_catalog::create_treatment_catalog(input)
+    session::require_permission(services::authorization::Permission::ManageCatalog)?;
+    repository::run(move |repo| repo.create_treatment_catalog(input)).await
 }
 
 #[tauri::command]
-fn update_treatment_catalog(
+async fn update_treatment_catalog(
     input: db::treatment_catalog::UpdateTreatmentCatalogInput,
 ) -> Result<(), String> {
-    db::treatment_catalog::update_treatment_catalog(input)
+    session::require_permission(services::authorization::Permission::ManageCatalog)?;
+    repository::run(move |repo| repo.update_treatment_catalog(input)).await
 }
 
 #[tauri::command]
-fn delete_treatment_catalog(id: i64) -> Result<(), String> {
-    db::treatment_catalog::delete_treatment_catalog(id)
+async fn delete_treatment_catalog(id: i64) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::DeleteRecords)?;
+    repository::run(move |repo| repo.delete_treatment_catalog(id)).await
 }
 
 #[tauri::command]
-fn get_treatment_catalog_items(
+async fn get_treatment_catalog_items(
     treatment_catalog_id: i64,
 ) -> Result<Vec<db::treatment_catalog::TreatmentCatalogItem>, String> {
-    db::treatment_catalog::get_treatment_catalog_items(treatment_catalog_id)
+    repository::run(move |repo| repo.get_treatment_catalog_items(treatment_catalog_id)).await
 }
 
 #[tauri::command]
-fn get_treatment_catalog_item_by_id(
+async fn get_treatment_catalog_item_by_id(
     id: i64,
 ) -> Result<Option<db::treatment_catalog::TreatmentCatalogItem>, String> {
-    db::treatment_catalog::get_treatment_catalog_item_by_id(id)
+    repository::run(move |repo| repo.get_treatment_catalog_item_by_id(id)).await
 }
 
 #[tauri::command]
-fn create_treatment_catalog_item(
+async fn create_treatment_catalog_item(
     input: db::treatment_catalog::CreateTreatmentCatalogItemInput,
 ) -> Result<i64, String> {
-    db::treatment_catalog::create_treatment_catalog_item(input)
+    session::require_permission(services::authorization::Permission::ManageCatalog)?;
+    repository::run(move |repo| repo.create_treatment_catalog_item(input)).await
 }
 
 #[tauri::command]
-fn update_treatment_catalog_item(
+async fn update_treatment_catalog_item(
     input: db::treatment_catalog::UpdateTreatmentCatalogItemInput,
 ) -> Result<(), String> {
-    db::treatment_catalog::update_treatment_catalog_item(input)
+    session::require_permission(services::authorization::Permission::ManageCatalog)?;
+    repository::run(move |repo| repo.update_treatment_catalog_item(input)).await
 }
 
 #[tauri::command]
-fn delete_treatment_catalog_item(id: i64) -> Result<(), String> {
-    db::treatment_catalog::delete_treatment_catalog_item(id)
+async fn delete_treatment_catalog_item(id: i64) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::DeleteRecords)?;
+    repository::run(move |repo| repo.delete_treatment_catalog_item(id)).await
 }
 
 // ===== APPOINTMENTS COMMANDS =====
 #[tauri::command]
-fn create_appointment(appointment: db::appointments::Appointment) -> Result<i64, String> {
-    let conn = db::get_connection()?;
+async fn create_appointment(
+    appointment: db::appointments::Appointment,
+    allow_overbooking: Option<bool>,
+) -> Result<i64, String> {
+    session::require_permission(services::authorization::Permission::ManageAppointments)?;
     let appointment_payload =
         serde_json::to_value(&appointment).unwrap_or_else(|_| serde_json::json!({}));
-    let id = db::appointments::create_appointment(&conn, &appointment)?;
+    let id = repository::run(move |repo| {
+        repo.create_appointment(&appointment, allow_overbooking.unwrap_or(false))
+    })
+    .await?;
 
     std::thread::spawn(move || {
         let _ = integrations::trigger_event(integrations::TriggerEventInput {
@@ -651,49 +684,145 @@ fn create_appointment(appointment: db::appointments::Appointment) -> Result<i64,
 }
 
 #[tauri::command]
-fn update_appointment(appointment: db::appointments::Appointment) -> Result<(), String> {
-    let conn = db::get_connection()?;
-    db::appointments::update_appointment(&conn, &appointment)
+async fn update_appointment(
+    appointment: db::appointments::Appointment,
+    allow_overbooking: Option<bool>,
+) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::ManageAppointments)?;
+    repository::run(move |repo| {
+        repo.update_appointment(&appointment, allow_overbooking.unwrap_or(false))
+    })
+    .await
 }
 
 #[tauri::command]
-fn delete_appointment(id: i64) -> Result<(), String> {
-    let conn = db::get_connection()?;
-    db::appointments::delete_appointment(&conn, id)
+async fn delete_appointment(id: i64) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::DeleteRecords)?;
+    repository::run(move |repo| repo.delete_appointment(id)).await
 }
 
 #[tauri::command]
-fn get_appointment(id: i64) -> Result<db::appointments::Appointment, String> {
-    let conn = db::get_connection()?;
-    db::appointments::get_appointment(&conn, id)
+async fn get_appointment(id: i64) -> Result<db::appointments::Appointment, String> {
+    repository::run(move |repo| repo.get_appointment(id)).await
 }
 
 #[tauri::command]
-fn list_appointments(
+async fn list_appointments(
     filter: db::appointments::AppointmentFilter,
 ) -> Result<Vec<db::appointments::AppointmentWithPatient>, String> {
-    let conn = db::get_connection()?;
-    db::appointments::list_appointments(&conn, &filter)
+    repository::run(move |repo| repo.list_appointments(&filter)).await
 }
 
 #[tauri::command]
-fn get_pending_reminders() -> Result<Vec<db::appointments::AppointmentReminder>, String> {
-    let conn = db::get_connection()?;
-    db::appointments::get_pending_reminders(&conn)
+async fn get_pending_reminders() -> Result<Vec<db::appointments::AppointmentReminder>, String> {
+    repository::run(move |repo| repo.get_pending_reminders()).await
 }
 
 #[tauri::command]
-fn mark_reminder_sent(reminder_id: i64, notification_id: String) -> Result<(), String> {
-    let conn = db::get_connection()?;
-    db::appointments::mark_reminder_sent(&conn, reminder_id, &notification_id)
+async fn get_reminder_notification(
+    reminder_id: i64,
+) -> Result<Option<db::appointments::ReminderNotification>, String> {
+    repository::run(move |repo| repo.get_reminder_notification(reminder_id)).await
 }
 
 #[tauri::command]
-fn get_upcoming_appointments(
+async fn send_test_patient_reminder(to: String) -> Result<String, String> {
+    session::require_permission(services::authorization::Permission::ManageAppointments)?;
+    // SMTP and gateway sends block until they finish or time out
+    tauri::async_runtime::spawn_blocking(move || reminders::send_test_message(&to))
+        .await
+        .map_err(|e| format!("Test reminder send panicked: {}", e))?
+}
+
+#[tauri::command]
+async fn mark_reminder_sent(reminder_id: i64, notification_id: String) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::ManageAppointments)?;
+    repository::run(move |repo| repo.mark_reminder_sent(reminder_id, &notification_id)).await
+}
+
+#[tauri::command]
+async fn get_upcoming_appointments(
     hours: i32,
 ) -> Result<Vec<db::appointments::AppointmentWithPatient>, String> {
-    let conn = db::get_connection()?;
-    db::appointments::get_upcoming_appointments(&conn, hours)
+    repository::run(move |repo| repo.get_upcoming_appointments(hours)).await
+}
+
+#[tauri::command]
+async fn update_appointment_occurrence(
+    series_id: i64,
+    original_start_time: String,
+    appointment: db::appointments::Appointment,
+    scope: db::appointments::RecurrenceScope,
+    allow_overbooking: Option<bool>,
+) -> Result<i64, String> {
+    session::require_permission(services::authorization::Permission::ManageAppointments)?;
+    repository::run(move |repo| {
+        repo.update_appointment_occurrence(
+            series_id,
+            &original_start_time,
+            &appointment,
+            scope,
+            allow_overbooking.unwrap_or(false),
+        )
+    })
+    .await
+}
+
+#[tauri::command]
+async fn cancel_appointment_occurrence(
+    series_id: i64,
+    original_start_time: String,
+    scope: db::appointments::RecurrenceScope,
+) -> Result<i64, String> {
+    session::require_permission(services::authorization::Permission::ManageAppointments)?;
+    repository::run(move |repo| {
+        repo.cancel_appointment_occurrence(series_id, &original_start_time, scope)
+    })
+    .await
+}
+
+// ===== AGENDA RESOURCE COMMANDS =====
+#[tauri::command]
+async fn list_agenda_resources(
+    include_inactive: Option<bool>,
+) -> Result<Vec<db::agenda_resources::AgendaResource>, String> {
+    repository::run(move |repo| repo.list_agenda_resources(include_inactive.unwrap_or(false))).await
+}
+
+#[tauri::command]
+async fn get_agenda_resource(
+    id: i64,
+) -> Result<Option<db::agenda_resources::AgendaResource>, String> {
+    repository::run(move |repo| repo.get_agenda_resource(id)).await
+}
+
+#[tauri::command]
+async fn create_agenda_resource(
+    resource: db::agenda_resources::AgendaResource,
+) -> Result<i64, String> {
+    session::require_permission(services::authorization::Permission::ManageAppointments)?;
+    repository::run(move |repo| repo.create_agenda_resource(&resource)).await
+}
+
+#[tauri::command]
+async fn update_agenda_resource(
+    resource: db::agenda_resources::AgendaResource,
+) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::ManageAppointments)?;
+    repository::run(move |repo| repo.update_agenda_resource(&resource)).await
+}
+
+#[tauri::command]
+async fn delete_agenda_resource(id: i64) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::DeleteRecords)?;
+    repository::run(move |repo| repo.delete_agenda_resource(id)).await
+}
+
+#[tauri::command]
+async fn find_free_slots(
+    query: db::agenda_resources::FreeSlotQuery,
+) -> Result<Vec<db::agenda_resources::FreeSlot>, String> {
+    repository::run(move |repo| repo.find_free_slots(&query)).await
 }
 
 // ===== LICENSING COMMANDS =====
@@ -818,7 +947,16 @@ fn get_node_config() -> Result<node::NodeConfig, String> {
 
 #[tauri::command]
 fn set_node_config(config: node::NodeConfig) -> Result<(), String> {
-    node::config::save_node_config(&config)
+    node::config::save_node_config(&config)?;
+
+    match (config.mode, config.client_config) {
+        (node::NodeMode::Client, Some(client_config)) => {
+            events::start_remote_listener(client_config)
+        }
+        _ => events::stop_remote_listener(),
+    }
+
+    Ok(())
 }
 
 #[tauri::command]
@@ -840,6 +978,7 @@ async fn is_api_server_running() -> Result<bool, String> {
 async fn test_remote_connection(
     remote_url: String,
     auth_token: String,
+    pinned_fingerprint: Option<String>,
 ) -> Result<serde_json::Value, String> {
     use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
 
@@ -850,7 +989,7 @@ async fn test_remote_connection(
         HeaderValue::from_str(&auth_value).map_err(|e| format!("Invalid token: {}", e))?,
     );
 
-    let client = reqwest::Client::builder()
+    let client = tls::client_builder(pinned_fingerprint.as_deref())
         .timeout(std::time::Duration::from_secs(5))
         .build()
         .map_err(|e| format!("Failed to create client: {}", e))?;
@@ -879,6 +1018,69 @@ async fn test_remote_connection(
     Ok(body)
 }
 
+// ===== NODE PAIRING COMMANDS =====
+
+#[tauri::command]
+fn list_pairing_requests() -> Result<Vec<services::pairing::PairingRequest>, String> {
+    session::require_permission(services::authorization::Permission::ManageSecurity)?;
+    Ok(services::pairing::PairingService::new().list_pending_requests())
+}
+
+#[tauri::command]
+fn approve_pairing_request(request_id: String) -> Result<i64, String> {
+    session::require_permission(services::authorization::Permission::ManageSecurity)?;
+    Ok(services::pairing::PairingService::new().approve_request(&request_id)?)
+}
+
+#[tauri::command]
+fn reject_pairing_request(request_id: String) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::ManageSecurity)?;
+    Ok(services::pairing::PairingService::new().reject_request(&request_id)?)
+}
+
+#[tauri::command]
+fn list_paired_nodes() -> Result<Vec<db::paired_nodes::PairedNode>, String> {
+    session::require_permission(services::authorization::Permission::ManageSecurity)?;
+    Ok(services::pairing::PairingService::new().list_paired_nodes()?)
+}
+
+#[tauri::command]
+fn revoke_paired_node(node_id: i64) -> Result<(), String> {
+    session::require_permission(services::authorization::Permission::ManageSecurity)?;
+    Ok(services::pairing::PairingService::new().revoke_paired_node(node_id)?)
+}
+
+/// Ask a host to pair with this node; the returned code is shown until the host approves
+#[tauri::command]
+async fn request_node_pairing(
+    remote_url: String,
+    pinned_fingerprint: Option<String>,
+) -> Result<services::pairing::PairingTicket, String> {
+    let node_name = node::config::load_node_config()?.node_name;
+    node::pairing::request_pairing(&remote_url, pinned_fingerprint.as_deref(), &node_name).await
+}
+
+/// Poll a pairing request; on approval this node switches to Client mode
+#[tauri::command]
+async fn complete_node_pairing(
+    remote_url: String,
+    pinned_fingerprint: Option<String>,
+    ticket: services::pairing::PairingTicket,
+) -> Result<services::pairing::PairingStatus, String> {
+    let status =
+        node::pairing::complete_pairing(&remote_url, pinned_fingerprint.as_deref(), &ticket)
+            .await?;
+
+    if let Some(client_config) = node::config::load_node_config()?
+        .client_config
+        .filter(|_| matches!(status, services::pairing::PairingStatus::Approved { .. }))
+    {
+        events::start_remote_listener(client_config);
+    }
+
+    Ok(status)
+}
+
 // ===== DISCOVERY COMMANDS =====
 
 #[tauri::command]
@@ -890,8 +1092,16 @@ async fn start_node_broadcast(node_name: String, port: u16) -> Result<(), String
         guard.clone().unwrap()
     };
 
+    let fingerprint = match node::config::load_node_config()?.host_config {
+        Some(host_config) => tls::advertised_fingerprint(&host_config)?,
+        None => None,
+    };
+
     tokio::spawn(async move {
-        if let Err(e) = service_clone.start_broadcasting(&node_name, port).await {
+        if let Err(e) = service_clone
+            .start_broadcasting(&node_name, port, fingerprint.as_deref())
+            .await
+        {
             log::error!("Failed to start node broadcasting: {}", e);
         }
     });
@@ -1156,6 +1366,53 @@ pub fn run() {
                 filesystem::initialize().expect("Failed to initialize filesystem");
             app.handle().manage(filesystem_state);
 
+            // Filesystem maintenance: purge expired trash and locks, fix storage usage drift
+            // and index the content of files missing from the search index
+            let fs_app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(filesystem::MAINTENANCE_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let state = fs_app_handle.state::<filesystem::FilesystemState>();
+
+                    match state.operations.purge_expired_trash().await {
+                        Ok(0) => {}
+                        Ok(purged) => log::info!("Purged {} expired trash entries", purged),
+                        Err(e) => log::warn!("Failed to purge expired trash: {}", e),
+                    }
+
+                    match state.operations.reap_expired_locks().await {
+                        Ok(0) => {}
+                        Ok(reaped) => log::info!("Removed {} expired file locks", reaped),
+                        Err(e) => log::warn!("Failed to remove expired file locks: {}", e),
+                    }
+
+                    match state.operations.recalculate_quotas().await {
+                        Ok(0) => {}
+                        Ok(fixed) => log::info!("Fixed storage usage of {} users", fixed),
+                        Err(e) => log::warn!("Failed to recalculate storage usage: {}", e),
+                    }
+
+                    match state.operations.update_content_index().await {
+                        Ok(0) => {}
+                        Ok(indexed) => log::info!("Indexed the content of {} files", indexed),
+                        Err(e) => log::warn!("Failed to update the content index: {}", e),
+                    }
+
+                    match state.operations.reencrypt_files().await {
+                        Ok(0) => {}
+                        Ok(count) => log::info!("Re-encrypted {} files", count),
+                        Err(e) => log::warn!("Failed to re-encrypt files: {}", e),
+                    }
+                }
+            });
+
+            // Re-emit changes made on this node (locally or through the API) to the frontend
+            events::start_local_forwarder();
+
+            // Send appointment reminders as they come due
+            reminders::start();
+
             // Auto-start server if configured as host
             let _app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
@@ -1183,9 +1440,15 @@ pub fn run() {
                                 };
                                 let node_name = config.node_name.clone();
                                 let port = host_config.api_port;
+                                let fingerprint = tls::advertised_fingerprint(&host_config)
+                                    .unwrap_or_else(|e| {
+                                        log::error!("Failed to load TLS certificate: {}", e);
+                                        None
+                                    });
                                 tauri::async_runtime::spawn(async move {
-                                    if let Err(e) =
-                                        service_clone.start_broadcasting(&node_name, port).await
+                                    if let Err(e) = service_clone
+                                        .start_broadcasting(&node_name, port, fingerprint.as_deref())
+                                        .await
                                     {
                                         log::error!("Failed to start node broadcasting: {}", e);
                                     }
@@ -1203,6 +1466,11 @@ pub fn run() {
                                     log::error!("Failed to start node discovery: {}", e);
                                 }
                             });
+
+                            // Follow changes made by other nodes on the host
+                            if let Some(client_config) = config.client_config {
+                                events::start_remote_listener(client_config);
+                            }
                         }
                     }
                     Err(e) => {
@@ -1267,6 +1535,7 @@ pub fn run() {
             wizard::update_user_password,
             wizard::delete_user,
             wizard::verify_system_password,
+            wizard::has_system_password,
             wizard::set_system_password,
             wizard::wipe_system,
             config::get_config_schema,
@@ -1282,6 +1551,9 @@ pub fn run() {
             session::remove_user_pin,
             session::update_user_preferences,
             session::logout_user,
+            session::revoke_user_sessions,
+            session::rotate_jwt_secret,
+            session::get_auth_audit_log,
             session::get_current_user,
             session::get_current_session_info,
             session::verify_session,
@@ -1373,8 +1645,19 @@ pub fn run() {
             get_appointment,
             list_appointments,
             get_pending_reminders,
+            get_reminder_notification,
+            send_test_patient_reminder,
             mark_reminder_sent,
             get_upcoming_appointments,
+            update_appointment_occurrence,
+            cancel_appointment_occurrence,
+            // agenda resources
+            list_agenda_resources,
+            get_agenda_resource,
+            create_agenda_resource,
+            update_agenda_resource,
+            delete_agenda_resource,
+            find_free_slots,
             // licensing
             activate_license,
             validate_license,
@@ -1389,6 +1672,14 @@ pub fn run() {
             stop_api_server,
             is_api_server_running,
             test_remote_connection,
+            // node pairing
+            list_pairing_requests,
+            approve_pairing_request,
+            reject_pairing_request,
+            list_paired_nodes,
+            revoke_paired_node,
+            request_node_pairing,
+            complete_node_pairing,
             // node discovery
             start_node_broadcast,
             start_node_discovery,
@@ -1424,16 +1715,42 @@ pub fn run() {
             filesystem::commands::fs_rename_entry,
             filesystem::commands::fs_move_entry,
             filesystem::commands::fs_upload_file,
+            filesystem::commands::fs_begin_upload,
+            filesystem::commands::fs_begin_overwrite,
+            filesystem::commands::fs_upload_chunk,
+            filesystem::commands::fs_finish_upload,
+            filesystem::commands::fs_cancel_upload,
             filesystem::commands::fs_download_file,
+            filesystem::commands::fs_download_chunk,
+            filesystem::commands::fs_list_versions,
+            filesystem::commands::fs_download_version,
+            filesystem::commands::fs_restore_version,
             filesystem::commands::fs_get_metadata,
+            filesystem::commands::fs_get_thumbnail,
+            filesystem::commands::fs_get_dicom_tags,
             filesystem::commands::fs_search,
             filesystem::commands::fs_get_storage_quota,
+            filesystem::commands::fs_set_storage_quota,
+            filesystem::commands::fs_get_encryption_status,
+            filesystem::commands::fs_setup_encryption,
+            filesystem::commands::fs_unlock_encryption,
+            filesystem::commands::fs_rotate_encryption_key,
+            filesystem::commands::fs_change_encryption_key_source,
             filesystem::commands::fs_link_to_patient,
+            filesystem::commands::fs_unlink_from_patient,
             filesystem::commands::fs_get_patient_files,
             filesystem::commands::fs_get_audit_logs,
+            filesystem::commands::fs_export_audit_logs,
+            filesystem::commands::fs_grant_access,
+            filesystem::commands::fs_revoke_access,
             filesystem::commands::fs_move_to_trash,
+            filesystem::commands::fs_list_trash,
             filesystem::commands::fs_restore_from_trash,
             filesystem::commands::fs_empty_trash,
+            filesystem::commands::fs_acquire_lock,
+            filesystem::commands::fs_renew_lock,
+            filesystem::commands::fs_release_lock,
+            filesystem::commands::fs_force_unlock,
             filesystem::commands::fs_get_file_lock_status,
             // database explorer
             db_explorer_list_tables,
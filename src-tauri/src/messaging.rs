@@ -0,0 +1,506 @@
+// Outbound Patient Messaging
+// Sends patient-facing messages by email over SMTP or as SMS/WhatsApp through an HTTP
+// gateway. Connection settings come from the configuration schema (`smtp*` and
+// `messageGateway*` keys) so a node can point them at a local SMTP sink or mock server.
+
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Configuration key selecting the channel of patient reminders
+pub const CHANNEL_CONFIG_KEY: &str = "patientRemindersChannel";
+
+/// Time allowed to deliver one message
+const SEND_TIMEOUT_SECS: u64 = 30;
+
+/// How a message reaches the patient
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Email,
+    Sms,
+    WhatsApp,
+}
+
+impl Channel {
+    /// Identifier used in configuration values and recorded notification IDs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Channel::Email => "email",
+            Channel::Sms => "sms",
+            Channel::WhatsApp => "whatsapp",
+        }
+    }
+
+    /// Channel configured for patient reminders; `None` when they are disabled
+    pub fn configured() -> Option<Self> {
+        match config_string(CHANNEL_CONFIG_KEY)?.as_str() {
+            "email" => Some(Channel::Email),
+            "sms" => Some(Channel::Sms),
+            "whatsapp" => Some(Channel::WhatsApp),
+            _ => None,
+        }
+    }
+}
+
+/// A message ready to send
+#[derive(Debug, Clone)]
+pub struct OutgoingMessage {
+    /// Email address or phone number, depending on the channel
+    pub to: String,
+    /// Email subject; gateways ignore it
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+/// Delivers messages over one channel
+pub trait MessageSender {
+    fn channel(&self) -> Channel;
+
+    /// Send a message; returns the ID the provider assigned to it
+    fn send(&self, message: &OutgoingMessage) -> Result<String, String>;
+}
+
+/// Sender for a channel, built from the current configuration
+pub fn sender_for(channel: Channel) -> Result<Box<dyn MessageSender>, String> {
+    match channel {
+        Channel::Email => Ok(Box::new(SmtpSender::new(SmtpSettings::from_config()?))),
+        Channel::Sms | Channel::WhatsApp => Ok(Box::new(GatewaySender::new(
+            channel,
+            GatewaySettings::from_config()?,
+        ))),
+    }
+}
+
+// ===== SMTP =====
+
+/// How the SMTP connection is secured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS (usually port 587)
+    StartTls,
+    /// TLS from the start (usually port 465)
+    Tls,
+    /// No encryption, only for local relays and test sinks
+    None,
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Sender mailbox, e.g. `Clínica <turnos@clinica.com>`
+    pub from: String,
+}
+
+impl SmtpSettings {
+    pub fn from_config() -> Result<Self, String> {
+        let host = config_string("smtpHost").ok_or("SMTP host is not configured")?;
+        let from = config_string("smtpFrom").ok_or("SMTP sender address is not configured")?;
+        let port = crate::config::current_value("smtpPort")
+            .and_then(|value| value.as_u64())
+            .and_then(|port| u16::try_from(port).ok())
+            .unwrap_or(587);
+        let security = match config_string("smtpSecurity").as_deref() {
+            Some("tls") => SmtpSecurity::Tls,
+            Some("none") => SmtpSecurity::None,
+            _ => SmtpSecurity::StartTls,
+        };
+
+        Ok(Self {
+            host,
+            port,
+            security,
+            username: config_string("smtpUsername"),
+            password: config_string("smtpPassword"),
+            from,
+        })
+    }
+}
+
+pub struct SmtpSender {
+    settings: SmtpSettings,
+}
+
+impl SmtpSender {
+    pub fn new(settings: SmtpSettings) -> Self {
+        Self { settings }
+    }
+
+    fn transport(&self) -> Result<SmtpTransport, String> {
+        let settings = &self.settings;
+        let builder = match settings.security {
+            SmtpSecurity::StartTls => SmtpTransport::starttls_relay(&settings.host)
+                .map_err(|e| format!("Invalid SMTP host: {}", e))?,
+            SmtpSecurity::Tls => SmtpTransport::relay(&settings.host)
+                .map_err(|e| format!("Invalid SMTP host: {}", e))?,
+            SmtpSecurity::None => SmtpTransport::builder_dangerous(&settings.host),
+        };
+        let mut builder = builder
+            .port(settings.port)
+            .timeout(Some(Duration::from_secs(SEND_TIMEOUT_SECS)));
+
+        if let Some(username) = &settings.username {
+            builder = builder.credentials(Credentials::new(
+                username.clone(),
+                settings.password.clone().unwrap_or_default(),
+            ));
+        }
+
+        Ok(builder.build())
+    }
+}
+
+impl MessageSender for SmtpSender {
+    fn channel(&self) -> Channel {
+        Channel::Email
+    }
+
+    fn send(&self, message: &OutgoingMessage) -> Result<String, String> {
+        let from: Mailbox = self
+            .settings
+            .from
+            .parse()
+            .map_err(|e| format!("Invalid sender address: {}", e))?;
+        let to: Mailbox = message
+            .to
+            .parse()
+            .map_err(|e| format!("Invalid recipient address: {}", e))?;
+        let message_id = format!("<{}@{}>", uuid::Uuid::new_v4(), from.email.domain());
+
+        let email = Message::builder()
+            .from(from)
+            .to(to)
+            .subject(message.subject.clone())
+            .message_id(Some(message_id.clone()))
+            .multipart(MultiPart::alternative_plain_html(
+                message.text.clone(),
+                message.html.clone(),
+            ))
+            .map_err(|e| format!("Failed to build email: {}", e))?;
+
+        self.transport()?
+            .send(&email)
+            .map_err(|e| format!("SMTP delivery failed: {}", e))?;
+
+        Ok(message_id)
+    }
+}
+
+// ===== HTTP GATEWAY =====
+
+#[derive(Debug, Clone)]
+pub struct GatewaySettings {
+    /// Endpoint receiving `POST {channel, to, message, reference}` as JSON
+    pub url: String,
+    /// Sent as a bearer token when set
+    pub token: Option<String>,
+}
+
+impl GatewaySettings {
+    pub fn from_config() -> Result<Self, String> {
+        Ok(Self {
+            url: config_string("messageGatewayUrl")
+                .ok_or("Message gateway URL is not configured")?,
+            token: config_string("messageGatewayToken"),
+        })
+    }
+}
+
+/// Adapter for SMS/WhatsApp providers behind a JSON HTTP endpoint
+pub struct GatewaySender {
+    channel: Channel,
+    settings: GatewaySettings,
+}
+
+impl GatewaySender {
+    pub fn new(channel: Channel, settings: GatewaySettings) -> Self {
+        Self { channel, settings }
+    }
+}
+
+impl MessageSender for GatewaySender {
+    fn channel(&self) -> Channel {
+        self.channel
+    }
+
+    /// The gateway's `id`, `messageId` or `sid` in the response, or the reference sent
+    /// with the message when it returns none
+    fn send(&self, message: &OutgoingMessage) -> Result<String, String> {
+        let reference = uuid::Uuid::new_v4().to_string();
+        let client = Client::builder()
+            .timeout(Duration::from_secs(SEND_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| format!("Failed to create client: {}", e))?;
+
+        let mut request = client.post(&self.settings.url).json(&json!({
+            "channel": self.channel.as_str(),
+            "to": message.to,
+            "message": message.text,
+            "reference": reference,
+        }));
+        if let Some(token) = &self.settings.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .map_err(|e| format!("Gateway request failed: {}", e))?;
+        let status = response.status();
+        let body = response.text().unwrap_or_default();
+        if !status.is_success() {
+            return Err(format!(
+                "Gateway responded with status {}: {}",
+                status, body
+            ));
+        }
+
+        let provider_id = serde_json::from_str::<Value>(&body).ok().and_then(|value| {
+            ["id", "messageId", "sid"]
+                .iter()
+                .find_map(|field| match value.get(field)? {
+                    Value::String(id) if !id.is_empty() => Some(id.clone()),
+                    Value::Number(id) => Some(id.to_string()),
+                    _ => None,
+                })
+        });
+
+        Ok(provider_id.unwrap_or(reference))
+    }
+}
+
+// ===== CONTENT =====
+
+/// Escape a value inserted into HTML content
+pub fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Plain-text version of HTML content for SMS, WhatsApp and the text part of emails
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_lowercase();
+        let name = tag
+            .trim_start_matches('/')
+            .split([' ', '/'])
+            .next()
+            .unwrap_or_default();
+        if matches!(
+            name,
+            "br" | "p" | "div" | "li" | "tr" | "h1" | "h2" | "h3" | "h4"
+        ) {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if !line.is_empty() || lines.last().is_some_and(|last| !last.is_empty()) {
+            lines.push(line);
+        }
+    }
+    lines.join("\n").trim().to_string()
+}
+
+/// Current value of a text setting; `None` when it is unset or blank
+pub fn config_string(key: &str) -> Option<String> {
+    crate::config::current_value(key)?
+        .as_str()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    fn message(to: &str) -> OutgoingMessage {
+        OutgoingMessage {
+            to: to.to_string(),
+            subject: "Recordatorio: Control".to_string(),
+            html: "<p>Hola Ana &amp; Luis,</p><p>su turno es el <b>martes</b>.</p>".to_string(),
+            text: "Hola Ana & Luis,\nsu turno es el martes.".to_string(),
+        }
+    }
+
+    /// Minimal SMTP sink: accepts one message and returns what was sent after DATA
+    fn smtp_sink() -> (u16, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut data = String::new();
+            let mut in_data = false;
+
+            writer.write_all(b"220 sink ESMTP\r\n").unwrap();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                if in_data {
+                    if line == ".\r\n" {
+                        in_data = false;
+                        writer.write_all(b"250 queued\r\n").unwrap();
+                    } else {
+                        data.push_str(&line);
+                    }
+                    continue;
+                }
+                let command = line.to_uppercase();
+                if command.starts_with("EHLO") {
+                    writer.write_all(b"250-sink\r\n250 8BITMIME\r\n").unwrap();
+                } else if command.starts_with("DATA") {
+                    in_data = true;
+                    writer.write_all(b"354 go ahead\r\n").unwrap();
+                } else if command.starts_with("QUIT") {
+                    writer.write_all(b"221 bye\r\n").unwrap();
+                    break;
+                } else {
+                    writer.write_all(b"250 ok\r\n").unwrap();
+                }
+            }
+            data
+        });
+
+        (port, handle)
+    }
+
+    /// Mock gateway: answers one request with `response` and returns the request
+    fn mock_gateway(response: &'static str) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/messages", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let request = read_http_request(&stream);
+            let mut stream = stream;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .unwrap();
+            request
+        });
+
+        (url, handle)
+    }
+
+    fn read_http_request(stream: &TcpStream) -> String {
+        let mut reader = BufReader::new(stream);
+        let mut head = String::new();
+        let mut length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                length = value.trim().parse().unwrap();
+            }
+            head.push_str(&line);
+            if line == "\r\n" {
+                break;
+            }
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        head + &String::from_utf8(body).unwrap()
+    }
+
+    #[test]
+    fn sends_email_to_smtp_sink() {
+        let (port, sink) = smtp_sink();
+        let sender = SmtpSender::new(SmtpSettings {
+            host: "127.0.0.1".to_string(),
+            port,
+            security: SmtpSecurity::None,
+            username: None,
+            password: None,
+            from: "Clínica <turnos@clinica.test>".to_string(),
+        });
+
+        let message_id = sender.send(&message("ana@example.com")).unwrap();
+        assert!(message_id.ends_with("@clinica.test>"));
+
+        let data = sink.join().unwrap();
+        assert!(data.contains(&format!("Message-ID: {}", message_id)));
+        assert!(data.contains("To: ana@example.com"));
+        assert!(data.contains("Hola Ana & Luis,"));
+        assert!(data.contains("text/html"));
+    }
+
+    #[test]
+    fn sends_through_http_gateway() {
+        let (url, gateway) = mock_gateway(r#"{"sid":"SM123"}"#);
+        let sender = GatewaySender::new(
+            Channel::WhatsApp,
+            GatewaySettings {
+                url,
+                token: Some("secreto".to_string()),
+            },
+        );
+
+        assert_eq!(sender.send(&message("+5491155550000")).unwrap(), "SM123");
+
+        let request = gateway.join().unwrap();
+        assert!(request.starts_with("POST /messages"));
+        assert!(request
+            .to_lowercase()
+            .contains("authorization: bearer secreto"));
+        let body: Value = serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(body["channel"], "whatsapp");
+        assert_eq!(body["to"], "+5491155550000");
+        assert_eq!(body["message"], "Hola Ana & Luis,\nsu turno es el martes.");
+    }
+
+    #[test]
+    fn converts_html_to_text() {
+        let html = "<p>Hola <b>Ana</b> &amp; Luis,</p><p></p><p>Turno:<br>martes 10:00</p>";
+        assert_eq!(
+            html_to_text(html),
+            "Hola Ana & Luis,\n\nTurno:\nmartes 10:00"
+        );
+        assert_eq!(html_to_text("Sin etiquetas"), "Sin etiquetas");
+        assert_eq!(
+            escape_html("<b>\"A&B\"</b>"),
+            "&lt;b&gt;&quot;A&amp;B&quot;&lt;/b&gt;"
+        );
+    }
+}
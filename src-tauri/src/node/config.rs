@@ -1,21 +1,37 @@
 // Node Configuration Persistence
 // Stores and retrieves node configuration from the database
+// Loaded configurations are cached: every repository-backed command reads it, and it only
+// changes through `save_node_config` or a system wipe.
 
 use super::{ClientConfig, HostConfig, NodeConfig, NodeMode};
 use crate::db;
+use once_cell::sync::Lazy;
 use rusqlite::{params, Connection};
 use rusqlite::OptionalExtension;
+use std::sync::Mutex;
 
 const CONFIG_KEY_NODE_MODE: &str = "node.mode";
 const CONFIG_KEY_NODE_NAME: &str = "node.name";
 const CONFIG_KEY_HOST_PORT: &str = "node.host.api_port";
 const CONFIG_KEY_HOST_TOKEN: &str = "node.host.api_token";
 const CONFIG_KEY_HOST_CORS: &str = "node.host.enable_cors";
+const CONFIG_KEY_HOST_TLS: &str = "node.host.enable_tls";
+const CONFIG_KEY_HOST_RATE_LIMIT: &str = "node.host.rate_limit_per_minute";
 const CONFIG_KEY_CLIENT_URL: &str = "node.client.remote_url";
 const CONFIG_KEY_CLIENT_TOKEN: &str = "node.client.auth_token";
+const CONFIG_KEY_CLIENT_FINGERPRINT: &str = "node.client.pinned_fingerprint";
+
+/// Configuration as last read from the database
+static CACHED_CONFIG: Lazy<Mutex<Option<NodeConfig>>> = Lazy::new(|| Mutex::new(None));
 
 /// Save node configuration to database
 pub fn save_node_config(config: &NodeConfig) -> Result<(), String> {
+    let result = write_node_config(config);
+    invalidate_cache();
+    result
+}
+
+fn write_node_config(config: &NodeConfig) -> Result<(), String> {
     let conn = db::get_connection()?;
 
     // Save mode
@@ -33,19 +49,50 @@ pub fn save_node_config(config: &NodeConfig) -> Result<(), String> {
             CONFIG_KEY_HOST_CORS,
             &host_config.enable_cors.to_string(),
         )?;
+        save_config_value(
+            &conn,
+            CONFIG_KEY_HOST_TLS,
+            &host_config.enable_tls.to_string(),
+        )?;
+        save_config_value(
+            &conn,
+            CONFIG_KEY_HOST_RATE_LIMIT,
+            &host_config.rate_limit_per_minute.to_string(),
+        )?;
     }
 
     // Save client config if present
     if let Some(client_config) = &config.client_config {
         save_config_value(&conn, CONFIG_KEY_CLIENT_URL, &client_config.remote_url)?;
         save_config_value(&conn, CONFIG_KEY_CLIENT_TOKEN, &client_config.auth_token)?;
+        save_config_value(
+            &conn,
+            CONFIG_KEY_CLIENT_FINGERPRINT,
+            client_config.pinned_fingerprint.as_deref().unwrap_or_default(),
+        )?;
     }
 
     Ok(())
 }
 
-/// Load node configuration from database
+/// Forget the cached configuration so the next load reads the database again
+pub fn invalidate_cache() {
+    *CACHED_CONFIG.lock().unwrap() = None;
+}
+
+/// Load node configuration, from the cache when it was already read
 pub fn load_node_config() -> Result<NodeConfig, String> {
+    if let Some(config) = CACHED_CONFIG.lock().unwrap().as_ref() {
+        return Ok(config.clone());
+    }
+
+    let config = read_node_config()?;
+    *CACHED_CONFIG.lock().unwrap() = Some(config.clone());
+    Ok(config)
+}
+
+/// Load node configuration from database
+fn read_node_config() -> Result<NodeConfig, String> {
     let conn = db::get_connection()?;
 
     let mode = get_config_value(&conn, CONFIG_KEY_NODE_MODE)?
@@ -69,6 +116,12 @@ pub fn load_node_config() -> Result<NodeConfig, String> {
             enable_cors: get_config_value(&conn, CONFIG_KEY_HOST_CORS)?
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(true),
+            enable_tls: get_config_value(&conn, CONFIG_KEY_HOST_TLS)?
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(false),
+            rate_limit_per_minute: get_config_value(&conn, CONFIG_KEY_HOST_RATE_LIMIT)?
+                .and_then(|s| s.parse().ok())
+                .unwrap_or_else(|| HostConfig::default().rate_limit_per_minute),
         })
     } else {
         None
@@ -78,6 +131,8 @@ pub fn load_node_config() -> Result<NodeConfig, String> {
         Some(ClientConfig {
             remote_url: get_config_value(&conn, CONFIG_KEY_CLIENT_URL)?.unwrap_or_default(),
             auth_token: get_config_value(&conn, CONFIG_KEY_CLIENT_TOKEN)?.unwrap_or_default(),
+            pinned_fingerprint: get_config_value(&conn, CONFIG_KEY_CLIENT_FINGERPRINT)?
+                .filter(|s| !s.is_empty()),
         })
     } else {
         None
@@ -2,6 +2,7 @@
 // Manages the multi-node architecture configuration
 
 pub mod config;
+pub mod pairing;
 
 use serde::{Deserialize, Serialize};
 
@@ -55,6 +56,16 @@ pub struct HostConfig {
     pub api_token: String,
     /// Enable CORS
     pub enable_cors: bool,
+    /// Serve HTTPS with the self-signed certificate from the app data dir
+    #[serde(default)]
+    pub enable_tls: bool,
+    /// Requests per minute accepted by the API from all clients together (0 disables the limit)
+    #[serde(default = "default_rate_limit_per_minute")]
+    pub rate_limit_per_minute: u32,
+}
+
+fn default_rate_limit_per_minute() -> u32 {
+    600
 }
 
 impl Default for HostConfig {
@@ -63,6 +74,8 @@ impl Default for HostConfig {
             api_port: 3000,
             api_token: String::new(),
             enable_cors: true,
+            enable_tls: false,
+            rate_limit_per_minute: default_rate_limit_per_minute(),
         }
     }
 }
@@ -74,6 +87,9 @@ pub struct ClientConfig {
     pub remote_url: String,
     /// Authentication token for remote host
     pub auth_token: String,
+    /// SHA-256 fingerprint of the host certificate; HTTPS connections only trust this certificate
+    #[serde(default)]
+    pub pinned_fingerprint: Option<String>,
 }
 
 impl Default for ClientConfig {
@@ -81,6 +97,7 @@ impl Default for ClientConfig {
         Self {
             remote_url: String::new(),
             auth_token: String::new(),
+            pinned_fingerprint: None,
         }
     }
 }
@@ -0,0 +1,94 @@
+// Client side of node pairing
+// Asks a host to pair, polls until the host operator decides, and on approval switches
+// this node to Client mode with the credential issued by the host.
+
+use std::time::Duration;
+
+use super::config::{load_node_config, save_node_config};
+use super::{ClientConfig, NodeMode};
+use crate::services::pairing::{PairingStatus, PairingTicket};
+
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// Ask the host at `remote_url` to pair with this node
+pub async fn request_pairing(
+    remote_url: &str,
+    pinned_fingerprint: Option<&str>,
+    node_name: &str,
+) -> Result<PairingTicket, String> {
+    let response = http_client(pinned_fingerprint)?
+        .post(format!("{}/api/pairing/requests", base_url(remote_url)))
+        .json(&serde_json::json!({ "node_name": node_name }))
+        .send()
+        .await
+        .map_err(|e| format!("Error de conexión con el host: {}", e))?;
+
+    parse_response(response).await
+}
+
+/// Check a pairing request; once approved, store the credential and switch to Client mode
+pub async fn complete_pairing(
+    remote_url: &str,
+    pinned_fingerprint: Option<&str>,
+    ticket: &PairingTicket,
+) -> Result<PairingStatus, String> {
+    let response = http_client(pinned_fingerprint)?
+        .get(format!(
+            "{}/api/pairing/requests/{}",
+            base_url(remote_url),
+            ticket.request_id
+        ))
+        .header("X-Pairing-Token", &ticket.poll_token)
+        .send()
+        .await
+        .map_err(|e| format!("Error de conexión con el host: {}", e))?;
+
+    let status: PairingStatus = parse_response(response).await?;
+
+    if let PairingStatus::Approved { credential, .. } = &status {
+        let mut config = load_node_config()?;
+        config.mode = NodeMode::Client;
+        config.client_config = Some(ClientConfig {
+            remote_url: base_url(remote_url).to_string(),
+            auth_token: credential.clone(),
+            pinned_fingerprint: pinned_fingerprint
+                .filter(|f| !f.is_empty())
+                .map(str::to_string),
+        });
+        save_node_config(&config)?;
+    }
+
+    Ok(status)
+}
+
+fn base_url(remote_url: &str) -> &str {
+    remote_url.trim_end_matches('/')
+}
+
+fn http_client(pinned_fingerprint: Option<&str>) -> Result<reqwest::Client, String> {
+    crate::tls::client_builder(pinned_fingerprint)
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("Failed to create client: {}", e))
+}
+
+/// Decode a successful response, or surface the host's error message
+async fn parse_response<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+) -> Result<T, String> {
+    let status = response.status();
+
+    if !status.is_success() {
+        let body: serde_json::Value = response.json().await.unwrap_or_default();
+        return Err(body
+            .get("message")
+            .and_then(|message| message.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Server responded with status: {}", status)));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Respuesta inválida del host: {}", e))
+}
@@ -0,0 +1,120 @@
+// Password and PIN hashing
+// New secrets are stored as salted Argon2id PHC strings. Older installs stored
+// unsalted SHA-256 hex digests (passwords) or plain text (PINs); those are still
+// accepted once and reported as needing a rehash so callers can upgrade them.
+
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use rand_core::OsRng;
+use sha2::{Digest, Sha256};
+
+/// Result of checking a secret against its stored hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verification {
+    /// Matches an Argon2id hash
+    Valid,
+    /// Matches a legacy hash; the caller should store `hash_secret` of it
+    ValidLegacy,
+    Invalid,
+}
+
+/// Hash a password or PIN with Argon2id and a random salt
+pub fn hash_secret(secret: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(secret.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| format!("Error generando hash: {}", e))
+}
+
+/// Verify a password against a stored Argon2id or legacy SHA-256 hash
+pub fn verify_password(password: &str, stored: &str) -> Verification {
+    verify(password, stored, |password, stored| {
+        constant_time_eq(sha256_hex(password).as_bytes(), stored.as_bytes())
+    })
+}
+
+/// Verify a PIN against a stored Argon2id hash or legacy plain-text PIN
+pub fn verify_pin(pin: &str, stored: &str) -> Verification {
+    verify(pin, stored, |pin, stored| {
+        constant_time_eq(pin.as_bytes(), stored.as_bytes())
+    })
+}
+
+fn verify(secret: &str, stored: &str, legacy_matches: fn(&str, &str) -> bool) -> Verification {
+    if is_argon2_hash(stored) {
+        let Ok(parsed) = PasswordHash::new(stored) else {
+            return Verification::Invalid;
+        };
+
+        return match Argon2::default().verify_password(secret.as_bytes(), &parsed) {
+            Ok(()) => Verification::Valid,
+            Err(_) => Verification::Invalid,
+        };
+    }
+
+    if !stored.is_empty() && legacy_matches(secret, stored) {
+        Verification::ValidLegacy
+    } else {
+        Verification::Invalid
+    }
+}
+
+fn is_argon2_hash(stored: &str) -> bool {
+    stored.starts_with("$argon2")
+}
+
+fn sha256_hex(value: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(value.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn argon2_roundtrip() {
+        let hash = hash_secret("test123").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert_eq!(verify_password("test123", &hash), Verification::Valid);
+        assert_eq!(verify_password("different", &hash), Verification::Invalid);
+    }
+
+    #[test]
+    fn hashes_are_salted() {
+        assert_ne!(hash_secret("test123").unwrap(), hash_secret("test123").unwrap());
+    }
+
+    #[test]
+    fn legacy_sha256_password_needs_rehash() {
+        let legacy = sha256_hex("test123");
+        assert_eq!(verify_password("test123", &legacy), Verification::ValidLegacy);
+        assert_eq!(verify_password("different", &legacy), Verification::Invalid);
+        // The legacy digest itself must not work as a password
+        assert_eq!(verify_password(&legacy, &legacy), Verification::Invalid);
+    }
+
+    #[test]
+    fn legacy_plain_pin_needs_rehash() {
+        assert_eq!(verify_pin("1234", "1234"), Verification::ValidLegacy);
+        assert_eq!(verify_pin("4321", "1234"), Verification::Invalid);
+
+        let hash = hash_secret("1234").unwrap();
+        assert_eq!(verify_pin("1234", &hash), Verification::Valid);
+    }
+
+    #[test]
+    fn empty_stored_hash_never_matches() {
+        assert_eq!(verify_password("", ""), Verification::Invalid);
+        assert_eq!(verify_pin("", ""), Verification::Invalid);
+    }
+}
@@ -0,0 +1,279 @@
+// Appointment Reminder Dispatcher
+// Background task that fires the reminders created from `reminder_minutes`. Reminders are
+// rows in the database, so the ones that came due while the app was closed fire on the next
+// start. A due reminder is sent to the patient when patient reminders are configured, runs
+// the `appointment:reminder` integration flows and publishes an `appointment.reminder`
+// change event, which every node's frontend turns into a desktop notification. Failed sends
+// are retried with exponential backoff.
+// Client nodes skip the dispatch: their host owns the database and sends the reminders.
+
+use chrono::{DateTime, Duration as ChronoDuration, Local, Utc};
+use rusqlite::Connection;
+use serde_json::json;
+use std::time::Duration;
+
+use crate::db::appointments::{
+    self as db_appointments, AppointmentReminder, AppointmentWithPatient, ReminderNotification,
+};
+use crate::db::templates::{self, Template};
+use crate::messaging::{self, OutgoingMessage};
+use crate::{db, events, integrations, node};
+
+/// How often due reminders are looked up
+pub const DISPATCH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Integration event run for every reminder sent
+pub const INTEGRATION_EVENT: &str = "appointment:reminder";
+
+/// Change event published for every reminder sent; its ID is the reminder's
+pub const CHANGE_EVENT: &str = "appointment.reminder";
+
+/// How far ahead occurrences of recurring series get their reminder rows
+const SCHEDULE_HORIZON_DAYS: i64 = 7;
+
+/// Delay before the first retry; it doubles on every failed attempt
+const RETRY_BASE_SECS: i64 = 60;
+
+/// Type of the `db::templates` entries used for patient reminders
+pub const TEMPLATE_TYPE: &str = "reminder";
+
+/// Configuration key of the patient reminder template (0 uses the default reminder template)
+const TEMPLATE_CONFIG_KEY: &str = "patientReminderTemplateId";
+
+/// Configuration key of the email subject, which takes the same variables as the template
+const SUBJECT_CONFIG_KEY: &str = "patientReminderSubject";
+
+/// Message sent when there is no reminder template
+const DEFAULT_TEMPLATE: &str = "<p>Hola {{patient_name}},</p><p>Le recordamos su cita \
+    \"{{appointment_title}}\" el {{appointment_date}} a las {{appointment_time}}.</p>";
+
+const DEFAULT_SUBJECT: &str = "Recordatorio: {{appointment_title}}";
+
+/// Start the dispatcher loop on the async runtime
+pub fn start() {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(DISPATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+            match tauri::async_runtime::spawn_blocking(dispatch_due).await {
+                Ok(Ok(0)) => {}
+                Ok(Ok(sent)) => log::info!("Sent {} appointment reminders", sent),
+                Ok(Err(e)) => log::warn!("Failed to dispatch appointment reminders: {}", e),
+                Err(e) => log::warn!("Appointment reminder dispatch panicked: {}", e),
+            }
+        }
+    });
+}
+
+/// Schedule upcoming series reminders and send the due ones; returns how many were sent
+pub fn dispatch_due() -> Result<usize, String> {
+    let client_mode = node::config::load_node_config()
+        .map(|config| config.mode == node::NodeMode::Client)
+        .unwrap_or(false);
+    if client_mode {
+        return Ok(0);
+    }
+
+    let conn = db::get_connection()?;
+    let now = Utc::now();
+    db_appointments::schedule_series_reminders(
+        &conn,
+        now,
+        now + ChronoDuration::days(SCHEDULE_HORIZON_DAYS),
+    )?;
+
+    let mut sent = 0;
+    for reminder in db_appointments::get_pending_reminders(&conn)? {
+        let id = reminder.id.unwrap_or_default();
+        match dispatch(&conn, &reminder) {
+            Ok(true) => sent += 1,
+            Ok(false) => {}
+            Err(e) => {
+                log::warn!("Failed to send reminder {}: {}", id, e);
+                db_appointments::record_reminder_failure(&conn, id, &e, next_attempt(&reminder))?;
+            }
+        }
+    }
+
+    Ok(sent)
+}
+
+/// Send one reminder; returns `false` when it was discarded instead
+fn dispatch(conn: &Connection, reminder: &AppointmentReminder) -> Result<bool, String> {
+    let id = reminder.id.unwrap_or_default();
+
+    let Some(notification) = db_appointments::get_reminder_notification(conn, id)? else {
+        db_appointments::discard_reminder(conn, id, "La cita cambió o fue cancelada")?;
+        return Ok(false);
+    };
+
+    let start = DateTime::parse_from_rfc3339(&notification.appointment.appointment.start_time)
+        .map_err(|e| format!("Invalid appointment start: {}", e))?;
+    if start <= Utc::now() {
+        db_appointments::discard_reminder(conn, id, "La cita ya comenzó")?;
+        return Ok(false);
+    }
+
+    // The patient message goes out once: a retry after a later failure finds its ID recorded.
+    // On the last attempt a failing patient message no longer holds back the staff notification.
+    let notification_id = match reminder.notification_id.clone() {
+        Some(notification_id) => notification_id,
+        None => {
+            let delivered = match send_to_patient(conn, &notification) {
+                Ok(delivered) => delivered,
+                Err(e) if reminder.attempts + 1 < db_appointments::MAX_REMINDER_ATTEMPTS => {
+                    return Err(e)
+                }
+                Err(e) => {
+                    log::warn!("Giving up on the patient message of reminder {}: {}", id, e);
+                    None
+                }
+            };
+            let notification_id =
+                delivered.unwrap_or_else(|| format!("desktop:{}", uuid::Uuid::new_v4()));
+            db_appointments::set_reminder_notification_id(conn, id, &notification_id)?;
+            notification_id
+        }
+    };
+
+    integrations::trigger_event(integrations::TriggerEventInput {
+        event_type: INTEGRATION_EVENT.to_string(),
+        payload: json!({
+            "reminderId": id,
+            "notificationId": notification_id,
+            "appointmentId": reminder.appointment_id,
+            "scheduledTime": reminder.scheduled_time,
+            "appointment": notification.appointment
+        }),
+    })?;
+
+    db_appointments::mark_reminder_sent(conn, id, &notification_id)?;
+    events::publish(CHANGE_EVENT, id);
+    Ok(true)
+}
+
+/// Send the reminder to the patient over the configured channel.
+///
+/// Returns the notification ID to record (`<channel>:<provider message ID>`), or `None`
+/// when patient reminders are off or the patient has no address for the channel.
+fn send_to_patient(
+    conn: &Connection,
+    notification: &ReminderNotification,
+) -> Result<Option<String>, String> {
+    let Some(channel) = messaging::Channel::configured() else {
+        return Ok(None);
+    };
+
+    let patient_id = notification.appointment.appointment.patient_id;
+    let patient = db::patients::get_patient_by_id(patient_id)?
+        .ok_or_else(|| format!("Patient {} not found", patient_id))?;
+    let contact = match channel {
+        messaging::Channel::Email => patient.email,
+        messaging::Channel::Sms | messaging::Channel::WhatsApp => patient.phone,
+    };
+    let Some(to) = contact.filter(|contact| !contact.trim().is_empty()) else {
+        log::info!(
+            "Patient {} has no {} contact; reminder only notifies staff",
+            patient_id,
+            channel.as_str()
+        );
+        return Ok(None);
+    };
+
+    let values = template_values(&notification.appointment)?;
+    deliver(conn, channel, to.trim(), &values).map(Some)
+}
+
+/// Send a sample reminder to `to` over the configured channel to check the SMTP or gateway
+/// settings; returns the notification ID a real reminder would record
+pub fn send_test_message(to: &str) -> Result<String, String> {
+    let channel = messaging::Channel::configured().ok_or("Patient reminders are disabled")?;
+    let conn = db::get_connection()?;
+    let start = Local::now() + ChronoDuration::days(1);
+
+    let values = vec![
+        ("patient_name", "Paciente de prueba".to_string()),
+        ("appointment_title", "Cita de prueba".to_string()),
+        ("appointment_date", start.format("%d/%m/%Y").to_string()),
+        ("appointment_time", start.format("%H:%M").to_string()),
+        ("appointment_location", String::new()),
+        ("appointment_type", String::new()),
+    ];
+    deliver(&conn, channel, to.trim(), &values)
+}
+
+/// Render the reminder template with `values` and send it; returns `<channel>:<message ID>`
+fn deliver(
+    conn: &Connection,
+    channel: messaging::Channel,
+    to: &str,
+    values: &[(&str, String)],
+) -> Result<String, String> {
+    let escaped: Vec<(&str, String)> = values
+        .iter()
+        .map(|(key, value)| (*key, messaging::escape_html(value)))
+        .collect();
+    let html = match reminder_template(conn)? {
+        Some(template) => template.render(&escaped),
+        None => templates::render_content(DEFAULT_TEMPLATE, &escaped),
+    };
+    let subject =
+        messaging::config_string(SUBJECT_CONFIG_KEY).unwrap_or_else(|| DEFAULT_SUBJECT.to_string());
+
+    let sender = messaging::sender_for(channel)?;
+    let message_id = sender.send(&OutgoingMessage {
+        to: to.to_string(),
+        subject: templates::render_content(&subject, values),
+        text: messaging::html_to_text(&html),
+        html,
+    })?;
+
+    Ok(format!("{}:{}", sender.channel().as_str(), message_id))
+}
+
+/// The configured reminder template, or the default one of its type
+fn reminder_template(conn: &Connection) -> Result<Option<Template>, String> {
+    let configured = crate::config::current_value(TEMPLATE_CONFIG_KEY)
+        .and_then(|value| value.as_i64())
+        .unwrap_or(0);
+
+    if configured > 0 {
+        return templates::get_template_by_id(conn, configured)
+            .map_err(|e| format!("Failed to load reminder template: {}", e))?
+            .map(Some)
+            .ok_or_else(|| format!("Reminder template {} not found", configured));
+    }
+
+    templates::get_templates_by_type(conn, TEMPLATE_TYPE)
+        .map(|templates| templates.into_iter().next())
+        .map_err(|e| format!("Failed to load reminder template: {}", e))
+}
+
+/// Template variables of an appointment; dates and times are in the clinic's local time
+fn template_values(item: &AppointmentWithPatient) -> Result<Vec<(&'static str, String)>, String> {
+    let appointment = &item.appointment;
+    let start = DateTime::parse_from_rfc3339(&appointment.start_time)
+        .map_err(|e| format!("Invalid appointment start: {}", e))?
+        .with_timezone(&Local);
+
+    Ok(vec![
+        ("patient_name", item.patient_name.clone()),
+        ("appointment_title", appointment.title.clone()),
+        ("appointment_date", start.format("%d/%m/%Y").to_string()),
+        ("appointment_time", start.format("%H:%M").to_string()),
+        (
+            "appointment_location",
+            appointment.location.clone().unwrap_or_default(),
+        ),
+        (
+            "appointment_type",
+            appointment.appointment_type.clone().unwrap_or_default(),
+        ),
+    ])
+}
+
+/// When to retry a reminder after its current attempt failed
+fn next_attempt(reminder: &AppointmentReminder) -> chrono::DateTime<Utc> {
+    let delay = RETRY_BASE_SECS << reminder.attempts.clamp(0, 16);
+    Utc::now() + ChronoDuration::seconds(delay)
+}
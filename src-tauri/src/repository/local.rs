@@ -0,0 +1,406 @@
+// Local repository: calls the domain services against the local SQLite database
+
+use super::*;
+use crate::services::agenda_resources::AgendaResourceService;
+use crate::services::appointments::AppointmentService;
+use crate::services::odontograms::OdontogramService;
+use crate::services::patients::PatientService;
+use crate::services::payments::PaymentService;
+use crate::services::treatment_catalog::TreatmentCatalogService;
+use crate::services::treatments::TreatmentService;
+use crate::services::ServiceResult;
+
+/// Repository backed by the local database (Standalone and Host modes)
+pub struct LocalRepository;
+
+/// Convert a service result to the `Result<_, String>` shape used by Tauri commands
+fn local<T>(result: ServiceResult<T>) -> Result<T, String> {
+    result.map_err(|e| e.to_string())
+}
+
+impl PatientRepository for LocalRepository {
+    fn get_patients(&self, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<Patient>, String> {
+        local(PatientService::new().get_all(limit, offset))
+    }
+
+    fn get_patient_by_id(&self, id: i64) -> Result<Option<Patient>, String> {
+        local(PatientService::new().get_by_id(id))
+    }
+
+    fn create_patient(&self, input: CreatePatientInput) -> Result<i64, String> {
+        local(PatientService::new().create(input))
+    }
+
+    fn update_patient(&self, id: i64, input: UpdatePatientInput) -> Result<(), String> {
+        local(PatientService::new().update(id, input))
+    }
+
+    fn delete_patient(&self, id: i64) -> Result<(), String> {
+        local(PatientService::new().delete(id))
+    }
+
+    fn search_patients(&self, query: &str) -> Result<Vec<Patient>, String> {
+        local(PatientService::new().search(query))
+    }
+
+    fn get_patients_count(&self) -> Result<i64, String> {
+        local(PatientService::new().get_count())
+    }
+}
+
+impl TreatmentRepository for LocalRepository {
+    fn get_all_treatments(
+        &self,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<Treatment>, String> {
+        local(TreatmentService::new().get_all(limit, offset))
+    }
+
+    fn get_treatment_by_id(&self, id: i64) -> Result<Option<Treatment>, String> {
+        local(TreatmentService::new().get_by_id(id))
+    }
+
+    fn get_treatments_by_patient(&self, patient_id: i64) -> Result<Vec<Treatment>, String> {
+        local(TreatmentService::new().get_by_patient(patient_id))
+    }
+
+    fn get_treatments_by_status(&self, status: &str) -> Result<Vec<Treatment>, String> {
+        local(TreatmentService::new().get_by_status(status))
+    }
+
+    fn create_treatment(&self, input: CreateTreatmentInput) -> Result<i64, String> {
+        local(TreatmentService::new().create(input))
+    }
+
+    fn update_treatment(&self, id: i64, input: UpdateTreatmentInput) -> Result<(), String> {
+        local(TreatmentService::new().update(id, input))
+    }
+
+    fn update_treatment_status(&self, id: i64, status: &str) -> Result<(), String> {
+        local(TreatmentService::new().update_status(id, status))
+    }
+
+    fn delete_treatment(&self, id: i64) -> Result<(), String> {
+        local(TreatmentService::new().delete(id))
+    }
+
+    fn get_treatment_stats(&self) -> Result<TreatmentStats, String> {
+        local(TreatmentService::new().get_stats())
+    }
+}
+
+impl PaymentRepository for LocalRepository {
+    fn get_all_payments(&self, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<Payment>, String> {
+        local(PaymentService::new().get_all(limit, offset))
+    }
+
+    fn get_payment_by_id(&self, id: i64) -> Result<Option<Payment>, String> {
+        local(PaymentService::new().get_by_id(id))
+    }
+
+    fn get_payments_by_treatment(&self, treatment_id: i64) -> Result<Vec<Payment>, String> {
+        local(PaymentService::new().get_by_treatment(treatment_id))
+    }
+
+    fn get_payments_by_patient(&self, patient_id: i64) -> Result<Vec<Payment>, String> {
+        local(PaymentService::new().get_by_patient(patient_id))
+    }
+
+    fn create_payment(&self, input: CreatePaymentInput) -> Result<i64, String> {
+        local(PaymentService::new().create(input))
+    }
+
+    fn update_payment(&self, id: i64, input: UpdatePaymentInput) -> Result<(), String> {
+        local(PaymentService::new().update(id, input))
+    }
+
+    fn delete_payment(&self, id: i64) -> Result<(), String> {
+        local(PaymentService::new().delete(id))
+    }
+
+    fn get_patient_balance(&self, patient_id: i64) -> Result<PatientBalance, String> {
+        local(PaymentService::new().get_patient_balance(patient_id))
+    }
+
+    fn get_patients_with_debt(
+        &self,
+        limit: Option<i64>,
+        offset: Option<i64>,
+        query: Option<String>,
+    ) -> Result<Vec<PatientBalance>, String> {
+        local(PaymentService::new().get_patients_with_debt(limit, offset, query))
+    }
+
+    fn get_patients_with_debt_count(&self) -> Result<i64, String> {
+        local(PaymentService::new().get_patients_with_debt_count())
+    }
+
+    fn get_patients_with_debt_summary(&self, query: Option<String>) -> Result<PatientDebtSummary, String> {
+        local(PaymentService::new().get_patients_with_debt_summary(query))
+    }
+
+    fn get_total_debt(&self) -> Result<f64, String> {
+        local(PaymentService::new().get_total_debt())
+    }
+
+    fn get_recent_payments(&self, limit: Option<i64>) -> Result<Vec<Payment>, String> {
+        local(PaymentService::new().get_recent(limit))
+    }
+}
+
+impl AppointmentRepository for LocalRepository {
+    fn create_appointment(&self, appointment: &Appointment, allow_overbooking: bool) -> Result<i64, String> {
+        local(AppointmentService::new().create(appointment, allow_overbooking))
+    }
+
+    fn update_appointment(&self, appointment: &Appointment, allow_overbooking: bool) -> Result<(), String> {
+        local(AppointmentService::new().update(appointment, allow_overbooking))
+    }
+
+    fn delete_appointment(&self, id: i64) -> Result<(), String> {
+        local(AppointmentService::new().delete(id))
+    }
+
+    fn get_appointment(&self, id: i64) -> Result<Appointment, String> {
+        local(AppointmentService::new().get_by_id(id))
+    }
+
+    fn list_appointments(&self, filter: &AppointmentFilter) -> Result<Vec<AppointmentWithPatient>, String> {
+        local(AppointmentService::new().list(filter))
+    }
+
+    fn get_pending_reminders(&self) -> Result<Vec<AppointmentReminder>, String> {
+        local(AppointmentService::new().get_pending_reminders())
+    }
+
+    fn get_reminder_notification(&self, reminder_id: i64) -> Result<Option<ReminderNotification>, String> {
+        local(AppointmentService::new().get_reminder_notification(reminder_id))
+    }
+
+    fn mark_reminder_sent(&self, reminder_id: i64, notification_id: &str) -> Result<(), String> {
+        local(AppointmentService::new().mark_reminder_sent(reminder_id, notification_id))
+    }
+
+    fn get_upcoming_appointments(&self, hours: i32) -> Result<Vec<AppointmentWithPatient>, String> {
+        local(AppointmentService::new().get_upcoming(hours))
+    }
+
+    fn update_appointment_occurrence(
+        &self,
+        series_id: i64,
+        original_start_time: &str,
+        appointment: &Appointment,
+        scope: RecurrenceScope,
+        allow_overbooking: bool,
+    ) -> Result<i64, String> {
+        local(AppointmentService::new().update_occurrence(
+            series_id,
+            original_start_time,
+            appointment,
+            scope,
+            allow_overbooking,
+        ))
+    }
+
+    fn cancel_appointment_occurrence(
+        &self,
+        series_id: i64,
+        original_start_time: &str,
+        scope: RecurrenceScope,
+    ) -> Result<i64, String> {
+        local(AppointmentService::new().cancel_occurrence(series_id, original_start_time, scope))
+    }
+}
+
+impl AgendaResourceRepository for LocalRepository {
+    fn list_agenda_resources(&self, include_inactive: bool) -> Result<Vec<AgendaResource>, String> {
+        local(AgendaResourceService::new().list(include_inactive))
+    }
+
+    fn get_agenda_resource(&self, id: i64) -> Result<Option<AgendaResource>, String> {
+        local(AgendaResourceService::new().get_by_id(id))
+    }
+
+    fn create_agenda_resource(&self, resource: &AgendaResource) -> Result<i64, String> {
+        local(AgendaResourceService::new().create(resource))
+    }
+
+    fn update_agenda_resource(&self, resource: &AgendaResource) -> Result<(), String> {
+        local(AgendaResourceService::new().update(resource))
+    }
+
+    fn delete_agenda_resource(&self, id: i64) -> Result<(), String> {
+        local(AgendaResourceService::new().delete(id))
+    }
+
+    fn find_free_slots(&self, query: &FreeSlotQuery) -> Result<Vec<FreeSlot>, String> {
+        local(AgendaResourceService::new().find_free_slots(query))
+    }
+}
+
+impl OdontogramRepository for LocalRepository {
+    fn get_odontogram_by_patient(&self, patient_id: i64) -> Result<Vec<OdontogramEntry>, String> {
+        local(OdontogramService::new().get_by_patient(patient_id))
+    }
+
+    fn get_tooth_by_patient_and_number(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> Result<Option<OdontogramEntry>, String> {
+        local(OdontogramService::new().get_tooth(patient_id, tooth_number))
+    }
+
+    fn update_tooth_condition(&self, input: UpdateToothConditionInput) -> Result<i64, String> {
+        local(OdontogramService::new().update_tooth_condition(input))
+    }
+
+    fn delete_tooth_condition(&self, patient_id: i64, tooth_number: &str) -> Result<(), String> {
+        local(OdontogramService::new().delete_tooth_condition(patient_id, tooth_number))
+    }
+
+    fn clear_patient_odontogram(&self, patient_id: i64) -> Result<(), String> {
+        local(OdontogramService::new().clear_patient_odontogram(patient_id))
+    }
+
+    fn get_tooth_history(&self, patient_id: i64, tooth_number: &str) -> Result<Vec<OdontogramEntry>, String> {
+        local(OdontogramService::new().get_tooth_history(patient_id, tooth_number))
+    }
+
+    fn get_odontogram_surfaces_by_patient(&self, patient_id: i64) -> Result<Vec<OdontogramSurface>, String> {
+        local(OdontogramService::new().get_surfaces_by_patient(patient_id))
+    }
+
+    fn get_tooth_surfaces(&self, patient_id: i64, tooth_number: &str) -> Result<Vec<OdontogramSurface>, String> {
+        local(OdontogramService::new().get_tooth_surfaces(patient_id, tooth_number))
+    }
+
+    fn update_tooth_surface(&self, input: UpdateSurfaceInput) -> Result<i64, String> {
+        local(OdontogramService::new().update_surface(input))
+    }
+
+    fn delete_tooth_surface(&self, patient_id: i64, tooth_number: &str, surface: &str) -> Result<(), String> {
+        local(OdontogramService::new().delete_surface(patient_id, tooth_number, surface))
+    }
+
+    fn clear_tooth_surfaces(&self, patient_id: i64, tooth_number: &str) -> Result<(), String> {
+        local(OdontogramService::new().clear_tooth_surfaces(patient_id, tooth_number))
+    }
+
+    fn add_tooth_surface_treatment(&self, input: AddSurfaceTreatmentInput) -> Result<i64, String> {
+        local(OdontogramService::new().add_surface_treatment(input))
+    }
+
+    fn get_surface_treatments(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+        surface: &str,
+    ) -> Result<Vec<OdontogramSurface>, String> {
+        local(OdontogramService::new().get_surface_treatments(patient_id, tooth_number, surface))
+    }
+
+    fn deactivate_surface_treatment(&self, surface_id: i64) -> Result<(), String> {
+        local(OdontogramService::new().deactivate_surface_treatment(surface_id))
+    }
+
+    fn get_surface_history(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+        surface: &str,
+    ) -> Result<Vec<SurfaceHistoryEntry>, String> {
+        local(OdontogramService::new().get_surface_history(patient_id, tooth_number, surface))
+    }
+
+    fn get_tooth_surface_history(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> Result<Vec<SurfaceHistoryEntry>, String> {
+        local(OdontogramService::new().get_tooth_surface_history(patient_id, tooth_number))
+    }
+
+    fn get_tooth_treatments(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> Result<Vec<OdontogramToothTreatment>, String> {
+        local(OdontogramService::new().get_tooth_treatments(patient_id, tooth_number))
+    }
+
+    fn get_tooth_treatments_by_patient(&self, patient_id: i64) -> Result<Vec<OdontogramToothTreatment>, String> {
+        local(OdontogramService::new().get_tooth_treatments_by_patient(patient_id))
+    }
+
+    fn add_tooth_treatment(&self, input: AddToothTreatmentInput) -> Result<i64, String> {
+        local(OdontogramService::new().add_tooth_treatment(input))
+    }
+
+    fn deactivate_tooth_treatment(&self, treatment_id: i64) -> Result<(), String> {
+        local(OdontogramService::new().deactivate_tooth_treatment(treatment_id))
+    }
+
+    fn get_tooth_treatment_history(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> Result<Vec<ToothTreatmentHistoryEntry>, String> {
+        local(OdontogramService::new().get_tooth_treatment_history(patient_id, tooth_number))
+    }
+
+    fn get_bridges_by_patient(&self, patient_id: i64) -> Result<Vec<OdontogramBridge>, String> {
+        local(OdontogramService::new().get_bridges_by_patient(patient_id))
+    }
+
+    fn add_bridge(&self, input: AddBridgeInput) -> Result<i64, String> {
+        local(OdontogramService::new().add_bridge(input))
+    }
+
+    fn deactivate_bridge(&self, bridge_id: i64) -> Result<(), String> {
+        local(OdontogramService::new().deactivate_bridge(bridge_id))
+    }
+}
+
+impl TreatmentCatalogRepository for LocalRepository {
+    fn get_all_treatment_catalog(&self) -> Result<Vec<TreatmentCatalogEntry>, String> {
+        local(TreatmentCatalogService::new().get_all())
+    }
+
+    fn get_treatment_catalog_by_id(&self, id: i64) -> Result<Option<TreatmentCatalogEntry>, String> {
+        local(TreatmentCatalogService::new().get_by_id(id))
+    }
+
+    fn create_treatment_catalog(&self, input: CreateTreatmentCatalogInput) -> Result<i64, String> {
+        local(TreatmentCatalogService::new().create(input))
+    }
+
+    fn update_treatment_catalog(&self, input: UpdateTreatmentCatalogInput) -> Result<(), String> {
+        local(TreatmentCatalogService::new().update(input))
+    }
+
+    fn delete_treatment_catalog(&self, id: i64) -> Result<(), String> {
+        local(TreatmentCatalogService::new().delete(id))
+    }
+
+    fn get_treatment_catalog_items(&self, treatment_catalog_id: i64) -> Result<Vec<TreatmentCatalogItem>, String> {
+        local(TreatmentCatalogService::new().get_items(treatment_catalog_id))
+    }
+
+    fn get_treatment_catalog_item_by_id(&self, id: i64) -> Result<Option<TreatmentCatalogItem>, String> {
+        local(TreatmentCatalogService::new().get_item_by_id(id))
+    }
+
+    fn create_treatment_catalog_item(&self, input: CreateTreatmentCatalogItemInput) -> Result<i64, String> {
+        local(TreatmentCatalogService::new().create_item(input))
+    }
+
+    fn update_treatment_catalog_item(&self, input: UpdateTreatmentCatalogItemInput) -> Result<(), String> {
+        local(TreatmentCatalogService::new().update_item(input))
+    }
+
+    fn delete_treatment_catalog_item(&self, id: i64) -> Result<(), String> {
+        local(TreatmentCatalogService::new().delete_item(id))
+    }
+}
@@ -0,0 +1,236 @@
+// Data Repository Layer
+// Tauri commands read and write clinical data through these traits instead of calling
+// the domain services directly. In Standalone/Host mode the local repository calls the
+// services (local SQLite); in Client mode every call is forwarded to the host's HTTP API.
+// Both implementations return the same `Result<_, String>` shapes, so the UI cannot tell
+// them apart. Remote calls block on the network, so commands go through `run`, which keeps
+// them off the main thread.
+
+pub mod local;
+pub mod remote;
+
+use crate::db::agenda_resources::{AgendaResource, FreeSlot, FreeSlotQuery};
+use crate::db::appointments::{
+    Appointment, AppointmentFilter, AppointmentReminder, AppointmentWithPatient, RecurrenceScope,
+    ReminderNotification,
+};
+use crate::db::odontogram_surfaces::{
+    AddSurfaceTreatmentInput, OdontogramSurface, SurfaceHistoryEntry, UpdateSurfaceInput,
+};
+use crate::db::odontogram_tooth_treatments::{
+    AddBridgeInput, AddToothTreatmentInput, OdontogramBridge, OdontogramToothTreatment,
+    ToothTreatmentHistoryEntry,
+};
+use crate::db::odontograms::{OdontogramEntry, UpdateToothConditionInput};
+use crate::db::patients::{CreatePatientInput, Patient, UpdatePatientInput};
+use crate::db::payments::{
+    CreatePaymentInput, PatientBalance, PatientDebtSummary, Payment, UpdatePaymentInput,
+};
+use crate::db::treatment_catalog::{
+    CreateTreatmentCatalogInput, CreateTreatmentCatalogItemInput, TreatmentCatalogEntry,
+    TreatmentCatalogItem, UpdateTreatmentCatalogInput, UpdateTreatmentCatalogItemInput,
+};
+use crate::db::treatments::{CreateTreatmentInput, Treatment, TreatmentStats, UpdateTreatmentInput};
+use crate::node::{config::load_node_config, NodeMode};
+
+use local::LocalRepository;
+use remote::RemoteRepository;
+
+pub trait PatientRepository {
+    fn get_patients(&self, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<Patient>, String>;
+    fn get_patient_by_id(&self, id: i64) -> Result<Option<Patient>, String>;
+    fn create_patient(&self, input: CreatePatientInput) -> Result<i64, String>;
+    fn update_patient(&self, id: i64, input: UpdatePatientInput) -> Result<(), String>;
+    fn delete_patient(&self, id: i64) -> Result<(), String>;
+    fn search_patients(&self, query: &str) -> Result<Vec<Patient>, String>;
+    fn get_patients_count(&self) -> Result<i64, String>;
+}
+
+pub trait TreatmentRepository {
+    fn get_all_treatments(
+        &self,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<Treatment>, String>;
+    fn get_treatment_by_id(&self, id: i64) -> Result<Option<Treatment>, String>;
+    fn get_treatments_by_patient(&self, patient_id: i64) -> Result<Vec<Treatment>, String>;
+    fn get_treatments_by_status(&self, status: &str) -> Result<Vec<Treatment>, String>;
+    fn create_treatment(&self, input: CreateTreatmentInput) -> Result<i64, String>;
+    fn update_treatment(&self, id: i64, input: UpdateTreatmentInput) -> Result<(), String>;
+    fn update_treatment_status(&self, id: i64, status: &str) -> Result<(), String>;
+    fn delete_treatment(&self, id: i64) -> Result<(), String>;
+    fn get_treatment_stats(&self) -> Result<TreatmentStats, String>;
+}
+
+pub trait PaymentRepository {
+    fn get_all_payments(&self, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<Payment>, String>;
+    fn get_payment_by_id(&self, id: i64) -> Result<Option<Payment>, String>;
+    fn get_payments_by_treatment(&self, treatment_id: i64) -> Result<Vec<Payment>, String>;
+    fn get_payments_by_patient(&self, patient_id: i64) -> Result<Vec<Payment>, String>;
+    fn create_payment(&self, input: CreatePaymentInput) -> Result<i64, String>;
+    fn update_payment(&self, id: i64, input: UpdatePaymentInput) -> Result<(), String>;
+    fn delete_payment(&self, id: i64) -> Result<(), String>;
+    fn get_patient_balance(&self, patient_id: i64) -> Result<PatientBalance, String>;
+    fn get_patients_with_debt(
+        &self,
+        limit: Option<i64>,
+        offset: Option<i64>,
+        query: Option<String>,
+    ) -> Result<Vec<PatientBalance>, String>;
+    fn get_patients_with_debt_count(&self) -> Result<i64, String>;
+    fn get_patients_with_debt_summary(&self, query: Option<String>) -> Result<PatientDebtSummary, String>;
+    fn get_total_debt(&self) -> Result<f64, String>;
+    fn get_recent_payments(&self, limit: Option<i64>) -> Result<Vec<Payment>, String>;
+}
+
+pub trait AppointmentRepository {
+    fn create_appointment(&self, appointment: &Appointment, allow_overbooking: bool) -> Result<i64, String>;
+    fn update_appointment(&self, appointment: &Appointment, allow_overbooking: bool) -> Result<(), String>;
+    fn delete_appointment(&self, id: i64) -> Result<(), String>;
+    fn get_appointment(&self, id: i64) -> Result<Appointment, String>;
+    fn list_appointments(&self, filter: &AppointmentFilter) -> Result<Vec<AppointmentWithPatient>, String>;
+    fn get_pending_reminders(&self) -> Result<Vec<AppointmentReminder>, String>;
+    fn get_reminder_notification(&self, reminder_id: i64) -> Result<Option<ReminderNotification>, String>;
+    fn mark_reminder_sent(&self, reminder_id: i64, notification_id: &str) -> Result<(), String>;
+    fn get_upcoming_appointments(&self, hours: i32) -> Result<Vec<AppointmentWithPatient>, String>;
+    fn update_appointment_occurrence(
+        &self,
+        series_id: i64,
+        original_start_time: &str,
+        appointment: &Appointment,
+        scope: RecurrenceScope,
+        allow_overbooking: bool,
+    ) -> Result<i64, String>;
+    fn cancel_appointment_occurrence(
+        &self,
+        series_id: i64,
+        original_start_time: &str,
+        scope: RecurrenceScope,
+    ) -> Result<i64, String>;
+}
+
+pub trait AgendaResourceRepository {
+    fn list_agenda_resources(&self, include_inactive: bool) -> Result<Vec<AgendaResource>, String>;
+    fn get_agenda_resource(&self, id: i64) -> Result<Option<AgendaResource>, String>;
+    fn create_agenda_resource(&self, resource: &AgendaResource) -> Result<i64, String>;
+    fn update_agenda_resource(&self, resource: &AgendaResource) -> Result<(), String>;
+    fn delete_agenda_resource(&self, id: i64) -> Result<(), String>;
+    fn find_free_slots(&self, query: &FreeSlotQuery) -> Result<Vec<FreeSlot>, String>;
+}
+
+pub trait OdontogramRepository {
+    fn get_odontogram_by_patient(&self, patient_id: i64) -> Result<Vec<OdontogramEntry>, String>;
+    fn get_tooth_by_patient_and_number(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> Result<Option<OdontogramEntry>, String>;
+    fn update_tooth_condition(&self, input: UpdateToothConditionInput) -> Result<i64, String>;
+    fn delete_tooth_condition(&self, patient_id: i64, tooth_number: &str) -> Result<(), String>;
+    fn clear_patient_odontogram(&self, patient_id: i64) -> Result<(), String>;
+    fn get_tooth_history(&self, patient_id: i64, tooth_number: &str) -> Result<Vec<OdontogramEntry>, String>;
+
+    fn get_odontogram_surfaces_by_patient(&self, patient_id: i64) -> Result<Vec<OdontogramSurface>, String>;
+    fn get_tooth_surfaces(&self, patient_id: i64, tooth_number: &str) -> Result<Vec<OdontogramSurface>, String>;
+    fn update_tooth_surface(&self, input: UpdateSurfaceInput) -> Result<i64, String>;
+    fn delete_tooth_surface(&self, patient_id: i64, tooth_number: &str, surface: &str) -> Result<(), String>;
+    fn clear_tooth_surfaces(&self, patient_id: i64, tooth_number: &str) -> Result<(), String>;
+    fn add_tooth_surface_treatment(&self, input: AddSurfaceTreatmentInput) -> Result<i64, String>;
+    fn get_surface_treatments(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+        surface: &str,
+    ) -> Result<Vec<OdontogramSurface>, String>;
+    fn deactivate_surface_treatment(&self, surface_id: i64) -> Result<(), String>;
+    fn get_surface_history(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+        surface: &str,
+    ) -> Result<Vec<SurfaceHistoryEntry>, String>;
+    fn get_tooth_surface_history(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> Result<Vec<SurfaceHistoryEntry>, String>;
+
+    fn get_tooth_treatments(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> Result<Vec<OdontogramToothTreatment>, String>;
+    fn get_tooth_treatments_by_patient(&self, patient_id: i64) -> Result<Vec<OdontogramToothTreatment>, String>;
+    fn add_tooth_treatment(&self, input: AddToothTreatmentInput) -> Result<i64, String>;
+    fn deactivate_tooth_treatment(&self, treatment_id: i64) -> Result<(), String>;
+    fn get_tooth_treatment_history(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> Result<Vec<ToothTreatmentHistoryEntry>, String>;
+
+    fn get_bridges_by_patient(&self, patient_id: i64) -> Result<Vec<OdontogramBridge>, String>;
+    fn add_bridge(&self, input: AddBridgeInput) -> Result<i64, String>;
+    fn deactivate_bridge(&self, bridge_id: i64) -> Result<(), String>;
+}
+
+pub trait TreatmentCatalogRepository {
+    fn get_all_treatment_catalog(&self) -> Result<Vec<TreatmentCatalogEntry>, String>;
+    fn get_treatment_catalog_by_id(&self, id: i64) -> Result<Option<TreatmentCatalogEntry>, String>;
+    fn create_treatment_catalog(&self, input: CreateTreatmentCatalogInput) -> Result<i64, String>;
+    fn update_treatment_catalog(&self, input: UpdateTreatmentCatalogInput) -> Result<(), String>;
+    fn delete_treatment_catalog(&self, id: i64) -> Result<(), String>;
+    fn get_treatment_catalog_items(&self, treatment_catalog_id: i64) -> Result<Vec<TreatmentCatalogItem>, String>;
+    fn get_treatment_catalog_item_by_id(&self, id: i64) -> Result<Option<TreatmentCatalogItem>, String>;
+    fn create_treatment_catalog_item(&self, input: CreateTreatmentCatalogItemInput) -> Result<i64, String>;
+    fn update_treatment_catalog_item(&self, input: UpdateTreatmentCatalogItemInput) -> Result<(), String>;
+    fn delete_treatment_catalog_item(&self, id: i64) -> Result<(), String>;
+}
+
+/// Every data operation available to Tauri commands
+pub trait Repository:
+    PatientRepository
+    + TreatmentRepository
+    + PaymentRepository
+    + AppointmentRepository
+    + AgendaResourceRepository
+    + OdontogramRepository
+    + TreatmentCatalogRepository
+{
+}
+
+impl<T> Repository for T where
+    T: PatientRepository
+        + TreatmentRepository
+        + PaymentRepository
+        + AppointmentRepository
+        + AgendaResourceRepository
+        + OdontogramRepository
+        + TreatmentCatalogRepository
+{
+}
+
+/// Repository for the current node mode
+/// Client mode forwards to the configured host; every other mode uses the local database.
+pub fn current() -> Result<Box<dyn Repository>, String> {
+    let config = load_node_config()?;
+
+    match (config.mode, config.client_config) {
+        (NodeMode::Client, Some(client_config)) => {
+            Ok(Box::new(RemoteRepository::new(&client_config)?))
+        }
+        (NodeMode::Client, None) => Err("Modo cliente sin host configurado".to_string()),
+        _ => Ok(Box::new(LocalRepository)),
+    }
+}
+
+/// Run `operation` against the current repository on the blocking thread pool
+pub async fn run<T, F>(operation: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&dyn Repository) -> Result<T, String> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(move || operation(current()?.as_ref()))
+        .await
+        .map_err(|e| format!("Error ejecutando la operación: {}", e))?
+}
@@ -0,0 +1,831 @@
+// Remote repository: forwards every call to the host's HTTP API (Client mode)
+// HTTPS hosts are trusted by the certificate fingerprint pinned in ClientConfig.
+// Errors returned by the host are mapped back to the same ServiceError strings the
+// local repository produces.
+
+use super::*;
+use crate::node::ClientConfig;
+use crate::services::ServiceError;
+use crate::tls;
+use once_cell::sync::Lazy;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Timeout for a single request to the host
+const REQUEST_TIMEOUT_SECS: u64 = 15;
+
+/// `error` of the host's body for records that don't exist (see `api::service_error_to_response`)
+const NOT_FOUND_ERROR: &str = "Not Found";
+
+/// Characters escaped in path segments: everything but RFC 3986 unreserved characters
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Client built for a pinned fingerprint (or none)
+type PinnedClient = (Option<String>, Client);
+
+/// HTTP client shared between commands, rebuilt when the pinned fingerprint changes
+static HTTP_CLIENT: Lazy<Mutex<Option<PinnedClient>>> = Lazy::new(|| Mutex::new(None));
+
+/// Client for the host, trusting only the pinned certificate when one is configured
+fn http_client(pinned_fingerprint: Option<&str>) -> Result<Client, String> {
+    let mut cached = HTTP_CLIENT.lock().unwrap();
+
+    if let Some((fingerprint, client)) = cached.as_ref() {
+        if fingerprint.as_deref() == pinned_fingerprint {
+            return Ok(client.clone());
+        }
+    }
+
+    let mut builder = Client::builder().timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS));
+    if let Some(fingerprint) = pinned_fingerprint {
+        builder = builder.use_preconfigured_tls(tls::pinned_client_config(fingerprint));
+    }
+
+    let client = builder
+        .build()
+        .map_err(|e| format!("Error creando cliente HTTP: {}", e))?;
+    *cached = Some((pinned_fingerprint.map(str::to_string), client.clone()));
+
+    Ok(client)
+}
+
+#[derive(Deserialize)]
+struct IdResponse {
+    id: i64,
+}
+
+#[derive(Deserialize)]
+struct CountResponse {
+    count: i64,
+}
+
+#[derive(Deserialize)]
+struct TotalResponse {
+    total: f64,
+}
+
+#[derive(Deserialize, Default)]
+struct ErrorResponse {
+    #[serde(default)]
+    error: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    conflicts: serde_json::Value,
+}
+
+#[derive(Serialize)]
+struct PageQuery {
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+#[derive(Serialize)]
+struct DebtQuery {
+    limit: Option<i64>,
+    offset: Option<i64>,
+    q: Option<String>,
+}
+
+/// Repository backed by a host node's HTTP API
+pub struct RemoteRepository {
+    base_url: String,
+    auth_token: String,
+    client: Client,
+}
+
+impl RemoteRepository {
+    pub fn new(config: &ClientConfig) -> Result<Self, String> {
+        let remote_url = config.remote_url.trim().trim_end_matches('/');
+        if remote_url.is_empty() {
+            return Err("Modo cliente sin host configurado".to_string());
+        }
+
+        let pinned_fingerprint = config
+            .pinned_fingerprint
+            .as_deref()
+            .filter(|fingerprint| !fingerprint.is_empty());
+
+        Ok(Self {
+            base_url: format!("{}/api", remote_url),
+            auth_token: config.auth_token.clone(),
+            client: http_client(pinned_fingerprint)?,
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}{}", self.base_url, path))
+            .bearer_auth(&self.auth_token)
+    }
+
+    fn dispatch(request: RequestBuilder) -> Result<reqwest::blocking::Response, String> {
+        request
+            .send()
+            .map_err(|e| format!("Error de conexión con el host: {}", e))
+    }
+
+    /// Map non-success statuses to the service error the host reported
+    fn check(response: reqwest::blocking::Response) -> Result<reqwest::blocking::Response, String> {
+        if response.status().is_success() {
+            return Ok(response);
+        }
+
+        let status = response.status();
+        Err(Self::error(status, response.json().unwrap_or_default()))
+    }
+
+    fn error(status: StatusCode, body: ErrorResponse) -> String {
+        let message = if body.message.is_empty() {
+            status.to_string()
+        } else {
+            body.message
+        };
+
+        let error = match status {
+            StatusCode::NOT_FOUND => ServiceError::NotFound(message),
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => {
+                ServiceError::ValidationError(message)
+            }
+            StatusCode::UNAUTHORIZED => ServiceError::Unauthorized(message),
+            StatusCode::FORBIDDEN => ServiceError::Forbidden(message),
+            StatusCode::TOO_MANY_REQUESTS => ServiceError::TooManyRequests(message),
+            StatusCode::CONFLICT => ServiceError::Conflict(message, body.conflicts),
+            _ => ServiceError::DatabaseError(message),
+        };
+
+        error.to_string()
+    }
+
+    fn send(request: RequestBuilder) -> Result<reqwest::blocking::Response, String> {
+        Self::check(Self::dispatch(request)?)
+    }
+
+    fn parse<T: DeserializeOwned>(response: reqwest::blocking::Response) -> Result<T, String> {
+        response
+            .json()
+            .map_err(|e| format!("Respuesta inválida del host: {}", e))
+    }
+
+    fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, String> {
+        Self::parse(Self::send(self.request(reqwest::Method::GET, path))?)
+    }
+
+    fn get_with_query<T: DeserializeOwned, Q: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        query: &Q,
+    ) -> Result<T, String> {
+        Self::parse(Self::send(self.request(reqwest::Method::GET, path).query(query))?)
+    }
+
+    /// GET that turns the host's "not found" into `None`
+    /// A 404 without that body means the host lacks the route (a version mismatch) and
+    /// stays an error.
+    fn get_optional<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>, String> {
+        let response = Self::dispatch(self.request(reqwest::Method::GET, path))?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            let body: ErrorResponse = response.json().unwrap_or_default();
+            if body.error == NOT_FOUND_ERROR {
+                return Ok(None);
+            }
+            return Err(format!(
+                "El host no reconoce la ruta {}; verifique que ambos nodos usen la misma versión",
+                path
+            ));
+        }
+
+        Self::parse(Self::check(response)?).map(Some)
+    }
+
+    /// POST for queries whose parameters do not fit in a query string
+    fn post_for<T: DeserializeOwned, B: Serialize>(&self, path: &str, body: &B) -> Result<T, String> {
+        Self::parse(Self::send(self.request(reqwest::Method::POST, path).json(body))?)
+    }
+
+    fn post_for_id<B: Serialize>(&self, path: &str, body: &B) -> Result<i64, String> {
+        let response: IdResponse =
+            Self::parse(Self::send(self.request(reqwest::Method::POST, path).json(body))?)?;
+        Ok(response.id)
+    }
+
+    fn put_for_id<B: Serialize>(&self, path: &str, body: &B) -> Result<i64, String> {
+        let response: IdResponse =
+            Self::parse(Self::send(self.request(reqwest::Method::PUT, path).json(body))?)?;
+        Ok(response.id)
+    }
+
+    fn post<B: Serialize>(&self, path: &str, body: &B) -> Result<(), String> {
+        Self::send(self.request(reqwest::Method::POST, path).json(body)).map(|_| ())
+    }
+
+    fn put<B: Serialize>(&self, path: &str, body: &B) -> Result<(), String> {
+        Self::send(self.request(reqwest::Method::PUT, path).json(body)).map(|_| ())
+    }
+
+    fn delete(&self, path: &str) -> Result<(), String> {
+        Self::send(self.request(reqwest::Method::DELETE, path)).map(|_| ())
+    }
+}
+
+/// Ask the host to save an appointment even if it overlaps others
+fn overbooking(path: &str, allow_overbooking: bool) -> String {
+    if allow_overbooking {
+        format!("{}?allow_overbooking=true", path)
+    } else {
+        path.to_string()
+    }
+}
+
+/// Percent-encode a value used as a single path segment
+fn segment(value: &str) -> String {
+    utf8_percent_encode(value, PATH_SEGMENT).to_string()
+}
+
+/// Path of a tooth inside a patient's odontogram
+fn tooth_path(patient_id: i64, tooth_number: &str) -> String {
+    format!("/patients/{}/odontogram/teeth/{}", patient_id, segment(tooth_number))
+}
+
+impl PatientRepository for RemoteRepository {
+    fn get_patients(&self, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<Patient>, String> {
+        self.get_with_query("/patients", &PageQuery { limit, offset })
+    }
+
+    fn get_patient_by_id(&self, id: i64) -> Result<Option<Patient>, String> {
+        self.get_optional(&format!("/patients/{}", id))
+    }
+
+    fn create_patient(&self, input: CreatePatientInput) -> Result<i64, String> {
+        self.post_for_id("/patients", &input)
+    }
+
+    fn update_patient(&self, id: i64, input: UpdatePatientInput) -> Result<(), String> {
+        self.put(&format!("/patients/{}", id), &input)
+    }
+
+    fn delete_patient(&self, id: i64) -> Result<(), String> {
+        self.delete(&format!("/patients/{}", id))
+    }
+
+    fn search_patients(&self, query: &str) -> Result<Vec<Patient>, String> {
+        self.get_with_query("/patients/search", &[("q", query)])
+    }
+
+    fn get_patients_count(&self) -> Result<i64, String> {
+        self.get::<CountResponse>("/patients/count").map(|r| r.count)
+    }
+}
+
+impl TreatmentRepository for RemoteRepository {
+    fn get_all_treatments(
+        &self,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<Treatment>, String> {
+        self.get_with_query("/treatments", &PageQuery { limit, offset })
+    }
+
+    fn get_treatment_by_id(&self, id: i64) -> Result<Option<Treatment>, String> {
+        self.get_optional(&format!("/treatments/{}", id))
+    }
+
+    fn get_treatments_by_patient(&self, patient_id: i64) -> Result<Vec<Treatment>, String> {
+        self.get(&format!("/patients/{}/treatments", patient_id))
+    }
+
+    fn get_treatments_by_status(&self, status: &str) -> Result<Vec<Treatment>, String> {
+        self.get(&format!("/treatments/status/{}", segment(status)))
+    }
+
+    fn create_treatment(&self, input: CreateTreatmentInput) -> Result<i64, String> {
+        self.post_for_id("/treatments", &input)
+    }
+
+    fn update_treatment(&self, id: i64, input: UpdateTreatmentInput) -> Result<(), String> {
+        self.put(&format!("/treatments/{}", id), &input)
+    }
+
+    fn update_treatment_status(&self, id: i64, status: &str) -> Result<(), String> {
+        self.put(
+            &format!("/treatments/{}/status", id),
+            &serde_json::json!({ "status": status }),
+        )
+    }
+
+    fn delete_treatment(&self, id: i64) -> Result<(), String> {
+        self.delete(&format!("/treatments/{}", id))
+    }
+
+    fn get_treatment_stats(&self) -> Result<TreatmentStats, String> {
+        self.get("/treatments/stats")
+    }
+}
+
+impl PaymentRepository for RemoteRepository {
+    fn get_all_payments(&self, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<Payment>, String> {
+        self.get_with_query("/payments", &PageQuery { limit, offset })
+    }
+
+    fn get_payment_by_id(&self, id: i64) -> Result<Option<Payment>, String> {
+        self.get_optional(&format!("/payments/{}", id))
+    }
+
+    fn get_payments_by_treatment(&self, treatment_id: i64) -> Result<Vec<Payment>, String> {
+        self.get(&format!("/treatments/{}/payments", treatment_id))
+    }
+
+    fn get_payments_by_patient(&self, patient_id: i64) -> Result<Vec<Payment>, String> {
+        self.get(&format!("/patients/{}/payments", patient_id))
+    }
+
+    fn create_payment(&self, input: CreatePaymentInput) -> Result<i64, String> {
+        self.post_for_id("/payments", &input)
+    }
+
+    fn update_payment(&self, id: i64, input: UpdatePaymentInput) -> Result<(), String> {
+        self.put(&format!("/payments/{}", id), &input)
+    }
+
+    fn delete_payment(&self, id: i64) -> Result<(), String> {
+        self.delete(&format!("/payments/{}", id))
+    }
+
+    fn get_patient_balance(&self, patient_id: i64) -> Result<PatientBalance, String> {
+        self.get(&format!("/patients/{}/balance", patient_id))
+    }
+
+    fn get_patients_with_debt(
+        &self,
+        limit: Option<i64>,
+        offset: Option<i64>,
+        query: Option<String>,
+    ) -> Result<Vec<PatientBalance>, String> {
+        self.get_with_query(
+            "/debts",
+            &DebtQuery {
+                limit,
+                offset,
+                q: query,
+            },
+        )
+    }
+
+    fn get_patients_with_debt_count(&self) -> Result<i64, String> {
+        self.get::<CountResponse>("/debts/count").map(|r| r.count)
+    }
+
+    fn get_patients_with_debt_summary(&self, query: Option<String>) -> Result<PatientDebtSummary, String> {
+        self.get_with_query("/debts/summary", &[("q", query)])
+    }
+
+    fn get_total_debt(&self) -> Result<f64, String> {
+        self.get::<TotalResponse>("/debts/total").map(|r| r.total)
+    }
+
+    fn get_recent_payments(&self, limit: Option<i64>) -> Result<Vec<Payment>, String> {
+        self.get_with_query("/payments/recent", &[("limit", limit)])
+    }
+}
+
+impl AppointmentRepository for RemoteRepository {
+    fn create_appointment(&self, appointment: &Appointment, allow_overbooking: bool) -> Result<i64, String> {
+        self.post_for_id(&overbooking("/appointments", allow_overbooking), appointment)
+    }
+
+    fn update_appointment(&self, appointment: &Appointment, allow_overbooking: bool) -> Result<(), String> {
+        let id = appointment
+            .id
+            .ok_or_else(|| ServiceError::ValidationError("Appointment id is required".to_string()).to_string())?;
+        self.put(
+            &overbooking(&format!("/appointments/{}", id), allow_overbooking),
+            appointment,
+        )
+    }
+
+    fn delete_appointment(&self, id: i64) -> Result<(), String> {
+        self.delete(&format!("/appointments/{}", id))
+    }
+
+    fn get_appointment(&self, id: i64) -> Result<Appointment, String> {
+        self.get(&format!("/appointments/{}", id))
+    }
+
+    fn list_appointments(&self, filter: &AppointmentFilter) -> Result<Vec<AppointmentWithPatient>, String> {
+        self.get_with_query("/appointments", filter)
+    }
+
+    fn get_pending_reminders(&self) -> Result<Vec<AppointmentReminder>, String> {
+        self.get("/appointments/reminders/pending")
+    }
+
+    fn get_reminder_notification(&self, reminder_id: i64) -> Result<Option<ReminderNotification>, String> {
+        self.get_optional(&format!("/appointments/reminders/{}/notification", reminder_id))
+    }
+
+    fn mark_reminder_sent(&self, reminder_id: i64, notification_id: &str) -> Result<(), String> {
+        self.post(
+            &format!("/appointments/reminders/{}/sent", reminder_id),
+            &serde_json::json!({ "notification_id": notification_id }),
+        )
+    }
+
+    fn get_upcoming_appointments(&self, hours: i32) -> Result<Vec<AppointmentWithPatient>, String> {
+        self.get_with_query("/appointments/upcoming", &[("hours", hours)])
+    }
+
+    fn update_appointment_occurrence(
+        &self,
+        series_id: i64,
+        original_start_time: &str,
+        appointment: &Appointment,
+        scope: RecurrenceScope,
+        allow_overbooking: bool,
+    ) -> Result<i64, String> {
+        self.put_for_id(
+            &overbooking(&format!("/appointments/{}/occurrences", series_id), allow_overbooking),
+            &serde_json::json!({
+                "original_start_time": original_start_time,
+                "scope": scope,
+                "appointment": appointment,
+            }),
+        )
+    }
+
+    fn cancel_appointment_occurrence(
+        &self,
+        series_id: i64,
+        original_start_time: &str,
+        scope: RecurrenceScope,
+    ) -> Result<i64, String> {
+        self.post_for_id(
+            &format!("/appointments/{}/occurrences/cancel", series_id),
+            &serde_json::json!({
+                "original_start_time": original_start_time,
+                "scope": scope,
+            }),
+        )
+    }
+}
+
+impl AgendaResourceRepository for RemoteRepository {
+    fn list_agenda_resources(&self, include_inactive: bool) -> Result<Vec<AgendaResource>, String> {
+        self.get_with_query("/agenda-resources", &[("include_inactive", include_inactive)])
+    }
+
+    fn get_agenda_resource(&self, id: i64) -> Result<Option<AgendaResource>, String> {
+        self.get_optional(&format!("/agenda-resources/{}", id))
+    }
+
+    fn create_agenda_resource(&self, resource: &AgendaResource) -> Result<i64, String> {
+        self.post_for_id("/agenda-resources", resource)
+    }
+
+    fn update_agenda_resource(&self, resource: &AgendaResource) -> Result<(), String> {
+        let id = resource
+            .id
+            .ok_or_else(|| ServiceError::ValidationError("Resource id is required".to_string()).to_string())?;
+        self.put(&format!("/agenda-resources/{}", id), resource)
+    }
+
+    fn delete_agenda_resource(&self, id: i64) -> Result<(), String> {
+        self.delete(&format!("/agenda-resources/{}", id))
+    }
+
+    fn find_free_slots(&self, query: &FreeSlotQuery) -> Result<Vec<FreeSlot>, String> {
+        self.post_for("/agenda-resources/free-slots", query)
+    }
+}
+
+impl OdontogramRepository for RemoteRepository {
+    fn get_odontogram_by_patient(&self, patient_id: i64) -> Result<Vec<OdontogramEntry>, String> {
+        self.get(&format!("/patients/{}/odontogram", patient_id))
+    }
+
+    fn get_tooth_by_patient_and_number(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> Result<Option<OdontogramEntry>, String> {
+        self.get_optional(&tooth_path(patient_id, tooth_number))
+    }
+
+    fn update_tooth_condition(&self, input: UpdateToothConditionInput) -> Result<i64, String> {
+        self.put_for_id("/odontogram/conditions", &input)
+    }
+
+    fn delete_tooth_condition(&self, patient_id: i64, tooth_number: &str) -> Result<(), String> {
+        self.delete(&tooth_path(patient_id, tooth_number))
+    }
+
+    fn clear_patient_odontogram(&self, patient_id: i64) -> Result<(), String> {
+        self.delete(&format!("/patients/{}/odontogram", patient_id))
+    }
+
+    fn get_tooth_history(&self, patient_id: i64, tooth_number: &str) -> Result<Vec<OdontogramEntry>, String> {
+        self.get(&format!("{}/history", tooth_path(patient_id, tooth_number)))
+    }
+
+    fn get_odontogram_surfaces_by_patient(&self, patient_id: i64) -> Result<Vec<OdontogramSurface>, String> {
+        self.get(&format!("/patients/{}/odontogram/surfaces", patient_id))
+    }
+
+    fn get_tooth_surfaces(&self, patient_id: i64, tooth_number: &str) -> Result<Vec<OdontogramSurface>, String> {
+        self.get(&format!("{}/surfaces", tooth_path(patient_id, tooth_number)))
+    }
+
+    fn update_tooth_surface(&self, input: UpdateSurfaceInput) -> Result<i64, String> {
+        self.put_for_id("/odontogram/surfaces", &input)
+    }
+
+    fn delete_tooth_surface(&self, patient_id: i64, tooth_number: &str, surface: &str) -> Result<(), String> {
+        self.delete(&format!(
+            "{}/surfaces/{}",
+            tooth_path(patient_id, tooth_number),
+            segment(surface)
+        ))
+    }
+
+    fn clear_tooth_surfaces(&self, patient_id: i64, tooth_number: &str) -> Result<(), String> {
+        self.delete(&format!("{}/surfaces", tooth_path(patient_id, tooth_number)))
+    }
+
+    fn add_tooth_surface_treatment(&self, input: AddSurfaceTreatmentInput) -> Result<i64, String> {
+        self.post_for_id("/odontogram/surfaces", &input)
+    }
+
+    fn get_surface_treatments(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+        surface: &str,
+    ) -> Result<Vec<OdontogramSurface>, String> {
+        self.get(&format!(
+            "{}/surfaces/{}",
+            tooth_path(patient_id, tooth_number),
+            segment(surface)
+        ))
+    }
+
+    fn deactivate_surface_treatment(&self, surface_id: i64) -> Result<(), String> {
+        self.delete(&format!("/odontogram/surfaces/{}", surface_id))
+    }
+
+    fn get_surface_history(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+        surface: &str,
+    ) -> Result<Vec<SurfaceHistoryEntry>, String> {
+        self.get(&format!(
+            "{}/surfaces/{}/history",
+            tooth_path(patient_id, tooth_number),
+            segment(surface)
+        ))
+    }
+
+    fn get_tooth_surface_history(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> Result<Vec<SurfaceHistoryEntry>, String> {
+        self.get(&format!("{}/surfaces/history", tooth_path(patient_id, tooth_number)))
+    }
+
+    fn get_tooth_treatments(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> Result<Vec<OdontogramToothTreatment>, String> {
+        self.get(&format!("{}/treatments", tooth_path(patient_id, tooth_number)))
+    }
+
+    fn get_tooth_treatments_by_patient(&self, patient_id: i64) -> Result<Vec<OdontogramToothTreatment>, String> {
+        self.get(&format!("/patients/{}/odontogram/tooth-treatments", patient_id))
+    }
+
+    fn add_tooth_treatment(&self, input: AddToothTreatmentInput) -> Result<i64, String> {
+        self.post_for_id("/odontogram/tooth-treatments", &input)
+    }
+
+    fn deactivate_tooth_treatment(&self, treatment_id: i64) -> Result<(), String> {
+        self.delete(&format!("/odontogram/tooth-treatments/{}", treatment_id))
+    }
+
+    fn get_tooth_treatment_history(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> Result<Vec<ToothTreatmentHistoryEntry>, String> {
+        self.get(&format!("{}/treatments/history", tooth_path(patient_id, tooth_number)))
+    }
+
+    fn get_bridges_by_patient(&self, patient_id: i64) -> Result<Vec<OdontogramBridge>, String> {
+        self.get(&format!("/patients/{}/odontogram/bridges", patient_id))
+    }
+
+    fn add_bridge(&self, input: AddBridgeInput) -> Result<i64, String> {
+        self.post_for_id("/odontogram/bridges", &input)
+    }
+
+    fn deactivate_bridge(&self, bridge_id: i64) -> Result<(), String> {
+        self.delete(&format!("/odontogram/bridges/{}", bridge_id))
+    }
+}
+
+impl TreatmentCatalogRepository for RemoteRepository {
+    fn get_all_treatment_catalog(&self) -> Result<Vec<TreatmentCatalogEntry>, String> {
+        self.get("/treatment-catalog")
+    }
+
+    fn get_treatment_catalog_by_id(&self, id: i64) -> Result<Option<TreatmentCatalogEntry>, String> {
+        self.get_optional(&format!("/treatment-catalog/{}", id))
+    }
+
+    fn create_treatment_catalog(&self, input: CreateTreatmentCatalogInput) -> Result<i64, String> {
+        self.post_for_id("/treatment-catalog", &input)
+    }
+
+    fn update_treatment_catalog(&self, input: UpdateTreatmentCatalogInput) -> Result<(), String> {
+        self.put(&format!("/treatment-catalog/{}", input.id), &input)
+    }
+
+    fn delete_treatment_catalog(&self, id: i64) -> Result<(), String> {
+        self.delete(&format!("/treatment-catalog/{}", id))
+    }
+
+    fn get_treatment_catalog_items(&self, treatment_catalog_id: i64) -> Result<Vec<TreatmentCatalogItem>, String> {
+        self.get(&format!("/treatment-catalog/{}/items", treatment_catalog_id))
+    }
+
+    fn get_treatment_catalog_item_by_id(&self, id: i64) -> Result<Option<TreatmentCatalogItem>, String> {
+        self.get_optional(&format!("/treatment-catalog/items/{}", id))
+    }
+
+    fn create_treatment_catalog_item(&self, input: CreateTreatmentCatalogItemInput) -> Result<i64, String> {
+        self.post_for_id("/treatment-catalog/items", &input)
+    }
+
+    fn update_treatment_catalog_item(&self, input: UpdateTreatmentCatalogItemInput) -> Result<(), String> {
+        self.put(&format!("/treatment-catalog/items/{}", input.id), &input)
+    }
+
+    fn delete_treatment_catalog_item(&self, id: i64) -> Result<(), String> {
+        self.delete(&format!("/treatment-catalog/items/{}", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::Path;
+    use axum::http::{HeaderMap, StatusCode, Uri};
+    use axum::routing::get;
+    use axum::{Json, Router};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    /// Serve `router` on a free local port; returns the node URL
+    fn serve(router: Router) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        listener.set_nonblocking(true).unwrap();
+
+        std::thread::spawn(move || {
+            tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(async move {
+                    let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+                    axum::serve(listener, router).await.unwrap();
+                });
+        });
+
+        format!("http://{}", addr)
+    }
+
+    fn repository(url: &str) -> RemoteRepository {
+        RemoteRepository::new(&ClientConfig {
+            remote_url: format!("{}/", url),
+            auth_token: "token-cliente".to_string(),
+            pinned_fingerprint: None,
+        })
+        .unwrap()
+    }
+
+    fn not_found() -> (StatusCode, Json<serde_json::Value>) {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "Not Found", "message": "No existe" })),
+        )
+    }
+
+    /// Answers each patient ID with a different status
+    async fn delete_patient(
+        Path(id): Path<i64>,
+        headers: HeaderMap,
+    ) -> (StatusCode, Json<serde_json::Value>) {
+        assert_eq!(headers["authorization"], "Bearer token-cliente");
+
+        let (status, error, message) = match id {
+            1 => return (StatusCode::OK, Json(json!({}))),
+            2 => (StatusCode::FORBIDDEN, "Forbidden", "Sin permiso"),
+            3 => (StatusCode::BAD_REQUEST, "Validation Error", "Inválido"),
+            4 => (StatusCode::CONFLICT, "Conflict", "Superpuesta"),
+            _ => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({}))),
+        };
+        let conflicts = if status == StatusCode::CONFLICT {
+            json!([1])
+        } else {
+            json!(null)
+        };
+        (
+            status,
+            Json(json!({ "error": error, "message": message, "conflicts": conflicts })),
+        )
+    }
+
+    #[test]
+    fn maps_host_statuses_to_service_errors() {
+        let router = Router::new()
+            .route(
+                "/api/patients/:id",
+                get(|| async { not_found() }).delete(delete_patient),
+            )
+            .route(
+                "/api/patients/count",
+                get(|| async { Json(json!({ "count": 3 })) }),
+            );
+        let remote = repository(&serve(router));
+
+        assert_eq!(remote.get_patients_count().unwrap(), 3);
+        assert!(remote.get_patient_by_id(9).unwrap().is_none());
+        assert!(remote.delete_patient(1).is_ok());
+        assert_eq!(
+            remote.delete_patient(2).unwrap_err(),
+            ServiceError::Forbidden("Sin permiso".to_string()).to_string()
+        );
+        assert_eq!(
+            remote.delete_patient(3).unwrap_err(),
+            ServiceError::ValidationError("Inválido".to_string()).to_string()
+        );
+        assert_eq!(
+            remote.delete_patient(4).unwrap_err(),
+            ServiceError::Conflict("Superpuesta".to_string(), json!([1])).to_string()
+        );
+        assert_eq!(
+            remote.delete_patient(5).unwrap_err(),
+            ServiceError::DatabaseError("500 Internal Server Error".to_string()).to_string()
+        );
+
+        // A route the host lacks is not "record not found"
+        assert!(remote.get_treatment_catalog_by_id(1).is_err());
+    }
+
+    #[test]
+    fn encodes_path_segments() {
+        let paths = Arc::new(Mutex::new(Vec::new()));
+        let recorded = paths.clone();
+        let router = Router::new()
+            .route(
+                "/api/patients/:id/odontogram/teeth/:tooth",
+                get(
+                    move |Path((id, tooth)): Path<(i64, String)>, uri: Uri| async move {
+                        assert_eq!((id, tooth.as_str()), (7, "18/ñ x"));
+                        recorded.lock().unwrap().push(uri.path().to_string());
+                        not_found()
+                    },
+                ),
+            )
+            .route(
+                "/api/treatments/status/:status",
+                get(|Path(status): Path<String>| async move {
+                    assert_eq!(status, "en curso?");
+                    Json(json!([]))
+                }),
+            );
+        let remote = repository(&serve(router));
+
+        assert!(remote
+            .get_tooth_by_patient_and_number(7, "18/ñ x")
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            paths.lock().unwrap().as_slice(),
+            ["/api/patients/7/odontogram/teeth/18%2F%C3%B1%20x"]
+        );
+        assert!(remote
+            .get_treatments_by_status("en curso?")
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+// Agenda Resource Domain Service
+// Contains the business logic for practitioners, chairs and free-slot search
+// Independent of Tauri and HTTP - pure business logic
+
+use crate::db::agenda_resources::{
+    self as db_resources, AgendaResource, FreeSlot, FreeSlotQuery, WeeklyHours, MAX_SLOT_LIMIT,
+    MAX_SLOT_MINUTES,
+};
+use crate::db::appointments as db_appointments;
+use crate::db::get_connection;
+use crate::events;
+use crate::services::{DomainService, ServiceError, ServiceResult};
+
+/// Agenda resource service handles practitioners, chairs and their schedules
+pub struct AgendaResourceService;
+
+impl AgendaResourceService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// List resources, optionally including inactive ones
+    pub fn list(&self, include_inactive: bool) -> ServiceResult<Vec<AgendaResource>> {
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        db_resources::list_resources(&conn, include_inactive).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get resource by ID
+    pub fn get_by_id(&self, id: i64) -> ServiceResult<Option<AgendaResource>> {
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        db_resources::get_resource(&conn, id).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Create a resource with its schedule
+    pub fn create(&self, resource: &AgendaResource) -> ServiceResult<i64> {
+        Self::validate(resource)?;
+
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        let id = db_resources::create_resource(&conn, resource)
+            .map_err(ServiceError::DatabaseError)?;
+        events::publish("agenda_resource.created", id);
+        Ok(id)
+    }
+
+    /// Update a resource, replacing its schedule
+    pub fn update(&self, resource: &AgendaResource) -> ServiceResult<()> {
+        let id = resource.id.ok_or_else(|| {
+            ServiceError::ValidationError("Resource ID is required for update".to_string())
+        })?;
+        Self::validate(resource)?;
+        self.ensure_exists(id)?;
+
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        db_resources::update_resource(&conn, resource).map_err(ServiceError::DatabaseError)?;
+        events::publish("agenda_resource.updated", id);
+        Ok(())
+    }
+
+    /// Delete a resource and its schedule
+    pub fn delete(&self, id: i64) -> ServiceResult<()> {
+        self.ensure_exists(id)?;
+
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        db_resources::delete_resource(&conn, id).map_err(ServiceError::DatabaseError)?;
+        events::publish("agenda_resource.deleted", id);
+        Ok(())
+    }
+
+    /// Find the next openings where every requested resource is working and free
+    pub fn find_free_slots(&self, query: &FreeSlotQuery) -> ServiceResult<Vec<FreeSlot>> {
+        if query.resource_ids.is_empty() {
+            return Err(ServiceError::ValidationError(
+                "At least one resource is required".to_string(),
+            ));
+        }
+        if !(1..=MAX_SLOT_MINUTES).contains(&query.duration_minutes) {
+            return Err(ServiceError::ValidationError(format!(
+                "Duration must be between 1 and {} minutes",
+                MAX_SLOT_MINUTES
+            )));
+        }
+        if query
+            .step_minutes
+            .is_some_and(|step| !(1..=MAX_SLOT_MINUTES).contains(&step))
+        {
+            return Err(ServiceError::ValidationError(format!(
+                "Step must be between 1 and {} minutes",
+                MAX_SLOT_MINUTES
+            )));
+        }
+        if query.limit.is_some_and(|limit| limit == 0 || limit > MAX_SLOT_LIMIT) {
+            return Err(ServiceError::ValidationError(format!(
+                "Limit must be between 1 and {}",
+                MAX_SLOT_LIMIT
+            )));
+        }
+        for date in query.from.iter().chain(&query.to) {
+            db_appointments::parse_filter_date(date).map_err(ServiceError::ValidationError)?;
+        }
+        for &id in &query.resource_ids {
+            self.ensure_exists(id)?;
+        }
+
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        db_resources::find_free_slots(&conn, query).map_err(ServiceError::DatabaseError)
+    }
+
+    fn ensure_exists(&self, id: i64) -> ServiceResult<()> {
+        match self.get_by_id(id)? {
+            Some(_) => Ok(()),
+            None => Err(ServiceError::NotFound(format!("Resource {} not found", id))),
+        }
+    }
+
+    fn validate(resource: &AgendaResource) -> ServiceResult<()> {
+        if resource.name.trim().is_empty() {
+            return Err(ServiceError::ValidationError("Name is required".to_string()));
+        }
+
+        for hours in resource.working_hours.iter().chain(&resource.breaks) {
+            Self::validate_hours(hours)?;
+        }
+
+        for day_off in &resource.days_off {
+            let start = db_resources::parse_date(&day_off.start_date)
+                .map_err(ServiceError::ValidationError)?;
+            let end = db_resources::parse_date(&day_off.end_date)
+                .map_err(ServiceError::ValidationError)?;
+            if end < start {
+                return Err(ServiceError::ValidationError(
+                    "A day off cannot end before it starts".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_hours(hours: &WeeklyHours) -> ServiceResult<()> {
+        if hours.weekday > 6 {
+            return Err(ServiceError::ValidationError(
+                "Weekday must be between 0 (Monday) and 6 (Sunday)".to_string(),
+            ));
+        }
+
+        let start =
+            db_resources::parse_clock(&hours.start_time).map_err(ServiceError::ValidationError)?;
+        let end =
+            db_resources::parse_clock(&hours.end_time).map_err(ServiceError::ValidationError)?;
+        if end <= start {
+            return Err(ServiceError::ValidationError(
+                "Hours must end after they start".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl DomainService for AgendaResourceService {
+    fn name(&self) -> &'static str {
+        "AgendaResourceService"
+    }
+}
+
+impl Default for AgendaResourceService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
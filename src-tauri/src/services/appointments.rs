@@ -0,0 +1,300 @@
+// Appointment Domain Service
+// Contains all business logic for the agenda
+// Independent of Tauri and HTTP - pure business logic
+
+use crate::db::agenda_resources::{self as db_resources, ResourceKind};
+use crate::db::appointments::{
+    self as db_appointments, Appointment, AppointmentFilter, AppointmentReminder,
+    AppointmentWithPatient, RecurrenceScope, ReminderNotification,
+};
+use crate::db::recurrence::RecurrenceRule;
+use crate::db::get_connection;
+use crate::events;
+use crate::services::{DomainService, ServiceError, ServiceResult};
+
+/// Appointment service handles all agenda-related business logic
+pub struct AppointmentService;
+
+impl AppointmentService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Create a new appointment.
+    ///
+    /// Fails with a conflict when it overlaps another appointment in the same chair
+    /// or with the same practitioner, unless `allow_overbooking` is set.
+    pub fn create(&self, appointment: &Appointment, allow_overbooking: bool) -> ServiceResult<i64> {
+        if appointment.series_id.is_some() {
+            return Err(ServiceError::ValidationError(
+                "Occurrences of a recurring appointment are changed with update_occurrence"
+                    .to_string(),
+            ));
+        }
+        Self::validate(appointment)?;
+
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        Self::ensure_resources(&conn, appointment)?;
+        if !allow_overbooking {
+            Self::ensure_no_conflicts(&conn, appointment, &[])?;
+        }
+        let id = db_appointments::create_appointment(&conn, appointment)
+            .map_err(ServiceError::DatabaseError)?;
+        events::publish("appointment.created", id);
+        Ok(id)
+    }
+
+    /// Update an existing appointment, checking for conflicts like `create`
+    pub fn update(&self, appointment: &Appointment, allow_overbooking: bool) -> ServiceResult<()> {
+        let id = appointment.id.ok_or_else(|| {
+            ServiceError::ValidationError("Appointment ID is required for update".to_string())
+        })?;
+        if appointment.series_id == Some(id) {
+            return Err(ServiceError::ValidationError(
+                "Occurrences of a recurring appointment are changed with update_occurrence"
+                    .to_string(),
+            ));
+        }
+        if appointment.series_id.is_some() && appointment.recurrence_rule.is_some() {
+            return Err(ServiceError::ValidationError(
+                "An occurrence of a recurring appointment cannot have its own recurrence"
+                    .to_string(),
+            ));
+        }
+        Self::validate(appointment)?;
+
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        Self::ensure_exists(&conn, id)?;
+        Self::ensure_resources(&conn, appointment)?;
+        if !allow_overbooking {
+            Self::ensure_no_conflicts(&conn, appointment, &[id])?;
+        }
+        db_appointments::update_appointment(&conn, appointment)
+            .map_err(ServiceError::DatabaseError)?;
+        events::publish("appointment.updated", id);
+        Ok(())
+    }
+
+    /// Update an occurrence of a recurring appointment, the occurrences from it on,
+    /// or the whole series. Returns the ID of the appointment holding the change.
+    ///
+    /// Conflicts are checked from the edited occurrence on, unless `allow_overbooking`
+    /// is set.
+    pub fn update_occurrence(
+        &self,
+        series_id: i64,
+        original_start_time: &str,
+        appointment: &Appointment,
+        scope: RecurrenceScope,
+        allow_overbooking: bool,
+    ) -> ServiceResult<i64> {
+        Self::validate(appointment)?;
+
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        Self::ensure_series(&conn, series_id)?;
+        Self::ensure_resources(&conn, appointment)?;
+        if !allow_overbooking {
+            let mut changed = appointment.clone();
+            if scope == RecurrenceScope::Occurrence {
+                changed.recurrence_rule = None;
+            }
+            Self::ensure_no_conflicts(&conn, &changed, &[series_id])?;
+        }
+        let id = db_appointments::update_occurrence(
+            &conn,
+            series_id,
+            original_start_time,
+            appointment,
+            scope,
+        )
+        .map_err(ServiceError::DatabaseError)?;
+        events::publish("appointment.updated", id);
+        Ok(id)
+    }
+
+    /// Cancel an occurrence of a recurring appointment, the occurrences from it on,
+    /// or the whole series. Returns the ID of the appointment holding the change.
+    pub fn cancel_occurrence(
+        &self,
+        series_id: i64,
+        original_start_time: &str,
+        scope: RecurrenceScope,
+    ) -> ServiceResult<i64> {
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        Self::ensure_series(&conn, series_id)?;
+        let id = db_appointments::cancel_occurrence(&conn, series_id, original_start_time, scope)
+            .map_err(ServiceError::DatabaseError)?;
+        events::publish("appointment.updated", id);
+        Ok(id)
+    }
+
+    /// Delete appointment
+    pub fn delete(&self, id: i64) -> ServiceResult<()> {
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        Self::ensure_exists(&conn, id)?;
+        db_appointments::delete_appointment(&conn, id).map_err(ServiceError::DatabaseError)?;
+        events::publish("appointment.deleted", id);
+        Ok(())
+    }
+
+    /// Get appointment by ID
+    pub fn get_by_id(&self, id: i64) -> ServiceResult<Appointment> {
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        Self::ensure_exists(&conn, id)?;
+        db_appointments::get_appointment(&conn, id).map_err(ServiceError::DatabaseError)
+    }
+
+    /// List appointments matching the given filter
+    pub fn list(&self, filter: &AppointmentFilter) -> ServiceResult<Vec<AppointmentWithPatient>> {
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        db_appointments::list_appointments(&conn, filter).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get scheduled appointments in the next `hours` hours
+    pub fn get_upcoming(&self, hours: i32) -> ServiceResult<Vec<AppointmentWithPatient>> {
+        if hours <= 0 {
+            return Err(ServiceError::ValidationError(
+                "Hours must be greater than zero".to_string(),
+            ));
+        }
+
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        db_appointments::get_upcoming_appointments(&conn, hours)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get reminders that are due and not yet sent
+    pub fn get_pending_reminders(&self) -> ServiceResult<Vec<AppointmentReminder>> {
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        db_appointments::get_pending_reminders(&conn).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get a reminder with the appointment it announces; `None` once the appointment
+    /// moved away from the announced time or was cancelled
+    pub fn get_reminder_notification(
+        &self,
+        reminder_id: i64,
+    ) -> ServiceResult<Option<ReminderNotification>> {
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        db_appointments::get_reminder_notification(&conn, reminder_id)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Mark a reminder as sent
+    pub fn mark_reminder_sent(&self, reminder_id: i64, notification_id: &str) -> ServiceResult<()> {
+        let conn = get_connection().map_err(ServiceError::DatabaseError)?;
+        db_appointments::mark_reminder_sent(&conn, reminder_id, notification_id)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    fn ensure_exists(conn: &rusqlite::Connection, id: i64) -> ServiceResult<()> {
+        if db_appointments::appointment_exists(conn, id).map_err(ServiceError::DatabaseError)? {
+            Ok(())
+        } else {
+            Err(ServiceError::NotFound(format!("Appointment {} not found", id)))
+        }
+    }
+
+    fn ensure_series(conn: &rusqlite::Connection, id: i64) -> ServiceResult<()> {
+        Self::ensure_exists(conn, id)?;
+        let series =
+            db_appointments::get_appointment(conn, id).map_err(ServiceError::DatabaseError)?;
+
+        if series.recurrence_rule.is_none() {
+            return Err(ServiceError::ValidationError(format!(
+                "Appointment {} is not recurring",
+                id
+            )));
+        }
+        Ok(())
+    }
+
+    /// The practitioner and chair must be agenda resources of that kind
+    fn ensure_resources(
+        conn: &rusqlite::Connection,
+        appointment: &Appointment,
+    ) -> ServiceResult<()> {
+        let assigned = [
+            (
+                appointment.practitioner_id,
+                ResourceKind::Practitioner,
+                "practitioner",
+            ),
+            (appointment.chair_id, ResourceKind::Chair, "chair"),
+        ];
+
+        for (id, kind, label) in assigned {
+            let Some(id) = id else { continue };
+            let resource =
+                db_resources::get_resource(conn, id).map_err(ServiceError::DatabaseError)?;
+            if resource.is_none_or(|resource| resource.kind != kind) {
+                return Err(ServiceError::ValidationError(format!(
+                    "Agenda resource {} is not a {}",
+                    id, label
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn ensure_no_conflicts(
+        conn: &rusqlite::Connection,
+        appointment: &Appointment,
+        ignore: &[i64],
+    ) -> ServiceResult<()> {
+        let conflicts = db_appointments::find_conflicts(conn, appointment, ignore)
+            .map_err(ServiceError::DatabaseError)?;
+        if conflicts.is_empty() {
+            return Ok(());
+        }
+
+        let details = serde_json::to_value(&conflicts)
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+        Err(ServiceError::Conflict(
+            format!(
+                "The appointment overlaps {} existing appointment(s) in the same chair or with the same practitioner",
+                conflicts.len()
+            ),
+            details,
+        ))
+    }
+
+    fn validate(appointment: &Appointment) -> ServiceResult<()> {
+        if appointment.title.trim().is_empty() {
+            return Err(ServiceError::ValidationError(
+                "Appointment title is required".to_string(),
+            ));
+        }
+
+        let start = chrono::DateTime::parse_from_rfc3339(&appointment.start_time).map_err(|_| {
+            ServiceError::ValidationError("Start time must be an RFC 3339 date".to_string())
+        })?;
+        let end = chrono::DateTime::parse_from_rfc3339(&appointment.end_time).map_err(|_| {
+            ServiceError::ValidationError("End time must be an RFC 3339 date".to_string())
+        })?;
+
+        if end <= start {
+            return Err(ServiceError::ValidationError(
+                "End time must be after start time".to_string(),
+            ));
+        }
+
+        if let Some(ref rule) = appointment.recurrence_rule {
+            RecurrenceRule::parse(rule).map_err(ServiceError::ValidationError)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl DomainService for AppointmentService {
+    fn name(&self) -> &'static str {
+        "AppointmentService"
+    }
+}
+
+impl Default for AppointmentService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
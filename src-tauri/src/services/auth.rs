@@ -1,17 +1,28 @@
 // Authentication Service
-// Handles user authentication and JWT token generation
+// Handles user authentication, JWT access/refresh token generation and revocation
 
-use crate::db::{config, users::{self, User}};
+use crate::db::{config, tokens, users::{self, User}};
+use crate::passwords::{self, Verification};
+use crate::services::login_throttle::{LoginOrigin, LoginThrottle};
+use crate::services::{ServiceError, ServiceResult};
+use base64::{engine::general_purpose, Engine as _};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use rand_core::{OsRng, RngCore};
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
 use std::time::{SystemTime, UNIX_EPOCH};
+use utoipa::ToSchema;
 
 /// JWT expiration time (24 hours)
 const JWT_EXPIRATION_HOURS: u64 = 24;
 
-/// Fallback JWT secret if system password is not configured
-const FALLBACK_JWT_SECRET: &str = "NUEVO_GALENO_DEFAULT_SECRET_CHANGE_SYSTEM_PASSWORD";
+/// Refresh token expiration time (30 days)
+const REFRESH_EXPIRATION_DAYS: u64 = 30;
+
+/// app_config key holding the per-install JWT signing secret
+pub const JWT_SECRET_CONFIG_KEY: &str = "jwt_secret";
+
+const ACCESS_TOKEN_TYPE: &str = "access";
+const REFRESH_TOKEN_TYPE: &str = "refresh";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
@@ -20,17 +31,21 @@ pub struct Claims {
     pub role: String,     // User role
     pub exp: u64,         // Expiration time
     pub iat: u64,         // Issued at
+    pub jti: String,      // Token ID (used by the revocation list)
+    pub ver: i64,         // User token version (bumped by "revoke all sessions")
+    pub typ: String,      // "access" or "refresh"
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct LoginRequest {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct LoginResponse {
     pub token: String,
+    pub refresh_token: String,
     pub user: User,
 }
 
@@ -43,34 +58,158 @@ impl AuthService {
     }
 
     /// Authenticate user with username and password
-    /// Returns JWT token and user info
-    pub fn login(&self, username: String, password: String) -> Result<LoginResponse, String> {
-        // Hash the password
-        let password_hash = Self::hash_password(&password);
+    /// Returns JWT access and refresh tokens and user info
+    pub fn login(
+        &self,
+        username: String,
+        password: String,
+        origin: &LoginOrigin,
+    ) -> ServiceResult<LoginResponse> {
+        let user = self
+            .authenticate_attempt(&username, &password, origin)?
+            .ok_or_else(|| {
+                ServiceError::Unauthorized("Usuario o contraseña incorrectos".to_string())
+            })?;
 
-        // Get user from database
-        let (user, stored_hash) = users::get_user_by_username(&username)?
-            .ok_or_else(|| "Usuario no encontrado".to_string())?;
+        Self::issue_tokens(user).map_err(ServiceError::DatabaseError)
+    }
+
+    /// `authenticate` with brute-force protection
+    /// Locked out usernames/IPs are rejected before the password is checked, and every
+    /// failure counts towards a lockout and is written to the auth audit log.
+    pub fn authenticate_attempt(
+        &self,
+        username: &str,
+        password: &str,
+        origin: &LoginOrigin,
+    ) -> ServiceResult<Option<User>> {
+        let throttle = LoginThrottle::new();
+        throttle.check(username, origin)?;
+
+        match self.authenticate(username, password) {
+            Ok(Some(user)) => {
+                throttle.record_success(username, origin);
+                Ok(Some(user))
+            }
+            Ok(None) => {
+                throttle.record_failure(username, origin, "Usuario o contraseña incorrectos");
+                Ok(None)
+            }
+            Err(e) => {
+                throttle.record_failure(username, origin, &e);
+                Err(ServiceError::Unauthorized(e))
+            }
+        }
+    }
+
+    /// Check a user's PIN with the same throttling as passwords
+    pub fn authenticate_pin_attempt(
+        &self,
+        username: &str,
+        pin: &str,
+        origin: &LoginOrigin,
+    ) -> ServiceResult<Option<User>> {
+        let throttle = LoginThrottle::new();
+        throttle.check(username, origin)?;
+
+        match users::verify_user_pin(username, pin) {
+            Ok(Some(user)) => {
+                throttle.record_success(username, origin);
+                Ok(Some(user))
+            }
+            Ok(None) => {
+                throttle.record_failure(username, origin, "PIN incorrecto");
+                Ok(None)
+            }
+            Err(e) => {
+                throttle.record_failure(username, origin, &e);
+                Err(ServiceError::Unauthorized(e))
+            }
+        }
+    }
+
+    /// Check a username and plain-text password against the stored hash
+    /// Returns `None` for unknown users or wrong passwords and an error for inactive users.
+    /// Legacy SHA-256 hashes are replaced with Argon2id on the first successful check.
+    pub fn authenticate(&self, username: &str, password: &str) -> Result<Option<User>, String> {
+        let Some((user, stored_hash)) = users::get_user_by_username(username)? else {
+            return Ok(None);
+        };
 
-        // Verify user is active
         if !user.active {
             return Err("Usuario inactivo".to_string());
         }
 
-        // Verify password
-        if stored_hash != password_hash {
-            return Err("Contraseña incorrecta".to_string());
+        match passwords::verify_password(password, &stored_hash) {
+            Verification::Valid => Ok(Some(user)),
+            Verification::ValidLegacy => {
+                let new_hash = passwords::hash_secret(password)?;
+                users::update_user_password(username, &new_hash)?;
+                Ok(Some(user))
+            }
+            Verification::Invalid => Ok(None),
         }
+    }
 
-        // Generate JWT token
-        let token = Self::generate_jwt(&user)?;
+    /// Verify JWT access token and return user info
+    pub fn verify_token(&self, token: &str) -> Result<User, String> {
+        let claims = Self::decode_jwt(token, ACCESS_TOKEN_TYPE)?;
+        Self::validate_claims(&claims)
+    }
+
+    /// Exchange a refresh token for a new access/refresh token pair
+    /// The used refresh token is revoked so it cannot be replayed.
+    pub fn refresh(&self, refresh_token: &str) -> Result<LoginResponse, String> {
+        let claims = Self::decode_jwt(refresh_token, REFRESH_TOKEN_TYPE)?;
+        let user = Self::validate_claims(&claims)?;
 
-        Ok(LoginResponse { token, user })
+        tokens::revoke_token(&claims.jti, claims.user_id, claims.exp)?;
+        Self::issue_tokens(user)
     }
 
-    /// Verify JWT token and return user info
-    pub fn verify_token(&self, token: &str) -> Result<User, String> {
-        let claims = Self::decode_jwt(token)?;
+    /// Revoke an access token and, optionally, the refresh token issued with it
+    pub fn logout(&self, token: &str, refresh_token: Option<&str>) -> Result<(), String> {
+        let claims = Self::decode_jwt(token, ACCESS_TOKEN_TYPE)?;
+        tokens::revoke_token(&claims.jti, claims.user_id, claims.exp)?;
+
+        if let Some(refresh_token) = refresh_token {
+            let refresh_claims = Self::decode_jwt(refresh_token, REFRESH_TOKEN_TYPE)?;
+            if refresh_claims.user_id != claims.user_id {
+                return Err("El refresh token no pertenece al usuario".to_string());
+            }
+            tokens::revoke_token(&refresh_claims.jti, refresh_claims.user_id, refresh_claims.exp)?;
+        }
+
+        Ok(())
+    }
+
+    /// Invalidate every access and refresh token issued to a user
+    pub fn revoke_all_sessions(&self, user_id: i64) -> Result<(), String> {
+        tokens::increment_token_version(user_id)
+    }
+
+    /// Replace the JWT signing secret, invalidating every issued token
+    pub fn rotate_secret(&self) -> Result<(), String> {
+        config::set_config(JWT_SECRET_CONFIG_KEY, &Self::generate_secret())
+    }
+
+    /// Get the per-install JWT secret, generating it on first use
+    fn get_jwt_secret() -> Result<String, String> {
+        config::get_or_init_config(JWT_SECRET_CONFIG_KEY, Self::generate_secret)
+    }
+
+    /// Generate a random 256-bit secret
+    fn generate_secret() -> String {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        general_purpose::STANDARD.encode(bytes)
+    }
+
+    /// Check that the claims still belong to an active user and were not revoked
+    fn validate_claims(claims: &Claims) -> Result<User, String> {
+        if tokens::is_token_revoked(&claims.jti)? {
+            return Err("Token revocado".to_string());
+        }
 
         // Get user from database to ensure it still exists and is active
         let (user, _) = users::get_user_by_username(&claims.sub)?
@@ -80,28 +219,38 @@ impl AuthService {
             return Err("Usuario inactivo".to_string());
         }
 
+        if user.id != claims.user_id || tokens::get_token_version(user.id)? != claims.ver {
+            return Err("Token revocado".to_string());
+        }
+
         Ok(user)
     }
 
-    /// Get JWT secret from system password
-    /// Uses system password as secret for enhanced security
-    /// Falls back to default secret if system password is not configured
-    fn get_jwt_secret() -> String {
-        config::get_config("system_password")
-            .ok()
-            .flatten()
-            .unwrap_or_else(|| FALLBACK_JWT_SECRET.to_string())
-    }
+    /// Generate an access/refresh token pair for a user
+    fn issue_tokens(user: User) -> Result<LoginResponse, String> {
+        let version = tokens::get_token_version(user.id)?;
+        let token = Self::generate_jwt(&user, version, ACCESS_TOKEN_TYPE, JWT_EXPIRATION_HOURS * 3600)?;
+        let refresh_token = Self::generate_jwt(
+            &user,
+            version,
+            REFRESH_TOKEN_TYPE,
+            REFRESH_EXPIRATION_DAYS * 24 * 3600,
+        )?;
 
-    /// Hash password using SHA256
-    fn hash_password(password: &str) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(password.as_bytes());
-        format!("{:x}", hasher.finalize())
+        Ok(LoginResponse {
+            token,
+            refresh_token,
+            user,
+        })
     }
 
     /// Generate JWT token for user
-    fn generate_jwt(user: &User) -> Result<String, String> {
+    fn generate_jwt(
+        user: &User,
+        version: i64,
+        token_type: &str,
+        expires_in_secs: u64,
+    ) -> Result<String, String> {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map_err(|e| format!("Error obteniendo tiempo: {}", e))?
@@ -111,12 +260,15 @@ impl AuthService {
             sub: user.username.clone(),
             user_id: user.id,
             role: user.role.clone(),
-            exp: now + (JWT_EXPIRATION_HOURS * 3600),
+            exp: now + expires_in_secs,
             iat: now,
+            jti: uuid::Uuid::new_v4().to_string(),
+            ver: version,
+            typ: token_type.to_string(),
         };
 
-        let secret = Self::get_jwt_secret();
-        
+        let secret = Self::get_jwt_secret()?;
+
         encode(
             &Header::default(),
             &claims,
@@ -125,32 +277,23 @@ impl AuthService {
         .map_err(|e| format!("Error generando token: {}", e))
     }
 
-    /// Decode and validate JWT token
-    fn decode_jwt(token: &str) -> Result<Claims, String> {
-        let secret = Self::get_jwt_secret();
+    /// Decode and validate JWT token of the expected type
+    fn decode_jwt(token: &str, token_type: &str) -> Result<Claims, String> {
+        let secret = Self::get_jwt_secret()?;
         let validation = Validation::default();
 
-        decode::<Claims>(
+        let claims = decode::<Claims>(
             token,
             &DecodingKey::from_secret(secret.as_bytes()),
             &validation,
         )
         .map(|data| data.claims)
-        .map_err(|e| format!("Token inválido: {}", e))
-    }
-}
+        .map_err(|e| format!("Token inválido: {}", e))?;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_hash_password() {
-        let hash1 = AuthService::hash_password("test123");
-        let hash2 = AuthService::hash_password("test123");
-        assert_eq!(hash1, hash2);
+        if claims.typ != token_type {
+            return Err("Token inválido: tipo de token incorrecto".to_string());
+        }
 
-        let hash3 = AuthService::hash_password("different");
-        assert_ne!(hash1, hash3);
+        Ok(claims)
     }
 }
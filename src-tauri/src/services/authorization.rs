@@ -0,0 +1,129 @@
+// Authorization Service
+// Maps user roles to the permissions required by mutating operations
+// Independent of Tauri and HTTP - both adapters ask this module before calling other services
+
+use crate::db::users::User;
+use crate::services::{ServiceError, ServiceResult};
+
+/// Operations that require more than being authenticated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    /// Create and update patients
+    WritePatients,
+    /// Create and update treatments and odontograms
+    WriteClinical,
+    /// Create, move and update appointments
+    ManageAppointments,
+    /// Register new payments
+    RecordPayments,
+    /// Modify existing payments
+    EditPayments,
+    /// Manage the treatment catalog
+    ManageCatalog,
+    /// Delete patients, treatments, payments, appointments and catalog entries
+    DeleteRecords,
+    /// Revoke other users' sessions and rotate the JWT signing secret
+    ManageSecurity,
+}
+
+impl Permission {
+    /// Stable identifier used in error bodies
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::WritePatients => "patients.write",
+            Permission::WriteClinical => "clinical.write",
+            Permission::ManageAppointments => "appointments.write",
+            Permission::RecordPayments => "payments.record",
+            Permission::EditPayments => "payments.edit",
+            Permission::ManageCatalog => "catalog.manage",
+            Permission::DeleteRecords => "records.delete",
+            Permission::ManageSecurity => "security.manage",
+        }
+    }
+}
+
+/// Check whether a role grants a permission
+///
+/// Unknown roles only get read access.
+pub fn role_has_permission(role: &str, permission: Permission) -> bool {
+    use Permission::*;
+
+    match role.trim().to_lowercase().as_str() {
+        "admin" => true,
+        "doctor" => matches!(
+            permission,
+            WritePatients | WriteClinical | ManageAppointments | RecordPayments
+        ),
+        "assistant" | "receptionist" => matches!(
+            permission,
+            WritePatients | ManageAppointments | RecordPayments
+        ),
+        "finance" => matches!(permission, RecordPayments | EditPayments),
+        _ => false,
+    }
+}
+
+/// Check whether a paired client node may use a permission
+///
+/// The host doesn't know which user is signed in on a paired node, so deletes and
+/// security settings stay with the host's own users and the static token.
+pub fn paired_node_has_permission(permission: Permission) -> bool {
+    !matches!(permission, Permission::ManageSecurity | Permission::DeleteRecords)
+}
+
+/// Ensure a user is allowed to perform an operation
+pub fn authorize(user: &User, permission: Permission) -> ServiceResult<()> {
+    if role_has_permission(&user.role, permission) {
+        Ok(())
+    } else {
+        Err(ServiceError::Forbidden(format!(
+            "Role '{}' lacks permission '{}'",
+            user.role,
+            permission.as_str()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admin_has_every_permission() {
+        for permission in [
+            Permission::WritePatients,
+            Permission::WriteClinical,
+            Permission::ManageAppointments,
+            Permission::RecordPayments,
+            Permission::EditPayments,
+            Permission::ManageCatalog,
+            Permission::DeleteRecords,
+            Permission::ManageSecurity,
+        ] {
+            assert!(role_has_permission("admin", permission));
+        }
+    }
+
+    #[test]
+    fn deletes_are_admin_only() {
+        for role in ["doctor", "assistant", "receptionist", "finance", "user"] {
+            assert!(!role_has_permission(role, Permission::DeleteRecords));
+        }
+    }
+
+    #[test]
+    fn paired_nodes_cannot_delete_or_manage_security() {
+        assert!(paired_node_has_permission(Permission::WritePatients));
+        assert!(paired_node_has_permission(Permission::EditPayments));
+        assert!(!paired_node_has_permission(Permission::DeleteRecords));
+        assert!(!paired_node_has_permission(Permission::ManageSecurity));
+    }
+
+    #[test]
+    fn payment_edits_are_finance_only() {
+        assert!(role_has_permission("finance", Permission::EditPayments));
+        assert!(!role_has_permission("doctor", Permission::EditPayments));
+        assert!(!role_has_permission("receptionist", Permission::EditPayments));
+        assert!(!role_has_permission("finance", Permission::WritePatients));
+    }
+}
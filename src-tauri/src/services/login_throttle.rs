@@ -0,0 +1,284 @@
+// Login Throttling Service
+// Counts failed logins per username and per client IP. Too many failures inside the window
+// lock the username (or IP) out for a while. API failures count against the username from
+// that IP only, so remote clients cannot lock a user out of the desktop app. Failures, lockouts and attempts rejected while
+// locked out are written to the auth audit log.
+
+use crate::db::auth_audit::{self, AuthAuditEntry};
+use crate::services::{DomainService, ServiceError, ServiceResult};
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+
+/// Failures are counted within this window
+const FAILURE_WINDOW_MINUTES: i64 = 15;
+
+/// Failed attempts for one username before it is locked
+const MAX_FAILURES_PER_USER: u32 = 5;
+
+/// Failed attempts from one IP (any username) before it is locked
+const MAX_FAILURES_PER_IP: u32 = 20;
+
+/// Duration of a lockout
+const LOCKOUT_MINUTES: i64 = 15;
+
+const DEFAULT_AUDIT_LIMIT: i64 = 200;
+const MAX_AUDIT_LIMIT: i64 = 1000;
+
+pub const EVENT_LOGIN_FAILED: &str = "login_failed";
+pub const EVENT_LOCKOUT: &str = "lockout";
+pub const EVENT_LOGIN_BLOCKED: &str = "login_blocked";
+
+/// Where a login attempt comes from
+#[derive(Debug, Clone)]
+pub struct LoginOrigin {
+    /// "api" or "desktop"
+    pub source: &'static str,
+    /// Client IP for API logins; desktop logins are only throttled per username
+    pub ip: Option<IpAddr>,
+}
+
+impl LoginOrigin {
+    pub fn desktop() -> Self {
+        Self {
+            source: "desktop",
+            ip: None,
+        }
+    }
+
+    pub fn api(ip: Option<IpAddr>) -> Self {
+        Self { source: "api", ip }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FailureCounter {
+    failures: u32,
+    window_start: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+static COUNTERS: Lazy<Mutex<HashMap<String, FailureCounter>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Login throttling service
+pub struct LoginThrottle;
+
+impl LoginThrottle {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Reject the attempt while the username or the client IP is locked out
+    pub fn check(&self, username: &str, origin: &LoginOrigin) -> ServiceResult<()> {
+        let now = Utc::now();
+        let locked_until = {
+            let counters = COUNTERS.lock().unwrap();
+            Self::keys(username, origin)
+                .iter()
+                .filter_map(|key| counters.get(key).and_then(|counter| counter.locked_until))
+                .filter(|until| *until > now)
+                .max()
+        };
+
+        let Some(until) = locked_until else {
+            return Ok(());
+        };
+
+        let minutes = ((until - now).num_seconds() + 59) / 60;
+        Self::audit(username, origin, EVENT_LOGIN_BLOCKED, None);
+
+        Err(ServiceError::TooManyRequests(format!(
+            "Demasiados intentos fallidos. Intente nuevamente en {} minuto(s)",
+            minutes.max(1)
+        )))
+    }
+
+    /// Count a failed attempt, locking the username or IP once over the limit
+    pub fn record_failure(&self, username: &str, origin: &LoginOrigin, reason: &str) {
+        let now = Utc::now();
+        let mut locked = Vec::new();
+
+        {
+            let mut counters = COUNTERS.lock().unwrap();
+            Self::purge_stale(&mut counters, now);
+
+            for key in Self::keys(username, origin) {
+                let limit = if key.starts_with("ip:") {
+                    MAX_FAILURES_PER_IP
+                } else {
+                    MAX_FAILURES_PER_USER
+                };
+
+                let counter = counters.entry(key.clone()).or_insert(FailureCounter {
+                    failures: 0,
+                    window_start: now,
+                    locked_until: None,
+                });
+
+                if now - counter.window_start > Duration::minutes(FAILURE_WINDOW_MINUTES) {
+                    counter.failures = 0;
+                    counter.window_start = now;
+                }
+
+                counter.failures += 1;
+                if counter.failures >= limit {
+                    counter.locked_until = Some(now + Duration::minutes(LOCKOUT_MINUTES));
+                    counter.failures = 0;
+                    counter.window_start = now;
+                    locked.push(key);
+                }
+            }
+        }
+
+        Self::audit(username, origin, EVENT_LOGIN_FAILED, Some(reason));
+        for key in locked {
+            log::warn!("Login locked out for {} ({} minutes)", key, LOCKOUT_MINUTES);
+            Self::audit(username, origin, EVENT_LOCKOUT, Some(&key));
+        }
+    }
+
+    /// Forget the username's failures from this origin after a successful login
+    /// The IP counter is kept so one valid account cannot be used to reset it.
+    pub fn record_success(&self, username: &str, origin: &LoginOrigin) {
+        COUNTERS
+            .lock()
+            .unwrap()
+            .remove(&Self::user_key(username, origin));
+    }
+
+    /// Most recent auth audit entries, newest first
+    pub fn audit_log(&self, limit: Option<i64>) -> ServiceResult<Vec<AuthAuditEntry>> {
+        let limit = limit.unwrap_or(DEFAULT_AUDIT_LIMIT).clamp(1, MAX_AUDIT_LIMIT);
+        auth_audit::list_auth_events(limit).map_err(ServiceError::DatabaseError)
+    }
+
+    fn keys(username: &str, origin: &LoginOrigin) -> Vec<String> {
+        let mut keys = vec![Self::user_key(username, origin)];
+        if let Some(ip) = origin.ip {
+            keys.push(format!("ip:{}", ip));
+        }
+        keys
+    }
+
+    /// Desktop logins share one counter per username; API logins get one per (username, IP)
+    fn user_key(username: &str, origin: &LoginOrigin) -> String {
+        let username = username.trim().to_lowercase();
+        match origin.source {
+            "desktop" => format!("user:{}", username),
+            source => match origin.ip {
+                Some(ip) => format!("user:{}@{}", username, ip),
+                None => format!("user:{}@{}", username, source),
+            },
+        }
+    }
+
+    /// Drop counters whose window and lockout are both over
+    fn purge_stale(counters: &mut HashMap<String, FailureCounter>, now: DateTime<Utc>) {
+        counters.retain(|_, counter| {
+            counter.locked_until.is_some_and(|until| until > now)
+                || now - counter.window_start <= Duration::minutes(FAILURE_WINDOW_MINUTES)
+        });
+    }
+
+    /// Auditing must never block a login, so failures are only logged
+    fn audit(username: &str, origin: &LoginOrigin, event: &str, detail: Option<&str>) {
+        let ip = origin.ip.map(|ip| ip.to_string());
+        if let Err(e) =
+            auth_audit::record_auth_event(username, ip.as_deref(), origin.source, event, detail)
+        {
+            log::error!("Failed to write auth audit entry: {}", e);
+        }
+    }
+}
+
+impl DomainService for LoginThrottle {
+    fn name(&self) -> &'static str {
+        "LoginThrottle"
+    }
+}
+
+impl Default for LoginThrottle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn username_is_locked_after_repeated_failures() {
+        let throttle = LoginThrottle::new();
+        let origin = LoginOrigin::desktop();
+        let username = "throttle-test-user";
+
+        for _ in 0..MAX_FAILURES_PER_USER - 1 {
+            throttle.record_failure(username, &origin, "test");
+            assert!(throttle.check(username, &origin).is_ok());
+        }
+
+        throttle.record_failure(username, &origin, "test");
+        assert!(matches!(
+            throttle.check("Throttle-Test-User", &origin),
+            Err(ServiceError::TooManyRequests(_))
+        ));
+        assert!(throttle.check("another-user", &origin).is_ok());
+    }
+
+    #[test]
+    fn success_resets_the_username_counter() {
+        let throttle = LoginThrottle::new();
+        let origin = LoginOrigin::desktop();
+        let username = "throttle-reset-user";
+
+        for _ in 0..MAX_FAILURES_PER_USER - 1 {
+            throttle.record_failure(username, &origin, "test");
+        }
+        throttle.record_success(username, &origin);
+        throttle.record_failure(username, &origin, "test");
+
+        assert!(throttle.check(username, &origin).is_ok());
+    }
+
+    #[test]
+    fn ip_is_locked_across_usernames() {
+        let throttle = LoginThrottle::new();
+        let origin = LoginOrigin::api(Some("203.0.113.7".parse().unwrap()));
+
+        for attempt in 0..MAX_FAILURES_PER_IP {
+            throttle.record_failure(&format!("spray-{}", attempt), &origin, "test");
+        }
+
+        assert!(matches!(
+            throttle.check("fresh-user", &origin),
+            Err(ServiceError::TooManyRequests(_))
+        ));
+        assert!(throttle
+            .check("fresh-user", &LoginOrigin::api(Some("203.0.113.8".parse().unwrap())))
+            .is_ok());
+    }
+
+    #[test]
+    fn api_failures_do_not_lock_the_desktop_login() {
+        let throttle = LoginThrottle::new();
+        let remote = LoginOrigin::api(Some("198.51.100.4".parse().unwrap()));
+        let username = "throttle-remote-user";
+
+        for _ in 0..MAX_FAILURES_PER_USER {
+            throttle.record_failure(username, &remote, "test");
+        }
+
+        assert!(matches!(
+            throttle.check(username, &remote),
+            Err(ServiceError::TooManyRequests(_))
+        ));
+        assert!(throttle.check(username, &LoginOrigin::desktop()).is_ok());
+        assert!(throttle
+            .check(username, &LoginOrigin::api(Some("198.51.100.5".parse().unwrap())))
+            .is_ok());
+    }
+}
@@ -2,8 +2,17 @@
 // This layer contains all business logic and is independent of Tauri and HTTP adapters
 // Both tauri::command and HTTP routes should call these services
 
+pub mod agenda_resources;
+pub mod appointments;
 pub mod auth;
+pub mod authorization;
+pub mod login_throttle;
+pub mod odontograms;
+pub mod pairing;
 pub mod patients;
+pub mod payments;
+pub mod treatment_catalog;
+pub mod treatments;
 
 
 
@@ -17,6 +26,10 @@ pub enum ServiceError {
     ValidationError(String),
     DatabaseError(String),
     Unauthorized(String),
+    Forbidden(String),
+    TooManyRequests(String),
+    /// The change clashes with existing data; the details are sent to the client
+    Conflict(String, serde_json::Value),
 }
 
 impl std::fmt::Display for ServiceError {
@@ -26,6 +39,13 @@ impl std::fmt::Display for ServiceError {
             ServiceError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
             ServiceError::DatabaseError(msg) => write!(f, "Database error: {}", msg),
             ServiceError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            ServiceError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
+            ServiceError::TooManyRequests(msg) => write!(f, "Too many requests: {}", msg),
+            ServiceError::Conflict(msg, details) => write!(
+                f,
+                "Conflict: {}",
+                serde_json::json!({ "message": msg, "conflicts": details })
+            ),
         }
     }
 }
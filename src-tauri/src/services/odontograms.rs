@@ -0,0 +1,283 @@
+// Odontogram Domain Service
+// Contains all business logic for tooth conditions, surfaces, whole-tooth treatments and bridges
+// Independent of Tauri and HTTP - pure business logic
+
+use crate::db::odontogram_surfaces::{
+    self as db_surfaces, AddSurfaceTreatmentInput, OdontogramSurface, SurfaceHistoryEntry,
+    UpdateSurfaceInput,
+};
+use crate::db::odontogram_tooth_treatments::{
+    self as db_tooth_treatments, AddBridgeInput, AddToothTreatmentInput, OdontogramBridge,
+    OdontogramToothTreatment, ToothTreatmentHistoryEntry,
+};
+use crate::db::odontograms::{self as db_odontograms, OdontogramEntry, UpdateToothConditionInput};
+use crate::services::{DomainService, ServiceError, ServiceResult};
+
+/// Odontogram service handles all odontogram-related business logic
+pub struct OdontogramService;
+
+impl OdontogramService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    // ===== TOOTH CONDITIONS =====
+
+    /// Get the odontogram of a patient
+    pub fn get_by_patient(&self, patient_id: i64) -> ServiceResult<Vec<OdontogramEntry>> {
+        db_odontograms::get_odontogram_by_patient(patient_id).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get the condition of a single tooth
+    pub fn get_tooth(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> ServiceResult<Option<OdontogramEntry>> {
+        Self::validate_tooth_number(tooth_number)?;
+        db_odontograms::get_tooth_by_patient_and_number(patient_id, tooth_number)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Create or update the condition of a tooth
+    pub fn update_tooth_condition(&self, input: UpdateToothConditionInput) -> ServiceResult<i64> {
+        Self::validate_tooth_number(&input.tooth_number)?;
+        Self::validate_condition(&input.condition)?;
+        db_odontograms::update_tooth_condition(input).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Remove the condition of a tooth
+    pub fn delete_tooth_condition(&self, patient_id: i64, tooth_number: &str) -> ServiceResult<()> {
+        Self::validate_tooth_number(tooth_number)?;
+        db_odontograms::delete_tooth_condition(patient_id, tooth_number)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Remove every tooth condition of a patient
+    pub fn clear_patient_odontogram(&self, patient_id: i64) -> ServiceResult<()> {
+        db_odontograms::clear_patient_odontogram(patient_id).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get the condition history of a tooth
+    pub fn get_tooth_history(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> ServiceResult<Vec<OdontogramEntry>> {
+        Self::validate_tooth_number(tooth_number)?;
+        db_odontograms::get_tooth_history(patient_id, tooth_number)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    // ===== SURFACES =====
+
+    /// Get all active surfaces of a patient
+    pub fn get_surfaces_by_patient(&self, patient_id: i64) -> ServiceResult<Vec<OdontogramSurface>> {
+        db_surfaces::get_odontogram_surfaces_by_patient(patient_id)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get all active surfaces of a tooth
+    pub fn get_tooth_surfaces(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> ServiceResult<Vec<OdontogramSurface>> {
+        Self::validate_tooth_number(tooth_number)?;
+        db_surfaces::get_tooth_surfaces(patient_id, tooth_number)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get the active treatments of a single surface
+    pub fn get_surface_treatments(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+        surface: &str,
+    ) -> ServiceResult<Vec<OdontogramSurface>> {
+        Self::validate_tooth_number(tooth_number)?;
+        Self::validate_surface(surface)?;
+        db_surfaces::get_surface_treatments(patient_id, tooth_number, surface)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Add a treatment to a surface
+    pub fn add_surface_treatment(&self, input: AddSurfaceTreatmentInput) -> ServiceResult<i64> {
+        Self::validate_tooth_number(&input.tooth_number)?;
+        Self::validate_surface(&input.surface)?;
+        Self::validate_condition(&input.condition)?;
+        db_surfaces::add_tooth_surface_treatment(input).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Update a surface (adds a new treatment to it)
+    pub fn update_surface(&self, input: UpdateSurfaceInput) -> ServiceResult<i64> {
+        Self::validate_tooth_number(&input.tooth_number)?;
+        Self::validate_surface(&input.surface)?;
+        Self::validate_condition(&input.condition)?;
+        db_surfaces::update_tooth_surface(input).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Deactivate a surface treatment
+    pub fn deactivate_surface_treatment(&self, surface_id: i64) -> ServiceResult<()> {
+        db_surfaces::deactivate_surface_treatment(surface_id).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Physically delete a surface
+    pub fn delete_surface(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+        surface: &str,
+    ) -> ServiceResult<()> {
+        Self::validate_tooth_number(tooth_number)?;
+        Self::validate_surface(surface)?;
+        db_surfaces::delete_tooth_surface(patient_id, tooth_number, surface)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Physically delete every surface of a tooth
+    pub fn clear_tooth_surfaces(&self, patient_id: i64, tooth_number: &str) -> ServiceResult<()> {
+        Self::validate_tooth_number(tooth_number)?;
+        db_surfaces::clear_tooth_surfaces(patient_id, tooth_number)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get the history of a single surface
+    pub fn get_surface_history(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+        surface: &str,
+    ) -> ServiceResult<Vec<SurfaceHistoryEntry>> {
+        Self::validate_tooth_number(tooth_number)?;
+        Self::validate_surface(surface)?;
+        db_surfaces::get_surface_history(patient_id, tooth_number, surface)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get the surface history of a whole tooth
+    pub fn get_tooth_surface_history(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> ServiceResult<Vec<SurfaceHistoryEntry>> {
+        Self::validate_tooth_number(tooth_number)?;
+        db_surfaces::get_tooth_history(patient_id, tooth_number)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    // ===== WHOLE-TOOTH TREATMENTS =====
+
+    /// Get the active whole-tooth treatments of a tooth
+    pub fn get_tooth_treatments(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> ServiceResult<Vec<OdontogramToothTreatment>> {
+        Self::validate_tooth_number(tooth_number)?;
+        db_tooth_treatments::get_tooth_treatments(patient_id, tooth_number)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get the active whole-tooth treatments of a patient
+    pub fn get_tooth_treatments_by_patient(
+        &self,
+        patient_id: i64,
+    ) -> ServiceResult<Vec<OdontogramToothTreatment>> {
+        db_tooth_treatments::get_tooth_treatments_by_patient(patient_id)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Add a whole-tooth treatment
+    pub fn add_tooth_treatment(&self, input: AddToothTreatmentInput) -> ServiceResult<i64> {
+        Self::validate_tooth_number(&input.tooth_number)?;
+        Self::validate_condition(&input.condition)?;
+        db_tooth_treatments::add_tooth_treatment(input).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Deactivate a whole-tooth treatment
+    pub fn deactivate_tooth_treatment(&self, treatment_id: i64) -> ServiceResult<()> {
+        db_tooth_treatments::deactivate_tooth_treatment(treatment_id)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get the whole-tooth treatment history of a tooth
+    pub fn get_tooth_treatment_history(
+        &self,
+        patient_id: i64,
+        tooth_number: &str,
+    ) -> ServiceResult<Vec<ToothTreatmentHistoryEntry>> {
+        Self::validate_tooth_number(tooth_number)?;
+        db_tooth_treatments::get_tooth_treatment_history(patient_id, tooth_number)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    // ===== BRIDGES =====
+
+    /// Get the active bridges of a patient
+    pub fn get_bridges_by_patient(&self, patient_id: i64) -> ServiceResult<Vec<OdontogramBridge>> {
+        db_tooth_treatments::get_bridges_by_patient(patient_id).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Add a bridge
+    pub fn add_bridge(&self, input: AddBridgeInput) -> ServiceResult<i64> {
+        if input.bridge_name.trim().is_empty() {
+            return Err(ServiceError::ValidationError(
+                "Bridge name is required".to_string(),
+            ));
+        }
+        Self::validate_tooth_number(&input.tooth_start)?;
+        Self::validate_tooth_number(&input.tooth_end)?;
+        if input.tooth_start == input.tooth_end {
+            return Err(ServiceError::ValidationError(
+                "A bridge must span at least two teeth".to_string(),
+            ));
+        }
+
+        db_tooth_treatments::add_bridge(input).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Deactivate a bridge
+    pub fn deactivate_bridge(&self, bridge_id: i64) -> ServiceResult<()> {
+        db_tooth_treatments::deactivate_bridge(bridge_id).map_err(ServiceError::DatabaseError)
+    }
+
+    fn validate_tooth_number(tooth_number: &str) -> ServiceResult<()> {
+        if tooth_number.trim().is_empty() {
+            return Err(ServiceError::ValidationError(
+                "Tooth number is required".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn validate_surface(surface: &str) -> ServiceResult<()> {
+        if surface.trim().is_empty() {
+            return Err(ServiceError::ValidationError(
+                "Surface is required".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn validate_condition(condition: &str) -> ServiceResult<()> {
+        if condition.trim().is_empty() {
+            return Err(ServiceError::ValidationError(
+                "Condition is required".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl DomainService for OdontogramService {
+    fn name(&self) -> &'static str {
+        "OdontogramService"
+    }
+}
+
+impl Default for OdontogramService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,343 @@
+// Node Pairing Service
+// Lets a client node join a host without copying the API token by hand. The client
+// requests to join, both screens show the same short code, and once the host operator
+// approves the request the host issues a credential that belongs to that client only.
+// Paired nodes are kept in the database and can be revoked one by one.
+
+use crate::db::paired_nodes::{self, PairedNode};
+use crate::services::{DomainService, ServiceError, ServiceResult};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use utoipa::ToSchema;
+
+/// Minutes a pairing request waits for the host operator
+const REQUEST_TTL_MINUTES: i64 = 10;
+
+/// Pending requests kept at once, so an open host cannot be flooded
+const MAX_PENDING_REQUESTS: usize = 16;
+
+const MAX_NODE_NAME_LEN: usize = 64;
+
+/// Prefix of node credentials, to tell them apart from JWTs without a database lookup
+pub const CREDENTIAL_PREFIX: &str = "gpn_";
+
+/// Returned to the client that asked to join
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PairingTicket {
+    pub request_id: String,
+    /// Code shown on both nodes so the operator can match them
+    pub code: String,
+    /// Secret the client must present to collect its credential
+    pub poll_token: String,
+    pub expires_at: String,
+}
+
+/// Pending request as shown on the host
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PairingRequest {
+    pub request_id: String,
+    pub node_name: String,
+    pub code: String,
+    pub requested_at: String,
+    pub expires_at: String,
+}
+
+/// State of a request as seen by the client
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PairingStatus {
+    Pending,
+    Approved { node_id: i64, credential: String },
+    Rejected,
+}
+
+struct PendingEntry {
+    request: PairingRequest,
+    poll_token: String,
+    expires_at: DateTime<Utc>,
+    status: PairingStatus,
+}
+
+static PENDING: Lazy<Mutex<HashMap<String, PendingEntry>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Pairing service
+pub struct PairingService;
+
+impl PairingService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Register a join request from a client node
+    pub fn request_pairing(&self, node_name: &str) -> ServiceResult<PairingTicket> {
+        let node_name = node_name.trim();
+        if node_name.is_empty() {
+            return Err(ServiceError::ValidationError(
+                "El nombre del nodo es obligatorio".to_string(),
+            ));
+        }
+        if node_name.chars().count() > MAX_NODE_NAME_LEN {
+            return Err(ServiceError::ValidationError(format!(
+                "El nombre del nodo no puede superar {} caracteres",
+                MAX_NODE_NAME_LEN
+            )));
+        }
+
+        let mut pending = PENDING.lock().unwrap();
+        Self::purge_expired(&mut pending);
+
+        if pending.len() >= MAX_PENDING_REQUESTS {
+            return Err(ServiceError::ValidationError(
+                "Hay demasiadas solicitudes de emparejamiento pendientes".to_string(),
+            ));
+        }
+
+        let now = Utc::now();
+        let expires_at = now + Duration::minutes(REQUEST_TTL_MINUTES);
+        let request = PairingRequest {
+            request_id: uuid::Uuid::new_v4().to_string(),
+            node_name: node_name.to_string(),
+            code: Self::generate_code(),
+            requested_at: now.to_rfc3339(),
+            expires_at: expires_at.to_rfc3339(),
+        };
+        let ticket = PairingTicket {
+            request_id: request.request_id.clone(),
+            code: request.code.clone(),
+            poll_token: Self::random_token(),
+            expires_at: request.expires_at.clone(),
+        };
+
+        pending.insert(
+            request.request_id.clone(),
+            PendingEntry {
+                request,
+                poll_token: ticket.poll_token.clone(),
+                expires_at,
+                status: PairingStatus::Pending,
+            },
+        );
+
+        Ok(ticket)
+    }
+
+    /// Requests still waiting for the host operator
+    pub fn list_pending_requests(&self) -> Vec<PairingRequest> {
+        let mut pending = PENDING.lock().unwrap();
+        Self::purge_expired(&mut pending);
+
+        let mut requests: Vec<PairingRequest> = pending
+            .values()
+            .filter(|entry| matches!(entry.status, PairingStatus::Pending))
+            .map(|entry| entry.request.clone())
+            .collect();
+        requests.sort_by(|a, b| a.requested_at.cmp(&b.requested_at));
+        requests
+    }
+
+    /// Approve a request: the node is registered and its credential waits for the client
+    pub fn approve_request(&self, request_id: &str) -> ServiceResult<i64> {
+        let mut pending = PENDING.lock().unwrap();
+        Self::purge_expired(&mut pending);
+
+        let entry = Self::pending_entry(&mut pending, request_id)?;
+
+        let credential = format!("{}{}", CREDENTIAL_PREFIX, Self::random_token());
+        let node_id = paired_nodes::create_paired_node(
+            &entry.request.node_name,
+            &Self::hash_credential(&credential),
+        )
+        .map_err(ServiceError::DatabaseError)?;
+
+        entry.status = PairingStatus::Approved {
+            node_id,
+            credential,
+        };
+        log::info!(
+            "Paired node '{}' (id {})",
+            entry.request.node_name,
+            node_id
+        );
+
+        Ok(node_id)
+    }
+
+    pub fn reject_request(&self, request_id: &str) -> ServiceResult<()> {
+        let mut pending = PENDING.lock().unwrap();
+        Self::purge_expired(&mut pending);
+
+        Self::pending_entry(&mut pending, request_id)?.status = PairingStatus::Rejected;
+        Ok(())
+    }
+
+    /// Status of a request for the client that created it
+    /// An approved credential is handed out once; the request is forgotten afterwards.
+    pub fn poll_request(&self, request_id: &str, poll_token: &str) -> ServiceResult<PairingStatus> {
+        let mut pending = PENDING.lock().unwrap();
+        Self::purge_expired(&mut pending);
+
+        let entry = pending.get(request_id).ok_or_else(|| {
+            ServiceError::NotFound("Solicitud de emparejamiento no encontrada o expirada".to_string())
+        })?;
+
+        if !bool::from(entry.poll_token.as_bytes().ct_eq(poll_token.as_bytes())) {
+            return Err(ServiceError::Unauthorized(
+                "Token de solicitud inválido".to_string(),
+            ));
+        }
+
+        match entry.status {
+            PairingStatus::Pending => Ok(PairingStatus::Pending),
+            _ => Ok(pending.remove(request_id).unwrap().status),
+        }
+    }
+
+    pub fn list_paired_nodes(&self) -> ServiceResult<Vec<PairedNode>> {
+        paired_nodes::list_paired_nodes().map_err(ServiceError::DatabaseError)
+    }
+
+    pub fn revoke_paired_node(&self, id: i64) -> ServiceResult<()> {
+        paired_nodes::revoke_paired_node(id).map_err(ServiceError::NotFound)?;
+        log::info!("Revoked paired node {}", id);
+        Ok(())
+    }
+
+    /// Resolve a node credential to its paired node, recording when it was last used
+    /// Returns `None` for anything that is not an active node credential.
+    pub fn authenticate_node(&self, credential: &str) -> Result<Option<PairedNode>, String> {
+        if !credential.starts_with(CREDENTIAL_PREFIX) {
+            return Ok(None);
+        }
+
+        let node = paired_nodes::find_active_by_credential(&Self::hash_credential(credential))?;
+        if let Some(node) = &node {
+            paired_nodes::touch_paired_node(node.id)?;
+        }
+
+        Ok(node)
+    }
+
+    fn pending_entry<'a>(
+        pending: &'a mut HashMap<String, PendingEntry>,
+        request_id: &str,
+    ) -> ServiceResult<&'a mut PendingEntry> {
+        match pending.get_mut(request_id) {
+            Some(entry) if matches!(entry.status, PairingStatus::Pending) => Ok(entry),
+            Some(_) => Err(ServiceError::ValidationError(
+                "La solicitud de emparejamiento ya fue resuelta".to_string(),
+            )),
+            None => Err(ServiceError::NotFound(
+                "Solicitud de emparejamiento no encontrada o expirada".to_string(),
+            )),
+        }
+    }
+
+    fn purge_expired(pending: &mut HashMap<String, PendingEntry>) {
+        let now = Utc::now();
+        pending.retain(|_, entry| entry.expires_at > now);
+    }
+
+    /// Six digit code, easy to read out loud
+    fn generate_code() -> String {
+        format!("{:06}", OsRng.next_u32() % 1_000_000)
+    }
+
+    fn random_token() -> String {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    fn hash_credential(credential: &str) -> String {
+        Sha256::digest(credential.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+impl DomainService for PairingService {
+    fn name(&self) -> &'static str {
+        "PairingService"
+    }
+}
+
+impl Default for PairingService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairing_codes_have_six_digits() {
+        for _ in 0..50 {
+            let code = PairingService::generate_code();
+            assert_eq!(code.len(), 6);
+            assert!(code.chars().all(|c| c.is_ascii_digit()));
+        }
+    }
+
+    #[test]
+    fn poll_requires_the_ticket_token() {
+        let service = PairingService::new();
+        let ticket = service.request_pairing("Recepción").unwrap();
+
+        assert!(matches!(
+            service.poll_request(&ticket.request_id, "wrong"),
+            Err(ServiceError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            service.poll_request(&ticket.request_id, &ticket.poll_token),
+            Ok(PairingStatus::Pending)
+        ));
+    }
+
+    #[test]
+    fn rejected_request_is_reported_once() {
+        let service = PairingService::new();
+        let ticket = service.request_pairing("Consultorio 2").unwrap();
+        assert!(service
+            .list_pending_requests()
+            .iter()
+            .any(|request| request.request_id == ticket.request_id && request.code == ticket.code));
+
+        service.reject_request(&ticket.request_id).unwrap();
+        assert!(service.reject_request(&ticket.request_id).is_err());
+
+        assert!(matches!(
+            service.poll_request(&ticket.request_id, &ticket.poll_token),
+            Ok(PairingStatus::Rejected)
+        ));
+        assert!(matches!(
+            service.poll_request(&ticket.request_id, &ticket.poll_token),
+            Err(ServiceError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn non_node_credentials_are_ignored() {
+        let service = PairingService::new();
+        assert!(service.authenticate_node("eyJhbGciOi.jwt").unwrap().is_none());
+    }
+
+    #[test]
+    fn blank_node_names_are_rejected() {
+        let service = PairingService::new();
+        assert!(matches!(
+            service.request_pairing("   "),
+            Err(ServiceError::ValidationError(_))
+        ));
+    }
+}
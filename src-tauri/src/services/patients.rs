@@ -8,6 +8,7 @@ use crate::db::patients::{
     get_patients_count as db_get_patients_count, search_patients as db_search_patients,
     update_patient as db_update_patient, CreatePatientInput, Patient, UpdatePatientInput,
 };
+use crate::events;
 use crate::services::{DomainService, ServiceError, ServiceResult};
 
 /// Patient service handles all patient-related business logic
@@ -33,7 +34,9 @@ impl PatientService {
         }
 
         // Call database layer
-        db_create_patient(input).map_err(|e| ServiceError::DatabaseError(e))
+        let id = db_create_patient(input).map_err(|e| ServiceError::DatabaseError(e))?;
+        events::publish("patient.created", id);
+        Ok(id)
     }
 
     /// Get patient by ID
@@ -65,14 +68,20 @@ impl PatientService {
             ));
         }
 
-        db_update_patient(id, input).map_err(|e| ServiceError::DatabaseError(e))
+        db_update_patient(id, input).map_err(|e| ServiceError::DatabaseError(e))?;
+        events::publish("patient.updated", id);
+        Ok(())
     }
 
     /// Delete patient
     pub fn delete(&self, id: i64) -> ServiceResult<()> {
         // Check if patient exists
         match self.get_by_id(id)? {
-            Some(_) => db_delete_patient(id).map_err(|e| ServiceError::DatabaseError(e)),
+            Some(_) => {
+                db_delete_patient(id).map_err(|e| ServiceError::DatabaseError(e))?;
+                events::publish("patient.deleted", id);
+                Ok(())
+            }
             None => Err(ServiceError::NotFound(format!("Patient {} not found", id))),
         }
     }
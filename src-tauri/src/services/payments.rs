@@ -0,0 +1,151 @@
+// Payment Domain Service
+// Contains all business logic for payments and patient balances
+// Independent of Tauri and HTTP - pure business logic
+
+use crate::db::payments::{
+    create_payment as db_create_payment, delete_payment as db_delete_payment,
+    get_all_payments as db_get_all_payments, get_patient_balance as db_get_patient_balance,
+    get_patients_with_debt as db_get_patients_with_debt,
+    get_patients_with_debt_count as db_get_patients_with_debt_count,
+    get_patients_with_debt_summary as db_get_patients_with_debt_summary,
+    get_payment_by_id as db_get_payment_by_id,
+    get_payments_by_patient as db_get_payments_by_patient,
+    get_payments_by_treatment as db_get_payments_by_treatment,
+    get_recent_payments as db_get_recent_payments, get_total_debt as db_get_total_debt,
+    update_payment as db_update_payment, CreatePaymentInput, PatientBalance, PatientDebtSummary,
+    Payment, UpdatePaymentInput,
+};
+use crate::db::treatments::get_treatment_by_id as db_get_treatment_by_id;
+use crate::events;
+use crate::services::{DomainService, ServiceError, ServiceResult};
+
+/// Payment service handles all payment-related business logic
+pub struct PaymentService;
+
+impl PaymentService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Register a new payment against a treatment
+    pub fn create(&self, input: CreatePaymentInput) -> ServiceResult<i64> {
+        if input.amount <= 0.0 {
+            return Err(ServiceError::ValidationError(
+                "Payment amount must be greater than zero".to_string(),
+            ));
+        }
+
+        if db_get_treatment_by_id(input.treatment_id)
+            .map_err(ServiceError::DatabaseError)?
+            .is_none()
+        {
+            return Err(ServiceError::NotFound(format!(
+                "Treatment {} not found",
+                input.treatment_id
+            )));
+        }
+
+        let id = db_create_payment(input).map_err(ServiceError::DatabaseError)?;
+        events::publish("payment.created", id);
+        Ok(id)
+    }
+
+    /// Get payment by ID
+    pub fn get_by_id(&self, id: i64) -> ServiceResult<Option<Payment>> {
+        db_get_payment_by_id(id).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get all payments with optional pagination
+    pub fn get_all(&self, limit: Option<i64>, offset: Option<i64>) -> ServiceResult<Vec<Payment>> {
+        db_get_all_payments(limit, offset).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get all payments of a treatment
+    pub fn get_by_treatment(&self, treatment_id: i64) -> ServiceResult<Vec<Payment>> {
+        db_get_payments_by_treatment(treatment_id).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get all payments of a patient
+    pub fn get_by_patient(&self, patient_id: i64) -> ServiceResult<Vec<Payment>> {
+        db_get_payments_by_patient(patient_id).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get the most recent payments
+    pub fn get_recent(&self, limit: Option<i64>) -> ServiceResult<Vec<Payment>> {
+        db_get_recent_payments(limit).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Update payment
+    pub fn update(&self, id: i64, input: UpdatePaymentInput) -> ServiceResult<()> {
+        if matches!(input.amount, Some(amount) if amount <= 0.0) {
+            return Err(ServiceError::ValidationError(
+                "Payment amount must be greater than zero".to_string(),
+            ));
+        }
+
+        self.ensure_exists(id)?;
+        db_update_payment(id, input).map_err(ServiceError::DatabaseError)?;
+        events::publish("payment.updated", id);
+        Ok(())
+    }
+
+    /// Delete payment
+    pub fn delete(&self, id: i64) -> ServiceResult<()> {
+        self.ensure_exists(id)?;
+        db_delete_payment(id).map_err(ServiceError::DatabaseError)?;
+        events::publish("payment.deleted", id);
+        Ok(())
+    }
+
+    /// Get the balance of a patient
+    pub fn get_patient_balance(&self, patient_id: i64) -> ServiceResult<PatientBalance> {
+        db_get_patient_balance(patient_id).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get patients with outstanding balance
+    pub fn get_patients_with_debt(
+        &self,
+        limit: Option<i64>,
+        offset: Option<i64>,
+        query: Option<String>,
+    ) -> ServiceResult<Vec<PatientBalance>> {
+        db_get_patients_with_debt(limit, offset, query).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get the number of patients with outstanding balance
+    pub fn get_patients_with_debt_count(&self) -> ServiceResult<i64> {
+        db_get_patients_with_debt_count().map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get a summary of outstanding debt
+    pub fn get_patients_with_debt_summary(
+        &self,
+        query: Option<String>,
+    ) -> ServiceResult<PatientDebtSummary> {
+        db_get_patients_with_debt_summary(query).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get the total outstanding debt
+    pub fn get_total_debt(&self) -> ServiceResult<f64> {
+        db_get_total_debt().map_err(ServiceError::DatabaseError)
+    }
+
+    fn ensure_exists(&self, id: i64) -> ServiceResult<()> {
+        match self.get_by_id(id)? {
+            Some(_) => Ok(()),
+            None => Err(ServiceError::NotFound(format!("Payment {} not found", id))),
+        }
+    }
+}
+
+impl DomainService for PaymentService {
+    fn name(&self) -> &'static str {
+        "PaymentService"
+    }
+}
+
+impl Default for PaymentService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
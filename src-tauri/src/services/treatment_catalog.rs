@@ -0,0 +1,127 @@
+// Treatment Catalog Domain Service
+// Contains all business logic for the treatment catalog and its items
+// Independent of Tauri and HTTP - pure business logic
+
+use crate::db::treatment_catalog::{
+    self as db_catalog, CreateTreatmentCatalogInput, CreateTreatmentCatalogItemInput,
+    TreatmentCatalogEntry, TreatmentCatalogItem, UpdateTreatmentCatalogInput,
+    UpdateTreatmentCatalogItemInput,
+};
+use crate::services::{DomainService, ServiceError, ServiceResult};
+
+/// Treatment catalog service handles all catalog-related business logic
+pub struct TreatmentCatalogService;
+
+impl TreatmentCatalogService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    // ===== CATALOG ENTRIES =====
+
+    /// Get all active catalog entries
+    pub fn get_all(&self) -> ServiceResult<Vec<TreatmentCatalogEntry>> {
+        db_catalog::get_all_treatment_catalog().map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get catalog entry by ID
+    pub fn get_by_id(&self, id: i64) -> ServiceResult<Option<TreatmentCatalogEntry>> {
+        db_catalog::get_treatment_catalog_by_id(id).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Create a new catalog entry
+    pub fn create(&self, input: CreateTreatmentCatalogInput) -> ServiceResult<i64> {
+        Self::validate_entry(&input.name, input.default_cost)?;
+        db_catalog::create_treatment_catalog(input).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Update a catalog entry
+    pub fn update(&self, input: UpdateTreatmentCatalogInput) -> ServiceResult<()> {
+        Self::validate_entry(&input.name, input.default_cost)?;
+        self.ensure_entry_exists(input.id)?;
+        db_catalog::update_treatment_catalog(input).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Soft-delete a catalog entry
+    pub fn delete(&self, id: i64) -> ServiceResult<()> {
+        self.ensure_entry_exists(id)?;
+        db_catalog::delete_treatment_catalog(id).map_err(ServiceError::DatabaseError)
+    }
+
+    // ===== CATALOG ITEMS =====
+
+    /// Get the active items of a catalog entry
+    pub fn get_items(&self, treatment_catalog_id: i64) -> ServiceResult<Vec<TreatmentCatalogItem>> {
+        db_catalog::get_treatment_catalog_items(treatment_catalog_id)
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get catalog item by ID
+    pub fn get_item_by_id(&self, id: i64) -> ServiceResult<Option<TreatmentCatalogItem>> {
+        db_catalog::get_treatment_catalog_item_by_id(id).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Create a new catalog item
+    pub fn create_item(&self, input: CreateTreatmentCatalogItemInput) -> ServiceResult<i64> {
+        Self::validate_entry(&input.name, input.default_cost)?;
+        self.ensure_entry_exists(input.treatment_catalog_id)?;
+        db_catalog::create_treatment_catalog_item(input).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Update a catalog item
+    pub fn update_item(&self, input: UpdateTreatmentCatalogItemInput) -> ServiceResult<()> {
+        Self::validate_entry(&input.name, input.default_cost)?;
+        self.ensure_item_exists(input.id)?;
+        db_catalog::update_treatment_catalog_item(input).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Soft-delete a catalog item
+    pub fn delete_item(&self, id: i64) -> ServiceResult<()> {
+        self.ensure_item_exists(id)?;
+        db_catalog::delete_treatment_catalog_item(id).map_err(ServiceError::DatabaseError)
+    }
+
+    fn ensure_entry_exists(&self, id: i64) -> ServiceResult<()> {
+        match self.get_by_id(id)? {
+            Some(_) => Ok(()),
+            None => Err(ServiceError::NotFound(format!(
+                "Treatment catalog entry {} not found",
+                id
+            ))),
+        }
+    }
+
+    fn ensure_item_exists(&self, id: i64) -> ServiceResult<()> {
+        match self.get_item_by_id(id)? {
+            Some(_) => Ok(()),
+            None => Err(ServiceError::NotFound(format!(
+                "Treatment catalog item {} not found",
+                id
+            ))),
+        }
+    }
+
+    fn validate_entry(name: &str, default_cost: f64) -> ServiceResult<()> {
+        if name.trim().is_empty() {
+            return Err(ServiceError::ValidationError("Name is required".to_string()));
+        }
+        if default_cost < 0.0 {
+            return Err(ServiceError::ValidationError(
+                "Default cost cannot be negative".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl DomainService for TreatmentCatalogService {
+    fn name(&self) -> &'static str {
+        "TreatmentCatalogService"
+    }
+}
+
+impl Default for TreatmentCatalogService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
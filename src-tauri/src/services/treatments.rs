@@ -0,0 +1,135 @@
+// Treatment Domain Service
+// Contains all business logic for treatment management
+// Independent of Tauri and HTTP - pure business logic
+
+use crate::db::treatments::{
+    create_treatment as db_create_treatment, delete_treatment as db_delete_treatment,
+    get_all_treatments as db_get_all_treatments, get_treatment_by_id as db_get_treatment_by_id,
+    get_treatment_stats as db_get_treatment_stats,
+    get_treatments_by_patient as db_get_treatments_by_patient,
+    get_treatments_by_status as db_get_treatments_by_status,
+    update_treatment as db_update_treatment, update_treatment_status as db_update_treatment_status,
+    CreateTreatmentInput, Treatment, TreatmentStats, UpdateTreatmentInput,
+};
+use crate::events;
+use crate::services::{DomainService, ServiceError, ServiceResult};
+
+/// Valid treatment statuses
+const TREATMENT_STATUSES: &[&str] = &["Pending", "InProgress", "Completed", "Cancelled"];
+
+/// Treatment service handles all treatment-related business logic
+pub struct TreatmentService;
+
+impl TreatmentService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Create a new treatment
+    pub fn create(&self, input: CreateTreatmentInput) -> ServiceResult<i64> {
+        if input.name.trim().is_empty() {
+            return Err(ServiceError::ValidationError(
+                "Treatment name is required".to_string(),
+            ));
+        }
+        if input.total_cost < 0.0 {
+            return Err(ServiceError::ValidationError(
+                "Total cost cannot be negative".to_string(),
+            ));
+        }
+
+        let id = db_create_treatment(input).map_err(ServiceError::DatabaseError)?;
+        events::publish("treatment.created", id);
+        Ok(id)
+    }
+
+    /// Get treatment by ID
+    pub fn get_by_id(&self, id: i64) -> ServiceResult<Option<Treatment>> {
+        db_get_treatment_by_id(id).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get all treatments with optional pagination
+    pub fn get_all(&self, limit: Option<i64>, offset: Option<i64>) -> ServiceResult<Vec<Treatment>> {
+        db_get_all_treatments(limit, offset).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get all treatments of a patient
+    pub fn get_by_patient(&self, patient_id: i64) -> ServiceResult<Vec<Treatment>> {
+        db_get_treatments_by_patient(patient_id).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Get all treatments with the given status
+    pub fn get_by_status(&self, status: &str) -> ServiceResult<Vec<Treatment>> {
+        Self::validate_status(status)?;
+        db_get_treatments_by_status(status).map_err(ServiceError::DatabaseError)
+    }
+
+    /// Update treatment
+    pub fn update(&self, id: i64, input: UpdateTreatmentInput) -> ServiceResult<()> {
+        if let Some(status) = &input.status {
+            Self::validate_status(status)?;
+        }
+        if matches!(input.total_cost, Some(cost) if cost < 0.0) {
+            return Err(ServiceError::ValidationError(
+                "Total cost cannot be negative".to_string(),
+            ));
+        }
+
+        self.ensure_exists(id)?;
+        db_update_treatment(id, input).map_err(ServiceError::DatabaseError)?;
+        events::publish("treatment.updated", id);
+        Ok(())
+    }
+
+    /// Update only the status of a treatment
+    pub fn update_status(&self, id: i64, status: &str) -> ServiceResult<()> {
+        Self::validate_status(status)?;
+        self.ensure_exists(id)?;
+        db_update_treatment_status(id, status).map_err(ServiceError::DatabaseError)?;
+        events::publish("treatment.updated", id);
+        Ok(())
+    }
+
+    /// Delete treatment
+    pub fn delete(&self, id: i64) -> ServiceResult<()> {
+        self.ensure_exists(id)?;
+        db_delete_treatment(id).map_err(ServiceError::DatabaseError)?;
+        events::publish("treatment.deleted", id);
+        Ok(())
+    }
+
+    /// Get aggregated treatment statistics
+    pub fn get_stats(&self) -> ServiceResult<TreatmentStats> {
+        db_get_treatment_stats().map_err(ServiceError::DatabaseError)
+    }
+
+    fn ensure_exists(&self, id: i64) -> ServiceResult<()> {
+        match self.get_by_id(id)? {
+            Some(_) => Ok(()),
+            None => Err(ServiceError::NotFound(format!("Treatment {} not found", id))),
+        }
+    }
+
+    fn validate_status(status: &str) -> ServiceResult<()> {
+        if TREATMENT_STATUSES.contains(&status) {
+            Ok(())
+        } else {
+            Err(ServiceError::ValidationError(format!(
+                "Invalid treatment status '{}'",
+                status
+            )))
+        }
+    }
+}
+
+impl DomainService for TreatmentService {
+    fn name(&self) -> &'static str {
+        "TreatmentService"
+    }
+}
+
+impl Default for TreatmentService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
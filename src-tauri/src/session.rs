@@ -1,4 +1,9 @@
+use crate::db::auth_audit::AuthAuditEntry;
 use crate::db::users::{self, User};
+use crate::services::auth::AuthService;
+use crate::services::authorization::{self, Permission};
+use crate::services::login_throttle::{LoginOrigin, LoginThrottle};
+use crate::services::ServiceError;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, RwLock};
@@ -50,33 +55,47 @@ pub fn has_active_session() -> bool {
     CURRENT_SESSION.read().map(|s| s.is_some()).unwrap_or(false)
 }
 
+/// Verificar que el usuario de la sesión actual tenga un permiso
+/// Es el equivalente de `api::require_permission` para los comandos de Tauri
+pub fn require_permission(permission: Permission) -> Result<(), String> {
+    let session = get_session()?.ok_or_else(|| {
+        ServiceError::Unauthorized("No hay una sesión activa".to_string()).to_string()
+    })?;
+
+    authorization::authorize(&session.user, permission).map_err(|e| e.to_string())
+}
+
+/// Verificar que la sesión actual sea la de `username` o tenga un permiso
+/// Permite que cada usuario cambie su propia contraseña o PIN sin ser administrador
+pub fn require_self_or_permission(username: &str, permission: Permission) -> Result<(), String> {
+    let session = get_session()?.ok_or_else(|| {
+        ServiceError::Unauthorized("No hay una sesión activa".to_string()).to_string()
+    })?;
+
+    if session.user.username == username {
+        return Ok(());
+    }
+    authorization::authorize(&session.user, permission).map_err(|e| e.to_string())
+}
+
 // ========== TAURI COMMANDS ==========
 
 #[tauri::command]
-pub fn login_user(username: String, password_hash: String) -> Result<User, String> {
-    // Autenticar usuario
-    match users::get_user_by_username(&username)? {
-        Some((user, stored_hash)) => {
-            if !user.active {
-                return Err("Usuario inactivo".to_string());
-            }
-
-            if stored_hash != password_hash {
-                return Err("Contraseña incorrecta".to_string());
-            }
+pub fn login_user(username: String, password: String) -> Result<User, String> {
+    // Autenticar usuario (la verificación del hash se hace en el backend)
+    let user = AuthService::new()
+        .authenticate_attempt(&username, &password, &LoginOrigin::desktop())?
+        .ok_or_else(|| "Usuario o contraseña incorrectos".to_string())?;
 
-            // Iniciar sesión
-            start_session(user.clone())?;
+    // Iniciar sesión
+    start_session(user.clone())?;
 
-            Ok(user)
-        }
-        None => Err("Usuario no encontrado".to_string()),
-    }
+    Ok(user)
 }
 
 #[tauri::command]
 pub fn login_with_pin(username: String, pin: String) -> Result<User, String> {
-    match users::verify_user_pin(&username, &pin)? {
+    match AuthService::new().authenticate_pin_attempt(&username, &pin, &LoginOrigin::desktop())? {
         Some(user) => {
             start_session(user.clone())?;
             Ok(user)
@@ -86,9 +105,9 @@ pub fn login_with_pin(username: String, pin: String) -> Result<User, String> {
 }
 
 #[tauri::command]
-pub fn unlock_with_password(username: String, password_hash: String) -> Result<User, String> {
+pub fn unlock_with_password(username: String, password: String) -> Result<User, String> {
     // Reutilizamos la lógica de login
-    login_user(username, password_hash)
+    login_user(username, password)
 }
 
 #[tauri::command]
@@ -99,6 +118,8 @@ pub fn unlock_with_pin(username: String, pin: String) -> Result<User, String> {
 
 #[tauri::command]
 pub fn set_user_pin(username: String, pin: String) -> Result<(), String> {
+    require_self_or_permission(&username, Permission::ManageSecurity)?;
+
     // Validar que el PIN tenga entre 4 y 6 dígitos
     if pin.len() < 4 || pin.len() > 6 {
         return Err("El PIN debe tener entre 4 y 6 dígitos".to_string());
@@ -113,6 +134,7 @@ pub fn set_user_pin(username: String, pin: String) -> Result<(), String> {
 
 #[tauri::command]
 pub fn remove_user_pin(username: String) -> Result<(), String> {
+    require_self_or_permission(&username, Permission::ManageSecurity)?;
     users::update_user_pin(&username, None)
 }
 
@@ -126,6 +148,27 @@ pub fn logout_user() -> Result<(), String> {
     end_session()
 }
 
+/// Invalida todos los JWT (acceso y refresh) emitidos a un usuario
+#[tauri::command]
+pub fn revoke_user_sessions(user_id: i64) -> Result<(), String> {
+    require_permission(Permission::ManageSecurity)?;
+    AuthService::new().revoke_all_sessions(user_id)
+}
+
+/// Genera un nuevo secreto de firma JWT; todos los tokens emitidos dejan de ser válidos
+#[tauri::command]
+pub fn rotate_jwt_secret() -> Result<(), String> {
+    require_permission(Permission::ManageSecurity)?;
+    AuthService::new().rotate_secret()
+}
+
+/// Intentos de login fallidos y bloqueos, del más reciente al más antiguo
+#[tauri::command]
+pub fn get_auth_audit_log(limit: Option<i64>) -> Result<Vec<AuthAuditEntry>, String> {
+    require_permission(Permission::ManageSecurity)?;
+    Ok(LoginThrottle::new().audit_log(limit)?)
+}
+
 #[tauri::command]
 pub fn get_current_user() -> Result<User, String> {
     match get_session()? {
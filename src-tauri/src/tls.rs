@@ -0,0 +1,223 @@
+// TLS for the host HTTP API
+// Hosts serve HTTPS with a self-signed certificate generated on first use and kept in
+// the app data dir. No CA can vouch for it, so clients pin its SHA-256 fingerprint
+// (advertised over mDNS) instead of validating a certificate chain.
+
+use base64::{engine::general_purpose, Engine as _};
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, ServerName};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::db::path::get_app_data_dir;
+use crate::node::HostConfig;
+
+const TLS_DIR: &str = "tls";
+const CERT_FILE: &str = "api_cert.pem";
+const KEY_FILE: &str = "api_key.pem";
+
+/// Certificate and private key used by the host API
+pub struct HostIdentity {
+    pub cert_pem: String,
+    pub key_pem: String,
+    /// SHA-256 of the DER certificate, as colon separated hex
+    pub fingerprint: String,
+}
+
+/// Load the host certificate, generating a self-signed one on first use
+pub fn load_or_create_identity() -> Result<HostIdentity, String> {
+    let dir = tls_dir()?;
+    let cert_path = dir.join(CERT_FILE);
+    let key_path = dir.join(KEY_FILE);
+
+    if cert_path.exists() && key_path.exists() {
+        let cert_pem = fs::read_to_string(&cert_path)
+            .map_err(|e| format!("Error leyendo certificado TLS: {}", e))?;
+        let key_pem = fs::read_to_string(&key_path)
+            .map_err(|e| format!("Error leyendo clave TLS: {}", e))?;
+        let fingerprint = fingerprint(&pem_to_der(&cert_pem)?);
+
+        return Ok(HostIdentity {
+            cert_pem,
+            key_pem,
+            fingerprint,
+        });
+    }
+
+    let identity = generate_identity()?;
+    fs::write(&cert_path, &identity.cert_pem)
+        .map_err(|e| format!("Error guardando certificado TLS: {}", e))?;
+    write_private_key(&key_path, &identity.key_pem)?;
+
+    log::info!("Generated self-signed API certificate ({})", identity.fingerprint);
+    Ok(identity)
+}
+
+/// Fingerprint to advertise for a host, `None` when it serves plain HTTP
+pub fn advertised_fingerprint(config: &HostConfig) -> Result<Option<String>, String> {
+    if !config.enable_tls {
+        return Ok(None);
+    }
+
+    load_or_create_identity().map(|identity| Some(identity.fingerprint))
+}
+
+/// SHA-256 fingerprint of a DER certificate ("AB:CD:...")
+pub fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Compare fingerprints ignoring case, colons and whitespace
+pub fn fingerprints_match(a: &str, b: &str) -> bool {
+    let normalize = |value: &str| {
+        value
+            .chars()
+            .filter(|c| c.is_ascii_hexdigit())
+            .map(|c| c.to_ascii_uppercase())
+            .collect::<String>()
+    };
+
+    let a = normalize(a);
+    !a.is_empty() && a == normalize(b)
+}
+
+/// rustls client configuration that only trusts the certificate with this fingerprint
+/// Use with `reqwest::ClientBuilder::use_preconfigured_tls`.
+pub fn pinned_client_config(pinned_fingerprint: &str) -> rustls::ClientConfig {
+    rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(PinnedCertVerifier {
+            fingerprint: pinned_fingerprint.to_string(),
+        }))
+        .with_no_client_auth()
+}
+
+/// Async HTTP client builder for talking to a host, pinned when a fingerprint is given
+pub fn client_builder(pinned_fingerprint: Option<&str>) -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder();
+    match pinned_fingerprint.filter(|f| !f.is_empty()) {
+        Some(fingerprint) => builder.use_preconfigured_tls(pinned_client_config(fingerprint)),
+        None => builder,
+    }
+}
+
+/// Accepts exactly one certificate, identified by its fingerprint
+/// Host names are not checked: the certificate is self-signed and hosts are usually
+/// reached by LAN IP, so the fingerprint is the only identity that matters.
+struct PinnedCertVerifier {
+    fingerprint: String,
+}
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let presented = fingerprint(&end_entity.0);
+
+        if fingerprints_match(&presented, &self.fingerprint) {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::General(format!(
+                "El certificado del host ({}) no coincide con la huella fijada",
+                presented
+            )))
+        }
+    }
+}
+
+fn tls_dir() -> Result<PathBuf, String> {
+    let dir = get_app_data_dir()?.join(TLS_DIR);
+    fs::create_dir_all(&dir).map_err(|e| format!("Error creando directorio TLS: {}", e))?;
+    Ok(dir)
+}
+
+fn generate_identity() -> Result<HostIdentity, String> {
+    let hostname = hostname::get()
+        .ok()
+        .and_then(|h| h.into_string().ok())
+        .unwrap_or_else(|| "nuevogaleno".to_string());
+
+    let subject_alt_names = vec![
+        "localhost".to_string(),
+        hostname.clone(),
+        format!("{}.local", hostname),
+    ];
+
+    let certified = rcgen::generate_simple_self_signed(subject_alt_names)
+        .map_err(|e| format!("Error generando certificado TLS: {}", e))?;
+
+    Ok(HostIdentity {
+        cert_pem: certified.cert.pem(),
+        key_pem: certified.key_pair.serialize_pem(),
+        fingerprint: fingerprint(certified.cert.der()),
+    })
+}
+
+#[cfg(unix)]
+fn write_private_key(path: &PathBuf, key_pem: &str) -> Result<(), String> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .and_then(|mut file| file.write_all(key_pem.as_bytes()))
+        .map_err(|e| format!("Error guardando clave TLS: {}", e))
+}
+
+#[cfg(not(unix))]
+fn write_private_key(path: &PathBuf, key_pem: &str) -> Result<(), String> {
+    fs::write(path, key_pem).map_err(|e| format!("Error guardando clave TLS: {}", e))
+}
+
+/// Decode the first PEM block of a file
+fn pem_to_der(pem: &str) -> Result<Vec<u8>, String> {
+    let body: String = pem
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| !line.starts_with("-----BEGIN"))
+        .skip(1)
+        .take_while(|line| !line.starts_with("-----END"))
+        .collect();
+
+    general_purpose::STANDARD
+        .decode(body)
+        .map_err(|e| format!("Certificado TLS inválido: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_survives_pem_roundtrip() {
+        let identity = generate_identity().unwrap();
+        let der = pem_to_der(&identity.cert_pem).unwrap();
+        assert_eq!(fingerprint(&der), identity.fingerprint);
+        assert_eq!(identity.fingerprint.len(), 32 * 3 - 1);
+    }
+
+    #[test]
+    fn fingerprint_comparison_ignores_format() {
+        assert!(fingerprints_match("AB:CD:01", "abcd01"));
+        assert!(fingerprints_match("ab cd 01", "AB:CD:01"));
+        assert!(!fingerprints_match("AB:CD:01", "AB:CD:02"));
+        assert!(!fingerprints_match("", ""));
+    }
+}
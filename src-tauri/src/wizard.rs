@@ -1,5 +1,10 @@
 use crate::db::config;
 use crate::db::users::{self, CreateUserInput, User};
+use crate::passwords::{self, Verification};
+use crate::services::auth::{AuthService, JWT_SECRET_CONFIG_KEY};
+use crate::services::authorization::Permission;
+use crate::services::login_throttle::LoginOrigin;
+use crate::session;
 use chrono::Utc;
 use serde::Serialize;
 use std::path::PathBuf;
@@ -143,13 +148,35 @@ fn dir_size(path: &std::path::Path) -> u64 {
     total
 }
 
+/// Claves de `app_config` que `get_config`/`set_config` no exponen: secretos y ajustes
+/// de seguridad que solo cambian sus propios comandos
+const RESERVED_CONFIG_KEYS: [&str; 3] = [
+    JWT_SECRET_CONFIG_KEY,
+    "system_password",
+    "fsEncryptionEnabled",
+];
+
+/// Prefijo de la configuración del nodo, que incluye los tokens de la API
+const NODE_CONFIG_PREFIX: &str = "node.";
+
+fn ensure_public_config_key(key: &str) -> Result<(), String> {
+    if RESERVED_CONFIG_KEYS.contains(&key) || key.starts_with(NODE_CONFIG_PREFIX) {
+        return Err(format!("La clave de configuración '{}' está reservada", key));
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub fn set_config(key: String, value: String) -> Result<(), String> {
+    session::require_permission(Permission::ManageSecurity)?;
+    ensure_public_config_key(&key)?;
     config::set_config(&key, &value)
 }
 
 #[tauri::command]
 pub fn get_config(key: String) -> Result<Option<String>, String> {
+    session::require_permission(Permission::ManageSecurity)?;
+    ensure_public_config_key(&key)?;
     config::get_config(&key)
 }
 
@@ -158,34 +185,26 @@ pub fn get_config(key: String) -> Result<Option<String>, String> {
 #[tauri::command]
 pub fn create_user(
     username: String,
-    password_hash: String,
+    password: String,
     name: String,
     role: String,
 ) -> Result<i64, String> {
+    // El asistente de primera ejecución crea el primer administrador sin sesión
+    if !users::list_users()?.is_empty() {
+        session::require_permission(Permission::ManageSecurity)?;
+    }
+
     users::create_user(CreateUserInput {
         username,
-        password_hash,
+        password_hash: passwords::hash_secret(&password)?,
         name,
         role,
     })
 }
 
 #[tauri::command]
-pub fn authenticate_user(username: String, password_hash: String) -> Result<Option<User>, String> {
-    match users::get_user_by_username(&username)? {
-        Some((user, stored_hash)) => {
-            if !user.active {
-                return Err("Usuario inactivo".to_string());
-            }
-
-            if stored_hash == password_hash {
-                Ok(Some(user))
-            } else {
-                Ok(None)
-            }
-        }
-        None => Ok(None),
-    }
+pub fn authenticate_user(username: String, password: String) -> Result<Option<User>, String> {
+    Ok(AuthService::new().authenticate_attempt(&username, &password, &LoginOrigin::desktop())?)
 }
 
 #[tauri::command]
@@ -194,32 +213,64 @@ pub fn list_users() -> Result<Vec<User>, String> {
 }
 
 #[tauri::command]
-pub fn update_user_password(username: String, new_password_hash: String) -> Result<(), String> {
-    users::update_user_password(&username, &new_password_hash)
+pub fn update_user_password(username: String, new_password: String) -> Result<(), String> {
+    session::require_self_or_permission(&username, Permission::ManageSecurity)?;
+    users::update_user_password(&username, &passwords::hash_secret(&new_password)?)
 }
 
 #[tauri::command]
 pub fn delete_user(username: String) -> Result<(), String> {
+    session::require_permission(Permission::ManageSecurity)?;
     users::delete_user(&username)
 }
 
 #[tauri::command]
-pub fn verify_system_password(password_hash: String) -> Result<bool, String> {
-    match config::get_config("system_password")? {
-        Some(stored_hash) => Ok(stored_hash == password_hash),
-        None => Err("Contraseña del sistema no configurada".to_string()),
+pub fn verify_system_password(password: String) -> Result<bool, String> {
+    let stored_hash = config::get_config("system_password")?
+        .ok_or_else(|| "Contraseña del sistema no configurada".to_string())?;
+
+    match passwords::verify_password(&password, &stored_hash) {
+        Verification::Valid => Ok(true),
+        Verification::ValidLegacy => {
+            // Migrar el hash SHA-256 heredado a Argon2id; la contraseña no cambia,
+            // así que las claves de cifrado de archivos siguen siendo válidas
+            config::set_config("system_password", &passwords::hash_secret(&password)?)?;
+            Ok(true)
+        }
+        Verification::Invalid => Ok(false),
     }
 }
 
 #[tauri::command]
-pub fn set_system_password(password_hash: String) -> Result<(), String> {
-    config::set_config("system_password", &password_hash)
+pub fn has_system_password() -> Result<bool, String> {
+    Ok(config::get_config("system_password")?.is_some())
+}
+
+#[tauri::command]
+pub fn set_system_password(
+    password: String,
+    current_password: Option<String>,
+) -> Result<(), String> {
+    session::require_permission(Permission::ManageSecurity)?;
+
+    // Cambiar una contraseña existente exige conocerla: las claves de cifrado
+    // de archivos se vuelven a proteger con la nueva
+    if has_system_password()? {
+        let current = current_password
+            .ok_or_else(|| "Se requiere la contraseña del sistema actual".to_string())?;
+        if !verify_system_password(current)? {
+            return Err("Contraseña del sistema incorrecta".to_string());
+        }
+    }
+
+    crate::filesystem::on_system_password_changed(&password)?;
+    config::set_config("system_password", &passwords::hash_secret(&password)?)
 }
 
 #[tauri::command]
-pub fn wipe_system(system_password_hash: String) -> Result<(), String> {
+pub fn wipe_system(system_password: String) -> Result<(), String> {
     // Verificar contraseña del sistema
-    if !verify_system_password(system_password_hash)? {
+    if !verify_system_password(system_password)? {
         return Err("Contraseña del sistema incorrecta".to_string());
     }
 
@@ -231,6 +282,7 @@ pub fn wipe_system(system_password_hash: String) -> Result<(), String> {
 
     conn.execute("DELETE FROM app_config WHERE key != 'system_password'", [])
         .map_err(|e| format!("Error limpiando configuración: {}", e))?;
+    crate::node::config::invalidate_cache();
 
     Ok(())
 }
@@ -16,7 +16,7 @@ import { LockScreen } from "./components/LockScreen";
 import FirstRunWizard from "./components/FirstRunWizard";
 import { APP_DEFINITIONS } from "./apps";
 import { motion, AnimatePresence } from "motion/react"; // Importación actualizada
-import { reminderService, formatAppointmentNotification } from './services/appointmentReminders';
+import { reminderService, formatAppointmentNotification, ReminderNotification } from './services/appointmentReminders';
 import { useConfig } from '@/hooks/useConfig';
 import { useShell } from '@/contexts/ShellContext';
 import { SearchOverlay } from "./components/kiosk/SearchOverlay";
@@ -116,10 +116,10 @@ function KioskContent() {
       return;
     }
 
-    const handleReminder = (notification: any) => {
+    const handleReminder = (notification: ReminderNotification) => {
       const { title, message } = formatAppointmentNotification(notification.appointment);
 
-      const notificationId = addNotificationRef.current({
+      addNotificationRef.current({
         type: 'info',
         title,
         message,
@@ -142,8 +142,6 @@ function KioskContent() {
           },
         ],
       });
-
-      reminderService.markReminderSent(notification.reminder.id!, notificationId);
     };
 
     reminderService.start(handleReminder);
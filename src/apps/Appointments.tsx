@@ -5,8 +5,9 @@ import { AppointmentCalendar } from '../components/appointments/AppointmentCalen
 import { AppointmentForm } from '../components/appointments/AppointmentForm';
 import { AppointmentDetails } from '../components/appointments/AppointmentDetails';
 import { useAppointments } from '../hooks/useAppointments';
+import { useDataChanges } from '../hooks/useDataChanges';
 import { useNotifications } from '../contexts/NotificationContext';
-import { Appointment, CalendarEvent, AppointmentFilter } from '../types/appointments';
+import { Appointment, CalendarEvent, AppointmentFilter, parseConflictError } from '../types/appointments';
 import { motion, AnimatePresence } from 'motion/react';
 import { format } from 'date-fns';
 import { es } from 'date-fns/locale';
@@ -160,6 +161,9 @@ export default function Appointments() {
 
     useEffect(() => { fetchAppointments(filter); }, [fetchAppointments, filter]);
 
+    // Reload when another workstation changes the agenda or a patient
+    useDataChanges(['appointment', 'patient'], () => { fetchAppointments(filter); });
+
     const handleSelectSlot = ({ start }: { start: Date; end: Date }) => {
         setSelectedDate(start); setSelectedAppointment(undefined); setShowForm(true);
     };
@@ -167,9 +171,26 @@ export default function Appointments() {
         setSelectedAppointment(event.resource); setShowDetails(true);
     };
 
+    // Si la cita se superpone con otras, pide confirmación para sobreagendar
+    const confirmOverbooking = (err: unknown) => {
+        const conflict = parseConflictError(err);
+        if (!conflict) return false;
+        const clashes = conflict.conflicts.map(c =>
+            `• ${format(new Date(c.appointment.start_time), 'dd/MM HH:mm')} ${c.appointment.patient_name} (${
+                c.reasons.map(r => r === 'chair' ? 'mismo sillón' : 'mismo profesional').join(', ')})`
+        );
+        return confirm(`La cita se superpone con:\n${clashes.join('\n')}\n\n¿Agendarla de todos modos?`);
+    };
+
     const handleCreate = async (appt: Appointment) => {
         try {
-            await createAppointment(appt); setShowForm(false);
+            try {
+                await createAppointment(appt);
+            } catch (err) {
+                if (!confirmOverbooking(err)) throw err;
+                await createAppointment(appt, true);
+            }
+            setShowForm(false);
             addNotification({ type: 'success', title: 'Cita creada', message: 'Agendada correctamente' });
         } catch {
             addNotification({ type: 'error', title: 'Error', message: 'No se pudo crear la cita' });
@@ -177,8 +198,15 @@ export default function Appointments() {
     };
     const handleUpdate = async (appt: Appointment) => {
         if (!appt.id) return;
+        const id = appt.id;
         try {
-            await updateAppointment(appt.id, appt); setShowForm(false); setShowDetails(false);
+            try {
+                await updateAppointment(id, appt);
+            } catch (err) {
+                if (!confirmOverbooking(err)) throw err;
+                await updateAppointment(id, appt, true);
+            }
+            setShowForm(false); setShowDetails(false);
             addNotification({ type: 'success', title: 'Cita actualizada', message: 'Cambios guardados' });
         } catch {
             addNotification({ type: 'error', title: 'Error', message: 'No se pudo actualizar' });
@@ -44,9 +44,9 @@ export function DevToolsApp() {
         });
     };
 
-    const handlePasswordConfirm = async (passwordHash: string) => {
+    const handlePasswordConfirm = async (_password: string) => {
         // Simular una acción administrativa
-        console.log('Password confirmed with hash:', passwordHash);
+        console.log('System password confirmed');
         toast.success('Acción administrativa completada exitosamente');
     };
 
@@ -48,9 +48,9 @@ export function MaintenanceApp({ windowId: _windowId }: { windowId: WindowId; da
     const [isCleaning, setIsCleaning] = useState(false);
     const [isClearingImports, setIsClearingImports] = useState(false);
 
-    const handleWipeSystem = async (passwordHash: string) => {
+    const handleWipeSystem = async (password: string) => {
         try {
-            await invoke('wipe_system', { systemPasswordHash: passwordHash });
+            await invoke('wipe_system', { systemPassword: password });
             // Cerrar todas las ventanas
             Object.values(apps).forEach(app => closeWindow(app.windowId));
             success('Sistema reiniciado', 'Reiniciando servicios...');
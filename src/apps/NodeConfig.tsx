@@ -1,15 +1,44 @@
 // Node Configuration Component
 // UI for managing multi-node architecture settings refined for Windows 11 Fluent Dark
 
-import { useState, useEffect } from 'react';
+import { useState, useEffect, useCallback } from 'react';
+import { invoke } from '@tauri-apps/api/core';
 import { useNode } from '../contexts/NodeContext';
 import { Button } from '../components/ui/button';
 import type { WindowId } from '../types/window-manager';
 import { useWindowManager } from '../contexts/WindowManagerContext';
-import { Server, Globe, Laptop, X, Save, RefreshCw, ChevronRight, ShieldCheck } from 'lucide-react';
+import { Server, Globe, Laptop, X, Save, RefreshCw, ChevronRight, ShieldCheck, Link2, Check, Ban } from 'lucide-react';
+
+interface PairingRequest {
+  request_id: string;
+  node_name: string;
+  code: string;
+  requested_at: string;
+  expires_at: string;
+}
+
+interface PairedNode {
+  id: number;
+  node_name: string;
+  created_at: string;
+  last_seen_at: string | null;
+  revoked_at: string | null;
+}
+
+interface PairingTicket {
+  request_id: string;
+  code: string;
+  poll_token: string;
+  expires_at: string;
+}
+
+type PairingStatus =
+  | { status: 'pending' }
+  | { status: 'approved'; node_id: number; credential: string }
+  | { status: 'rejected' };
 
 export function NodeConfigApp({ windowId }: { windowId: WindowId }) {
-  const { nodeConfig, updateNodeConfig, activeContext, startApiServer, stopApiServer, isApiServerRunning } = useNode();
+  const { nodeConfig, updateNodeConfig, activeContext, startApiServer, stopApiServer, isApiServerRunning, refreshConfig } = useNode();
   const { closeWindow } = useWindowManager();
 
   const [localConfig, setLocalConfig] = useState(nodeConfig);
@@ -69,10 +98,13 @@ export function NodeConfigApp({ windowId }: { windowId: WindowId }) {
         api_port: 3000,
         api_token: '',
         enable_cors: true,
+        enable_tls: false,
+        rate_limit_per_minute: 600,
       }) : undefined,
       client_config: mode === 'client' ? (localConfig.client_config || {
         remote_url: '',
         auth_token: '',
+        pinned_fingerprint: null,
       }) : undefined,
     });
   };
@@ -198,6 +230,23 @@ export function NodeConfigApp({ windowId }: { windowId: WindowId }) {
                 </div>
               </div>
 
+              <div className="space-y-2">
+                <label className="text-[12px] text-white/60 ml-1">Límite de peticiones por minuto (0 = sin límite)</label>
+                <input
+                  type="number"
+                  min={0}
+                  value={localConfig.host_config.rate_limit_per_minute ?? 600}
+                  onChange={(e) => {
+                    const value = parseInt(e.target.value);
+                    setLocalConfig({
+                      ...localConfig,
+                      host_config: { ...localConfig.host_config!, rate_limit_per_minute: Number.isNaN(value) ? 600 : Math.max(0, value) }
+                    });
+                  }}
+                  className="w-full px-4 py-2 bg-[#00000040] border border-white/10 rounded-[4px] text-sm focus:border-[#60cdff] outline-none"
+                />
+              </div>
+
               <div className="flex items-center gap-3 p-3 bg-[#ffffff04] rounded-md border border-white/5">
                 <div className="relative flex items-center cursor-pointer">
                   <input
@@ -215,6 +264,23 @@ export function NodeConfigApp({ windowId }: { windowId: WindowId }) {
                 <label htmlFor="enable-cors" className="text-sm text-white/80">Permitir peticiones externas (CORS)</label>
               </div>
 
+              <div className="flex items-center gap-3 p-3 bg-[#ffffff04] rounded-md border border-white/5">
+                <div className="relative flex items-center cursor-pointer">
+                  <input
+                    type="checkbox"
+                    id="enable-tls"
+                    checked={localConfig.host_config.enable_tls ?? false}
+                    onChange={(e) => setLocalConfig({
+                      ...localConfig,
+                      host_config: { ...localConfig.host_config!, enable_tls: e.target.checked }
+                    })}
+                    className="w-10 h-5 appearance-none bg-white/10 checked:bg-[#60cdff] rounded-full transition-all cursor-pointer border border-white/20"
+                  />
+                  <div className={`absolute left-1 w-3 h-3 bg-white rounded-full transition-transform ${localConfig.host_config.enable_tls ? 'translate-x-5' : 'translate-x-0'}`} />
+                </div>
+                <label htmlFor="enable-tls" className="text-sm text-white/80">Cifrar conexiones (HTTPS con certificado propio)</label>
+              </div>
+
               <Button
                 onClick={handleToggleServer}
                 variant="outline"
@@ -225,6 +291,10 @@ export function NodeConfigApp({ windowId }: { windowId: WindowId }) {
             </div>
           )}
 
+          {localConfig.mode === 'host' && serverRunning && (
+            <HostPairingPanel onError={setError} />
+          )}
+
           {localConfig.mode === 'client' && localConfig.client_config && (
             <div className="space-y-5 p-6 rounded-[8px] bg-[#ffffff05] border border-white/10 animate-in fade-in slide-in-from-bottom-2">
               <div className="flex items-center gap-2 text-[#4ec9b0]">
@@ -258,7 +328,27 @@ export function NodeConfigApp({ windowId }: { windowId: WindowId }) {
                     className="w-full px-4 py-2 bg-[#00000040] border border-white/10 rounded-[4px] text-sm"
                   />
                 </div>
+                <div className="space-y-2">
+                  <label className="text-[12px] text-white/60 ml-1">Huella del certificado (HTTPS)</label>
+                  <input
+                    type="text"
+                    value={localConfig.client_config.pinned_fingerprint ?? ''}
+                    onChange={(e) => setLocalConfig({
+                      ...localConfig,
+                      client_config: { ...localConfig.client_config!, pinned_fingerprint: e.target.value || null }
+                    })}
+                    className="w-full px-4 py-2 bg-[#00000040] border border-white/10 rounded-[4px] text-sm font-mono placeholder:opacity-20"
+                    placeholder="AB:CD:EF:..."
+                  />
+                </div>
               </div>
+
+              <ClientPairingPanel
+                remoteUrl={localConfig.client_config.remote_url}
+                pinnedFingerprint={localConfig.client_config.pinned_fingerprint ?? null}
+                onPaired={refreshConfig}
+                onError={setError}
+              />
             </div>
           )}
         </div>
@@ -281,4 +371,161 @@ export function NodeConfigApp({ windowId }: { windowId: WindowId }) {
       </div>
     </div>
   );
-}
\ No newline at end of file
+}
+
+// Host: approve pending join requests and revoke paired nodes
+function HostPairingPanel({ onError }: { onError: (message: string) => void }) {
+  const [requests, setRequests] = useState<PairingRequest[]>([]);
+  const [pairedNodes, setPairedNodes] = useState<PairedNode[]>([]);
+
+  const load = useCallback(async () => {
+    try {
+      const [pending, nodes] = await Promise.all([
+        invoke<PairingRequest[]>('list_pairing_requests'),
+        invoke<PairedNode[]>('list_paired_nodes'),
+      ]);
+      setRequests(pending);
+      setPairedNodes(nodes);
+    } catch (err) {
+      onError(String(err));
+    }
+  }, [onError]);
+
+  useEffect(() => {
+    load();
+    const interval = setInterval(load, 3000);
+    return () => clearInterval(interval);
+  }, [load]);
+
+  const run = async (command: string, args: Record<string, unknown>) => {
+    try {
+      await invoke(command, args);
+      await load();
+    } catch (err) {
+      onError(String(err));
+    }
+  };
+
+  return (
+    <div className="space-y-5 p-6 rounded-[8px] bg-[#ffffff05] border border-white/10">
+      <div className="flex items-center gap-2 text-[#60cdff]">
+        <Link2 className="w-4 h-4" />
+        <h3 className="text-sm font-semibold uppercase tracking-widest">Nodos Emparejados</h3>
+      </div>
+
+      {requests.length > 0 && (
+        <div className="space-y-2">
+          <p className="text-[12px] text-white/60">Aprueba solo si el código coincide con el que muestra el equipo cliente.</p>
+          {requests.map((request) => (
+            <div key={request.request_id} className="flex items-center gap-4 p-3 rounded-md bg-[#60cdff]/10 border border-[#60cdff]/30">
+              <div className="flex-1">
+                <div className="text-sm font-medium">{request.node_name}</div>
+                <div className="text-[12px] text-white/40">Solicita unirse a este servidor</div>
+              </div>
+              <span className="font-mono text-lg tracking-[0.3em] text-[#60cdff]">{request.code}</span>
+              <Button size="sm" onClick={() => run('approve_pairing_request', { requestId: request.request_id })}>
+                <Check className="w-4 h-4 mr-1" /> Aprobar
+              </Button>
+              <Button size="sm" variant="outline" className="border-white/10" onClick={() => run('reject_pairing_request', { requestId: request.request_id })}>
+                Rechazar
+              </Button>
+            </div>
+          ))}
+        </div>
+      )}
+
+      {pairedNodes.length === 0 ? (
+        <p className="text-[12px] text-white/40">Ningún nodo emparejado todavía.</p>
+      ) : (
+        <div className="space-y-1">
+          {pairedNodes.map((node) => (
+            <div key={node.id} className="flex items-center gap-4 p-3 rounded-md bg-[#ffffff04] border border-white/5">
+              <div className="flex-1">
+                <div className={`text-sm ${node.revoked_at ? 'text-white/40 line-through' : ''}`}>{node.node_name}</div>
+                <div className="text-[12px] text-white/40">
+                  {node.revoked_at
+                    ? `Revocado el ${new Date(node.revoked_at).toLocaleString()}`
+                    : node.last_seen_at
+                      ? `Última conexión ${new Date(node.last_seen_at).toLocaleString()}`
+                      : `Emparejado el ${new Date(node.created_at).toLocaleString()}`}
+                </div>
+              </div>
+              {!node.revoked_at && (
+                <Button size="sm" variant="outline" className="border-white/10 hover:bg-red-500/10 hover:text-red-400" onClick={() => run('revoke_paired_node', { nodeId: node.id })}>
+                  <Ban className="w-4 h-4 mr-1" /> Revocar
+                </Button>
+              )}
+            </div>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+}
+
+// Client: ask the host to pair and wait for the operator's approval
+function ClientPairingPanel({ remoteUrl, pinnedFingerprint, onPaired, onError }: {
+  remoteUrl: string;
+  pinnedFingerprint: string | null;
+  onPaired: () => Promise<void>;
+  onError: (message: string) => void;
+}) {
+  const [ticket, setTicket] = useState<PairingTicket | null>(null);
+
+  useEffect(() => {
+    if (!ticket) return;
+
+    const interval = setInterval(async () => {
+      try {
+        const result = await invoke<PairingStatus>('complete_node_pairing', { remoteUrl, pinnedFingerprint, ticket });
+        if (result.status === 'pending') return;
+
+        setTicket(null);
+        if (result.status === 'approved') {
+          await onPaired();
+        } else {
+          onError('El servidor rechazó la solicitud de emparejamiento');
+        }
+      } catch (err) {
+        setTicket(null);
+        onError(String(err));
+      }
+    }, 2000);
+
+    return () => clearInterval(interval);
+  }, [ticket, remoteUrl, pinnedFingerprint, onPaired, onError]);
+
+  const handleRequest = async () => {
+    try {
+      setTicket(await invoke<PairingTicket>('request_node_pairing', { remoteUrl, pinnedFingerprint }));
+    } catch (err) {
+      onError(String(err));
+    }
+  };
+
+  if (ticket) {
+    return (
+      <div className="flex items-center gap-4 p-4 rounded-md bg-[#4ec9b0]/10 border border-[#4ec9b0]/30">
+        <RefreshCw className="w-4 h-4 text-[#4ec9b0] animate-spin" />
+        <div className="flex-1 text-[12px] text-white/70">
+          Esperando aprobación en el servidor. Confirma que allí aparece este código:
+        </div>
+        <span className="font-mono text-lg tracking-[0.3em] text-[#4ec9b0]">{ticket.code}</span>
+        <Button size="sm" variant="outline" className="border-white/10" onClick={() => setTicket(null)}>
+          Cancelar
+        </Button>
+      </div>
+    );
+  }
+
+  return (
+    <Button
+      onClick={handleRequest}
+      disabled={!remoteUrl}
+      variant="outline"
+      className="w-full py-5 border-white/10 font-normal hover:bg-[#4ec9b0]/10 hover:text-[#4ec9b0]"
+    >
+      <Link2 className="w-4 h-4 mr-2" /> Emparejar con el servidor (sin copiar el token)
+    </Button>
+  );
+}
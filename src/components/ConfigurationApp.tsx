@@ -43,6 +43,17 @@ const FIELD_LABELS: Record<string, string> = {
     wallpaperProvider: 'Proveedor de fondo de pantalla',
     soundTheme: 'Tema de sonidos',
     playBootSound: 'Sonido de arranque',
+    patientRemindersChannel: 'Recordatorios a pacientes',
+    patientReminderTemplateId: 'Plantilla de recordatorio',
+    patientReminderSubject: 'Asunto del recordatorio',
+    smtpHost: 'Servidor SMTP',
+    smtpPort: 'Puerto SMTP',
+    smtpSecurity: 'Seguridad SMTP',
+    smtpUsername: 'Usuario SMTP',
+    smtpPassword: 'Contraseña SMTP',
+    smtpFrom: 'Remitente',
+    messageGatewayUrl: 'URL del gateway de SMS/WhatsApp',
+    messageGatewayToken: 'Token del gateway de SMS/WhatsApp',
 };
 
 /** Traducciones de ui_section del schema → nombre visible en sidebar. */
@@ -55,6 +66,7 @@ const SECTION_LABELS: Record<string, string> = {
     accounts: 'Cuentas',
     accessibility: 'Accesibilidad',
     updates: 'Actualizaciones',
+    integrations: 'Integraciones',
 };
 
 const SECTION_ICONS: Record<string, React.ElementType> = {
@@ -367,7 +379,9 @@ export function ConfigurationApp({ data }: { data?: { section?: string }; window
 
         return (
             <input
-                type={entry.definition.type === 'integer' || entry.definition.type === 'float' ? 'number' : 'text'}
+                type={entry.definition.type === 'integer' || entry.definition.type === 'float'
+                    ? 'number'
+                    : /password|token/i.test(entry.key) ? 'password' : 'text'}
                 defaultValue={String(effectiveValue ?? '')}
                 onBlur={e => {
                     const v = e.target.value;
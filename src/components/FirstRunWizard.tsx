@@ -310,13 +310,20 @@ export default function FirstRunWizard({ onFinish }: { onFinish: () => void }) {
     async function handleCreateAdmin() {
         try {
             await invoke("init_app_db");
-            const pwBuffer = new TextEncoder().encode(password || "");
-            const hashBuf = await crypto.subtle.digest("SHA-256", pwBuffer);
-            const hashHex = Array.from(new Uint8Array(hashBuf)).map(b => b.toString(16).padStart(2, "0")).join("");
-            await invoke("create_user", { username, passwordHash: hashHex, name, role: "admin" });
+            await invoke("create_user", { username, password: password || "", name, role: "admin" });
             if (isFirstUser) {
-                await invoke("set_system_password", { passwordHash: hashHex });
-                await invoke("set_config", { key: "first_run_completed", value: "true" });
+                // Configurar el sistema requiere la sesión del administrador recién creado
+                await invoke("login_user", { username, password: password || "" });
+                try {
+                    // Tras un borrado del sistema se conserva la contraseña del sistema anterior
+                    const hasSystemPassword: boolean = await invoke("has_system_password");
+                    if (!hasSystemPassword) {
+                        await invoke("set_system_password", { password: password || "", currentPassword: null });
+                    }
+                    await invoke("set_config", { key: "first_run_completed", value: "true" });
+                } finally {
+                    await invoke("logout_user");
+                }
             }
             toast.success(`Usuario ${username} creado correctamente`);
             finishAndPersist();
@@ -596,7 +603,7 @@ export default function FirstRunWizard({ onFinish }: { onFinish: () => void }) {
                                                     {/* Feature chips */}
                                                     {[
                                                         { icon: <Database style={{ width: 14, height: 14 }} />, text: "Base de datos local SQLite" },
-                                                        { icon: <ShieldCheck style={{ width: 14, height: 14 }} />, text: "Contraseña cifrada con SHA-256" },
+                                                        { icon: <ShieldCheck style={{ width: 14, height: 14 }} />, text: "Contraseña protegida con Argon2id" },
                                                         { icon: <HardDrive style={{ width: 14, height: 14 }} />, text: "Importación de respaldos .gln" },
                                                     ].map((f, i) => (
                                                         <div key={i} style={{
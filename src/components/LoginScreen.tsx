@@ -80,6 +80,7 @@ export function LoginScreen({ onLogin }: LoginScreenProps) {
     // Remote logic
     const [remoteUrl, setRemoteUrl] = useState('');
     const [remoteToken, setRemoteToken] = useState('');
+    const [remoteFingerprint, setRemoteFingerprint] = useState<string | null>(null);
     const [testingConnection, setTestingConnection] = useState(false);
 
     useEffect(() => {
@@ -314,7 +315,10 @@ export function LoginScreen({ onLogin }: LoginScreenProps) {
                                             <div className="space-y-1.5 max-h-32 overflow-y-auto custom-scrollbar">
                                                 {discoveredNodes.map((node) => (
                                                     <button key={node.service_name}
-                                                        onClick={() => setRemoteUrl(`http://${node.hostname}:${node.port}`)}
+                                                        onClick={() => {
+                                                            setRemoteUrl(`${node.tls ? 'https' : 'http'}://${node.hostname}:${node.port}`);
+                                                            setRemoteFingerprint(node.tls ? node.fingerprint ?? null : null);
+                                                        }}
                                                         className="w-full flex items-center justify-between p-2.5 rounded-xl hover:bg-white/10 transition-colors text-left group">
                                                         <div className="truncate pr-2">
                                                             <div className="text-[13px] text-white font-medium">{node.node_name}</div>
@@ -333,7 +337,7 @@ export function LoginScreen({ onLogin }: LoginScreenProps) {
                                             e.preventDefault();
                                             setTestingConnection(true);
                                             try {
-                                                const result = await invoke<any>('test_remote_connection', { remoteUrl, authToken: remoteToken });
+                                                const result = await invoke<any>('test_remote_connection', { remoteUrl, authToken: remoteToken, pinnedFingerprint: remoteFingerprint });
                                                 setTemporaryRemoteConnection(remoteUrl, remoteToken, result.service || 'Remoto');
                                                 // BUG FIX: Vamos al login genérico (sin usuario pre-seleccionado)
                                                 handleGenericLogin();
@@ -343,7 +347,7 @@ export function LoginScreen({ onLogin }: LoginScreenProps) {
                                             } finally { setTestingConnection(false); }
                                         }} className="space-y-4">
                                             <div className="space-y-3">
-                                                <Input value={remoteUrl} onChange={(e) => setRemoteUrl(e.target.value)} placeholder="http://192.168.1.X:8080"
+                                                <Input value={remoteUrl} onChange={(e) => { setRemoteUrl(e.target.value); setRemoteFingerprint(null); }} placeholder="http://192.168.1.X:8080"
                                                     className="h-11 bg-black/20 border-white/5 focus:border-blue-500/50 focus:bg-black/40 rounded-xl px-4 text-[13px] text-white placeholder:text-white/20 transition-all" />
                                                 <Input type="password" value={remoteToken} onChange={(e) => setRemoteToken(e.target.value)} placeholder="Token de seguridad"
                                                     className="h-11 bg-black/20 border-white/5 focus:border-blue-500/50 focus:bg-black/40 rounded-xl px-4 text-[13px] text-white placeholder:text-white/20 transition-all" />
@@ -237,7 +237,7 @@ interface SystemPasswordDialogProps {
     title: string;
     description: string;
     confirmLabel?: string;
-    onConfirm: (passwordHash: string) => Promise<void>;
+    onConfirm: (password: string) => Promise<void>;
     dangerous?: boolean;
     appId?: string;
     moduleName?: string;
@@ -282,10 +282,7 @@ export function SystemPasswordDialog({
         if (!password) { inputRef.current?.focus(); return; }
         setLoading(true);
         try {
-            const buf = new TextEncoder().encode(password);
-            const hashBuf = await crypto.subtle.digest("SHA-256", buf);
-            const hashHex = Array.from(new Uint8Array(hashBuf)).map(b => b.toString(16).padStart(2, "0")).join("");
-            const isValid: boolean = await invoke("verify_system_password", { passwordHash: hashHex });
+            const isValid: boolean = await invoke("verify_system_password", { password });
             if (!isValid) {
                 toast.error("La contraseña es incorrecta. Inténtelo de nuevo.");
                 setPassword("");
@@ -293,7 +290,7 @@ export function SystemPasswordDialog({
                 setLoading(false);
                 return;
             }
-            await onConfirm(hashHex);
+            await onConfirm(password);
             onOpenChange(false);
         } catch (err: any) {
             toast.error(err.toString());
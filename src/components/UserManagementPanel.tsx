@@ -60,6 +60,8 @@ const ROLES = [
     { value: 'admin', label: 'Administrador', description: 'Acceso completo al sistema' },
     { value: 'doctor', label: 'Doctor', description: 'Gestión de pacientes y tratamientos' },
     { value: 'assistant', label: 'Asistente', description: 'Consulta y soporte administrativo' },
+    { value: 'receptionist', label: 'Recepcionista', description: 'Pacientes, agenda y cobros' },
+    { value: 'finance', label: 'Finanzas', description: 'Registro y corrección de pagos' },
 ];
 
 export function UserManagementPanel() {
@@ -118,16 +120,10 @@ export function UserManagementPanel() {
         try {
             setProcessing(true);
 
-            // Hashear la contraseña con SHA-256
-            const pwBuffer = new TextEncoder().encode(formData.password);
-            const hashBuf = await crypto.subtle.digest('SHA-256', pwBuffer);
-            const hashHex = Array.from(new Uint8Array(hashBuf))
-                .map((b) => b.toString(16).padStart(2, '0'))
-                .join('');
-
+            // El backend se encarga de hashear la contraseña
             await invoke('create_user', {
                 username: formData.username,
-                passwordHash: hashHex,
+                password: formData.password,
                 name: formData.name,
                 role: formData.role,
             });
@@ -159,16 +155,10 @@ export function UserManagementPanel() {
         try {
             setProcessing(true);
 
-            // Hashear la contraseña con SHA-256
-            const pwBuffer = new TextEncoder().encode(formData.password);
-            const hashBuf = await crypto.subtle.digest('SHA-256', pwBuffer);
-            const hashHex = Array.from(new Uint8Array(hashBuf))
-                .map((b) => b.toString(16).padStart(2, '0'))
-                .join('');
-
+            // El backend se encarga de hashear la contraseña
             await invoke('update_user_password', {
                 username: selectedUser.username,
-                newPasswordHash: hashHex,
+                newPassword: formData.password,
             });
 
             toast.success('Contraseña actualizada', 'La contraseña se ha cambiado correctamente');
@@ -219,6 +209,8 @@ export function UserManagementPanel() {
             admin: 'bg-red-500/10 text-red-400 border-red-500/20',
             doctor: 'bg-blue-500/10 text-blue-400 border-blue-500/20',
             assistant: 'bg-green-500/10 text-green-400 border-green-500/20',
+            receptionist: 'bg-amber-500/10 text-amber-400 border-amber-500/20',
+            finance: 'bg-purple-500/10 text-purple-400 border-purple-500/20',
         };
 
         return (
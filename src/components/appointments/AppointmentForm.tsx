@@ -1,9 +1,10 @@
 import { useState, useEffect } from 'react';
-import { X, Calendar, Clock, User, MapPin, Bell, Tag, Save, ChevronDown } from 'lucide-react';
+import { X, Calendar, Clock, User, MapPin, Bell, Tag, Save, ChevronDown, Stethoscope, Armchair } from 'lucide-react';
 import { format, addMinutes } from 'date-fns';
 import { motion, AnimatePresence } from 'motion/react';
 import { Appointment, APPOINTMENT_TYPES, AppointmentStatus } from '../../types/appointments';
 import { usePatients, type Patient } from '../../hooks/usePatients';
+import { useAgendaResources } from '../../hooks/useAgendaResources';
 import { cn } from '@/lib/utils';
 
 interface AppointmentFormProps {
@@ -25,6 +26,7 @@ export function AppointmentForm({
     const [loading, setLoading] = useState(false);
     const [searchTerm, setSearchTerm] = useState('');
     const { getPatients } = usePatients();
+    const { resources, fetchResources } = useAgendaResources();
     const [formData, setFormData] = useState<Partial<Appointment>>({
         patient_id: appointment?.patient_id || 0,
         title: appointment?.title || '',
@@ -36,8 +38,16 @@ export function AppointmentForm({
         location: appointment?.location || 'Consultorio Principal',
         reminder_minutes: appointment?.reminder_minutes ?? 30,
         color: appointment?.color || '#60cdff',
+        practitioner_id: appointment?.practitioner_id,
+        chair_id: appointment?.chair_id,
     });
 
+    // Los recursos inactivos solo se muestran si la cita ya los tiene asignados
+    const practitioners = resources.filter(r =>
+        r.kind === 'practitioner' && (r.active || r.id === formData.practitioner_id));
+    const chairs = resources.filter(r =>
+        r.kind === 'chair' && (r.active || r.id === formData.chair_id));
+
     useEffect(() => {
         const loadPatients = async () => {
             try {
@@ -53,7 +63,8 @@ export function AppointmentForm({
             }
         };
         loadPatients();
-    }, [appointment]);
+        fetchResources(true);
+    }, [appointment, fetchResources]);
 
     const handleSubmit = async (e: React.FormEvent) => {
         e.preventDefault();
@@ -235,6 +246,48 @@ export function AppointmentForm({
                                 </div>
                             </div>
 
+                            {/* Profesional y Sillón - Grid */}
+                            <div className="grid grid-cols-2 gap-4">
+                                <div className="space-y-2">
+                                    <label className="text-xs font-semibold text-white/60 uppercase tracking-wider flex items-center gap-1.5">
+                                        <Stethoscope className="w-3.5 h-3.5" />
+                                        Profesional
+                                    </label>
+                                    <div className="relative">
+                                        <select
+                                            className="w-full h-10 px-3 bg-[#2b2b2b] border border-white/10 rounded-[4px] text-white/90 text-sm focus:outline-none focus:border-[#005FB8] focus:ring-1 focus:ring-[#005FB8] appearance-none"
+                                            value={formData.practitioner_id ?? ''}
+                                            onChange={(e) => updateField('practitioner_id', e.target.value ? Number(e.target.value) : undefined)}
+                                        >
+                                            <option value="">Sin asignar</option>
+                                            {practitioners.map(r => (
+                                                <option key={r.id} value={r.id}>{r.name}</option>
+                                            ))}
+                                        </select>
+                                        <ChevronDown className="absolute right-3 top-3 w-4 h-4 text-white/40 pointer-events-none" />
+                                    </div>
+                                </div>
+                                <div className="space-y-2">
+                                    <label className="text-xs font-semibold text-white/60 uppercase tracking-wider flex items-center gap-1.5">
+                                        <Armchair className="w-3.5 h-3.5" />
+                                        Sillón
+                                    </label>
+                                    <div className="relative">
+                                        <select
+                                            className="w-full h-10 px-3 bg-[#2b2b2b] border border-white/10 rounded-[4px] text-white/90 text-sm focus:outline-none focus:border-[#005FB8] focus:ring-1 focus:ring-[#005FB8] appearance-none"
+                                            value={formData.chair_id ?? ''}
+                                            onChange={(e) => updateField('chair_id', e.target.value ? Number(e.target.value) : undefined)}
+                                        >
+                                            <option value="">Sin asignar</option>
+                                            {chairs.map(r => (
+                                                <option key={r.id} value={r.id}>{r.name}</option>
+                                            ))}
+                                        </select>
+                                        <ChevronDown className="absolute right-3 top-3 w-4 h-4 text-white/40 pointer-events-none" />
+                                    </div>
+                                </div>
+                            </div>
+
                             {/* Ubicación */}
                             <div className="space-y-2">
                                 <label className="text-xs font-semibold text-white/60 uppercase tracking-wider flex items-center gap-1.5">
@@ -1,7 +1,7 @@
 import { useEffect, useMemo, useRef } from 'react';
 import * as Blockly from 'blockly';
 
-type EventType = 'patient:create' | 'appointment:create';
+type EventType = 'patient:create' | 'appointment:create' | 'appointment:reminder';
 
 type IntegrationCommand = {
     command: string;
@@ -75,6 +75,7 @@ function registerBlocks() {
                     options: [
                         ['patient:create', 'patient:create'],
                         ['appointment:create', 'appointment:create'],
+                        ['appointment:reminder', 'appointment:reminder'],
                     ],
                 },
             ],
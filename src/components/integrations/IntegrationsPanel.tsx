@@ -13,7 +13,7 @@ const BLOCKLY_FLUENT_PALETTE = {
     workspaceBackground: '#1b1b1b',
 };
 
-type EventType = 'patient:create' | 'appointment:create';
+type EventType = 'patient:create' | 'appointment:create' | 'appointment:reminder';
 
 type IntegrationCommand = {
     command: string;
@@ -110,6 +110,7 @@ export function IntegrationsPanel() {
         return {
             patient: flows.filter((f) => f.event_type === 'patient:create').length,
             appointment: flows.filter((f) => f.event_type === 'appointment:create').length,
+            reminder: flows.filter((f) => f.event_type === 'appointment:reminder').length,
         };
     }, [flows]);
 
@@ -273,6 +274,21 @@ export function IntegrationsPanel() {
         }
     }
 
+    async function testPatientReminder() {
+        const to = window.prompt('Email o teléfono de destino del recordatorio de prueba');
+        if (!to?.trim()) {
+            return;
+        }
+
+        try {
+            const notificationId = await invoke<string>('send_test_patient_reminder', { to });
+            toast.success(`Recordatorio de prueba enviado (${notificationId})`);
+        } catch (error) {
+            console.error(error);
+            toast.error(`No se pudo enviar el recordatorio de prueba: ${error}`);
+        }
+    }
+
     return (
         <div className="relative w-full h-full text-[#e4e4e4]">
             <div className="absolute inset-0 bg-[linear-gradient(135deg,#1a1a1a_0%,#202020_50%,#252525_100%)] pointer-events-none" />
@@ -291,6 +307,12 @@ export function IntegrationsPanel() {
                         <button className="px-3 py-1.5 text-[12px] rounded bg-[#2d2d2d] hover:bg-[#363636]" onClick={() => void testEvent('appointment:create')}>
                             Test appointment:create ({eventCounts.appointment})
                         </button>
+                        <button className="px-3 py-1.5 text-[12px] rounded bg-[#2d2d2d] hover:bg-[#363636]" onClick={() => void testEvent('appointment:reminder')}>
+                            Test appointment:reminder ({eventCounts.reminder})
+                        </button>
+                        <button className="px-3 py-1.5 text-[12px] rounded bg-[#2d2d2d] hover:bg-[#363636]" onClick={() => void testPatientReminder()}>
+                            Probar recordatorio a paciente
+                        </button>
                         {!composerOpen ? (
                             <button className="px-3 py-1.5 text-[12px] rounded bg-[#0f4f8c] hover:bg-[#0f5ea9]" onClick={startNewFlow}>
                                 + Nuevo Workflow
@@ -12,6 +12,7 @@ const TEMPLATE_TYPE_LABELS: Record<TemplateType, string> = {
     invoice: 'Factura',
     prescription: 'Receta',
     report: 'Reporte',
+    reminder: 'Recordatorio de cita',
     other: 'Otro',
 };
 
@@ -11,11 +11,15 @@ export interface HostConfig {
   api_port: number;
   api_token: string;
   enable_cors: boolean;
+  enable_tls?: boolean;
+  /** Requests per minute for the whole API; 0 disables the limit */
+  rate_limit_per_minute?: number;
 }
 
 export interface ClientConfig {
   remote_url: string;
   auth_token: string;
+  pinned_fingerprint?: string | null;
 }
 
 export interface NodeConfig {
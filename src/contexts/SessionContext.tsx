@@ -106,15 +106,9 @@ export function SessionProvider({ children }: { children: React.ReactNode }) {
                     pin: password,
                 });
             } else {
-                const pwBuffer = new TextEncoder().encode(password);
-                const hashBuf = await crypto.subtle.digest('SHA-256', pwBuffer);
-                const hashHex = Array.from(new Uint8Array(hashBuf))
-                    .map((b) => b.toString(16).padStart(2, '0'))
-                    .join('');
-
                 await invoke<User>('unlock_with_password', {
                     username: currentUser.username,
-                    passwordHash: hashHex,
+                    password,
                 });
             }
         }
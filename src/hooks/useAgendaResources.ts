@@ -0,0 +1,103 @@
+import { useState, useCallback } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { AgendaResource, FreeSlot, FreeSlotQuery } from '../types/appointments';
+
+export function useAgendaResources() {
+    const [resources, setResources] = useState<AgendaResource[]>([]);
+    const [loading, setLoading] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    const fetchResources = useCallback(async (includeInactive = false) => {
+        setLoading(true);
+        setError(null);
+        try {
+            const result = await invoke<AgendaResource[]>('list_agenda_resources', { includeInactive });
+            setResources(result);
+        } catch (err) {
+            const message = err instanceof Error ? err.message : String(err);
+            setError(message);
+            console.error('Error fetching agenda resources:', err);
+        } finally {
+            setLoading(false);
+        }
+    }, []);
+
+    const createResource = useCallback(async (resource: AgendaResource) => {
+        setLoading(true);
+        setError(null);
+        try {
+            const id = await invoke<number>('create_agenda_resource', { resource });
+            await fetchResources();
+            return id;
+        } catch (err) {
+            const message = err instanceof Error ? err.message : String(err);
+            setError(message);
+            console.error('Error creating agenda resource:', err);
+            throw err;
+        } finally {
+            setLoading(false);
+        }
+    }, [fetchResources]);
+
+    const updateResource = useCallback(async (id: number, resource: AgendaResource) => {
+        setLoading(true);
+        setError(null);
+        try {
+            await invoke('update_agenda_resource', { resource: { ...resource, id } });
+            await fetchResources();
+        } catch (err) {
+            const message = err instanceof Error ? err.message : String(err);
+            setError(message);
+            console.error('Error updating agenda resource:', err);
+            throw err;
+        } finally {
+            setLoading(false);
+        }
+    }, [fetchResources]);
+
+    const deleteResource = useCallback(async (id: number) => {
+        setLoading(true);
+        setError(null);
+        try {
+            await invoke('delete_agenda_resource', { id });
+            await fetchResources();
+        } catch (err) {
+            const message = err instanceof Error ? err.message : String(err);
+            setError(message);
+            console.error('Error deleting agenda resource:', err);
+            throw err;
+        } finally {
+            setLoading(false);
+        }
+    }, [fetchResources]);
+
+    const getResource = useCallback(async (id: number) => {
+        try {
+            return await invoke<AgendaResource | null>('get_agenda_resource', { id });
+        } catch (err) {
+            console.error('Error getting agenda resource:', err);
+            throw err;
+        }
+    }, []);
+
+    const findFreeSlots = useCallback(async (query: FreeSlotQuery) => {
+        try {
+            return await invoke<FreeSlot[]>('find_free_slots', { query });
+        } catch (err) {
+            console.error('Error finding free slots:', err);
+            throw err;
+        }
+    }, []);
+
+    return {
+        resources,
+        loading,
+        error,
+        fetchResources,
+        createResource,
+        updateResource,
+        deleteResource,
+        getResource,
+        findFreeSlots,
+    };
+}
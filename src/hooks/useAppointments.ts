@@ -5,6 +5,7 @@ import {
     AppointmentWithPatient,
     AppointmentFilter,
     AppointmentReminder,
+    RecurrenceScope,
 } from '../types/appointments';
 
 export function useAppointments() {
@@ -29,11 +30,11 @@ export function useAppointments() {
         }
     }, []);
 
-    const createAppointment = useCallback(async (appointment: Appointment) => {
+    const createAppointment = useCallback(async (appointment: Appointment, allowOverbooking = false) => {
         setLoading(true);
         setError(null);
         try {
-            const id = await invoke<number>('create_appointment', { appointment });
+            const id = await invoke<number>('create_appointment', { appointment, allowOverbooking });
             await fetchAppointments();
             return id;
         } catch (err) {
@@ -46,11 +47,15 @@ export function useAppointments() {
         }
     }, [fetchAppointments]);
 
-    const updateAppointment = useCallback(async (id: number, appointment: Appointment) => {
+    const updateAppointment = useCallback(async (
+        id: number,
+        appointment: Appointment,
+        allowOverbooking = false
+    ) => {
         setLoading(true);
         setError(null);
         try {
-            await invoke('update_appointment', { appointment: { ...appointment, id } });
+            await invoke('update_appointment', { appointment: { ...appointment, id }, allowOverbooking });
             await fetchAppointments();
         } catch (err) {
             const message = err instanceof Error ? err.message : String(err);
@@ -78,6 +83,60 @@ export function useAppointments() {
         }
     }, [fetchAppointments]);
 
+    const updateOccurrence = useCallback(async (
+        seriesId: number,
+        originalStartTime: string,
+        appointment: Appointment,
+        scope: RecurrenceScope,
+        allowOverbooking = false
+    ) => {
+        setLoading(true);
+        setError(null);
+        try {
+            const id = await invoke<number>('update_appointment_occurrence', {
+                seriesId,
+                originalStartTime,
+                appointment,
+                scope,
+                allowOverbooking,
+            });
+            await fetchAppointments();
+            return id;
+        } catch (err) {
+            const message = err instanceof Error ? err.message : String(err);
+            setError(message);
+            console.error('Error updating appointment occurrence:', err);
+            throw err;
+        } finally {
+            setLoading(false);
+        }
+    }, [fetchAppointments]);
+
+    const cancelOccurrence = useCallback(async (
+        seriesId: number,
+        originalStartTime: string,
+        scope: RecurrenceScope
+    ) => {
+        setLoading(true);
+        setError(null);
+        try {
+            const id = await invoke<number>('cancel_appointment_occurrence', {
+                seriesId,
+                originalStartTime,
+                scope,
+            });
+            await fetchAppointments();
+            return id;
+        } catch (err) {
+            const message = err instanceof Error ? err.message : String(err);
+            setError(message);
+            console.error('Error cancelling appointment occurrence:', err);
+            throw err;
+        } finally {
+            setLoading(false);
+        }
+    }, [fetchAppointments]);
+
     const getAppointment = useCallback(async (id: number) => {
         setLoading(true);
         setError(null);
@@ -130,6 +189,8 @@ export function useAppointments() {
         createAppointment,
         updateAppointment,
         deleteAppointment,
+        updateOccurrence,
+        cancelOccurrence,
         getAppointment,
         getPendingReminders,
         markReminderSent,
@@ -0,0 +1,35 @@
+// Data Change Hook
+// Subscribes to entity changes made on this node or, in client mode, on the host
+
+import { listen } from '@tauri-apps/api/event';
+import { useEffect, useRef } from 'react';
+
+export interface DataChangeEvent {
+    type: string; // "<entity>.<action>", e.g. "appointment.created"
+    id: number;
+    timestamp: string; // ISO date string
+}
+
+/**
+ * Call `onChange` whenever an entity of one of the given kinds changes
+ * (e.g. `['appointment', 'patient']`)
+ */
+export function useDataChanges(entities: string[], onChange: (event: DataChangeEvent) => void) {
+    const onChangeRef = useRef(onChange);
+    onChangeRef.current = onChange;
+    const entitiesKey = entities.join(',');
+
+    useEffect(() => {
+        const watched = entitiesKey.split(',');
+        const unlisten = listen<DataChangeEvent>('data:changed', event => {
+            const entity = event.payload?.type?.split('.')[0];
+            if (entity && watched.includes(entity)) {
+                onChangeRef.current(event.payload);
+            }
+        });
+
+        return () => {
+            unlisten.then(fn => fn());
+        };
+    }, [entitiesKey]);
+}
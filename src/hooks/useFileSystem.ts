@@ -34,13 +34,149 @@ export interface FilePermissions {
     canDelete: boolean;
 }
 
+export interface UploadSession {
+    upload_id: string;
+    chunk_size: number;
+}
+
+export interface TrashEntry {
+    id: number;
+    original_virtual_path: string;
+    original_parent_path: string;
+    name: string;
+    entry_type: 'file' | 'folder';
+    size: number;
+    owner_username: string;
+    deleted_at: string;
+    deleted_by: string;
+}
+
+export interface PatientFileLink {
+    id: number;
+    virtual_path: string;
+    patient_id: number;
+    linked_at: string;
+    linked_by: string;
+    notes: string | null;
+}
+
+export interface PatientFile {
+    entry: FileEntry;
+    link: PatientFileLink;
+}
+
+export interface AuditLogEntry {
+    id: number;
+    timestamp: string;
+    username: string;
+    operation: string;
+    path: string;
+    target_path: string | null;
+    success: boolean;
+    error_message: string | null;
+    metadata: string | null;
+}
+
+export interface AuditLogFilter {
+    username?: string;
+    path_prefix?: string;
+    operation?: string;
+    /** RFC 3339 timestamp or YYYY-MM-DD */
+    from?: string;
+    /** RFC 3339 timestamp or YYYY-MM-DD (the whole day is included) */
+    to?: string;
+    limit?: number;
+    offset?: number;
+}
+
+export interface AccessGrant {
+    can_read: boolean;
+    can_write: boolean;
+    can_delete: boolean;
+}
+
 export interface StorageQuota {
-    totalBytes: number;
-    usedBytes: number;
-    availableBytes: number;
-    percentageUsed: number;
+    username: string;
+    /** null when the user has no limit */
+    total_bytes: number | null;
+    used_bytes: number;
+    available_bytes: number | null;
+    percentage_used: number | null;
+    is_custom: boolean;
+    last_calculated: string;
+}
+
+export type EncryptionKeySource = 'password' | 'key_file';
+
+export interface EncryptionStatus {
+    /** New writes are encrypted (fsEncryptionEnabled) */
+    enabled: boolean;
+    configured: boolean;
+    /** false until an admin enters the system password after startup */
+    unlocked: boolean;
+    key_source: EncryptionKeySource | null;
+    key_file_path: string | null;
+    active_key_id: number | null;
+    key_count: number;
+}
+
+export interface SearchQuery {
+    /** Words to find in names and in the content of PDF, TXT and DOCX files */
+    text?: string;
+    root_path?: string;
+    /** Exact MIME type or a prefix such as "image/*" */
+    mime_type?: string;
+    owner?: string;
+    entry_type?: 'file' | 'folder';
+    /** YYYY-MM-DD or RFC 3339 */
+    modified_from?: string;
+    modified_to?: string;
+    patient_id?: number;
+    limit?: number;
+    offset?: number;
 }
 
+export interface SearchResult {
+    entry: FileEntry;
+    /** Content excerpt with the matched words in [brackets] */
+    snippet: string | null;
+}
+
+export interface FileLock {
+    file_path: string;
+    locked_by: string;
+    locked_at: string;
+    expires_at: string;
+}
+
+export interface FileVersion {
+    id: number;
+    virtual_path: string;
+    version: number;
+    size: number;
+    mime_type: string | null;
+    /** When this content was written */
+    modified_at: string;
+    /** When this content was overwritten, and by whom */
+    replaced_at: string;
+    replaced_by: string;
+}
+
+export interface DicomTags {
+    patient_name: string | null;
+    patient_id: string | null;
+    /** YYYY-MM-DD */
+    study_date: string | null;
+    /** e.g. IO (intraoral), PX (panoramic) */
+    modality: string | null;
+    study_description: string | null;
+    rows: number | null;
+    columns: number | null;
+}
+
+/** Bytes requested per fs_download_chunk call */
+const DOWNLOAD_CHUNK_SIZE = 1024 * 1024;
+
 export const useFileSystem = () => {
     const listDirectory = async (path: string): Promise<DirectoryListing> => {
         return await invoke('fs_list_directory', { virtualPath: path });
@@ -67,47 +203,214 @@ export const useFileSystem = () => {
         file: File,
         onProgress?: (progress: number) => void
     ): Promise<FileEntry> => {
-        const data = await file.arrayBuffer();
-        return await invoke('fs_upload_file', {
+        const session: UploadSession = await invoke('fs_begin_upload', {
             virtualDir: dir,
-            fileData: Array.from(new Uint8Array(data)),
-            filename: file.name
+            filename: file.name,
+            totalSize: file.size
         });
+
+        return await sendChunks(session, file, onProgress);
+    };
+
+    /** Replaces the content of an existing file; the previous content is kept as a version */
+    const overwriteFile = async (
+        path: string,
+        file: File,
+        onProgress?: (progress: number) => void
+    ): Promise<FileEntry> => {
+        const session: UploadSession = await invoke('fs_begin_overwrite', {
+            virtualPath: path,
+            totalSize: file.size
+        });
+
+        return await sendChunks(session, file, onProgress);
+    };
+
+    const sendChunks = async (
+        session: UploadSession,
+        file: File,
+        onProgress?: (progress: number) => void
+    ): Promise<FileEntry> => {
+        // Chunks are sent as raw binary bodies so large scans never go through JSON
+        try {
+            for (let offset = 0; offset < file.size; offset += session.chunk_size) {
+                const chunk = await file.slice(offset, offset + session.chunk_size).arrayBuffer();
+                await invoke('fs_upload_chunk', new Uint8Array(chunk), {
+                    headers: {
+                        'x-upload-id': session.upload_id,
+                        'x-upload-offset': String(offset)
+                    }
+                });
+                onProgress?.(Math.min(1, (offset + chunk.byteLength) / file.size));
+            }
+
+            return await invoke('fs_finish_upload', { uploadId: session.upload_id });
+        } catch (error) {
+            await invoke('fs_cancel_upload', { uploadId: session.upload_id }).catch(() => undefined);
+            throw error;
+        }
     };
 
     const downloadFile = async (path: string): Promise<Blob> => {
-        const data: number[] = await invoke('fs_download_file', { virtualPath: path });
-        return new Blob([new Uint8Array(data)]);
+        const metadata = await getMetadata(path);
+        const parts: ArrayBuffer[] = [];
+
+        for (let offset = 0; offset < metadata.size; offset += DOWNLOAD_CHUNK_SIZE) {
+            const chunk: ArrayBuffer = await invoke('fs_download_chunk', {
+                virtualPath: path,
+                offset,
+                length: DOWNLOAD_CHUNK_SIZE
+            });
+            if (chunk.byteLength === 0) break;
+            parts.push(chunk);
+        }
+
+        return new Blob(parts);
+    };
+
+    const listVersions = async (path: string): Promise<FileVersion[]> => {
+        return await invoke('fs_list_versions', { virtualPath: path });
+    };
+
+    const downloadVersion = async (version: FileVersion): Promise<Blob> => {
+        const parts: ArrayBuffer[] = [];
+
+        for (let offset = 0; offset < version.size; offset += DOWNLOAD_CHUNK_SIZE) {
+            const chunk: ArrayBuffer = await invoke('fs_download_version', {
+                virtualPath: version.virtual_path,
+                version: version.version,
+                offset,
+                length: DOWNLOAD_CHUNK_SIZE
+            });
+            if (chunk.byteLength === 0) break;
+            parts.push(chunk);
+        }
+
+        return new Blob(parts);
+    };
+
+    const restoreVersion = async (path: string, version: number): Promise<FileEntry> => {
+        return await invoke('fs_restore_version', { virtualPath: path, version });
+    };
+
+    /** PNG thumbnail of an image or DICOM file */
+    const getThumbnail = async (path: string): Promise<Blob> => {
+        const data: ArrayBuffer = await invoke('fs_get_thumbnail', { virtualPath: path });
+        return new Blob([data], { type: 'image/png' });
+    };
+
+    const getDicomTags = async (path: string): Promise<DicomTags> => {
+        return await invoke('fs_get_dicom_tags', { virtualPath: path });
+    };
+
+    const search = async (query: SearchQuery): Promise<SearchResult[]> => {
+        return await invoke('fs_search', { query });
+    };
+
+    const getStorageQuota = async (username?: string): Promise<StorageQuota> => {
+        return await invoke('fs_get_storage_quota', { username });
+    };
+
+    const setStorageQuota = async (username: string, quotaBytes: number | null): Promise<StorageQuota> => {
+        return await invoke('fs_set_storage_quota', { username, quotaBytes });
+    };
+
+    const getEncryptionStatus = async (): Promise<EncryptionStatus> => {
+        return await invoke('fs_get_encryption_status');
+    };
+
+    const setupEncryption = async (
+        keySource: EncryptionKeySource,
+        systemPassword?: string,
+        keyFilePath?: string
+    ): Promise<EncryptionStatus> => {
+        return await invoke('fs_setup_encryption', { keySource, systemPassword, keyFilePath });
+    };
+
+    const unlockEncryption = async (systemPassword: string): Promise<EncryptionStatus> => {
+        return await invoke('fs_unlock_encryption', { systemPassword });
+    };
+
+    const rotateEncryptionKey = async (): Promise<EncryptionStatus> => {
+        return await invoke('fs_rotate_encryption_key');
+    };
+
+    const changeEncryptionKeySource = async (
+        keySource: EncryptionKeySource,
+        systemPassword?: string,
+        keyFilePath?: string
+    ): Promise<EncryptionStatus> => {
+        return await invoke('fs_change_encryption_key_source', { keySource, systemPassword, keyFilePath });
+    };
+
+    const linkToPatient = async (filePath: string, patientId: number, notes?: string): Promise<PatientFileLink> => {
+        return await invoke('fs_link_to_patient', { filePath, patientId, notes });
+    };
+
+    const unlinkFromPatient = async (filePath: string, patientId: number): Promise<void> => {
+        return await invoke('fs_unlink_from_patient', { filePath, patientId });
+    };
+
+    const getPatientFiles = async (patientId: number): Promise<PatientFile[]> => {
+        return await invoke('fs_get_patient_files', { patientId });
+    };
+
+    const getAuditLogs = async (filter?: AuditLogFilter): Promise<AuditLogEntry[]> => {
+        return await invoke('fs_get_audit_logs', { filter });
     };
 
-    const search = async (query: string, rootPath: string): Promise<FileEntry[]> => {
-        return await invoke('fs_search', { query, rootPath });
+    const exportAuditLogs = async (filter?: AuditLogFilter): Promise<string> => {
+        return await invoke('fs_export_audit_logs', { filter });
     };
 
-    const getStorageQuota = async (): Promise<StorageQuota> => {
-        return await invoke('fs_get_storage_quota');
+    const grantAccess = async (path: string, grantee: string, permissions: AccessGrant): Promise<void> => {
+        return await invoke('fs_grant_access', { virtualPath: path, grantee, permissions });
     };
 
-    const linkToPatient = async (filePath: string, patientId: number): Promise<void> => {
-        return await invoke('fs_link_to_patient', { filePath, patientId });
+    const revokeAccess = async (path: string, grantee: string): Promise<void> => {
+        return await invoke('fs_revoke_access', { virtualPath: path, grantee });
     };
 
     const getMetadata = async (path: string): Promise<FileMetadata> => {
         return await invoke('fs_get_metadata', { virtualPath: path });
     };
 
-    const moveToTrash = async (path: string): Promise<void> => {
+    const moveToTrash = async (path: string): Promise<TrashEntry> => {
         return await invoke('fs_move_to_trash', { virtualPath: path });
     };
 
+    const listTrash = async (): Promise<TrashEntry[]> => {
+        return await invoke('fs_list_trash');
+    };
+
     const restoreFromTrash = async (trashId: number): Promise<FileEntry> => {
         return await invoke('fs_restore_from_trash', { trashId });
     };
 
-    const emptyTrash = async (): Promise<void> => {
+    const emptyTrash = async (): Promise<number> => {
         return await invoke('fs_empty_trash');
     };
 
+    const acquireLock = async (path: string, durationMinutes?: number): Promise<FileLock> => {
+        return await invoke('fs_acquire_lock', { virtualPath: path, durationMinutes });
+    };
+
+    const renewLock = async (path: string, durationMinutes?: number): Promise<FileLock> => {
+        return await invoke('fs_renew_lock', { virtualPath: path, durationMinutes });
+    };
+
+    const releaseLock = async (path: string): Promise<void> => {
+        return await invoke('fs_release_lock', { virtualPath: path });
+    };
+
+    const forceUnlock = async (path: string): Promise<FileLock | null> => {
+        return await invoke('fs_force_unlock', { virtualPath: path });
+    };
+
+    const getLockStatus = async (path: string): Promise<FileLock | null> => {
+        return await invoke('fs_get_file_lock_status', { virtualPath: path });
+    };
+
     return {
         listDirectory,
         createFolder,
@@ -115,13 +418,37 @@ export const useFileSystem = () => {
         renameEntry,
         moveEntry,
         uploadFile,
+        overwriteFile,
         downloadFile,
+        listVersions,
+        downloadVersion,
+        restoreVersion,
+        getThumbnail,
+        getDicomTags,
         search,
         getStorageQuota,
+        setStorageQuota,
+        getEncryptionStatus,
+        setupEncryption,
+        unlockEncryption,
+        rotateEncryptionKey,
+        changeEncryptionKeySource,
         linkToPatient,
+        unlinkFromPatient,
+        getPatientFiles,
+        getAuditLogs,
+        exportAuditLogs,
+        grantAccess,
+        revokeAccess,
         getMetadata,
         moveToTrash,
+        listTrash,
         restoreFromTrash,
         emptyTrash,
+        acquireLock,
+        renewLock,
+        releaseLock,
+        forceUnlock,
+        getLockStatus,
     };
 };
@@ -10,6 +10,8 @@ export interface DiscoveredNode {
     hostname: string;
     port: number;
     version: string;
+    tls: boolean;
+    fingerprint?: string | null; // SHA-256 of the host certificate (HTTPS only)
     last_seen: string; // ISO date string
 }
 
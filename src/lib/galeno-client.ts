@@ -52,6 +52,7 @@ export interface UpdatePatientInput {
 
 export interface LoginResponse {
   token?: string;  // JWT token (only for remote)
+  refresh_token?: string;  // JWT refresh token (only for remote)
   user: {
     id: number;
     username: string;
@@ -97,15 +98,10 @@ export interface GalenoClient {
 export class LocalGalenoClient implements GalenoClient {
   // Authentication
   async login(username: string, password: string): Promise<LoginResponse> {
-    const pwBuffer = new TextEncoder().encode(password);
-    const hashBuf = await crypto.subtle.digest('SHA-256', pwBuffer);
-    const hashHex = Array.from(new Uint8Array(hashBuf))
-      .map((b) => b.toString(16).padStart(2, '0'))
-      .join('');
-
+    // The password is hashed and verified by the backend
     const user = await invoke<any>('login_user', {
       username,
-      passwordHash: hashHex,
+      password,
     });
 
     return { user };
@@ -164,6 +160,7 @@ export class RemoteGalenoClient implements GalenoClient {
   private baseUrl: string;
   private authToken: string;
   private jwtToken?: string; // JWT token after login
+  private refreshToken?: string; // Used to renew the JWT token when it expires
 
   constructor(baseUrl: string, authToken: string) {
     this.baseUrl = baseUrl.endsWith('/') ? baseUrl.slice(0, -1) : baseUrl;
@@ -173,7 +170,8 @@ export class RemoteGalenoClient implements GalenoClient {
   private async request<T>(
     method: string,
     path: string,
-    body?: any
+    body?: any,
+    retryOnExpired = true
   ): Promise<T> {
     const url = `${this.baseUrl}/api${path}`;
     // Use JWT token if available, otherwise use static API token
@@ -189,6 +187,11 @@ export class RemoteGalenoClient implements GalenoClient {
       body: body ? JSON.stringify(body) : undefined,
     });
 
+    // Expired or revoked JWT: try once with a refreshed token
+    if (response.status === 401 && retryOnExpired && this.refreshToken && await this.refresh()) {
+      return this.request<T>(method, path, body, false);
+    }
+
     if (!response.ok) {
       const errorData = await response.json().catch(() => ({}));
       throw new Error(
@@ -257,11 +260,31 @@ export class RemoteGalenoClient implements GalenoClient {
     // Store JWT token for future requests
     if (response.token) {
       this.jwtToken = response.token;
+      this.refreshToken = response.refresh_token;
     }
 
     return response;
   }
 
+  private async refresh(): Promise<boolean> {
+    const response = await fetch(`${this.baseUrl}/api/auth/refresh`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ refresh_token: this.refreshToken }),
+    });
+
+    if (!response.ok) {
+      this.jwtToken = undefined;
+      this.refreshToken = undefined;
+      return false;
+    }
+
+    const data: LoginResponse = await response.json();
+    this.jwtToken = data.token;
+    this.refreshToken = data.refresh_token;
+    return true;
+  }
+
   async verifySession(): Promise<boolean> {
     if (!this.jwtToken) {
       return false;
@@ -276,8 +299,14 @@ export class RemoteGalenoClient implements GalenoClient {
   }
 
   async logout(): Promise<void> {
-    // Clear JWT token
+    // Revoke the tokens on the host, then clear them locally
+    if (this.jwtToken) {
+      await this.request<void>('POST', '/auth/logout', {
+        refresh_token: this.refreshToken,
+      }, false).catch(() => undefined);
+    }
     this.jwtToken = undefined;
+    this.refreshToken = undefined;
   }
 }
 
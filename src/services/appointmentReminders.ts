@@ -1,92 +1,56 @@
 import { invoke } from '@tauri-apps/api/core';
-import { AppointmentReminder, AppointmentWithPatient } from '../types/appointments';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import { AppointmentWithPatient, ReminderNotification } from '../types/appointments';
+import { DataChangeEvent } from '../hooks/useDataChanges';
 import { format } from 'date-fns';
 import { es } from 'date-fns/locale';
 
-export interface ReminderNotification {
-    reminder: AppointmentReminder;
-    appointment: AppointmentWithPatient;
-}
+export type { ReminderNotification };
 
+/**
+ * Los recordatorios los envía el backend (el host en modo cliente) cuando vencen y
+ * los anuncia como el cambio `appointment.reminder`; acá solo se escuchan.
+ */
 class AppointmentReminderService {
-    private intervalId: number | null = null;
-    private checkIntervalMs = 60000; // Revisar cada minuto
+    private unlisten: Promise<UnlistenFn> | null = null;
     private onReminderCallback: ((notification: ReminderNotification) => void) | null = null;
 
     start(onReminder: (notification: ReminderNotification) => void) {
-        if (this.intervalId) {
+        if (this.unlisten) {
             console.warn('Reminder service already running');
             return;
         }
 
         this.onReminderCallback = onReminder;
-        this.checkReminders(); // Check immediately
-        this.intervalId = setInterval(() => this.checkReminders(), this.checkIntervalMs);
+        this.unlisten = listen<DataChangeEvent>('data:changed', event => {
+            if (event.payload?.type === 'appointment.reminder') {
+                void this.showReminder(event.payload.id);
+            }
+        });
         console.log('Appointment reminder service started');
     }
 
     stop() {
-        if (this.intervalId) {
-            clearInterval(this.intervalId);
-            this.intervalId = null;
+        if (this.unlisten) {
+            this.unlisten.then(fn => fn());
+            this.unlisten = null;
             this.onReminderCallback = null;
             console.log('Appointment reminder service stopped');
         }
     }
 
-    private async checkReminders() {
-        try {
-            const reminders = await invoke<AppointmentReminder[]>('get_pending_reminders');
-
-            if (reminders.length === 0) {
-                return;
-            }
-
-            console.log(`Found ${reminders.length} pending reminders`);
-
-            for (const reminder of reminders) {
-                try {
-                    // Obtener datos completos con paciente
-                    const appointments = await invoke<AppointmentWithPatient[]>('list_appointments', {
-                        filter: {},
-                    });
-
-                    const fullAppointment = appointments.find(a => a.id === reminder.appointment_id);
-
-                    if (fullAppointment && this.onReminderCallback) {
-                        this.onReminderCallback({
-                            reminder,
-                            appointment: fullAppointment,
-                        });
-                    }
-                } catch (error) {
-                    console.error('Error processing reminder:', error);
-                }
-            }
-        } catch (error) {
-            console.error('Error checking reminders:', error);
-        }
-    }
-
-    async markReminderSent(reminderId: number, notificationId: string) {
+    private async showReminder(reminderId: number) {
         try {
-            await invoke('mark_reminder_sent', {
+            // Obtener datos completos con paciente
+            const notification = await invoke<ReminderNotification | null>('get_reminder_notification', {
                 reminderId,
-                notificationId,
             });
-        } catch (error) {
-            console.error('Error marking reminder as sent:', error);
-        }
-    }
 
-    setCheckInterval(intervalMs: number) {
-        this.checkIntervalMs = intervalMs;
-        if (this.intervalId) {
-            // Restart with new interval
-            this.stop();
-            if (this.onReminderCallback) {
-                this.start(this.onReminderCallback);
+            if (notification && this.onReminderCallback) {
+                this.onReminderCallback(notification);
             }
+        } catch (error) {
+            console.error('Error processing reminder:', error);
         }
     }
 }
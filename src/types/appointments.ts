@@ -13,13 +13,56 @@ export interface Appointment {
     created_by?: number;
     created_at?: string;
     updated_at?: string;
+    /** RRULE-style rule of a series, e.g. "FREQ=WEEKLY;COUNT=10" */
+    recurrence_rule?: string;
+    /** Series of an occurrence or exception */
+    series_id?: number;
+    /** Start of the occurrence according to the series rule; identifies it with series_id */
+    original_start_time?: string;
+    /** Agenda resource of kind 'practitioner' seeing the patient */
+    practitioner_id?: number;
+    /** Agenda resource of kind 'chair' the appointment takes place in */
+    chair_id?: number;
 }
 
+/** Which occurrences of a recurring appointment a change applies to */
+export type RecurrenceScope = 'occurrence' | 'following' | 'series';
+
 export interface AppointmentWithPatient extends Appointment {
     patient_name: string;
     patient_phone?: string;
 }
 
+/** Why an existing appointment clashes with the one being saved */
+export type ConflictReason = 'chair' | 'practitioner';
+
+/** An existing appointment that overlaps the one being saved */
+export interface AppointmentConflict {
+    /** Start of the new appointment (or occurrence) that clashes */
+    start_time: string;
+    reasons: ConflictReason[];
+    appointment: AppointmentWithPatient;
+}
+
+/** Details of a rejected double booking; save again with allowOverbooking to book anyway */
+export interface AppointmentConflictError {
+    message: string;
+    conflicts: AppointmentConflict[];
+}
+
+const CONFLICT_PREFIX = 'Conflict: ';
+
+/** Reads the conflicts out of an error of create/update, or null for other errors */
+export function parseConflictError(error: unknown): AppointmentConflictError | null {
+    const text = error instanceof Error ? error.message : String(error);
+    if (!text.startsWith(CONFLICT_PREFIX)) return null;
+    try {
+        return JSON.parse(text.slice(CONFLICT_PREFIX.length)) as AppointmentConflictError;
+    } catch {
+        return null;
+    }
+}
+
 export type AppointmentStatus =
     | 'scheduled'
     | 'confirmed'
@@ -40,8 +83,81 @@ export interface AppointmentReminder {
     scheduled_time: string;
     sent: boolean;
     sent_at?: string;
+    /** `email:`, `sms:` or `whatsapp:` plus the patient message ID, or `desktop:` when only staff were notified */
     notification_id?: string;
     created_at?: string;
+    /** Start of the appointment or occurrence the reminder announces */
+    occurrence_start_time?: string;
+    /** Failed sends so far */
+    attempts?: number;
+    next_attempt_at?: string;
+    /** Error of the last failed send, or why the reminder was discarded */
+    last_error?: string;
+}
+
+/** A reminder with the appointment (or occurrence) it announces */
+export interface ReminderNotification {
+    reminder: AppointmentReminder;
+    appointment: AppointmentWithPatient;
+}
+
+/** Kind of agenda resource */
+export type ResourceKind = 'practitioner' | 'chair';
+
+/** Weekly time range in the clinic's local time */
+export interface WeeklyHours {
+    /** 0 = Monday ... 6 = Sunday */
+    weekday: number;
+    /** "HH:MM" */
+    start_time: string;
+    /** "HH:MM" */
+    end_time: string;
+}
+
+/** Days off (vacations, leave), both dates included */
+export interface DayOff {
+    /** "YYYY-MM-DD" */
+    start_date: string;
+    /** "YYYY-MM-DD" */
+    end_date: string;
+    reason?: string;
+}
+
+/**
+ * Practitioner or chair with its weekly schedule. Its appointments are those with
+ * its ID in practitioner_id or chair_id.
+ */
+export interface AgendaResource {
+    id?: number;
+    kind: ResourceKind;
+    name: string;
+    user_id?: number;
+    color?: string;
+    active: boolean;
+    working_hours: WeeklyHours[];
+    breaks: WeeklyHours[];
+    days_off: DayOff[];
+    created_at?: string;
+    updated_at?: string;
+}
+
+/** Search for the next openings where every resource is working and free */
+export interface FreeSlotQuery {
+    resource_ids: number[];
+    duration_minutes: number;
+    /** RFC 3339 or YYYY-MM-DD; defaults to now */
+    from?: string;
+    /** Defaults to 30 days after `from` */
+    to?: string;
+    /** Minutes between slot starts; defaults to 15 */
+    step_minutes?: number;
+    /** Defaults to 10, at most 200 */
+    limit?: number;
+}
+
+export interface FreeSlot {
+    start_time: string;
+    end_time: string;
 }
 
 // Tipo para react-big-calendar
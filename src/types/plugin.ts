@@ -140,6 +140,7 @@ export type PluginEvent =
     | 'treatment:update'
     | 'appointment:create'
     | 'appointment:update'
+    | 'appointment:reminder'
     | 'payment:create'
     | 'app:ready'
     | 'app:shutdown';
@@ -1,4 +1,4 @@
-export type TemplateType = 'receipt' | 'invoice' | 'prescription' | 'report' | 'other';
+export type TemplateType = 'receipt' | 'invoice' | 'prescription' | 'report' | 'reminder' | 'other';
 
 export interface TemplateVariable {
     key: string;
@@ -63,6 +63,15 @@ export const TEMPLATE_VARIABLES: Record<string, TemplateVariable[]> = {
         { key: 'clinic_name', label: 'Nombre de la Clínica', example: 'Clínica Dental' },
         { key: 'clinic_tax_id', label: 'RFC/NIT de la Clínica', example: 'ABC123456XYZ' },
     ],
+    // Recordatorios de citas enviados al paciente por email, SMS o WhatsApp
+    reminder: [
+        { key: 'patient_name', label: 'Nombre del Paciente', example: 'Juan Pérez' },
+        { key: 'appointment_title', label: 'Motivo de la Cita', example: 'Control de ortodoncia' },
+        { key: 'appointment_date', label: 'Fecha de la Cita', example: '01/02/2026' },
+        { key: 'appointment_time', label: 'Hora de la Cita', example: '10:30' },
+        { key: 'appointment_location', label: 'Ubicación', example: 'Sillón 1' },
+        { key: 'appointment_type', label: 'Tipo de Cita', example: 'Control' },
+    ],
 };
 
 // Template por defecto para recibos